@@ -0,0 +1,6 @@
+//! Shared library of the C API declared in `include/vrt.h`.
+//!
+//! The functions are those of the `vrt::ffi` module, enabled by the `ffi` feature, which
+//! this crate links into a `cdylib`.
+
+pub use vrt_core::ffi::*;
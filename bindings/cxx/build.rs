@@ -0,0 +1,6 @@
+fn main() {
+    cxx_build::bridge("src/lib.rs")
+        .std("c++14")
+        .compile("vrt-cxx");
+    println!("cargo:rerun-if-changed=src/lib.rs");
+}
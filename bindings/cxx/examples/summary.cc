@@ -0,0 +1,48 @@
+// Prints a summary of each packet of a file of back-to-back VRT packets, and the events of a
+// session pairing its data and context streams.
+//
+// Build with, from the workspace root:
+//   cargo build -p vrt-cxx
+//   c++ -std=c++14 -I target/cxxbridge -o summary bindings/cxx/examples/summary.cc
+//       target/debug/libvrt_cxx.a -lpthread -ldl
+
+#include "rust/cxx.h"
+#include "vrt-cxx/src/lib.rs.h"
+
+#include <cstdint>
+#include <fstream>
+#include <iostream>
+#include <iterator>
+#include <vector>
+
+int main(int argc, char **argv) {
+  if (argc != 2) {
+    std::cerr << "usage: " << argv[0] << " FILE" << std::endl;
+    return 2;
+  }
+  std::ifstream file(argv[1], std::ios::binary);
+  std::vector<std::uint8_t> bytes((std::istreambuf_iterator<char>(file)),
+                                  std::istreambuf_iterator<char>());
+
+  auto session = vrt::new_session(0);
+  std::size_t offset = 0;
+  while (offset < bytes.size()) {
+    rust::Slice<const std::uint8_t> rest(bytes.data() + offset, bytes.size() - offset);
+    try {
+      auto packet = vrt::parse_packet(rest);
+      std::cout << std::string(packet->summary()) << std::endl;
+      session->process(*packet);
+      offset += packet->packet_size() * 4;
+    } catch (const rust::Error &e) {
+      std::cerr << "offset " << offset << ": " << e.what() << std::endl;
+      return 1;
+    }
+    for (const auto &event : session->take_events()) {
+      if (event.kind == vrt::EventKind::PacketLoss) {
+        std::cout << "  lost " << event.lost << " packets of stream " << event.stream_id
+                  << std::endl;
+      }
+    }
+  }
+  return 0;
+}
@@ -0,0 +1,263 @@
+//! C++ bindings for parsing packets and pairing data with context packets.
+//!
+//! A `cxx` bridge in the `vrt` namespace: `vrt::parse_packet` returns a `rust::Box<Packet>`
+//! and `vrt::new_session` a `rust::Box<Session>`, which free the Rust objects when they go out
+//! of scope and throw `rust::Error` where the Rust functions fail. The generated headers are
+//! `rust/cxx.h` and `vrt-cxx/src/lib.rs.h` under `target/cxxbridge`, and the static library
+//! `libvrt_cxx.a` holds both the bindings and the generated C++ code; see
+//! `examples/summary.cc`.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use vrt::{VrtPacket, VrtPacketOwned, VrtSession};
+
+#[cxx::bridge(namespace = "vrt")]
+mod ffi {
+    /// Kind of a [`SessionEvent`]
+    #[derive(Debug)]
+    enum EventKind {
+        /// The first packet of a stream was received.
+        NewStream,
+        /// A context packet differing from the previous one of its stream was received.
+        ContextChanged,
+        /// Packets of a stream were lost.
+        PacketLoss,
+        /// The timestamp of a packet is discontinuous with the previous one of its stream.
+        TimestampDiscontinuity,
+    }
+
+    /// Event raised by a [`Session`] while processing packets
+    #[derive(Debug)]
+    struct SessionEvent {
+        /// Kind of event
+        kind: EventKind,
+        /// Whether the stream has a Stream Identifier
+        has_stream_id: bool,
+        /// Stream Identifier, or 0 for packets without one
+        stream_id: u32,
+        /// Whether a lost packet belongs to the context stream
+        context: bool,
+        /// Number of lost packets, for a packet loss event
+        lost: u64,
+    }
+
+    /// Signal context fields accumulated from a context stream, NaN where not yet received
+    #[derive(Debug)]
+    struct Signal {
+        /// Bandwidth in Hz
+        bandwidth: f64,
+        /// IF Reference Frequency in Hz
+        if_frequency: f64,
+        /// RF Reference Frequency in Hz
+        rf_frequency: f64,
+        /// Reference Level in dBm
+        reference_level: f64,
+        /// Gain in dB
+        gain: f64,
+        /// Sample Rate in Hz
+        sample_rate: f64,
+        /// Temperature in degrees Celsius
+        temperature: f64,
+    }
+
+    extern "Rust" {
+        type Packet;
+
+        fn parse_packet(bytes: &[u8]) -> Result<Box<Packet>>;
+        fn packet_type(self: &Packet) -> u8;
+        fn packet_count(self: &Packet) -> u8;
+        fn packet_size(self: &Packet) -> u16;
+        fn has_stream_id(self: &Packet) -> bool;
+        fn stream_id(self: &Packet) -> u32;
+        fn has_class_id(self: &Packet) -> bool;
+        fn class_id(self: &Packet) -> u64;
+        fn has_tsi(self: &Packet) -> bool;
+        fn tsi(self: &Packet) -> u32;
+        fn has_tsf(self: &Packet) -> bool;
+        fn tsf(self: &Packet) -> u64;
+        fn payload(self: &Packet) -> &[u8];
+        fn has_trailer(self: &Packet) -> bool;
+        fn to_bytes(self: &Packet) -> Result<Vec<u8>>;
+        fn summary(self: &Packet) -> String;
+
+        type Session;
+
+        fn new_session(bytes_per_sample: usize) -> Box<Session>;
+        fn process(self: &mut Session, packet: &Packet) -> bool;
+        fn has_context(self: &Session, stream_id: u32) -> bool;
+        fn signal(self: &Session, stream_id: u32) -> Signal;
+        fn take_events(self: &mut Session) -> Vec<SessionEvent>;
+    }
+}
+
+pub use ffi::{EventKind, SessionEvent, Signal};
+
+/// A parsed packet
+#[derive(Clone, Debug)]
+pub struct Packet(VrtPacketOwned);
+
+/// Parses the packet at the start of `bytes`
+pub fn parse_packet(bytes: &[u8]) -> Result<Box<Packet>, vrt::Error> {
+    let (_, packet) = VrtPacket::parse(bytes)?;
+    Ok(Box::new(Packet(VrtPacketOwned::from(&packet))))
+}
+
+impl Packet {
+    /// Returns the packet type
+    pub fn packet_type(&self) -> u8 {
+        self.0.header.packet_type.0
+    }
+
+    /// Returns the packet count
+    pub fn packet_count(&self) -> u8 {
+        self.0.header.packet_count
+    }
+
+    /// Returns the packet size in 32-bit words
+    pub fn packet_size(&self) -> u16 {
+        self.0.header.packet_size
+    }
+
+    /// Returns whether the packet has a Stream Identifier
+    pub fn has_stream_id(&self) -> bool {
+        self.0.stream_id.is_some()
+    }
+
+    /// Returns the Stream Identifier, or 0 if absent
+    pub fn stream_id(&self) -> u32 {
+        self.0.stream_id.unwrap_or(0)
+    }
+
+    /// Returns whether the packet has a Class Identifier
+    pub fn has_class_id(&self) -> bool {
+        self.0.class_id.is_some()
+    }
+
+    /// Returns the Class Identifier, or 0 if absent
+    pub fn class_id(&self) -> u64 {
+        self.0.class_id.unwrap_or(0)
+    }
+
+    /// Returns whether the packet has an Integer-seconds Timestamp
+    pub fn has_tsi(&self) -> bool {
+        self.0.tsi.is_some()
+    }
+
+    /// Returns the Integer-seconds Timestamp, or 0 if absent
+    pub fn tsi(&self) -> u32 {
+        self.0.tsi.unwrap_or(0)
+    }
+
+    /// Returns whether the packet has a Fractional-seconds Timestamp
+    pub fn has_tsf(&self) -> bool {
+        self.0.tsf.is_some()
+    }
+
+    /// Returns the Fractional-seconds Timestamp, or 0 if absent
+    pub fn tsf(&self) -> u64 {
+        self.0.tsf.unwrap_or(0)
+    }
+
+    /// Returns the data payload
+    pub fn payload(&self) -> &[u8] {
+        &self.0.data_payload
+    }
+
+    /// Returns whether the packet has a trailer
+    pub fn has_trailer(&self) -> bool {
+        self.0.trailer.is_some()
+    }
+
+    /// Encodes the packet
+    pub fn to_bytes(&self) -> Result<Vec<u8>, vrt::Error> {
+        self.0.as_packet().to_vec()
+    }
+
+    /// Returns a one-line summary of the packet
+    pub fn summary(&self) -> String {
+        self.0.as_packet().to_string()
+    }
+}
+
+/// Data and context packet session, pairing data streams with the context streams carrying
+/// the same Stream Identifier
+pub struct Session {
+    session: VrtSession<'static>,
+    events: Rc<RefCell<Vec<SessionEvent>>>,
+}
+
+/// Creates a new Session; a non-zero `bytes_per_sample` enables the detection of timestamp
+/// discontinuities from the sample rate as described for `VrtSession::with_bytes_per_sample`
+pub fn new_session(bytes_per_sample: usize) -> Box<Session> {
+    let mut session = VrtSession::new();
+    if bytes_per_sample != 0 {
+        session = session.with_bytes_per_sample(bytes_per_sample);
+    }
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let queue = Rc::clone(&events);
+    session.on_event(move |event| queue.borrow_mut().push(SessionEvent::from(event)));
+    Box::new(Session { session, events })
+}
+
+impl Session {
+    /// Processes `packet`, returning whether it is a data packet whose context stream has
+    /// sent a context packet
+    pub fn process(&mut self, packet: &Packet) -> bool {
+        self.session
+            .process(packet.0.as_packet())
+            .is_some_and(|p| p.context.is_some())
+    }
+
+    /// Returns whether a context packet of the stream `stream_id` has been received
+    pub fn has_context(&self, stream_id: u32) -> bool {
+        self.session.context(stream_id).is_some()
+    }
+
+    /// Returns the signal context of the context stream `stream_id`
+    pub fn signal(&self, stream_id: u32) -> Signal {
+        let signal = self.session.signal(stream_id).cloned().unwrap_or_default();
+        let value = |field: Option<f64>| field.unwrap_or(f64::NAN);
+        Signal {
+            bandwidth: value(signal.bandwidth),
+            if_frequency: value(signal.if_frequency),
+            rf_frequency: value(signal.rf_frequency),
+            reference_level: value(signal.reference_level),
+            gain: value(signal.gain),
+            sample_rate: value(signal.sample_rate),
+            temperature: value(signal.temperature),
+        }
+    }
+
+    /// Returns the events raised since the last call
+    pub fn take_events(&mut self) -> Vec<SessionEvent> {
+        self.events.take()
+    }
+}
+
+impl From<&vrt::SessionEvent<'_>> for SessionEvent {
+    fn from(event: &vrt::SessionEvent<'_>) -> Self {
+        let (kind, stream_id, context, lost) = match *event {
+            vrt::SessionEvent::NewStream { stream_id } => {
+                (EventKind::NewStream, stream_id, false, 0)
+            }
+            vrt::SessionEvent::ContextChanged { stream_id, .. } => {
+                (EventKind::ContextChanged, Some(stream_id), true, 0)
+            }
+            vrt::SessionEvent::PacketLoss {
+                stream_id,
+                context,
+                lost,
+            } => (EventKind::PacketLoss, stream_id, context, lost),
+            vrt::SessionEvent::TimestampDiscontinuity { stream_id, .. } => {
+                (EventKind::TimestampDiscontinuity, stream_id, false, 0)
+            }
+        };
+        SessionEvent {
+            kind,
+            has_stream_id: stream_id.is_some(),
+            stream_id: stream_id.unwrap_or(0),
+            context,
+            lost,
+        }
+    }
+}
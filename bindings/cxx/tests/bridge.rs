@@ -0,0 +1,67 @@
+extern crate vrt_cxx;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt_cxx::*;
+
+    /// Gain of 5 dB and Sample Rate of 2 MHz
+    static VRT_STATIC_CONTEXT_STREAM_1_SIGNAL: &[u8] = &[
+        0x40, 0x02, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0xa0, 0x00, 0x00, 0x00, 0x00, 0x02,
+        0x80, 0x00, 0x00, 0x01, 0xe8, 0x48, 0x00, 0x00, 0x00,
+    ];
+
+    static VRT_STATIC_DATA_STREAM_1: &[u8] = &[
+        0x10, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    static VRT_STATIC_DATA_STREAM_1_COUNT_3: &[u8] = &[
+        0x10, 0x03, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    #[test]
+    fn test_packet() {
+        let packet = parse_packet(VRT_STATIC_DATA_STREAM_1).unwrap();
+        assert_eq!(packet.packet_type(), 1);
+        assert_eq!(packet.packet_size(), 3);
+        assert!(packet.has_stream_id());
+        assert_eq!(packet.stream_id(), 1);
+        assert!(!packet.has_class_id());
+        assert!(!packet.has_tsi());
+        assert_eq!(packet.payload(), &[0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(packet.to_bytes().unwrap(), VRT_STATIC_DATA_STREAM_1);
+        assert!(parse_packet(&VRT_STATIC_DATA_STREAM_1[..8]).is_err());
+    }
+
+    #[test]
+    fn test_session() {
+        let mut session = new_session(0);
+        let data = parse_packet(VRT_STATIC_DATA_STREAM_1).unwrap();
+        assert!(!session.process(&data));
+        assert!(session.signal(1).sample_rate.is_nan());
+
+        let context = parse_packet(VRT_STATIC_CONTEXT_STREAM_1_SIGNAL).unwrap();
+        assert!(!session.process(&context));
+        assert!(session.has_context(1));
+        assert_eq!(session.signal(1).sample_rate, 2e6);
+        assert_eq!(session.signal(1).gain, 5.0);
+        assert!(session.signal(1).bandwidth.is_nan());
+
+        let data = parse_packet(VRT_STATIC_DATA_STREAM_1_COUNT_3).unwrap();
+        assert!(session.process(&data));
+        let events = session.take_events();
+        let kinds: Vec<_> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                EventKind::NewStream,
+                EventKind::ContextChanged,
+                EventKind::PacketLoss
+            ]
+        );
+        assert!(events[2].has_stream_id);
+        assert_eq!(events[2].stream_id, 1);
+        assert!(!events[2].context);
+        assert_eq!(events[2].lost, 2);
+        assert!(session.take_events().is_empty());
+    }
+}
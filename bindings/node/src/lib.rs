@@ -0,0 +1,210 @@
+//! Node.js bindings for parsing packets and tracking stream statistics.
+//!
+//! Built as a native addon with napi-rs, for example with `npm run build`, this crate exports
+//! `parsePacket` and `parsePackets` returning [`Packet`] objects, and a [`StreamTracker`]
+//! class counting lost, duplicated and reordered packets per stream.
+
+use napi::bindgen_prelude::Buffer;
+use napi::{Error, Result};
+use napi_derive::napi;
+use vrt::{Continuity, PacketStream, VrtPacket, VrtPacketOwned};
+
+fn js_error(e: vrt::Error) -> Error {
+    Error::from_reason(e.to_string())
+}
+
+/// A parsed packet
+#[napi]
+#[derive(Clone, Debug)]
+pub struct Packet {
+    packet: VrtPacketOwned,
+}
+
+#[napi]
+impl Packet {
+    /// Returns the packet type
+    #[napi(getter)]
+    pub fn packet_type(&self) -> u8 {
+        self.packet.header.packet_type.0
+    }
+
+    /// Returns the packet count
+    #[napi(getter)]
+    pub fn packet_count(&self) -> u8 {
+        self.packet.header.packet_count
+    }
+
+    /// Returns the packet size in 32-bit words
+    #[napi(getter)]
+    pub fn packet_size(&self) -> u16 {
+        self.packet.header.packet_size
+    }
+
+    /// Returns the Stream Identifier, if present
+    #[napi(getter)]
+    pub fn stream_id(&self) -> Option<u32> {
+        self.packet.stream_id
+    }
+
+    /// Returns the Class Identifier as a BigInt, if present
+    #[napi(getter)]
+    pub fn class_id(&self) -> Option<u64> {
+        self.packet.class_id
+    }
+
+    /// Returns the Integer-seconds Timestamp, if present
+    #[napi(getter)]
+    pub fn tsi(&self) -> Option<u32> {
+        self.packet.tsi
+    }
+
+    /// Returns the Fractional-seconds Timestamp as a BigInt, if present
+    #[napi(getter)]
+    pub fn tsf(&self) -> Option<u64> {
+        self.packet.tsf
+    }
+
+    /// Returns a copy of the data payload
+    #[napi(getter)]
+    pub fn payload(&self) -> Buffer {
+        self.packet.data_payload.clone().into()
+    }
+
+    /// Returns whether the packet has a trailer
+    #[napi(getter)]
+    pub fn has_trailer(&self) -> bool {
+        self.packet.trailer.is_some()
+    }
+
+    /// Returns a one-line summary of the packet
+    #[napi(js_name = "toString")]
+    pub fn describe(&self) -> String {
+        self.packet.as_packet().to_string()
+    }
+}
+
+impl From<VrtPacketOwned> for Packet {
+    fn from(packet: VrtPacketOwned) -> Self {
+        Packet { packet }
+    }
+}
+
+/// Parses the packet at the start of `bytes`, throwing if it is incomplete or malformed
+#[napi]
+pub fn parse_packet(bytes: Buffer) -> Result<Packet> {
+    let (_, packet) = VrtPacket::parse(&bytes).map_err(js_error)?;
+    Ok(VrtPacketOwned::from(&packet).into())
+}
+
+/// Parses the back-to-back packets in `bytes`, ignoring a trailing incomplete packet and
+/// throwing if a packet is malformed
+#[napi]
+pub fn parse_packets(bytes: Buffer) -> Result<Vec<Packet>> {
+    PacketStream::new(&bytes)
+        .map(|packet| {
+            packet
+                .map(|p| VrtPacketOwned::from(&p).into())
+                .map_err(js_error)
+        })
+        .collect()
+}
+
+/// Continuity counters of a packet stream
+#[napi(object)]
+#[derive(Clone, Debug)]
+pub struct StreamStats {
+    /// Stream Identifier, or undefined for packets without one
+    pub stream_id: Option<u32>,
+    /// Number of packets received, including duplicated and reordered packets
+    pub received: f64,
+    /// Number of packets which were expected but not received
+    pub lost: f64,
+    /// Number of packets received more than once
+    pub duplicated: f64,
+    /// Number of packets received after a later packet of the same stream
+    pub reordered: f64,
+    /// Fraction of expected packets which were lost, from 0.0 to 1.0
+    pub loss_rate: f64,
+}
+
+impl StreamStats {
+    fn new(stream_id: Option<u32>, stats: vrt::StreamStats) -> Self {
+        StreamStats {
+            stream_id,
+            received: stats.received as f64,
+            lost: stats.lost as f64,
+            duplicated: stats.duplicated as f64,
+            reordered: stats.reordered as f64,
+            loss_rate: stats.loss_rate(),
+        }
+    }
+}
+
+/// Continuity of a packet relative to the previous packets of its stream
+#[napi(object)]
+#[derive(Clone, Debug)]
+pub struct PacketContinuity {
+    /// One of `first`, `inOrder`, `lost`, `duplicate` or `reordered`
+    pub kind: String,
+    /// Number of packets lost before this packet
+    pub lost: f64,
+}
+
+/// Continuity checker for multiple packet streams, keyed by Stream Identifier
+#[napi]
+#[derive(Debug, Default)]
+pub struct StreamTracker {
+    tracker: vrt::StreamTracker,
+}
+
+#[napi]
+impl StreamTracker {
+    /// Creates a new StreamTracker
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks the continuity of `packet` against the previous packets of its stream
+    #[napi]
+    pub fn update(&mut self, packet: &Packet) -> PacketContinuity {
+        let (kind, lost) = match self.tracker.update(&packet.packet.as_packet()) {
+            Continuity::First => ("first", 0),
+            Continuity::InOrder => ("inOrder", 0),
+            Continuity::Lost(n) => ("lost", n),
+            Continuity::Duplicate => ("duplicate", 0),
+            Continuity::Reordered => ("reordered", 0),
+        };
+        PacketContinuity {
+            kind: kind.to_string(),
+            lost: lost as f64,
+        }
+    }
+
+    /// Returns the total counters for the stream `streamId`, if any packets were seen
+    #[napi]
+    pub fn stats(&self, stream_id: Option<u32>) -> Option<StreamStats> {
+        self.tracker
+            .stats(stream_id)
+            .map(|stats| StreamStats::new(stream_id, stats))
+    }
+
+    /// Returns the total counters of every stream
+    #[napi]
+    pub fn streams(&self) -> Vec<StreamStats> {
+        self.tracker
+            .iter()
+            .map(|(id, stats)| StreamStats::new(id, stats))
+            .collect()
+    }
+
+    /// Returns the window counters of every stream and starts a new window
+    #[napi]
+    pub fn take_window(&mut self) -> Vec<StreamStats> {
+        self.tracker
+            .take_window()
+            .into_iter()
+            .map(|(id, stats)| StreamStats::new(id, stats))
+            .collect()
+    }
+}
@@ -0,0 +1,47 @@
+const assert = require('node:assert');
+const test = require('node:test');
+const vrt = require('../vrt.node');
+
+const STREAM_1 = Buffer.from([
+  0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+  0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+]);
+
+function withCount(bytes, count) {
+  const packet = Buffer.from(bytes);
+  packet[1] = (packet[1] & 0xf0) | count;
+  return packet;
+}
+
+test('parsePacket', () => {
+  const packet = vrt.parsePacket(STREAM_1);
+  assert.strictEqual(packet.packetType, 1);
+  assert.strictEqual(packet.packetSize, 6);
+  assert.strictEqual(packet.streamId, 1);
+  assert.strictEqual(packet.classId, null);
+  assert.strictEqual(packet.tsi, 10);
+  assert.strictEqual(packet.tsf, 5n);
+  assert.deepStrictEqual(packet.payload, Buffer.from([0xca, 0xfe, 0xf0, 0x0d]));
+  assert.strictEqual(packet.hasTrailer, false);
+  assert.throws(() => vrt.parsePacket(STREAM_1.subarray(0, 8)), /incomplete/);
+});
+
+test('parsePackets', () => {
+  const packets = vrt.parsePackets(Buffer.concat([STREAM_1, withCount(STREAM_1, 1)]));
+  assert.deepStrictEqual(packets.map((p) => p.packetCount), [0, 1]);
+});
+
+test('StreamTracker', () => {
+  const tracker = new vrt.StreamTracker();
+  assert.strictEqual(tracker.update(vrt.parsePacket(withCount(STREAM_1, 0))).kind, 'first');
+  assert.deepStrictEqual(tracker.update(vrt.parsePacket(withCount(STREAM_1, 3))), {
+    kind: 'lost',
+    lost: 2,
+  });
+  const stats = tracker.stats(1);
+  assert.strictEqual(stats.received, 2);
+  assert.strictEqual(stats.lost, 2);
+  assert.strictEqual(stats.lossRate, 0.5);
+  assert.strictEqual(tracker.stats(2), null);
+  assert.strictEqual(tracker.takeWindow().length, 1);
+});
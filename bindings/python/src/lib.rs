@@ -0,0 +1,339 @@
+//! Python bindings for parsing, building and reading captures of packets.
+//!
+//! Built as the `vrt` extension module with maturin, for example with `maturin develop`, this
+//! crate exports `parse_packet` and `parse_packets` returning [`Packet`] objects, which can
+//! also be built from their fields and encoded with `Packet.to_bytes`, the [`Recorder`] of
+//! indexed captures, and the [`CaptureReader`] and [`PcapReader`] iterators over the packets
+//! of indexed and of pcap or pcapng captures. `Packet.samples` returns the data payload as a
+//! numpy array.
+
+use numpy::PyArray1;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use vrt::{Header, PacketStream, Trailer, Tsf, Tsi, VitaPacketType, VrtPacket, VrtPacketOwned};
+
+fn value_error(e: vrt::Error) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+/// A parsed or built packet
+#[pyclass(frozen, skip_from_py_object, module = "vrt")]
+#[derive(Clone, Debug)]
+pub struct Packet {
+    packet: VrtPacketOwned,
+}
+
+#[pymethods]
+impl Packet {
+    /// Builds a packet from its fields, raising ValueError if the Stream Identifier does not
+    /// match the packet type or the packet is too long
+    #[new]
+    #[pyo3(signature = (
+        packet_type = VitaPacketType::IFDATAWITHSTREAM,
+        *,
+        stream_id = None,
+        class_id = None,
+        tsi = None,
+        tsf = None,
+        tsi_type = Tsi::TSI_UTC.0,
+        tsf_type = Tsf::TSF_REAL_TIME.0,
+        packet_count = 0,
+        payload = Vec::new(),
+        trailer = false,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        packet_type: u8,
+        stream_id: Option<u32>,
+        class_id: Option<u64>,
+        tsi: Option<u32>,
+        tsf: Option<u64>,
+        tsi_type: u8,
+        tsf_type: u8,
+        packet_count: u8,
+        payload: Vec<u8>,
+        trailer: bool,
+    ) -> PyResult<Self> {
+        let packet_type = VitaPacketType(packet_type & 0x0f);
+        let trailer = trailer && packet_type.is_data();
+        let mut packet = VrtPacketOwned {
+            header: Header {
+                packet_type,
+                c: class_id.is_some(),
+                t: trailer,
+                indicators: 0,
+                tsi: tsi.map_or(Tsi::TSI_NONE, |_| Tsi(tsi_type & 3)),
+                tsf: tsf.map_or(Tsf::TSF_NONE, |_| Tsf(tsf_type & 3)),
+                packet_count: packet_count & 0x0f,
+                packet_size: 0,
+            },
+            stream_id,
+            class_id,
+            tsi,
+            tsf,
+            data_payload: payload,
+            trailer: trailer.then(Trailer::default),
+        };
+        let len = packet.as_packet().to_vec().map_err(value_error)?.len();
+        packet.header.packet_size = (len / 4) as u16;
+        Ok(packet.into())
+    }
+
+    /// Packet type
+    #[getter]
+    fn packet_type(&self) -> u8 {
+        self.packet.header.packet_type.0
+    }
+
+    /// Packet count
+    #[getter]
+    fn packet_count(&self) -> u8 {
+        self.packet.header.packet_count
+    }
+
+    /// Packet size in 32-bit words
+    #[getter]
+    fn packet_size(&self) -> u16 {
+        self.packet.header.packet_size
+    }
+
+    /// Stream Identifier, or None
+    #[getter]
+    fn stream_id(&self) -> Option<u32> {
+        self.packet.stream_id
+    }
+
+    /// Class Identifier, or None
+    #[getter]
+    fn class_id(&self) -> Option<u64> {
+        self.packet.class_id
+    }
+
+    /// Integer-seconds Timestamp, or None
+    #[getter]
+    fn tsi(&self) -> Option<u32> {
+        self.packet.tsi
+    }
+
+    /// Fractional-seconds Timestamp, or None
+    #[getter]
+    fn tsf(&self) -> Option<u64> {
+        self.packet.tsf
+    }
+
+    /// Data payload as bytes
+    #[getter]
+    fn payload<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.packet.data_payload)
+    }
+
+    /// Whether the packet has a trailer
+    #[getter]
+    fn has_trailer(&self) -> bool {
+        self.packet.trailer.is_some()
+    }
+
+    /// Returns the data payload as a numpy array of big-endian samples of `dtype`, one of
+    /// `int8`, `int16`, `int32`, `float32` and `float64`, in native byte order; trailing bytes
+    /// short of a whole sample are ignored
+    #[pyo3(signature = (dtype = "int16"))]
+    fn samples<'py>(&self, py: Python<'py>, dtype: &str) -> PyResult<Bound<'py, PyAny>> {
+        let payload = &self.packet.data_payload;
+        Ok(match dtype {
+            "int8" => PyArray1::from_iter(py, payload.iter().map(|&b| b as i8)).into_any(),
+            "int16" => samples(py, payload, i16::from_be_bytes).into_any(),
+            "int32" => samples(py, payload, i32::from_be_bytes).into_any(),
+            "float32" => samples(py, payload, f32::from_be_bytes).into_any(),
+            "float64" => samples(py, payload, f64::from_be_bytes).into_any(),
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "unsupported sample dtype {:?}",
+                    dtype
+                )))
+            }
+        })
+    }
+
+    /// Encodes the packet
+    fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let bytes = self.packet.as_packet().to_vec().map_err(value_error)?;
+        Ok(PyBytes::new(py, &bytes))
+    }
+
+    fn __str__(&self) -> String {
+        self.packet.as_packet().to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<vrt.Packet {}>", self.packet.as_packet())
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.packet == other.packet
+    }
+}
+
+fn samples<'py, T: numpy::Element, const N: usize>(
+    py: Python<'py>,
+    payload: &[u8],
+    from_be_bytes: fn([u8; N]) -> T,
+) -> Bound<'py, PyArray1<T>> {
+    let samples = payload.chunks_exact(N).map(|chunk| {
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(chunk);
+        from_be_bytes(bytes)
+    });
+    PyArray1::from_iter(py, samples)
+}
+
+impl From<VrtPacketOwned> for Packet {
+    fn from(packet: VrtPacketOwned) -> Self {
+        Packet { packet }
+    }
+}
+
+/// Parses the packet at the start of `bytes`, raising ValueError if it is incomplete or
+/// malformed
+#[pyfunction]
+fn parse_packet(bytes: &[u8]) -> PyResult<Packet> {
+    let (_, packet) = VrtPacket::parse(bytes).map_err(value_error)?;
+    Ok(VrtPacketOwned::from(&packet).into())
+}
+
+/// Parses the back-to-back packets in `bytes`, ignoring a trailing incomplete packet and
+/// raising ValueError if a packet is malformed
+#[pyfunction]
+fn parse_packets(bytes: &[u8]) -> PyResult<Vec<Packet>> {
+    PacketStream::new(bytes)
+        .map(|packet| {
+            packet
+                .map(|p| VrtPacketOwned::from(&p).into())
+                .map_err(value_error)
+        })
+        .collect()
+}
+
+/// Iterator over the packets of a capture written by `vrt::Recorder`, with random access
+/// through its index
+#[pyclass(module = "vrt")]
+pub struct CaptureReader {
+    reader: vrt::CaptureReader<BufReader<File>, BufReader<File>>,
+}
+
+#[pymethods]
+impl CaptureReader {
+    /// Opens the capture at `path` together with its index
+    #[new]
+    fn new(path: &str) -> PyResult<Self> {
+        Ok(CaptureReader {
+            reader: vrt::CaptureReader::open(path)?,
+        })
+    }
+
+    /// Moves to the `n`th packet of the capture
+    fn seek(&mut self, n: u64) -> PyResult<()> {
+        Ok(self.reader.seek_to_packet(n)?)
+    }
+
+    /// Index of the next packet to be read
+    #[getter]
+    fn position(&self) -> u64 {
+        self.reader.position()
+    }
+
+    fn __len__(&self) -> usize {
+        self.reader.len() as usize
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> PyResult<Option<Packet>> {
+        Ok(self.reader.read_packet()?.map(Packet::from))
+    }
+}
+
+/// Writer of captures with an index, read back by [`CaptureReader`]
+#[pyclass(module = "vrt")]
+pub struct Recorder {
+    recorder: vrt::Recorder<BufWriter<File>, BufWriter<File>>,
+}
+
+#[pymethods]
+impl Recorder {
+    /// Creates the capture at `path` together with its index
+    #[new]
+    fn new(path: &str) -> PyResult<Self> {
+        Ok(Recorder {
+            recorder: vrt::Recorder::create(path)?,
+        })
+    }
+
+    /// Appends the packet at the start of `bytes` to the capture, raising OSError if `bytes`
+    /// does not start with a valid packet
+    fn record(&mut self, bytes: &[u8]) -> PyResult<()> {
+        self.recorder.record(bytes)?;
+        Ok(())
+    }
+
+    /// Writes the buffered packets and index entries to the files
+    fn flush(&mut self) -> PyResult<()> {
+        Ok(self.recorder.flush()?)
+    }
+
+    fn __len__(&self) -> usize {
+        self.recorder.packets() as usize
+    }
+}
+
+/// Iterator over the VRT packets of a pcap or pcapng capture, yielding tuples of the capture
+/// time in seconds since the UNIX epoch and the packet
+#[pyclass(module = "vrt")]
+pub struct PcapReader {
+    reader: vrt::PcapReader<BufReader<File>>,
+}
+
+#[pymethods]
+impl PcapReader {
+    /// Opens the capture at `path`
+    #[new]
+    fn new(path: &str) -> PyResult<Self> {
+        Ok(PcapReader {
+            reader: vrt::PcapReader::open(path)?,
+        })
+    }
+
+    /// Number of frames skipped as not holding VRT packets
+    #[getter]
+    fn skipped(&self) -> u64 {
+        self.reader.skipped()
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> PyResult<Option<(f64, Packet)>> {
+        Ok(self
+            .reader
+            .read_packet()?
+            .map(|p| (p.timestamp.as_secs_f64(), p.packet.into())))
+    }
+}
+
+/// VITA Radio Transport packets
+#[pymodule]
+#[pyo3(name = "vrt")]
+fn vrt_python(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Packet>()?;
+    m.add_class::<CaptureReader>()?;
+    m.add_class::<PcapReader>()?;
+    m.add_class::<Recorder>()?;
+    m.add_function(wrap_pyfunction!(parse_packet, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_packets, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,106 @@
+import os
+import struct
+import tempfile
+import unittest
+
+import vrt
+
+try:
+    import numpy
+except ImportError:
+    numpy = None
+
+STREAM_1 = bytes([
+    0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+])
+
+STREAM_2 = bytes([0x10, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef])
+
+
+def pcap(*datagrams):
+    """Returns a pcap capture of UDP datagrams over IPv4 and Ethernet"""
+    capture = struct.pack("<IHHiIII", 0xA1B2C3D4, 2, 4, 0, 0, 65535, 1)
+    for i, payload in enumerate(datagrams):
+        udp = struct.pack(">HHHH", 5000, 4991, 8 + len(payload), 0) + payload
+        ip = struct.pack(">BBHHHBBH4s4s", 0x45, 0, 20 + len(udp), 0, 0, 64, 17, 0,
+                         bytes([127, 0, 0, 1]), bytes([127, 0, 0, 1])) + udp
+        frame = bytes(12) + struct.pack(">H", 0x0800) + ip
+        capture += struct.pack("<IIII", 1000 + i, 500000, len(frame), len(frame)) + frame
+    return capture
+
+
+class TestPacket(unittest.TestCase):
+    def test_parse_packet(self):
+        packet = vrt.parse_packet(STREAM_1)
+        self.assertEqual(packet.packet_type, 1)
+        self.assertEqual(packet.packet_size, 6)
+        self.assertEqual(packet.stream_id, 1)
+        self.assertIsNone(packet.class_id)
+        self.assertEqual(packet.tsi, 10)
+        self.assertEqual(packet.tsf, 5)
+        self.assertEqual(packet.payload, b"\xca\xfe\xf0\x0d")
+        self.assertFalse(packet.has_trailer)
+        with self.assertRaisesRegex(ValueError, "incomplete"):
+            vrt.parse_packet(STREAM_1[:8])
+
+    def test_parse_packets(self):
+        packets = vrt.parse_packets(STREAM_1 + STREAM_2)
+        self.assertEqual([p.stream_id for p in packets], [1, 2])
+
+    def test_build_packet(self):
+        packet = vrt.Packet(stream_id=1, tsi=10, tsf=5, payload=b"\xca\xfe\xf0\x0d")
+        self.assertEqual(packet.packet_size, 6)
+        self.assertEqual(packet.to_bytes(), STREAM_1)
+        self.assertEqual(packet, vrt.parse_packet(STREAM_1))
+        packet = vrt.Packet(stream_id=2, payload=b"\x01", trailer=True)
+        self.assertEqual(vrt.parse_packet(packet.to_bytes()).payload, b"\x01\x00\x00\x00")
+        with self.assertRaises(ValueError):
+            vrt.Packet(payload=b"")
+
+    @unittest.skipIf(numpy is None, "numpy is not installed")
+    def test_samples(self):
+        packet = vrt.Packet(stream_id=1, payload=b"\x00\x01\xff\xfe\x7f")
+        samples = packet.samples()
+        self.assertEqual(samples.dtype, numpy.int16)
+        self.assertEqual(samples.tolist(), [1, -2])
+        self.assertEqual(packet.samples("int8").tolist(), [0, 1, -1, -2, 127])
+        with self.assertRaises(ValueError):
+            packet.samples("complex64")
+
+
+class TestCaptures(unittest.TestCase):
+    def setUp(self):
+        self.dir = tempfile.TemporaryDirectory()
+
+    def tearDown(self):
+        self.dir.cleanup()
+
+    def test_capture_reader(self):
+        path = os.path.join(self.dir.name, "capture.vrt")
+        recorder = vrt.Recorder(path)
+        recorder.record(STREAM_1)
+        recorder.record(STREAM_2)
+        self.assertEqual(len(recorder), 2)
+        recorder.flush()
+
+        reader = vrt.CaptureReader(path)
+        self.assertEqual(len(reader), 2)
+        self.assertEqual([p.stream_id for p in reader], [1, 2])
+        reader.seek(1)
+        self.assertEqual(reader.position, 1)
+        self.assertEqual(next(reader).stream_id, 2)
+
+    def test_pcap_reader(self):
+        path = os.path.join(self.dir.name, "capture.pcap")
+        with open(path, "wb") as f:
+            f.write(pcap(STREAM_1, b"not vrt", STREAM_2))
+        reader = vrt.PcapReader(path)
+        packets = list(reader)
+        self.assertEqual([p.stream_id for _, p in packets], [1, 2])
+        self.assertEqual(packets[0][0], 1000.5)
+        self.assertEqual(reader.skipped, 1)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,158 @@
+/*
+ * C API of the vrt crate, enabled by the `ffi` feature.
+ *
+ * Build the shared library (libvrt.so, libvrt.dylib or vrt.dll) with:
+ *     cargo build --release -p vrt-c
+ */
+#ifndef VRT_H
+#define VRT_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define VRT_OK 0
+#define VRT_ERR_INCOMPLETE -1
+#define VRT_ERR_MALFORMED -2
+#define VRT_ERR_BUFFER_TOO_SMALL -3
+#define VRT_ERR_INVALID_ARGUMENT -4
+#define VRT_ERR_DISCONTINUITY -5
+
+#define VRT_CIF0_CHANGE_INDICATOR (1u << 31)
+#define VRT_CIF0_REFERENCE_POINT_ID (1u << 30)
+#define VRT_CIF0_BANDWIDTH (1u << 29)
+#define VRT_CIF0_IF_REFERENCE_FREQUENCY (1u << 28)
+#define VRT_CIF0_RF_REFERENCE_FREQUENCY (1u << 27)
+#define VRT_CIF0_RF_REFERENCE_FREQUENCY_OFFSET (1u << 26)
+#define VRT_CIF0_IF_BAND_OFFSET (1u << 25)
+#define VRT_CIF0_REFERENCE_LEVEL (1u << 24)
+#define VRT_CIF0_GAIN (1u << 23)
+#define VRT_CIF0_OVER_RANGE_COUNT (1u << 22)
+#define VRT_CIF0_SAMPLE_RATE (1u << 21)
+#define VRT_CIF0_TIMESTAMP_ADJUSTMENT (1u << 20)
+#define VRT_CIF0_TIMESTAMP_CALIBRATION_TIME (1u << 19)
+#define VRT_CIF0_TEMPERATURE (1u << 18)
+
+/* Mirror of the packet header */
+typedef struct VrtHeader {
+    uint8_t packet_type;
+    bool c;
+    bool t;
+    uint8_t indicators;
+    uint8_t tsi;
+    uint8_t tsf;
+    uint8_t packet_count;
+    uint16_t packet_size;
+} VrtHeader;
+
+/* Components of a Class Identifier */
+typedef struct VrtClassId {
+    uint32_t oui;
+    uint16_t information_class_code;
+    uint16_t packet_class_code;
+} VrtClassId;
+
+/* Mirror of the packet trailer */
+typedef struct VrtTrailer {
+    bool calibrated_time_enable;
+    bool valid_data_enable;
+    bool reference_lock_enable;
+    bool agcmgc_enable;
+    bool detected_signal_enable;
+    bool spectral_inversion_enable;
+    bool overrange_enable;
+    bool sample_loss_enable;
+    bool user_defined_enable_1;
+    bool user_defined_enable_2;
+    bool user_defined_enable_3;
+    bool user_defined_enable_4;
+    bool calibrated_time_indicator;
+    bool valid_data_indicator;
+    bool reference_lock_indicator;
+    bool agcmgc_indicator;
+    bool detected_signal_indicator;
+    bool spectral_inversion_indicator;
+    bool overrange_indicator;
+    bool sample_loss_indicator;
+    bool user_defined_indicator_1;
+    bool user_defined_indicator_2;
+    bool user_defined_indicator_3;
+    bool user_defined_indicator_4;
+    bool associated_context_packet_count_enable;
+    uint8_t associated_context_packet_count;
+} VrtTrailer;
+
+/* Header fields of one packet and the location of its samples */
+typedef struct VrtPacketInfo {
+    VrtHeader header;
+    bool has_stream_id;
+    uint32_t stream_id;
+    uint64_t class_id;
+    uint32_t tsi;
+    uint64_t tsf;
+    size_t sample_offset;
+    size_t sample_count;
+} VrtPacketInfo;
+
+/* Context Indicator Field 0 fields of a context packet, present if their VRT_CIF0_* bit is set */
+typedef struct VrtContext {
+    uint32_t cif0;
+    uint32_t reference_point_id;
+    double bandwidth;
+    double if_reference_frequency;
+    double rf_reference_frequency;
+    double rf_reference_frequency_offset;
+    double if_band_offset;
+    double reference_level;
+    double gain;
+    uint32_t over_range_count;
+    double sample_rate;
+    int64_t timestamp_adjustment;
+    uint32_t timestamp_calibration_time;
+    double temperature;
+} VrtContext;
+
+/* Opaque packet handle owning a copy of the packet data */
+typedef struct VrtPacket VrtPacket;
+
+int32_t vrt_packet_parse(const uint8_t *buf, size_t len, VrtPacket **out, size_t *consumed);
+VrtPacket *vrt_packet_new_data(uint32_t stream_id);
+VrtPacket *vrt_packet_new_context(uint32_t stream_id);
+void vrt_packet_free(VrtPacket *packet);
+
+uint8_t vrt_packet_type(const VrtPacket *packet);
+uint8_t vrt_packet_count(const VrtPacket *packet);
+void vrt_packet_set_count(VrtPacket *packet, uint8_t packet_count);
+bool vrt_packet_stream_id(const VrtPacket *packet, uint32_t *stream_id);
+bool vrt_packet_header(const VrtPacket *packet, VrtHeader *header);
+bool vrt_packet_class_id(const VrtPacket *packet, uint64_t *class_id);
+bool vrt_packet_timestamp(const VrtPacket *packet, uint8_t *tsi_type, uint32_t *tsi,
+                          uint8_t *tsf_type, uint64_t *tsf);
+int32_t vrt_packet_set_timestamp(VrtPacket *packet, uint8_t tsi_type, uint32_t tsi,
+                                 uint8_t tsf_type, uint64_t tsf);
+const uint8_t *vrt_packet_payload(const VrtPacket *packet, size_t *len);
+int32_t vrt_packet_set_payload(VrtPacket *packet, const uint8_t *payload, size_t len);
+bool vrt_packet_trailer(const VrtPacket *packet, VrtTrailer *trailer);
+int32_t vrt_packet_set_trailer(VrtPacket *packet, const VrtTrailer *trailer);
+bool vrt_packet_context(const VrtPacket *packet, VrtContext *context);
+int32_t vrt_packet_set_context(VrtPacket *packet, const VrtContext *context);
+
+VrtClassId vrt_class_id_split(uint64_t class_id);
+uint64_t vrt_class_id_join(VrtClassId class_id);
+
+size_t vrt_packet_encoded_len(const VrtPacket *packet);
+int32_t vrt_packet_serialize(const VrtPacket *packet, uint8_t *buf, size_t len, size_t *written);
+
+int32_t vrt_buffer_count(const uint8_t *buf, size_t len, size_t *packets, size_t *samples);
+int32_t vrt_buffer_unpack(const uint8_t *buf, size_t len, VrtPacketInfo *infos, size_t max_packets,
+                          int16_t *samples, size_t max_samples, size_t *written);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* VRT_H */
@@ -0,0 +1,146 @@
+//! Time alignment of samples across multiple coherent channels.
+
+use crate::error::Error;
+use crate::vrt::{Tsf, VrtPacket};
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+/// A block of time-aligned samples, one payload per channel
+pub struct AlignedBlock {
+    /// Sample index of the first sample of the block, counted from the epoch of the
+    /// Integer-Seconds Timestamp
+    pub start: i64,
+    /// Samples of each channel, indexed by channel number
+    pub channels: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Channel {
+    offset: i64,
+    start: Option<i64>,
+    samples: Vec<u8>,
+}
+
+/// Aligns the samples of multiple channels sharing a sample clock into coherent blocks
+///
+/// The sample index of each data packet is derived from its timestamp: a Real Time
+/// (Picoseconds) timestamp is converted using the sample rate and rounded to the nearest
+/// sample, and a Sample Count timestamp is taken as the sample number within its integer
+/// second. A per-channel offset in samples, set with [`Aligner::set_offset`], is added to
+/// compensate for fixed timestamp offsets such as cable delays.
+///
+/// Blocks start at the latest channel start; earlier samples of the other channels are
+/// discarded. A packet which does not directly follow the previous packet of its channel
+/// restarts that channel, discarding its buffered samples.
+#[derive(Clone, Debug)]
+pub struct Aligner {
+    sample_rate: u64,
+    bytes_per_sample: usize,
+    block_len: usize,
+    channels: Vec<Channel>,
+}
+
+impl Aligner {
+    /// Creates a new Aligner for `channels` channels sampled at `sample_rate` samples per
+    /// second, producing blocks of `block_len` samples of `bytes_per_sample` bytes each
+    pub fn new(
+        channels: usize,
+        sample_rate: u64,
+        bytes_per_sample: usize,
+        block_len: usize,
+    ) -> Self {
+        Aligner {
+            sample_rate,
+            bytes_per_sample: bytes_per_sample.max(1),
+            block_len: block_len.max(1),
+            channels: vec![Channel::default(); channels],
+        }
+    }
+
+    /// Sets the offset in samples added to the sample indices of channel `channel`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not less than the number of channels.
+    pub fn set_offset(&mut self, channel: usize, offset: i64) {
+        self.channels[channel].offset = offset;
+    }
+
+    fn sample_index(&self, packet: &VrtPacket<'_>) -> Option<i64> {
+        let rate = self.sample_rate as i128;
+        let integer = packet.tsi? as i128;
+        let fractional = packet.tsf? as i128;
+        let index = match packet.header.tsf {
+            Tsf::TSF_SAMPLE_COUNT => integer * rate + fractional,
+            // rounded to the nearest sample, as a sample period need not be a whole number
+            // of picoseconds
+            Tsf::TSF_REAL_TIME => {
+                integer * rate + (fractional * rate + 500_000_000_000) / 1_000_000_000_000
+            }
+            _ => return None,
+        };
+        i64::try_from(index).ok()
+    }
+
+    /// Appends the data payload of `packet` to channel `channel`
+    ///
+    /// Returns [`Error::Malformed`] if the packet has no Sample Count or Real Time
+    /// (Picoseconds) timestamp.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not less than the number of channels.
+    pub fn push(&mut self, channel: usize, packet: &VrtPacket<'_>) -> Result<(), Error> {
+        let index = self.sample_index(packet).ok_or(Error::Malformed)?;
+        let bytes_per_sample = self.bytes_per_sample;
+        let ch = &mut self.channels[channel];
+        let index = index + ch.offset;
+        let end = ch
+            .start
+            .map(|start| start + (ch.samples.len() / bytes_per_sample) as i64);
+        if end != Some(index) {
+            ch.start = Some(index);
+            ch.samples.clear();
+        }
+        ch.samples.extend_from_slice(packet.data_payload);
+        Ok(())
+    }
+
+    /// Returns the next block of aligned samples, or None until every channel has buffered
+    /// enough samples
+    pub fn next_block(&mut self) -> Option<AlignedBlock> {
+        let start = self
+            .channels
+            .iter()
+            .map(|ch| ch.start)
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .max()?;
+        let bytes_per_sample = self.bytes_per_sample;
+        let block_bytes = self.block_len * bytes_per_sample;
+
+        let mut ready = true;
+        for ch in self.channels.iter_mut() {
+            let ch_start = ch.start.unwrap_or(start);
+            if ch_start < start {
+                let skip = ((start - ch_start) as usize).saturating_mul(bytes_per_sample);
+                let skip = skip.min(ch.samples.len() / bytes_per_sample * bytes_per_sample);
+                ch.samples.drain(..skip);
+                ch.start = Some(ch_start + (skip / bytes_per_sample) as i64);
+            }
+            ready &= ch.start == Some(start) && ch.samples.len() >= block_bytes;
+        }
+        if !ready {
+            return None;
+        }
+
+        let channels = self
+            .channels
+            .iter_mut()
+            .map(|ch| {
+                ch.start = Some(start + self.block_len as i64);
+                ch.samples.drain(..block_bytes).collect()
+            })
+            .collect();
+        Some(AlignedBlock { start, channels })
+    }
+}
@@ -0,0 +1,420 @@
+//! Runtime-independent asynchronous reading and writing of VRT packets on byte streams and
+//! datagram sockets.
+
+use crate::encoder::encode_vrt_packet;
+use crate::net::{parse_datagram, DEFAULT_MAX_DATAGRAM_LEN};
+use crate::parser::{parse_vrt_header, parse_vrt_packet};
+use crate::vrt::VrtPacketOwned;
+use futures_io::{AsyncRead, AsyncWrite};
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Size of the read buffer increments
+const READ_CHUNK: usize = 4096;
+
+/// Asynchronous Packet Reader
+///
+/// A [`Stream`](futures_core::Stream) of the VRT packets read from any [`AsyncRead`] byte
+/// stream, framed by the `packet_size` field of each header. Being generic over the `futures`
+/// traits, it works with async-std, smol or any other executor; Tokio streams can be adapted
+/// with `tokio-util`'s `compat` module.
+///
+/// The stream ends when the reader reaches end-of-file between packets. End-of-file within a
+/// packet is an error of kind [`io::ErrorKind::UnexpectedEof`], and a header with a
+/// `packet_size` of zero or a malformed packet is an error of kind
+/// [`io::ErrorKind::InvalidData`].
+#[derive(Debug)]
+pub struct AsyncPacketReader<R> {
+    reader: R,
+    buf: Vec<u8>,
+    filled: usize,
+}
+
+impl<R: AsyncRead + Unpin> AsyncPacketReader<R> {
+    /// Creates a new AsyncPacketReader reading from `reader`
+    pub fn new(reader: R) -> Self {
+        AsyncPacketReader {
+            reader,
+            buf: Vec::new(),
+            filled: 0,
+        }
+    }
+
+    /// Returns the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Returns the length of the packet at the start of the buffer, if its header has been read
+    fn packet_len(&self) -> io::Result<Option<usize>> {
+        match parse_vrt_header(&self.buf[..self.filled]) {
+            Ok((_, header)) if header.packet_size == 0 => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "VRT packet_size of zero",
+            )),
+            Ok((_, header)) => Ok(Some(header.packet_size as usize * 4)),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> futures_core::Stream for AsyncPacketReader<R> {
+    type Item = io::Result<VrtPacketOwned>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            let len = match this.packet_len() {
+                Ok(len) => len,
+                Err(e) => return Poll::Ready(Some(Err(e))),
+            };
+            if let Some(len) = len.filter(|len| *len <= this.filled) {
+                let packet = parse_vrt_packet(&this.buf[..len])
+                    .map(|(_, packet)| VrtPacketOwned::from(&packet))
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid VRT packet"));
+                this.buf.copy_within(len..this.filled, 0);
+                this.filled -= len;
+                return Poll::Ready(Some(packet));
+            }
+
+            let wanted = len.unwrap_or(4).max(this.filled + READ_CHUNK);
+            if this.buf.len() < wanted {
+                this.buf.resize(wanted, 0);
+            }
+            let read = match Pin::new(&mut this.reader).poll_read(cx, &mut this.buf[this.filled..])
+            {
+                Poll::Ready(Ok(read)) => read,
+                Poll::Ready(Err(e)) => return Poll::Ready(Some(Err(e))),
+                Poll::Pending => return Poll::Pending,
+            };
+            if read == 0 {
+                if this.filled == 0 {
+                    return Poll::Ready(None);
+                }
+                this.filled = 0;
+                return Poll::Ready(Some(Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "end of stream within a VRT packet",
+                ))));
+            }
+            this.filled += read;
+        }
+    }
+}
+
+/// Asynchronous Packet Writer
+///
+/// A [`Sink`](futures_sink::Sink) encoding VRT packets back-to-back onto any [`AsyncWrite`]
+/// byte stream. Packets are buffered until the sink is flushed. Packets which cannot be encoded
+/// are rejected with an error of kind [`io::ErrorKind::InvalidInput`].
+#[derive(Debug)]
+pub struct AsyncPacketWriter<W> {
+    writer: W,
+    buf: Vec<u8>,
+    written: usize,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncPacketWriter<W> {
+    /// Creates a new AsyncPacketWriter writing to `writer`
+    pub fn new(writer: W) -> Self {
+        AsyncPacketWriter {
+            writer,
+            buf: Vec::new(),
+            written: 0,
+        }
+    }
+
+    /// Returns the underlying writer. Any packets not yet flushed are discarded.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: AsyncWrite + Unpin> futures_sink::Sink<VrtPacketOwned> for AsyncPacketWriter<W> {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, packet: VrtPacketOwned) -> io::Result<()> {
+        let this = self.get_mut();
+        let packet = packet.as_packet();
+        let start = this.buf.len();
+        this.buf.resize(start + packet.encoded_len(), 0);
+        encode_vrt_packet(&packet, &mut this.buf[start..]).map_err(|_| {
+            this.buf.truncate(start);
+            io::Error::new(io::ErrorKind::InvalidInput, "invalid VRT packet")
+        })?;
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        while this.written < this.buf.len() {
+            let n = match Pin::new(&mut this.writer).poll_write(cx, &this.buf[this.written..]) {
+                Poll::Ready(Ok(0)) => {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write VRT packet",
+                    )))
+                }
+                Poll::Ready(Ok(n)) => n,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            };
+            this.written += n;
+        }
+        this.buf.clear();
+        this.written = 0;
+        Pin::new(&mut this.writer).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.as_mut().poll_flush(cx) {
+            Poll::Ready(Ok(())) => Pin::new(&mut self.get_mut().writer).poll_close(cx),
+            other => other,
+        }
+    }
+}
+
+/// Asynchronous Datagram Receiver
+///
+/// A [`Stream`](futures_core::Stream) of the VRT packets received in datagrams, with the
+/// address of their source, like the Tokio `AsyncUdpReceiver` of the [`net`](crate::net)
+/// module but independent of the runtime. Datagrams are received by `recv`, which is given a
+/// buffer and returns a future resolving to the buffer, the length of the datagram received
+/// into it and its source, as written here with the `recv_from` method of an async
+/// UDP socket:
+///
+/// ```no_run
+/// # async fn example() -> std::io::Result<()> {
+/// use std::sync::Arc;
+/// use vrt::AsyncDatagramReceiver;
+///
+/// let socket = Arc::new(tokio::net::UdpSocket::bind("0.0.0.0:4991").await?);
+/// let receiver = AsyncDatagramReceiver::new(move |mut buf: Vec<u8>| {
+///     let socket = socket.clone();
+///     async move {
+///         let (len, source) = socket.recv_from(&mut buf).await?;
+///         Ok((buf, len, source))
+///     }
+/// });
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Each datagram must hold one or more complete packets; datagrams longer than the maximum
+/// datagram length, which defaults to [`DEFAULT_MAX_DATAGRAM_LEN`], or not consisting of valid
+/// packets are dropped and counted. Errors returned by `recv` are yielded as errors.
+pub struct AsyncDatagramReceiver<F, Fut> {
+    recv: F,
+    receiving: Option<Pin<Box<Fut>>>,
+    buf: Vec<u8>,
+    max_datagram_len: usize,
+    pending: VecDeque<(SocketAddr, VrtPacketOwned)>,
+    truncated: u64,
+    malformed: u64,
+}
+
+impl<F, Fut> AsyncDatagramReceiver<F, Fut>
+where
+    F: FnMut(Vec<u8>) -> Fut + Unpin,
+    Fut: Future<Output = io::Result<(Vec<u8>, usize, SocketAddr)>>,
+{
+    /// Creates a new AsyncDatagramReceiver receiving datagrams with `recv`
+    pub fn new(recv: F) -> Self {
+        AsyncDatagramReceiver {
+            recv,
+            receiving: None,
+            buf: Vec::new(),
+            max_datagram_len: DEFAULT_MAX_DATAGRAM_LEN,
+            pending: VecDeque::new(),
+            truncated: 0,
+            malformed: 0,
+        }
+    }
+
+    /// Sets the maximum datagram length; longer datagrams are truncated and dropped
+    pub fn with_max_datagram_len(mut self, len: usize) -> Self {
+        self.max_datagram_len = len;
+        self
+    }
+
+    /// Returns the number of truncated datagrams dropped
+    pub fn truncated(&self) -> u64 {
+        self.truncated
+    }
+
+    /// Returns the number of malformed datagrams dropped
+    pub fn malformed(&self) -> u64 {
+        self.malformed
+    }
+}
+
+impl<F, Fut> futures_core::Stream for AsyncDatagramReceiver<F, Fut>
+where
+    F: FnMut(Vec<u8>) -> Fut + Unpin,
+    Fut: Future<Output = io::Result<(Vec<u8>, usize, SocketAddr)>>,
+{
+    type Item = io::Result<(SocketAddr, VrtPacketOwned)>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(packet) = this.pending.pop_front() {
+                return Poll::Ready(Some(Ok(packet)));
+            }
+            let receiving = match &mut this.receiving {
+                Some(receiving) => receiving,
+                None => {
+                    // one spare byte detects datagrams longer than the maximum
+                    let mut buf = std::mem::take(&mut this.buf);
+                    buf.resize(this.max_datagram_len + 1, 0);
+                    this.receiving.insert(Box::pin((this.recv)(buf)))
+                }
+            };
+            let (buf, len, source) = match receiving.as_mut().poll(cx) {
+                Poll::Ready(Ok(received)) => received,
+                Poll::Ready(Err(e)) => {
+                    this.receiving = None;
+                    return Poll::Ready(Some(Err(e)));
+                }
+                Poll::Pending => return Poll::Pending,
+            };
+            this.receiving = None;
+            if len > this.max_datagram_len || len > buf.len() {
+                this.truncated += 1;
+            } else if let Some(packets) = parse_datagram(&buf[..len]) {
+                this.pending
+                    .extend(packets.into_iter().map(|p| (source, p)));
+            } else {
+                this.malformed += 1;
+            }
+            this.buf = buf;
+        }
+    }
+}
+
+impl<F, Fut> fmt::Debug for AsyncDatagramReceiver<F, Fut> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncDatagramReceiver")
+            .field("receiving", &self.receiving.is_some())
+            .field("max_datagram_len", &self.max_datagram_len)
+            .field("pending", &self.pending)
+            .field("truncated", &self.truncated)
+            .field("malformed", &self.malformed)
+            .finish()
+    }
+}
+
+/// Asynchronous Datagram Sender
+///
+/// A [`Sink`](futures_sink::Sink) encoding each VRT packet into a datagram, like the Tokio
+/// `AsyncUdpSender` of the [`net`](crate::net) module but independent of the runtime. Each
+/// datagram is sent by `send`, which is given the encoded packet and returns a future
+/// resolving to the buffer once it is sent, as written here with the `send_to` method of an
+/// async UDP socket:
+///
+/// ```no_run
+/// # async fn example() -> std::io::Result<()> {
+/// use std::sync::Arc;
+/// use vrt::AsyncDatagramSender;
+///
+/// let socket = Arc::new(tokio::net::UdpSocket::bind("0.0.0.0:0").await?);
+/// let sender = AsyncDatagramSender::new(move |buf: Vec<u8>| {
+///     let socket = socket.clone();
+///     async move {
+///         socket.send_to(&buf, "192.168.1.20:4991").await?;
+///         Ok(buf)
+///     }
+/// });
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Packets which cannot be encoded are rejected with an error of kind
+/// [`io::ErrorKind::InvalidInput`].
+pub struct AsyncDatagramSender<F, Fut> {
+    send: F,
+    sending: Option<Pin<Box<Fut>>>,
+    buf: Vec<u8>,
+    buffered: bool,
+}
+
+impl<F, Fut> AsyncDatagramSender<F, Fut>
+where
+    F: FnMut(Vec<u8>) -> Fut + Unpin,
+    Fut: Future<Output = io::Result<Vec<u8>>>,
+{
+    /// Creates a new AsyncDatagramSender sending datagrams with `send`
+    pub fn new(send: F) -> Self {
+        AsyncDatagramSender {
+            send,
+            sending: None,
+            buf: Vec::new(),
+            buffered: false,
+        }
+    }
+}
+
+impl<F, Fut> futures_sink::Sink<VrtPacketOwned> for AsyncDatagramSender<F, Fut>
+where
+    F: FnMut(Vec<u8>) -> Fut + Unpin,
+    Fut: Future<Output = io::Result<Vec<u8>>>,
+{
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, packet: VrtPacketOwned) -> io::Result<()> {
+        let this = self.get_mut();
+        let packet = packet.as_packet();
+        this.buf.resize(packet.encoded_len(), 0);
+        encode_vrt_packet(&packet, &mut this.buf)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid VRT packet"))?;
+        this.buffered = true;
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.buffered {
+            let buf = std::mem::take(&mut this.buf);
+            this.sending = Some(Box::pin((this.send)(buf)));
+            this.buffered = false;
+        }
+        if let Some(sending) = &mut this.sending {
+            match sending.as_mut().poll(cx) {
+                Poll::Ready(Ok(buf)) => this.buf = buf,
+                Poll::Ready(Err(e)) => {
+                    this.sending = None;
+                    return Poll::Ready(Err(e));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+            this.sending = None;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+impl<F, Fut> fmt::Debug for AsyncDatagramSender<F, Fut> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncDatagramSender")
+            .field("sending", &self.sending.is_some())
+            .field("buffered", &self.buffered)
+            .finish()
+    }
+}
@@ -0,0 +1,317 @@
+//! Argument parsing and packet input and output shared by the command line tools.
+//!
+//! Each tool uses only part of this module, so the items some tools leave unused allow dead
+//! code.
+
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::net::{TcpStream, UdpSocket};
+use std::process;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use vrt::net::{
+    parse_vrl_frame, read_raw_packet, TcpConnection, TcpFraming, UdpReceiver,
+    VRL_FRAME_ALIGNMENT_WORD,
+};
+use vrt::{PcapReader, Timestamp, Tsf, Tsi, VrtPacket, VrtPacketOwned};
+
+/// Command line arguments, consumed option by option
+pub struct Args {
+    usage: &'static str,
+    args: Vec<String>,
+}
+
+impl Args {
+    /// Collects the arguments of the process, printing `usage` and exiting on `-h`/`--help`
+    pub fn new(usage: &'static str) -> Self {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        if args.iter().any(|a| a == "-h" || a == "--help") {
+            print!("{}", usage);
+            process::exit(0);
+        }
+        Args { usage, args }
+    }
+
+    /// Removes the flag `name`, returning whether it was present
+    #[allow(dead_code)]
+    pub fn flag(&mut self, name: &str) -> bool {
+        let len = self.args.len();
+        self.args.retain(|a| a != name);
+        self.args.len() != len
+    }
+
+    /// Removes every occurrence of the option `name` and its value, parsing the values
+    #[allow(dead_code)]
+    pub fn values<T: FromStr>(&mut self, name: &str) -> Vec<T>
+    where
+        T::Err: Display,
+    {
+        let mut values = Vec::new();
+        while let Some(i) = self.args.iter().position(|a| a == name) {
+            if i + 1 == self.args.len() {
+                self.fail(format!("missing value for {}", name));
+            }
+            let value = self.args.remove(i + 1);
+            self.args.remove(i);
+            match value.parse() {
+                Ok(value) => values.push(value),
+                Err(e) => self.fail(format!("invalid value {:?} for {}: {}", value, name, e)),
+            }
+        }
+        values
+    }
+
+    /// Removes the option `name` and its value, parsing the value
+    #[allow(dead_code)]
+    pub fn value<T: FromStr>(&mut self, name: &str) -> Option<T>
+    where
+        T::Err: Display,
+    {
+        self.values(name).pop()
+    }
+
+    /// Returns the remaining positional arguments, failing if any options are left
+    pub fn finish(self) -> Vec<String> {
+        if let Some(option) = self.args.iter().find(|a| a.starts_with('-') && *a != "-") {
+            self.fail(format!("unknown option {}", option));
+        }
+        self.args
+    }
+
+    /// Prints `message` and the usage to standard error and exits
+    pub fn fail(&self, message: String) -> ! {
+        eprint!("error: {}\n\n{}", message, self.usage);
+        process::exit(2);
+    }
+}
+
+/// A 32-bit identifier given in decimal or, with a `0x` prefix, hexadecimal
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Id(pub u32);
+
+impl FromStr for Id {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex) => u32::from_str_radix(hex, 16).map(Id),
+            None => s.parse().map(Id),
+        }
+    }
+}
+
+/// A UTC time given as seconds since the UNIX epoch, with an optional fraction
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Time(pub Timestamp);
+
+impl FromStr for Time {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (secs, fraction) = s.split_once('.').unwrap_or((s, ""));
+        let integer = secs.parse().map_err(|e| format!("{}", e))?;
+        if fraction.len() > 12 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+            return Err("invalid fraction of a second".to_string());
+        }
+        let fractional = format!("{:0<12}", fraction).parse().unwrap_or(0);
+        Ok(Time(Timestamp {
+            tsi: Tsi::TSI_UTC,
+            integer,
+            tsf: Tsf::TSF_REAL_TIME,
+            fractional,
+        }))
+    }
+}
+
+/// A source of packets: a raw, VRL or pcap capture file, standard input or a UDP socket
+#[allow(dead_code)]
+pub enum Input {
+    /// Back-to-back packets, and the buffer they are read into
+    Raw(Box<dyn BufRead>, Vec<u8>),
+    /// Packets wrapped in VITA 49.1 VRL frames, and those left of the last frame read
+    Vrl(Box<dyn BufRead>, VecDeque<VrtPacketOwned>),
+    /// VRT-over-UDP packets in a pcap or pcapng capture
+    Pcap(PcapReader<Box<dyn BufRead>>),
+    /// Datagrams received on a UDP socket
+    Udp(UdpReceiver),
+}
+
+#[allow(dead_code)]
+impl Input {
+    /// Opens `spec`, which is `-` for standard input, `udp:ADDRESS:PORT` to receive on a UDP
+    /// socket, or the path of a file; pcap and pcapng captures and VRL framed streams are
+    /// recognized by their magic
+    pub fn open(spec: &str) -> io::Result<Input> {
+        if let Some(addr) = spec.strip_prefix("udp:") {
+            return Ok(Input::Udp(UdpReceiver::bind(addr)?));
+        }
+        let mut reader: Box<dyn BufRead> = match spec {
+            "-" => Box::new(BufReader::new(io::stdin())),
+            path => Box::new(BufReader::new(File::open(path)?)),
+        };
+        let magic = reader.fill_buf()?;
+        if magic.len() < 4 {
+            return Ok(Input::Raw(reader, Vec::new()));
+        }
+        let magic = [magic[0], magic[1], magic[2], magic[3]];
+        Ok(match u32::from_le_bytes(magic) {
+            0xa1b2_c3d4 | 0xa1b2_3c4d | 0xd4c3_b2a1 | 0x4d3c_b2a1 | 0x0a0d_0d0a => {
+                Input::Pcap(PcapReader::new(reader)?)
+            }
+            _ if u32::from_be_bytes(magic) == VRL_FRAME_ALIGNMENT_WORD => {
+                Input::Vrl(reader, VecDeque::new())
+            }
+            _ => Input::Raw(reader, Vec::new()),
+        })
+    }
+
+    /// Reads the next packet, or returns None at the end of the input
+    pub fn next_packet(&mut self) -> io::Result<Option<VrtPacketOwned>> {
+        Ok(self.next_packet_with_arrival()?.map(|(_, packet)| packet))
+    }
+
+    /// Reads the next packet together with its arrival time since the UNIX epoch: the capture
+    /// time in pcap captures, the receive time on a socket, and unknown in raw captures
+    pub fn next_packet_with_arrival(
+        &mut self,
+    ) -> io::Result<Option<(Option<Duration>, VrtPacketOwned)>> {
+        match self {
+            Input::Raw(reader, buf) => Ok(read_raw_packet(reader, buf)?.map(|p| (None, p))),
+            Input::Vrl(reader, pending) => {
+                while pending.is_empty() {
+                    match read_vrl_frame(reader)? {
+                        Some(packets) => pending.extend(packets),
+                        None => return Ok(None),
+                    }
+                }
+                Ok(pending.pop_front().map(|p| (None, p)))
+            }
+            Input::Pcap(reader) => Ok(reader.read_packet()?.map(|p| (Some(p.timestamp), p.packet))),
+            Input::Udp(receiver) => {
+                let (_, packet) = receiver.recv_packet()?;
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).ok();
+                Ok(Some((now, packet)))
+            }
+        }
+    }
+}
+
+impl Iterator for Input {
+    type Item = io::Result<VrtPacketOwned>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_packet().transpose()
+    }
+}
+
+/// A destination for packets: a raw capture file, standard output, a UDP socket or a TCP
+/// connection
+#[allow(dead_code)]
+pub enum Output {
+    /// Back-to-back packets
+    Raw(Box<dyn Write>),
+    /// One datagram per packet sent from a connected UDP socket
+    Udp(UdpSocket),
+    /// Packets sent on a TCP connection
+    Tcp(TcpConnection),
+}
+
+#[allow(dead_code)]
+impl Output {
+    /// Opens `spec`, which is `-` for standard output, `udp:ADDRESS:PORT` to send datagrams,
+    /// `tcp:ADDRESS:PORT` to connect and send back-to-back packets, or the path of a file to
+    /// create
+    pub fn open(spec: &str) -> io::Result<Output> {
+        if let Some(addr) = spec.strip_prefix("tcp:") {
+            let stream = TcpStream::connect(addr)?;
+            return Ok(Output::Tcp(TcpConnection::new(stream, TcpFraming::Raw)));
+        }
+        if let Some(addr) = spec.strip_prefix("udp:") {
+            let socket = UdpSocket::bind(match addr.starts_with('[') {
+                true => "[::]:0",
+                false => "0.0.0.0:0",
+            })?;
+            socket.connect(addr)?;
+            return Ok(Output::Udp(socket));
+        }
+        Ok(Output::Raw(match spec {
+            "-" => Box::new(BufWriter::new(io::stdout())),
+            path => Box::new(BufWriter::new(File::create(path)?)),
+        }))
+    }
+
+    /// Writes `packet`
+    pub fn write_packet(&mut self, packet: &VrtPacket<'_>) -> io::Result<()> {
+        let bytes = packet
+            .to_vec()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "cannot encode packet"))?;
+        match self {
+            Output::Raw(writer) => writer.write_all(&bytes),
+            Output::Udp(socket) => socket.send(&bytes).map(|_| ()),
+            Output::Tcp(connection) => connection.send(packet),
+        }
+    }
+
+    /// Flushes buffered packets
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Raw(writer) => writer.flush(),
+            Output::Udp(_) | Output::Tcp(_) => Ok(()),
+        }
+    }
+}
+
+/// Reads the packets of one VRL frame, whose CRC, if any, is not checked
+#[allow(dead_code)]
+fn read_vrl_frame<R: Read + ?Sized>(reader: &mut R) -> io::Result<Option<Vec<VrtPacketOwned>>> {
+    let mut buf = vec![0; 8];
+    match reader.read_exact(&mut buf) {
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        result => result?,
+    }
+    loop {
+        match parse_vrl_frame(&buf) {
+            Ok((_, mut stream)) => {
+                let packets: Result<Vec<_>, _> = stream.by_ref().collect();
+                return match packets {
+                    Ok(packets) if stream.remainder().is_empty() => {
+                        Ok(Some(packets.iter().map(VrtPacketOwned::from).collect()))
+                    }
+                    _ => Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "invalid VRT packet in VRL frame",
+                    )),
+                };
+            }
+            Err(vrt::Error::Incomplete(Some(needed))) => {
+                let len = buf.len();
+                buf.resize(len + needed, 0);
+                reader.read_exact(&mut buf[len..])?;
+            }
+            Err(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "invalid VRL frame",
+                ))
+            }
+        }
+    }
+}
+
+/// Runs `main`, printing its error and exiting with a failure status if it fails
+///
+/// A closed standard output, as when piping into `head`, ends the tool quietly.
+pub fn run(name: &str, main: impl FnOnce() -> io::Result<()>) {
+    match main() {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(e) => {
+            eprintln!("{}: {}", name, e);
+            process::exit(1);
+        }
+    }
+}
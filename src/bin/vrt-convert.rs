@@ -0,0 +1,316 @@
+//! Converts packets between capture formats, framings and payload sample formats.
+
+mod common;
+
+use common::{Args, Input, Output};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::str::FromStr;
+use std::time::Duration;
+use vrt::net::{VRL_FRAME_ALIGNMENT_WORD, VRL_FRAME_END_WORD};
+use vrt::{
+    BlueExporter, ContextCsvWriter, PcapngWriter, SigmfExporter, Tsf, Tsi, VrtPacket,
+    VrtPacketOwned,
+};
+
+const USAGE: &str = "\
+Usage: vrt-convert [OPTIONS] INPUT OUTPUT
+
+Converts the VRT packets of INPUT to another capture format or framing, optionally
+converting the samples of data packet payloads to another sample format. INPUT is a
+raw, VRL framed or pcap capture file, - for standard input, or udp:ADDRESS:PORT to
+receive datagrams. OUTPUT is the file to create, or - for standard output; raw output
+may also be sent as datagrams to udp:ADDRESS:PORT or on a TCP connection to
+tcp:ADDRESS:PORT, and sigmf output writes the OUTPUT.sigmf-data and OUTPUT.sigmf-meta
+files of a recording.
+
+Options:
+  --to FORMAT         Output format: raw (the default), vrl, pcapng, sigmf, blue or csv
+  --payload FROM:TO   Convert data payload samples from sample format FROM to TO
+  --sample-rate HZ    Sample rate of sigmf output, which requires it, and blue output
+  --real              Samples are real rather than complex, for sigmf and blue output
+  --fields LIST       Comma-separated context fields of csv output (default
+                      rf_reference_frequency,gain,sample_rate,temperature)
+  -h, --help          Print this help
+
+Sample formats are i8, i16, i32 and f32, big-endian unless suffixed with le, as in
+i16le. Integer samples are scaled to and from floating point samples in [-1, 1).
+SigMF and BLUE output record samples in the format TO, or i16 without --payload;
+BLUE output requires a big-endian format. CSV output writes a row of the time, Stream
+Identifier and selected fields of each context packet carrying any of the fields, named
+as in the context object of vrt-dump --json output.
+";
+
+/// Format of a sample component
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct SampleFormat {
+    size: usize,
+    float: bool,
+    little_endian: bool,
+}
+
+impl FromStr for SampleFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, little_endian) = match s.strip_suffix("le") {
+            Some(name) => (name, true),
+            None => (s, false),
+        };
+        let (size, float) = match name {
+            "i8" => (1, false),
+            "i16" => (2, false),
+            "i32" => (4, false),
+            "f32" => (4, true),
+            _ => return Err("expected i8, i16, i32 or f32".to_string()),
+        };
+        Ok(SampleFormat {
+            size,
+            float,
+            little_endian,
+        })
+    }
+}
+
+impl SampleFormat {
+    /// Decodes one component, scaling integers to [-1, 1)
+    fn decode(&self, bytes: &[u8]) -> f64 {
+        let mut be = [0; 4];
+        be[..self.size].copy_from_slice(bytes);
+        if self.little_endian {
+            be[..self.size].reverse();
+        }
+        let full_scale = (1u64 << (self.size * 8 - 1)) as f64;
+        match (self.float, self.size) {
+            (true, _) => f32::from_be_bytes(be) as f64,
+            (false, 1) => be[0] as i8 as f64 / full_scale,
+            (false, 2) => i16::from_be_bytes([be[0], be[1]]) as f64 / full_scale,
+            _ => i32::from_be_bytes(be) as f64 / full_scale,
+        }
+    }
+
+    /// Encodes one component, scaling from [-1, 1) and saturating integers
+    fn encode(&self, value: f64, out: &mut Vec<u8>) {
+        let be = match self.float {
+            true => (value as f32).to_be_bytes(),
+            false => {
+                let full_scale = (1u64 << (self.size * 8 - 1)) as f64;
+                let value = (value * full_scale)
+                    .round()
+                    .clamp(-full_scale, full_scale - 1.0);
+                (value as i32).to_be_bytes()
+            }
+        };
+        let start = out.len();
+        out.extend_from_slice(&be[be.len() - self.size..]);
+        if self.little_endian {
+            out[start..].reverse();
+        }
+    }
+
+    /// Returns the SigMF `core:datatype` of samples with components of this format
+    fn sigmf_datatype(&self, complex: bool) -> String {
+        let kind = if complex { 'c' } else { 'r' };
+        let name = match (self.float, self.size) {
+            (true, _) => "f32",
+            (false, 1) => "i8",
+            (false, 2) => "i16",
+            _ => "i32",
+        };
+        match (self.size, self.little_endian) {
+            (1, _) => format!("{}{}", kind, name),
+            (_, true) => format!("{}{}_le", kind, name),
+            (_, false) => format!("{}{}_be", kind, name),
+        }
+    }
+
+    /// Returns the BLUE format of samples with components of this format
+    fn blue_format(&self, complex: bool) -> String {
+        let mode = if complex { 'C' } else { 'S' };
+        let kind = match (self.float, self.size) {
+            (true, _) => 'F',
+            (false, 1) => 'B',
+            (false, 2) => 'I',
+            _ => 'L',
+        };
+        format!("{}{}", mode, kind)
+    }
+}
+
+/// Format of the samples written to sigmf and blue output without a payload conversion
+const I16: SampleFormat = SampleFormat {
+    size: 2,
+    float: false,
+    little_endian: false,
+};
+
+/// Conversion of data payload samples from one format to another
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Conversion {
+    from: SampleFormat,
+    to: SampleFormat,
+}
+
+impl FromStr for Conversion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (from, to) = s.split_once(':').ok_or("expected FROM:TO")?;
+        Ok(Conversion {
+            from: from.parse()?,
+            to: to.parse()?,
+        })
+    }
+}
+
+impl Conversion {
+    /// Converts the payload of a data packet, dropping any trailing partial sample
+    fn apply(&self, packet: &mut VrtPacketOwned) {
+        if !packet.header.packet_type.is_data() {
+            return;
+        }
+        let components = packet.data_payload.len() / self.from.size;
+        let mut payload = Vec::with_capacity(components * self.to.size);
+        for bytes in packet.data_payload.chunks_exact(self.from.size) {
+            self.to.encode(self.from.decode(bytes), &mut payload);
+        }
+        packet.data_payload = payload;
+    }
+}
+
+/// Destination of the converted packets
+enum Sink {
+    Raw(Output),
+    Vrl(Box<dyn Write>, u32),
+    Pcapng(PcapngWriter<BufWriter<File>>),
+    Sigmf(SigmfExporter<BufWriter<File>>, String),
+    Blue(BlueExporter<BufWriter<File>>),
+    Csv(ContextCsvWriter<Box<dyn Write>>),
+}
+
+impl Sink {
+    fn write(&mut self, arrival: Option<Duration>, packet: &VrtPacket<'_>) -> io::Result<()> {
+        match self {
+            Sink::Raw(output) => output.write_packet(packet),
+            Sink::Vrl(writer, frame_count) => {
+                let bytes = packet.to_vec().map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "cannot encode packet")
+                })?;
+                let words = (bytes.len() / 4 + 3) as u32;
+                writer.write_all(&VRL_FRAME_ALIGNMENT_WORD.to_be_bytes())?;
+                writer.write_all(&(*frame_count << 20 | words).to_be_bytes())?;
+                writer.write_all(&bytes)?;
+                writer.write_all(&VRL_FRAME_END_WORD.to_be_bytes())?;
+                *frame_count = (*frame_count + 1) & 0xfff;
+                Ok(())
+            }
+            Sink::Pcapng(writer) => {
+                let timestamp = arrival.unwrap_or_else(|| packet_time(packet));
+                writer.write_packet(timestamp, packet)
+            }
+            Sink::Sigmf(exporter, _) => exporter.push(packet),
+            Sink::Blue(exporter) => exporter.push(packet),
+            Sink::Csv(writer) => writer.write_packet(packet).map(|_| ()),
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self {
+            Sink::Raw(mut output) => output.flush(),
+            Sink::Vrl(mut writer, _) => writer.flush(),
+            Sink::Pcapng(writer) => writer.into_inner().map(|_| ()),
+            Sink::Sigmf(exporter, meta) => {
+                exporter.finish(BufWriter::new(File::create(meta)?))?;
+                Ok(())
+            }
+            Sink::Blue(exporter) => exporter.finish().map(|_| ()),
+            Sink::Csv(writer) => writer.into_inner().map(|_| ()),
+        }
+    }
+}
+
+/// Returns the UTC real-time timestamp of `packet` since the UNIX epoch, or zero
+fn packet_time(packet: &VrtPacket<'_>) -> Duration {
+    match (packet.header.tsi, packet.tsi, packet.header.tsf, packet.tsf) {
+        (Tsi::TSI_UTC, Some(tsi), Tsf::TSF_REAL_TIME, Some(tsf)) => {
+            Duration::new(tsi as u64, (tsf / 1000).min(999_999_999) as u32)
+        }
+        (Tsi::TSI_UTC, Some(tsi), _, _) => Duration::from_secs(tsi as u64),
+        _ => Duration::ZERO,
+    }
+}
+
+fn main() {
+    let mut args = Args::new(USAGE);
+    let to: Option<String> = args.value("--to");
+    let conversion: Option<Conversion> = args.value("--payload");
+    let sample_rate: Option<f64> = args.value("--sample-rate");
+    let real = args.flag("--real");
+    let fields: Option<String> = args.value("--fields");
+    let to = to.unwrap_or_else(|| "raw".to_string());
+    let format = conversion.map_or(I16, |c| c.to);
+    match to.as_str() {
+        "raw" | "vrl" | "pcapng" | "csv" => {}
+        "sigmf" if sample_rate.is_none() => args.fail("sigmf output requires --sample-rate".into()),
+        "blue" if format.little_endian => {
+            args.fail("blue output requires a big-endian sample format".into())
+        }
+        "sigmf" | "blue" => {}
+        _ => args.fail(format!("unknown output format {:?}", to)),
+    }
+    let paths = args.finish();
+    let [input, output] = paths.as_slice() else {
+        eprint!("error: expected INPUT and OUTPUT\n\n{}", USAGE);
+        std::process::exit(2);
+    };
+
+    common::run("vrt-convert", || {
+        let mut input = Input::open(input)?;
+        let mut sink = match to.as_str() {
+            "raw" => Sink::Raw(Output::open(output)?),
+            "vrl" => match output.as_str() {
+                "-" => Sink::Vrl(Box::new(BufWriter::new(io::stdout())), 0),
+                path => Sink::Vrl(Box::new(BufWriter::new(File::create(path)?)), 0),
+            },
+            "pcapng" => Sink::Pcapng(PcapngWriter::create(output)?),
+            "csv" => {
+                let writer: Box<dyn Write> = match output.as_str() {
+                    "-" => Box::new(BufWriter::new(io::stdout())),
+                    path => Box::new(BufWriter::new(File::create(path)?)),
+                };
+                let writer = ContextCsvWriter::new(writer);
+                Sink::Csv(match &fields {
+                    Some(fields) => writer.with_columns(&fields.split(',').collect::<Vec<_>>())?,
+                    None => writer,
+                })
+            }
+            "sigmf" => {
+                let base = output
+                    .strip_suffix(".sigmf-data")
+                    .or_else(|| output.strip_suffix(".sigmf-meta"))
+                    .unwrap_or(output);
+                let exporter = SigmfExporter::create(
+                    format!("{}.sigmf-data", base),
+                    &format.sigmf_datatype(!real),
+                    format.size * if real { 1 } else { 2 },
+                    sample_rate.unwrap_or_default(),
+                )?;
+                Sink::Sigmf(exporter, format!("{}.sigmf-meta", base))
+            }
+            _ => {
+                let exporter = BlueExporter::create(output, &format.blue_format(!real))?;
+                Sink::Blue(match sample_rate {
+                    Some(sample_rate) => exporter.with_sample_rate(sample_rate),
+                    None => exporter,
+                })
+            }
+        };
+        while let Some((arrival, mut packet)) = input.next_packet_with_arrival()? {
+            if let Some(conversion) = conversion {
+                conversion.apply(&mut packet);
+            }
+            sink.write(arrival, &packet.as_packet())?;
+        }
+        sink.finish()
+    });
+}
@@ -0,0 +1,188 @@
+//! Prints the decoded fields of each packet read from a capture, standard input or a UDP
+//! socket.
+
+mod common;
+
+use common::{Args, Id, Input};
+use std::io::{self, Write};
+use vrt::{packet_json, Filter, Trailer, Tsf, VrtPacket};
+
+const USAGE: &str = "\
+Usage: vrt-dump [OPTIONS] [INPUT]
+
+Prints the decoded fields of each VRT packet in INPUT, which is a raw, VRL framed or
+pcap capture file, - for standard input (the default), or udp:ADDRESS:PORT to receive
+datagrams.
+
+Options:
+  --hexdump BYTES  Dump up to BYTES bytes of each payload
+  --json           Print one JSON object per packet instead, with decoded context fields
+  --payload        Include the payload of each packet in base64 with --json
+  --stream ID      Only print packets of stream ID, decimal or 0x-prefixed hex; repeatable
+  --count N        Stop after printing N packets
+  -h, --help       Print this help
+";
+
+fn trailer_fields(trailer: &Trailer) -> Vec<String> {
+    let indicators = [
+        (
+            "calibrated time",
+            trailer.calibrated_time_enable,
+            trailer.calibrated_time_indicator,
+        ),
+        (
+            "valid data",
+            trailer.valid_data_enable,
+            trailer.valid_data_indicator,
+        ),
+        (
+            "reference lock",
+            trailer.reference_lock_enable,
+            trailer.reference_lock_indicator,
+        ),
+        ("AGC", trailer.agcmgc_enable, trailer.agcmgc_indicator),
+        (
+            "detected signal",
+            trailer.detected_signal_enable,
+            trailer.detected_signal_indicator,
+        ),
+        (
+            "spectral inversion",
+            trailer.spectral_inversion_enable,
+            trailer.spectral_inversion_indicator,
+        ),
+        (
+            "over-range",
+            trailer.overrange_enable,
+            trailer.overrange_indicator,
+        ),
+        (
+            "sample loss",
+            trailer.sample_loss_enable,
+            trailer.sample_loss_indicator,
+        ),
+        (
+            "user 1",
+            trailer.user_defined_enable_1,
+            trailer.user_defined_indicator_1,
+        ),
+        (
+            "user 2",
+            trailer.user_defined_enable_2,
+            trailer.user_defined_indicator_2,
+        ),
+        (
+            "user 3",
+            trailer.user_defined_enable_3,
+            trailer.user_defined_indicator_3,
+        ),
+        (
+            "user 4",
+            trailer.user_defined_enable_4,
+            trailer.user_defined_indicator_4,
+        ),
+    ];
+    let mut fields: Vec<String> = indicators
+        .iter()
+        .filter(|(_, enabled, _)| *enabled)
+        .map(|(name, _, value)| format!("{}={}", name, *value as u8))
+        .collect();
+    if trailer.associated_context_packet_count_enable {
+        fields.push(format!(
+            "context packets={}",
+            trailer.associated_context_packet_count
+        ));
+    }
+    fields
+}
+
+fn hexdump(out: &mut impl Write, bytes: &[u8]) -> io::Result<()> {
+    for (n, line) in bytes.chunks(16).enumerate() {
+        let hex: Vec<String> = line.iter().map(|b| format!("{:02x}", b)).collect();
+        let ascii: String = line
+            .iter()
+            .map(|&b| match b {
+                0x20..=0x7e => b as char,
+                _ => '.',
+            })
+            .collect();
+        writeln!(out, "    {:04x}  {:<47}  {}", n * 16, hex.join(" "), ascii)?;
+    }
+    Ok(())
+}
+
+fn dump(
+    out: &mut impl Write,
+    n: u64,
+    packet: &VrtPacket<'_>,
+    hexdump_len: usize,
+) -> io::Result<()> {
+    let header = &packet.header;
+    writeln!(out, "#{} {}", n, packet)?;
+    writeln!(
+        out,
+        "    header: type {}, C {}, T {}, TSI {}, TSF {}, count {}, size {}",
+        header.packet_type.0,
+        header.c as u8,
+        header.t as u8,
+        header.tsi.0,
+        header.tsf.0,
+        header.packet_count,
+        header.packet_size
+    )?;
+    if let Some(class_id) = packet.class_id {
+        writeln!(
+            out,
+            "    class id: OUI 0x{:06x}, information class 0x{:04x}, packet class 0x{:04x}",
+            (class_id >> 32) & 0xff_ffff,
+            (class_id >> 16) & 0xffff,
+            class_id & 0xffff
+        )?;
+    }
+    if let (Some(tsi), Tsf::TSF_REAL_TIME, Some(tsf)) = (packet.tsi, header.tsf, packet.tsf) {
+        writeln!(out, "    time: {}.{:012} s", tsi, tsf)?;
+    }
+    if let Some(trailer) = &packet.trailer {
+        writeln!(out, "    trailer: {}", trailer_fields(trailer).join(", "))?;
+    }
+    let len = packet.data_payload.len().min(hexdump_len);
+    hexdump(out, &packet.data_payload[..len])
+}
+
+fn main() {
+    let mut args = Args::new(USAGE);
+    let hexdump_len = args.value("--hexdump").unwrap_or(0);
+    let streams: Vec<Id> = args.values("--stream");
+    let count: Option<u64> = args.value("--count");
+    let json = args.flag("--json");
+    let payload = args.flag("--payload");
+    let inputs = args.finish();
+    let filter = match streams.is_empty() {
+        true => Filter::Any,
+        false => Filter::StreamIds(streams.iter().map(|id| id.0).collect()),
+    };
+
+    common::run("vrt-dump", || {
+        let mut input = Input::open(inputs.first().map_or("-", |s| s.as_str()))?;
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        let mut printed = 0;
+        let mut index = 0;
+        while count != Some(printed) {
+            let packet = match input.next_packet()? {
+                Some(packet) => packet,
+                None => break,
+            };
+            let packet = packet.as_packet();
+            if filter.matches(&packet) {
+                match json {
+                    true => writeln!(out, "{}", packet_json(&packet, payload))?,
+                    false => dump(&mut out, index, &packet, hexdump_len)?,
+                }
+                printed += 1;
+            }
+            index += 1;
+        }
+        out.flush()
+    });
+}
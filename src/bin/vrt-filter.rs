@@ -0,0 +1,100 @@
+//! Copies the packets matching a filter expression from one capture, stream or socket to
+//! another.
+
+mod common;
+
+use common::{Args, Id, Input, Output, Time};
+use vrt::{Filter, Timestamp, Tsf, Tsi, VitaPacketType};
+
+const USAGE: &str = "\
+Usage: vrt-filter [OPTIONS] [INPUT]
+
+Writes the VRT packets of INPUT which match all the given conditions to the output.
+INPUT is a raw or pcap capture file, - for standard input (the default), or
+udp:ADDRESS:PORT to receive datagrams.
+
+Options:
+  -o, --output OUTPUT  Raw capture file to create, - for standard output (the default),
+                       udp:ADDRESS:PORT to send datagrams, or tcp:ADDRESS:PORT to send
+                       on a TCP connection
+  --stream ID          Match packets of stream ID, decimal or 0x-prefixed hex; repeatable
+  --type TYPE          Match packets of packet type TYPE (0-15); repeatable
+  --class-id ID        Match packets with the 64-bit hexadecimal Class Identifier ID
+  --start TIME         Match packets with a timestamp at or after TIME, in seconds
+  --end TIME           Match packets with a timestamp before TIME, in seconds
+  --invert             Write the packets which do not match instead
+  -h, --help           Print this help
+";
+
+fn any_of(filters: impl IntoIterator<Item = Filter>) -> Filter {
+    filters
+        .into_iter()
+        .reduce(Filter::or)
+        .unwrap_or(Filter::Any)
+}
+
+fn main() {
+    let mut args = Args::new(USAGE);
+    let mut outputs: Vec<String> = args.values("-o");
+    outputs.extend(args.values::<String>("--output"));
+    let streams: Vec<Id> = args.values("--stream");
+    let types: Vec<u8> = args.values("--type");
+    let class_id: Option<String> = args.value("--class-id");
+    let start: Option<Time> = args.value("--start");
+    let end: Option<Time> = args.value("--end");
+    let invert = args.flag("--invert");
+    let class_id = class_id.map(|id| {
+        let hex = id.trim_start_matches("0x");
+        u64::from_str_radix(hex, 16)
+            .unwrap_or_else(|e| args.fail(format!("invalid value {:?} for --class-id: {}", id, e)))
+    });
+    let inputs = args.finish();
+
+    let mut filter = Filter::Any;
+    if !streams.is_empty() {
+        filter = filter.and(Filter::StreamIds(streams.iter().map(|id| id.0).collect()));
+    }
+    if !types.is_empty() {
+        filter = filter.and(any_of(
+            types.iter().map(|t| Filter::PacketType(VitaPacketType(*t))),
+        ));
+    }
+    if let Some(class_id) = class_id {
+        filter = filter.and(Filter::ClassId(class_id));
+    }
+    if start.is_some() || end.is_some() {
+        let end = end.map_or(
+            Timestamp {
+                tsi: Tsi::TSI_UTC,
+                integer: u32::MAX,
+                tsf: Tsf::TSF_REAL_TIME,
+                fractional: u64::MAX,
+            },
+            |t| t.0,
+        );
+        let start = start.map_or(
+            Timestamp {
+                fractional: 0,
+                integer: 0,
+                ..end
+            },
+            |t| t.0,
+        );
+        filter = filter.and(Filter::TimeRange { start, end });
+    }
+    if invert {
+        filter = !filter;
+    }
+
+    common::run("vrt-filter", || {
+        let mut input = Input::open(inputs.first().map_or("-", |s| s.as_str()))?;
+        let mut output = Output::open(outputs.last().map_or("-", |s| s.as_str()))?;
+        while let Some(packet) = input.next_packet()? {
+            let packet = packet.as_packet();
+            if filter.matches(&packet) {
+                output.write_packet(&packet)?;
+            }
+        }
+        output.flush()
+    });
+}
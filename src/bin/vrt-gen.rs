@@ -0,0 +1,169 @@
+//! Generates synthetic packet streams for load-testing receivers.
+
+mod common;
+
+use common::{Args, Id, Output};
+use std::str::FromStr;
+use vrt::{Generator, Pacer, VitaPacketType, Waveform};
+
+const USAGE: &str = "\
+Usage: vrt-gen [OPTIONS]
+
+Generates compliant streams of IF data packets with UTC real-time timestamps, optional
+context packets carrying the sample rate, and optional impairments, interleaving the
+streams packet by packet.
+
+Options:
+  -o, --output OUTPUT   Raw capture file to create, - for standard output (the default),
+                        udp:ADDRESS:PORT to send datagrams, or tcp:ADDRESS:PORT to send
+                        on a TCP connection
+  --stream ID           Generate a stream of IF data and IF context packets with Stream
+                        Identifier ID, decimal or 0x-prefixed hex; repeatable (default 1)
+  --ext-stream ID       Generate a stream of extension data and extension context packets
+                        with Stream Identifier ID; repeatable
+  --sample-rate HZ      Sample rate of each stream (default 1e6)
+  --samples N           Complex 16-bit samples per data packet (default 256)
+  --waveform WAVEFORM   tone:FREQUENCY[:AMPLITUDE], noise[:AMPLITUDE] or ramp
+                        (default tone:1000:0.5)
+  --context N           Send a context packet every N data packets of a stream
+  --start SECS          Integer-seconds timestamp of the first packets (default 0)
+  --count N             Stop after N data packets per stream (default unlimited)
+  --realtime            Pace the packets at the rate implied by the sample rate
+  --drop-every N        Drop every Nth data packet of each stream
+  --duplicate-every N   Send every Nth data packet of each stream twice
+  -h, --help            Print this help
+";
+
+/// A Waveform given on the command line
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct WaveformArg(Waveform);
+
+impl FromStr for WaveformArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let kind = parts.next().unwrap_or_default();
+        let values = parts
+            .map(|value| value.parse::<f64>().map_err(|e| e.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let waveform = match (kind, values.as_slice()) {
+            ("tone", [frequency]) => Waveform::Tone {
+                frequency: *frequency,
+                amplitude: 0.5,
+            },
+            ("tone", [frequency, amplitude]) => Waveform::Tone {
+                frequency: *frequency,
+                amplitude: *amplitude,
+            },
+            ("noise", []) => Waveform::Noise {
+                amplitude: 0.5,
+                seed: 0,
+            },
+            ("noise", [amplitude]) => Waveform::Noise {
+                amplitude: *amplitude,
+                seed: 0,
+            },
+            ("ramp", []) => Waveform::Ramp,
+            _ => {
+                return Err("expected tone:FREQUENCY[:AMPLITUDE], noise[:AMPLITUDE] or ramp".into())
+            }
+        };
+        Ok(WaveformArg(waveform))
+    }
+}
+
+fn main() {
+    let mut args = Args::new(USAGE);
+    let mut outputs: Vec<String> = args.values("-o");
+    outputs.extend(args.values::<String>("--output"));
+    let mut streams: Vec<(u32, bool)> = args
+        .values::<Id>("--stream")
+        .into_iter()
+        .map(|id| (id.0, false))
+        .collect();
+    streams.extend(
+        args.values::<Id>("--ext-stream")
+            .into_iter()
+            .map(|id| (id.0, true)),
+    );
+    let sample_rate: f64 = args.value("--sample-rate").unwrap_or(1e6);
+    let samples: usize = args.value("--samples").unwrap_or(256);
+    let waveform = args.value::<WaveformArg>("--waveform").map_or(
+        Waveform::Tone {
+            frequency: 1000.0,
+            amplitude: 0.5,
+        },
+        |w| w.0,
+    );
+    let context: Option<u64> = args.value("--context");
+    let start: u32 = args.value("--start").unwrap_or(0);
+    let count: Option<u64> = args.value("--count");
+    let realtime = args.flag("--realtime");
+    let drop_every: u64 = args.value("--drop-every").unwrap_or(0);
+    let duplicate_every: u64 = args.value("--duplicate-every").unwrap_or(0);
+    if !(sample_rate.is_finite() && sample_rate > 0.0) {
+        args.fail(format!("invalid value {} for --sample-rate", sample_rate));
+    }
+    if samples == 0 || samples > u16::MAX as usize - 5 {
+        args.fail(format!("invalid value {} for --samples", samples));
+    }
+    if let Some(path) = args.finish().first() {
+        eprint!("error: unexpected argument {}\n\n{}", path, USAGE);
+        std::process::exit(2);
+    }
+    if streams.is_empty() {
+        streams.push((1, false));
+    }
+
+    let mut generators: Vec<_> = streams
+        .iter()
+        .map(|&(stream_id, extension)| {
+            let mut generator = Generator::new(sample_rate, samples, waveform)
+                .with_stream_id(stream_id)
+                .with_start_time(start);
+            if let Some(interval) = context {
+                generator = generator.with_context_interval(interval);
+            }
+            (generator, extension)
+        })
+        .collect();
+    let mut pacer = realtime.then(|| {
+        let interval = generators[0].0.pacer().interval() / generators.len() as u32;
+        Pacer::with_interval(interval)
+    });
+
+    common::run("vrt-gen", || {
+        let mut output = Output::open(outputs.last().map_or("-", |s| s.as_str()))?;
+        let mut n = 0;
+        while count != Some(n) {
+            n += 1;
+            for (generator, extension) in &mut generators {
+                if let Some(pacer) = &mut pacer {
+                    pacer.wait();
+                }
+                for mut packet in generator.by_ref() {
+                    let is_data = packet.header.packet_type.is_data();
+                    if *extension {
+                        packet.header.packet_type = VitaPacketType(match is_data {
+                            true => VitaPacketType::EXTDATAWITHSTREAM,
+                            false => VitaPacketType::EXTCONTEXT,
+                        });
+                    }
+                    if !is_data {
+                        output.write_packet(&packet.as_packet())?;
+                        continue;
+                    }
+                    if n.checked_rem(drop_every) != Some(0) {
+                        output.write_packet(&packet.as_packet())?;
+                        if n.checked_rem(duplicate_every) == Some(0) {
+                            output.write_packet(&packet.as_packet())?;
+                        }
+                    }
+                    break;
+                }
+            }
+        }
+        output.flush()
+    });
+}
@@ -0,0 +1,39 @@
+//! Shows a live terminal view of the VRT packet streams received on a UDP socket.
+
+mod common;
+
+use common::Args;
+use std::time::Duration;
+use vrt::net::AsyncUdpReceiver;
+
+const USAGE: &str = "\
+Usage: vrt-monitor [OPTIONS] ADDRESS:PORT
+
+Receives VRT packets as UDP datagrams on ADDRESS:PORT and shows a live view of each
+stream's packet rate, packet count, loss and latest context field values, above a
+scrolling log of the packets received. Press q or Esc to quit.
+
+Options:
+  --refresh MS        Refresh the view every MS milliseconds (default 500)
+  -h, --help          Print this help
+";
+
+fn main() {
+    let mut args = Args::new(USAGE);
+    let refresh: u64 = args.value("--refresh").unwrap_or(500);
+    let addresses = args.finish();
+    let [address] = addresses.as_slice() else {
+        eprint!("error: expected ADDRESS:PORT\n\n{}", USAGE);
+        std::process::exit(2);
+    };
+
+    common::run("vrt-monitor", || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        runtime.block_on(async {
+            let receiver = AsyncUdpReceiver::bind(address.as_str()).await?;
+            vrt::run_monitor(receiver, Duration::from_millis(refresh.max(1))).await
+        })
+    });
+}
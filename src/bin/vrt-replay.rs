@@ -0,0 +1,120 @@
+//! Transmits the packets of a capture with their original or scaled timing.
+
+mod common;
+
+use common::{Args, Id, Input, Output};
+use std::collections::HashMap;
+use std::net::TcpStream;
+use std::str::FromStr;
+use std::time::Duration;
+use vrt::net::{TcpConnection, TcpFraming};
+use vrt::{ReplaySpeed, Replayer, Timestamp, VrtPacketOwned};
+
+const USAGE: &str = "\
+Usage: vrt-replay [OPTIONS] INPUT DESTINATION
+
+Sends the VRT packets of INPUT to DESTINATION, spaced by the differences between their
+capture times in pcap captures or between their timestamps otherwise. INPUT is a raw,
+VRL framed or pcap capture file, or - for standard input. DESTINATION is
+udp:ADDRESS:PORT to send datagrams, tcp:ADDRESS:PORT to send on a TCP connection,
+- for standard output or the path of a raw capture file to create.
+
+Options:
+  --speed FACTOR    Scale the original timing: 2 replays twice as fast, 0 without any
+                    delay (default 1)
+  --timestamps      Time packets by their timestamps even in pcap captures
+  --loop            Replay the capture repeatedly until interrupted
+  --repeat N        Replay the capture N times (default 1)
+  --remap FROM=TO   Replace the Stream Identifier FROM by TO, decimal or 0x-prefixed
+                    hex; repeatable
+  --vrl             Wrap packets sent on a TCP connection in VITA 49.1 VRL frames
+  -h, --help        Print this help
+";
+
+/// Replacement of one Stream Identifier by another
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Remap(u32, u32);
+
+impl FromStr for Remap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (from, to) = s.split_once('=').ok_or("expected FROM=TO")?;
+        let parse = |id: &str| id.parse::<Id>().map(|id| id.0).map_err(|e| e.to_string());
+        Ok(Remap(parse(from)?, parse(to)?))
+    }
+}
+
+type Item<'a> = &'a (Option<Duration>, VrtPacketOwned);
+
+fn main() {
+    let mut args = Args::new(USAGE);
+    let speed: f64 = args.value("--speed").unwrap_or(1.0);
+    let timestamps = args.flag("--timestamps");
+    let forever = args.flag("--loop");
+    let repeat: u64 = args.value("--repeat").unwrap_or(1);
+    let remaps: HashMap<u32, u32> = args
+        .values::<Remap>("--remap")
+        .into_iter()
+        .map(|Remap(from, to)| (from, to))
+        .collect();
+    let vrl = args.flag("--vrl");
+    if speed < 0.0 || speed.is_nan() {
+        args.fail(format!("invalid value {} for --speed", speed));
+    }
+    let paths = args.finish();
+    let [input, destination] = paths.as_slice() else {
+        eprint!("error: expected INPUT and DESTINATION\n\n{}", USAGE);
+        std::process::exit(2);
+    };
+    if vrl && !destination.starts_with("tcp:") {
+        eprint!(
+            "error: --vrl requires a tcp:ADDRESS:PORT destination\n\n{}",
+            USAGE
+        );
+        std::process::exit(2);
+    }
+    let speed = match speed > 0.0 {
+        true => ReplaySpeed::Scaled(speed),
+        false => ReplaySpeed::AsFastAsPossible,
+    };
+
+    common::run("vrt-replay", || {
+        let mut capture = Vec::new();
+        let mut input = Input::open(input)?;
+        while let Some((arrival, mut packet)) = input.next_packet_with_arrival()? {
+            if let Some(to) = packet.stream_id.and_then(|id| remaps.get(&id)) {
+                packet.stream_id = Some(*to);
+            }
+            capture.push((arrival, packet));
+        }
+        let by_arrival = !timestamps && capture.iter().any(|(arrival, _)| arrival.is_some());
+        let key: fn(&Item<'_>) -> Option<Duration> = match by_arrival {
+            true => |(arrival, _)| *arrival,
+            false => |(_, packet)| {
+                packet
+                    .as_packet()
+                    .timestamp()
+                    .as_ref()
+                    .and_then(Timestamp::as_duration)
+            },
+        };
+
+        let mut output = match destination.strip_prefix("tcp:") {
+            Some(addr) if vrl => Output::Tcp(TcpConnection::new(
+                TcpStream::connect(addr)?,
+                TcpFraming::Vrl,
+            )),
+            _ => Output::open(destination)?,
+        };
+        let mut replayed = 0;
+        while forever || replayed < repeat {
+            for (_, packet) in Replayer::with_key(capture.iter(), speed, key) {
+                output.write_packet(&packet.as_packet())?;
+            }
+            output.flush()?;
+            replayed += 1;
+        }
+        Ok(())
+    });
+}
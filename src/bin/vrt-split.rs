@@ -0,0 +1,56 @@
+//! Splits a multi-stream capture into one indexed capture per stream.
+
+mod common;
+
+use common::{Args, Input};
+use std::io::{self, Write};
+use vrt::CaptureSplitter;
+
+const USAGE: &str = "\
+Usage: vrt-split [OPTIONS] INPUT OUTPUT
+
+Splits the VRT packets of INPUT into one indexed capture per Stream Identifier, named
+after OUTPUT with the Stream Identifier in hex before the extension: splitting into
+out.vrt writes out-00000001.vrt and its index out-00000001.vrt.idx for stream 1, and
+out-none.vrt for packets without a Stream Identifier. INPUT is a raw, VRL framed or
+pcap capture file, - for standard input, or udp:ADDRESS:PORT to receive datagrams.
+Prints the path and packet count of each capture written.
+
+Options:
+  --class-id          Also split by Class Identifier, as in
+                      out-00000001-00123456789abcde.vrt
+  -h, --help          Print this help
+";
+
+fn main() {
+    let mut args = Args::new(USAGE);
+    let by_class_id = args.flag("--class-id");
+    let paths = args.finish();
+    let [input, output] = paths.as_slice() else {
+        eprint!("error: expected INPUT and OUTPUT\n\n{}", USAGE);
+        std::process::exit(2);
+    };
+
+    common::run("vrt-split", || {
+        let mut input = Input::open(input)?;
+        let mut splitter = CaptureSplitter::new(output).with_class_id(by_class_id);
+        while let Some(packet) = input.next_packet()? {
+            let bytes = packet
+                .as_packet()
+                .to_vec()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "cannot encode packet"))?;
+            splitter.record(&bytes)?;
+        }
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        for capture in splitter.finish()? {
+            writeln!(
+                out,
+                "{}: {} packets",
+                capture.path.display(),
+                capture.packets
+            )?;
+        }
+        out.flush()
+    });
+}
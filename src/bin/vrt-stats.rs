@@ -0,0 +1,204 @@
+//! Summarizes the packet streams read from a capture, standard input or a UDP socket.
+
+mod common;
+
+use common::{Args, Input};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+use vrt::{ContextField, ContextFields, Fingerprint, Metrics, StreamTracker, Timestamp, VrtPacket};
+
+const USAGE: &str = "\
+Usage: vrt-stats [OPTIONS] [INPUT]
+
+Summarizes the VRT packet streams in INPUT, which is a raw or pcap capture file, - for
+standard input (the default), or udp:ADDRESS:PORT to receive datagrams: per-stream
+packet counts, loss, data rates, timestamp continuity and the history of each context
+stream.
+
+Rates are measured against the capture or receive time of the packets, or against their
+timestamps in raw captures.
+
+Options:
+  --interval SECS  Also print the summary every SECS seconds, for live streams
+  --history N      Print up to N context changes per stream (default 10)
+  --fingerprint    Also print the fingerprint of the packets, a digest of their
+                   headers and payloads which ignores arrival times
+  -h, --help       Print this help
+";
+
+/// Timestamp continuity and context history of one stream
+#[derive(Default)]
+struct StreamHistory {
+    last_time: Option<Timestamp>,
+    backwards: u64,
+    context_packets: u64,
+    last_context: Option<Vec<u8>>,
+    changes: Vec<(u64, String)>,
+}
+
+#[derive(Default)]
+struct Summary {
+    tracker: StreamTracker,
+    metrics: Metrics,
+    history: BTreeMap<Option<u32>, StreamHistory>,
+    fingerprint: Fingerprint,
+}
+
+impl Summary {
+    fn record(&mut self, index: u64, arrival: Option<Duration>, packet: &VrtPacket<'_>) {
+        let timestamp = packet.timestamp();
+        let arrival = arrival
+            .or_else(|| timestamp.and_then(|t| t.as_duration()))
+            .unwrap_or_default();
+        self.metrics.record(packet, arrival);
+        self.fingerprint.update(packet);
+        let history = self.history.entry(packet.stream_id).or_default();
+
+        if packet.header.packet_type.is_context() {
+            history.context_packets += 1;
+            if history.last_context.as_deref() != Some(packet.data_payload) {
+                let previous = history.last_context.as_deref().unwrap_or_default();
+                let change = context_change(previous, packet);
+                history.changes.push((index, change));
+                history.last_context = Some(packet.data_payload.to_vec());
+            }
+            return;
+        }
+
+        self.tracker.update(packet);
+        if let Some(t) = timestamp {
+            if history.last_time.is_some_and(|last| t < last) {
+                history.backwards += 1;
+            }
+            history.last_time = Some(t);
+        }
+    }
+
+    fn print(&self, out: &mut impl Write, history_len: usize) -> io::Result<()> {
+        writeln!(
+            out,
+            "{:<10} {:>9} {:>12} {:>7} {:>5} {:>7} {:>7} {:>10} {:>12} {:>8}",
+            "stream",
+            "packets",
+            "bytes",
+            "lost",
+            "dup",
+            "reorder",
+            "loss%",
+            "packets/s",
+            "bytes/s",
+            "ts-back"
+        )?;
+        for snapshot in self.metrics.snapshots() {
+            let stats = self.tracker.stats(snapshot.stream_id).unwrap_or_default();
+            let history = &self.history[&snapshot.stream_id];
+            writeln!(
+                out,
+                "{:<10} {:>9} {:>12} {:>7} {:>5} {:>7} {:>7.3} {:>10.1} {:>12.1} {:>8}",
+                stream_name(snapshot.stream_id),
+                snapshot.packets,
+                snapshot.bytes,
+                stats.lost,
+                stats.duplicated,
+                stats.reordered,
+                stats.loss_rate() * 100.0,
+                snapshot.packets_per_sec,
+                snapshot.bytes_per_sec,
+                history.backwards
+            )?;
+        }
+
+        for (stream_id, history) in &self.history {
+            if history.context_packets == 0 {
+                continue;
+            }
+            writeln!(
+                out,
+                "\ncontext {}: {} packets, {} changes",
+                stream_name(*stream_id),
+                history.context_packets,
+                history.changes.len()
+            )?;
+            for (index, change) in history.changes.iter().take(history_len) {
+                writeln!(out, "  #{} {}", index, change)?;
+            }
+            if history.changes.len() > history_len {
+                writeln!(out, "  ...")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Describes the decoded context fields of `packet` which differ from those of the context
+/// payload `previous`, such as `Sample Rate 1000 Hz -> 2000 Hz`
+fn context_change(previous: &[u8], packet: &VrtPacket<'_>) -> String {
+    let before = ContextFields::new(previous);
+    let mut deltas = Vec::new();
+    for field in packet.context_fields() {
+        let old = before.get(field.field);
+        if old.map(|f| f.bytes) == Some(field.bytes) {
+            continue;
+        }
+        let format = |f: ContextField<'_>| match f.value() {
+            Some(value) => value.to_string(),
+            None => format!("{} bytes", f.bytes.len()),
+        };
+        let name = field.field.name();
+        deltas.push(match old {
+            Some(old) => format!("{} {} -> {}", name, format(old), format(field)),
+            None => format!("{} {}", name, format(field)),
+        });
+    }
+    let fields = packet.context_fields();
+    for field in before.filter(|f| fields.get(f.field).is_none()) {
+        deltas.push(format!("{} removed", field.field.name()));
+    }
+    if deltas.is_empty() {
+        let cif0 = fields.cif0().unwrap_or(0);
+        return format!("CIF0 0x{:08x}, {} bytes", cif0, packet.data_payload.len());
+    }
+    deltas.join(", ")
+}
+
+fn stream_name(stream_id: Option<u32>) -> String {
+    match stream_id {
+        Some(id) => format!("0x{:08x}", id),
+        None => "none".to_string(),
+    }
+}
+
+fn main() {
+    let mut args = Args::new(USAGE);
+    let interval: Option<f64> = args.value("--interval");
+    let history_len = args.value("--history").unwrap_or(10);
+    let fingerprint = args.flag("--fingerprint");
+    let inputs = args.finish();
+
+    common::run("vrt-stats", || {
+        let mut input = Input::open(inputs.first().map_or("-", |s| s.as_str()))?;
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        let mut summary = Summary::default();
+        let mut last_report = Instant::now();
+        let mut index = 0;
+        while let Some((arrival, packet)) = input.next_packet_with_arrival()? {
+            summary.record(index, arrival, &packet.as_packet());
+            index += 1;
+            if let Some(interval) = interval {
+                if last_report.elapsed().as_secs_f64() >= interval {
+                    summary.print(&mut out, history_len)?;
+                    writeln!(out)?;
+                    out.flush()?;
+                    last_report = Instant::now();
+                }
+            }
+        }
+        summary.print(&mut out, history_len)?;
+        if fingerprint {
+            writeln!(out, "\nfingerprint {}", summary.fingerprint)?;
+        }
+        out.flush()
+    });
+}
@@ -0,0 +1,42 @@
+//! Cuts the packets of a time window out of an indexed capture.
+
+mod common;
+
+use common::{Args, Time};
+use std::io::{self, Write};
+use vrt::trim_capture;
+
+const USAGE: &str = "\
+Usage: vrt-trim --start TIME --end TIME INPUT OUTPUT
+
+Writes the VRT packets of the indexed capture INPUT with a timestamp at or after the
+start time and before the end time to the capture OUTPUT, with its index. INPUT must
+have an index file, as written by vrt-split or a capture recorder, and its packets must
+be in timestamp order; the index is used to seek to the start time without reading the
+packets before it. Times are in seconds, as in 1700000000.25. Prints the number of
+packets written.
+
+Options:
+  --start TIME        Start of the window (required)
+  --end TIME          End of the window, excluded (required)
+  -h, --help          Print this help
+";
+
+fn main() {
+    let mut args = Args::new(USAGE);
+    let start: Option<Time> = args.value("--start");
+    let end: Option<Time> = args.value("--end");
+    let (Some(start), Some(end)) = (start, end) else {
+        args.fail("--start and --end are required".into());
+    };
+    let paths = args.finish();
+    let [input, output] = paths.as_slice() else {
+        eprint!("error: expected INPUT and OUTPUT\n\n{}", USAGE);
+        std::process::exit(2);
+    };
+
+    common::run("vrt-trim", || {
+        let packets = trim_capture(input, output, start.0, end.0)?;
+        writeln!(io::stdout(), "{} packets", packets)
+    });
+}
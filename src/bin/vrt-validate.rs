@@ -0,0 +1,109 @@
+//! Checks the packets of a capture against VITA 49.0 rules, and optionally the DIFI profile,
+//! and prints a conformance report.
+
+mod common;
+
+use common::{Args, Input};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::process;
+use vrt::{Rule, Validator};
+
+const USAGE: &str = "\
+Usage: vrt-validate [OPTIONS] [INPUT]
+
+Checks the VRT packets of INPUT against VITA 49.0 rules, printing each offending packet
+with its index and byte offset, followed by a pass or fail line for every rule. Exits
+with status 1 if any packet fails a rule or cannot be parsed. INPUT is a raw, VRL
+framed or pcap capture file, or - for standard input (the default); offsets count
+the bytes of the VRT packets only, excluding any capture framing.
+
+Rules:
+  packet-type         The packet type is defined rather than reserved
+  class-id-reserved   The reserved bits of the Class Identifier are zero
+  real-time-range     Real-time fractional-seconds timestamps are below one second
+  timestamp-mode      The timestamp types of a stream do not change
+  timestamp-order     The timestamps of a stream do not go backwards
+  packet-count        The packet count of a stream increments by one, modulo 16
+
+DIFI rules, checked with --difi:
+  difi-class-id       The Class Identifier is that of a DIFI data, context or version packet
+  difi-header         The packet type, trailer and Timestamp Mode match the packet class
+  difi-timestamp      The timestamps are UTC and Real Time (Picoseconds) timestamps
+  difi-context-fields Context packets carry exactly the fields DIFI mandates
+
+Options:
+  --difi              Check the DIFI 1.2 standard flow profile too
+  --max N             Print at most N offending packets per rule (default 10)
+  --quiet             Print only the per-rule results
+  -h, --help          Print this help
+";
+
+fn main() {
+    let mut args = Args::new(USAGE);
+    let max: u64 = args.value("--max").unwrap_or(10);
+    let quiet = args.flag("--quiet");
+    let difi = args.flag("--difi");
+    let inputs = args.finish();
+
+    let mut passed = true;
+    common::run("vrt-validate", || {
+        let mut input = Input::open(inputs.first().map_or("-", |s| s.as_str()))?;
+        let mut validator = Validator::new();
+        if difi {
+            validator = validator.with_difi();
+        }
+        let mut printed = HashMap::new();
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        let malformed = loop {
+            let packet = match input.next_packet() {
+                Ok(Some(packet)) => packet,
+                Ok(None) => break None,
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => break Some(e),
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break Some(e),
+                Err(e) => return Err(e),
+            };
+            for violation in validator.check(&packet.as_packet()) {
+                let printed = printed.entry(violation.rule).or_insert(0);
+                if !quiet && *printed < max {
+                    writeln!(out, "{}", violation)?;
+                }
+                *printed += 1;
+            }
+        };
+
+        if !quiet && !printed.is_empty() {
+            writeln!(out)?;
+        }
+        let difi_rules = if difi { &Rule::DIFI[..] } else { &[] };
+        for &rule in Rule::ALL.iter().chain(difi_rules) {
+            let failures = validator.failures(rule);
+            match failures {
+                0 => writeln!(out, "{:<20} pass", rule.name())?,
+                _ => writeln!(out, "{:<20} FAIL {} packets", rule.name(), failures)?,
+            }
+        }
+        if let Some(e) = &malformed {
+            writeln!(
+                out,
+                "{:<20} FAIL packet #{} at offset {}: {}",
+                "parse",
+                validator.packets(),
+                validator.offset(),
+                e
+            )?;
+        }
+        passed = validator.passed() && malformed.is_none();
+        writeln!(
+            out,
+            "{} packets checked: {}",
+            validator.packets(),
+            if passed { "pass" } else { "FAIL" }
+        )?;
+        out.flush()
+    });
+    if !passed {
+        process::exit(1);
+    }
+}
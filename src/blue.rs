@@ -0,0 +1,248 @@
+//! Export of VRT data streams to X-Midas BLUE files.
+
+use crate::context::Cif0Field;
+use crate::vrt::{Tsf, Tsi, VitaPacketType, VrtPacket};
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Length of the BLUE header block, which the data follows
+const HEADER_LEN: usize = 512;
+/// Seconds from the BLUE epoch, 1950-01-01, to the UNIX epoch
+const BLUE_EPOCH_OFFSET: f64 = 631_152_000.0;
+/// BLUE `xunits`/`yunits` code for time in seconds
+const UNITS_TIME: i32 = 1;
+
+/// Value of an extended header keyword
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Keyword {
+    Long(i32),
+    Double(f64),
+}
+
+/// Decodes the context fields of an IF context packet which are recorded as keywords,
+/// stopping at the first truncated field
+fn context_keywords(packet: &VrtPacket<'_>) -> Vec<(&'static str, f64)> {
+    packet
+        .context_fields()
+        .filter_map(|field| {
+            let name = match field.field {
+                Cif0Field::Bandwidth => "BANDWIDTH",
+                Cif0Field::IfReferenceFrequency => "IF_FREQ",
+                Cif0Field::RfReferenceFrequency => "RF_FREQ",
+                Cif0Field::ReferenceLevel => "REF_LEVEL",
+                Cif0Field::Gain => "GAIN",
+                Cif0Field::SampleRate => "SAMPLE_RATE",
+                _ => return None,
+            };
+            Some((name, field.value()?.as_f64()?))
+        })
+        .collect()
+}
+
+/// BLUE Exporter
+///
+/// Writes the data payloads of a stream of data packets to an X-Midas BLUE file, either as a
+/// type 1000 file of samples or, with [`BlueExporter::with_subsize`], as a type 2000 file of
+/// fixed-size frames. The samples are written unchanged, so the file declares big-endian
+/// (`IEEE`) data.
+///
+/// The header is completed from the stream:
+///
+/// - `xdelta` (and `ydelta` for type 2000) from the Sample Rate of the most recent IF context
+///   packet, or the rate given to [`BlueExporter::with_sample_rate`]
+/// - `timecode` from the UTC timestamp of the first data packet
+/// - the extended header keywords `SAMPLE_RATE`, `BANDWIDTH`, `IF_FREQ`, `RF_FREQ`,
+///   `REF_LEVEL` and `GAIN` from the most recent value of the corresponding context field, and
+///   `STREAM_ID` from the Stream Identifier of the first data packet
+///
+/// Packets of other streams must be filtered out by the caller, for example with a `Demux`.
+#[derive(Debug)]
+pub struct BlueExporter<W: Write + Seek> {
+    out: W,
+    start: u64,
+    format: [u8; 2],
+    bytes_per_sample: usize,
+    subsize: Option<u32>,
+    sample_rate: Option<f64>,
+    timecode: Option<f64>,
+    keywords: Vec<(&'static str, Keyword)>,
+    first: bool,
+    data_size: u64,
+}
+
+impl BlueExporter<BufWriter<File>> {
+    /// Creates the BLUE file `path`, conventionally named `<name>.tmp` or `<name>.prm`
+    pub fn create<P: AsRef<Path>>(path: P, format: &str) -> io::Result<Self> {
+        BlueExporter::new(BufWriter::new(File::create(path)?), format)
+    }
+}
+
+impl<W: Write + Seek> BlueExporter<W> {
+    /// Creates a new BlueExporter writing to `out` from its current position
+    ///
+    /// `format` is the BLUE format of the samples, such as `CI` for complex 16-bit integers
+    /// or `SF` for scalar 32-bit floats. Returns an error of kind
+    /// [`io::ErrorKind::InvalidInput`] if it is not a valid format.
+    pub fn new(mut out: W, format: &str) -> io::Result<Self> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidInput, "invalid BLUE format");
+        let &[mode, kind] = format.as_bytes() else {
+            return Err(invalid());
+        };
+        let elements = match mode {
+            b'S' => 1,
+            b'C' => 2,
+            _ => return Err(invalid()),
+        };
+        let size = match kind {
+            b'B' => 1,
+            b'I' => 2,
+            b'L' | b'F' => 4,
+            b'X' | b'D' => 8,
+            _ => return Err(invalid()),
+        };
+        // the header is written by finish
+        let start = out.stream_position()?;
+        out.write_all(&[0; HEADER_LEN])?;
+        Ok(BlueExporter {
+            out,
+            start,
+            format: [mode, kind],
+            bytes_per_sample: elements * size,
+            subsize: None,
+            sample_rate: None,
+            timecode: None,
+            keywords: Vec::new(),
+            first: true,
+            data_size: 0,
+        })
+    }
+
+    /// Writes a type 2000 file of frames of `subsize` samples instead of a type 1000 file
+    pub fn with_subsize(mut self, subsize: u32) -> Self {
+        self.subsize = Some(subsize.max(1));
+        self
+    }
+
+    /// Sets the sample rate in Hz used until a context packet provides one
+    pub fn with_sample_rate(mut self, sample_rate: f64) -> Self {
+        self.sample_rate = Some(sample_rate);
+        self
+    }
+
+    fn set_keyword(&mut self, name: &'static str, value: Keyword) {
+        match self.keywords.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, v)) => *v = value,
+            None => self.keywords.push((name, value)),
+        }
+    }
+
+    /// Appends the data payload of a data packet to the file, or records the context fields
+    /// of an IF context packet
+    pub fn push(&mut self, packet: &VrtPacket<'_>) -> io::Result<()> {
+        let packet_type = packet.header.packet_type;
+        if packet_type.is_context() {
+            if packet_type.0 == VitaPacketType::IFCONTEXT {
+                for (name, value) in context_keywords(packet) {
+                    if name == "SAMPLE_RATE" && value > 0.0 {
+                        self.sample_rate = Some(value);
+                    }
+                    self.set_keyword(name, Keyword::Double(value));
+                }
+            }
+            return Ok(());
+        }
+        if !packet_type.is_data() {
+            return Ok(());
+        }
+
+        if self.first {
+            self.first = false;
+            if let (Tsi::TSI_UTC, Some(tsi)) = (packet.header.tsi, packet.tsi) {
+                let fraction = match (packet.header.tsf, packet.tsf) {
+                    (Tsf::TSF_REAL_TIME, Some(tsf)) => tsf as f64 * 1e-12,
+                    _ => 0.0,
+                };
+                self.timecode = Some(BLUE_EPOCH_OFFSET + tsi as f64 + fraction);
+            }
+            if let Some(stream_id) = packet.stream_id {
+                self.set_keyword("STREAM_ID", Keyword::Long(stream_id as i32));
+            }
+        }
+        let len = packet.data_payload.len() / self.bytes_per_sample * self.bytes_per_sample;
+        self.out.write_all(&packet.data_payload[..len])?;
+        self.data_size += len as u64;
+        Ok(())
+    }
+
+    /// Returns the number of samples written
+    pub fn samples(&self) -> u64 {
+        self.data_size / self.bytes_per_sample as u64
+    }
+
+    fn extended_header(&self) -> Vec<u8> {
+        let mut ext = Vec::new();
+        for (name, value) in &self.keywords {
+            let (kind, data) = match value {
+                Keyword::Long(v) => (b'L', v.to_be_bytes().to_vec()),
+                Keyword::Double(v) => (b'D', v.to_be_bytes().to_vec()),
+            };
+            let lkey = (8 + data.len() + name.len()).div_ceil(8) * 8;
+            ext.extend_from_slice(&(lkey as i32).to_be_bytes());
+            ext.extend_from_slice(&((lkey - data.len()) as i16).to_be_bytes());
+            ext.push(name.len() as u8);
+            ext.push(kind);
+            ext.extend_from_slice(&data);
+            ext.extend_from_slice(name.as_bytes());
+            ext.resize(ext.len().div_ceil(8) * 8, 0);
+        }
+        ext
+    }
+
+    fn header(&self, ext_start: u64, ext_size: usize) -> [u8; HEADER_LEN] {
+        let mut header = [0; HEADER_LEN];
+        let mut put = |offset: usize, bytes: &[u8]| {
+            header[offset..offset + bytes.len()].copy_from_slice(bytes);
+        };
+        put(0, b"BLUE");
+        put(4, b"IEEE");
+        put(8, b"IEEE");
+        put(24, &(ext_start as i32).to_be_bytes());
+        put(28, &(ext_size as i32).to_be_bytes());
+        put(32, &(HEADER_LEN as f64).to_be_bytes());
+        put(40, &(self.data_size as f64).to_be_bytes());
+        let file_type: i32 = if self.subsize.is_some() { 2000 } else { 1000 };
+        put(48, &file_type.to_be_bytes());
+        put(52, &self.format);
+        put(56, &self.timecode.unwrap_or(0.0).to_be_bytes());
+
+        // adjunct header
+        let xdelta = self.sample_rate.map_or(1.0, |rate| 1.0 / rate);
+        put(264, &xdelta.to_be_bytes());
+        put(272, &UNITS_TIME.to_be_bytes());
+        if let Some(subsize) = self.subsize {
+            put(276, &(subsize as i32).to_be_bytes());
+            put(288, &(xdelta * subsize as f64).to_be_bytes());
+            put(296, &UNITS_TIME.to_be_bytes());
+        }
+        header
+    }
+
+    /// Writes the extended header and completes the header, returning the flushed writer
+    pub fn finish(mut self) -> io::Result<W> {
+        // the extended header starts on the block after the data
+        let end = HEADER_LEN as u64 + self.data_size;
+        let ext_start = end.div_ceil(HEADER_LEN as u64);
+        let padding = (ext_start * HEADER_LEN as u64 - end) as usize;
+        self.out.write_all(&vec![0; padding])?;
+        let ext = self.extended_header();
+        self.out.write_all(&ext)?;
+
+        let header = self.header(ext_start, ext.len());
+        self.out.seek(SeekFrom::Start(self.start))?;
+        self.out.write_all(&header)?;
+        self.out.seek(SeekFrom::End(0))?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
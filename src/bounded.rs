@@ -0,0 +1,125 @@
+//! Statically bounded packet and tracker variants backed by `heapless` collections.
+
+use crate::encoder::encode_vrt_packet;
+use crate::error::Error;
+use crate::stats::{Continuity, ContinuityChecker, StreamStats};
+use crate::vrt::{Header, Trailer, VrtPacket};
+use heapless::{LinearMap, Vec};
+
+/// A VRT Packet which owns a data payload of at most `N` bytes
+///
+/// The fixed-capacity counterpart of `VrtPacketOwned` for targets without an allocator.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeaplessPacket<const N: usize> {
+    /// VRT Packet Header
+    pub header: Header,
+    /// Optional Stream Id
+    pub stream_id: Option<u32>,
+    /// Optional Class Id
+    pub class_id: Option<u64>,
+    /// Optional Integer-Seconds Timestamp
+    pub tsi: Option<u32>,
+    /// Optional Fractional-Seconds Timestamp
+    pub tsf: Option<u64>,
+    /// Data Payload
+    pub data_payload: Vec<u8, N>,
+    /// Optional VRT Packet Trailer
+    pub trailer: Option<Trailer>,
+}
+
+impl<const N: usize> HeaplessPacket<N> {
+    /// Returns a VrtPacket borrowing the data payload of this packet
+    pub fn as_packet(&self) -> VrtPacket<'_> {
+        VrtPacket {
+            header: self.header,
+            stream_id: self.stream_id,
+            class_id: self.class_id,
+            tsi: self.tsi,
+            tsf: self.tsf,
+            data_payload: &self.data_payload,
+            trailer: self.trailer,
+        }
+    }
+}
+
+impl<const N: usize> TryFrom<&VrtPacket<'_>> for HeaplessPacket<N> {
+    type Error = Error;
+
+    /// Copies `packet`, returning [`Error::BufferTooSmall`] if its payload exceeds `N` bytes
+    fn try_from(packet: &VrtPacket<'_>) -> Result<Self, Error> {
+        Ok(HeaplessPacket {
+            header: packet.header,
+            stream_id: packet.stream_id,
+            class_id: packet.class_id,
+            tsi: packet.tsi,
+            tsf: packet.tsf,
+            data_payload: Vec::from_slice(packet.data_payload)
+                .map_err(|_| Error::BufferTooSmall)?,
+            trailer: packet.trailer,
+        })
+    }
+}
+
+impl VrtPacket<'_> {
+    /// Encodes the packet into a new buffer of at most `N` bytes, as described for
+    /// [`encode_vrt_packet`]
+    pub fn to_heapless<const N: usize>(&self) -> Result<Vec<u8, N>, Error> {
+        let mut buf = Vec::new();
+        buf.resize_default(self.encoded_len().min(N))
+            .map_err(|_| Error::BufferTooSmall)?;
+        let len = encode_vrt_packet(self, &mut buf)?;
+        buf.truncate(len);
+        Ok(buf)
+    }
+}
+
+/// Continuity checker for up to `N` packet streams, keyed by Stream Identifier
+///
+/// The fixed-capacity counterpart of `StreamTracker` for targets without an allocator. Packets
+/// without a Stream Identifier are tracked together under the `None` key. Streams are looked up
+/// by linear search, which is fast for the handful of streams an embedded receiver handles.
+#[derive(Clone, Debug, Default)]
+pub struct HeaplessStreamTracker<const N: usize> {
+    streams: LinearMap<Option<u32>, ContinuityChecker, N>,
+}
+
+impl<const N: usize> HeaplessStreamTracker<N> {
+    /// Creates a new HeaplessStreamTracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks the continuity of `packet` against the previous packets of its stream.
+    ///
+    /// Returns [`Error::BufferTooSmall`] if the packet starts a new stream while `N` streams
+    /// are already tracked.
+    pub fn update(&mut self, packet: &VrtPacket<'_>) -> Result<Continuity, Error> {
+        if let Some(checker) = self.streams.get_mut(&packet.stream_id) {
+            return Ok(checker.update(packet));
+        }
+        let mut checker = ContinuityChecker::new();
+        let continuity = checker.update(packet);
+        self.streams
+            .insert(packet.stream_id, checker)
+            .map_err(|_| Error::BufferTooSmall)?;
+        Ok(continuity)
+    }
+
+    /// Returns the total counters for the stream `stream_id`, if any packets were seen.
+    pub fn stats(&self, stream_id: Option<u32>) -> Option<StreamStats> {
+        self.streams.get(&stream_id).map(|c| c.stats())
+    }
+
+    /// Returns the window counters of every stream and starts a new window.
+    pub fn take_window(&mut self) -> LinearMap<Option<u32>, StreamStats, N> {
+        self.streams
+            .iter_mut()
+            .map(|(id, c)| (*id, c.take_window()))
+            .collect()
+    }
+
+    /// Returns an iterator over the tracked Stream Identifiers and their total counters.
+    pub fn iter(&self) -> impl Iterator<Item = (Option<u32>, StreamStats)> + '_ {
+        self.streams.iter().map(|(id, c)| (*id, c.stats()))
+    }
+}
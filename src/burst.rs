@@ -0,0 +1,118 @@
+//! Reassembly of consecutive data packets into contiguous sample bursts.
+
+use crate::error::Error;
+use crate::stats::{Continuity, ContinuityChecker};
+use crate::vrt::VrtPacket;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// Handling of gaps between the packets of a burst
+pub enum GapPolicy {
+    /// Replace each lost packet with zeroed samples, assuming it had the same payload length as
+    /// the packet preceding the gap.
+    ZeroFill,
+    /// Reject the packet following the gap with [`Error::Discontinuity`].
+    Error,
+    /// End the burst at the gap and ignore the remaining packets.
+    Truncate,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+/// A contiguous burst of samples reassembled from consecutive data packets
+pub struct Burst {
+    /// Stream Identifier of the packets in the burst
+    pub stream_id: Option<u32>,
+    /// Integer-Seconds Timestamp of the first packet in the burst
+    pub tsi: Option<u32>,
+    /// Fractional-Seconds Timestamp of the first packet in the burst
+    pub tsf: Option<u64>,
+    /// Number of packets appended to the burst
+    pub packets: usize,
+    /// Number of lost packets which were zero filled
+    pub filled: u64,
+    /// Was the burst truncated at a gap?
+    pub truncated: bool,
+    /// The concatenated data payloads
+    pub samples: Vec<u8>,
+}
+
+/// Reassembles consecutive data packets of one stream into a contiguous sample burst
+///
+/// Gaps are detected from the header packet count and timestamps as described for
+/// [`ContinuityChecker`]. Duplicated and reordered packets are dropped, except under
+/// [`GapPolicy::Error`] where a reordered packet is rejected.
+#[derive(Clone, Debug)]
+pub struct BurstAssembler {
+    policy: GapPolicy,
+    checker: ContinuityChecker,
+    last_payload_len: usize,
+    burst: Burst,
+}
+
+impl BurstAssembler {
+    /// Creates a new BurstAssembler with the gap policy `policy`
+    pub fn new(policy: GapPolicy) -> Self {
+        BurstAssembler {
+            policy,
+            checker: ContinuityChecker::new(),
+            last_payload_len: 0,
+            burst: Burst::default(),
+        }
+    }
+
+    /// Appends the data payload of `packet` to the burst.
+    ///
+    /// Returns [`Error::Discontinuity`] if the packet belongs to a different stream than the
+    /// rest of the burst, or under [`GapPolicy::Error`] if the packet does not directly follow
+    /// the previous one. A rejected packet is not appended.
+    pub fn push(&mut self, packet: &VrtPacket<'_>) -> Result<(), Error> {
+        if self.burst.truncated {
+            return Ok(());
+        }
+        if self.burst.packets > 0 && packet.stream_id != self.burst.stream_id {
+            return Err(Error::Discontinuity);
+        }
+
+        let mut checker = self.checker.clone();
+        match (checker.update(packet), self.policy) {
+            (Continuity::First, _) => {
+                self.burst.stream_id = packet.stream_id;
+                self.burst.tsi = packet.tsi;
+                self.burst.tsf = packet.tsf;
+            }
+            (Continuity::InOrder, _) => {}
+            (Continuity::Duplicate, _) => return Ok(()),
+            (Continuity::Lost(_), GapPolicy::Error) | (Continuity::Reordered, GapPolicy::Error) => {
+                return Err(Error::Discontinuity)
+            }
+            (Continuity::Reordered, _) => return Ok(()),
+            (Continuity::Lost(_), GapPolicy::Truncate) => {
+                self.burst.truncated = true;
+                return Ok(());
+            }
+            (Continuity::Lost(n), GapPolicy::ZeroFill) => {
+                let fill = self.last_payload_len * n as usize;
+                self.burst
+                    .samples
+                    .resize(self.burst.samples.len() + fill, 0);
+                self.burst.filled += n;
+            }
+        }
+        self.checker = checker;
+        self.last_payload_len = packet.data_payload.len();
+        self.burst.packets += 1;
+        self.burst.samples.extend_from_slice(packet.data_payload);
+        Ok(())
+    }
+
+    /// Returns the burst assembled so far without ending it
+    pub fn burst(&self) -> &Burst {
+        &self.burst
+    }
+
+    /// Ends the current burst and returns it. The next packet pushed starts a new burst.
+    pub fn finish(&mut self) -> Burst {
+        self.checker = ContinuityChecker::new();
+        self.last_payload_len = 0;
+        core::mem::take(&mut self.burst)
+    }
+}
@@ -0,0 +1,594 @@
+//! Recording of VRT packets to capture files with a sidecar index.
+//!
+//! A capture consists of a data file holding the raw packets back-to-back, exactly as they were
+//! received, and an index file allowing random access to the packets without rescanning the
+//! data file. The index file starts with the 4-byte magic `VRTI` and a big-endian u32 version,
+//! followed by one fixed-size [`IndexEntry`] record per packet.
+
+use crate::context::Cif0Field;
+use crate::parser::parse_vrt_packet;
+use crate::vrt::{Timestamp, Tsf, Tsi, VrtPacketOwned};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Magic bytes at the start of a capture index file
+pub const INDEX_MAGIC: &[u8; 4] = b"VRTI";
+/// Version of the capture index file format
+pub const INDEX_VERSION: u32 = 1;
+/// Length of the capture index file header in bytes
+pub const INDEX_HEADER_LEN: usize = 8;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// Capture Index Entry describing one recorded packet
+pub struct IndexEntry {
+    /// Byte offset of the packet in the data file
+    pub offset: u64,
+    /// Length of the packet in bytes
+    pub len: u32,
+    /// Stream Identifier of the packet
+    pub stream_id: Option<u32>,
+    /// Timestamp of the packet
+    pub timestamp: Option<Timestamp>,
+}
+
+impl IndexEntry {
+    /// Length of an encoded index entry in bytes
+    pub const LEN: usize = 32;
+
+    /// Encodes the entry as a big-endian index record
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut b = [0u8; Self::LEN];
+        b[0..8].copy_from_slice(&self.offset.to_be_bytes());
+        b[8..12].copy_from_slice(&self.len.to_be_bytes());
+        b[12] = self.stream_id.map_or(0, |_| 0x01) | self.timestamp.map_or(0, |_| 0x02);
+        if let Some(ts) = self.timestamp {
+            b[13] = ts.tsi.0;
+            b[14] = ts.tsf.0;
+            b[20..24].copy_from_slice(&ts.integer.to_be_bytes());
+            b[24..32].copy_from_slice(&ts.fractional.to_be_bytes());
+        }
+        b[16..20].copy_from_slice(&self.stream_id.unwrap_or(0).to_be_bytes());
+        b
+    }
+
+    /// Decodes an index record produced by [`IndexEntry::to_bytes`]
+    pub fn from_bytes(b: &[u8; Self::LEN]) -> Self {
+        let u32_at = |i: usize| u32::from_be_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]]);
+        let mut fractional = [0u8; 8];
+        fractional.copy_from_slice(&b[24..32]);
+        let mut offset = [0u8; 8];
+        offset.copy_from_slice(&b[0..8]);
+
+        IndexEntry {
+            offset: u64::from_be_bytes(offset),
+            len: u32_at(8),
+            stream_id: (b[12] & 0x01 != 0).then(|| u32_at(16)),
+            timestamp: (b[12] & 0x02 != 0).then(|| Timestamp {
+                tsi: Tsi(b[13]),
+                integer: u32_at(20),
+                tsf: Tsf(b[14]),
+                fractional: u64::from_be_bytes(fractional),
+            }),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// Location of a sample within a capture
+pub struct SampleLocation {
+    /// Number of the packet containing the sample
+    pub packet: u64,
+    /// Index of the sample within the packet's data payload
+    pub sample: u64,
+}
+
+/// Returns the number of samples from `start` to `ts` at `sample_rate` samples per second, or
+/// None if `ts` is earlier or the timestamps cannot be compared
+fn sample_offset(start: &Timestamp, ts: &Timestamp, sample_rate: f64) -> Option<u64> {
+    if (start.tsi, start.tsf) != (ts.tsi, ts.tsf) || sample_rate <= 0.0 {
+        return None;
+    }
+    let integer = ts.integer as i128 - start.integer as i128;
+    let fractional = ts.fractional as i128 - start.fractional as i128;
+    let (ticks, ticks_per_second) = match ts.tsf {
+        Tsf::TSF_NONE => (integer, 1),
+        Tsf::TSF_REAL_TIME => (integer * 1_000_000_000_000 + fractional, 1_000_000_000_000),
+        // a Sample Count timestamp counts the samples, within the second if there are seconds
+        Tsf::TSF_SAMPLE_COUNT => {
+            let samples = integer * sample_rate.round() as i128 + fractional;
+            return u64::try_from(samples).ok();
+        }
+        _ => return None,
+    };
+    if ticks < 0 {
+        return None;
+    }
+    let samples = if sample_rate.fract() == 0.0 {
+        ticks * sample_rate as i128 / ticks_per_second
+    } else {
+        (ticks as f64 * sample_rate / ticks_per_second as f64) as i128
+    };
+    u64::try_from(samples).ok()
+}
+
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Returns the path of the index file belonging to the capture data file `path`
+pub fn index_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let mut path = path.as_ref().as_os_str().to_owned();
+    path.push(".idx");
+    PathBuf::from(path)
+}
+
+/// Capture Recorder
+///
+/// Writes packets to a capture data file and an entry per packet to its sidecar index.
+#[derive(Debug)]
+pub struct Recorder<W: Write, X: Write> {
+    data: W,
+    index: X,
+    offset: u64,
+    packets: u64,
+}
+
+impl Recorder<BufWriter<File>, BufWriter<File>> {
+    /// Creates the capture data file `path` and its index file, as named by [`index_path`]
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let data = BufWriter::new(File::create(&path)?);
+        let index = BufWriter::new(File::create(index_path(&path))?);
+        Recorder::new(data, index)
+    }
+}
+
+impl<W: Write, X: Write> Recorder<W, X> {
+    /// Creates a new Recorder writing packets to `data` and index entries to `index`, and
+    /// writes the index file header
+    pub fn new(data: W, mut index: X) -> io::Result<Self> {
+        index.write_all(INDEX_MAGIC)?;
+        index.write_all(&INDEX_VERSION.to_be_bytes())?;
+        Ok(Recorder {
+            data,
+            index,
+            offset: 0,
+            packets: 0,
+        })
+    }
+
+    /// Records the packet at the start of `bytes`, returning its index entry. Any bytes
+    /// following the packet are ignored.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if `bytes` does not start with a
+    /// complete, valid packet.
+    pub fn record(&mut self, bytes: &[u8]) -> io::Result<IndexEntry> {
+        let (rem, packet) =
+            parse_vrt_packet(bytes).map_err(|_| invalid_data("invalid VRT packet"))?;
+        let len = bytes.len() - rem.len();
+        let entry = IndexEntry {
+            offset: self.offset,
+            len: len as u32,
+            stream_id: packet.stream_id,
+            timestamp: packet.timestamp(),
+        };
+        self.data.write_all(&bytes[..len])?;
+        self.index.write_all(&entry.to_bytes())?;
+        self.offset += len as u64;
+        self.packets += 1;
+        Ok(entry)
+    }
+
+    /// Returns the number of packets recorded
+    pub fn packets(&self) -> u64 {
+        self.packets
+    }
+
+    /// Flushes the data and index writers
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.data.flush()?;
+        self.index.flush()
+    }
+
+    /// Flushes and returns the data and index writers
+    pub fn into_inner(mut self) -> io::Result<(W, X)> {
+        self.flush()?;
+        Ok((self.data, self.index))
+    }
+}
+
+/// Capture Reader
+///
+/// Reads the packets of a capture written by a [`Recorder`], using the sidecar index to seek to
+/// a packet number or timestamp without scanning the data file. The index is read from disk as
+/// needed rather than loaded into memory, so captures of any size can be opened quickly.
+///
+/// Iterating a CaptureReader returns the packets from the current position onwards.
+#[derive(Debug)]
+pub struct CaptureReader<R: Read + Seek, X: Read + Seek> {
+    data: R,
+    index: X,
+    packets: u64,
+    position: u64,
+    data_offset: Option<u64>,
+}
+
+impl CaptureReader<BufReader<File>, BufReader<File>> {
+    /// Opens the capture data file `path` and its index file, as named by [`index_path`]
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let data = BufReader::new(File::open(&path)?);
+        let index = BufReader::new(File::open(index_path(&path))?);
+        CaptureReader::new(data, index)
+    }
+}
+
+impl<R: Read + Seek, X: Read + Seek> CaptureReader<R, X> {
+    /// Creates a new CaptureReader reading packets from `data` and index entries from `index`
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if the index header is invalid.
+    pub fn new(data: R, mut index: X) -> io::Result<Self> {
+        let mut header = [0u8; INDEX_HEADER_LEN];
+        index.seek(SeekFrom::Start(0))?;
+        index.read_exact(&mut header)?;
+        if &header[0..4] != INDEX_MAGIC {
+            return Err(invalid_data("not a VRT capture index"));
+        }
+        if header[4..8] != INDEX_VERSION.to_be_bytes() {
+            return Err(invalid_data("unsupported VRT capture index version"));
+        }
+        let len = index.seek(SeekFrom::End(0))?;
+        let packets = (len - INDEX_HEADER_LEN as u64) / IndexEntry::LEN as u64;
+        Ok(CaptureReader {
+            data,
+            index,
+            packets,
+            position: 0,
+            data_offset: None,
+        })
+    }
+
+    /// Returns the number of packets in the capture
+    pub fn len(&self) -> u64 {
+        self.packets
+    }
+
+    /// Returns true if the capture contains no packets
+    pub fn is_empty(&self) -> bool {
+        self.packets == 0
+    }
+
+    /// Returns the number of the packet which will be read next
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns the index entry of packet number `n`
+    pub fn entry(&mut self, n: u64) -> io::Result<IndexEntry> {
+        if n >= self.packets {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "packet number beyond end of capture",
+            ));
+        }
+        let offset = INDEX_HEADER_LEN as u64 + n * IndexEntry::LEN as u64;
+        let mut record = [0u8; IndexEntry::LEN];
+        self.index.seek(SeekFrom::Start(offset))?;
+        self.index.read_exact(&mut record)?;
+        Ok(IndexEntry::from_bytes(&record))
+    }
+
+    /// Moves to packet number `n`. Seeking to the end of the capture is allowed.
+    pub fn seek_to_packet(&mut self, n: u64) -> io::Result<()> {
+        if n > self.packets {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "packet number beyond end of capture",
+            ));
+        }
+        self.position = n;
+        Ok(())
+    }
+
+    /// Moves to the first packet with a timestamp at or after `ts`, returning its packet number
+    ///
+    /// The packets must be in timestamp order, as for a capture of a single stream. Timestamps
+    /// are compared in the order of [`Timestamp`], and packets without a timestamp sort before
+    /// any timestamped packet.
+    pub fn seek_to_time(&mut self, ts: Timestamp) -> io::Result<u64> {
+        let (mut lo, mut hi) = (0, self.packets);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.entry(mid)?.timestamp < Some(ts) {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        self.position = lo;
+        Ok(lo)
+    }
+
+    /// Locates the data packet and sample containing the instant `ts`, for a stream with
+    /// `bytes_per_sample` bytes per sample
+    ///
+    /// The sample rate is taken from the most recent context packet of the stream before the
+    /// containing packet that carries a Sample Rate, found by scanning back through the
+    /// capture, or is `sample_rate` if there is none. Real Time (Picoseconds) and Sample Count
+    /// timestamps, and integer-seconds timestamps alone, can be located.
+    ///
+    /// On success the reader is positioned at the containing packet. Returns None if `ts` falls
+    /// before the first timestamped data packet, in a gap between packets or after the last
+    /// sample, or if its type differs from that of the packet. As for
+    /// [`CaptureReader::seek_to_time`] the packets must be in timestamp order.
+    pub fn locate_sample(
+        &mut self,
+        ts: Timestamp,
+        sample_rate: f64,
+        bytes_per_sample: usize,
+    ) -> io::Result<Option<SampleLocation>> {
+        // the last data packet at or before `ts`
+        let mut n = self.seek_to_time(ts)?;
+        while n < self.packets && self.entry(n)?.timestamp == Some(ts) {
+            n += 1;
+        }
+        let (n, packet) = loop {
+            if n == 0 {
+                return Ok(None);
+            }
+            n -= 1;
+            self.seek_to_packet(n)?;
+            match self.read_packet()? {
+                Some(packet) if packet.header.packet_type.is_data() => break (n, packet),
+                _ => {}
+            }
+        };
+        let start = match packet.as_packet().timestamp() {
+            Some(start) => start,
+            None => return Ok(None),
+        };
+        let sample_rate = self
+            .context_sample_rate(n, packet.stream_id)?
+            .unwrap_or(sample_rate);
+        let sample = sample_offset(&start, &ts, sample_rate);
+        let samples = match bytes_per_sample {
+            0 => 0,
+            _ => (packet.data_payload.len() / bytes_per_sample) as u64,
+        };
+        self.seek_to_packet(n)?;
+        Ok(sample
+            .filter(|sample| *sample < samples)
+            .map(|sample| SampleLocation { packet: n, sample }))
+    }
+
+    /// Returns the Sample Rate of the most recent context packet of the stream `stream_id`
+    /// before packet number `n` which carries one
+    fn context_sample_rate(
+        &mut self,
+        mut n: u64,
+        stream_id: Option<u32>,
+    ) -> io::Result<Option<f64>> {
+        while n > 0 {
+            n -= 1;
+            if self.entry(n)?.stream_id != stream_id {
+                continue;
+            }
+            self.seek_to_packet(n)?;
+            if let Some(packet) = self.read_packet()? {
+                let sample_rate = packet
+                    .as_packet()
+                    .context_fields()
+                    .get(Cif0Field::SampleRate)
+                    .and_then(|field| field.value()?.as_f64());
+                if sample_rate.is_some() {
+                    return Ok(sample_rate);
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Reads the bytes of the next packet, exactly as recorded, and advances the position, or
+    /// returns None at the end of the capture
+    pub fn read_packet_bytes(&mut self) -> io::Result<Option<Vec<u8>>> {
+        if self.position >= self.packets {
+            return Ok(None);
+        }
+        let entry = self.entry(self.position)?;
+        if self.data_offset != Some(entry.offset) {
+            self.data.seek(SeekFrom::Start(entry.offset))?;
+        }
+        let mut buf = vec![0u8; entry.len as usize];
+        self.data_offset = None;
+        self.data.read_exact(&mut buf)?;
+        self.data_offset = Some(entry.offset + entry.len as u64);
+        self.position += 1;
+        Ok(Some(buf))
+    }
+
+    /// Reads the next packet and advances the position, or returns None at the end of the capture
+    pub fn read_packet(&mut self) -> io::Result<Option<VrtPacketOwned>> {
+        let buf = match self.read_packet_bytes()? {
+            Some(buf) => buf,
+            None => return Ok(None),
+        };
+        let (_, packet) =
+            parse_vrt_packet(&buf).map_err(|_| invalid_data("invalid VRT packet in capture"))?;
+        Ok(Some(VrtPacketOwned::from(&packet)))
+    }
+
+    /// Records the packets with a timestamp from `start` up to but excluding `end` into
+    /// `recorder`, copying them byte for byte, and returns the number of packets recorded
+    ///
+    /// Seeks to `start` with [`CaptureReader::seek_to_time`], so the packets must be in
+    /// timestamp order and only the packets of the window are read. Packets without a
+    /// timestamp within the window are recorded too. The reader is left after the last packet
+    /// recorded.
+    pub fn trim_into<W: Write, Y: Write>(
+        &mut self,
+        start: Timestamp,
+        end: Timestamp,
+        recorder: &mut Recorder<W, Y>,
+    ) -> io::Result<u64> {
+        let mut packets = 0;
+        self.seek_to_time(start)?;
+        while self.position < self.packets {
+            if self
+                .entry(self.position)?
+                .timestamp
+                .is_some_and(|time| time >= end)
+            {
+                break;
+            }
+            if let Some(bytes) = self.read_packet_bytes()? {
+                recorder.record(&bytes)?;
+                packets += 1;
+            }
+        }
+        Ok(packets)
+    }
+}
+
+impl<R: Read + Seek, X: Read + Seek> Iterator for CaptureReader<R, X> {
+    type Item = io::Result<VrtPacketOwned>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_packet().transpose()
+    }
+}
+
+type FileRecorder = Recorder<BufWriter<File>, BufWriter<File>>;
+
+/// A capture written by a [`CaptureSplitter`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SplitCapture {
+    /// Stream Identifier of the packets in the capture
+    pub stream_id: Option<u32>,
+    /// Class Identifier of the packets in the capture, if split by Class Identifier
+    pub class_id: Option<u64>,
+    /// Path of the capture data file; the index file is named by [`index_path`]
+    pub path: PathBuf,
+    /// Number of packets in the capture
+    pub packets: u64,
+}
+
+/// Capture Splitter
+///
+/// Records the packets of a multi-stream capture into one capture per Stream Identifier, each
+/// with its own index file, so tools handling a single stream can read them. Packets can also
+/// be split by Class Identifier, giving one capture per Stream and Class Identifier pair.
+///
+/// The captures are named after the base path passed to [`CaptureSplitter::new`], with the
+/// Stream Identifier, and the Class Identifier if split by it, in hex before the extension:
+/// splitting `capture.vrt` writes `capture-00000001.vrt` and `capture-00000001.vrt.idx` for
+/// stream 1, or `capture-00000001-00123456789abcde.vrt` split by Class Identifier. A missing
+/// identifier is written as `none`.
+#[derive(Debug)]
+pub struct CaptureSplitter {
+    base: PathBuf,
+    by_class_id: bool,
+    captures: BTreeMap<(Option<u32>, Option<u64>), (PathBuf, FileRecorder)>,
+}
+
+impl CaptureSplitter {
+    /// Creates a new CaptureSplitter naming its captures after `base`
+    pub fn new<P: AsRef<Path>>(base: P) -> Self {
+        CaptureSplitter {
+            base: base.as_ref().to_path_buf(),
+            by_class_id: false,
+            captures: BTreeMap::new(),
+        }
+    }
+
+    /// Sets whether packets are also split by Class Identifier
+    pub fn with_class_id(mut self, by_class_id: bool) -> Self {
+        self.by_class_id = by_class_id;
+        self
+    }
+
+    fn path(&self, stream_id: Option<u32>, class_id: Option<u64>) -> PathBuf {
+        let stem = self.base.file_stem().unwrap_or_default().to_string_lossy();
+        let mut name = match stream_id {
+            Some(stream_id) => format!("{}-{:08x}", stem, stream_id),
+            None => format!("{}-none", stem),
+        };
+        if self.by_class_id {
+            match class_id {
+                Some(class_id) => name += &format!("-{:016x}", class_id),
+                None => name += "-none",
+            }
+        }
+        if let Some(extension) = self.base.extension() {
+            name += &format!(".{}", extension.to_string_lossy());
+        }
+        self.base.with_file_name(name)
+    }
+
+    /// Records the packet at the start of `bytes` into the capture of its stream, creating the
+    /// capture for the first packet of a stream
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if `bytes` does not start with a
+    /// complete, valid packet.
+    pub fn record(&mut self, bytes: &[u8]) -> io::Result<IndexEntry> {
+        let (_, packet) =
+            parse_vrt_packet(bytes).map_err(|_| invalid_data("invalid VRT packet"))?;
+        let class_id = packet.class_id.filter(|_| self.by_class_id);
+        let key = (packet.stream_id, class_id);
+        if let Some((_, recorder)) = self.captures.get_mut(&key) {
+            return recorder.record(bytes);
+        }
+        let path = self.path(packet.stream_id, class_id);
+        let mut recorder = Recorder::create(&path)?;
+        let entry = recorder.record(bytes);
+        self.captures.insert(key, (path, recorder));
+        entry
+    }
+
+    /// Flushes the captures and returns them, ordered by Stream and then Class Identifier
+    pub fn finish(self) -> io::Result<Vec<SplitCapture>> {
+        self.captures
+            .into_iter()
+            .map(|((stream_id, class_id), (path, mut recorder))| {
+                recorder.flush()?;
+                Ok(SplitCapture {
+                    stream_id,
+                    class_id,
+                    path,
+                    packets: recorder.packets(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Splits the capture `path` into one capture per Stream Identifier, and per Class Identifier
+/// if `by_class_id` is set, alongside it and named as described for [`CaptureSplitter`]
+///
+/// The packets are copied byte for byte in capture order, and each capture gets its own index.
+pub fn split_capture<P: AsRef<Path>>(path: P, by_class_id: bool) -> io::Result<Vec<SplitCapture>> {
+    let mut reader = CaptureReader::open(&path)?;
+    let mut splitter = CaptureSplitter::new(&path).with_class_id(by_class_id);
+    while let Some(bytes) = reader.read_packet_bytes()? {
+        splitter.record(&bytes)?;
+    }
+    splitter.finish()
+}
+
+/// Writes the capture `output`, with its index, holding the packets of the capture `input`
+/// with a timestamp from `start` up to but excluding `end`, and returns the number of packets
+/// written
+///
+/// The packets of `input` must be in timestamp order, as for a capture of a single stream; the
+/// index is used to seek to `start`, as described for [`CaptureReader::trim_into`].
+pub fn trim_capture<P: AsRef<Path>, Q: AsRef<Path>>(
+    input: P,
+    output: Q,
+    start: Timestamp,
+    end: Timestamp,
+) -> io::Result<u64> {
+    let mut reader = CaptureReader::open(input)?;
+    let mut recorder = Recorder::create(output)?;
+    let packets = reader.trim_into(start, end, &mut recorder)?;
+    recorder.flush()?;
+    Ok(packets)
+}
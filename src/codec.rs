@@ -0,0 +1,125 @@
+//! Framing of VRT packets on byte streams such as TCP connections.
+
+use crate::encoder::encode_vrt_packet;
+use crate::parser::{parse_vrt_header, parse_vrt_packet};
+use crate::shared::{parse_vrt_packet_bytes, VrtPacketBytes};
+use crate::vrt::{VrtPacket, VrtPacketOwned};
+use bytes::{Buf, BytesMut};
+use std::io;
+use tokio_util::codec::{Decoder, Encoder};
+
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// VRT Packet Codec
+///
+/// A [`Decoder`] and [`Encoder`] framing VRT packets sent back-to-back on a byte stream, for use
+/// with [`Framed`](tokio_util::codec::Framed). Each frame is delimited by the `packet_size`
+/// field of its header, so no additional framing is needed.
+///
+/// A header with a `packet_size` of zero cannot be framed and is reported as an error of kind
+/// [`io::ErrorKind::InvalidData`]; the stream cannot be decoded further.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VrtCodec;
+
+impl VrtCodec {
+    /// Creates a new VrtCodec
+    pub fn new() -> Self {
+        VrtCodec
+    }
+}
+
+impl Decoder for VrtCodec {
+    type Item = VrtPacketOwned;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<VrtPacketOwned>> {
+        let header = match parse_vrt_header(src) {
+            Ok((_, header)) => header,
+            Err(_) => return Ok(None),
+        };
+        let len = header.packet_size as usize * 4;
+        if len == 0 {
+            return Err(invalid_data("VRT packet_size of zero"));
+        }
+        if src.len() < len {
+            src.reserve(len - src.len());
+            return Ok(None);
+        }
+        let packet = parse_vrt_packet(&src[..len])
+            .map(|(_, packet)| VrtPacketOwned::from(&packet))
+            .map_err(|_| invalid_data("invalid VRT packet"));
+        src.advance(len);
+        packet.map(Some)
+    }
+}
+
+impl Encoder<&VrtPacket<'_>> for VrtCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, packet: &VrtPacket<'_>, dst: &mut BytesMut) -> io::Result<()> {
+        let start = dst.len();
+        dst.resize(start + packet.encoded_len(), 0);
+        encode_vrt_packet(packet, &mut dst[start..]).map_err(|_| {
+            dst.truncate(start);
+            io::Error::new(io::ErrorKind::InvalidInput, "invalid VRT packet")
+        })?;
+        Ok(())
+    }
+}
+
+impl Encoder<VrtPacketOwned> for VrtCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, packet: VrtPacketOwned, dst: &mut BytesMut) -> io::Result<()> {
+        self.encode(&packet.as_packet(), dst)
+    }
+}
+
+/// Zero-Copy VRT Packet Codec
+///
+/// Frames VRT packets as [`VrtCodec`] does, but decodes them into [`VrtPacketBytes`] whose
+/// payloads share the read buffer of the [`Framed`](tokio_util::codec::Framed) stream instead
+/// of being copied out of it.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VrtBytesCodec;
+
+impl VrtBytesCodec {
+    /// Creates a new VrtBytesCodec
+    pub fn new() -> Self {
+        VrtBytesCodec
+    }
+}
+
+impl Decoder for VrtBytesCodec {
+    type Item = VrtPacketBytes;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<VrtPacketBytes>> {
+        let header = match parse_vrt_header(src) {
+            Ok((_, header)) => header,
+            Err(_) => return Ok(None),
+        };
+        let len = header.packet_size as usize * 4;
+        if len == 0 {
+            return Err(invalid_data("VRT packet_size of zero"));
+        }
+        if src.len() < len {
+            src.reserve(len - src.len());
+            return Ok(None);
+        }
+        let frame = src.split_to(len).freeze();
+        parse_vrt_packet_bytes(&frame)
+            .map(|(_, packet)| Some(packet))
+            .map_err(|_| invalid_data("invalid VRT packet"))
+    }
+}
+
+impl Encoder<VrtPacketBytes> for VrtBytesCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, packet: VrtPacketBytes, dst: &mut BytesMut) -> io::Result<()> {
+        VrtCodec.encode(&packet.as_packet(), dst)
+    }
+}
@@ -0,0 +1,579 @@
+//! VITA 49.2 command packets: control, cancellation and acknowledge packets.
+//!
+//! The payload of a [`VitaPacketType::COMMAND`] or [`VitaPacketType::EXTCOMMAND`] packet starts
+//! with the Control/Acknowledge Mode (CAM) field and a Message ID, followed by the optional
+//! Controllee and Controller Identifiers announced by the CAM field, and then by the indicator
+//! words and fields of the command. [`Command`] parses and encodes this prologue, and like
+//! [`ContextFields`] needs neither the standard library nor an allocator.
+
+use crate::context::{ContextFields, SignalContext, CIF_EXTENSION_BITS};
+use crate::error::Error;
+use crate::parser::parse_vrt_warning_error_responses;
+use crate::vrt::{Header, Timestamp, Tsf, Tsi, VitaPacketType, VrtPacket, WarningErrorResponses};
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// Control/Acknowledge Mode (CAM) field of a VITA 49.2 command packet
+pub struct ControlAckMode(pub u32);
+
+impl ControlAckMode {
+    /// A Controllee Identifier follows the Message ID.
+    pub const CONTROLLEE_ENABLE: u32 = 1 << 31;
+    /// The Controllee Identifier is a 128-bit UUID rather than a 32-bit word.
+    pub const CONTROLLEE_UUID: u32 = 1 << 30;
+    /// A Controller Identifier follows the Message ID and any Controllee Identifier.
+    pub const CONTROLLER_ENABLE: u32 = 1 << 29;
+    /// The Controller Identifier is a 128-bit UUID rather than a 32-bit word.
+    pub const CONTROLLER_UUID: u32 = 1 << 28;
+    /// The controllee may execute part of the packet.
+    pub const PARTIAL_PERMITTED: u32 = 1 << 27;
+    /// The controllee may execute fields which raise warnings.
+    pub const WARNINGS_PERMITTED: u32 = 1 << 26;
+    /// The controllee may execute fields which raise errors.
+    pub const ERRORS_PERMITTED: u32 = 1 << 25;
+    /// Mask of the Action Mode, bits 24 and 23.
+    pub const ACTION_MASK: u32 = 0b11 << 23;
+    /// Acknowledge packets are requested only when warnings or errors occur.
+    pub const NACK_ONLY: u32 = 1 << 22;
+    /// A validation acknowledge is requested, or the acknowledge is one.
+    pub const VALIDATION: u32 = 1 << 20;
+    /// An execution acknowledge is requested, or the acknowledge is one.
+    pub const EXECUTION: u32 = 1 << 19;
+    /// A query-state acknowledge is requested, or the acknowledge is one.
+    pub const STATE: u32 = 1 << 18;
+    /// Warnings are requested in, or reported by, the acknowledge.
+    pub const WARNINGS: u32 = 1 << 17;
+    /// Errors are requested in, or reported by, the acknowledge.
+    pub const ERRORS: u32 = 1 << 16;
+    /// Mask of the Timing Control, bits 14 to 12.
+    pub const TIMING_MASK: u32 = 0b111 << 12;
+    /// In an acknowledge, only part of the action was taken.
+    pub const PARTIAL_ACTION: u32 = 1 << 11;
+    /// In an acknowledge, the action was scheduled or executed.
+    pub const SCHEDULED_OR_EXECUTED: u32 = 1 << 10;
+
+    /// Mask of the bits announcing the identifiers, which [`Command::encode`] writes from
+    /// the identifiers of the command
+    const IDENTIFIER_MASK: u32 = 0xf000_0000;
+
+    /// Returns true if every bit of `flag` is set in the CAM field.
+    pub fn contains(&self, flag: u32) -> bool {
+        self.0 & flag == flag
+    }
+
+    /// Returns the CAM field with every bit of `flag` set.
+    pub fn with(self, flag: u32) -> Self {
+        ControlAckMode(self.0 | flag)
+    }
+
+    /// Returns the Action Mode, or None for the reserved value.
+    pub fn action(&self) -> Option<ActionMode> {
+        match (self.0 & Self::ACTION_MASK) >> 23 {
+            0 => Some(ActionMode::NoAction),
+            1 => Some(ActionMode::DryRun),
+            2 => Some(ActionMode::Execute),
+            _ => None,
+        }
+    }
+
+    /// Returns the CAM field with the Action Mode `action`.
+    pub fn with_action(self, action: ActionMode) -> Self {
+        ControlAckMode(self.0 & !Self::ACTION_MASK | (action as u32) << 23)
+    }
+
+    /// Returns the Timing Control, or None for the reserved values.
+    pub fn timing(&self) -> Option<TimingControl> {
+        match (self.0 & Self::TIMING_MASK) >> 12 {
+            0 => Some(TimingControl::IgnoreTimestamp),
+            1 => Some(TimingControl::OnTime),
+            2 => Some(TimingControl::LateAllowed),
+            3 => Some(TimingControl::EarlyAllowed),
+            4 => Some(TimingControl::EarlyOrLateAllowed),
+            _ => None,
+        }
+    }
+
+    /// Returns the CAM field with the Timing Control `timing`.
+    pub fn with_timing(self, timing: TimingControl) -> Self {
+        ControlAckMode(self.0 & !Self::TIMING_MASK | (timing as u32) << 12)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// Action Mode of the CAM field: what the controllee does with a control packet
+pub enum ActionMode {
+    /// Take no action, for example to only request a validation acknowledge.
+    NoAction = 0,
+    /// Validate the packet as if executing it, without applying it.
+    DryRun = 1,
+    /// Execute the packet.
+    Execute = 2,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// Timing Control of the CAM field: when the controllee executes a control packet relative to
+/// the packet's timestamp
+pub enum TimingControl {
+    /// Execute on receipt, ignoring any timestamp.
+    IgnoreTimestamp = 0,
+    /// Execute at the timestamp, neither early nor late.
+    OnTime = 1,
+    /// Execute at the timestamp, or late if the timestamp has passed.
+    LateAllowed = 2,
+    /// Execute at the timestamp, or early if it cannot be met.
+    EarlyAllowed = 3,
+    /// Execute at the timestamp, or early or late.
+    EarlyOrLateAllowed = 4,
+}
+
+impl TimingControl {
+    /// Returns true if the controllee may execute the packet `lateness` seconds after its
+    /// timestamp, negative if before, counting execution within `window` seconds of the
+    /// timestamp as on time
+    ///
+    /// The lateness of a packet received at time `now` and timestamped `at` is
+    /// `now.seconds_since(&at, sample_rate)`, as given by [`Timestamp::seconds_since`].
+    pub fn permits(self, lateness: f64, window: f64) -> bool {
+        let early = lateness < -window;
+        let late = lateness > window;
+        match self {
+            TimingControl::IgnoreTimestamp | TimingControl::EarlyOrLateAllowed => true,
+            TimingControl::OnTime => !early && !late,
+            TimingControl::LateAllowed => !early,
+            TimingControl::EarlyAllowed => !late,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// Controllee or Controller Identifier of a command packet
+pub enum CommandId {
+    /// A 32-bit identifier
+    Word(u32),
+    /// A 128-bit UUID
+    Uuid(u128),
+}
+
+impl CommandId {
+    fn len(self) -> usize {
+        match self {
+            CommandId::Word(_) => 4,
+            CommandId::Uuid(_) => 16,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// Kind of a VITA 49.2 command packet, from the packet-specific bits of its header and, for
+/// acknowledge packets, from its CAM field
+pub enum CommandKind {
+    /// A control packet, setting or querying the controllee's state
+    Control,
+    /// A cancellation packet, cancelling the control packet with the same Message ID
+    Cancellation,
+    /// A validation acknowledge packet
+    ValidationAck,
+    /// An execution acknowledge packet
+    ExecutionAck,
+    /// A query-state acknowledge packet
+    QueryAck,
+}
+
+impl CommandKind {
+    /// Returns true for the acknowledge packets
+    pub fn is_ack(self) -> bool {
+        !matches!(self, CommandKind::Control | CommandKind::Cancellation)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// The command carried by the payload of a VITA 49.2 command packet
+pub struct Command<'a> {
+    /// Kind of the command packet
+    pub kind: CommandKind,
+    /// Control/Acknowledge Mode field
+    pub cam: ControlAckMode,
+    /// Message ID, which acknowledge and cancellation packets echo
+    pub message_id: u32,
+    /// Optional Controllee Identifier
+    pub controllee: Option<CommandId>,
+    /// Optional Controller Identifier
+    pub controller: Option<CommandId>,
+    /// The indicator words and fields following the identifiers
+    pub payload: &'a [u8],
+}
+
+impl<'a> Command<'a> {
+    /// Creates a control command with Message ID `message_id`, no identifiers and the
+    /// Context Indicator Field words and fields `payload`
+    pub fn control(message_id: u32, payload: &'a [u8]) -> Self {
+        Command {
+            kind: CommandKind::Control,
+            cam: ControlAckMode::default().with_action(ActionMode::Execute),
+            message_id,
+            controllee: None,
+            controller: None,
+            payload,
+        }
+    }
+
+    /// Parses the payload `bytes` of a command packet with header `header`
+    ///
+    /// Returns [`Error::Malformed`] if the packet is not a command packet, if the payload is
+    /// too short for the CAM field, Message ID and identifiers, or if the CAM field of an
+    /// acknowledge packet does not select exactly one kind of acknowledge.
+    pub fn parse(header: &Header, bytes: &'a [u8]) -> Result<Self, Error> {
+        if !header.packet_type.is_command() {
+            return Err(Error::Malformed);
+        }
+        let word = |at: usize| {
+            bytes
+                .get(at..at + 4)
+                .map(|w| u32::from_be_bytes([w[0], w[1], w[2], w[3]]))
+                .ok_or(Error::Malformed)
+        };
+        let cam = ControlAckMode(word(0)?);
+        let message_id = word(4)?;
+        let mut offset = 8;
+        let mut identifier = |enable: u32, uuid: u32| -> Result<Option<CommandId>, Error> {
+            if !cam.contains(enable) {
+                return Ok(None);
+            }
+            let id = match cam.contains(uuid) {
+                true => {
+                    let bytes = bytes.get(offset..offset + 16).ok_or(Error::Malformed)?;
+                    CommandId::Uuid(bytes.iter().fold(0, |acc, b| acc << 8 | *b as u128))
+                }
+                false => CommandId::Word(word(offset)?),
+            };
+            offset += id.len();
+            Ok(Some(id))
+        };
+        let controllee = identifier(
+            ControlAckMode::CONTROLLEE_ENABLE,
+            ControlAckMode::CONTROLLEE_UUID,
+        )?;
+        let controller = identifier(
+            ControlAckMode::CONTROLLER_ENABLE,
+            ControlAckMode::CONTROLLER_UUID,
+        )?;
+        let kind = match (header.t, header.indicators & 0x01 == 1) {
+            (true, _) => {
+                let acks = [
+                    (ControlAckMode::VALIDATION, CommandKind::ValidationAck),
+                    (ControlAckMode::EXECUTION, CommandKind::ExecutionAck),
+                    (ControlAckMode::STATE, CommandKind::QueryAck),
+                ];
+                let mut selected = acks.into_iter().filter(|(bit, _)| cam.contains(*bit));
+                match (selected.next(), selected.next()) {
+                    (Some((_, kind)), None) => kind,
+                    _ => return Err(Error::Malformed),
+                }
+            }
+            (false, true) => CommandKind::Cancellation,
+            (false, false) => CommandKind::Control,
+        };
+        Ok(Command {
+            kind,
+            cam,
+            message_id,
+            controllee,
+            controller,
+            payload: bytes.get(offset..).unwrap_or_default(),
+        })
+    }
+
+    /// Returns the length in bytes of the encoded command
+    pub fn encoded_len(&self) -> usize {
+        8 + self.controllee.map_or(0, CommandId::len)
+            + self.controller.map_or(0, CommandId::len)
+            + self.payload.len()
+    }
+
+    /// Encodes the command into the start of `buf`, returning the number of bytes written
+    ///
+    /// The bits of the CAM field announcing the identifiers are written from `controllee` and
+    /// `controller`, whatever their value in `cam`. Returns [`Error::BufferTooSmall`] if `buf`
+    /// cannot hold the command.
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        let len = self.encoded_len();
+        let buf = buf.get_mut(..len).ok_or(Error::BufferTooSmall)?;
+        let mut cam = self.cam.0 & !ControlAckMode::IDENTIFIER_MASK;
+        let mut offset = 8;
+        for (id, enable, uuid) in [
+            (
+                self.controllee,
+                ControlAckMode::CONTROLLEE_ENABLE,
+                ControlAckMode::CONTROLLEE_UUID,
+            ),
+            (
+                self.controller,
+                ControlAckMode::CONTROLLER_ENABLE,
+                ControlAckMode::CONTROLLER_UUID,
+            ),
+        ] {
+            match id {
+                Some(CommandId::Word(id)) => {
+                    cam |= enable;
+                    buf[offset..offset + 4].copy_from_slice(&id.to_be_bytes());
+                }
+                Some(CommandId::Uuid(id)) => {
+                    cam |= enable | uuid;
+                    buf[offset..offset + 16].copy_from_slice(&id.to_be_bytes());
+                }
+                None => continue,
+            }
+            offset += id.map_or(0, CommandId::len);
+        }
+        buf[..4].copy_from_slice(&cam.to_be_bytes());
+        buf[4..8].copy_from_slice(&self.message_id.to_be_bytes());
+        buf[offset..].copy_from_slice(self.payload);
+        Ok(len)
+    }
+
+    /// Encodes the command into a new buffer, as described for [`Command::encode`]
+    #[cfg(feature = "alloc")]
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut buf = vec![0u8; self.encoded_len()];
+        let _ = self.encode(&mut buf);
+        buf
+    }
+
+    /// Returns the header of a command packet of this command's kind, without a Class
+    /// Identifier or timestamps
+    ///
+    /// Acknowledge packets set header bit 26, the trailer flag `t` of data packets, and
+    /// cancellation packets set bit 24, the least significant of the `indicators`.
+    pub fn header(&self) -> Header {
+        Header {
+            packet_type: VitaPacketType(VitaPacketType::COMMAND),
+            c: false,
+            t: self.kind.is_ack(),
+            indicators: (self.kind == CommandKind::Cancellation) as u8,
+            tsi: Tsi::TSI_NONE,
+            tsf: Tsf::TSF_NONE,
+            packet_count: 0,
+            packet_size: 0,
+        }
+    }
+
+    /// Returns a command packet for stream `stream_id` with the [`Command::header`] of this
+    /// command and `encoded`, the command as encoded by [`Command::encode`], as its payload
+    pub fn packet<'b>(&self, stream_id: u32, encoded: &'b [u8]) -> VrtPacket<'b> {
+        VrtPacket {
+            header: self.header(),
+            stream_id: Some(stream_id),
+            class_id: None,
+            tsi: None,
+            tsf: None,
+            data_payload: encoded,
+            trailer: None,
+        }
+    }
+
+    /// Returns the command with the Timing Control `timing` in its CAM field
+    pub fn with_timing(self, timing: TimingControl) -> Self {
+        Command {
+            cam: self.cam.with_timing(timing),
+            ..self
+        }
+    }
+
+    /// Returns a command packet as [`Command::packet`] does, timestamped `at`, the time at
+    /// which the controllee is to execute it as allowed by the [`TimingControl`] of the command
+    pub fn packet_at<'b>(
+        &self,
+        stream_id: u32,
+        encoded: &'b [u8],
+        at: &Timestamp,
+    ) -> VrtPacket<'b> {
+        let mut packet = self.packet(stream_id, encoded);
+        packet.header.tsi = at.tsi;
+        packet.header.tsf = at.tsf;
+        packet.tsi = Some(at.integer).filter(|_| at.tsi != Tsi::TSI_NONE);
+        packet.tsf = Some(at.fractional).filter(|_| at.tsf != Tsf::TSF_NONE);
+        packet
+    }
+
+    /// Returns the Context Indicator Field 0 word at the start of the payload of a control,
+    /// cancellation or query-state acknowledge command
+    pub fn cif0(&self) -> Option<u32> {
+        ContextFields::new(self.payload).cif0()
+    }
+
+    /// Returns the context fields of a control or query-state acknowledge command, or no
+    /// fields for other commands
+    ///
+    /// The fields of a query-state acknowledge report the controllee's state, and are laid
+    /// out as in a context packet.
+    pub fn context_fields(&self) -> ContextFields<'a> {
+        match self.kind {
+            CommandKind::Control | CommandKind::QueryAck => ContextFields::new(self.payload),
+            _ => ContextFields::new(&[]),
+        }
+    }
+
+    /// Returns the state reported by a query-state acknowledge, or None for other commands
+    pub fn state(&self) -> Option<SignalContext> {
+        if self.kind != CommandKind::QueryAck {
+            return None;
+        }
+        let mut state = SignalContext::default();
+        state.update_fields(self.context_fields());
+        Some(state)
+    }
+
+    /// Returns the cancellation of this control command
+    ///
+    /// The cancellation echoes the Message ID and identifiers of the control, which is how
+    /// the controllee finds the control to cancel, and carries only its Context Indicator
+    /// Field words, without their fields, announcing the fields whose change is cancelled.
+    /// The cancellation packet must also carry the Stream Identifier of the control packet.
+    pub fn cancellation(&self) -> Command<'a> {
+        let fields = ContextFields::new(self.payload);
+        let words = fields.cif0().map_or(0, |_| 1 + fields.extensions().count());
+        Command {
+            kind: CommandKind::Cancellation,
+            payload: self.payload.get(..4 * words).unwrap_or(self.payload),
+            ..*self
+        }
+    }
+
+    /// Returns an acknowledge of kind `kind` of this control command, reporting no warnings or
+    /// errors
+    ///
+    /// The acknowledge echoes the Message ID and identifiers of the control. The acknowledge
+    /// packet must also carry the Stream Identifier of the control packet.
+    pub fn acknowledge(&self, kind: CommandKind) -> Command<'a> {
+        let requests = ControlAckMode::VALIDATION
+            | ControlAckMode::EXECUTION
+            | ControlAckMode::STATE
+            | ControlAckMode::WARNINGS
+            | ControlAckMode::ERRORS;
+        let bit = match kind {
+            CommandKind::ValidationAck => ControlAckMode::VALIDATION,
+            CommandKind::ExecutionAck => ControlAckMode::EXECUTION,
+            CommandKind::QueryAck => ControlAckMode::STATE,
+            _ => 0,
+        };
+        Command {
+            kind,
+            cam: ControlAckMode(self.cam.0 & !requests | bit),
+            payload: &[],
+            ..*self
+        }
+    }
+
+    /// Returns true if this is an acknowledge of the control command `control`: both share
+    /// the Message ID and the Controllee and Controller Identifiers
+    pub fn acknowledges(&self, control: &Command<'_>) -> bool {
+        self.kind.is_ack() && control.kind == CommandKind::Control && self.echoes(control)
+    }
+
+    /// Returns the warnings and errors reported by a validation or execution acknowledge, or
+    /// None for other commands and for acknowledges too short for the responses announced
+    ///
+    /// The Warning and Error Indicator Field words follow the identifiers, each with the
+    /// extension words it announces, and are followed by one response word for each field
+    /// announced. Only the fields announced by the first indicator word of each are returned;
+    /// the responses to the fields of the extension words are skipped.
+    pub fn responses(&self) -> Option<AckResponses<'a>> {
+        if !matches!(
+            self.kind,
+            CommandKind::ValidationAck | CommandKind::ExecutionAck
+        ) {
+            return None;
+        }
+        let word = |at: usize| {
+            self.payload
+                .get(at..at + 4)
+                .map(|w| u32::from_be_bytes([w[0], w[1], w[2], w[3]]))
+        };
+        let mut offset = 0;
+        let mut indicators = [None, None];
+        for (slot, flag) in indicators
+            .iter_mut()
+            .zip([ControlAckMode::WARNINGS, ControlAckMode::ERRORS])
+        {
+            if !self.cam.contains(flag) {
+                continue;
+            }
+            let indicator = word(offset)?;
+            offset += 4;
+            let mut skipped = 0;
+            for bit in CIF_EXTENSION_BITS {
+                if indicator >> bit & 1 == 1 {
+                    // the attributes of CIF7 have no responses of their own
+                    skipped += word(offset)?.count_ones() as usize * (bit != 7) as usize;
+                    offset += 4;
+                }
+            }
+            *slot = Some((indicator, skipped));
+        }
+        let mut responses = [None, None];
+        for (slot, indicators) in responses.iter_mut().zip(indicators) {
+            let Some((indicator, skipped)) = indicators else {
+                continue;
+            };
+            let (rest, words) =
+                parse_vrt_warning_error_responses(indicator, self.payload.get(offset..)?).ok()?;
+            offset = self.payload.len() - rest.len() + 4 * skipped;
+            *slot = Some(words);
+        }
+        self.payload.get(..offset)?;
+        let [warnings, errors] = responses;
+        Some(AckResponses { warnings, errors })
+    }
+
+    fn echoes(&self, command: &Command<'_>) -> bool {
+        self.message_id == command.message_id
+            && self.controllee == command.controllee
+            && self.controller == command.controller
+    }
+
+    /// Returns true if this is a cancellation of the control command `control`: both share
+    /// the Message ID and the Controllee and Controller Identifiers
+    pub fn cancels(&self, control: &Command<'_>) -> bool {
+        self.kind == CommandKind::Cancellation
+            && control.kind == CommandKind::Control
+            && self.echoes(control)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// The warnings and errors reported by a validation or execution acknowledge, as returned by
+/// [`Command::responses`]
+pub struct AckResponses<'a> {
+    /// The Warning Indicator Field 0 word and the response words of its fields, if the
+    /// acknowledge reports warnings
+    pub warnings: Option<WarningErrorResponses<'a>>,
+    /// The Error Indicator Field 0 word and the response words of its fields, if the
+    /// acknowledge reports errors
+    pub errors: Option<WarningErrorResponses<'a>>,
+}
+
+impl AckResponses<'_> {
+    /// Returns true if an error is reported for any field
+    pub fn has_errors(&self) -> bool {
+        self.errors.is_some_and(|errors| !errors.is_empty())
+    }
+}
+
+impl<'a> VrtPacket<'a> {
+    /// Returns the command of a VITA 49.2 command packet, or None for other packets and for
+    /// command packets which [`Command::parse`] rejects
+    pub fn command(&self) -> Option<Command<'a>> {
+        match self.header.packet_type.is_command() {
+            true => Command::parse(&self.header, self.data_payload).ok(),
+            false => None,
+        }
+    }
+}
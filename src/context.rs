@@ -0,0 +1,452 @@
+//! Decoding of the Context Indicator Field 0 fields of context packets.
+//!
+//! [`ContextFields`] walks the fixed-size fields announced by the Context Indicator Field 0
+//! word at the start of a context packet payload, and each [`ContextField`] decodes to a
+//! [`ContextValue`] in the field's unit, or to an exact [`FixedPoint`] value without floating
+//! point. Decoding needs neither the standard library nor an allocator.
+
+use crate::vrt::VrtPacket;
+use core::fmt;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// A fixed-size field announced by Context Indicator Field 0, in the order the fields follow
+/// the indicator words
+pub enum Cif0Field {
+    /// Reference Point Identifier (bit 30)
+    ReferencePointId,
+    /// Bandwidth (bit 29)
+    Bandwidth,
+    /// IF Reference Frequency (bit 28)
+    IfReferenceFrequency,
+    /// RF Reference Frequency (bit 27)
+    RfReferenceFrequency,
+    /// RF Reference Frequency Offset (bit 26)
+    RfReferenceFrequencyOffset,
+    /// IF Band Offset (bit 25)
+    IfBandOffset,
+    /// Reference Level (bit 24)
+    ReferenceLevel,
+    /// Gain, the sum of its two stages (bit 23)
+    Gain,
+    /// Over-range Count (bit 22)
+    OverRangeCount,
+    /// Sample Rate (bit 21)
+    SampleRate,
+    /// Timestamp Adjustment (bit 20)
+    TimestampAdjustment,
+    /// Timestamp Calibration Time (bit 19)
+    TimestampCalibrationTime,
+    /// Temperature (bit 18)
+    Temperature,
+    /// Device Identifier (bit 17)
+    DeviceId,
+    /// State and Event Indicators (bit 16)
+    StateEventIndicators,
+    /// Data Packet Payload Format (bit 15)
+    DataPayloadFormat,
+    /// Formatted GPS (bit 14)
+    FormattedGps,
+    /// Formatted INS (bit 13)
+    FormattedIns,
+    /// ECEF Ephemeris (bit 12)
+    EcefEphemeris,
+    /// Relative Ephemeris (bit 11)
+    RelativeEphemeris,
+    /// Ephemeris Reference Identifier (bit 10)
+    EphemerisReferenceId,
+}
+
+impl Cif0Field {
+    /// Every fixed-size field, from bit 30 down to the last one before the variable-length
+    /// fields
+    pub const ALL: [Cif0Field; 21] = [
+        Self::ReferencePointId,
+        Self::Bandwidth,
+        Self::IfReferenceFrequency,
+        Self::RfReferenceFrequency,
+        Self::RfReferenceFrequencyOffset,
+        Self::IfBandOffset,
+        Self::ReferenceLevel,
+        Self::Gain,
+        Self::OverRangeCount,
+        Self::SampleRate,
+        Self::TimestampAdjustment,
+        Self::TimestampCalibrationTime,
+        Self::Temperature,
+        Self::DeviceId,
+        Self::StateEventIndicators,
+        Self::DataPayloadFormat,
+        Self::FormattedGps,
+        Self::FormattedIns,
+        Self::EcefEphemeris,
+        Self::RelativeEphemeris,
+        Self::EphemerisReferenceId,
+    ];
+
+    /// Returns the field's bit in Context Indicator Field 0
+    pub fn bit(self) -> u32 {
+        30 - self as u32
+    }
+
+    /// Returns the field with bit `bit` in Context Indicator Field 0, if it is a fixed-size
+    /// field
+    pub fn from_bit(bit: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|field| field.bit() == bit)
+    }
+
+    /// Returns the field's size in 32-bit words
+    pub fn words(self) -> usize {
+        match self {
+            Self::ReferencePointId
+            | Self::ReferenceLevel
+            | Self::Gain
+            | Self::OverRangeCount
+            | Self::TimestampCalibrationTime
+            | Self::Temperature
+            | Self::StateEventIndicators
+            | Self::EphemerisReferenceId => 1,
+            Self::FormattedGps | Self::FormattedIns => 11,
+            Self::EcefEphemeris | Self::RelativeEphemeris => 13,
+            _ => 2,
+        }
+    }
+
+    /// Returns the field's name, such as `Sample Rate`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ReferencePointId => "Reference Point Identifier",
+            Self::Bandwidth => "Bandwidth",
+            Self::IfReferenceFrequency => "IF Reference Frequency",
+            Self::RfReferenceFrequency => "RF Reference Frequency",
+            Self::RfReferenceFrequencyOffset => "RF Reference Frequency Offset",
+            Self::IfBandOffset => "IF Band Offset",
+            Self::ReferenceLevel => "Reference Level",
+            Self::Gain => "Gain",
+            Self::OverRangeCount => "Over-range Count",
+            Self::SampleRate => "Sample Rate",
+            Self::TimestampAdjustment => "Timestamp Adjustment",
+            Self::TimestampCalibrationTime => "Timestamp Calibration Time",
+            Self::Temperature => "Temperature",
+            Self::DeviceId => "Device Identifier",
+            Self::StateEventIndicators => "State and Event Indicators",
+            Self::DataPayloadFormat => "Data Packet Payload Format",
+            Self::FormattedGps => "Formatted GPS",
+            Self::FormattedIns => "Formatted INS",
+            Self::EcefEphemeris => "ECEF Ephemeris",
+            Self::RelativeEphemeris => "Relative Ephemeris",
+            Self::EphemerisReferenceId => "Ephemeris Reference Identifier",
+        }
+    }
+}
+
+/// Context Indicator Field 0 bits announcing the CIF1, CIF2, CIF3 and CIF7 words, which follow
+/// CIF0 in this order before the CIF0 fields
+pub(crate) const CIF_EXTENSION_BITS: [u32; 4] = [1, 2, 3, 7];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// Decoded value of a context field
+pub enum ContextValue {
+    /// A frequency or rate in Hz
+    Hertz(f64),
+    /// A power level in dBm
+    Dbm(f64),
+    /// A gain in dB
+    Db(f64),
+    /// A temperature in degrees Celsius
+    Celsius(f64),
+    /// A time in femtoseconds
+    Femtoseconds(i64),
+    /// A count or integer-seconds time
+    Integer(u64),
+    /// A field of flags, codes or identifiers
+    Bits(u64),
+}
+
+impl ContextValue {
+    /// Returns the value's unit, such as `Hz`, or None for integers and bit fields
+    pub fn unit(&self) -> Option<&'static str> {
+        match self {
+            Self::Hertz(_) => Some("Hz"),
+            Self::Dbm(_) => Some("dBm"),
+            Self::Db(_) => Some("dB"),
+            Self::Celsius(_) => Some("degC"),
+            Self::Femtoseconds(_) => Some("fs"),
+            Self::Integer(_) | Self::Bits(_) => None,
+        }
+    }
+
+    /// Returns the value as a float in its unit, or None for integers and bit fields
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::Hertz(v) | Self::Dbm(v) | Self::Db(v) | Self::Celsius(v) => Some(v),
+            Self::Femtoseconds(v) => Some(v as f64),
+            Self::Integer(_) | Self::Bits(_) => None,
+        }
+    }
+}
+
+/// Formats the value with its unit, such as `1000000 Hz`, integers in decimal and bit fields
+/// in hexadecimal
+impl fmt::Display for ContextValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hertz(v) | Self::Dbm(v) | Self::Db(v) | Self::Celsius(v) => {
+                write!(f, "{} {}", v, self.unit().unwrap_or_default())
+            }
+            Self::Femtoseconds(v) => write!(f, "{} fs", v),
+            Self::Integer(v) => write!(f, "{}", v),
+            Self::Bits(v) => write!(f, "{:#x}", v),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// A context field of a context packet payload
+pub struct ContextField<'a> {
+    /// The field
+    pub field: Cif0Field,
+    /// Offset of the field in bytes from the start of the payload
+    pub offset: usize,
+    /// The field's bytes, [`Cif0Field::words`] words of them
+    pub bytes: &'a [u8],
+}
+
+impl ContextField<'_> {
+    /// Returns the field as a big-endian integer, or None for fields wider than 64 bits
+    pub fn raw(&self) -> Option<u64> {
+        match self.bytes.len() {
+            4 | 8 => Some(self.bytes.iter().fold(0, |acc, b| acc << 8 | *b as u64)),
+            _ => None,
+        }
+    }
+
+    /// Returns the exact fixed-point value of a field with a fractional part: the frequency
+    /// and rate fields, Reference Level, Gain and Temperature
+    ///
+    /// Unlike [`ContextField::value`], this needs no floating point, for targets without a
+    /// floating-point unit. Integer and bit fields are returned by [`ContextField::raw`].
+    pub fn fixed(&self) -> Option<FixedPoint> {
+        let raw = self.raw()?;
+        let radix7 = |raw: u64| raw as u16 as i16 as i64;
+        let (raw, radix) = match self.field {
+            Cif0Field::Bandwidth
+            | Cif0Field::IfReferenceFrequency
+            | Cif0Field::RfReferenceFrequency
+            | Cif0Field::RfReferenceFrequencyOffset
+            | Cif0Field::IfBandOffset
+            | Cif0Field::SampleRate => (raw as i64, 20),
+            Cif0Field::ReferenceLevel => (radix7(raw), 7),
+            // the sum of the stage 1 and stage 2 gains
+            Cif0Field::Gain => (radix7(raw) + radix7(raw >> 16), 7),
+            Cif0Field::Temperature => (raw as u16 as i16 as i64, 6),
+            _ => return None,
+        };
+        Some(FixedPoint { raw, radix })
+    }
+
+    /// Decodes the field, or returns None for fields wider than 64 bits
+    pub fn value(&self) -> Option<ContextValue> {
+        let raw = self.raw()?;
+        let fixed = self.fixed().map_or(0.0, FixedPoint::to_f64);
+        Some(match self.field {
+            Cif0Field::Bandwidth
+            | Cif0Field::IfReferenceFrequency
+            | Cif0Field::RfReferenceFrequency
+            | Cif0Field::RfReferenceFrequencyOffset
+            | Cif0Field::IfBandOffset
+            | Cif0Field::SampleRate => ContextValue::Hertz(fixed),
+            Cif0Field::ReferenceLevel => ContextValue::Dbm(fixed),
+            Cif0Field::Gain => ContextValue::Db(fixed),
+            Cif0Field::Temperature => ContextValue::Celsius(fixed),
+            Cif0Field::TimestampAdjustment => ContextValue::Femtoseconds(raw as i64),
+            Cif0Field::OverRangeCount | Cif0Field::TimestampCalibrationTime => {
+                ContextValue::Integer(raw)
+            }
+            _ => ContextValue::Bits(raw),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// A fixed-point context field value, `raw` / 2^`radix` in the field's unit
+pub struct FixedPoint {
+    /// The two's-complement value, scaled by 2^`radix`
+    pub raw: i64,
+    /// Number of bits to the right of the radix point
+    pub radix: u32,
+}
+
+impl FixedPoint {
+    /// Returns the value rounded down to a whole number of units
+    pub fn integer(self) -> i64 {
+        self.raw >> self.radix
+    }
+
+    /// Returns the value multiplied by `scale` and rounded down, such as the frequency in mHz
+    /// for a `scale` of 1000, or None if the result does not fit in an `i64`
+    pub fn scaled(self, scale: i64) -> Option<i64> {
+        i64::try_from((self.raw as i128 * scale as i128) >> self.radix).ok()
+    }
+
+    /// Returns the value as a float
+    pub fn to_f64(self) -> f64 {
+        self.raw as f64 / (1u64 << self.radix) as f64
+    }
+}
+
+/// Iterator over the context fields of a context packet payload
+///
+/// Yields the fixed-size fields announced by the Context Indicator Field 0 word at the start
+/// of the payload, in payload order, skipping the CIF1, CIF2, CIF3 and CIF7 words which follow
+/// it. Iteration stops at the first field which does not fit in the payload; the
+/// variable-length fields, bits 9 and 8, and the fields of the other indicator words are not
+/// decoded.
+#[derive(Clone, Debug)]
+pub struct ContextFields<'a> {
+    payload: &'a [u8],
+    cif0: Option<u32>,
+    offset: usize,
+    next: usize,
+}
+
+impl<'a> ContextFields<'a> {
+    /// Creates an iterator over the fields of `payload`, which starts with the Context
+    /// Indicator Field 0 word
+    pub fn new(payload: &'a [u8]) -> Self {
+        let cif0 = payload
+            .get(..4)
+            .map(|word| u32::from_be_bytes([word[0], word[1], word[2], word[3]]));
+        let extensions = CIF_EXTENSION_BITS
+            .iter()
+            .filter(|bit| cif0.is_some_and(|cif0| cif0 >> *bit & 1 == 1))
+            .count();
+        ContextFields {
+            payload,
+            cif0,
+            offset: 4 * (1 + extensions),
+            next: 0,
+        }
+    }
+
+    /// Returns the Context Indicator Field 0 word, or None if the payload is shorter than a
+    /// word
+    pub fn cif0(&self) -> Option<u32> {
+        self.cif0
+    }
+
+    /// Returns the CIF1, CIF2, CIF3 and CIF7 words announced by Context Indicator Field 0,
+    /// each with its announcing bit
+    pub fn extensions(&self) -> impl Iterator<Item = (u32, u32)> + 'a {
+        let cif0 = self.cif0.unwrap_or(0);
+        CIF_EXTENSION_BITS
+            .into_iter()
+            .filter(move |bit| cif0 >> bit & 1 == 1)
+            .zip(self.payload.get(4..).unwrap_or_default().chunks_exact(4))
+            .map(|(bit, word)| {
+                (
+                    bit,
+                    u32::from_be_bytes([word[0], word[1], word[2], word[3]]),
+                )
+            })
+    }
+
+    /// Returns the offset in bytes of the payload following the fields yielded so far
+    pub fn offset(&self) -> usize {
+        self.offset.min(self.payload.len())
+    }
+
+    /// Returns the field `field` if the payload carries it
+    pub fn get(&self, field: Cif0Field) -> Option<ContextField<'a>> {
+        self.clone().find(|f| f.field == field)
+    }
+}
+
+impl<'a> Iterator for ContextFields<'a> {
+    type Item = ContextField<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cif0 = self.cif0?;
+        while let Some(field) = Cif0Field::ALL.get(self.next).copied() {
+            self.next += 1;
+            if cif0 >> field.bit() & 1 == 0 {
+                continue;
+            }
+            let len = 4 * field.words();
+            let Some(bytes) = self.payload.get(self.offset..self.offset + len) else {
+                self.next = Cif0Field::ALL.len();
+                return None;
+            };
+            let field = ContextField {
+                field,
+                offset: self.offset,
+                bytes,
+            };
+            self.offset += len;
+            return Some(field);
+        }
+        None
+    }
+}
+
+impl<'a> VrtPacket<'a> {
+    /// Returns the Context Indicator Field 0 fields of a context packet, or no fields for
+    /// other packets
+    pub fn context_fields(&self) -> ContextFields<'a> {
+        match self.header.packet_type.is_context() {
+            true => ContextFields::new(self.data_payload),
+            false => ContextFields::new(&[]),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// The signal description accumulated from the context packets of a context stream
+///
+/// Context packets need only carry the fields which changed, so each field keeps the most
+/// recent value received for it, or None if no context packet has carried it yet.
+pub struct SignalContext {
+    /// Bandwidth in Hz
+    pub bandwidth: Option<f64>,
+    /// IF Reference Frequency in Hz
+    pub if_frequency: Option<f64>,
+    /// RF Reference Frequency in Hz
+    pub rf_frequency: Option<f64>,
+    /// Reference Level in dBm
+    pub reference_level: Option<f64>,
+    /// Gain in dB, the sum of both stages
+    pub gain: Option<f64>,
+    /// Sample Rate in Hz
+    pub sample_rate: Option<f64>,
+    /// Temperature in degrees Celsius
+    pub temperature: Option<f64>,
+}
+
+impl SignalContext {
+    /// Updates the fields carried by the context packet `packet`, keeping the others
+    pub fn update(&mut self, packet: &VrtPacket<'_>) {
+        self.update_fields(packet.context_fields());
+    }
+
+    /// Updates the fields in `fields`, keeping the others, for payloads with context fields
+    /// other than those of context packets, such as query-state acknowledges
+    pub fn update_fields(&mut self, fields: ContextFields<'_>) {
+        for field in fields {
+            let slot = match field.field {
+                Cif0Field::Bandwidth => &mut self.bandwidth,
+                Cif0Field::IfReferenceFrequency => &mut self.if_frequency,
+                Cif0Field::RfReferenceFrequency => &mut self.rf_frequency,
+                Cif0Field::ReferenceLevel => &mut self.reference_level,
+                Cif0Field::Gain => &mut self.gain,
+                Cif0Field::SampleRate => &mut self.sample_rate,
+                Cif0Field::Temperature => &mut self.temperature,
+                _ => continue,
+            };
+            *slot = field.fixed().map(FixedPoint::to_f64);
+        }
+    }
+}
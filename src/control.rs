@@ -0,0 +1,305 @@
+//! Request/response sessions for VITA 49.2 command flows.
+
+use crate::command::{ActionMode, Command, CommandId, CommandKind, ControlAckMode, TimingControl};
+use crate::context::SignalContext;
+use crate::vrt::{
+    Timestamp, VrtPacket, VrtPacketOwned, WarningErrorResponse, WarningErrorResponses,
+};
+use std::fmt;
+use std::io;
+use std::time::{Duration, Instant};
+
+/// An acknowledge received by a [`ControlSession`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ControlAck {
+    /// Kind of the acknowledge
+    pub kind: CommandKind,
+    /// Control/Acknowledge Mode field of the acknowledge
+    pub cam: ControlAckMode,
+    /// Warning response of each field with a warning, with its Context Indicator Field 0 bit
+    pub warnings: Vec<(u32, WarningErrorResponse)>,
+    /// Error response of each field with an error, with its Context Indicator Field 0 bit
+    pub errors: Vec<(u32, WarningErrorResponse)>,
+    /// The acknowledge packet
+    pub packet: VrtPacketOwned,
+}
+
+impl ControlAck {
+    /// Returns the state reported by a query-state acknowledge, or None for other
+    /// acknowledges
+    pub fn state(&self) -> Option<SignalContext> {
+        self.packet.as_packet().command()?.state()
+    }
+}
+
+#[derive(Debug)]
+/// Error returned by a [`ControlSession`]
+pub enum ControlError {
+    /// Sending or receiving failed.
+    Io(io::Error),
+    /// No acknowledge arrived within the timeout of any attempt.
+    Timeout {
+        /// Number of times the control packet was sent
+        attempts: u32,
+    },
+    /// The controllee acknowledged the control packet with errors.
+    Rejected(Box<ControlAck>),
+}
+
+impl fmt::Display for ControlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ControlError::Io(e) => write!(f, "control packet I/O failed: {}", e),
+            ControlError::Timeout { attempts } => {
+                write!(f, "no acknowledge after {} attempts", attempts)
+            }
+            ControlError::Rejected(ack) => {
+                write!(
+                    f,
+                    "control packet rejected with {} errors",
+                    ack.errors.len()
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ControlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ControlError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ControlError {
+    fn from(e: io::Error) -> Self {
+        ControlError::Io(e)
+    }
+}
+
+/// Request/Response Session for VITA 49.2 Control Packets
+///
+/// Sends control packets to a controllee through `send` and awaits their acknowledges from
+/// `recv`, which returns the next packet received within the timeout given, or None if none
+/// arrives. Each control packet gets the next Message ID, and acknowledges are matched to it
+/// by their Stream Identifier, Message ID and identifiers, so stale acknowledges of earlier
+/// attempts and unrelated packets are skipped. If no matching acknowledge arrives within the
+/// timeout, the packet is sent again, with the same Message ID, up to the number of retries.
+///
+/// Errors returned by `recv` of kind `TimedOut` or `WouldBlock`, as returned by sockets with
+/// a read timeout, count as timeouts; other errors end the request.
+#[derive(Debug)]
+pub struct ControlSession<S, R> {
+    send: S,
+    recv: R,
+    stream_id: u32,
+    controllee: Option<CommandId>,
+    controller: Option<CommandId>,
+    timeout: Duration,
+    retries: u32,
+    message_id: u32,
+    skipped: u64,
+}
+
+impl<S, R> ControlSession<S, R>
+where
+    S: FnMut(&VrtPacket<'_>) -> io::Result<()>,
+    R: FnMut(Duration) -> io::Result<Option<VrtPacketOwned>>,
+{
+    /// Creates a new ControlSession sending control packets for stream `stream_id`, with a
+    /// timeout of one second and two retries
+    pub fn new(stream_id: u32, send: S, recv: R) -> Self {
+        ControlSession {
+            send,
+            recv,
+            stream_id,
+            controllee: None,
+            controller: None,
+            timeout: Duration::from_secs(1),
+            retries: 2,
+            message_id: 0,
+            skipped: 0,
+        }
+    }
+
+    /// Sets the time to wait for an acknowledge before sending the packet again
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the number of times a packet is sent again when no acknowledge arrives
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Sets the Controllee Identifier of the control packets
+    pub fn with_controllee(mut self, controllee: CommandId) -> Self {
+        self.controllee = Some(controllee);
+        self
+    }
+
+    /// Sets the Controller Identifier of the control packets
+    pub fn with_controller(mut self, controller: CommandId) -> Self {
+        self.controller = Some(controller);
+        self
+    }
+
+    /// Returns the number of received packets which did not acknowledge the packet awaited
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    /// Requests execution of the Context Indicator Field words and fields `fields`, returning
+    /// the execution acknowledge
+    ///
+    /// Warnings and errors are requested in the acknowledge, and an acknowledge with errors
+    /// is returned as [`ControlError::Rejected`].
+    pub fn execute(&mut self, fields: &[u8]) -> Result<ControlAck, ControlError> {
+        self.request(Self::execution(fields), None)
+    }
+
+    /// Requests execution of `fields` at `at`, as allowed by `timing`, as described for
+    /// [`ControlSession::execute`]
+    pub fn execute_at(
+        &mut self,
+        fields: &[u8],
+        at: &Timestamp,
+        timing: TimingControl,
+    ) -> Result<ControlAck, ControlError> {
+        self.request(Self::execution(fields).with_timing(timing), Some(at))
+    }
+
+    /// Queries the state of the fields announced by the Context Indicator Field 0 word
+    /// `cif0`, returning the state reported by the query-state acknowledge
+    ///
+    /// The control packet takes no action and carries only the indicator word, without
+    /// fields.
+    pub fn query(&mut self, cif0: u32) -> Result<SignalContext, ControlError> {
+        let indicators = cif0.to_be_bytes();
+        let mut command = Command::control(0, &indicators);
+        command.cam = command
+            .cam
+            .with_action(ActionMode::NoAction)
+            .with(ControlAckMode::STATE);
+        let ack = self.request(command, None)?;
+        Ok(ack.state().unwrap_or_default())
+    }
+
+    fn execution(fields: &[u8]) -> Command<'_> {
+        let mut command = Command::control(0, fields);
+        command.cam = command
+            .cam
+            .with_action(ActionMode::Execute)
+            .with(ControlAckMode::EXECUTION | ControlAckMode::WARNINGS | ControlAckMode::ERRORS);
+        command
+    }
+
+    /// Sends the control command `command`, timestamped `at` if given, and returns the
+    /// acknowledge requested by its CAM field: the execution acknowledge if one is requested,
+    /// else the validation or query-state acknowledge
+    ///
+    /// The Message ID and identifiers of `command` are replaced by those of the session. If
+    /// the command requests no acknowledge, it is sent once and
+    /// [`ControlError::Timeout`] is returned without waiting.
+    pub fn request(
+        &mut self,
+        command: Command<'_>,
+        at: Option<&Timestamp>,
+    ) -> Result<ControlAck, ControlError> {
+        self.message_id = self.message_id.wrapping_add(1);
+        let command = Command {
+            kind: CommandKind::Control,
+            message_id: self.message_id,
+            controllee: self.controllee,
+            controller: self.controller,
+            ..command
+        };
+        let expected = [
+            (ControlAckMode::EXECUTION, CommandKind::ExecutionAck),
+            (ControlAckMode::VALIDATION, CommandKind::ValidationAck),
+            (ControlAckMode::STATE, CommandKind::QueryAck),
+        ]
+        .into_iter()
+        .find(|(bit, _)| command.cam.contains(*bit))
+        .map(|(_, kind)| kind);
+        let encoded = command.to_vec();
+        let packet = match at {
+            Some(at) => command.packet_at(self.stream_id, &encoded, at),
+            None => command.packet(self.stream_id, &encoded),
+        };
+        let mut attempts = 0;
+        loop {
+            (self.send)(&packet)?;
+            attempts += 1;
+            if let Some(expected) = expected {
+                if let Some(ack) = self.await_ack(&command, expected)? {
+                    return match ack.errors.is_empty() {
+                        true => Ok(ack),
+                        false => Err(ControlError::Rejected(Box::new(ack))),
+                    };
+                }
+            }
+            if expected.is_none() || attempts > self.retries {
+                return Err(ControlError::Timeout { attempts });
+            }
+        }
+    }
+
+    /// Receives packets until the acknowledge of kind `expected` of `command` arrives, or
+    /// returns None when the timeout expires
+    fn await_ack(
+        &mut self,
+        command: &Command<'_>,
+        expected: CommandKind,
+    ) -> Result<Option<ControlAck>, ControlError> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(None);
+            }
+            let packet = match (self.recv)(remaining) {
+                Ok(Some(packet)) => packet,
+                Ok(None) => return Ok(None),
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock
+                    ) =>
+                {
+                    return Ok(None)
+                }
+                Err(e) => return Err(e.into()),
+            };
+            let view = packet.as_packet();
+            let ack = match view.command() {
+                Some(ack)
+                    if view.stream_id == Some(self.stream_id)
+                        && ack.kind == expected
+                        && ack.acknowledges(command) =>
+                {
+                    ack
+                }
+                _ => {
+                    self.skipped += 1;
+                    continue;
+                }
+            };
+            let responses = ack.responses();
+            let collect = |words: Option<WarningErrorResponses<'_>>| {
+                words.map_or_else(Vec::new, |words| words.iter().collect())
+            };
+            return Ok(Some(ControlAck {
+                kind: ack.kind,
+                cam: ack.cam,
+                warnings: collect(responses.and_then(|r| r.warnings)),
+                errors: collect(responses.and_then(|r| r.errors)),
+                packet: packet.clone(),
+            }));
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! CSV export of context fields over time.
+
+use crate::context::{Cif0Field, ContextValue};
+use crate::dissect::snake_case;
+use crate::vrt::{Tsf, VrtPacket};
+use std::io::{self, Write};
+
+/// Context fields exported by default: RF reference frequency, gain, sample rate and
+/// temperature
+pub const DEFAULT_CSV_COLUMNS: [&str; 4] = [
+    "rf_reference_frequency",
+    "gain",
+    "sample_rate",
+    "temperature",
+];
+
+/// Context CSV Writer
+///
+/// Writes a CSV time series of selected context fields: a header row, then one row per
+/// context packet carrying at least one of the fields, with the packet's time and Stream
+/// Identifier followed by the field values. Data packets and context packets without any of
+/// the fields are skipped.
+///
+/// Fields are named as in the `context` object of the JSON Lines output, which is the snake_case
+/// name of the field decoded by [`VrtPacket::context_fields`], such as `sample_rate` or
+/// `if_reference_frequency`. Values are in the decoded unit, Hz, dB, dBm, degrees Celsius or
+/// femtoseconds, or the raw value for fields without a unit. A cell is empty when the packet does
+/// not carry the field; context packets usually carry only the fields which changed.
+///
+/// The time is the integer-seconds timestamp, with the fractional-seconds timestamp as twelve
+/// decimals when it is a Real Time (Picoseconds) timestamp, and is empty for packets without
+/// a timestamp.
+#[derive(Debug)]
+pub struct ContextCsvWriter<W: Write> {
+    writer: W,
+    columns: Vec<String>,
+    header_written: bool,
+}
+
+impl<W: Write> ContextCsvWriter<W> {
+    /// Creates a new ContextCsvWriter exporting [`DEFAULT_CSV_COLUMNS`] to `writer`
+    pub fn new(writer: W) -> Self {
+        ContextCsvWriter {
+            writer,
+            columns: DEFAULT_CSV_COLUMNS.iter().map(|c| c.to_string()).collect(),
+            header_written: false,
+        }
+    }
+
+    /// Sets the context fields exported, in column order
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidInput`], listing the valid names, if a
+    /// column does not name a context field.
+    pub fn with_columns<S: AsRef<str>>(mut self, columns: &[S]) -> io::Result<Self> {
+        let names: Vec<String> = Cif0Field::ALL
+            .iter()
+            .map(|field| snake_case(field.name()))
+            .collect();
+        if let Some(unknown) = columns
+            .iter()
+            .find(|c| !names.iter().any(|n| n == c.as_ref()))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "unknown context field {:?}, expected one of: {}",
+                    unknown.as_ref(),
+                    names.join(", ")
+                ),
+            ));
+        }
+        self.columns = columns.iter().map(|c| c.as_ref().to_string()).collect();
+        Ok(self)
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        if self.header_written {
+            return Ok(());
+        }
+        write!(self.writer, "time,stream_id")?;
+        for column in &self.columns {
+            write!(self.writer, ",{}", column)?;
+        }
+        writeln!(self.writer)?;
+        self.header_written = true;
+        Ok(())
+    }
+
+    /// Writes the row of `packet`, returning whether it carried any of the fields
+    pub fn write_packet(&mut self, packet: &VrtPacket<'_>) -> io::Result<bool> {
+        self.write_header()?;
+        if !packet.header.packet_type.is_context() {
+            return Ok(false);
+        }
+        let fields: Vec<_> = packet.context_fields().collect();
+        let values: Vec<Option<String>> = self
+            .columns
+            .iter()
+            .map(|column| {
+                let field = fields
+                    .iter()
+                    .find(|f| snake_case(f.field.name()) == *column)?;
+                Some(match field.value()? {
+                    ContextValue::Femtoseconds(v) => v.to_string(),
+                    ContextValue::Integer(v) | ContextValue::Bits(v) => v.to_string(),
+                    ContextValue::Hertz(v)
+                    | ContextValue::Dbm(v)
+                    | ContextValue::Db(v)
+                    | ContextValue::Celsius(v) => v.to_string(),
+                })
+            })
+            .collect();
+        if values.iter().all(Option::is_none) {
+            return Ok(false);
+        }
+
+        match (packet.tsi, packet.header.tsf, packet.tsf) {
+            (Some(tsi), Tsf::TSF_REAL_TIME, Some(tsf)) => {
+                write!(self.writer, "{}.{:012}", tsi, tsf)?
+            }
+            (Some(tsi), _, _) => write!(self.writer, "{}", tsi)?,
+            (None, _, _) => {}
+        }
+        match packet.stream_id {
+            Some(stream_id) => write!(self.writer, ",{:#010x}", stream_id)?,
+            None => write!(self.writer, ",")?,
+        }
+        for value in values {
+            write!(self.writer, ",{}", value.unwrap_or_default())?;
+        }
+        writeln!(self.writer)?;
+        Ok(true)
+    }
+
+    /// Flushes and returns the underlying writer, writing the header row if no packet was
+    /// written
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.write_header()?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
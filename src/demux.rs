@@ -0,0 +1,150 @@
+//! Routing of parsed VRT packets to per-stream handlers.
+
+use crate::vrt::VrtPacket;
+use std::collections::HashMap;
+use std::fmt;
+
+type Handler<'a> = Box<dyn FnMut(&VrtPacket<'_>) + 'a>;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+struct Route {
+    stream_id: u32,
+    class_id: Option<u64>,
+}
+
+/// Stream Demultiplexer
+///
+/// Routes parsed packets to the handler registered for their Stream Identifier. A handler may
+/// also be registered for a Stream Identifier and Class Identifier pair, in which case it takes
+/// precedence over a handler registered for the Stream Identifier alone. Packets which do not
+/// match any route are passed to the fallback handler, if one is set.
+///
+/// Handlers are plain closures; to forward packets to another thread, register a closure which
+/// sends to a channel.
+pub struct Demux<'a> {
+    routes: HashMap<Route, Handler<'a>>,
+    fallback: Option<Handler<'a>>,
+}
+
+impl<'a> Demux<'a> {
+    /// Creates a new Demux with no registered streams
+    pub fn new() -> Self {
+        Demux {
+            routes: HashMap::new(),
+            fallback: None,
+        }
+    }
+
+    /// Registers `handler` for packets with the Stream Identifier `stream_id`, replacing any
+    /// existing handler for that stream.
+    pub fn register<F>(&mut self, stream_id: u32, handler: F)
+    where
+        F: FnMut(&VrtPacket<'_>) + 'a,
+    {
+        let route = Route {
+            stream_id,
+            class_id: None,
+        };
+        self.routes.insert(route, Box::new(handler));
+    }
+
+    /// Registers `handler` for packets with both the Stream Identifier `stream_id` and the
+    /// Class Identifier `class_id`, replacing any existing handler for that pair.
+    pub fn register_with_class_id<F>(&mut self, stream_id: u32, class_id: u64, handler: F)
+    where
+        F: FnMut(&VrtPacket<'_>) + 'a,
+    {
+        let route = Route {
+            stream_id,
+            class_id: Some(class_id),
+        };
+        self.routes.insert(route, Box::new(handler));
+    }
+
+    /// Unregisters the handler for `stream_id`. Returns true if a handler was registered.
+    pub fn unregister(&mut self, stream_id: u32) -> bool {
+        let route = Route {
+            stream_id,
+            class_id: None,
+        };
+        self.routes.remove(&route).is_some()
+    }
+
+    /// Unregisters the handler for the `stream_id` and `class_id` pair. Returns true if a
+    /// handler was registered.
+    pub fn unregister_with_class_id(&mut self, stream_id: u32, class_id: u64) -> bool {
+        let route = Route {
+            stream_id,
+            class_id: Some(class_id),
+        };
+        self.routes.remove(&route).is_some()
+    }
+
+    /// Sets the handler for packets which do not match any registered stream.
+    pub fn set_fallback<F>(&mut self, handler: F)
+    where
+        F: FnMut(&VrtPacket<'_>) + 'a,
+    {
+        self.fallback = Some(Box::new(handler));
+    }
+
+    /// Removes the fallback handler.
+    pub fn clear_fallback(&mut self) {
+        self.fallback = None;
+    }
+
+    /// Returns true if a handler is registered for `stream_id`, with or without a Class Identifier.
+    pub fn is_registered(&self, stream_id: u32) -> bool {
+        self.routes.keys().any(|r| r.stream_id == stream_id)
+    }
+
+    /// Routes `packet` to its handler. Returns false if the packet was not handled by a
+    /// registered stream or the fallback handler.
+    pub fn route(&mut self, packet: &VrtPacket<'_>) -> bool {
+        let handler = packet.stream_id.and_then(|stream_id| {
+            let with_class = packet.class_id.map(|class_id| Route {
+                stream_id,
+                class_id: Some(class_id),
+            });
+            let stream_only = Route {
+                stream_id,
+                class_id: None,
+            };
+            match with_class {
+                Some(r) if self.routes.contains_key(&r) => self.routes.get_mut(&r),
+                _ => self.routes.get_mut(&stream_only),
+            }
+        });
+        let handled = match handler.or(self.fallback.as_mut()) {
+            Some(handler) => {
+                handler(packet);
+                true
+            }
+            None => false,
+        };
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            stream_id = packet.stream_id,
+            class_id = packet.class_id,
+            packet_count = packet.header.packet_count,
+            handled,
+            "routed VRT packet"
+        );
+        handled
+    }
+}
+
+impl Default for Demux<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Demux<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Demux")
+            .field("routes", &self.routes.keys().collect::<Vec<_>>())
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
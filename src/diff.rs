@@ -0,0 +1,259 @@
+//! Comparison of two captures packet by packet.
+
+use crate::context::{Cif0Field, ContextField};
+use crate::vrt::{Timestamp, VrtPacket};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+
+/// Difference between two captures found by [`diff_captures`]
+///
+/// Packets are identified by their index in their capture, counting from zero.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Divergence {
+    /// A packet of capture `a` has no counterpart in capture `b`
+    MissingFromB {
+        /// Index of the packet in capture `a`
+        packet: u64,
+        /// Stream Identifier of the packet
+        stream_id: Option<u32>,
+        /// Timestamp of the packet
+        timestamp: Option<Timestamp>,
+    },
+    /// A packet of capture `b` has no counterpart in capture `a`
+    MissingFromA {
+        /// Index of the packet in capture `b`
+        packet: u64,
+        /// Stream Identifier of the packet
+        stream_id: Option<u32>,
+        /// Timestamp of the packet
+        timestamp: Option<Timestamp>,
+    },
+    /// The payloads of a pair of data packets differ
+    Payload {
+        /// Index of the packet in capture `a`
+        a: u64,
+        /// Index of the packet in capture `b`
+        b: u64,
+        /// Stream Identifier of the packets
+        stream_id: Option<u32>,
+        /// Timestamp of the packets
+        timestamp: Option<Timestamp>,
+        /// Offset of the first differing payload byte, which is the length of the shorter
+        /// payload when one is a prefix of the other
+        offset: usize,
+    },
+    /// The context fields of a pair of context packets differ
+    Context {
+        /// Index of the packet in capture `a`
+        a: u64,
+        /// Index of the packet in capture `b`
+        b: u64,
+        /// Stream Identifier of the packets
+        stream_id: Option<u32>,
+        /// Timestamp of the packets
+        timestamp: Option<Timestamp>,
+        /// Names of the fields, as named by [`Cif0Field::name`], which differ or are present
+        /// in only one of the packets, together with `Context Indicator Field 0` if the
+        /// indicators differ and `Remaining Context Fields` if the fields which are not
+        /// decoded differ
+        fields: Vec<&'static str>,
+    },
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (stream_id, timestamp) = match self {
+            Divergence::MissingFromB {
+                stream_id,
+                timestamp,
+                ..
+            }
+            | Divergence::MissingFromA {
+                stream_id,
+                timestamp,
+                ..
+            }
+            | Divergence::Payload {
+                stream_id,
+                timestamp,
+                ..
+            }
+            | Divergence::Context {
+                stream_id,
+                timestamp,
+                ..
+            } => (stream_id, timestamp),
+        };
+        match stream_id {
+            Some(stream_id) => write!(f, "stream {:#010x}", stream_id)?,
+            None => write!(f, "no stream")?,
+        }
+        if let Some(t) = timestamp {
+            write!(f, " at {}.{:012}", t.integer, t.fractional)?;
+        }
+        match self {
+            Divergence::MissingFromB { packet, .. } => {
+                write!(f, ": packet #{} of a missing from b", packet)
+            }
+            Divergence::MissingFromA { packet, .. } => {
+                write!(f, ": packet #{} of b missing from a", packet)
+            }
+            Divergence::Payload { a, b, offset, .. } => write!(
+                f,
+                ": payloads of packet #{} of a and #{} of b differ at byte {}",
+                a, b, offset
+            ),
+            Divergence::Context { a, b, fields, .. } => write!(
+                f,
+                ": context of packet #{} of a and #{} of b differs in {}",
+                a,
+                b,
+                fields.join(", ")
+            ),
+        }
+    }
+}
+
+/// Result of [`diff_captures`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CaptureDiff {
+    /// Number of packets in capture `a`
+    pub packets_a: u64,
+    /// Number of packets in capture `b`
+    pub packets_b: u64,
+    /// Number of packets of `a` paired with a packet of `b`
+    pub matched: u64,
+    /// Differences found, ordered by stream and then by timestamp
+    pub divergences: Vec<Divergence>,
+}
+
+impl CaptureDiff {
+    /// Returns whether the captures hold the same packets
+    pub fn is_identical(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+/// Key aligning packets: the stream, whether the packet is a context packet, and its timestamp
+type AlignKey = (Option<u32>, bool, Option<(u32, u64)>);
+
+fn align<'a, I>(packets: I) -> (u64, BTreeMap<AlignKey, VecDeque<(u64, VrtPacket<'a>)>>)
+where
+    I: IntoIterator<Item = VrtPacket<'a>>,
+{
+    let mut aligned: BTreeMap<_, VecDeque<_>> = BTreeMap::new();
+    let mut count = 0;
+    for packet in packets {
+        let key = (
+            packet.stream_id,
+            packet.header.packet_type.is_context(),
+            packet.timestamp().map(|t| (t.integer, t.fractional)),
+        );
+        aligned.entry(key).or_default().push_back((count, packet));
+        count += 1;
+    }
+    (count, aligned)
+}
+
+/// Returns the names of the context fields which differ between `a` and `b`, comparing the
+/// decoded fields rather than bit positions, which shift with the optional header fields
+fn context_fields(a: &VrtPacket<'_>, b: &VrtPacket<'_>) -> Vec<&'static str> {
+    let (mut x, mut y) = (a.context_fields(), b.context_fields());
+    let mut names = Vec::new();
+    if x.cif0() != y.cif0() {
+        names.push("Context Indicator Field 0");
+    }
+    let (fields_x, fields_y): (Vec<_>, Vec<_>) = (x.by_ref().collect(), y.by_ref().collect());
+    for field in Cif0Field::ALL {
+        let bytes = |fields: &[ContextField<'_>]| {
+            fields
+                .iter()
+                .find(|f| f.field == field)
+                .map(|f| f.bytes.to_vec())
+        };
+        if bytes(&fields_x) != bytes(&fields_y) {
+            names.push(field.name());
+        }
+    }
+    // the fields which are not decoded
+    if a.data_payload[x.offset()..] != b.data_payload[y.offset()..] {
+        names.push("Remaining Context Fields");
+    }
+    names
+}
+
+/// Compares captures `a` and `b`, such as a capture of a new transmitter and a golden
+/// capture
+///
+/// Packets are aligned by Stream Identifier, data or context packet type, and timestamp;
+/// packets sharing all three, such as packets without timestamps, are paired in capture
+/// order. The payloads of each pair of data packets are compared byte for byte, and the
+/// context fields of each pair of context packets are compared as decoded by
+/// [`VrtPacket::context_fields`]. Header fields such as the packet count, and any arrival times of
+/// the captures, are not compared.
+pub fn diff_captures<'a, 'b, A, B>(a: A, b: B) -> CaptureDiff
+where
+    A: IntoIterator<Item = VrtPacket<'a>>,
+    B: IntoIterator<Item = VrtPacket<'b>>,
+{
+    let (packets_a, mut a) = align(a);
+    let (packets_b, mut b) = align(b);
+    let mut keys: Vec<AlignKey> = a.keys().chain(b.keys()).copied().collect();
+    keys.sort_unstable();
+    keys.dedup();
+
+    let mut diff = CaptureDiff {
+        packets_a,
+        packets_b,
+        ..Default::default()
+    };
+    for key in keys {
+        let mut a = a.remove(&key).unwrap_or_default();
+        let mut b = b.remove(&key).unwrap_or_default();
+        loop {
+            let divergence = match (a.pop_front(), b.pop_front()) {
+                (Some((a, x)), Some((b, y))) => {
+                    diff.matched += 1;
+                    let (stream_id, timestamp) = (x.stream_id, x.timestamp());
+                    if x.data_payload == y.data_payload {
+                        continue;
+                    }
+                    match key.1 {
+                        true => Divergence::Context {
+                            a,
+                            b,
+                            stream_id,
+                            timestamp,
+                            fields: context_fields(&x, &y),
+                        },
+                        false => Divergence::Payload {
+                            a,
+                            b,
+                            stream_id,
+                            timestamp,
+                            offset: x
+                                .data_payload
+                                .iter()
+                                .zip(y.data_payload)
+                                .take_while(|(x, y)| x == y)
+                                .count(),
+                        },
+                    }
+                }
+                (Some((packet, x)), None) => Divergence::MissingFromB {
+                    packet,
+                    stream_id: x.stream_id,
+                    timestamp: x.timestamp(),
+                },
+                (None, Some((packet, y))) => Divergence::MissingFromA {
+                    packet,
+                    stream_id: y.stream_id,
+                    timestamp: y.timestamp(),
+                },
+                (None, None) => break,
+            };
+            diff.divergences.push(divergence);
+        }
+    }
+    diff
+}
@@ -0,0 +1,667 @@
+//! Packets of the DIFI (Digital IF Interoperability) 1.2 standard flow profile.
+//!
+//! A DIFI standard flow is a stream of signal data packets, signal context packets and
+//! version context packets sharing a Stream Identifier. The profile fixes their header
+//! flags, timestamp types and Class Identifier, and the exact set of context fields, so the
+//! packets are represented here by the values which may vary, and converted to and from the
+//! generic packets with [`DifiPacket::to_packet`] and `TryFrom<&VrtPacket>`.
+
+use crate::context::{Cif0Field, ContextFields, FixedPoint};
+use crate::error::Error;
+use crate::validate::Rule;
+use crate::vrt::{radix20, Header, Tsf, Tsi, VitaPacketType, VrtPacket, VrtPacketOwned};
+use std::collections::HashMap;
+
+/// Organizationally Unique Identifier of the DIFI Consortium, in DIFI Class Identifiers
+pub const DIFI_OUI: u32 = 0x6a_621e;
+
+/// Packet Class Code of DIFI signal data packets
+pub const DIFI_DATA_CLASS: u16 = 0x0000;
+/// Packet Class Code of DIFI signal context packets
+pub const DIFI_CONTEXT_CLASS: u16 = 0x0001;
+/// Packet Class Code of DIFI version context packets
+pub const DIFI_VERSION_CLASS: u16 = 0x0004;
+
+/// Context Indicator Field 0 of DIFI signal context packets, without the Context Field
+/// Change Indicator (bit 31)
+pub const DIFI_CONTEXT_CIF0: u32 = 0x7bb9_8000;
+/// Context Indicator Field 0 of DIFI version context packets, without the Context Field
+/// Change Indicator (bit 31)
+pub const DIFI_VERSION_CIF0: u32 = 0x0000_0002;
+/// Context Indicator Field 1 of DIFI version context packets: V49 Spec Compliance and
+/// Version and Build Code
+pub const DIFI_VERSION_CIF1: u32 = 0x0000_000c;
+
+/// V49 Spec Compliance field value of VITA 49.2
+pub const DIFI_V49_2: u32 = 0x0000_0004;
+
+/// Context Field Change Indicator bit of Context Indicator Field 0
+const CHANGE_INDICATOR: u32 = 1 << 31;
+
+/// Size in bytes of the payload of a DIFI signal context packet: CIF0 and 19 words of
+/// fields
+const CONTEXT_PAYLOAD_LEN: usize = 80;
+
+/// Size in bytes of the payload of a DIFI version context packet: CIF0, CIF1 and two fields
+const VERSION_PAYLOAD_LEN: usize = 16;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// Size of the signed complex samples of a DIFI stream
+pub enum DifiItemSize {
+    /// 8-bit I and Q components
+    Bits8,
+    /// 16-bit I and Q components
+    Bits16,
+}
+
+impl DifiItemSize {
+    /// Returns the size of each I or Q component in bits
+    pub fn bits(self) -> u32 {
+        match self {
+            DifiItemSize::Bits8 => 8,
+            DifiItemSize::Bits16 => 16,
+        }
+    }
+
+    /// Returns the Data Packet Payload Format field describing the samples: link-efficient
+    /// packing of complex Cartesian signed fixed-point items
+    pub fn payload_format(self) -> u64 {
+        let size = self.bits() as u64 - 1;
+        (0xa000_0000 | size << 6 | size) << 32
+    }
+
+    /// Returns the item size described by the Data Packet Payload Format field `format`, if
+    /// it is one DIFI allows
+    pub fn from_payload_format(format: u64) -> Option<Self> {
+        [DifiItemSize::Bits8, DifiItemSize::Bits16]
+            .into_iter()
+            .find(|size| size.payload_format() == format)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// A DIFI signal data packet
+pub struct DifiData<'a> {
+    /// Stream Identifier
+    pub stream_id: u32,
+    /// Packet count, modulo 16
+    pub packet_count: u8,
+    /// UTC integer-seconds timestamp
+    pub seconds: u32,
+    /// Real Time (Picoseconds) fractional-seconds timestamp
+    pub picoseconds: u64,
+    /// The samples, in the item size announced by the stream's context packets
+    pub payload: &'a [u8],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// A DIFI signal context packet
+///
+/// Every field is mandatory in DIFI. The Gain is carried as the stage 1 gain, with a zero
+/// stage 2 gain.
+pub struct DifiContext {
+    /// Stream Identifier
+    pub stream_id: u32,
+    /// Packet count, modulo 16
+    pub packet_count: u8,
+    /// UTC integer-seconds timestamp
+    pub seconds: u32,
+    /// Real Time (Picoseconds) fractional-seconds timestamp
+    pub picoseconds: u64,
+    /// Context Field Change Indicator: whether a field changed since the previous packet
+    pub changed: bool,
+    /// Reference Point Identifier
+    pub reference_point: u32,
+    /// Bandwidth in Hz
+    pub bandwidth: f64,
+    /// IF Reference Frequency in Hz
+    pub if_frequency: f64,
+    /// RF Reference Frequency in Hz
+    pub rf_frequency: f64,
+    /// IF Band Offset in Hz
+    pub if_band_offset: f64,
+    /// Reference Level in dBm
+    pub reference_level: f64,
+    /// Gain in dB
+    pub gain: f64,
+    /// Sample Rate in Hz
+    pub sample_rate: f64,
+    /// Timestamp Adjustment in femtoseconds
+    pub timestamp_adjustment: i64,
+    /// Timestamp Calibration Time in UTC seconds
+    pub timestamp_calibration: u32,
+    /// State and Event Indicators
+    pub state_event: u32,
+    /// Size of the samples of the stream's data packets
+    pub item_size: DifiItemSize,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// A DIFI version context packet
+pub struct DifiVersion {
+    /// Stream Identifier
+    pub stream_id: u32,
+    /// Packet count, modulo 16
+    pub packet_count: u8,
+    /// UTC integer-seconds timestamp
+    pub seconds: u32,
+    /// Real Time (Picoseconds) fractional-seconds timestamp
+    pub picoseconds: u64,
+    /// Context Field Change Indicator: whether a field changed since the previous packet
+    pub changed: bool,
+    /// V49 Spec Compliance field, [`DIFI_V49_2`] for DIFI 1.2
+    pub spec_compliance: u32,
+    /// Version and Build Code field
+    pub version: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// A packet of a DIFI standard flow
+pub enum DifiPacket<'a> {
+    /// Signal data packet
+    Data(DifiData<'a>),
+    /// Signal context packet
+    Context(DifiContext),
+    /// Version context packet
+    Version(DifiVersion),
+}
+
+impl DifiPacket<'_> {
+    /// Returns the Stream Identifier of the packet
+    pub fn stream_id(&self) -> u32 {
+        match self {
+            DifiPacket::Data(data) => data.stream_id,
+            DifiPacket::Context(context) => context.stream_id,
+            DifiPacket::Version(version) => version.stream_id,
+        }
+    }
+
+    /// Returns the generic packet, with the header flags, timestamp types and Class
+    /// Identifier mandated by DIFI
+    pub fn to_packet(&self) -> VrtPacketOwned {
+        let (packet_type, tsm, class, count, seconds, picoseconds, data_payload) = match self {
+            DifiPacket::Data(data) => (
+                VitaPacketType::IFDATAWITHSTREAM,
+                false,
+                DIFI_DATA_CLASS,
+                data.packet_count,
+                data.seconds,
+                data.picoseconds,
+                data.payload.to_vec(),
+            ),
+            DifiPacket::Context(context) => (
+                VitaPacketType::IFCONTEXT,
+                true,
+                DIFI_CONTEXT_CLASS,
+                context.packet_count,
+                context.seconds,
+                context.picoseconds,
+                context.payload(),
+            ),
+            DifiPacket::Version(version) => (
+                VitaPacketType::EXTCONTEXT,
+                true,
+                DIFI_VERSION_CLASS,
+                version.packet_count,
+                version.seconds,
+                version.picoseconds,
+                version.payload(),
+            ),
+        };
+        VrtPacketOwned {
+            header: Header {
+                packet_type: VitaPacketType(packet_type),
+                c: true,
+                t: false,
+                indicators: tsm as u8,
+                tsi: Tsi::TSI_UTC,
+                tsf: Tsf::TSF_REAL_TIME,
+                packet_count: count & 0x0f,
+                packet_size: (7 + data_payload.len() / 4) as u16,
+            },
+            stream_id: Some(self.stream_id()),
+            class_id: Some(class_id(class)),
+            tsi: Some(seconds),
+            tsf: Some(picoseconds),
+            data_payload,
+            trailer: None,
+        }
+    }
+}
+
+/// Returns the DIFI Class Identifier with Packet Class Code `class`
+fn class_id(class: u16) -> u64 {
+    (DIFI_OUI as u64) << 32 | class as u64
+}
+
+/// Encodes `value` as the 16-bit fixed-point low half of a field with the radix point to the
+/// right of bit 7
+fn radix7(value: f64) -> [u8; 4] {
+    ((value * 128.0).round() as i16 as u16 as u32).to_be_bytes()
+}
+
+impl DifiContext {
+    fn payload(&self) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(CONTEXT_PAYLOAD_LEN);
+        let change = if self.changed { CHANGE_INDICATOR } else { 0 };
+        payload.extend_from_slice(&(DIFI_CONTEXT_CIF0 | change).to_be_bytes());
+        payload.extend_from_slice(&self.reference_point.to_be_bytes());
+        payload.extend_from_slice(&radix20(self.bandwidth));
+        payload.extend_from_slice(&radix20(self.if_frequency));
+        payload.extend_from_slice(&radix20(self.rf_frequency));
+        payload.extend_from_slice(&radix20(self.if_band_offset));
+        payload.extend_from_slice(&radix7(self.reference_level));
+        payload.extend_from_slice(&radix7(self.gain));
+        payload.extend_from_slice(&radix20(self.sample_rate));
+        payload.extend_from_slice(&self.timestamp_adjustment.to_be_bytes());
+        payload.extend_from_slice(&self.timestamp_calibration.to_be_bytes());
+        payload.extend_from_slice(&self.state_event.to_be_bytes());
+        payload.extend_from_slice(&self.item_size.payload_format().to_be_bytes());
+        payload
+    }
+
+    /// Decodes the fields of `packet`, which must not deviate from DIFI
+    fn parse(packet: &VrtPacket<'_>) -> Option<Self> {
+        let fields = ContextFields::new(packet.data_payload);
+        let cif0 = fields.cif0()?;
+        let raw = |field| fields.get(field).and_then(|f| f.raw());
+        let fixed = |field| {
+            fields
+                .get(field)
+                .and_then(|f| f.fixed())
+                .map(FixedPoint::to_f64)
+        };
+        Some(DifiContext {
+            stream_id: packet.stream_id?,
+            packet_count: packet.header.packet_count,
+            seconds: packet.tsi?,
+            picoseconds: packet.tsf?,
+            changed: cif0 & CHANGE_INDICATOR != 0,
+            reference_point: raw(Cif0Field::ReferencePointId)? as u32,
+            bandwidth: fixed(Cif0Field::Bandwidth)?,
+            if_frequency: fixed(Cif0Field::IfReferenceFrequency)?,
+            rf_frequency: fixed(Cif0Field::RfReferenceFrequency)?,
+            if_band_offset: fixed(Cif0Field::IfBandOffset)?,
+            reference_level: fixed(Cif0Field::ReferenceLevel)?,
+            gain: fixed(Cif0Field::Gain)?,
+            sample_rate: fixed(Cif0Field::SampleRate)?,
+            timestamp_adjustment: raw(Cif0Field::TimestampAdjustment)? as i64,
+            timestamp_calibration: raw(Cif0Field::TimestampCalibrationTime)? as u32,
+            state_event: raw(Cif0Field::StateEventIndicators)? as u32,
+            item_size: DifiItemSize::from_payload_format(raw(Cif0Field::DataPayloadFormat)?)?,
+        })
+    }
+}
+
+impl DifiVersion {
+    fn payload(&self) -> Vec<u8> {
+        let change = if self.changed { CHANGE_INDICATOR } else { 0 };
+        [
+            DIFI_VERSION_CIF0 | change,
+            DIFI_VERSION_CIF1,
+            self.spec_compliance,
+            self.version,
+        ]
+        .iter()
+        .flat_map(|word| word.to_be_bytes())
+        .collect()
+    }
+
+    /// Decodes the fields of `packet`, which must not deviate from DIFI
+    fn parse(packet: &VrtPacket<'_>) -> Option<Self> {
+        let words: Vec<u32> = packet
+            .data_payload
+            .chunks_exact(4)
+            .map(|w| u32::from_be_bytes([w[0], w[1], w[2], w[3]]))
+            .collect();
+        Some(DifiVersion {
+            stream_id: packet.stream_id?,
+            packet_count: packet.header.packet_count,
+            seconds: packet.tsi?,
+            picoseconds: packet.tsf?,
+            changed: words[0] & CHANGE_INDICATOR != 0,
+            spec_compliance: words[2],
+            version: words[3],
+        })
+    }
+}
+
+impl<'a> TryFrom<&VrtPacket<'a>> for DifiPacket<'a> {
+    type Error = Error;
+
+    /// Converts a packet of a DIFI standard flow, returning [`Error::Malformed`] if the
+    /// packet deviates from DIFI, as reported by [`Validator::with_difi`]
+    ///
+    /// [`Validator::with_difi`]: crate::Validator::with_difi
+    fn try_from(packet: &VrtPacket<'a>) -> Result<Self, Error> {
+        if !deviations(packet).is_empty() {
+            return Err(Error::Malformed);
+        }
+        let difi = match packet.class_id.map(|id| id as u16) {
+            Some(DIFI_DATA_CLASS) => Some(DifiPacket::Data(DifiData {
+                stream_id: packet.stream_id.ok_or(Error::Malformed)?,
+                packet_count: packet.header.packet_count,
+                seconds: packet.tsi.ok_or(Error::Malformed)?,
+                picoseconds: packet.tsf.ok_or(Error::Malformed)?,
+                payload: packet.data_payload,
+            })),
+            Some(DIFI_CONTEXT_CLASS) => DifiContext::parse(packet).map(DifiPacket::Context),
+            Some(DIFI_VERSION_CLASS) => DifiVersion::parse(packet).map(DifiPacket::Version),
+            _ => None,
+        };
+        difi.ok_or(Error::Malformed)
+    }
+}
+
+/// Returns each deviation of `packet` from DIFI, with the rule it fails
+///
+/// The packet class is taken from the Class Identifier, so a packet without a DIFI Class
+/// Identifier fails [`Rule::DifiClassId`] only.
+pub(crate) fn deviations(packet: &VrtPacket<'_>) -> Vec<(Rule, String)> {
+    let mut deviations = Vec::new();
+    let class = match packet.class_id {
+        Some(id) if id >> 32 & 0x00ff_ffff == DIFI_OUI as u64 && id >> 16 & 0xffff == 0 => {
+            id as u16
+        }
+        Some(id) => {
+            let message = format!("Class Identifier {:#018x} is not a DIFI one", id);
+            return vec![(Rule::DifiClassId, message)];
+        }
+        None => return vec![(Rule::DifiClassId, "no Class Identifier".to_string())],
+    };
+    let (packet_type, tsm, payload_len) = match class {
+        DIFI_DATA_CLASS => (VitaPacketType::IFDATAWITHSTREAM, false, None),
+        DIFI_CONTEXT_CLASS => (VitaPacketType::IFCONTEXT, true, Some(CONTEXT_PAYLOAD_LEN)),
+        DIFI_VERSION_CLASS => (VitaPacketType::EXTCONTEXT, true, Some(VERSION_PAYLOAD_LEN)),
+        _ => {
+            let message = format!("unknown Packet Class Code {:#06x}", class);
+            return vec![(Rule::DifiClassId, message)];
+        }
+    };
+
+    let header = &packet.header;
+    if header.packet_type.0 != packet_type {
+        let message = format!(
+            "packet type {}, expected {} for Packet Class Code {:#06x}",
+            header.packet_type.0, packet_type, class
+        );
+        deviations.push((Rule::DifiHeader, message));
+    }
+    if header.t {
+        deviations.push((Rule::DifiHeader, "trailer included".to_string()));
+    }
+    if tsm && header.indicators & 1 == 0 {
+        let message = "fine Timestamp Mode (TSM) in a context packet".to_string();
+        deviations.push((Rule::DifiHeader, message));
+    }
+    if header.tsi != Tsi::TSI_UTC || header.tsf != Tsf::TSF_REAL_TIME {
+        let message = format!(
+            "TSI {} TSF {}, expected UTC and Real Time (Picoseconds)",
+            header.tsi.0, header.tsf.0
+        );
+        deviations.push((Rule::DifiTimestamp, message));
+    }
+
+    let payload = packet.data_payload;
+    let word = |n: usize| {
+        payload
+            .get(4 * n..4 * n + 4)
+            .map(|w| u32::from_be_bytes([w[0], w[1], w[2], w[3]]))
+    };
+    let fields = match class {
+        DIFI_CONTEXT_CLASS => match word(0).map(|cif0| cif0 & !CHANGE_INDICATOR) {
+            Some(DIFI_CONTEXT_CIF0) => {
+                let format = ContextFields::new(payload)
+                    .get(Cif0Field::DataPayloadFormat)
+                    .and_then(|field| field.raw());
+                match format {
+                    Some(format) if DifiItemSize::from_payload_format(format).is_none() => {
+                        Some(format!("Data Packet Payload Format {:#018x}", format))
+                    }
+                    _ => None,
+                }
+            }
+            cif0 => Some(format!("CIF0 {:#010x}", cif0.unwrap_or(0))),
+        },
+        DIFI_VERSION_CLASS => match (word(0).map(|cif0| cif0 & !CHANGE_INDICATOR), word(1)) {
+            (Some(DIFI_VERSION_CIF0), Some(DIFI_VERSION_CIF1)) => None,
+            (cif0, cif1) => Some(format!(
+                "CIF0 {:#010x} CIF1 {:#010x}",
+                cif0.unwrap_or(0),
+                cif1.unwrap_or(0)
+            )),
+        },
+        _ => None,
+    };
+    if let Some(message) = fields {
+        deviations.push((Rule::DifiContextFields, message));
+    } else if let Some(len) = payload_len.filter(|len| *len != payload.len()) {
+        let message = format!("{} payload bytes, expected {}", payload.len(), len);
+        deviations.push((Rule::DifiContextFields, message));
+    }
+    deviations
+}
+
+/// Writer of DIFI standard flows as GNU Radio's gr-difi blocks expect them
+///
+/// Packs complex samples into signal data packets of `samples_per_packet` samples in the
+/// stream's item size, timestamped from the sample count and the Sample Rate of the context
+/// template, starting at its timestamp. A signal context packet sharing the timestamp of the
+/// next data packet precedes the first data packet and then every context interval, 100 data
+/// packets unless set with [`DifiWriter::with_context_interval`], and immediately after the
+/// template changes. Its Context Field Change Indicator is set only in the first packet after
+/// a change. Data and context packets keep separate packet counts.
+#[derive(Clone, Debug)]
+pub struct DifiWriter {
+    context: DifiContext,
+    samples_per_packet: usize,
+    context_interval: u64,
+    until_context: u64,
+    changed: bool,
+    samples: u64,
+    pending: Vec<(i16, i16)>,
+    data_count: u8,
+    context_count: u8,
+}
+
+impl DifiWriter {
+    /// Creates a new DifiWriter of the stream described by `context`, with data packets of
+    /// `samples_per_packet` samples
+    ///
+    /// # Panics
+    ///
+    /// Panics if the Sample Rate of `context` is not positive and finite, or if
+    /// `samples_per_packet` is zero, too large for a packet, or odd with 8-bit items, whose
+    /// packets hold whole words of samples.
+    pub fn new(context: DifiContext, samples_per_packet: usize) -> Self {
+        assert!(
+            context.sample_rate.is_finite() && context.sample_rate > 0.0,
+            "sample rate must be positive"
+        );
+        let words = samples_per_packet * context.item_size.bits() as usize / 16;
+        let whole = context.item_size == DifiItemSize::Bits16 || samples_per_packet & 1 == 0;
+        assert!(
+            samples_per_packet > 0 && whole && words <= u16::MAX as usize - 7,
+            "invalid number of samples per packet"
+        );
+        DifiWriter {
+            context,
+            samples_per_packet,
+            context_interval: 100,
+            until_context: 0,
+            changed: true,
+            samples: 0,
+            pending: Vec::new(),
+            data_count: 0,
+            context_count: 0,
+        }
+    }
+
+    /// Emits a context packet every `interval` data packets, or only before the first data
+    /// packet and after changes if `interval` is zero
+    pub fn with_context_interval(mut self, interval: u64) -> Self {
+        self.context_interval = interval;
+        self
+    }
+
+    /// Returns the context template for changes, which the next context packet announces
+    /// before the next data packet
+    ///
+    /// The template's timestamp is that of the next data packet, so the Sample Rate and item
+    /// size apply to the samples written from then on, and setting the timestamp restarts
+    /// the timestamps from it.
+    pub fn context_mut(&mut self) -> &mut DifiContext {
+        let (seconds, picoseconds) = self.timestamp();
+        self.context.seconds = seconds;
+        self.context.picoseconds = picoseconds;
+        self.samples = 0;
+        self.changed = true;
+        self.until_context = 0;
+        &mut self.context
+    }
+
+    /// Writes the complex samples `samples`, returning the packets completed
+    ///
+    /// Samples which do not fill a data packet are kept for the next call, or for
+    /// [`DifiWriter::flush`]. With 8-bit items, each component is truncated to its low 8
+    /// bits.
+    pub fn write(&mut self, samples: &[(i16, i16)]) -> Vec<VrtPacketOwned> {
+        self.pending.extend_from_slice(samples);
+        let pending = std::mem::take(&mut self.pending);
+        let mut packets = Vec::new();
+        let mut chunks = pending.chunks_exact(self.samples_per_packet);
+        for chunk in &mut chunks {
+            self.packets(chunk, &mut packets);
+        }
+        self.pending = chunks.remainder().to_vec();
+        packets
+    }
+
+    /// Returns the packets of the samples kept by [`DifiWriter::write`], in a last, shorter
+    /// data packet
+    ///
+    /// With 8-bit items, an odd number of samples is padded with a zero sample to fill the
+    /// last word.
+    pub fn flush(&mut self) -> Vec<VrtPacketOwned> {
+        let mut packets = Vec::new();
+        if !self.pending.is_empty() {
+            let samples = std::mem::take(&mut self.pending);
+            self.packets(&samples, &mut packets);
+        }
+        packets
+    }
+
+    /// Returns the timestamp of the next data packet
+    fn timestamp(&self) -> (u32, u64) {
+        let rate = self.context.sample_rate;
+        let secs = (self.samples as f64 / rate).floor();
+        let picos = ((self.samples as f64 - secs * rate) / rate * 1e12).round() as u64;
+        let picos = self.context.picoseconds + picos.min(999_999_999_999);
+        let carry = picos / 1_000_000_000_000;
+        (
+            self.context
+                .seconds
+                .wrapping_add(secs as u32)
+                .wrapping_add(carry as u32),
+            picos % 1_000_000_000_000,
+        )
+    }
+
+    fn packets(&mut self, samples: &[(i16, i16)], packets: &mut Vec<VrtPacketOwned>) {
+        let (seconds, picoseconds) = self.timestamp();
+        if self.until_context == 0 {
+            let context = DifiContext {
+                packet_count: self.context_count,
+                seconds,
+                picoseconds,
+                changed: self.changed,
+                ..self.context
+            };
+            packets.push(DifiPacket::Context(context).to_packet());
+            self.context_count = (self.context_count + 1) & 0x0f;
+            self.until_context = self.context_interval;
+            self.changed = false;
+        }
+        self.until_context = self.until_context.saturating_sub(1);
+
+        let mut payload = Vec::with_capacity(samples.len() * 4);
+        for (i, q) in samples {
+            match self.context.item_size {
+                DifiItemSize::Bits8 => payload.extend_from_slice(&[*i as u8, *q as u8]),
+                DifiItemSize::Bits16 => {
+                    payload.extend_from_slice(&i.to_be_bytes());
+                    payload.extend_from_slice(&q.to_be_bytes());
+                }
+            }
+        }
+        // packets are whole words
+        payload.resize(payload.len().div_ceil(4) * 4, 0);
+        let data = DifiData {
+            stream_id: self.context.stream_id,
+            packet_count: self.data_count,
+            seconds,
+            picoseconds,
+            payload: &payload,
+        };
+        packets.push(DifiPacket::Data(data).to_packet());
+        self.data_count = (self.data_count + 1) & 0x0f;
+        self.samples += samples.len() as u64;
+    }
+}
+
+/// Reader of DIFI standard flows, such as those written by GNU Radio's gr-difi blocks
+///
+/// Keeps the latest signal context of each stream, so the samples of its data packets can be
+/// decoded in the item size the context announces.
+#[derive(Clone, Debug, Default)]
+pub struct DifiReader {
+    contexts: HashMap<u32, DifiContext>,
+}
+
+impl DifiReader {
+    /// Creates a new DifiReader
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Converts `packet` as with `DifiPacket::try_from`, keeping the context of signal
+    /// context packets
+    pub fn read<'a>(&mut self, packet: &VrtPacket<'a>) -> Result<DifiPacket<'a>, Error> {
+        let difi = DifiPacket::try_from(packet)?;
+        if let DifiPacket::Context(context) = difi {
+            self.contexts.insert(context.stream_id, context);
+        }
+        Ok(difi)
+    }
+
+    /// Returns the latest signal context of stream `stream_id`
+    pub fn context(&self, stream_id: u32) -> Option<&DifiContext> {
+        self.contexts.get(&stream_id)
+    }
+
+    /// Returns the complex samples of `data`, or None if no context of its stream has been
+    /// read yet
+    ///
+    /// The zero sample padding the last word of an odd number of 8-bit samples is returned
+    /// as a sample.
+    pub fn samples(&self, data: &DifiData<'_>) -> Option<Vec<(i16, i16)>> {
+        let samples = match self.context(data.stream_id)?.item_size {
+            DifiItemSize::Bits8 => data
+                .payload
+                .chunks_exact(2)
+                .map(|s| (s[0] as i8 as i16, s[1] as i8 as i16))
+                .collect(),
+            DifiItemSize::Bits16 => data
+                .payload
+                .chunks_exact(4)
+                .map(|s| {
+                    (
+                        i16::from_be_bytes([s[0], s[1]]),
+                        i16::from_be_bytes([s[2], s[3]]),
+                    )
+                })
+                .collect(),
+        };
+        Some(samples)
+    }
+}
@@ -0,0 +1,353 @@
+//! Dissection of packets into a tree of fields for display by viewers.
+
+use crate::context::ContextFields;
+use crate::encoder::{encode_vrt_header, encode_vrt_trailer};
+use crate::vrt::{Tsf, VrtPacket};
+use std::fmt;
+use std::ops::Range;
+
+/// A node of a packet dissection tree
+///
+/// Bit ranges count from the most significant bit of the first byte of the packet, so the
+/// packet type occupies bits `0..4` and the Stream Identifier, if present, bits `32..64`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    /// Name of the field, such as `Packet Count`
+    pub name: &'static str,
+    /// Range of bits occupied by the field within the packet
+    pub bits: Range<usize>,
+    /// Raw value of the field, or None for fields wider than 64 bits such as the payload
+    pub raw: Option<u64>,
+    /// Decoded value of the field, such as `UTC` or `10000000 Hz`
+    pub value: String,
+    /// Subfields of the field
+    pub children: Vec<Field>,
+}
+
+impl Field {
+    fn new(name: &'static str, start: usize, len: usize, raw: u64, value: String) -> Self {
+        Field {
+            name,
+            bits: start..start + len,
+            raw: Some(raw),
+            value,
+            children: Vec::new(),
+        }
+    }
+
+    fn bytes(name: &'static str, start: usize, bytes: &[u8]) -> Self {
+        Field {
+            name,
+            bits: start..start + bytes.len() * 8,
+            raw: None,
+            value: format!("{} bytes", bytes.len()),
+            children: Vec::new(),
+        }
+    }
+
+    fn with_children(mut self, children: Vec<Field>) -> Self {
+        self.children = children;
+        self
+    }
+
+    /// Returns the first field named `name` in a depth-first search of this tree
+    pub fn find(&self, name: &str) -> Option<&Field> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(name))
+    }
+
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        writeln!(
+            f,
+            "{:indent$}{} [bits {}..{}]: {}",
+            "",
+            self.name,
+            self.bits.start,
+            self.bits.end,
+            self.value,
+            indent = depth * 4
+        )?;
+        for child in &self.children {
+            child.fmt_indented(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Prints the tree with one line per field, indenting subfields by four spaces
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+/// Converts a field name such as `RF Reference Frequency` to `rf_reference_frequency`
+pub(crate) fn snake_case(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn flag(name: &'static str, start: usize, set: bool) -> Field {
+    Field::new(name, start, 1, set as u64, set.to_string())
+}
+
+/// Names of the trailer enable and indicator bits, from the most significant
+const TRAILER_BITS: [&str; 12] = [
+    "calibrated time",
+    "valid data",
+    "reference lock",
+    "AGC/MGC",
+    "detected signal",
+    "spectral inversion",
+    "over-range",
+    "sample loss",
+    "user defined 1",
+    "user defined 2",
+    "user defined 3",
+    "user defined 4",
+];
+
+fn trailer_names(bits: u32) -> String {
+    let names: Vec<_> = TRAILER_BITS
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| bits >> (11 - i) & 1 == 1)
+        .map(|(_, name)| *name)
+        .collect();
+    match names.is_empty() {
+        true => "none".to_string(),
+        false => names.join(", "),
+    }
+}
+
+/// Dissects the payload of a context packet starting at bit `start` of the packet
+fn context_fields(payload: &[u8], start: usize) -> Vec<Field> {
+    let mut fields = Vec::new();
+    let mut decoder = ContextFields::new(payload);
+    let cif0 = match decoder.cif0() {
+        Some(cif0) => cif0,
+        None => return fields,
+    };
+    let indicators: Vec<_> = (0..32)
+        .rev()
+        .filter(|bit| cif0 >> bit & 1 == 1)
+        .map(|bit| bit.to_string())
+        .collect();
+    let indicators = match indicators.is_empty() {
+        true => "no fields".to_string(),
+        false => format!("bits {}", indicators.join(", ")),
+    };
+    let changed = flag("Context Field Change Indicator", start, cif0 >> 31 == 1);
+    fields.push(
+        Field::new(
+            "Context Indicator Field 0",
+            start,
+            32,
+            cif0 as u64,
+            indicators,
+        )
+        .with_children(vec![changed]),
+    );
+    // the CIF1, CIF2, CIF3 and CIF7 words follow CIF0, and then the CIF0 fields
+    for (n, (bit, word)) in decoder.extensions().enumerate() {
+        let name = match bit {
+            1 => "CIF1",
+            2 => "CIF2",
+            3 => "CIF3",
+            _ => "CIF7",
+        };
+        let offset = start + (n + 1) * 32;
+        let value = format!("{:#010x}", word);
+        fields.push(Field::new(name, offset, 32, word as u64, value));
+    }
+    for field in decoder.by_ref() {
+        let offset = start + field.offset * 8;
+        fields.push(match (field.raw(), field.value()) {
+            (Some(raw), Some(value)) => Field::new(
+                field.field.name(),
+                offset,
+                field.bytes.len() * 8,
+                raw,
+                value.to_string(),
+            ),
+            _ => Field::bytes(field.field.name(), offset, field.bytes),
+        });
+    }
+    let offset = decoder.offset();
+    if offset < payload.len() {
+        fields.push(Field::bytes(
+            "Remaining Context Fields",
+            start + offset * 8,
+            &payload[offset..],
+        ));
+    }
+    fields
+}
+
+impl VrtPacket<'_> {
+    /// Dissects the packet into a tree of fields, rooted at a field spanning the whole packet
+    ///
+    /// The header, optional prologue fields, payload and trailer are children of the root.
+    /// The payload of a context packet is dissected into its Context Indicator Field 0 and the
+    /// fixed-size fields it announces, up to the first variable-length field; other payloads
+    /// are a single field.
+    pub fn dissect(&self) -> Field {
+        let header = &self.header;
+        let raw = u32::from_be_bytes(encode_vrt_header(header));
+        let mut fields = vec![
+            Field::new("Header", 0, 32, raw as u64, format!("{:#010x}", raw)).with_children(vec![
+                Field::new(
+                    "Packet Type",
+                    0,
+                    4,
+                    header.packet_type.0 as u64,
+                    header.packet_type.to_string(),
+                ),
+                flag("Class Identifier Included", 4, header.c),
+                flag("Trailer Included", 5, header.t),
+                Field::new("TSI", 8, 2, header.tsi.0 as u64, header.tsi.to_string()),
+                Field::new("TSF", 10, 2, header.tsf.0 as u64, header.tsf.to_string()),
+                Field::new(
+                    "Packet Count",
+                    12,
+                    4,
+                    header.packet_count as u64,
+                    header.packet_count.to_string(),
+                ),
+                Field::new(
+                    "Packet Size",
+                    16,
+                    16,
+                    header.packet_size as u64,
+                    format!("{} words", header.packet_size),
+                ),
+            ]),
+        ];
+        let mut bit = 32;
+        if let Some(stream_id) = self.stream_id {
+            fields.push(Field::new(
+                "Stream Identifier",
+                bit,
+                32,
+                stream_id as u64,
+                format!("{:#010x}", stream_id),
+            ));
+            bit += 32;
+        }
+        if let Some(class_id) = self.class_id {
+            fields.push(
+                Field::new(
+                    "Class Identifier",
+                    bit,
+                    64,
+                    class_id,
+                    format!("{:#018x}", class_id),
+                )
+                .with_children(vec![
+                    Field::new(
+                        "Pad Bit Count",
+                        bit,
+                        5,
+                        class_id >> 59,
+                        (class_id >> 59).to_string(),
+                    ),
+                    Field::new(
+                        "OUI",
+                        bit + 8,
+                        24,
+                        class_id >> 32 & 0xff_ffff,
+                        format!("{:06X}", class_id >> 32 & 0xff_ffff),
+                    ),
+                    Field::new(
+                        "Information Class Code",
+                        bit + 32,
+                        16,
+                        class_id >> 16 & 0xffff,
+                        format!("{:#06x}", class_id >> 16 & 0xffff),
+                    ),
+                    Field::new(
+                        "Packet Class Code",
+                        bit + 48,
+                        16,
+                        class_id & 0xffff,
+                        format!("{:#06x}", class_id & 0xffff),
+                    ),
+                ]),
+            );
+            bit += 64;
+        }
+        if let Some(tsi) = self.tsi {
+            let name = "Integer-seconds Timestamp";
+            fields.push(Field::new(name, bit, 32, tsi as u64, tsi.to_string()));
+            bit += 32;
+        }
+        if let Some(tsf) = self.tsf {
+            let value = match header.tsf {
+                Tsf::TSF_REAL_TIME => format!("{} ps", tsf),
+                _ => tsf.to_string(),
+            };
+            fields.push(Field::new(
+                "Fractional-seconds Timestamp",
+                bit,
+                64,
+                tsf,
+                value,
+            ));
+            bit += 64;
+        }
+        let payload = self.data_payload;
+        let mut payload_field = Field::bytes("Payload", bit, payload);
+        if header.packet_type.is_context() {
+            payload_field.children = context_fields(payload, bit);
+        }
+        fields.push(payload_field);
+        bit += payload.len() * 8;
+        if let Some(trailer) = &self.trailer {
+            let raw = u32::from_be_bytes(encode_vrt_trailer(trailer));
+            let count = match raw >> 7 & 1 {
+                1 => (raw & 0x7f).to_string(),
+                _ => "not given".to_string(),
+            };
+            fields.push(
+                Field::new("Trailer", bit, 32, raw as u64, format!("{:#010x}", raw)).with_children(
+                    vec![
+                        Field::new(
+                            "Enables",
+                            bit,
+                            12,
+                            (raw >> 20) as u64,
+                            trailer_names(raw >> 20),
+                        ),
+                        Field::new(
+                            "Indicators",
+                            bit + 12,
+                            12,
+                            (raw >> 8 & 0xfff) as u64,
+                            trailer_names(raw >> 8 & 0xfff),
+                        ),
+                        Field::new(
+                            "Associated Context Packet Count",
+                            bit + 24,
+                            8,
+                            (raw & 0xff) as u64,
+                            count,
+                        ),
+                    ],
+                ),
+            );
+            bit += 32;
+        }
+        Field {
+            name: "VRT Packet",
+            bits: 0..bit,
+            raw: None,
+            value: self.to_string(),
+            children: fields,
+        }
+    }
+}
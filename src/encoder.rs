@@ -0,0 +1,332 @@
+//! Encoding of VRT packets into their wire format.
+
+use crate::error::Error;
+use crate::vrt::*;
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+use core::mem::MaybeUninit;
+use core::ops::Range;
+
+/// Encodes a VRT packet header
+///
+/// As a `const fn` this can build fixed packet prologues at compile time, leaving only the
+/// packet count and packet size to be patched when each packet is sent.
+///
+/// Both bits of the [`Header::indicators`] are encoded, reserved or not.
+pub const fn encode_vrt_header(header: &Header) -> [u8; 4] {
+    let size = header.packet_size.to_be_bytes();
+    [
+        header.packet_type.0 << 4
+            | (header.c as u8) << 3
+            | (header.t as u8) << 2
+            | header.indicators & 0x03,
+        (header.tsi.0 & 0x03) << 6 | (header.tsf.0 & 0x03) << 4 | header.packet_count & 0x0f,
+        size[0],
+        size[1],
+    ]
+}
+
+/// Packs eight flags into a byte, most significant bit first
+const fn bits(flags: [bool; 8]) -> u8 {
+    let mut byte = 0;
+    let mut i = 0;
+    while i < 8 {
+        byte = byte << 1 | flags[i] as u8;
+        i += 1;
+    }
+    byte
+}
+
+/// Encodes a VRT packet trailer
+pub const fn encode_vrt_trailer(trailer: &Trailer) -> [u8; 4] {
+    [
+        bits([
+            trailer.calibrated_time_enable,
+            trailer.valid_data_enable,
+            trailer.reference_lock_enable,
+            trailer.agcmgc_enable,
+            trailer.detected_signal_enable,
+            trailer.spectral_inversion_enable,
+            trailer.overrange_enable,
+            trailer.sample_loss_enable,
+        ]),
+        bits([
+            trailer.user_defined_enable_1,
+            trailer.user_defined_enable_2,
+            trailer.user_defined_enable_3,
+            trailer.user_defined_enable_4,
+            trailer.calibrated_time_indicator,
+            trailer.valid_data_indicator,
+            trailer.reference_lock_indicator,
+            trailer.agcmgc_indicator,
+        ]),
+        bits([
+            trailer.detected_signal_indicator,
+            trailer.spectral_inversion_indicator,
+            trailer.overrange_indicator,
+            trailer.sample_loss_indicator,
+            trailer.user_defined_indicator_1,
+            trailer.user_defined_indicator_2,
+            trailer.user_defined_indicator_3,
+            trailer.user_defined_indicator_4,
+        ]),
+        (trailer.associated_context_packet_count_enable as u8) << 7
+            | trailer.associated_context_packet_count & 0x7f,
+    ]
+}
+
+/// Encodes `packet` into the start of `buf`, returning the number of bytes written
+///
+/// The `packet_size` written is computed from the packet contents rather than taken from the
+/// header, and a data payload which does not end on a 32-bit word boundary is padded with
+/// zeros.
+///
+/// Encoding a packet parsed by [`parse_vrt_packet`](crate::parse_vrt_packet) writes exactly the
+/// bytes parsed, including the indicator bits of the header.
+///
+/// Returns [`Error::Malformed`] if the optional fields present do not match the header, and
+/// [`Error::BufferTooSmall`] if `buf` cannot hold the packet.
+pub fn encode_vrt_packet(packet: &VrtPacket<'_>, buf: &mut [u8]) -> Result<usize, Error> {
+    let payload = packet.data_payload;
+    let (len, start) = encode_packet(packet, payload.len(), buf.len(), |offset, bytes| {
+        buf[offset..offset + bytes.len()].copy_from_slice(bytes)
+    })?;
+    buf[start..start + payload.len()].copy_from_slice(payload);
+    Ok(len)
+}
+
+/// Encodes `packet` into the start of a possibly uninitialized `buf`, as described for
+/// [`encode_vrt_packet`]
+///
+/// Exactly the first `n` bytes of `buf` are initialized on success, where `n` is the returned
+/// length, so DMA descriptors and freshly allocated buffers need not be zeroed first.
+pub fn encode_vrt_packet_uninit(
+    packet: &VrtPacket<'_>,
+    buf: &mut [MaybeUninit<u8>],
+) -> Result<usize, Error> {
+    let write = |buf: &mut [MaybeUninit<u8>], offset: usize, bytes: &[u8]| {
+        for (dst, src) in buf[offset..offset + bytes.len()].iter_mut().zip(bytes) {
+            dst.write(*src);
+        }
+    };
+    let payload = packet.data_payload;
+    let (len, start) = encode_packet(packet, payload.len(), buf.len(), |offset, bytes| {
+        write(buf, offset, bytes)
+    })?;
+    write(buf, start, payload);
+    Ok(len)
+}
+
+/// Layout of a packet encoded into a frame by [`encode_vrt_frame`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FrameLayout {
+    /// Range of the frame into which the data payload must be written
+    pub payload: Range<usize>,
+    /// Offset of the end of the packet, including payload padding and trailer, in the frame
+    pub end: usize,
+}
+
+/// Encodes `packet` into `frame` at `offset`, leaving room for a data payload of
+/// `payload_len` bytes instead of copying the payload
+///
+/// This lets a network driver build the VRT packet directly after the link, network and
+/// transport headers of a transmit frame and then DMA the samples into the returned payload
+/// range. The `data_payload` of `packet` is ignored; the header, optional fields, payload
+/// padding and trailer are written as described for [`encode_vrt_packet`].
+///
+/// Returns [`Error::Malformed`] if the optional fields present do not match the header, and
+/// [`Error::BufferTooSmall`] if the frame cannot hold the packet at `offset`.
+pub fn encode_vrt_frame(
+    packet: &VrtPacket<'_>,
+    payload_len: usize,
+    frame: &mut [u8],
+    offset: usize,
+) -> Result<FrameLayout, Error> {
+    let available = frame.len().saturating_sub(offset);
+    let (len, start) = encode_packet(packet, payload_len, available, |at, bytes| {
+        frame[offset + at..offset + at + bytes.len()].copy_from_slice(bytes)
+    })?;
+    Ok(FrameLayout {
+        payload: offset + start..offset + start + payload_len,
+        end: offset + len,
+    })
+}
+
+/// A packet encoded for vectored (scatter-gather) output
+///
+/// The header, optional fields and trailer are encoded into small inline buffers while the data
+/// payload is referenced in place, so writing the packet with `write_vectored` or `sendmsg`
+/// does not copy the payload.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug)]
+pub struct VectoredPacket<'a> {
+    head: [u8; 28],
+    head_len: usize,
+    payload: &'a [u8],
+    tail: [u8; 7],
+    tail_len: usize,
+}
+
+#[cfg(feature = "std")]
+impl<'a> VectoredPacket<'a> {
+    /// Returns the header and optional fields, the data payload, and the payload padding and
+    /// trailer, in wire order
+    pub fn io_slices(&self) -> [std::io::IoSlice<'_>; 3] {
+        [
+            std::io::IoSlice::new(&self.head[..self.head_len]),
+            std::io::IoSlice::new(self.payload),
+            std::io::IoSlice::new(&self.tail[..self.tail_len]),
+        ]
+    }
+
+    /// Returns the total length in bytes of the encoded packet
+    pub fn len(&self) -> usize {
+        self.head_len + self.payload.len() + self.tail_len
+    }
+
+    /// Returns true if the encoded packet is empty, which is never the case
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Validates `packet` and passes its encoding to `put` in order, together with the offset of
+/// each piece, leaving room for a data payload of `payload_len` bytes
+///
+/// Returns the length of the packet and the offset of the data payload.
+fn encode_packet(
+    packet: &VrtPacket<'_>,
+    payload_len: usize,
+    available: usize,
+    mut put: impl FnMut(usize, &[u8]),
+) -> Result<(usize, usize), Error> {
+    let header = &packet.header;
+    let consistent = packet.stream_id.is_some() == header.packet_type.has_stream_id()
+        && packet.class_id.is_some() == header.c
+        && packet.tsi.is_some() == (header.tsi != Tsi::TSI_NONE)
+        && packet.tsf.is_some() == (header.tsf != Tsf::TSF_NONE)
+        && packet.trailer.is_some() == (header.t && header.packet_type.is_data());
+    let len = packet.encoded_len_with(payload_len);
+    if !consistent || len > u16::MAX as usize * 4 {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            stream_id = packet.stream_id,
+            "cannot encode malformed VRT packet"
+        );
+        return Err(Error::Malformed);
+    }
+    if len > available {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            stream_id = packet.stream_id,
+            len,
+            available,
+            "buffer too small for VRT packet"
+        );
+        return Err(Error::BufferTooSmall);
+    }
+
+    let mut header = *header;
+    header.packet_size = (len / 4) as u16;
+    let mut offset = 0;
+    let mut field = |bytes: &[u8]| {
+        put(offset, bytes);
+        offset += bytes.len();
+    };
+    field(&encode_vrt_header(&header));
+    if let Some(stream_id) = packet.stream_id {
+        field(&stream_id.to_be_bytes());
+    }
+    if let Some(class_id) = packet.class_id {
+        field(&class_id.to_be_bytes());
+    }
+    if let Some(tsi) = packet.tsi {
+        field(&tsi.to_be_bytes());
+    }
+    if let Some(tsf) = packet.tsf {
+        field(&tsf.to_be_bytes());
+    }
+    // the payload itself is written by the caller
+    let start = offset;
+    let padding = payload_len.wrapping_neg() & 3;
+    put(start + payload_len, &[0; 3][..padding]);
+    if let Some(trailer) = &packet.trailer {
+        put(start + payload_len + padding, &encode_vrt_trailer(trailer));
+    }
+    #[cfg(feature = "tracing")]
+    tracing::trace!(
+        stream_id = packet.stream_id,
+        packet_count = header.packet_count,
+        len,
+        "encoded VRT packet"
+    );
+    Ok((len, start))
+}
+
+impl<'a> VrtPacket<'a> {
+    /// Returns the length in bytes of the encoded packet, including any payload padding
+    pub fn encoded_len(&self) -> usize {
+        self.encoded_len_with(self.data_payload.len())
+    }
+
+    fn encoded_len_with(&self, payload_len: usize) -> usize {
+        4 + self.stream_id.map_or(0, |_| 4)
+            + self.class_id.map_or(0, |_| 8)
+            + self.tsi.map_or(0, |_| 4)
+            + self.tsf.map_or(0, |_| 8)
+            + payload_len.div_ceil(4) * 4
+            + self.trailer.map_or(0, |_| 4)
+    }
+
+    /// Encodes the packet for vectored output, as described for [`encode_vrt_packet`], without
+    /// copying the data payload
+    #[cfg(feature = "std")]
+    pub fn encode_vectored(&self) -> Result<VectoredPacket<'a>, Error> {
+        let mut vectored = VectoredPacket {
+            head: [0; 28],
+            head_len: 0,
+            payload: self.data_payload,
+            tail: [0; 7],
+            tail_len: 0,
+        };
+        let tail_start = self.encoded_len()
+            - self.trailer.map_or(0, |_| 4)
+            - self.data_payload.len().div_ceil(4) * 4
+            + self.data_payload.len();
+        let head_len = tail_start - self.data_payload.len();
+        encode_packet(
+            self,
+            self.data_payload.len(),
+            usize::MAX,
+            |offset, bytes| {
+                if offset < head_len {
+                    vectored.head[offset..offset + bytes.len()].copy_from_slice(bytes);
+                } else if offset >= tail_start {
+                    let offset = offset - tail_start;
+                    vectored.tail[offset..offset + bytes.len()].copy_from_slice(bytes);
+                }
+            },
+        )?;
+        vectored.head_len = head_len;
+        vectored.tail_len = self.encoded_len() - tail_start;
+        Ok(vectored)
+    }
+
+    /// Encodes the packet into a new buffer, as described for [`encode_vrt_packet`]
+    #[cfg(feature = "alloc")]
+    pub fn to_vec(&self) -> Result<Vec<u8>, Error> {
+        let mut buf = vec![0u8; self.encoded_len()];
+        encode_vrt_packet(self, &mut buf)?;
+        Ok(buf)
+    }
+
+    /// Encodes the packet into a new buffer as described for [`encode_vrt_packet`], which keeps
+    /// every bit of [`Header::indicators`], reserved or not
+    ///
+    /// For any packet parsed by [`parse_vrt_packet`](crate::parse_vrt_packet) this returns
+    /// exactly the bytes parsed. It is equivalent to [`VrtPacket::to_vec`].
+    #[cfg(feature = "alloc")]
+    pub fn reserialize_exact(&self) -> Result<Vec<u8>, Error> {
+        self.to_vec()
+    }
+}
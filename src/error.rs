@@ -0,0 +1,138 @@
+//! Errors returned by the VRT packet parsers and encoders.
+
+use core::fmt;
+use nom::error::Error as NomError;
+use nom::{Err, Needed};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+/// VRT Error
+///
+/// New variants may be added in minor releases, so matches must include a wildcard arm.
+pub enum Error {
+    /// The input ended before the end of the packet. Contains the number of additional bytes
+    /// needed, if known.
+    Incomplete(Option<usize>),
+    /// The packet is malformed and cannot be parsed.
+    Malformed,
+    /// The packet is malformed and cannot be parsed, at the field described.
+    MalformedField(FieldError),
+    /// The input continues beyond the declared packet size. Contains the number of bytes
+    /// following the packet.
+    TrailingBytes(usize),
+    /// The output buffer or MTU is too small to hold the packet.
+    BufferTooSmall,
+    /// The packet does not continue the sequence of packets before it.
+    Discontinuity,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+/// Field of a VRT packet, locating an [`Error::MalformedField`]
+///
+/// New fields, such as those of VITA 49.2, may be added in minor releases.
+pub enum PacketField {
+    /// VRT Packet Header
+    Header,
+    /// Stream Identifier
+    StreamId,
+    /// Class Identifier
+    ClassId,
+    /// Integer-seconds Timestamp
+    IntegerTimestamp,
+    /// Fractional-seconds Timestamp
+    FractionalTimestamp,
+    /// VRT Packet Trailer
+    Trailer,
+}
+
+impl PacketField {
+    /// Returns the name of the field, as used in VITA 49.0
+    pub fn name(&self) -> &'static str {
+        match self {
+            PacketField::Header => "Header",
+            PacketField::StreamId => "Stream Identifier",
+            PacketField::ClassId => "Class Identifier",
+            PacketField::IntegerTimestamp => "Integer-seconds Timestamp",
+            PacketField::FractionalTimestamp => "Fractional-seconds Timestamp",
+            PacketField::Trailer => "Trailer",
+        }
+    }
+}
+
+impl fmt::Display for PacketField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// Location and cause of a malformed packet field
+pub struct FieldError {
+    /// The field which could not be parsed
+    pub field: PacketField,
+    /// The 32-bit word of the field at which parsing failed, counting from 1
+    pub word: u8,
+    /// Offset in bytes of that word from the start of the input
+    pub offset: usize,
+    /// Why the field could not be parsed
+    pub reason: &'static str,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} word {} at offset {}: {}",
+            self.field, self.word, self.offset, self.reason
+        )
+    }
+}
+
+impl Error {
+    /// Returns the error with the offset of any field error moved `by` bytes later, for a
+    /// packet parsed `by` bytes into the input
+    pub(crate) fn offset_by(self, by: usize) -> Self {
+        match self {
+            Error::MalformedField(e) => Error::MalformedField(FieldError {
+                offset: e.offset + by,
+                ..e
+            }),
+            e => e,
+        }
+    }
+}
+
+impl<I> From<Err<NomError<I>>> for Error {
+    fn from(e: Err<NomError<I>>) -> Self {
+        match e {
+            Err::Incomplete(Needed::Size(n)) => Error::Incomplete(Some(n.get())),
+            Err::Incomplete(Needed::Unknown) => Error::Incomplete(None),
+            Err::Error(_) | Err::Failure(_) => Error::Malformed,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Incomplete(Some(n)) => {
+                write!(f, "incomplete VRT packet: {} more bytes needed", n)
+            }
+            Error::Incomplete(None) => f.write_str("incomplete VRT packet"),
+            Error::Malformed => f.write_str("malformed VRT packet"),
+            Error::MalformedField(e) => write!(f, "malformed VRT packet: {}", e),
+            Error::TrailingBytes(n) => {
+                write!(f, "{} bytes beyond the declared VRT packet size", n)
+            }
+            Error::BufferTooSmall => f.write_str("buffer too small for VRT packet"),
+            Error::Discontinuity => f.write_str("VRT packet sequence discontinuity"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
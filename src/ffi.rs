@@ -0,0 +1,959 @@
+//! C API for parsing, building and encoding packets.
+//!
+//! Packets are exposed as opaque `VrtPacket` handles owning a copy of their data, created by
+//! [`vrt_packet_parse`], [`vrt_packet_new_data`] or [`vrt_packet_new_context`] and released
+//! with [`vrt_packet_free`]. Functions which can fail return one of the `VRT_*` status codes.
+//! Header, Class Identifier, trailer and context fields are exchanged as the `#[repr(C)]`
+//! mirrors [`VrtHeader`], [`VrtClassId`], [`VrtTrailer`] and [`VrtContext`], which convert to
+//! and from the crate types.
+//! The declarations are in `include/vrt.h`, which `cbindgen.toml` configures cbindgen to
+//! regenerate. The `vrt-c` crate of `bindings/c` links them into the shared library, built by
+//! `cargo build --release -p vrt-c` as `libvrt.so`, `libvrt.dylib` or `vrt.dll` under
+//! `target/release`.
+//!
+//! For MATLAB/Octave MEX functions and similar array-oriented callers, [`vrt_buffer_count`]
+//! and [`vrt_buffer_unpack`] convert a whole buffer of back-to-back packets into a
+//! [`VrtPacketInfo`] array and one int16 sample array, without creating packet handles. A
+//! MEX gateway sizes its `mxArray`s from the counts, unpacks into them and then copies the
+//! infos into a struct array.
+#![allow(unsafe_code)]
+
+use crate::context::{Cif0Field, ContextFields};
+use crate::encoder::encode_vrt_packet;
+use crate::error::Error;
+use crate::parser::parse_vrt_packet;
+use crate::stream::PacketStream;
+use crate::vrt::{radix20, ClassId, Header, Trailer, Tsf, Tsi, VitaPacketType, VrtPacketOwned};
+use std::slice;
+
+/// The call succeeded
+pub const VRT_OK: i32 = 0;
+/// The input ended before the end of the packet
+pub const VRT_ERR_INCOMPLETE: i32 = -1;
+/// The packet is malformed
+pub const VRT_ERR_MALFORMED: i32 = -2;
+/// The output buffer is too small to hold the packet
+pub const VRT_ERR_BUFFER_TOO_SMALL: i32 = -3;
+/// A required pointer argument is null or an argument is out of range
+pub const VRT_ERR_INVALID_ARGUMENT: i32 = -4;
+/// The packet does not continue the sequence of packets before it
+pub const VRT_ERR_DISCONTINUITY: i32 = -5;
+
+fn status(e: Error) -> i32 {
+    match e {
+        Error::Incomplete(_) => VRT_ERR_INCOMPLETE,
+        Error::Malformed | Error::MalformedField(_) | Error::TrailingBytes(_) => VRT_ERR_MALFORMED,
+        Error::BufferTooSmall => VRT_ERR_BUFFER_TOO_SMALL,
+        Error::Discontinuity => VRT_ERR_DISCONTINUITY,
+    }
+}
+
+/// `#[repr(C)]` mirror of [`Header`]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct VrtHeader {
+    /// Packet type
+    pub packet_type: u8,
+    /// Whether the Class Identifier is present
+    pub c: bool,
+    /// Whether the trailer is present
+    pub t: bool,
+    /// Packet-specific indicator bits 25 and 24, as the two least significant bits
+    pub indicators: u8,
+    /// Integer-seconds Timestamp type
+    pub tsi: u8,
+    /// Fractional-seconds Timestamp type
+    pub tsf: u8,
+    /// Packet count
+    pub packet_count: u8,
+    /// Packet size in 32-bit words
+    pub packet_size: u16,
+}
+
+impl From<Header> for VrtHeader {
+    fn from(header: Header) -> Self {
+        VrtHeader {
+            packet_type: header.packet_type.0,
+            c: header.c,
+            t: header.t,
+            indicators: header.indicators,
+            tsi: header.tsi.0,
+            tsf: header.tsf.0,
+            packet_count: header.packet_count,
+            packet_size: header.packet_size,
+        }
+    }
+}
+
+impl From<VrtHeader> for Header {
+    fn from(header: VrtHeader) -> Self {
+        Header {
+            packet_type: VitaPacketType(header.packet_type),
+            c: header.c,
+            t: header.t,
+            indicators: header.indicators,
+            tsi: Tsi(header.tsi),
+            tsf: Tsf(header.tsf),
+            packet_count: header.packet_count,
+            packet_size: header.packet_size,
+        }
+    }
+}
+
+/// `#[repr(C)]` mirror of [`ClassId`]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct VrtClassId {
+    /// Organizationally Unique Identifier in the low 24 bits
+    pub oui: u32,
+    /// Information Class Code, bits 31..16 of the second word
+    pub information_class_code: u16,
+    /// Packet Class Code, bits 15..0 of the second word
+    pub packet_class_code: u16,
+}
+
+impl From<ClassId> for VrtClassId {
+    fn from(class_id: ClassId) -> Self {
+        VrtClassId {
+            oui: class_id.oui,
+            information_class_code: class_id.information_class_code,
+            packet_class_code: class_id.packet_class_code,
+        }
+    }
+}
+
+impl From<VrtClassId> for ClassId {
+    fn from(class_id: VrtClassId) -> Self {
+        ClassId {
+            oui: class_id.oui,
+            packet_class_code: class_id.packet_class_code,
+            information_class_code: class_id.information_class_code,
+        }
+    }
+}
+
+impl From<u64> for VrtClassId {
+    /// Splits a Class Identifier field as stored in [`VrtPacketOwned::class_id`]
+    fn from(class_id: u64) -> Self {
+        VrtClassId {
+            oui: (class_id >> 32) as u32,
+            information_class_code: (class_id >> 16) as u16,
+            packet_class_code: class_id as u16,
+        }
+    }
+}
+
+impl From<VrtClassId> for u64 {
+    fn from(class_id: VrtClassId) -> Self {
+        (class_id.oui as u64) << 32
+            | (class_id.information_class_code as u64) << 16
+            | class_id.packet_class_code as u64
+    }
+}
+
+/// `#[repr(C)]` mirror of [`Trailer`]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct VrtTrailer {
+    /// Enable bit of the calibrated time indicator
+    pub calibrated_time_enable: bool,
+    /// Enable bit of the valid data indicator
+    pub valid_data_enable: bool,
+    /// Enable bit of the reference lock indicator
+    pub reference_lock_enable: bool,
+    /// Enable bit of the AGC/MGC indicator
+    pub agcmgc_enable: bool,
+    /// Enable bit of the detected signal indicator
+    pub detected_signal_enable: bool,
+    /// Enable bit of the spectral inversion indicator
+    pub spectral_inversion_enable: bool,
+    /// Enable bit of the overrange indicator
+    pub overrange_enable: bool,
+    /// Enable bit of the sample loss indicator
+    pub sample_loss_enable: bool,
+    /// Enable bit of user defined indicator 1
+    pub user_defined_enable_1: bool,
+    /// Enable bit of user defined indicator 2
+    pub user_defined_enable_2: bool,
+    /// Enable bit of user defined indicator 3
+    pub user_defined_enable_3: bool,
+    /// Enable bit of user defined indicator 4
+    pub user_defined_enable_4: bool,
+    /// Calibrated time indicator
+    pub calibrated_time_indicator: bool,
+    /// Valid data indicator
+    pub valid_data_indicator: bool,
+    /// Reference lock indicator
+    pub reference_lock_indicator: bool,
+    /// AGC/MGC indicator
+    pub agcmgc_indicator: bool,
+    /// Detected signal indicator
+    pub detected_signal_indicator: bool,
+    /// Spectral inversion indicator
+    pub spectral_inversion_indicator: bool,
+    /// Overrange indicator
+    pub overrange_indicator: bool,
+    /// Sample loss indicator
+    pub sample_loss_indicator: bool,
+    /// User defined indicator 1
+    pub user_defined_indicator_1: bool,
+    /// User defined indicator 2
+    pub user_defined_indicator_2: bool,
+    /// User defined indicator 3
+    pub user_defined_indicator_3: bool,
+    /// User defined indicator 4
+    pub user_defined_indicator_4: bool,
+    /// Whether `associated_context_packet_count` is defined
+    pub associated_context_packet_count_enable: bool,
+    /// Associated context packet count
+    pub associated_context_packet_count: u8,
+}
+
+impl From<Trailer> for VrtTrailer {
+    fn from(trailer: Trailer) -> Self {
+        VrtTrailer {
+            calibrated_time_enable: trailer.calibrated_time_enable,
+            valid_data_enable: trailer.valid_data_enable,
+            reference_lock_enable: trailer.reference_lock_enable,
+            agcmgc_enable: trailer.agcmgc_enable,
+            detected_signal_enable: trailer.detected_signal_enable,
+            spectral_inversion_enable: trailer.spectral_inversion_enable,
+            overrange_enable: trailer.overrange_enable,
+            sample_loss_enable: trailer.sample_loss_enable,
+            user_defined_enable_1: trailer.user_defined_enable_1,
+            user_defined_enable_2: trailer.user_defined_enable_2,
+            user_defined_enable_3: trailer.user_defined_enable_3,
+            user_defined_enable_4: trailer.user_defined_enable_4,
+            calibrated_time_indicator: trailer.calibrated_time_indicator,
+            valid_data_indicator: trailer.valid_data_indicator,
+            reference_lock_indicator: trailer.reference_lock_indicator,
+            agcmgc_indicator: trailer.agcmgc_indicator,
+            detected_signal_indicator: trailer.detected_signal_indicator,
+            spectral_inversion_indicator: trailer.spectral_inversion_indicator,
+            overrange_indicator: trailer.overrange_indicator,
+            sample_loss_indicator: trailer.sample_loss_indicator,
+            user_defined_indicator_1: trailer.user_defined_indicator_1,
+            user_defined_indicator_2: trailer.user_defined_indicator_2,
+            user_defined_indicator_3: trailer.user_defined_indicator_3,
+            user_defined_indicator_4: trailer.user_defined_indicator_4,
+            associated_context_packet_count_enable: trailer.associated_context_packet_count_enable,
+            associated_context_packet_count: trailer.associated_context_packet_count,
+        }
+    }
+}
+
+impl From<VrtTrailer> for Trailer {
+    fn from(trailer: VrtTrailer) -> Self {
+        Trailer {
+            calibrated_time_enable: trailer.calibrated_time_enable,
+            valid_data_enable: trailer.valid_data_enable,
+            reference_lock_enable: trailer.reference_lock_enable,
+            agcmgc_enable: trailer.agcmgc_enable,
+            detected_signal_enable: trailer.detected_signal_enable,
+            spectral_inversion_enable: trailer.spectral_inversion_enable,
+            overrange_enable: trailer.overrange_enable,
+            sample_loss_enable: trailer.sample_loss_enable,
+            user_defined_enable_1: trailer.user_defined_enable_1,
+            user_defined_enable_2: trailer.user_defined_enable_2,
+            user_defined_enable_3: trailer.user_defined_enable_3,
+            user_defined_enable_4: trailer.user_defined_enable_4,
+            calibrated_time_indicator: trailer.calibrated_time_indicator,
+            valid_data_indicator: trailer.valid_data_indicator,
+            reference_lock_indicator: trailer.reference_lock_indicator,
+            agcmgc_indicator: trailer.agcmgc_indicator,
+            detected_signal_indicator: trailer.detected_signal_indicator,
+            spectral_inversion_indicator: trailer.spectral_inversion_indicator,
+            overrange_indicator: trailer.overrange_indicator,
+            sample_loss_indicator: trailer.sample_loss_indicator,
+            user_defined_indicator_1: trailer.user_defined_indicator_1,
+            user_defined_indicator_2: trailer.user_defined_indicator_2,
+            user_defined_indicator_3: trailer.user_defined_indicator_3,
+            user_defined_indicator_4: trailer.user_defined_indicator_4,
+            associated_context_packet_count_enable: trailer.associated_context_packet_count_enable,
+            associated_context_packet_count: trailer.associated_context_packet_count,
+        }
+    }
+}
+
+/// Header fields of one packet and the location of its samples, filled by
+/// [`vrt_buffer_unpack`]
+///
+/// Absent optional fields are reported as false or 0.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct VrtPacketInfo {
+    /// Packet header
+    pub header: VrtHeader,
+    /// Whether the Stream Identifier is present
+    pub has_stream_id: bool,
+    /// Stream Identifier
+    pub stream_id: u32,
+    /// Class Identifier field
+    pub class_id: u64,
+    /// Integer-seconds Timestamp
+    pub tsi: u32,
+    /// Fractional-seconds Timestamp
+    pub tsf: u64,
+    /// Index of the first sample of the packet in the sample array
+    pub sample_offset: usize,
+    /// Number of int16 samples in the packet
+    pub sample_count: usize,
+}
+
+/// Context Indicator Field 0 bit of the Context Field Change Indicator
+pub const VRT_CIF0_CHANGE_INDICATOR: u32 = 1 << 31;
+/// Context Indicator Field 0 bit of the Reference Point Identifier
+pub const VRT_CIF0_REFERENCE_POINT_ID: u32 = 1 << 30;
+/// Context Indicator Field 0 bit of the Bandwidth
+pub const VRT_CIF0_BANDWIDTH: u32 = 1 << 29;
+/// Context Indicator Field 0 bit of the IF Reference Frequency
+pub const VRT_CIF0_IF_REFERENCE_FREQUENCY: u32 = 1 << 28;
+/// Context Indicator Field 0 bit of the RF Reference Frequency
+pub const VRT_CIF0_RF_REFERENCE_FREQUENCY: u32 = 1 << 27;
+/// Context Indicator Field 0 bit of the RF Reference Frequency Offset
+pub const VRT_CIF0_RF_REFERENCE_FREQUENCY_OFFSET: u32 = 1 << 26;
+/// Context Indicator Field 0 bit of the IF Band Offset
+pub const VRT_CIF0_IF_BAND_OFFSET: u32 = 1 << 25;
+/// Context Indicator Field 0 bit of the Reference Level
+pub const VRT_CIF0_REFERENCE_LEVEL: u32 = 1 << 24;
+/// Context Indicator Field 0 bit of the Gain
+pub const VRT_CIF0_GAIN: u32 = 1 << 23;
+/// Context Indicator Field 0 bit of the Over-Range Count
+pub const VRT_CIF0_OVER_RANGE_COUNT: u32 = 1 << 22;
+/// Context Indicator Field 0 bit of the Sample Rate
+pub const VRT_CIF0_SAMPLE_RATE: u32 = 1 << 21;
+/// Context Indicator Field 0 bit of the Timestamp Adjustment
+pub const VRT_CIF0_TIMESTAMP_ADJUSTMENT: u32 = 1 << 20;
+/// Context Indicator Field 0 bit of the Timestamp Calibration Time
+pub const VRT_CIF0_TIMESTAMP_CALIBRATION_TIME: u32 = 1 << 19;
+/// Context Indicator Field 0 bit of the Temperature
+pub const VRT_CIF0_TEMPERATURE: u32 = 1 << 18;
+
+/// Context Indicator Field 0 bits of the fields mirrored by [`VrtContext`]
+const VRT_CONTEXT_FIELDS: u32 = 0x7ffc_0000;
+
+/// `#[repr(C)]` mirror of the Context Indicator Field 0 fields decoded by [`ContextFields`],
+/// from the Reference Point Identifier to the Temperature
+///
+/// A field is present if its `VRT_CIF0_*` bit is set in `cif0`; the values of absent fields
+/// are zero. Later fields, such as the Device Identifier and the GPS and INS fields, and the
+/// fields of the extension indicator words are not mirrored and their bits are clear.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct VrtContext {
+    /// Context Indicator Field 0, limited to the Change Indicator and the mirrored fields
+    pub cif0: u32,
+    /// Reference Point Identifier
+    pub reference_point_id: u32,
+    /// Bandwidth in Hz
+    pub bandwidth: f64,
+    /// IF Reference Frequency in Hz
+    pub if_reference_frequency: f64,
+    /// RF Reference Frequency in Hz
+    pub rf_reference_frequency: f64,
+    /// RF Reference Frequency Offset in Hz
+    pub rf_reference_frequency_offset: f64,
+    /// IF Band Offset in Hz
+    pub if_band_offset: f64,
+    /// Reference Level in dBm
+    pub reference_level: f64,
+    /// Gain in dB, the sum of both stages
+    pub gain: f64,
+    /// Over-Range Count
+    pub over_range_count: u32,
+    /// Sample Rate in Hz
+    pub sample_rate: f64,
+    /// Timestamp Adjustment in femtoseconds
+    pub timestamp_adjustment: i64,
+    /// Timestamp Calibration Time
+    pub timestamp_calibration_time: u32,
+    /// Temperature in degrees Celsius
+    pub temperature: f64,
+}
+
+impl From<ContextFields<'_>> for VrtContext {
+    fn from(fields: ContextFields<'_>) -> Self {
+        let mut context = VrtContext {
+            cif0: fields.cif0().unwrap_or(0) & VRT_CIF0_CHANGE_INDICATOR,
+            ..VrtContext::default()
+        };
+        for field in fields {
+            let (raw, value) = match (field.raw(), field.value()) {
+                (Some(raw), Some(value)) => (raw, value.as_f64().unwrap_or(0.0)),
+                _ => continue,
+            };
+            match field.field {
+                Cif0Field::ReferencePointId => context.reference_point_id = raw as u32,
+                Cif0Field::Bandwidth => context.bandwidth = value,
+                Cif0Field::IfReferenceFrequency => context.if_reference_frequency = value,
+                Cif0Field::RfReferenceFrequency => context.rf_reference_frequency = value,
+                Cif0Field::RfReferenceFrequencyOffset => {
+                    context.rf_reference_frequency_offset = value
+                }
+                Cif0Field::IfBandOffset => context.if_band_offset = value,
+                Cif0Field::ReferenceLevel => context.reference_level = value,
+                Cif0Field::Gain => context.gain = value,
+                Cif0Field::OverRangeCount => context.over_range_count = raw as u32,
+                Cif0Field::SampleRate => context.sample_rate = value,
+                Cif0Field::TimestampAdjustment => context.timestamp_adjustment = raw as i64,
+                Cif0Field::TimestampCalibrationTime => {
+                    context.timestamp_calibration_time = raw as u32
+                }
+                Cif0Field::Temperature => context.temperature = value,
+                _ => continue,
+            }
+            context.cif0 |= 1 << field.field.bit();
+        }
+        context
+    }
+}
+
+impl VrtContext {
+    /// Encodes the present fields as a context packet payload, starting with the Context
+    /// Indicator Field 0 word
+    pub fn to_payload(&self) -> Vec<u8> {
+        let cif0 = self.cif0 & (VRT_CIF0_CHANGE_INDICATOR | VRT_CONTEXT_FIELDS);
+        let radix = |value: f64, radix: u32| {
+            let raw = (value * (1u32 << radix) as f64).round() as i16;
+            (raw as u16 as u32).to_be_bytes()
+        };
+        let mut payload = cif0.to_be_bytes().to_vec();
+        for field in Cif0Field::ALL {
+            if cif0 >> field.bit() & 1 == 0 {
+                continue;
+            }
+            match field {
+                Cif0Field::ReferencePointId => {
+                    payload.extend_from_slice(&self.reference_point_id.to_be_bytes())
+                }
+                Cif0Field::Bandwidth => payload.extend_from_slice(&radix20(self.bandwidth)),
+                Cif0Field::IfReferenceFrequency => {
+                    payload.extend_from_slice(&radix20(self.if_reference_frequency))
+                }
+                Cif0Field::RfReferenceFrequency => {
+                    payload.extend_from_slice(&radix20(self.rf_reference_frequency))
+                }
+                Cif0Field::RfReferenceFrequencyOffset => {
+                    payload.extend_from_slice(&radix20(self.rf_reference_frequency_offset))
+                }
+                Cif0Field::IfBandOffset => payload.extend_from_slice(&radix20(self.if_band_offset)),
+                Cif0Field::ReferenceLevel => {
+                    payload.extend_from_slice(&radix(self.reference_level, 7))
+                }
+                // all of the gain in stage 1
+                Cif0Field::Gain => payload.extend_from_slice(&radix(self.gain, 7)),
+                Cif0Field::OverRangeCount => {
+                    payload.extend_from_slice(&self.over_range_count.to_be_bytes())
+                }
+                Cif0Field::SampleRate => payload.extend_from_slice(&radix20(self.sample_rate)),
+                Cif0Field::TimestampAdjustment => {
+                    payload.extend_from_slice(&self.timestamp_adjustment.to_be_bytes())
+                }
+                Cif0Field::TimestampCalibrationTime => {
+                    payload.extend_from_slice(&self.timestamp_calibration_time.to_be_bytes())
+                }
+                Cif0Field::Temperature => payload.extend_from_slice(&radix(self.temperature, 6)),
+                _ => {}
+            }
+        }
+        payload
+    }
+}
+
+/// Opaque packet handle
+#[derive(Debug)]
+pub struct VrtPacket(VrtPacketOwned);
+
+impl VrtPacket {
+    fn new(packet_type: u8, stream_id: u32) -> *mut VrtPacket {
+        let packet = VrtPacketOwned {
+            header: Header {
+                packet_type: VitaPacketType(packet_type),
+                c: false,
+                t: false,
+                indicators: 0,
+                tsi: Tsi::TSI_NONE,
+                tsf: Tsf::TSF_NONE,
+                packet_count: 0,
+                packet_size: 2,
+            },
+            stream_id: Some(stream_id),
+            class_id: None,
+            tsi: None,
+            tsf: None,
+            data_payload: Vec::new(),
+            trailer: None,
+        };
+        Box::into_raw(Box::new(VrtPacket(packet)))
+    }
+}
+
+/// Parses the packet at the start of `buf`
+///
+/// On success `*out` is set to a new packet handle and `*consumed`, if not null, to the length
+/// of the packet in bytes.
+///
+/// # Safety
+///
+/// `buf` must point to `len` readable bytes, `out` must be a valid pointer and `consumed` must
+/// be null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_packet_parse(
+    buf: *const u8,
+    len: usize,
+    out: *mut *mut VrtPacket,
+    consumed: *mut usize,
+) -> i32 {
+    if buf.is_null() || out.is_null() {
+        return VRT_ERR_INVALID_ARGUMENT;
+    }
+    let input = slice::from_raw_parts(buf, len);
+    match parse_vrt_packet(input) {
+        Ok((rest, packet)) => {
+            *out = Box::into_raw(Box::new(VrtPacket(VrtPacketOwned::from(&packet))));
+            if !consumed.is_null() {
+                *consumed = len - rest.len();
+            }
+            VRT_OK
+        }
+        Err(e) => status(e.into()),
+    }
+}
+
+/// Creates an IF data packet with the Stream Identifier `stream_id` and an empty payload
+#[no_mangle]
+pub extern "C" fn vrt_packet_new_data(stream_id: u32) -> *mut VrtPacket {
+    VrtPacket::new(VitaPacketType::IFDATAWITHSTREAM, stream_id)
+}
+
+/// Creates an IF context packet with the Stream Identifier `stream_id` and no context fields
+#[no_mangle]
+pub extern "C" fn vrt_packet_new_context(stream_id: u32) -> *mut VrtPacket {
+    VrtPacket::new(VitaPacketType::IFCONTEXT, stream_id)
+}
+
+/// Releases a packet handle
+///
+/// # Safety
+///
+/// `packet` must be null or a handle which has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_packet_free(packet: *mut VrtPacket) {
+    if !packet.is_null() {
+        drop(Box::from_raw(packet));
+    }
+}
+
+/// Returns the packet type, or 0xff if `packet` is null
+///
+/// # Safety
+///
+/// `packet` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_packet_type(packet: *const VrtPacket) -> u8 {
+    packet.as_ref().map_or(0xff, |p| p.0.header.packet_type.0)
+}
+
+/// Returns the packet count, or 0 if `packet` is null
+///
+/// # Safety
+///
+/// `packet` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_packet_count(packet: *const VrtPacket) -> u8 {
+    packet.as_ref().map_or(0, |p| p.0.header.packet_count)
+}
+
+/// Sets the packet count, which is taken modulo 16
+///
+/// # Safety
+///
+/// `packet` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_packet_set_count(packet: *mut VrtPacket, packet_count: u8) {
+    if let Some(p) = packet.as_mut() {
+        p.0.header.packet_count = packet_count & 0x0f;
+    }
+}
+
+/// Stores the Stream Identifier in `*stream_id`, returning false if the packet has none
+///
+/// # Safety
+///
+/// `packet` must be null or a valid handle and `stream_id` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_packet_stream_id(
+    packet: *const VrtPacket,
+    stream_id: *mut u32,
+) -> bool {
+    match (
+        packet.as_ref().and_then(|p| p.0.stream_id),
+        stream_id.is_null(),
+    ) {
+        (Some(id), false) => {
+            *stream_id = id;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Stores the Class Identifier in `*class_id`, returning false if the packet has none
+///
+/// # Safety
+///
+/// `packet` must be null or a valid handle and `class_id` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_packet_class_id(packet: *const VrtPacket, class_id: *mut u64) -> bool {
+    match (
+        packet.as_ref().and_then(|p| p.0.class_id),
+        class_id.is_null(),
+    ) {
+        (Some(id), false) => {
+            *class_id = id;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Stores the header in `*header`, returning false if `packet` or `header` is null
+///
+/// # Safety
+///
+/// `packet` must be null or a valid handle and `header` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_packet_header(
+    packet: *const VrtPacket,
+    header: *mut VrtHeader,
+) -> bool {
+    match (packet.as_ref(), header.is_null()) {
+        (Some(p), false) => {
+            *header = p.0.header.into();
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Stores the trailer in `*trailer`, returning false if the packet has none
+///
+/// # Safety
+///
+/// `packet` must be null or a valid handle and `trailer` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_packet_trailer(
+    packet: *const VrtPacket,
+    trailer: *mut VrtTrailer,
+) -> bool {
+    match (packet.as_ref().and_then(|p| p.0.trailer), trailer.is_null()) {
+        (Some(t), false) => {
+            *trailer = t.into();
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Sets the trailer to a copy of `*trailer`, or removes it if `trailer` is null
+///
+/// # Safety
+///
+/// `packet` must be null or a valid handle and `trailer` null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_packet_set_trailer(
+    packet: *mut VrtPacket,
+    trailer: *const VrtTrailer,
+) -> i32 {
+    match packet.as_mut() {
+        Some(p) => {
+            p.0.trailer = trailer.as_ref().map(|&t| t.into());
+            p.0.header.t = p.0.trailer.is_some();
+            VRT_OK
+        }
+        None => VRT_ERR_INVALID_ARGUMENT,
+    }
+}
+
+/// Splits a Class Identifier field into its components
+#[no_mangle]
+pub extern "C" fn vrt_class_id_split(class_id: u64) -> VrtClassId {
+    class_id.into()
+}
+
+/// Joins the components of a Class Identifier into a Class Identifier field
+#[no_mangle]
+pub extern "C" fn vrt_class_id_join(class_id: VrtClassId) -> u64 {
+    class_id.into()
+}
+
+/// Stores the timestamp types and values, returning false if the packet has no timestamp
+///
+/// Absent timestamp fields are reported with type 0 and value 0.
+///
+/// # Safety
+///
+/// `packet` must be null or a valid handle and the other arguments valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_packet_timestamp(
+    packet: *const VrtPacket,
+    tsi_type: *mut u8,
+    tsi: *mut u32,
+    tsf_type: *mut u8,
+    tsf: *mut u64,
+) -> bool {
+    if tsi_type.is_null() || tsi.is_null() || tsf_type.is_null() || tsf.is_null() {
+        return false;
+    }
+    match packet.as_ref().and_then(|p| p.0.as_packet().timestamp()) {
+        Some(timestamp) => {
+            *tsi_type = timestamp.tsi.0;
+            *tsi = timestamp.integer;
+            *tsf_type = timestamp.tsf.0;
+            *tsf = timestamp.fractional;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Sets the timestamp, where a type of 0 removes the corresponding field
+///
+/// # Safety
+///
+/// `packet` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_packet_set_timestamp(
+    packet: *mut VrtPacket,
+    tsi_type: u8,
+    tsi: u32,
+    tsf_type: u8,
+    tsf: u64,
+) -> i32 {
+    let packet = match packet.as_mut() {
+        Some(p) if tsi_type <= 3 && tsf_type <= 3 => &mut p.0,
+        _ => return VRT_ERR_INVALID_ARGUMENT,
+    };
+    packet.header.tsi = Tsi(tsi_type);
+    packet.header.tsf = Tsf(tsf_type);
+    packet.tsi = Some(tsi).filter(|_| tsi_type != 0);
+    packet.tsf = Some(tsf).filter(|_| tsf_type != 0);
+    VRT_OK
+}
+
+/// Stores the context fields of a context packet in `*context`, returning false if the packet
+/// is not a context packet
+///
+/// # Safety
+///
+/// `packet` must be null or a valid handle and `context` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_packet_context(
+    packet: *const VrtPacket,
+    context: *mut VrtContext,
+) -> bool {
+    match (packet.as_ref(), context.is_null()) {
+        (Some(p), false) if p.0.header.packet_type.is_context() => {
+            *context = p.0.as_packet().context_fields().into();
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Replaces the payload of a context packet with the fields of `*context`
+///
+/// # Safety
+///
+/// `packet` must be null or a valid handle and `context` null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_packet_set_context(
+    packet: *mut VrtPacket,
+    context: *const VrtContext,
+) -> i32 {
+    match (packet.as_mut(), context.as_ref()) {
+        (Some(p), Some(context)) if p.0.header.packet_type.is_context() => {
+            p.0.data_payload = context.to_payload();
+            VRT_OK
+        }
+        _ => VRT_ERR_INVALID_ARGUMENT,
+    }
+}
+
+/// Returns a pointer to the data payload and stores its length in `*len`
+///
+/// The pointer is valid until the packet is modified or released.
+///
+/// # Safety
+///
+/// `packet` must be null or a valid handle and `len` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_packet_payload(
+    packet: *const VrtPacket,
+    len: *mut usize,
+) -> *const u8 {
+    match (packet.as_ref(), len.is_null()) {
+        (Some(p), false) => {
+            *len = p.0.data_payload.len();
+            p.0.data_payload.as_ptr()
+        }
+        _ => std::ptr::null(),
+    }
+}
+
+/// Replaces the data payload with a copy of `len` bytes at `payload`
+///
+/// # Safety
+///
+/// `packet` must be null or a valid handle, and `payload` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_packet_set_payload(
+    packet: *mut VrtPacket,
+    payload: *const u8,
+    len: usize,
+) -> i32 {
+    match packet.as_mut() {
+        Some(p) if !payload.is_null() || len == 0 => {
+            p.0.data_payload = match len {
+                0 => Vec::new(),
+                _ => slice::from_raw_parts(payload, len).to_vec(),
+            };
+            VRT_OK
+        }
+        _ => VRT_ERR_INVALID_ARGUMENT,
+    }
+}
+
+/// Returns the length in bytes of the encoded packet, or 0 if `packet` is null
+///
+/// # Safety
+///
+/// `packet` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_packet_encoded_len(packet: *const VrtPacket) -> usize {
+    packet.as_ref().map_or(0, |p| p.0.as_packet().encoded_len())
+}
+
+/// Encodes the packet into `buf`, storing the number of bytes written in `*written`
+///
+/// # Safety
+///
+/// `packet` must be null or a valid handle, `buf` must point to `len` writable bytes and
+/// `written` must be null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_packet_serialize(
+    packet: *const VrtPacket,
+    buf: *mut u8,
+    len: usize,
+    written: *mut usize,
+) -> i32 {
+    let packet = match packet.as_ref() {
+        Some(p) if !buf.is_null() => p,
+        _ => return VRT_ERR_INVALID_ARGUMENT,
+    };
+    let buf = slice::from_raw_parts_mut(buf, len);
+    match encode_vrt_packet(&packet.0.as_packet(), buf) {
+        Ok(n) => {
+            if !written.is_null() {
+                *written = n;
+            }
+            VRT_OK
+        }
+        Err(e) => status(e),
+    }
+}
+
+/// Counts the complete packets in `buf` and the int16 samples in their payloads
+///
+/// A partial packet at the end of `buf` is ignored.
+///
+/// # Safety
+///
+/// `buf` must point to `len` readable bytes and `packets` and `samples` must be valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_buffer_count(
+    buf: *const u8,
+    len: usize,
+    packets: *mut usize,
+    samples: *mut usize,
+) -> i32 {
+    if buf.is_null() || packets.is_null() || samples.is_null() {
+        return VRT_ERR_INVALID_ARGUMENT;
+    }
+    let (mut n, mut total) = (0, 0);
+    for packet in PacketStream::new(slice::from_raw_parts(buf, len)) {
+        match packet {
+            Ok(p) => {
+                n += 1;
+                total += p.data_payload.len() / 2;
+            }
+            Err(e) => return status(e),
+        }
+    }
+    *packets = n;
+    *samples = total;
+    VRT_OK
+}
+
+/// Unpacks the complete packets in `buf` into `infos` and their big-endian int16 payload
+/// samples into `samples`
+///
+/// The arrays are filled in packet order and `*written`, if not null, is set to the number of
+/// packets unpacked, which is 0 if an argument is invalid or `buf` holds no packets. Size the
+/// arrays with [`vrt_buffer_count`]; if they are too small nothing is written past their end and
+/// `VRT_ERR_BUFFER_TOO_SMALL` is returned.
+///
+/// # Safety
+///
+/// `buf` must point to `len` readable bytes, `infos` to `max_packets` writable infos,
+/// `samples` to `max_samples` writable samples, and `written` must be null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn vrt_buffer_unpack(
+    buf: *const u8,
+    len: usize,
+    infos: *mut VrtPacketInfo,
+    max_packets: usize,
+    samples: *mut i16,
+    max_samples: usize,
+    written: *mut usize,
+) -> i32 {
+    if !written.is_null() {
+        *written = 0;
+    }
+    if buf.is_null() || (infos.is_null() && max_packets > 0) {
+        return VRT_ERR_INVALID_ARGUMENT;
+    }
+    if samples.is_null() && max_samples > 0 {
+        return VRT_ERR_INVALID_ARGUMENT;
+    }
+    let (mut n, mut offset) = (0, 0);
+    for packet in PacketStream::new(slice::from_raw_parts(buf, len)) {
+        let packet = match packet {
+            Ok(p) => p,
+            Err(e) => return status(e),
+        };
+        let count = packet.data_payload.len() / 2;
+        if n == max_packets || max_samples - offset < count {
+            return VRT_ERR_BUFFER_TOO_SMALL;
+        }
+        *infos.add(n) = VrtPacketInfo {
+            header: packet.header.into(),
+            has_stream_id: packet.stream_id.is_some(),
+            stream_id: packet.stream_id.unwrap_or(0),
+            class_id: packet.class_id.unwrap_or(0),
+            tsi: packet.tsi.unwrap_or(0),
+            tsf: packet.tsf.unwrap_or(0),
+            sample_offset: offset,
+            sample_count: count,
+        };
+        for (i, pair) in packet.data_payload.chunks_exact(2).enumerate() {
+            *samples.add(offset + i) = i16::from_be_bytes([pair[0], pair[1]]);
+        }
+        offset += count;
+        n += 1;
+        if !written.is_null() {
+            *written = n;
+        }
+    }
+    VRT_OK
+}
@@ -0,0 +1,118 @@
+//! Packet filter expressions.
+
+use crate::vrt::{Timestamp, Trailer, VitaPacketType, VrtPacket};
+use std::fmt;
+use std::ops::Not;
+
+#[derive(Default)]
+/// Packet Filter Expression
+///
+/// Filters are combined with [`Filter::and`], [`Filter::or`] and `!` to build expressions
+/// which are evaluated against packets with [`Filter::matches`]:
+///
+/// ```
+/// use vrt::{Filter, VitaPacketType};
+///
+/// let filter = Filter::PacketType(VitaPacketType(VitaPacketType::IFDATAWITHSTREAM))
+///     .and(Filter::StreamIds(vec![1, 2]))
+///     .and(!Filter::Trailer(Box::new(|t| {
+///         t.valid_data_enable && !t.valid_data_indicator
+///     })));
+/// ```
+pub enum Filter {
+    /// Matches every packet.
+    #[default]
+    Any,
+    /// Matches packets of the given packet type.
+    PacketType(VitaPacketType),
+    /// Matches packets with one of the given Stream Identifiers.
+    StreamIds(Vec<u32>),
+    /// Matches packets with the given Class Identifier.
+    ClassId(u64),
+    /// Matches packets with a timestamp at or after `start` and before `end`, in the order of
+    /// [`Timestamp`]. Packets without a timestamp never match.
+    TimeRange {
+        /// Start of the time range, inclusive.
+        start: Timestamp,
+        /// End of the time range, exclusive.
+        end: Timestamp,
+    },
+    /// Matches packets with a trailer for which the predicate returns true.
+    Trailer(Box<dyn Fn(&Trailer) -> bool + Send + Sync>),
+    /// Matches packets matching both filters.
+    And(Box<Filter>, Box<Filter>),
+    /// Matches packets matching either filter.
+    Or(Box<Filter>, Box<Filter>),
+    /// Matches packets not matching the filter.
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    /// Returns a filter matching packets which match both `self` and `other`
+    pub fn and(self, other: Filter) -> Filter {
+        Filter::And(Box::new(self), Box::new(other))
+    }
+
+    /// Returns a filter matching packets which match either `self` or `other`
+    pub fn or(self, other: Filter) -> Filter {
+        Filter::Or(Box::new(self), Box::new(other))
+    }
+
+    /// Returns true if `packet` matches the filter
+    pub fn matches(&self, packet: &VrtPacket<'_>) -> bool {
+        match self {
+            Filter::Any => true,
+            Filter::PacketType(packet_type) => packet.header.packet_type == *packet_type,
+            Filter::StreamIds(stream_ids) => packet
+                .stream_id
+                .is_some_and(|stream_id| stream_ids.contains(&stream_id)),
+            Filter::ClassId(class_id) => packet.class_id == Some(*class_id),
+            Filter::TimeRange { start, end } => packet
+                .timestamp()
+                .is_some_and(|t| (start..end).contains(&&t)),
+            Filter::Trailer(predicate) => packet.trailer.as_ref().is_some_and(predicate),
+            Filter::And(a, b) => a.matches(packet) && b.matches(packet),
+            Filter::Or(a, b) => a.matches(packet) || b.matches(packet),
+            Filter::Not(a) => !a.matches(packet),
+        }
+    }
+
+    /// Returns an iterator over the packets of `packets` which match the filter
+    pub fn apply<'f, 'a, I>(&'f self, packets: I) -> impl Iterator<Item = VrtPacket<'a>> + 'f
+    where
+        I: IntoIterator<Item = VrtPacket<'a>>,
+        I::IntoIter: 'f,
+    {
+        packets.into_iter().filter(move |p| self.matches(p))
+    }
+}
+
+impl fmt::Debug for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Filter::Any => f.write_str("Any"),
+            Filter::PacketType(packet_type) => {
+                f.debug_tuple("PacketType").field(packet_type).finish()
+            }
+            Filter::StreamIds(stream_ids) => f.debug_tuple("StreamIds").field(stream_ids).finish(),
+            Filter::ClassId(class_id) => f.debug_tuple("ClassId").field(class_id).finish(),
+            Filter::TimeRange { start, end } => f
+                .debug_struct("TimeRange")
+                .field("start", start)
+                .field("end", end)
+                .finish(),
+            Filter::Trailer(_) => f.debug_tuple("Trailer").finish_non_exhaustive(),
+            Filter::And(a, b) => f.debug_tuple("And").field(a).field(b).finish(),
+            Filter::Or(a, b) => f.debug_tuple("Or").field(a).field(b).finish(),
+            Filter::Not(a) => f.debug_tuple("Not").field(a).finish(),
+        }
+    }
+}
+
+impl Not for Filter {
+    type Output = Filter;
+
+    fn not(self) -> Filter {
+        Filter::Not(Box::new(self))
+    }
+}
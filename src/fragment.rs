@@ -0,0 +1,186 @@
+//! Splitting of large sample buffers into MTU-sized VRT data packets.
+
+use crate::error::Error;
+use crate::vrt::{Header, Trailer, Tsf, Tsi, VrtPacket};
+
+const PICOSECONDS_PER_SECOND: u64 = 1_000_000_000_000;
+
+/// Splits a sample buffer into a sequence of data packets which each fit within an MTU
+///
+/// Every packet copies the header flags, Stream Identifier, Class Identifier and trailer of the
+/// template packet. The packet count is incremented for each packet, and the timestamps are
+/// advanced by the number of samples in the previous packets:
+///
+/// * Sample Count and Free Running Count fractional timestamps are advanced by the sample count.
+///   When a sample rate is set and the template has an integer-seconds timestamp, a Sample
+///   Count timestamp rolls over into the integer-seconds timestamp once per second.
+/// * Real Time (Picoseconds) fractional timestamps are advanced only when a sample rate is set,
+///   rolling over into the integer-seconds timestamp once per second.
+///
+/// Each packet's timestamps are computed from the template timestamps and the number of samples
+/// since the first packet, so they do not drift when a sample period is not a whole number of
+/// picoseconds.
+///
+/// Packet payloads always hold whole samples. The payload of the last packet is shorter than
+/// the others and, if it does not end on a 32-bit word boundary, its `packet_size` is rounded
+/// up to include the padding which must follow it on the wire.
+#[derive(Clone, Debug)]
+pub struct Fragmenter<'a> {
+    header: Header,
+    stream_id: Option<u32>,
+    class_id: Option<u64>,
+    tsi: Option<u32>,
+    tsf: Option<u64>,
+    trailer: Option<Trailer>,
+    samples: &'a [u8],
+    offset: u64,
+    payload_len: usize,
+    bytes_per_sample: usize,
+    /// Samples per second as a numerator and denominator
+    sample_rate: Option<(u64, u64)>,
+}
+
+impl<'a> Fragmenter<'a> {
+    /// Creates a new Fragmenter splitting `samples` into packets of at most `mtu` bytes
+    ///
+    /// Returns [`Error::BufferTooSmall`] if `mtu` cannot hold the header, optional fields and
+    /// at least one 32-bit word of samples, or if `bytes_per_sample` is zero.
+    pub fn new(
+        template: &VrtPacket<'_>,
+        samples: &'a [u8],
+        bytes_per_sample: usize,
+        mtu: usize,
+    ) -> Result<Self, Error> {
+        let header = template.header;
+        let stream_id = template
+            .stream_id
+            .filter(|_| header.packet_type.has_stream_id());
+        let class_id = template.class_id.filter(|_| header.c);
+        let tsi = template.tsi.filter(|_| header.tsi != Tsi::TSI_NONE);
+        let tsf = template.tsf.filter(|_| header.tsf != Tsf::TSF_NONE);
+        let trailer = template.trailer.filter(|_| header.t);
+
+        let mut fragmenter = Fragmenter {
+            header,
+            stream_id,
+            class_id,
+            tsi,
+            tsf,
+            trailer,
+            samples,
+            offset: 0,
+            payload_len: 0,
+            bytes_per_sample,
+            sample_rate: None,
+        };
+
+        // largest payload of whole samples which also ends on a word boundary
+        let step = match bytes_per_sample {
+            0 => return Err(Error::BufferTooSmall),
+            n if n % 4 == 0 => n,
+            n if n % 2 == 0 => n * 2,
+            n => n * 4,
+        };
+        let max_words = (mtu / 4).min(u16::MAX as usize);
+        let payload_words = max_words.saturating_sub(fragmenter.overhead_words());
+        fragmenter.payload_len = (payload_words * 4) / step * step;
+        if fragmenter.payload_len == 0 {
+            return Err(Error::BufferTooSmall);
+        }
+        Ok(fragmenter)
+    }
+
+    /// Sets the sample rate in samples per second, used to advance the timestamps
+    pub fn with_sample_rate(self, sample_rate: u64) -> Self {
+        self.with_sample_rate_ratio(sample_rate, 1)
+    }
+
+    /// Sets the sample rate to `numerator` / `denominator` samples per second, for rates which
+    /// are not a whole number of hertz
+    ///
+    /// Sample Count timestamps roll over into the integer-seconds timestamp only for whole
+    /// rates, as a second does not otherwise hold a whole number of samples.
+    pub fn with_sample_rate_ratio(mut self, numerator: u64, denominator: u64) -> Self {
+        self.sample_rate = Some((numerator, denominator)).filter(|(n, d)| *n > 0 && *d > 0);
+        self
+    }
+
+    /// Returns the maximum payload length of each packet in bytes
+    pub fn payload_len(&self) -> usize {
+        self.payload_len
+    }
+
+    fn overhead_words(&self) -> usize {
+        1 + self.stream_id.map_or(0, |_| 1)
+            + self.class_id.map_or(0, |_| 2)
+            + self.tsi.map_or(0, |_| 1)
+            + self.tsf.map_or(0, |_| 2)
+            + self.trailer.map_or(0, |_| 1)
+    }
+
+    /// Returns the timestamps of the packet starting `self.offset` samples after the first,
+    /// computed from the template timestamps so that rounding does not accumulate
+    fn timestamps(&self) -> (Option<u32>, Option<u64>) {
+        let tsf = match self.tsf {
+            Some(tsf) => tsf,
+            None => return (self.tsi, None),
+        };
+        let (tsf, carry) = match (self.header.tsf, self.sample_rate, self.tsi) {
+            (Tsf::TSF_SAMPLE_COUNT, Some((rate, 1)), Some(_)) => {
+                let total = tsf as u128 + self.offset as u128;
+                ((total % rate as u128) as u64, total / rate as u128)
+            }
+            (Tsf::TSF_SAMPLE_COUNT, _, _) | (Tsf::TSF_FREE_RUNNING, _, _) => {
+                (tsf.wrapping_add(self.offset), 0)
+            }
+            (Tsf::TSF_REAL_TIME, Some((numerator, denominator)), _) => {
+                let picos = PICOSECONDS_PER_SECOND as u128;
+                // whole and partial seconds separately, so that neither product overflows
+                let scaled = self.offset as u128 * denominator as u128;
+                let (seconds, rest) = (scaled / numerator as u128, scaled % numerator as u128);
+                let elapsed = seconds
+                    .saturating_mul(picos)
+                    .saturating_add(rest * picos / numerator as u128);
+                let total = tsf as u128 + elapsed;
+                ((total % picos) as u64, total / picos)
+            }
+            _ => (tsf, 0),
+        };
+        // integer-seconds timestamps wrap at 2^32 seconds
+        let tsi = self.tsi.map(|tsi| tsi.wrapping_add(carry as u32));
+        (tsi, Some(tsf))
+    }
+}
+
+impl<'a> Iterator for Fragmenter<'a> {
+    type Item = VrtPacket<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let len = self.payload_len.min(self.samples.len());
+        let (data_payload, rest) = self.samples.split_at(len);
+        self.samples = rest;
+
+        let mut header = self.header;
+        header.packet_size = (self.overhead_words() + len.div_ceil(4)) as u16;
+
+        let (tsi, tsf) = self.timestamps();
+        let packet = VrtPacket {
+            header,
+            stream_id: self.stream_id,
+            class_id: self.class_id,
+            tsi,
+            tsf,
+            data_payload,
+            trailer: self.trailer,
+        };
+
+        self.header.packet_count = (self.header.packet_count + 1) & 0x0f;
+        self.offset = self
+            .offset
+            .wrapping_add((len / self.bytes_per_sample) as u64);
+        Some(packet)
+    }
+}
@@ -0,0 +1,83 @@
+//! Fuzzing entry points, shared by the crate's `cargo fuzz` targets.
+//!
+//! Each function runs a group of parsers over arbitrary input and panics only if an
+//! invariant between them is broken, so it can be called from a fuzz target for this crate or
+//! from one which feeds the same input on into an application's own packet handling.
+//!
+//! Available with the `fuzz-helpers` feature.
+
+use crate::net::parse_vrl_frame;
+use crate::parser::{parse_vrt_header, parse_vrt_packet, parse_vrt_trailer, TrailingBytes};
+use crate::stream::{resync, PacketStream, PushParser};
+use crate::vrt::VrtPacket;
+
+/// Parses `data` as packets with every parsing entry point, checking that they agree
+pub fn fuzz_packet(data: &[u8]) {
+    let _ = parse_vrt_header(data);
+    let _ = parse_vrt_trailer(data);
+    match (parse_vrt_packet(data), VrtPacket::parse(data)) {
+        (Ok((rest, a)), Ok((len, b))) => {
+            assert_eq!(len, data.len() - rest.len());
+            assert_eq!(a.data_payload, b.data_payload);
+            let (_, rest) = VrtPacket::parse_checked(data, TrailingBytes::Remainder).unwrap();
+            assert_eq!(rest, &data[len..]);
+        }
+        (Err(_), Err(_)) => {}
+        (a, b) => panic!("parsers disagree: {:?} and {:?}", a.is_ok(), b.is_ok()),
+    }
+
+    let mut stream = PacketStream::new(data).with_recovery();
+    for packet in stream.by_ref().flatten() {
+        assert!(packet.data_payload.len() <= data.len());
+    }
+    assert_eq!(stream.offset() + stream.remainder().len(), data.len());
+    if let Some(start) = resync(data, 2) {
+        assert!(start < data.len());
+    }
+    let _ = PushParser::<1024>::new().feed(data, |_| {});
+}
+
+/// Parses `data` as the context fields of an IF context packet and decodes them
+pub fn fuzz_context(data: &[u8]) {
+    let words = data.len().div_ceil(4) + 2;
+    if words > u16::MAX as usize {
+        return;
+    }
+    let mut bytes = vec![0x40, 0x00];
+    bytes.extend_from_slice(&(words as u16).to_be_bytes());
+    bytes.extend_from_slice(&[0, 0, 0, 1]);
+    bytes.extend_from_slice(data);
+    bytes.resize(words * 4, 0);
+    let (_, packet) = VrtPacket::parse(&bytes).unwrap();
+    packet.dissect();
+    let mut fields = packet.context_fields();
+    for field in fields.by_ref() {
+        assert_eq!(field.raw().is_some(), field.value().is_some());
+    }
+    assert!(fields.offset() <= packet.data_payload.len());
+}
+
+/// Parses `data` as a VITA 49.1 VRL frame and the packets it carries
+pub fn fuzz_vrl_frame(data: &[u8]) {
+    if let Ok((len, packets)) = parse_vrl_frame(data) {
+        assert!(len <= data.len());
+        packets.with_recovery().count();
+    }
+}
+
+/// Checks that a packet parsed from `data` reserializes to the bytes it was parsed from, and
+/// that encoding and parsing it again leaves it unchanged apart from reserved header bits
+pub fn fuzz_round_trip(data: &[u8]) {
+    let (len, packet) = match VrtPacket::parse(data) {
+        Ok(parsed) => parsed,
+        Err(_) => return,
+    };
+    assert_eq!(packet.reserialize_exact().unwrap(), &data[..len]);
+    let encoded = packet.to_vec().unwrap();
+    let (_, again) = VrtPacket::parse(&encoded).unwrap();
+    assert_eq!(again.data_payload, packet.data_payload);
+    assert_eq!(again.stream_id, packet.stream_id);
+    assert_eq!(again.class_id, packet.class_id);
+    assert_eq!((again.tsi, again.tsf), (packet.tsi, packet.tsf));
+    assert_eq!(again.trailer, packet.trailer);
+}
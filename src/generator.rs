@@ -0,0 +1,235 @@
+//! Generation of synthetic packet streams for testing and benchmarking.
+
+use crate::pace::Pacer;
+use crate::vrt::{radix20, Header, Tsf, Tsi, VitaPacketType, VrtPacketOwned, CIF0_SAMPLE_RATE};
+use std::f64::consts::TAU;
+
+const PICOSECONDS_PER_SECOND: f64 = 1e12;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Waveform of the samples carried by generated data packets
+pub enum Waveform {
+    /// A complex tone at `frequency` Hz, which may be negative, with the amplitude given as a
+    /// fraction of full scale.
+    Tone {
+        /// Tone frequency in Hz
+        frequency: f64,
+        /// Amplitude as a fraction of full scale, from 0.0 to 1.0
+        amplitude: f64,
+    },
+    /// Uniformly distributed noise with the amplitude given as a fraction of full scale. The
+    /// same seed always produces the same samples.
+    Noise {
+        /// Amplitude as a fraction of full scale, from 0.0 to 1.0
+        amplitude: f64,
+        /// Seed of the pseudo-random number generator
+        seed: u64,
+    },
+    /// A ramp in which both components of each sample hold the sample index, wrapping at the
+    /// 16-bit boundary, so dropped or reordered samples are easy to spot.
+    Ramp,
+}
+
+/// Synthetic Packet Generator
+///
+/// Produces an endless, well-formed stream of IF data packets with a Stream Identifier and UTC
+/// Real Time (Picoseconds) timestamps derived from the sample rate. Each packet carries
+/// `samples_per_packet` complex samples of 16-bit big-endian I and Q components following the
+/// [`Waveform`].
+///
+/// Periodic IF context packets carrying the Sample Rate field can be interleaved with
+/// [`Generator::with_context_interval`]; each context packet precedes the data packet sharing
+/// its timestamp. Several streams can be generated by combining generators with different
+/// Stream Identifiers, and [`Generator::pacer`] returns a [`Pacer`] sending the packets in real
+/// time.
+#[derive(Clone, Debug)]
+pub struct Generator {
+    stream_id: u32,
+    sample_rate: f64,
+    samples_per_packet: usize,
+    waveform: Waveform,
+    start: u32,
+    context_interval: Option<u64>,
+    samples: u64,
+    until_context: u64,
+    data_count: u8,
+    context_count: u8,
+    noise: u64,
+}
+
+impl Generator {
+    /// Creates a new Generator of packets of `samples_per_packet` samples of a stream at
+    /// `sample_rate` samples per second
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sample_rate` is not positive and finite, or if `samples_per_packet` is zero
+    /// or too large for a packet.
+    pub fn new(sample_rate: f64, samples_per_packet: usize, waveform: Waveform) -> Self {
+        assert!(
+            sample_rate.is_finite() && sample_rate > 0.0,
+            "sample rate must be positive"
+        );
+        assert!(
+            samples_per_packet > 0 && samples_per_packet <= u16::MAX as usize - 5,
+            "invalid number of samples per packet"
+        );
+        let noise = match waveform {
+            Waveform::Noise { seed, .. } => seed,
+            _ => 0,
+        };
+        Generator {
+            stream_id: 0,
+            sample_rate,
+            samples_per_packet,
+            waveform,
+            start: 0,
+            context_interval: None,
+            samples: 0,
+            until_context: 0,
+            data_count: 0,
+            context_count: 0,
+            noise,
+        }
+    }
+
+    /// Sets the Stream Identifier of the generated packets, which defaults to 0
+    pub fn with_stream_id(mut self, stream_id: u32) -> Self {
+        self.stream_id = stream_id;
+        self
+    }
+
+    /// Sets the integer-seconds timestamp of the first packet, which defaults to 0
+    pub fn with_start_time(mut self, secs: u32) -> Self {
+        self.start = secs;
+        self
+    }
+
+    /// Emits a context packet before the next data packet and then every `interval` data
+    /// packets
+    pub fn with_context_interval(mut self, interval: u64) -> Self {
+        self.context_interval = Some(interval).filter(|i| *i > 0);
+        self.until_context = 0;
+        self
+    }
+
+    /// Returns a Pacer emitting the data packets at the rate implied by the sample rate
+    pub fn pacer(&self) -> Pacer {
+        Pacer::from_sample_rate(self.sample_rate, self.samples_per_packet as u64)
+    }
+
+    /// Returns the timestamp of the next data packet
+    fn timestamp(&self) -> (u32, u64) {
+        let secs = (self.samples as f64 / self.sample_rate).floor();
+        let rem = self.samples as f64 - secs * self.sample_rate;
+        let picos = (rem / self.sample_rate * PICOSECONDS_PER_SECOND).round() as u64;
+        let picos = picos.min(PICOSECONDS_PER_SECOND as u64 - 1);
+        (self.start.wrapping_add(secs as u32), picos)
+    }
+
+    fn header(&self, packet_type: u8, packet_count: u8, payload_len: usize) -> Header {
+        Header {
+            packet_type: VitaPacketType(packet_type),
+            c: false,
+            t: false,
+            indicators: 0,
+            tsi: Tsi::TSI_UTC,
+            tsf: Tsf::TSF_REAL_TIME,
+            packet_count,
+            packet_size: (5 + payload_len / 4) as u16,
+        }
+    }
+
+    /// Returns the next pseudo-random number in -1.0..1.0 (splitmix64)
+    fn next_noise(&mut self) -> f64 {
+        self.noise = self.noise.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.noise;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+    }
+
+    fn sample(&mut self, n: u64) -> (i16, i16) {
+        let full_scale = |x: f64| (x * i16::MAX as f64).round() as i16;
+        match self.waveform {
+            Waveform::Tone {
+                frequency,
+                amplitude,
+            } => {
+                let phase = TAU * (n as f64 * frequency / self.sample_rate).fract();
+                (
+                    full_scale(amplitude * phase.cos()),
+                    full_scale(amplitude * phase.sin()),
+                )
+            }
+            Waveform::Noise { amplitude, .. } => {
+                let i = full_scale(amplitude * self.next_noise());
+                (i, full_scale(amplitude * self.next_noise()))
+            }
+            Waveform::Ramp => (n as i16, n as i16),
+        }
+    }
+
+    fn context_packet(&mut self) -> VrtPacketOwned {
+        let mut data_payload = CIF0_SAMPLE_RATE.to_be_bytes().to_vec();
+        data_payload.extend_from_slice(&radix20(self.sample_rate));
+        let (tsi, tsf) = self.timestamp();
+        let packet = VrtPacketOwned {
+            header: self.header(
+                VitaPacketType::IFCONTEXT,
+                self.context_count,
+                data_payload.len(),
+            ),
+            stream_id: Some(self.stream_id),
+            class_id: None,
+            tsi: Some(tsi),
+            tsf: Some(tsf),
+            data_payload,
+            trailer: None,
+        };
+        self.context_count = (self.context_count + 1) & 0x0f;
+        packet
+    }
+
+    fn data_packet(&mut self) -> VrtPacketOwned {
+        let mut data_payload = Vec::with_capacity(self.samples_per_packet * 4);
+        for n in self.samples..self.samples + self.samples_per_packet as u64 {
+            let (i, q) = self.sample(n);
+            data_payload.extend_from_slice(&i.to_be_bytes());
+            data_payload.extend_from_slice(&q.to_be_bytes());
+        }
+        let (tsi, tsf) = self.timestamp();
+        let packet = VrtPacketOwned {
+            header: self.header(
+                VitaPacketType::IFDATAWITHSTREAM,
+                self.data_count,
+                data_payload.len(),
+            ),
+            stream_id: Some(self.stream_id),
+            class_id: None,
+            tsi: Some(tsi),
+            tsf: Some(tsf),
+            data_payload,
+            trailer: None,
+        };
+        self.data_count = (self.data_count + 1) & 0x0f;
+        self.samples += self.samples_per_packet as u64;
+        self.until_context = self.until_context.saturating_sub(1);
+        packet
+    }
+}
+
+impl Iterator for Generator {
+    type Item = VrtPacketOwned;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(interval) = self.context_interval {
+            if self.until_context == 0 {
+                self.until_context = interval;
+                return Some(self.context_packet());
+            }
+        }
+        Some(self.data_packet())
+    }
+}
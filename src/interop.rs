@@ -0,0 +1,35 @@
+//! Conversion to and from the packet types of the `vita49` crate.
+//!
+//! Packets are converted through their wire encoding, so every packet type either crate can
+//! encode converts, and the conversions produce the same bytes as encoding the packet with
+//! its own crate.
+
+use crate::encoder::encode_vrt_packet;
+use crate::error::Error;
+use crate::parser::parse_vrt_packet;
+use crate::vrt::{VrtPacket, VrtPacketOwned};
+use vita49::prelude::*;
+
+impl TryFrom<&VrtPacket<'_>> for Vrt {
+    type Error = Error;
+
+    /// Encodes `packet` and parses it with `vita49`, returning [`Error::Malformed`] if
+    /// `vita49` rejects it
+    fn try_from(packet: &VrtPacket<'_>) -> Result<Self, Error> {
+        let mut buf = vec![0; packet.encoded_len()];
+        let len = encode_vrt_packet(packet, &mut buf)?;
+        Vrt::try_from(&buf[..len]).map_err(|_| Error::Malformed)
+    }
+}
+
+impl TryFrom<&Vrt> for VrtPacketOwned {
+    type Error = Error;
+
+    /// Encodes `packet` with `vita49` and parses it, returning [`Error::Malformed`] if either
+    /// step fails
+    fn try_from(packet: &Vrt) -> Result<Self, Error> {
+        let buf = packet.to_bytes().map_err(|_| Error::Malformed)?;
+        let (_, parsed) = parse_vrt_packet(&buf)?;
+        Ok(VrtPacketOwned::from(&parsed))
+    }
+}
@@ -0,0 +1,187 @@
+//! JSON Lines output of packets for processing with tools such as `jq`.
+
+use crate::context::ContextValue;
+use crate::dissect::snake_case;
+use crate::vrt::{Trailer, VrtPacket};
+use serde_json::{json, Map, Value};
+use std::io::{self, Write};
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes `bytes` as padded standard base64
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => encoded.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char),
+                false => encoded.push('='),
+            }
+        }
+    }
+    encoded
+}
+
+/// Returns the JSON value of a context field: a number in the field's unit, or the integer
+/// value of counts and bit fields
+fn context_value(value: ContextValue) -> Value {
+    match value {
+        ContextValue::Femtoseconds(v) => json!(v),
+        ContextValue::Integer(v) | ContextValue::Bits(v) => json!(v),
+        ContextValue::Hertz(v)
+        | ContextValue::Dbm(v)
+        | ContextValue::Db(v)
+        | ContextValue::Celsius(v) => json!(v),
+    }
+}
+
+fn trailer_value(trailer: &Trailer) -> Value {
+    let indicators = [
+        (
+            "calibrated_time",
+            trailer.calibrated_time_enable,
+            trailer.calibrated_time_indicator,
+        ),
+        (
+            "valid_data",
+            trailer.valid_data_enable,
+            trailer.valid_data_indicator,
+        ),
+        (
+            "reference_lock",
+            trailer.reference_lock_enable,
+            trailer.reference_lock_indicator,
+        ),
+        ("agc", trailer.agcmgc_enable, trailer.agcmgc_indicator),
+        (
+            "detected_signal",
+            trailer.detected_signal_enable,
+            trailer.detected_signal_indicator,
+        ),
+        (
+            "spectral_inversion",
+            trailer.spectral_inversion_enable,
+            trailer.spectral_inversion_indicator,
+        ),
+        (
+            "over_range",
+            trailer.overrange_enable,
+            trailer.overrange_indicator,
+        ),
+        (
+            "sample_loss",
+            trailer.sample_loss_enable,
+            trailer.sample_loss_indicator,
+        ),
+    ];
+    let mut object: Map<String, Value> = indicators
+        .iter()
+        .filter(|(_, enable, _)| *enable)
+        .map(|(name, _, indicator)| (name.to_string(), json!(indicator)))
+        .collect();
+    let count = trailer
+        .associated_context_packet_count_enable
+        .then_some(trailer.associated_context_packet_count);
+    object.insert("associated_context_packet_count".into(), json!(count));
+    Value::Object(object)
+}
+
+/// Returns the JSON object describing `packet`, with its base64 payload if `payload` is set
+pub fn packet_json(packet: &VrtPacket<'_>, payload: bool) -> Value {
+    let header = &packet.header;
+    let timestamp = packet.timestamp().map(|t| {
+        json!({
+            "tsi": t.tsi.0,
+            "integer": t.integer,
+            "tsf": t.tsf.0,
+            "fractional": t.fractional,
+        })
+    });
+    let mut object = json!({
+        "packet_type": header.packet_type.0,
+        "packet_type_name": header.packet_type.to_string(),
+        "stream_id": packet.stream_id,
+        "class_id": packet.class_id,
+        "packet_count": header.packet_count,
+        "packet_size": header.packet_size,
+        "timestamp": timestamp,
+        "payload_len": packet.data_payload.len(),
+        "trailer": packet.trailer.as_ref().map(trailer_value),
+    });
+    if header.packet_type.is_context() {
+        let decoder = packet.context_fields();
+        let mut fields = Map::new();
+        if let Some(cif0) = decoder.cif0() {
+            fields.insert("context_indicator_field_0".into(), json!(cif0));
+        }
+        for (bit, word) in decoder.extensions() {
+            fields.insert(format!("cif{}", bit), json!(word));
+        }
+        for field in decoder {
+            if let Some(value) = field.value() {
+                fields.insert(snake_case(field.field.name()), context_value(value));
+            }
+        }
+        object["context"] = Value::Object(fields);
+    }
+    if payload {
+        object["payload"] = json!(base64(packet.data_payload));
+    }
+    object
+}
+
+/// JSON Lines Writer
+///
+/// Writes one JSON object per packet, each on its own line, for piping into tools such as
+/// `jq` or loading into a search engine. Each object holds the header fields, Stream and
+/// Class Identifiers, timestamp, payload length and the enabled trailer indicators:
+///
+/// ```json
+/// {"class_id":null,"packet_count":0,"packet_size":6,"packet_type":1,
+///  "packet_type_name":"IF Data with Stream ID","payload_len":4,"stream_id":1,
+///  "timestamp":{"fractional":5,"integer":10,"tsf":2,"tsi":1},"trailer":null}
+/// ```
+///
+/// Context packets add a `context` object holding the indicator words and the fixed-size
+/// Context Indicator Field 0 fields decoded by [`VrtPacket::context_fields`], such as
+/// `"sample_rate":1000000.0`, and
+/// [`JsonLinesWriter::with_payload`] adds the payload as a base64 `payload` string. Keys are
+/// written in alphabetical order; the line above is wrapped for readability.
+#[derive(Debug)]
+pub struct JsonLinesWriter<W: Write> {
+    writer: W,
+    payload: bool,
+}
+
+impl<W: Write> JsonLinesWriter<W> {
+    /// Creates a new JsonLinesWriter writing to `writer`
+    pub fn new(writer: W) -> Self {
+        JsonLinesWriter {
+            writer,
+            payload: false,
+        }
+    }
+
+    /// Sets whether each object includes the payload, encoded as base64
+    pub fn with_payload(mut self, payload: bool) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    /// Writes the line describing `packet`
+    pub fn write_packet(&mut self, packet: &VrtPacket<'_>) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, &packet_json(packet, self.payload))?;
+        self.writer.write_all(b"\n")
+    }
+
+    /// Flushes and returns the underlying writer
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
@@ -6,7 +6,28 @@
 //! # A VRT parser/encoder library for Rust
 //!
 //! A VITA Radio Transport (VITA 49.0-2015) parser and encoder, implemented with the [nom](https://github.com/Geal/nom)
-//! parser combinator framework.
+//! parser combinator framework. [`VrtPacket::parse`] returns the crate's own [`Error`], so
+//! applications need not depend on nom themselves.
+//!
+//! ## `no_std` support
+//!
+//! Parsing and encoding into caller-provided buffers work without the standard library when
+//! the default `std` feature is disabled. The `alloc` feature adds [`VrtPacketOwned`] and
+//! [`VrtPacket::to_vec`] for targets with a global allocator, and the `heapless` feature adds
+//! fixed-capacity alternatives for targets without one; everything else requires `std`.
+//!
+//! [`parse_vrt_packet`], [`PacketStream`], [`Fragmenter`] and [`encode_vrt_packet`] never
+//! allocate, with or without these features: parsed packets borrow their payload from the
+//! input buffer and encoding writes into the buffer supplied by the caller.
+//!
+//! ## Unsafe code
+//!
+//! The crate is built with `#![forbid(unsafe_code)]` unless the `unsafe-fast` or `ffi` feature
+//! is enabled, so a build without them is free of unsafe code in this crate. `unsafe-fast`
+//! enables the fast paths which need unsafe code, currently the memory-mapped captures of
+//! the `mmap` feature and the io_uring receiver of the `io-uring` feature, and `ffi` the C
+//! API. The `zerocopy` overlays need no unsafe code here, as their layouts are checked by
+//! zerocopy's derives.
 //!
 
 #![deny(
@@ -16,7 +37,11 @@
     unused_qualifications,
     unreachable_pub
 )]
-#![forbid(unsafe_code)]
+#![cfg_attr(
+    not(any(feature = "ffi", feature = "unsafe-fast")),
+    forbid(unsafe_code)
+)]
+#![cfg_attr(any(feature = "ffi", feature = "unsafe-fast"), deny(unsafe_code))]
 #![warn(rust_2018_idioms, missing_debug_implementations)]
 // pragmas for doc
 #![deny(rustdoc::broken_intra_doc_links)]
@@ -27,8 +52,181 @@
 ))]
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+mod align;
+#[cfg(feature = "futures")]
+mod async_io;
+#[cfg(feature = "blue")]
+mod blue;
+#[cfg(feature = "heapless")]
+mod bounded;
+#[cfg(feature = "std")]
+mod burst;
+#[cfg(feature = "std")]
+mod capture;
+#[cfg(feature = "codec")]
+mod codec;
+mod command;
+mod context;
+#[cfg(feature = "std")]
+mod control;
+#[cfg(feature = "std")]
+mod csv;
+#[cfg(feature = "std")]
+mod demux;
+#[cfg(feature = "std")]
+mod diff;
+#[cfg(feature = "std")]
+mod difi;
+#[cfg(feature = "std")]
+mod dissect;
+mod encoder;
+mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+mod filter;
+mod fingerprint;
+mod fragment;
+#[cfg(feature = "fuzz-helpers")]
+pub mod fuzz_helpers;
+#[cfg(feature = "std")]
+mod generator;
+#[cfg(feature = "vita49")]
+mod interop;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "std")]
+mod merge;
+#[cfg(feature = "std")]
+mod metrics;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(any(feature = "embedded-nal", feature = "embedded-nal-async"))]
+mod nal;
+#[cfg(feature = "std")]
+pub mod net;
+#[cfg(feature = "zerocopy")]
+mod overlay;
+#[cfg(feature = "std")]
+mod pace;
 mod parser;
+#[cfg(feature = "pcap")]
+mod pcap;
+#[cfg(feature = "std")]
+mod pipeline;
+#[cfg(feature = "std")]
+mod pool;
+#[cfg(feature = "prometheus")]
+mod prometheus;
+#[cfg(feature = "zmq")]
+mod pubsub;
+#[cfg(feature = "std")]
+mod replay;
+mod sdr;
+#[cfg(feature = "std")]
+mod session;
+#[cfg(feature = "bytes")]
+mod shared;
+#[cfg(feature = "sigmf")]
+mod sigmf;
+mod stats;
+mod stream;
+#[cfg(feature = "test-vectors")]
+pub mod test_vectors;
+#[cfg(feature = "tui")]
+mod tui;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+mod uring;
+#[cfg(feature = "std")]
+mod validate;
 mod vrt;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "websocket")]
+mod websocket;
 
+#[cfg(feature = "std")]
+pub use align::*;
+#[cfg(feature = "futures")]
+pub use async_io::*;
+#[cfg(feature = "blue")]
+pub use blue::*;
+#[cfg(feature = "heapless")]
+pub use bounded::*;
+#[cfg(feature = "std")]
+pub use burst::*;
+#[cfg(feature = "std")]
+pub use capture::*;
+#[cfg(feature = "codec")]
+pub use codec::*;
+pub use command::*;
+pub use context::*;
+#[cfg(feature = "std")]
+pub use control::*;
+#[cfg(feature = "std")]
+pub use csv::*;
+#[cfg(feature = "std")]
+pub use demux::*;
+#[cfg(feature = "std")]
+pub use diff::*;
+#[cfg(feature = "std")]
+pub use difi::*;
+#[cfg(feature = "std")]
+pub use dissect::*;
+pub use encoder::*;
+pub use error::*;
+#[cfg(feature = "std")]
+pub use filter::*;
+pub use fingerprint::*;
+pub use fragment::*;
+#[cfg(feature = "std")]
+pub use generator::*;
+#[cfg(feature = "json")]
+pub use json::*;
+#[cfg(feature = "std")]
+pub use merge::*;
+#[cfg(feature = "std")]
+pub use metrics::*;
+#[cfg(feature = "mmap")]
+pub use mmap::*;
+#[cfg(any(feature = "embedded-nal", feature = "embedded-nal-async"))]
+pub use nal::*;
+#[cfg(feature = "zerocopy")]
+pub use overlay::*;
+#[cfg(feature = "std")]
+pub use pace::*;
 pub use parser::*;
+#[cfg(feature = "pcap")]
+pub use pcap::*;
+#[cfg(feature = "std")]
+pub use pipeline::*;
+#[cfg(feature = "std")]
+pub use pool::*;
+#[cfg(feature = "prometheus")]
+pub use prometheus::*;
+#[cfg(feature = "zmq")]
+pub use pubsub::*;
+#[cfg(feature = "std")]
+pub use replay::*;
+pub use sdr::*;
+#[cfg(feature = "std")]
+pub use session::*;
+#[cfg(feature = "bytes")]
+pub use shared::*;
+#[cfg(feature = "sigmf")]
+pub use sigmf::*;
+pub use stats::*;
+pub use stream::*;
+#[cfg(feature = "tui")]
+pub use tui::*;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub use uring::*;
+#[cfg(feature = "std")]
+pub use validate::*;
 pub use vrt::*;
+#[cfg(feature = "websocket")]
+pub use websocket::*;
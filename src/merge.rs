@@ -0,0 +1,72 @@
+//! Time-ordered merging of multiple VRT packet sources.
+
+use crate::vrt::{Timestamp, VrtPacket};
+use std::fmt;
+use std::iter::Peekable;
+
+/// Iterator merging several packet sources into a single sequence in timestamp order
+///
+/// Each source must already be in timestamp order. At every step the packet with the earliest
+/// timestamp among the heads of the sources is returned; packets without a timestamp sort
+/// before any timestamped packet, and ties are broken in favour of the earlier source.
+/// Timestamps are compared as described for [`Timestamp`], so sources whose timestamps are of
+/// different types are merged type by type rather than interleaved.
+pub struct MergeByTime<I: Iterator> {
+    sources: Vec<Peekable<I>>,
+    key: fn(&I::Item) -> Option<Timestamp>,
+}
+
+impl<'a, I> MergeByTime<I>
+where
+    I: Iterator<Item = VrtPacket<'a>>,
+{
+    /// Creates a new MergeByTime over `sources`
+    pub fn new<S>(sources: S) -> Self
+    where
+        S: IntoIterator<Item = I>,
+    {
+        Self::with_key(sources, |p| p.timestamp())
+    }
+}
+
+impl<I: Iterator> MergeByTime<I> {
+    /// Creates a new MergeByTime over sources of any item type, where `key` returns the
+    /// timestamp of an item
+    pub fn with_key<S>(sources: S, key: fn(&I::Item) -> Option<Timestamp>) -> Self
+    where
+        S: IntoIterator<Item = I>,
+    {
+        MergeByTime {
+            sources: sources.into_iter().map(Iterator::peekable).collect(),
+            key,
+        }
+    }
+}
+
+impl<I: Iterator> Iterator for MergeByTime<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.key;
+        let mut earliest: Option<(usize, Option<Timestamp>)> = None;
+        for (n, source) in self.sources.iter_mut().enumerate() {
+            let time = match source.peek() {
+                Some(item) => key(item),
+                None => continue,
+            };
+            match earliest {
+                Some((_, e)) if e <= time => {}
+                _ => earliest = Some((n, time)),
+            }
+        }
+        earliest.and_then(|(n, _)| self.sources[n].next())
+    }
+}
+
+impl<I: Iterator> fmt::Debug for MergeByTime<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MergeByTime")
+            .field("sources", &self.sources.len())
+            .finish()
+    }
+}
@@ -0,0 +1,132 @@
+//! Rate, jitter and timestamp skew statistics for VRT packet streams.
+
+use crate::vrt::{Tsf, Tsi, VrtPacket};
+use std::collections::HashMap;
+use std::time::Duration;
+
+const PICOSECONDS_PER_SECOND: f64 = 1e12;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// Snapshot of the statistics of a single packet stream
+pub struct MetricsSnapshot {
+    /// Stream Identifier, or None for packets without one
+    pub stream_id: Option<u32>,
+    /// Number of packets received
+    pub packets: u64,
+    /// Number of bytes received, as declared by the packets' `packet_size`
+    pub bytes: u64,
+    /// Average packet rate between the first and last packet, in packets per second
+    pub packets_per_sec: f64,
+    /// Average data rate between the first and last packet, in bytes per second
+    pub bytes_per_sec: f64,
+    /// Smoothed inter-packet arrival jitter, in seconds
+    pub jitter: f64,
+    /// Difference between the arrival time and the packet timestamp of the most recent packet,
+    /// in seconds. Only available for packets with UTC or GPS integer-seconds timestamps.
+    pub skew: Option<f64>,
+    /// Number of packets received for each packet type, indexed by packet type
+    pub packet_type_counts: [u64; 16],
+}
+
+#[derive(Clone, Debug, Default)]
+struct StreamMetrics {
+    first_arrival: Option<Duration>,
+    first_bytes: u64,
+    last_arrival: Option<Duration>,
+    last_time: Option<f64>,
+    last_interval: Option<f64>,
+    snapshot: MetricsSnapshot,
+}
+
+fn packet_time(packet: &VrtPacket<'_>) -> Option<f64> {
+    let tsi = packet.tsi?;
+    if packet.header.tsi != Tsi::TSI_UTC && packet.header.tsi != Tsi::TSI_GPS {
+        return None;
+    }
+    let fraction = match (packet.header.tsf, packet.tsf) {
+        (Tsf::TSF_REAL_TIME, Some(tsf)) => tsf as f64 / PICOSECONDS_PER_SECOND,
+        (Tsf::TSF_NONE, _) => 0.0,
+        _ => return None,
+    };
+    Some(tsi as f64 + fraction)
+}
+
+/// Stream Metrics Collector
+///
+/// Accumulates packet and data rates, inter-packet jitter, timestamp skew and per-packet-type
+/// counts for each Stream Identifier. Arrival times are supplied by the caller as the wall
+/// clock time since the UNIX epoch, so captured packets can be analysed with their recorded
+/// arrival times.
+///
+/// Jitter is estimated as in RFC 3550: the difference between the inter-arrival interval and
+/// the interval between the packet timestamps, smoothed with a gain of 1/16. Packets without
+/// usable timestamps are compared against the previous inter-arrival interval instead.
+#[derive(Clone, Debug, Default)]
+pub struct Metrics {
+    streams: HashMap<Option<u32>, StreamMetrics>,
+}
+
+impl Metrics {
+    /// Creates a new Metrics collector
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `packet`, which arrived at the wall clock time `arrival` since the UNIX epoch.
+    pub fn record(&mut self, packet: &VrtPacket<'_>, arrival: Duration) {
+        let stream = self.streams.entry(packet.stream_id).or_default();
+        let snapshot = &mut stream.snapshot;
+        snapshot.stream_id = packet.stream_id;
+        snapshot.packets += 1;
+        snapshot.bytes += packet.header.packet_size as u64 * 4;
+        snapshot.packet_type_counts[(packet.header.packet_type.0 & 0x0f) as usize] += 1;
+
+        let time = packet_time(packet);
+        if let Some(last_arrival) = stream.last_arrival {
+            let interval = arrival.as_secs_f64() - last_arrival.as_secs_f64();
+            let expected = match (time, stream.last_time) {
+                (Some(time), Some(last_time)) => Some(time - last_time),
+                _ => stream.last_interval,
+            };
+            if let Some(expected) = expected {
+                let d = (interval - expected).abs();
+                snapshot.jitter += (d - snapshot.jitter) / 16.0;
+            }
+            stream.last_interval = Some(interval);
+        }
+        snapshot.skew = time.map(|time| arrival.as_secs_f64() - time);
+
+        let first_arrival = match stream.first_arrival {
+            Some(first_arrival) => first_arrival,
+            None => {
+                stream.first_bytes = snapshot.bytes;
+                *stream.first_arrival.insert(arrival)
+            }
+        };
+        // the first packet starts the measurement interval
+        let elapsed = arrival.saturating_sub(first_arrival).as_secs_f64();
+        if elapsed > 0.0 {
+            snapshot.packets_per_sec = (snapshot.packets - 1) as f64 / elapsed;
+            snapshot.bytes_per_sec = (snapshot.bytes - stream.first_bytes) as f64 / elapsed;
+        }
+        stream.last_arrival = Some(arrival);
+        stream.last_time = time;
+    }
+
+    /// Returns a snapshot of the statistics of the stream `stream_id`
+    pub fn snapshot(&self, stream_id: Option<u32>) -> Option<MetricsSnapshot> {
+        self.streams.get(&stream_id).map(|s| s.snapshot.clone())
+    }
+
+    /// Returns snapshots of the statistics of every stream, ordered by Stream Identifier
+    pub fn snapshots(&self) -> Vec<MetricsSnapshot> {
+        let mut snapshots: Vec<_> = self.streams.values().map(|s| s.snapshot.clone()).collect();
+        snapshots.sort_by_key(|s| s.stream_id);
+        snapshots
+    }
+
+    /// Clears the statistics of every stream
+    pub fn reset(&mut self) {
+        self.streams.clear();
+    }
+}
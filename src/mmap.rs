@@ -0,0 +1,63 @@
+//! Memory-mapped capture files.
+
+use crate::capture::IndexEntry;
+use crate::error::Error;
+use crate::parser::parse_vrt_packet;
+use crate::stream::PacketStream;
+use crate::vrt::VrtPacket;
+use memmap2::Mmap;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+/// Memory-Mapped Capture
+///
+/// Maps a capture data file, such as one written by a [`Recorder`](crate::Recorder), into
+/// memory so its packets can be parsed in place without copying. Packets borrow directly from
+/// the mapping.
+///
+/// The file must not be modified or truncated while it is mapped: doing so can cause the
+/// process to crash or packets to change underneath the parser. Mapping a file needs unsafe
+/// code, so the `mmap` feature enables the `unsafe-fast` feature.
+#[derive(Debug)]
+pub struct MmapCapture {
+    map: Mmap,
+}
+
+impl MmapCapture {
+    /// Maps the capture data file `path` into memory
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        Self::from_file(&file)
+    }
+
+    /// Maps the capture data file `file` into memory
+    #[allow(unsafe_code)]
+    pub fn from_file(file: &File) -> io::Result<Self> {
+        // SAFETY: the mapping is read-only, and modifying the file while it is mapped is
+        // documented as unsupported on MmapCapture.
+        let map = unsafe { Mmap::map(file)? };
+        Ok(MmapCapture { map })
+    }
+
+    /// Returns the mapped capture data
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.map
+    }
+
+    /// Returns an iterator over the packets of the capture
+    pub fn packets(&self) -> PacketStream<'_> {
+        PacketStream::new(&self.map)
+    }
+
+    /// Parses the packet described by the index entry `entry`
+    pub fn packet(&self, entry: &IndexEntry) -> Result<VrtPacket<'_>, Error> {
+        let start = entry.offset as usize;
+        let bytes = self
+            .map
+            .get(start..start + entry.len as usize)
+            .ok_or(Error::Incomplete(None))?;
+        let (_, packet) = parse_vrt_packet(bytes)?;
+        Ok(packet)
+    }
+}
@@ -0,0 +1,154 @@
+//! Sending and receiving VRT packets over `embedded-nal` and `embedded-nal-async` UDP stacks.
+
+use crate::encoder::encode_vrt_packet;
+use crate::error::Error;
+use crate::stream::PacketStream;
+use crate::vrt::VrtPacket;
+use core::fmt;
+#[cfg(feature = "embedded-nal")]
+use embedded_nal::{SocketAddr, UdpClientStack};
+#[cfg(feature = "embedded-nal-async")]
+use embedded_nal_async::{ConnectedUdp, UdpStack};
+
+/// Error returned by a [`NalTransport`] or [`NalAsyncTransport`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NalError<E> {
+    /// The network stack returned an error
+    Stack(E),
+    /// The packet could not be encoded, or the buffer was too small to hold it
+    Packet(Error),
+}
+
+/// UDP Transport over an `embedded-nal` Stack
+#[cfg(feature = "embedded-nal")]
+///
+/// Sends and receives VRT packets over a UDP socket of any network stack implementing the
+/// `embedded-nal` [`UdpClientStack`] trait, such as smoltcp based or vendor stacks on
+/// microcontrollers. It works without `std` or an allocator: packets are encoded into, and
+/// received datagrams parsed from, buffers provided by the caller. Operations follow the `nb`
+/// conventions of the stack, returning [`nb::Error::WouldBlock`] when they would block.
+pub struct NalTransport<S: UdpClientStack> {
+    stack: S,
+    socket: S::UdpSocket,
+}
+
+#[cfg(feature = "embedded-nal")]
+impl<S: UdpClientStack> NalTransport<S> {
+    /// Creates a new NalTransport with a socket of `stack` connected to `remote`
+    pub fn connect(mut stack: S, remote: SocketAddr) -> Result<Self, S::Error> {
+        let mut socket = stack.socket()?;
+        stack.connect(&mut socket, remote)?;
+        Ok(NalTransport { stack, socket })
+    }
+
+    /// Creates a new NalTransport using the connected `socket` of `stack`
+    pub fn from_socket(stack: S, socket: S::UdpSocket) -> Self {
+        NalTransport { stack, socket }
+    }
+
+    /// Encodes `packet` into `buf` and sends it as a datagram
+    pub fn send(
+        &mut self,
+        packet: &VrtPacket<'_>,
+        buf: &mut [u8],
+    ) -> nb::Result<(), NalError<S::Error>> {
+        let len =
+            encode_vrt_packet(packet, buf).map_err(|e| nb::Error::Other(NalError::Packet(e)))?;
+        self.stack
+            .send(&mut self.socket, &buf[..len])
+            .map_err(|e| e.map(NalError::Stack))
+    }
+
+    /// Receives a datagram into `buf`, returning its source and an iterator over the packets it
+    /// holds
+    pub fn receive<'b>(
+        &mut self,
+        buf: &'b mut [u8],
+    ) -> nb::Result<(SocketAddr, PacketStream<'b>), NalError<S::Error>> {
+        let (len, source) = self
+            .stack
+            .receive(&mut self.socket, buf)
+            .map_err(|e| e.map(NalError::Stack))?;
+        Ok((source, PacketStream::new(&buf[..len])))
+    }
+
+    /// Closes the socket, returning the stack
+    pub fn close(mut self) -> Result<S, S::Error> {
+        self.stack.close(self.socket)?;
+        Ok(self.stack)
+    }
+}
+
+#[cfg(feature = "embedded-nal")]
+impl<S: UdpClientStack> fmt::Debug for NalTransport<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NalTransport").finish_non_exhaustive()
+    }
+}
+
+/// UDP Transport over an `embedded-nal-async` connected socket
+///
+/// The asynchronous counterpart of [`NalTransport`], sending and receiving VRT packets over any
+/// socket implementing the `embedded-nal-async` [`ConnectedUdp`] trait, such as the UDP sockets
+/// of embassy-net, without `std` or an allocator.
+#[cfg(feature = "embedded-nal-async")]
+pub struct NalAsyncTransport<C: ConnectedUdp> {
+    socket: C,
+}
+
+#[cfg(feature = "embedded-nal-async")]
+impl<C: ConnectedUdp> NalAsyncTransport<C> {
+    /// Creates a new NalAsyncTransport with a socket of `stack` connected to `remote`
+    pub async fn connect<S>(stack: &S, remote: core::net::SocketAddr) -> Result<Self, S::Error>
+    where
+        S: UdpStack<Connected = C>,
+    {
+        let (_, socket) = stack.connect(remote).await?;
+        Ok(NalAsyncTransport { socket })
+    }
+
+    /// Creates a new NalAsyncTransport using the connected `socket`
+    pub fn from_socket(socket: C) -> Self {
+        NalAsyncTransport { socket }
+    }
+
+    /// Encodes `packet` into `buf` and sends it as a datagram
+    pub async fn send(
+        &mut self,
+        packet: &VrtPacket<'_>,
+        buf: &mut [u8],
+    ) -> Result<(), NalError<C::Error>> {
+        let len = encode_vrt_packet(packet, buf).map_err(NalError::Packet)?;
+        self.socket.send(&buf[..len]).await.map_err(NalError::Stack)
+    }
+
+    /// Receives a datagram into `buf`, returning an iterator over the packets it holds
+    ///
+    /// A datagram longer than `buf` is discarded with [`Error::BufferTooSmall`].
+    pub async fn receive<'b>(
+        &mut self,
+        buf: &'b mut [u8],
+    ) -> Result<PacketStream<'b>, NalError<C::Error>> {
+        let len = self
+            .socket
+            .receive_into(buf)
+            .await
+            .map_err(NalError::Stack)?;
+        if len > buf.len() {
+            return Err(NalError::Packet(Error::BufferTooSmall));
+        }
+        Ok(PacketStream::new(&buf[..len]))
+    }
+
+    /// Returns the socket
+    pub fn into_inner(self) -> C {
+        self.socket
+    }
+}
+
+#[cfg(feature = "embedded-nal-async")]
+impl<C: ConnectedUdp> fmt::Debug for NalAsyncTransport<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NalAsyncTransport").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,1288 @@
+//! Sending and receiving VRT packets over UDP and TCP.
+
+use crate::encoder::encode_vrt_packet;
+use crate::error::Error;
+use crate::pace::Pacer;
+use crate::pool::{BufferPool, PooledBuffer, DEFAULT_BUFFER_ALIGNMENT};
+use crate::stream::PacketStream;
+use crate::vrt::{Timestamp, Tsi, VrtPacket, VrtPacketOwned};
+use std::collections::VecDeque;
+use std::io::{self, BufReader, Read, Write};
+#[cfg(feature = "multicast")]
+use std::net::{IpAddr, Ipv4Addr};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::thread;
+use std::time::Duration;
+
+/// Default maximum datagram length accepted by a [`UdpReceiver`], the largest UDP payload
+/// over IPv4
+pub const DEFAULT_MAX_DATAGRAM_LEN: usize = 65_507;
+
+/// Arrival time of a datagram, reported by the kernel once timestamping is enabled with
+/// `UdpReceiver::enable_timestamping`
+///
+/// Both times are durations since the epoch of the clock which took them: the Unix epoch for
+/// software timestamps, and for hardware timestamps whatever the network interface clock is
+/// disciplined to, usually UTC or, with PTP, TAI.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ArrivalTime {
+    /// Time at which the network interface received the datagram, from its hardware clock
+    pub hardware: Option<Duration>,
+    /// Time at which the kernel received the datagram, from the system realtime clock
+    pub software: Option<Duration>,
+}
+
+impl ArrivalTime {
+    /// Returns the hardware arrival time if known, or else the software arrival time
+    pub fn time(&self) -> Option<Duration> {
+        self.hardware.or(self.software)
+    }
+
+    /// Returns the time in seconds from the UTC packet timestamp `timestamp` to the arrival of
+    /// the packet, negative if the packet arrived before its timestamp
+    ///
+    /// Returns None if the arrival time is unknown, or if the timestamp is not a UTC timestamp
+    /// which can be converted as described for [`Timestamp::as_duration`].
+    pub fn latency(&self, timestamp: &Timestamp) -> Option<f64> {
+        if timestamp.tsi != Tsi::TSI_UTC {
+            return None;
+        }
+        let sent = timestamp.as_duration()?;
+        let arrival = self.time()?;
+        Some(match arrival.checked_sub(sent) {
+            Some(latency) => latency.as_secs_f64(),
+            None => -(sent - arrival).as_secs_f64(),
+        })
+    }
+}
+
+/// A datagram received by a [`UdpReceiver`]
+#[derive(Debug)]
+pub struct Datagram {
+    /// Address the datagram was received from
+    pub source: SocketAddr,
+    /// Whether the datagram was longer than the maximum datagram length and was truncated
+    pub truncated: bool,
+    /// Arrival time of the datagram, if timestamping is enabled
+    pub arrival: ArrivalTime,
+    buf: PooledBuffer,
+    len: usize,
+}
+
+impl Datagram {
+    /// Returns the received bytes
+    pub fn bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Returns an iterator over the VRT packets held in the datagram
+    pub fn packets(&self) -> PacketStream<'_> {
+        PacketStream::new(self.bytes())
+    }
+}
+
+/// Parses the packets of the datagram `bytes`, returning None if the datagram does not consist
+/// of complete, valid packets
+pub(crate) fn parse_datagram(bytes: &[u8]) -> Option<Vec<VrtPacketOwned>> {
+    let mut stream = PacketStream::new(bytes);
+    let packets: Result<Vec<_>, _> = stream.by_ref().collect();
+    match packets {
+        Ok(packets) if !packets.is_empty() && stream.remainder().is_empty() => {
+            Some(packets.iter().map(VrtPacketOwned::from).collect())
+        }
+        _ => None,
+    }
+}
+
+/// Converts the source address of a datagram returned by nix into a standard socket address
+///
+/// Returns an error of kind [`io::ErrorKind::InvalidData`] if the address is missing or is not
+/// an IPv4 or IPv6 address.
+#[cfg(all(any(feature = "batch", feature = "timestamping"), target_os = "linux"))]
+fn socket_addr(addr: Option<&nix::sys::socket::SockaddrStorage>) -> io::Result<SocketAddr> {
+    use std::net::{SocketAddrV4, SocketAddrV6};
+
+    addr.and_then(|addr| {
+        addr.as_sockaddr_in()
+            .map(|a| SocketAddr::from(SocketAddrV4::from(*a)))
+            .or_else(|| {
+                addr.as_sockaddr_in6()
+                    .map(|a| SocketAddr::from(SocketAddrV6::from(*a)))
+            })
+    })
+    .ok_or_else(|| invalid_data("datagram without a source address"))
+}
+
+/// Extracts the arrival time from the control messages of a received datagram
+#[cfg(all(any(feature = "batch", feature = "timestamping"), target_os = "linux"))]
+fn arrival_time(cmsgs: nix::sys::socket::CmsgIterator<'_>) -> ArrivalTime {
+    use nix::sys::socket::ControlMessageOwned;
+    use nix::sys::time::TimeSpec;
+
+    // the kernel leaves the timestamps it did not take zeroed
+    let time = |ts: TimeSpec| Some(Duration::from(ts)).filter(|d| !d.is_zero());
+    let mut arrival = ArrivalTime::default();
+    for cmsg in cmsgs {
+        if let ControlMessageOwned::ScmTimestampsns(ts) = cmsg {
+            arrival.hardware = time(ts.hw_raw);
+            arrival.software = time(ts.system);
+        }
+    }
+    arrival
+}
+
+/// Blocking UDP Receiver
+///
+/// Owns a UDP socket and a pool of receive buffers, and parses the VRT packets held in each
+/// received datagram. A datagram may hold several packets back-to-back, but a packet may not
+/// span datagrams.
+///
+/// Datagrams can be received one at a time with [`UdpReceiver::recv`] or several at a time with
+/// [`UdpReceiver::recv_batch`]. Each [`Datagram`] holds a lease on a buffer of the receiver's
+/// [`BufferPool`], so the packets parsed from it borrow the receive buffer directly, and the buffer
+/// returns to the pool when the datagram is dropped. Alternatively the receiver can be iterated to
+/// yield each packet together with its source address. On Linux, the arrival time of each datagram
+/// can also be reported by enabling timestamping with `UdpReceiver::enable_timestamping`. When
+/// iterating, truncated datagrams and datagrams which do not consist of complete, valid packets are
+/// dropped and counted by [`UdpReceiver::truncated`] and [`UdpReceiver::malformed`]. Socket errors,
+/// including read timeouts, are returned as errors.
+#[derive(Debug)]
+pub struct UdpReceiver {
+    socket: UdpSocket,
+    max_datagram_len: usize,
+    pool: BufferPool,
+    batch_size: usize,
+    pending: VecDeque<(SocketAddr, ArrivalTime, VrtPacketOwned)>,
+    truncated: u64,
+    malformed: u64,
+    #[cfg(all(feature = "timestamping", target_os = "linux"))]
+    timestamping: bool,
+}
+
+impl UdpReceiver {
+    /// Creates a new UdpReceiver with a socket bound to `addr`
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Ok(Self::from_socket(UdpSocket::bind(addr)?))
+    }
+
+    /// Creates a new UdpReceiver receiving from `socket`
+    pub fn from_socket(socket: UdpSocket) -> Self {
+        UdpReceiver {
+            socket,
+            max_datagram_len: DEFAULT_MAX_DATAGRAM_LEN,
+            // one spare byte detects datagrams longer than the maximum
+            pool: BufferPool::new(DEFAULT_MAX_DATAGRAM_LEN + 1, DEFAULT_BUFFER_ALIGNMENT),
+            batch_size: 1,
+            pending: VecDeque::new(),
+            truncated: 0,
+            malformed: 0,
+            #[cfg(all(feature = "timestamping", target_os = "linux"))]
+            timestamping: false,
+        }
+    }
+
+    /// Sets the maximum datagram length; longer datagrams are truncated
+    pub fn with_max_datagram_len(mut self, len: usize) -> Self {
+        self.max_datagram_len = len;
+        self.pool = BufferPool::new(len + 1, self.pool.alignment());
+        self
+    }
+
+    /// Receives into buffers leased from `pool`, which may be shared with other receivers
+    ///
+    /// The maximum datagram length becomes one less than the length of the pool's buffers, the
+    /// spare byte being used to detect longer datagrams.
+    pub fn with_pool(mut self, pool: BufferPool) -> Self {
+        self.max_datagram_len = pool.buffer_len().saturating_sub(1);
+        self.pool = pool;
+        self
+    }
+
+    /// Returns the pool of receive buffers
+    pub fn pool(&self) -> &BufferPool {
+        &self.pool
+    }
+
+    /// Sets the maximum number of datagrams received at a time when iterating, which defaults
+    /// to 1. See [`UdpReceiver::recv_batch`].
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Returns the underlying socket, for example to set a read timeout
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Returns the number of truncated datagrams dropped while iterating
+    pub fn truncated(&self) -> u64 {
+        self.truncated
+    }
+
+    /// Returns the number of malformed datagrams dropped while iterating
+    pub fn malformed(&self) -> u64 {
+        self.malformed
+    }
+
+    /// Enables receive timestamping (feature `timestamping`, Linux only)
+    ///
+    /// Each datagram received afterwards reports its arrival time in [`Datagram::arrival`],
+    /// taken by the kernel when the datagram was received and, if `hardware` is set, by the
+    /// network interface. Hardware timestamps also have to be enabled on the interface itself,
+    /// for example with `hwstamp_ctl` or the `SIOCSHWTSTAMP` ioctl, and are absent otherwise.
+    #[cfg(all(feature = "timestamping", target_os = "linux"))]
+    pub fn enable_timestamping(&mut self, hardware: bool) -> io::Result<()> {
+        use nix::sys::socket::{setsockopt, sockopt::Timestamping, TimestampingFlag};
+
+        let mut flags = TimestampingFlag::SOF_TIMESTAMPING_SOFTWARE
+            | TimestampingFlag::SOF_TIMESTAMPING_RX_SOFTWARE;
+        if hardware {
+            flags |= TimestampingFlag::SOF_TIMESTAMPING_RAW_HARDWARE
+                | TimestampingFlag::SOF_TIMESTAMPING_RX_HARDWARE;
+        }
+        setsockopt(&self.socket, Timestamping, &flags)?;
+        self.timestamping = true;
+        Ok(())
+    }
+
+    /// Receives the next datagram into a buffer from the pool
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if the kernel does not report
+    /// an IPv4 or IPv6 source address for the datagram.
+    pub fn recv(&mut self) -> io::Result<Datagram> {
+        let mut buf = self.pool.lease();
+        let (len, source, arrival) = self.recv_into(&mut buf)?;
+        Ok(Datagram {
+            source,
+            truncated: len > self.max_datagram_len,
+            arrival,
+            len: len.min(self.max_datagram_len),
+            buf,
+        })
+    }
+
+    fn recv_into(&mut self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr, ArrivalTime)> {
+        #[cfg(all(feature = "timestamping", target_os = "linux"))]
+        if self.timestamping {
+            use nix::sys::socket::{recvmsg, MsgFlags, SockaddrStorage};
+            use std::io::IoSliceMut;
+            use std::os::fd::AsRawFd;
+
+            let mut cmsg = self.cmsg_space();
+            let mut iov = [IoSliceMut::new(buf)];
+            let msg = recvmsg::<SockaddrStorage>(
+                self.socket.as_raw_fd(),
+                &mut iov,
+                cmsg.as_mut(),
+                MsgFlags::empty(),
+            )?;
+            let source = socket_addr(msg.address.as_ref())?;
+            let arrival = msg.cmsgs().map(arrival_time).unwrap_or_default();
+            return Ok((msg.bytes, source, arrival));
+        }
+        let (len, source) = self.socket.recv_from(buf)?;
+        Ok((len, source, ArrivalTime::default()))
+    }
+
+    /// Returns a control message buffer for the arrival time, if timestamping is enabled
+    #[cfg(all(any(feature = "batch", feature = "timestamping"), target_os = "linux"))]
+    fn cmsg_space(&self) -> Option<Vec<u8>> {
+        #[cfg(feature = "timestamping")]
+        if self.timestamping {
+            return Some(nix::cmsg_space!(nix::sys::socket::Timestamps));
+        }
+        None
+    }
+
+    /// Receives up to `max` datagrams, blocking until at least one is available
+    ///
+    /// With the `batch` feature on Linux the datagrams already queued on the socket are
+    /// received with a single `recvmmsg` call; elsewhere a single datagram is received. A
+    /// datagram without a source address fails the whole batch, as [`UdpReceiver::recv`] does.
+    pub fn recv_batch(&mut self, max: usize) -> io::Result<Vec<Datagram>> {
+        if max <= 1 {
+            return Ok(vec![self.recv()?]);
+        }
+        self.recv_many(max)
+    }
+
+    #[cfg(all(feature = "batch", target_os = "linux"))]
+    fn recv_many(&mut self, max: usize) -> io::Result<Vec<Datagram>> {
+        use nix::sys::socket::{recvmmsg, MsgFlags, MultiHeaders, SockaddrStorage};
+        use std::io::IoSliceMut;
+        use std::os::fd::AsRawFd;
+
+        let mut buffers: Vec<_> = (0..max).map(|_| self.pool.lease()).collect();
+        let received: Result<Vec<_>, _> = {
+            let mut slices: Vec<_> = buffers.iter_mut().map(|b| [IoSliceMut::new(b)]).collect();
+            let mut headers = MultiHeaders::<SockaddrStorage>::preallocate(max, self.cmsg_space());
+            recvmmsg(
+                self.socket.as_raw_fd(),
+                &mut headers,
+                slices.iter_mut(),
+                MsgFlags::MSG_WAITFORONE,
+                None,
+            )
+            .map(|results| {
+                results
+                    .map(|msg| {
+                        let address = socket_addr(msg.address.as_ref());
+                        let arrival = msg.cmsgs().map(arrival_time).unwrap_or_default();
+                        address.map(|address| (msg.bytes, address, arrival))
+                    })
+                    .collect()
+            })?
+        };
+        let received: Vec<_> = received?;
+
+        buffers.truncate(received.len());
+        Ok(received
+            .into_iter()
+            .zip(buffers)
+            .map(|((len, source, arrival), buf)| Datagram {
+                source,
+                truncated: len > self.max_datagram_len,
+                arrival,
+                len: len.min(self.max_datagram_len),
+                buf,
+            })
+            .collect())
+    }
+
+    #[cfg(not(all(feature = "batch", target_os = "linux")))]
+    fn recv_many(&mut self, _max: usize) -> io::Result<Vec<Datagram>> {
+        Ok(vec![self.recv()?])
+    }
+
+    /// Returns the buffer of `datagram` to the pool, as dropping the datagram does
+    pub fn recycle(&mut self, datagram: Datagram) {
+        drop(datagram);
+    }
+
+    /// Receives the next packet, blocking until a datagram holding valid packets arrives
+    pub fn recv_packet(&mut self) -> io::Result<(SocketAddr, VrtPacketOwned)> {
+        let (source, _, packet) = self.recv_packet_with_arrival()?;
+        Ok((source, packet))
+    }
+
+    /// Receives the next packet as [`UdpReceiver::recv_packet`] does, together with the arrival
+    /// time of its datagram
+    pub fn recv_packet_with_arrival(
+        &mut self,
+    ) -> io::Result<(SocketAddr, ArrivalTime, VrtPacketOwned)> {
+        loop {
+            if let Some(packet) = self.pending.pop_front() {
+                return Ok(packet);
+            }
+            for datagram in self.recv_batch(self.batch_size)? {
+                if datagram.truncated {
+                    self.truncated += 1;
+                } else if let Some(packets) = parse_datagram(datagram.bytes()) {
+                    let (source, arrival) = (datagram.source, datagram.arrival);
+                    self.pending
+                        .extend(packets.into_iter().map(|p| (source, arrival, p)));
+                } else {
+                    self.malformed += 1;
+                }
+                self.recycle(datagram);
+            }
+        }
+    }
+}
+
+impl Iterator for UdpReceiver {
+    type Item = io::Result<(SocketAddr, VrtPacketOwned)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.recv_packet())
+    }
+}
+
+/// Batched UDP Sender
+///
+/// Encodes batches of packets into a pool of send buffers and sends each packet as a datagram
+/// to a fixed destination. With the `batch` feature on Linux the whole batch is submitted with
+/// `sendmmsg`, avoiding a system call per packet; otherwise the datagrams are sent one by one.
+#[derive(Debug)]
+pub struct BatchSender {
+    socket: UdpSocket,
+    destination: SocketAddr,
+    pool: Vec<Vec<u8>>,
+}
+
+impl BatchSender {
+    /// Creates a new BatchSender sending from `socket` to `destination`
+    pub fn new(socket: UdpSocket, destination: SocketAddr) -> Self {
+        BatchSender {
+            socket,
+            destination,
+            pool: Vec::new(),
+        }
+    }
+
+    /// Returns the underlying socket
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Encodes and sends `packets`, one datagram per packet, returning the number sent
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidInput`], without sending any packet, if
+    /// one of the packets cannot be encoded.
+    pub fn send_batch(&mut self, packets: &[VrtPacket<'_>]) -> io::Result<usize> {
+        if self.pool.len() < packets.len() {
+            self.pool.resize_with(packets.len(), Vec::new);
+        }
+        for (packet, buf) in packets.iter().zip(self.pool.iter_mut()) {
+            buf.resize(packet.encoded_len(), 0);
+            encode_vrt_packet(packet, buf)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid VRT packet"))?;
+        }
+        self.send_buffers(packets.len())
+    }
+
+    #[cfg(all(feature = "batch", target_os = "linux"))]
+    fn send_buffers(&mut self, count: usize) -> io::Result<usize> {
+        use nix::sys::socket::{sendmmsg, ControlMessage, MsgFlags, MultiHeaders, SockaddrStorage};
+        use std::io::IoSlice;
+        use std::os::fd::AsRawFd;
+
+        let mut sent = 0;
+        while sent < count {
+            let buffers = &self.pool[sent..count];
+            let slices: Vec<_> = buffers.iter().map(|b| [IoSlice::new(b)]).collect();
+            let addrs = vec![Some(SockaddrStorage::from(self.destination)); buffers.len()];
+            let mut headers = MultiHeaders::<SockaddrStorage>::preallocate(buffers.len(), None);
+            let cmsgs: [ControlMessage<'_>; 0] = [];
+            let results = sendmmsg(
+                self.socket.as_raw_fd(),
+                &mut headers,
+                &slices,
+                &addrs,
+                cmsgs,
+                MsgFlags::empty(),
+            )
+            .map_err(io::Error::from)?;
+            match results.count() {
+                0 => break,
+                n => sent += n,
+            }
+        }
+        Ok(sent)
+    }
+
+    #[cfg(not(all(feature = "batch", target_os = "linux")))]
+    fn send_buffers(&mut self, count: usize) -> io::Result<usize> {
+        for buf in &self.pool[..count] {
+            self.socket.send_to(buf, self.destination)?;
+        }
+        Ok(count)
+    }
+}
+
+/// Paced UDP Sender
+///
+/// Sends each packet as a datagram to a fixed destination once it is due according to a
+/// [`Pacer`], for feeding devices such as real-time modulators which expect packets at the
+/// stream's sample rate rather than in bursts.
+#[derive(Debug)]
+pub struct PacedSender {
+    socket: UdpSocket,
+    destination: SocketAddr,
+    pacer: Pacer,
+    buf: Vec<u8>,
+}
+
+impl PacedSender {
+    /// Creates a new PacedSender sending from `socket` to `destination` at the rate of `pacer`
+    pub fn new(socket: UdpSocket, destination: SocketAddr, pacer: Pacer) -> Self {
+        PacedSender {
+            socket,
+            destination,
+            pacer,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Returns the underlying socket
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Returns the pacer, for example to reset the schedule after a pause
+    pub fn pacer_mut(&mut self) -> &mut Pacer {
+        &mut self.pacer
+    }
+
+    /// Encodes `packet`, waits until it is due and sends it
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidInput`], without waiting, if the packet
+    /// cannot be encoded.
+    pub fn send(&mut self, packet: &VrtPacket<'_>) -> io::Result<()> {
+        self.buf.resize(packet.encoded_len(), 0);
+        encode_vrt_packet(packet, &mut self.buf)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid VRT packet"))?;
+        self.pacer.wait();
+        self.socket.send_to(&self.buf, self.destination)?;
+        Ok(())
+    }
+}
+
+/// Alignment word starting each VITA 49.1 VRL frame, "VRLP" in ASCII
+pub const VRL_FRAME_ALIGNMENT_WORD: u32 = 0x5652_4C50;
+
+/// Word ending a VITA 49.1 VRL frame which carries no CRC, "VEND" in ASCII
+pub const VRL_FRAME_END_WORD: u32 = 0x5645_4E44;
+
+/// Parses the VITA 49.1 VRL frame at the start of `bytes`, returning its length in bytes and
+/// an iterator over the packets it carries
+///
+/// The final word of the frame, either [`VRL_FRAME_END_WORD`] or a CRC, is not checked.
+/// Returns [`Error::Malformed`] if the frame does not start with
+/// [`VRL_FRAME_ALIGNMENT_WORD`] or is shorter than three words, and [`Error::Incomplete`] if
+/// `bytes` ends before the frame does.
+pub fn parse_vrl_frame(bytes: &[u8]) -> Result<(usize, PacketStream<'_>), Error> {
+    if bytes.len() < 8 {
+        return Err(Error::Incomplete(Some(8 - bytes.len())));
+    }
+    let word = |i: usize| u32::from_be_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+    let len = (word(4) & 0xf_ffff) as usize * 4;
+    if word(0) != VRL_FRAME_ALIGNMENT_WORD || len < 12 {
+        return Err(Error::Malformed);
+    }
+    if bytes.len() < len {
+        return Err(Error::Incomplete(Some(len - bytes.len())));
+    }
+    Ok((len, PacketStream::new(&bytes[8..len - 4])))
+}
+
+/// Reads the next packet of a stream of back-to-back VRT packets, such as a raw capture file,
+/// or returns None at a clean end of the stream
+///
+/// The packet is read into `buf`, which may be reused between calls to avoid allocating. A
+/// packet size of zero or a packet which cannot be parsed is returned as an error of kind
+/// [`io::ErrorKind::InvalidData`], and a stream ending within a packet as one of kind
+/// [`io::ErrorKind::UnexpectedEof`].
+pub fn read_raw_packet<R: Read + ?Sized>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+) -> io::Result<Option<VrtPacketOwned>> {
+    buf.resize(4, 0);
+    if !read_first(reader, buf)? {
+        return Ok(None);
+    }
+    let words = u16::from_be_bytes([buf[2], buf[3]]) as usize;
+    if words == 0 {
+        return Err(invalid_data("VRT packet size of zero"));
+    }
+    buf.resize(words * 4, 0);
+    reader.read_exact(&mut buf[4..])?;
+    match VrtPacket::parse(buf) {
+        Ok((_, packet)) => Ok(Some(VrtPacketOwned::from(&packet))),
+        Err(_) => Err(invalid_data("invalid VRT packet")),
+    }
+}
+
+/// Fills `buf` with the first bytes of a packet or frame, returning false on a clean end of
+/// stream
+fn read_first<R: Read + ?Sized>(reader: &mut R, buf: &mut [u8]) -> io::Result<bool> {
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) if read == 0 => return Ok(false),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => read += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(true)
+}
+
+/// Framing of VRT packets on a TCP stream
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TcpFraming {
+    /// Packets are sent back-to-back, delimited by their packet size
+    Raw,
+    /// Each packet is wrapped in a VITA 49.1 VRL frame, which allows a receiver to find the
+    /// next packet boundary after corrupted data
+    Vrl,
+}
+
+/// VRT-over-TCP Connection
+///
+/// Sends and receives VRT packets over a TCP stream, framed as given by [`TcpFraming`]. With VRL
+/// framing the connection resynchronizes on the next frame alignment word when a frame does not
+/// start where expected, is too short, or carries packets which cannot be parsed, counting such
+/// events in [`TcpConnection::resyncs`]; the CRC of frames carrying one is not checked. Raw streams
+/// cannot be resynchronized, and a packet size of zero is returned as an error of kind
+/// [`io::ErrorKind::InvalidData`].
+#[derive(Debug)]
+pub struct TcpConnection {
+    stream: BufReader<TcpStream>,
+    framing: TcpFraming,
+    frame_count: u16,
+    buf: Vec<u8>,
+    pending: VecDeque<VrtPacketOwned>,
+    unread: Vec<u8>,
+    resyncs: u64,
+}
+
+impl TcpConnection {
+    /// Creates a new TcpConnection over `stream`
+    pub fn new(stream: TcpStream, framing: TcpFraming) -> Self {
+        TcpConnection {
+            stream: BufReader::new(stream),
+            framing,
+            frame_count: 0,
+            buf: Vec::new(),
+            pending: VecDeque::new(),
+            unread: Vec::new(),
+            resyncs: 0,
+        }
+    }
+
+    /// Returns the underlying stream
+    pub fn stream(&self) -> &TcpStream {
+        self.stream.get_ref()
+    }
+
+    /// Returns the number of times the connection lost and regained VRL frame alignment
+    pub fn resyncs(&self) -> u64 {
+        self.resyncs
+    }
+
+    /// Encodes and sends `packet`
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidInput`], without sending anything, if
+    /// the packet cannot be encoded.
+    pub fn send(&mut self, packet: &VrtPacket<'_>) -> io::Result<()> {
+        let len = packet.encoded_len();
+        let offset = match self.framing {
+            TcpFraming::Raw => 0,
+            TcpFraming::Vrl => 8,
+        };
+        self.buf.resize(offset + len, 0);
+        encode_vrt_packet(packet, &mut self.buf[offset..])
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid VRT packet"))?;
+        if self.framing == TcpFraming::Vrl {
+            let words = (len / 4 + 3) as u32;
+            let word = (self.frame_count as u32) << 20 | words;
+            self.buf[..4].copy_from_slice(&VRL_FRAME_ALIGNMENT_WORD.to_be_bytes());
+            self.buf[4..8].copy_from_slice(&word.to_be_bytes());
+            self.buf
+                .extend_from_slice(&VRL_FRAME_END_WORD.to_be_bytes());
+            self.frame_count = (self.frame_count + 1) & 0xfff;
+        }
+        self.stream.get_mut().write_all(&self.buf)
+    }
+
+    /// Receives the next packet, or None if the peer closed the connection
+    pub fn recv_packet(&mut self) -> io::Result<Option<VrtPacketOwned>> {
+        loop {
+            if let Some(packet) = self.pending.pop_front() {
+                return Ok(Some(packet));
+            }
+            if self.framing == TcpFraming::Raw {
+                return read_raw_packet(&mut self.stream, &mut self.buf);
+            }
+            let mut word = [0u8; 4];
+            if !self.read_first(&mut word)? {
+                return Ok(None);
+            }
+            if u32::from_be_bytes(word) != VRL_FRAME_ALIGNMENT_WORD {
+                self.unread.splice(0..0, word);
+                self.resync()?;
+                continue;
+            }
+            self.read_exact(&mut word)?;
+            let words = (u32::from_be_bytes(word) & 0xf_ffff) as usize;
+            if words < 3 {
+                self.unread.splice(0..0, word);
+                self.resync()?;
+                continue;
+            }
+            let mut buf = std::mem::take(&mut self.buf);
+            buf.resize((words - 2) * 4, 0);
+            let read = self.read_exact(&mut buf);
+            self.buf = buf;
+            read?;
+            // the final word is either the end word or a CRC, which is not checked
+            let body = &self.buf[..self.buf.len() - 4];
+            if body.is_empty() {
+                continue;
+            }
+            match parse_datagram(body) {
+                Some(packets) => self.pending.extend(packets),
+                None => {
+                    // the frame length may be corrupt too, so the next frame may
+                    // start within this one
+                    self.unread.splice(0..0, self.buf.iter().copied());
+                    self.resync()?;
+                }
+            }
+        }
+    }
+
+    /// Skips to the next VRL frame alignment word, leaving it to be read next
+    fn resync(&mut self) -> io::Result<()> {
+        self.resyncs += 1;
+        let mut word = [0u8; 4];
+        self.read_exact(&mut word)?;
+        while u32::from_be_bytes(word) != VRL_FRAME_ALIGNMENT_WORD {
+            word.copy_within(1.., 0);
+            self.read_exact(&mut word[3..])?;
+        }
+        self.unread.splice(0..0, word);
+        Ok(())
+    }
+
+    /// Fills `buf`, with the bytes left unread by a resynchronization first
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        let n = buf.len().min(self.unread.len());
+        buf[..n].copy_from_slice(&self.unread[..n]);
+        self.unread.drain(..n);
+        self.stream.read_exact(&mut buf[n..])
+    }
+
+    /// Reads the first word of a frame, returning false on a clean end of stream
+    fn read_first(&mut self, word: &mut [u8; 4]) -> io::Result<bool> {
+        if !self.unread.is_empty() {
+            self.read_exact(word)?;
+            return Ok(true);
+        }
+        read_first(&mut self.stream, word)
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Returns whether `e` means the TCP connection is lost, rather than an error such as a
+/// timeout or invalid data which leaves it usable
+fn connection_lost(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::UnexpectedEof | io::ErrorKind::ConnectionReset | io::ErrorKind::BrokenPipe
+    )
+}
+
+/// VRT-over-TCP Client with Reconnection
+///
+/// Connects to a TCP server, such as a recorder, and sends or receives packets over a
+/// [`TcpConnection`]. When the connection is closed or lost, that is when it ends or fails with an
+/// error of kind `UnexpectedEof`, `ConnectionReset` or `BrokenPipe`, the client drops the
+/// connection and connects again, waiting the reconnect delay between attempts and giving up after
+/// the maximum number of attempts. Other errors, such as timeouts and
+/// [`io::ErrorKind::InvalidData`] for a raw stream which cannot be parsed, are returned. A new
+/// connection always starts on a packet boundary, so the received stream is resynchronized; a
+/// packet being sent when the connection is lost is sent again on the new connection, and packets
+/// in flight may be lost.
+#[derive(Debug)]
+pub struct TcpClient {
+    addr: SocketAddr,
+    framing: TcpFraming,
+    reconnect_delay: Duration,
+    max_attempts: u32,
+    connection: Option<TcpConnection>,
+    reconnects: u64,
+}
+
+impl TcpClient {
+    /// Creates a new TcpClient connected to `addr`
+    pub fn connect<A: ToSocketAddrs>(addr: A, framing: TcpFraming) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        Ok(TcpClient {
+            addr: stream.peer_addr()?,
+            framing,
+            reconnect_delay: Duration::from_secs(1),
+            max_attempts: 10,
+            connection: Some(TcpConnection::new(stream, framing)),
+            reconnects: 0,
+        })
+    }
+
+    /// Sets the delay between reconnection attempts, which defaults to one second
+    pub fn with_reconnect_delay(mut self, delay: Duration) -> Self {
+        self.reconnect_delay = delay;
+        self
+    }
+
+    /// Sets the number of reconnection attempts made before giving up, which defaults to 10
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Returns the current connection, or None if it was lost and could not be re-established
+    pub fn connection(&self) -> Option<&TcpConnection> {
+        self.connection.as_ref()
+    }
+
+    /// Returns the number of times the client reconnected
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects
+    }
+
+    /// Encodes and sends `packet`, reconnecting if the connection is lost
+    pub fn send(&mut self, packet: &VrtPacket<'_>) -> io::Result<()> {
+        loop {
+            match self.connected()?.send(packet) {
+                Err(e) if connection_lost(&e) => self.connection = None,
+                result => return result,
+            }
+        }
+    }
+
+    /// Receives the next packet, reconnecting if the connection is lost or closed
+    pub fn recv_packet(&mut self) -> io::Result<VrtPacketOwned> {
+        loop {
+            match self.connected()?.recv_packet() {
+                Ok(Some(packet)) => return Ok(packet),
+                Ok(None) => self.connection = None,
+                Err(e) if connection_lost(&e) => self.connection = None,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn connected(&mut self) -> io::Result<&mut TcpConnection> {
+        let connection = match self.connection.take() {
+            Some(connection) => connection,
+            None => self.reconnect()?,
+        };
+        Ok(self.connection.insert(connection))
+    }
+
+    fn reconnect(&mut self) -> io::Result<TcpConnection> {
+        let mut attempt = 0;
+        let stream = loop {
+            thread::sleep(self.reconnect_delay);
+            match TcpStream::connect(self.addr) {
+                Ok(stream) => break stream,
+                Err(e) => {
+                    attempt += 1;
+                    if attempt >= self.max_attempts {
+                        return Err(e);
+                    }
+                }
+            }
+        };
+        self.reconnects += 1;
+        Ok(TcpConnection::new(stream, self.framing))
+    }
+}
+
+/// VRT-over-TCP Server
+///
+/// Accepts connections from TCP clients, such as legacy recorders or sources which only speak
+/// TCP. Connections can be accepted individually with [`TcpServer::accept`], or the server can
+/// receive the packets of one client after another with [`TcpServer::recv_packet`], accepting
+/// the next connection whenever the current one is closed or lost.
+#[derive(Debug)]
+pub struct TcpServer {
+    listener: TcpListener,
+    framing: TcpFraming,
+    connection: Option<(SocketAddr, TcpConnection)>,
+}
+
+impl TcpServer {
+    /// Creates a new TcpServer listening on `addr`
+    pub fn bind<A: ToSocketAddrs>(addr: A, framing: TcpFraming) -> io::Result<Self> {
+        Ok(TcpServer {
+            listener: TcpListener::bind(addr)?,
+            framing,
+            connection: None,
+        })
+    }
+
+    /// Returns the underlying listener
+    pub fn listener(&self) -> &TcpListener {
+        &self.listener
+    }
+
+    /// Accepts the next connection, returning it with the address of the client
+    pub fn accept(&self) -> io::Result<(SocketAddr, TcpConnection)> {
+        let (stream, addr) = self.listener.accept()?;
+        Ok((addr, TcpConnection::new(stream, self.framing)))
+    }
+
+    /// Receives the next packet from the current client, together with the client's address,
+    /// accepting a new connection if there is no current client or it has gone away
+    ///
+    /// A client has gone away when its connection is closed or lost, as described for
+    /// [`TcpClient`]; other errors are returned, keeping the client.
+    pub fn recv_packet(&mut self) -> io::Result<(SocketAddr, VrtPacketOwned)> {
+        loop {
+            let (addr, connection) = match &mut self.connection {
+                Some(connection) => connection,
+                None => self.connection.insert(self.accept()?),
+            };
+            match connection.recv_packet() {
+                Ok(Some(packet)) => return Ok((*addr, packet)),
+                Ok(None) => self.connection = None,
+                Err(e) if connection_lost(&e) => self.connection = None,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Multicast Socket Options
+///
+/// Creates UDP sockets for sending or receiving multicast VRT streams and manages their group
+/// memberships, covering both any-source (ASM) and, over IPv4 on the platforms supporting it,
+/// source-specific (SSM) groups.
+/// Sockets created with [`MulticastOptions::bind`] allow the address to be reused, so several
+/// receivers on one host can join the same group.
+///
+/// ```no_run
+/// use std::net::{Ipv4Addr, SocketAddr};
+/// use vrt::net::{MulticastOptions, UdpReceiver};
+///
+/// let options = MulticastOptions::new()
+///     .with_interface_v4(Ipv4Addr::new(192, 168, 1, 10))
+///     .with_recv_buffer_size(8 << 20);
+/// let socket = options.bind(SocketAddr::from(([0, 0, 0, 0], 4991)))?;
+/// options.join(&socket, [239, 1, 2, 3].into())?;
+/// let receiver = UdpReceiver::from_socket(socket);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg(feature = "multicast")]
+#[derive(Clone, Debug)]
+pub struct MulticastOptions {
+    interface_v4: Ipv4Addr,
+    interface_v6: u32,
+    ttl: Option<u32>,
+    loopback: Option<bool>,
+    recv_buffer_size: Option<usize>,
+    send_buffer_size: Option<usize>,
+}
+
+#[cfg(feature = "multicast")]
+impl MulticastOptions {
+    /// Creates new MulticastOptions using the interface chosen by the system and the system
+    /// defaults for the TTL, loopback and buffer sizes
+    pub fn new() -> Self {
+        MulticastOptions {
+            interface_v4: Ipv4Addr::UNSPECIFIED,
+            interface_v6: 0,
+            ttl: None,
+            loopback: None,
+            recv_buffer_size: None,
+            send_buffer_size: None,
+        }
+    }
+
+    /// Sets the address of the local interface used for IPv4 groups
+    pub fn with_interface_v4(mut self, interface: Ipv4Addr) -> Self {
+        self.interface_v4 = interface;
+        self
+    }
+
+    /// Sets the index of the local interface used for IPv6 groups
+    pub fn with_interface_v6(mut self, interface: u32) -> Self {
+        self.interface_v6 = interface;
+        self
+    }
+
+    /// Sets the TTL, or IPv6 hop limit, of packets sent to a group
+    pub fn with_ttl(mut self, ttl: u32) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Sets whether packets sent to a group are looped back to the sending host
+    pub fn with_loopback(mut self, loopback: bool) -> Self {
+        self.loopback = Some(loopback);
+        self
+    }
+
+    /// Sets the size of the socket receive buffer in bytes. High rate streams usually need a
+    /// buffer of several megabytes to ride out scheduling delays without dropping datagrams.
+    pub fn with_recv_buffer_size(mut self, size: usize) -> Self {
+        self.recv_buffer_size = Some(size);
+        self
+    }
+
+    /// Sets the size of the socket send buffer in bytes
+    pub fn with_send_buffer_size(mut self, size: usize) -> Self {
+        self.send_buffer_size = Some(size);
+        self
+    }
+
+    /// Creates a UDP socket bound to `addr` with the options applied
+    pub fn bind(&self, addr: SocketAddr) -> io::Result<UdpSocket> {
+        use socket2::{Domain, Protocol, Socket, Type};
+
+        let socket = Socket::new(Domain::for_address(addr), Type::DGRAM, Some(Protocol::UDP))?;
+        socket.set_reuse_address(true)?;
+        if let Some(size) = self.recv_buffer_size {
+            socket.set_recv_buffer_size(size)?;
+        }
+        if let Some(size) = self.send_buffer_size {
+            socket.set_send_buffer_size(size)?;
+        }
+        if addr.is_ipv4() {
+            socket.set_multicast_if_v4(&self.interface_v4)?;
+            if let Some(ttl) = self.ttl {
+                socket.set_multicast_ttl_v4(ttl)?;
+            }
+            if let Some(loopback) = self.loopback {
+                socket.set_multicast_loop_v4(loopback)?;
+            }
+        } else {
+            socket.set_multicast_if_v6(self.interface_v6)?;
+            if let Some(hops) = self.ttl {
+                socket.set_multicast_hops_v6(hops)?;
+            }
+            if let Some(loopback) = self.loopback {
+                socket.set_multicast_loop_v6(loopback)?;
+            }
+        }
+        socket.bind(&addr.into())?;
+        Ok(socket.into())
+    }
+
+    /// Joins the any-source multicast group `group` on `socket`
+    pub fn join(&self, socket: &UdpSocket, group: IpAddr) -> io::Result<()> {
+        match group {
+            IpAddr::V4(group) => socket.join_multicast_v4(&group, &self.interface_v4),
+            IpAddr::V6(group) => socket.join_multicast_v6(&group, self.interface_v6),
+        }
+    }
+
+    /// Leaves the any-source multicast group `group` on `socket`
+    pub fn leave(&self, socket: &UdpSocket, group: IpAddr) -> io::Result<()> {
+        match group {
+            IpAddr::V4(group) => socket.leave_multicast_v4(&group, &self.interface_v4),
+            IpAddr::V6(group) => socket.leave_multicast_v6(&group, self.interface_v6),
+        }
+    }
+
+    /// Joins the source-specific multicast group `group` for the sender `source` on `socket`
+    #[cfg(not(any(
+        target_os = "dragonfly",
+        target_os = "haiku",
+        target_os = "hurd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "redox",
+        target_os = "fuchsia",
+        target_os = "nto",
+        target_os = "espidf",
+        target_os = "vita",
+    )))]
+    pub fn join_source(
+        &self,
+        socket: &UdpSocket,
+        group: Ipv4Addr,
+        source: Ipv4Addr,
+    ) -> io::Result<()> {
+        socket2::SockRef::from(socket).join_ssm_v4(&source, &group, &self.interface_v4)
+    }
+
+    /// Leaves the source-specific multicast group `group` for the sender `source` on `socket`
+    #[cfg(not(any(
+        target_os = "dragonfly",
+        target_os = "haiku",
+        target_os = "hurd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "redox",
+        target_os = "fuchsia",
+        target_os = "nto",
+        target_os = "espidf",
+        target_os = "vita",
+    )))]
+    pub fn leave_source(
+        &self,
+        socket: &UdpSocket,
+        group: Ipv4Addr,
+        source: Ipv4Addr,
+    ) -> io::Result<()> {
+        socket2::SockRef::from(socket).leave_ssm_v4(&source, &group, &self.interface_v4)
+    }
+}
+
+#[cfg(feature = "multicast")]
+impl Default for MulticastOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Asynchronous UDP Receiver
+///
+/// A [`Stream`](futures_core::Stream) of the VRT packets received on a Tokio UDP socket,
+/// together with their source addresses. Datagrams are handled as described for
+/// [`UdpReceiver`]: truncated and malformed datagrams are dropped and counted, and socket errors
+/// are yielded as errors. The packets can be routed through a [`Demux`](crate::Demux) or
+/// [`VrtSession`](crate::VrtSession) with [`VrtPacketOwned::as_packet`].
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct AsyncUdpReceiver {
+    socket: tokio::net::UdpSocket,
+    max_datagram_len: usize,
+    buf: Vec<u8>,
+    pending: VecDeque<(SocketAddr, VrtPacketOwned)>,
+    truncated: u64,
+    malformed: u64,
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncUdpReceiver {
+    /// Creates a new AsyncUdpReceiver with a socket bound to `addr`
+    pub async fn bind<A: tokio::net::ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Ok(Self::from_socket(tokio::net::UdpSocket::bind(addr).await?))
+    }
+
+    /// Creates a new AsyncUdpReceiver receiving from `socket`
+    pub fn from_socket(socket: tokio::net::UdpSocket) -> Self {
+        AsyncUdpReceiver {
+            socket,
+            max_datagram_len: DEFAULT_MAX_DATAGRAM_LEN,
+            buf: Vec::new(),
+            pending: VecDeque::new(),
+            truncated: 0,
+            malformed: 0,
+        }
+    }
+
+    /// Sets the maximum datagram length; longer datagrams are truncated and dropped
+    pub fn with_max_datagram_len(mut self, len: usize) -> Self {
+        self.max_datagram_len = len;
+        self
+    }
+
+    /// Returns the underlying socket
+    pub fn socket(&self) -> &tokio::net::UdpSocket {
+        &self.socket
+    }
+
+    /// Returns the number of truncated datagrams dropped
+    pub fn truncated(&self) -> u64 {
+        self.truncated
+    }
+
+    /// Returns the number of malformed datagrams dropped
+    pub fn malformed(&self) -> u64 {
+        self.malformed
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl futures_core::Stream for AsyncUdpReceiver {
+    type Item = io::Result<(SocketAddr, VrtPacketOwned)>;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        use std::task::Poll;
+
+        let this = self.get_mut();
+        loop {
+            if let Some(packet) = this.pending.pop_front() {
+                return Poll::Ready(Some(Ok(packet)));
+            }
+            // one spare byte detects datagrams longer than the maximum
+            this.buf.resize(this.max_datagram_len + 1, 0);
+            let mut buf = tokio::io::ReadBuf::new(&mut this.buf);
+            let source = match this.socket.poll_recv_from(cx, &mut buf) {
+                Poll::Ready(Ok(source)) => source,
+                Poll::Ready(Err(e)) => return Poll::Ready(Some(Err(e))),
+                Poll::Pending => return Poll::Pending,
+            };
+            let len = buf.filled().len();
+            if len > this.max_datagram_len {
+                this.truncated += 1;
+            } else if let Some(packets) = parse_datagram(&this.buf[..len]) {
+                this.pending
+                    .extend(packets.into_iter().map(|p| (source, p)));
+            } else {
+                this.malformed += 1;
+            }
+        }
+    }
+}
+
+/// Asynchronous UDP Sender
+///
+/// A [`Sink`](futures_sink::Sink) encoding each VRT packet into a datagram sent from a Tokio UDP
+/// socket to a fixed destination. Packets which cannot be encoded are rejected with an error of
+/// kind [`io::ErrorKind::InvalidInput`].
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct AsyncUdpSender {
+    socket: tokio::net::UdpSocket,
+    destination: SocketAddr,
+    buf: Vec<u8>,
+    buffered: bool,
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncUdpSender {
+    /// Creates a new AsyncUdpSender sending from `socket` to `destination`
+    pub fn new(socket: tokio::net::UdpSocket, destination: SocketAddr) -> Self {
+        AsyncUdpSender {
+            socket,
+            destination,
+            buf: Vec::new(),
+            buffered: false,
+        }
+    }
+
+    /// Returns the underlying socket
+    pub fn socket(&self) -> &tokio::net::UdpSocket {
+        &self.socket
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl futures_sink::Sink<VrtPacketOwned> for AsyncUdpSender {
+    type Error = io::Error;
+
+    fn poll_ready(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+
+    fn start_send(self: std::pin::Pin<&mut Self>, packet: VrtPacketOwned) -> io::Result<()> {
+        let this = self.get_mut();
+        let packet = packet.as_packet();
+        this.buf.resize(packet.encoded_len(), 0);
+        encode_vrt_packet(&packet, &mut this.buf)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid VRT packet"))?;
+        this.buffered = true;
+        Ok(())
+    }
+
+    fn poll_flush(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<io::Result<()>> {
+        use std::task::Poll;
+
+        let this = self.get_mut();
+        if this.buffered {
+            match this.socket.poll_send_to(cx, &this.buf, this.destination) {
+                Poll::Ready(Ok(_)) => this.buffered = false,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
@@ -0,0 +1,152 @@
+//! Overlay types reading and writing the fixed-width prologue words in place.
+
+use crate::encoder::encode_vrt_header;
+use crate::error::Error;
+use crate::parser::parse_vrt_packet;
+use crate::vrt::{Header, Tsf, Tsi, VitaPacketType, VrtPacket};
+use zerocopy::byteorder::big_endian::{U32, U64};
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
+
+/// Overlay of the VRT packet header word
+///
+/// Like the other overlay types, it can be viewed directly in a network buffer of any alignment
+/// with the `zerocopy` traits, such as [`FromBytes::ref_from_prefix`] and
+/// [`FromBytes::mut_from_prefix`], so hot paths can read or patch individual fields, for
+/// example the packet count, without parsing or re-encoding the packet.
+#[derive(
+    Clone, Copy, Debug, Eq, PartialEq, FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned,
+)]
+#[repr(C)]
+pub struct HeaderOverlay {
+    word: [u8; 4],
+}
+
+impl HeaderOverlay {
+    /// Returns the packet type
+    pub fn packet_type(&self) -> VitaPacketType {
+        VitaPacketType(self.word[0] >> 4)
+    }
+
+    /// Returns whether the Class Identifier is present
+    pub fn c(&self) -> bool {
+        self.word[0] & 0x08 != 0
+    }
+
+    /// Returns whether the trailer is present
+    pub fn t(&self) -> bool {
+        self.word[0] & 0x04 != 0
+    }
+
+    /// Returns the indicator bits 25 and 24 of the header, as described for
+    /// [`Header::indicators`]
+    pub fn indicators(&self) -> u8 {
+        self.word[0] & 0x03
+    }
+
+    /// Returns the type of the Integer-seconds Timestamp
+    pub fn tsi(&self) -> Tsi {
+        Tsi(self.word[1] >> 6)
+    }
+
+    /// Returns the type of the Fractional-seconds Timestamp
+    pub fn tsf(&self) -> Tsf {
+        Tsf((self.word[1] >> 4) & 0x03)
+    }
+
+    /// Returns the packet count
+    pub fn packet_count(&self) -> u8 {
+        self.word[1] & 0x0f
+    }
+
+    /// Sets the packet count, which is taken modulo 16
+    pub fn set_packet_count(&mut self, packet_count: u8) {
+        self.word[1] = self.word[1] & 0xf0 | packet_count & 0x0f;
+    }
+
+    /// Returns the packet size in 32-bit words
+    pub fn packet_size(&self) -> u16 {
+        u16::from_be_bytes([self.word[2], self.word[3]])
+    }
+
+    /// Sets the packet size in 32-bit words
+    pub fn set_packet_size(&mut self, packet_size: u16) {
+        self.word[2..].copy_from_slice(&packet_size.to_be_bytes());
+    }
+
+    /// Returns the decoded header
+    pub fn header(&self) -> Header {
+        Header {
+            packet_type: self.packet_type(),
+            c: self.c(),
+            t: self.t(),
+            indicators: self.indicators(),
+            tsi: self.tsi(),
+            tsf: self.tsf(),
+            packet_count: self.packet_count(),
+            packet_size: self.packet_size(),
+        }
+    }
+
+    /// Overwrites the header word with `header`
+    pub fn set_header(&mut self, header: &Header) {
+        self.word = encode_vrt_header(header);
+    }
+}
+
+impl From<&Header> for HeaderOverlay {
+    fn from(header: &Header) -> Self {
+        HeaderOverlay {
+            word: encode_vrt_header(header),
+        }
+    }
+}
+
+/// Overlay of the prologue of a packet starting with a header and a Stream Identifier, such as
+/// a signal data packet with stream identifier
+#[derive(
+    Clone, Copy, Debug, Eq, PartialEq, FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned,
+)]
+#[repr(C)]
+pub struct StreamPrologue {
+    /// Header word
+    pub header: HeaderOverlay,
+    /// Stream Identifier
+    pub stream_id: U32,
+}
+
+/// Overlay of the prologue of a packet starting with a header, a Stream Identifier and a Class
+/// Identifier
+#[derive(
+    Clone, Copy, Debug, Eq, PartialEq, FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned,
+)]
+#[repr(C)]
+pub struct ClassPrologue {
+    /// Header word
+    pub header: HeaderOverlay,
+    /// Stream Identifier
+    pub stream_id: U32,
+    /// Class Identifier
+    pub class_id: U64,
+}
+
+/// Parses a packet from the start of `words`, returning the remaining words and the packet
+///
+/// The words must hold the packet in wire byte order, as delivered by a DMA engine copying the
+/// byte stream into word-aligned memory; the packet borrows its payload from `words` without
+/// any copy. Words holding host-order values must first be converted with
+/// [`host_words_to_wire`].
+pub fn parse_vrt_packet_words(words: &[u32]) -> Result<(&[u32], VrtPacket<'_>), Error> {
+    let bytes = words.as_bytes();
+    let (rest, packet) = parse_vrt_packet(bytes)?;
+    Ok((&words[(bytes.len() - rest.len()) / 4..], packet))
+}
+
+/// Converts words holding host-order values, such as those read from a 32-bit FIFO register,
+/// to wire byte order in place
+///
+/// This is a no-op on big-endian hosts.
+pub fn host_words_to_wire(words: &mut [u32]) {
+    for word in words {
+        *word = word.to_be();
+    }
+}
@@ -0,0 +1,123 @@
+//! Pacing of packet transmission at a fixed rate.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Default time before a deadline from which a [`Pacer`] busy-waits instead of sleeping
+pub const DEFAULT_SPIN: Duration = Duration::from_micros(100);
+
+/// Packet Pacer
+///
+/// Schedules packets at a fixed rate, either given in packets per second or derived from the
+/// sample rate and the number of samples per packet. [`Pacer::wait`] blocks until the next packet
+/// is due, sleeping while the deadline is far away and busy-waiting for the last microseconds,
+/// since sleeps are only accurate to the scheduler granularity.
+///
+/// Deadlines are computed from the time of the first packet rather than from the previous
+/// packet, so timing errors do not accumulate. A sender falling behind the schedule sends the
+/// late packets without delay until it has caught up.
+#[derive(Clone, Debug)]
+pub struct Pacer {
+    interval: Duration,
+    rate: Option<f64>,
+    spin: Duration,
+    start: Option<Instant>,
+    packets: u64,
+}
+
+impl Pacer {
+    /// Creates a new Pacer emitting `packets_per_second` packets per second
+    ///
+    /// # Panics
+    ///
+    /// Panics if `packets_per_second` is not positive and finite.
+    pub fn new(packets_per_second: f64) -> Self {
+        assert!(
+            packets_per_second.is_finite() && packets_per_second > 0.0,
+            "packet rate must be positive"
+        );
+        Pacer {
+            rate: Some(packets_per_second),
+            ..Self::with_interval(Duration::from_secs_f64(1.0 / packets_per_second))
+        }
+    }
+
+    /// Creates a new Pacer for packets of `samples_per_packet` samples of a stream at
+    /// `sample_rate` samples per second
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resulting packet rate is not positive and finite.
+    pub fn from_sample_rate(sample_rate: f64, samples_per_packet: u64) -> Self {
+        Self::new(sample_rate / samples_per_packet as f64)
+    }
+
+    /// Creates a new Pacer emitting a packet every `interval`
+    pub fn with_interval(interval: Duration) -> Self {
+        Pacer {
+            interval,
+            rate: None,
+            spin: DEFAULT_SPIN,
+            start: None,
+            packets: 0,
+        }
+    }
+
+    /// Sets the time before each deadline from which the pacer busy-waits, which defaults to
+    /// [`DEFAULT_SPIN`]. A longer time is more accurate on a loaded system but uses more CPU.
+    pub fn with_spin(mut self, spin: Duration) -> Self {
+        self.spin = spin;
+        self
+    }
+
+    /// Returns the interval between packets, rounded to the nanosecond for a pacer created
+    /// from a packet rate
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Returns the number of packets paced since the pacer was created or reset
+    pub fn packets(&self) -> u64 {
+        self.packets
+    }
+
+    /// Returns the instant at which the next packet is due, or None before the first packet
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.start.map(|start| start + self.offset(self.packets))
+    }
+
+    /// Blocks until the next packet is due; the first packet is due immediately
+    pub fn wait(&mut self) {
+        match self.next_deadline() {
+            Some(deadline) => {
+                let now = Instant::now();
+                if deadline > now + self.spin {
+                    thread::sleep(deadline - now - self.spin);
+                }
+                while Instant::now() < deadline {
+                    std::hint::spin_loop();
+                }
+            }
+            None => self.start = Some(Instant::now()),
+        }
+        self.packets += 1;
+    }
+
+    /// Restarts the schedule, so the next packet is due immediately
+    pub fn reset(&mut self) {
+        self.start = None;
+        self.packets = 0;
+    }
+
+    fn offset(&self, packets: u64) -> Duration {
+        // a deadline computed from the rate itself carries no rounding error of the interval
+        if let Some(rate) = self.rate {
+            return Duration::from_secs_f64(packets as f64 / rate);
+        }
+        let nanos = self.interval.as_nanos() * packets as u128;
+        Duration::new(
+            (nanos / 1_000_000_000) as u64,
+            (nanos % 1_000_000_000) as u32,
+        )
+    }
+}
@@ -1,13 +1,33 @@
+//! Parsing of VRT packets from their wire format.
+//!
+//! The parsers are not recursive: each one calls a fixed chain of functions whose depth does
+//! not depend on the input, and no parser keeps a buffer on the stack, so the stack used by a
+//! parse is bounded by a small constant regardless of the packet contents. Back-to-back
+//! packets are consumed iteratively by [`PacketStream`](crate::PacketStream) and
+//! [`PushParser`](crate::PushParser), never by recursion.
+//!
+//! No parser panics, whatever its input: every length is checked against the input before it
+//! is used, and malformed input is reported as an error. The lints below keep panicking
+//! operations out of this module.
+
+#![deny(
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unwrap_used,
+    clippy::expect_used
+)]
+
+use crate::error::{Error, FieldError, PacketField};
 use crate::vrt::*;
-use nom::number::streaming::be_u8;
+use nom::bytes::complete::take;
+use nom::combinator::cond;
+use nom::error::{make_error, ErrorKind};
+use nom::number::complete;
+use nom::number::streaming::{be_u16, be_u32, be_u8};
 use nom::{Err, IResult, Needed};
 
 fn u8_to_bool(v: u8) -> bool {
-    match v {
-        0 => false,
-        1 => true,
-        _ => panic!("Invalid bool in u8 {}", v),
-    }
+    v & 0x01 != 0
 }
 
 /// Parses the VRT packet's header
@@ -26,14 +46,13 @@ pub fn parse_vrt_header(i: &[u8]) -> IResult<&[u8], Header> {
     let tsf: u8 = (second_byte >> 4) & 0x03;
     let packet_count: u8 = (second_byte) & 0xf;
 
-    let (i, third_byte) = be_u8(i)?;
-    let (i, fourth_byte) = be_u8(i)?;
-    let packet_size: u16 = (third_byte + fourth_byte) as u16;
+    let (i, packet_size) = be_u16(i)?;
 
     let hdr = Header {
         packet_type: VitaPacketType(packet_type),
         c: u8_to_bool(c),
         t: u8_to_bool(t),
+        indicators: first_byte & 0x03,
         tsi: Tsi(tsi),
         tsf: Tsf(tsf),
         packet_count,
@@ -117,3 +136,207 @@ pub fn parse_vrt_trailer(i: &[u8]) -> IResult<&[u8], Trailer> {
     };
     Ok((i, hdr))
 }
+
+/// Parses a complete VRT packet, including any optional fields, data payload and trailer
+///
+/// The packet length is taken from the header's `packet_size` field. Any input following
+/// the packet is returned as the remaining input.
+pub fn parse_vrt_packet(i: &[u8]) -> IResult<&[u8], VrtPacket<'_>> {
+    let result = parse_packet(i);
+    #[cfg(feature = "tracing")]
+    match &result {
+        Ok((_, packet)) => tracing::trace!(
+            stream_id = packet.stream_id,
+            packet_type = packet.header.packet_type.0,
+            packet_count = packet.header.packet_count,
+            packet_size = packet.header.packet_size,
+            "parsed VRT packet"
+        ),
+        Err(Err::Incomplete(needed)) => tracing::trace!(?needed, "incomplete VRT packet"),
+        Err(_) => tracing::debug!(len = i.len(), "malformed VRT packet"),
+    }
+    result
+}
+
+impl<'a> VrtPacket<'a> {
+    /// Parses the complete VRT packet at the start of `bytes`, returning its length in bytes
+    /// and the packet
+    ///
+    /// Equivalent to [`parse_vrt_packet`], but returns the crate's [`Error`] rather than nom's
+    /// error types, so callers need not depend on nom. Any bytes following the packet, which
+    /// start at the returned length, are ignored.
+    pub fn parse(bytes: &'a [u8]) -> Result<(usize, VrtPacket<'a>), Error> {
+        let (rem, packet) = parse_vrt_packet(bytes).map_err(|e| packet_error(bytes, e))?;
+        Ok((bytes.len() - rem.len(), packet))
+    }
+}
+
+/// Handling of input following the `packet_size` declared by a packet, for
+/// [`VrtPacket::parse_checked`]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TrailingBytes {
+    /// Fail with [`Error::TrailingBytes`]
+    #[default]
+    Error,
+    /// Discard them, logging a warning with the `tracing` feature
+    Warn,
+    /// Return them as the remainder
+    Remainder,
+}
+
+impl<'a> VrtPacket<'a> {
+    /// Parses `bytes`, which should hold exactly one VRT packet, such as a UDP datagram,
+    /// returning the packet and any bytes following it as handled by `trailing`
+    ///
+    /// Like [`VrtPacket::parse`], this fails with [`Error::MalformedField`] if the declared
+    /// `packet_size` is smaller than [`Header::min_packet_size`]. Any bytes beyond the
+    /// declared size are an error, discarded or returned, as chosen by `trailing`; the
+    /// remainder returned is empty unless `trailing` is [`TrailingBytes::Remainder`].
+    pub fn parse_checked(
+        bytes: &'a [u8],
+        trailing: TrailingBytes,
+    ) -> Result<(VrtPacket<'a>, &'a [u8]), Error> {
+        let (len, packet) = Self::parse(bytes)?;
+        let rest = bytes.get(len..).unwrap_or_default();
+        match trailing {
+            _ if rest.is_empty() => Ok((packet, rest)),
+            TrailingBytes::Error => Err(Error::TrailingBytes(rest.len())),
+            TrailingBytes::Warn => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    stream_id = packet.stream_id,
+                    trailing = rest.len(),
+                    "bytes beyond the declared VRT packet size"
+                );
+                Ok((packet, &[]))
+            }
+            TrailingBytes::Remainder => Ok((packet, rest)),
+        }
+    }
+}
+
+/// Converts the error of parsing the packet at the start of `i` to an [`Error`], locating the
+/// field at fault in an [`Error::MalformedField`] where possible
+pub(crate) fn packet_error(i: &[u8], e: Err<nom::error::Error<&[u8]>>) -> Error {
+    if let Err::Incomplete(_) = e {
+        return e.into();
+    }
+    match parse_vrt_header(i) {
+        Ok((_, header)) => malformed_field(&header).map_or(Error::Malformed, Error::MalformedField),
+        Err(_) => Error::Malformed,
+    }
+}
+
+/// Returns the first field implied by `header` which does not fit within its `packet_size`
+pub(crate) fn malformed_field(header: &Header) -> Option<FieldError> {
+    let packet_len = header.packet_size as usize * 4;
+    if packet_len < 4 {
+        return Some(FieldError {
+            field: PacketField::Header,
+            word: 1,
+            offset: 0,
+            reason: "packet size is less than one word",
+        });
+    }
+    let fields = [
+        (PacketField::StreamId, header.packet_type.has_stream_id(), 1),
+        (PacketField::ClassId, header.c, 2),
+        (
+            PacketField::IntegerTimestamp,
+            header.tsi != Tsi::TSI_NONE,
+            1,
+        ),
+        (
+            PacketField::FractionalTimestamp,
+            header.tsf != Tsf::TSF_NONE,
+            2,
+        ),
+        (
+            PacketField::Trailer,
+            header.t && header.packet_type.is_data(),
+            1,
+        ),
+    ];
+    let mut offset = 4;
+    for (field, present, words) in fields {
+        if !present {
+            continue;
+        }
+        if offset + words * 4 > packet_len {
+            let word = (packet_len.max(offset) - offset) / 4;
+            return Some(FieldError {
+                field,
+                word: word as u8 + 1,
+                offset: offset + word * 4,
+                reason: "beyond the declared packet size",
+            });
+        }
+        offset += words * 4;
+    }
+    None
+}
+
+fn parse_packet(i: &[u8]) -> IResult<&[u8], VrtPacket<'_>> {
+    let (_, header) = parse_vrt_header(i)?;
+    let packet_len = header.packet_size as usize * 4;
+    if header.packet_size < header.min_packet_size() {
+        return Err(Err::Error(make_error(i, ErrorKind::LengthValue)));
+    }
+    if i.len() < packet_len {
+        return Err(Err::Incomplete(Needed::new(packet_len - i.len())));
+    }
+    let (rem, packet) = take(packet_len)(i)?;
+
+    // the optional fields must fit within the declared packet_size
+    let (p, _) = take(4usize)(packet)?;
+    let (p, stream_id) = cond(header.packet_type.has_stream_id(), complete::be_u32)(p)?;
+    let (p, class_id) = cond(header.c, complete::be_u64)(p)?;
+    let (p, tsi) = cond(header.tsi != Tsi::TSI_NONE, complete::be_u32)(p)?;
+    let (p, tsf) = cond(header.tsf != Tsf::TSF_NONE, complete::be_u64)(p)?;
+
+    let trailer_len = if header.t && header.packet_type.is_data() {
+        4
+    } else {
+        0
+    };
+    let (p, data_payload) = take(p.len().saturating_sub(trailer_len))(p)?;
+    let trailer = if trailer_len > 0 {
+        let (_, trailer) =
+            parse_vrt_trailer(p).map_err(|_| Err::Error(make_error(p, ErrorKind::Eof)))?;
+        Some(trailer)
+    } else {
+        None
+    };
+
+    let packet = VrtPacket {
+        header,
+        stream_id,
+        class_id,
+        tsi,
+        tsf,
+        data_payload,
+        trailer,
+    };
+    Ok((rem, packet))
+}
+
+/// Parses a per-field Warning/Error Response word from a VITA 49.2 Acknowledge packet
+pub fn parse_vrt_warning_error_response(i: &[u8]) -> IResult<&[u8], WarningErrorResponse> {
+    let (i, word) = be_u32(i)?;
+    Ok((i, WarningErrorResponse(word)))
+}
+
+/// Parses the per-field Warning/Error Response words announced by the Warning or Error
+/// Indicator Field 0 word `indicators` of a VITA 49.2 Acknowledge packet: one word for each of
+/// its bits 30 to 8 which is set, in order from the most significant bit
+pub fn parse_vrt_warning_error_responses(
+    indicators: u32,
+    i: &[u8],
+) -> IResult<&[u8], WarningErrorResponses<'_>> {
+    let len = (indicators & INDICATOR_FIELD_BITS).count_ones() as usize * 4;
+    if i.len() < len {
+        return Err(Err::Incomplete(Needed::new(len - i.len())));
+    }
+    let (i, words) = take(len)(i)?;
+    Ok((i, WarningErrorResponses::new(indicators, words)))
+}
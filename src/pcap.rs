@@ -0,0 +1,609 @@
+//! Reading and writing of VRT-over-UDP packets in pcap and pcapng capture files.
+
+use crate::encoder::encode_vrt_packet;
+use crate::stream::PacketStream;
+use crate::vrt::{VrtPacket, VrtPacketOwned};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::Path;
+use std::time::Duration;
+
+/// Link type of Ethernet frames
+pub const LINKTYPE_ETHERNET: u16 = 1;
+/// Link type of raw IPv4 or IPv6 packets
+pub const LINKTYPE_RAW: u16 = 101;
+/// Link type of Linux cooked captures
+pub const LINKTYPE_LINUX_SLL: u16 = 113;
+
+const PCAP_MAGIC_MICROS: u32 = 0xa1b2_c3d4;
+const PCAP_MAGIC_NANOS: u32 = 0xa1b2_3c4d;
+const PCAPNG_SECTION_HEADER: u32 = 0x0a0d_0d0a;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1a2b_3c4d;
+const PCAPNG_INTERFACE_DESCRIPTION: u32 = 1;
+const PCAPNG_SIMPLE_PACKET: u32 = 3;
+const PCAPNG_ENHANCED_PACKET: u32 = 6;
+const PCAPNG_OPTION_TSRESOL: u16 = 9;
+/// Longest pcapng block read, the limit libpcap applies
+const PCAPNG_MAX_BLOCK_LEN: usize = 16 * 1024 * 1024;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+const ETHERTYPE_QINQ: u16 = 0x88a8;
+const IPPROTO_UDP: u8 = 17;
+
+#[derive(Clone, Debug, PartialEq)]
+/// A VRT packet read from a pcap or pcapng capture
+pub struct PcapPacket {
+    /// Capture timestamp as the time since the UNIX epoch
+    pub timestamp: Duration,
+    /// Source address and UDP port of the datagram
+    pub source: SocketAddr,
+    /// Destination address and UDP port of the datagram
+    pub destination: SocketAddr,
+    /// The VRT packet
+    pub packet: VrtPacketOwned,
+}
+
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn u16_at(b: &[u8], i: usize, le: bool) -> u16 {
+    let v = [b[i], b[i + 1]];
+    if le {
+        u16::from_le_bytes(v)
+    } else {
+        u16::from_be_bytes(v)
+    }
+}
+
+fn u32_at(b: &[u8], i: usize, le: bool) -> u32 {
+    let v = [b[i], b[i + 1], b[i + 2], b[i + 3]];
+    if le {
+        u32::from_le_bytes(v)
+    } else {
+        u32::from_be_bytes(v)
+    }
+}
+
+/// Returns the source and destination addresses and the payload of the UDP datagram carried in
+/// `frame`, or None if the frame does not hold an unfragmented UDP datagram
+pub(crate) fn udp_payload(linktype: u16, frame: &[u8]) -> Option<(SocketAddr, SocketAddr, &[u8])> {
+    let (ethertype, ip) = match linktype {
+        LINKTYPE_ETHERNET => {
+            let mut offset = 12;
+            let mut ethertype = u16_at(frame.get(..offset + 2)?, offset, false);
+            while ethertype == ETHERTYPE_VLAN || ethertype == ETHERTYPE_QINQ {
+                offset += 4;
+                ethertype = u16_at(frame.get(..offset + 2)?, offset, false);
+            }
+            (Some(ethertype), &frame[offset + 2..])
+        }
+        LINKTYPE_LINUX_SLL => (Some(u16_at(frame.get(..16)?, 14, false)), &frame[16..]),
+        LINKTYPE_RAW => (None, frame),
+        _ => return None,
+    };
+    let version = ip.first()? >> 4;
+    let (src, dst, udp) = match (ethertype, version) {
+        (Some(ETHERTYPE_IPV4) | None, 4) => {
+            let ihl = (ip[0] & 0x0f) as usize * 4;
+            let total = u16_at(ip.get(..20)?, 2, false) as usize;
+            let fragmented = u16_at(ip, 6, false) & 0x3fff != 0;
+            if fragmented || ip[9] != IPPROTO_UDP || ihl < 20 {
+                return None;
+            }
+            let src = Ipv4Addr::new(ip[12], ip[13], ip[14], ip[15]);
+            let dst = Ipv4Addr::new(ip[16], ip[17], ip[18], ip[19]);
+            let udp = ip.get(ihl..total.min(ip.len()))?;
+            (IpAddr::V4(src), IpAddr::V4(dst), udp)
+        }
+        (Some(ETHERTYPE_IPV6) | None, 6) => {
+            let header = ip.get(..40)?;
+            let mut src = [0u8; 16];
+            src.copy_from_slice(&header[8..24]);
+            let mut dst = [0u8; 16];
+            dst.copy_from_slice(&header[24..40]);
+            let end = (40 + u16_at(header, 4, false) as usize).min(ip.len());
+            let (mut next, mut offset) = (header[6], 40);
+            // hop-by-hop, routing and destination options extension headers
+            while matches!(next, 0 | 43 | 60) {
+                let ext = ip.get(offset..offset + 2)?;
+                next = ext[0];
+                offset += (ext[1] as usize + 1) * 8;
+            }
+            if next != IPPROTO_UDP {
+                return None;
+            }
+            let udp = ip.get(offset..end)?;
+            (
+                IpAddr::V6(Ipv6Addr::from(src)),
+                IpAddr::V6(Ipv6Addr::from(dst)),
+                udp,
+            )
+        }
+        _ => return None,
+    };
+    let header = udp.get(..8)?;
+    let len = (u16_at(header, 4, false) as usize).clamp(8, udp.len());
+    Some((
+        SocketAddr::new(src, u16_at(header, 0, false)),
+        SocketAddr::new(dst, u16_at(header, 2, false)),
+        &udp[8..len],
+    ))
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Interface {
+    linktype: u16,
+    // timestamp units per second
+    resolution: u64,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Format {
+    Pcap {
+        le: bool,
+        interface: Interface,
+        snaplen: usize,
+    },
+    Pcapng {
+        le: bool,
+    },
+}
+
+/// pcap and pcapng Capture Reader
+///
+/// Reads a capture file in either the classic pcap or the pcapng format, decapsulates the
+/// Ethernet, Linux cooked or raw IP frames carrying UDP over IPv4 or IPv6, and yields the VRT
+/// packets held in each UDP datagram. Frames which do not carry an unfragmented UDP datagram
+/// are ignored, and datagrams which do not hold valid VRT packets are counted by
+/// [`PcapReader::skipped`]. Records longer than the snapshot length of a pcap capture and
+/// pcapng blocks longer than 16 MiB are returned as errors of kind
+/// [`io::ErrorKind::InvalidData`] rather than read. After an error reading the capture the
+/// reader is at an unknown position, so it ends.
+///
+/// The Stream Identifiers seen on each UDP source port are tracked and available from
+/// [`PcapReader::streams_by_port`].
+#[derive(Debug)]
+pub struct PcapReader<R: Read> {
+    reader: R,
+    format: Format,
+    interfaces: Vec<Interface>,
+    pending: VecDeque<PcapPacket>,
+    ports: BTreeMap<u16, BTreeSet<Option<u32>>>,
+    skipped: u64,
+    failed: bool,
+}
+
+impl PcapReader<BufReader<File>> {
+    /// Opens the pcap or pcapng capture file `path`
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        PcapReader::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> PcapReader<R> {
+    /// Creates a new PcapReader reading a pcap or pcapng capture from `reader`
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if the capture does not start
+    /// with a pcap file header or pcapng section header block.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        let (format, interfaces) = if u32::from_be_bytes(magic) == PCAPNG_SECTION_HEADER {
+            let mut len = [0u8; 4];
+            reader.read_exact(&mut len)?;
+            let le = Self::read_section_header(&mut reader, len)?;
+            (Format::Pcapng { le }, Vec::new())
+        } else {
+            let (le, resolution) = match (u32::from_be_bytes(magic), u32::from_le_bytes(magic)) {
+                (PCAP_MAGIC_MICROS, _) => (false, 1_000_000),
+                (PCAP_MAGIC_NANOS, _) => (false, 1_000_000_000),
+                (_, PCAP_MAGIC_MICROS) => (true, 1_000_000),
+                (_, PCAP_MAGIC_NANOS) => (true, 1_000_000_000),
+                _ => return Err(invalid_data("not a pcap or pcapng capture")),
+            };
+            let mut header = [0u8; 20];
+            reader.read_exact(&mut header)?;
+            let interface = Interface {
+                linktype: u32_at(&header, 16, le) as u16,
+                resolution,
+            };
+            let snaplen = u32_at(&header, 12, le) as usize;
+            (
+                Format::Pcap {
+                    le,
+                    interface,
+                    snaplen,
+                },
+                vec![interface],
+            )
+        };
+        Ok(PcapReader {
+            reader,
+            format,
+            interfaces,
+            pending: VecDeque::new(),
+            ports: BTreeMap::new(),
+            skipped: 0,
+            failed: false,
+        })
+    }
+
+    /// Reads the remainder of a section header block following its block type and the bytes
+    /// `len` of its block length, returning whether the section is little-endian
+    fn read_section_header(reader: &mut R, len: [u8; 4]) -> io::Result<bool> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        let le = match u32::from_be_bytes(magic) {
+            PCAPNG_BYTE_ORDER_MAGIC => false,
+            m if m.swap_bytes() == PCAPNG_BYTE_ORDER_MAGIC => true,
+            _ => return Err(invalid_data("invalid pcapng byte-order magic")),
+        };
+        let len = u32_at(&len, 0, le) as usize;
+        if len < 28 || len & 3 != 0 {
+            return Err(invalid_data("invalid pcapng block length"));
+        }
+        io::copy(&mut reader.take((len - 12) as u64), &mut io::sink())?;
+        Ok(le)
+    }
+
+    /// Returns the number of UDP datagrams which did not hold valid VRT packets
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    /// Returns the Stream Identifiers seen on each UDP source port. Packets without a Stream
+    /// Identifier are recorded as None.
+    pub fn streams_by_port(&self) -> &BTreeMap<u16, BTreeSet<Option<u32>>> {
+        &self.ports
+    }
+
+    /// Reads the next frame, returning its interface, timestamp and contents, or None at the
+    /// end of the capture
+    fn read_frame(&mut self) -> io::Result<Option<(Interface, Duration, Vec<u8>)>> {
+        loop {
+            let mut header = [0u8; 16];
+            match self.reader.read_exact(&mut header[..8]) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(e) => return Err(e),
+            }
+            match self.format {
+                Format::Pcap {
+                    le,
+                    interface,
+                    snaplen,
+                } => {
+                    self.reader.read_exact(&mut header[8..])?;
+                    let secs = u32_at(&header, 0, le) as u64;
+                    let units = u32_at(&header, 4, le) as u64;
+                    let captured = u32_at(&header, 8, le) as usize;
+                    if captured > snaplen {
+                        return Err(invalid_data("pcap record longer than the snapshot length"));
+                    }
+                    let mut frame = vec![0u8; captured];
+                    self.reader.read_exact(&mut frame)?;
+                    let nanos = units * 1_000_000_000 / interface.resolution;
+                    return Ok(Some((interface, Duration::new(secs, nanos as u32), frame)));
+                }
+                Format::Pcapng { le } => {
+                    let block_type = u32_at(&header, 0, le);
+                    if block_type == PCAPNG_SECTION_HEADER {
+                        let len = [header[4], header[5], header[6], header[7]];
+                        let le = Self::read_section_header(&mut self.reader, len)?;
+                        self.format = Format::Pcapng { le };
+                        self.interfaces.clear();
+                        continue;
+                    }
+                    let len = u32_at(&header, 4, le) as usize;
+                    if len < 12 || len & 3 != 0 {
+                        return Err(invalid_data("invalid pcapng block length"));
+                    }
+                    if len > PCAPNG_MAX_BLOCK_LEN {
+                        return Err(invalid_data("pcapng block too long"));
+                    }
+                    let mut body = vec![0u8; len - 8];
+                    self.reader.read_exact(&mut body)?;
+                    let body = &body[..len - 12];
+                    if let Some(frame) = self.pcapng_block(block_type, body, le)? {
+                        return Ok(Some(frame));
+                    }
+                }
+            }
+        }
+    }
+
+    fn pcapng_block(
+        &mut self,
+        block_type: u32,
+        body: &[u8],
+        le: bool,
+    ) -> io::Result<Option<(Interface, Duration, Vec<u8>)>> {
+        let truncated = || invalid_data("truncated pcapng block");
+        match block_type {
+            PCAPNG_INTERFACE_DESCRIPTION => {
+                let linktype = u16_at(body.get(..8).ok_or_else(truncated)?, 0, le);
+                let mut resolution = 1_000_000;
+                let mut options = &body[8..];
+                while options.len() >= 4 {
+                    let code = u16_at(options, 0, le);
+                    let len = u16_at(options, 2, le) as usize;
+                    let value = options.get(4..4 + len).ok_or_else(truncated)?;
+                    if code == PCAPNG_OPTION_TSRESOL && len >= 1 {
+                        let exp = (value[0] & 0x7f) as u32;
+                        let base: u64 = if value[0] & 0x80 != 0 { 2 } else { 10 };
+                        resolution = base.checked_pow(exp).ok_or_else(|| {
+                            invalid_data("unsupported pcapng timestamp resolution")
+                        })?;
+                    }
+                    if code == 0 {
+                        break;
+                    }
+                    options = options.get(4 + len.next_multiple_of(4)..).unwrap_or(&[]);
+                }
+                self.interfaces.push(Interface {
+                    linktype,
+                    resolution,
+                });
+                Ok(None)
+            }
+            PCAPNG_ENHANCED_PACKET => {
+                let b = body.get(..20).ok_or_else(truncated)?;
+                let interface = *self
+                    .interfaces
+                    .get(u32_at(b, 0, le) as usize)
+                    .ok_or_else(|| invalid_data("unknown pcapng interface"))?;
+                let ts = (u32_at(b, 4, le) as u64) << 32 | u32_at(b, 8, le) as u64;
+                let captured = u32_at(b, 12, le) as usize;
+                let frame = body.get(20..20 + captured).ok_or_else(truncated)?;
+                let secs = ts / interface.resolution;
+                let nanos = (ts % interface.resolution) as u128 * 1_000_000_000
+                    / interface.resolution as u128;
+                let timestamp = Duration::new(secs, nanos as u32);
+                Ok(Some((interface, timestamp, frame.to_vec())))
+            }
+            PCAPNG_SIMPLE_PACKET => {
+                let interface = *self
+                    .interfaces
+                    .first()
+                    .ok_or_else(|| invalid_data("unknown pcapng interface"))?;
+                let original = u32_at(body.get(..4).ok_or_else(truncated)?, 0, le) as usize;
+                let frame = &body[4..(4 + original).min(body.len())];
+                Ok(Some((interface, Duration::ZERO, frame.to_vec())))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Reads the next VRT packet, or returns None at the end of the capture or after an error
+    pub fn read_packet(&mut self) -> io::Result<Option<PcapPacket>> {
+        while self.pending.is_empty() {
+            if self.failed {
+                return Ok(None);
+            }
+            let (interface, timestamp, frame) = match self.read_frame() {
+                Ok(Some(frame)) => frame,
+                Ok(None) => return Ok(None),
+                Err(e) => {
+                    self.failed = true;
+                    return Err(e);
+                }
+            };
+            let (source, destination, payload) = match udp_payload(interface.linktype, &frame) {
+                Some(datagram) => datagram,
+                None => continue,
+            };
+            let mut stream = PacketStream::new(payload);
+            let packets: Result<Vec<_>, _> = stream.by_ref().collect();
+            match packets {
+                Ok(packets) if !packets.is_empty() && stream.remainder().is_empty() => {
+                    for packet in packets {
+                        self.ports
+                            .entry(source.port())
+                            .or_default()
+                            .insert(packet.stream_id);
+                        self.pending.push_back(PcapPacket {
+                            timestamp,
+                            source,
+                            destination,
+                            packet: VrtPacketOwned::from(&packet),
+                        });
+                    }
+                }
+                _ => self.skipped += 1,
+            }
+        }
+        Ok(self.pending.pop_front())
+    }
+}
+
+impl<R: Read> Iterator for PcapReader<R> {
+    type Item = io::Result<PcapPacket>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_packet().transpose()
+    }
+}
+
+/// Returns the ones' complement sum of `data` folded to 16 bits, as used by IP checksums
+fn checksum(data: &[&[u8]]) -> u16 {
+    let mut sum = 0u32;
+    for chunk in data.iter().flat_map(|d| d.chunks(2)) {
+        let word = u16::from_be_bytes([chunk[0], chunk.get(1).copied().unwrap_or(0)]);
+        sum += word as u32;
+    }
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// Wraps `payload` in a UDP datagram and an IPv4 or IPv6 header for the given addresses
+fn ip_udp_frame(
+    source: SocketAddr,
+    destination: SocketAddr,
+    payload: &[u8],
+) -> io::Result<Vec<u8>> {
+    let udp_len = 8 + payload.len();
+    let mut udp = Vec::with_capacity(udp_len);
+    udp.extend_from_slice(&source.port().to_be_bytes());
+    udp.extend_from_slice(&destination.port().to_be_bytes());
+    udp.extend_from_slice(&(udp_len as u16).to_be_bytes());
+    udp.extend_from_slice(&[0, 0]);
+    udp.extend_from_slice(payload);
+
+    let (mut frame, addresses) = match (source.ip(), destination.ip()) {
+        (IpAddr::V4(src), IpAddr::V4(dst)) if udp_len + 20 <= u16::MAX as usize => {
+            let mut ip = vec![0x45, 0x00];
+            ip.extend_from_slice(&((20 + udp_len) as u16).to_be_bytes());
+            ip.extend_from_slice(&[0, 0, 0x40, 0x00, 64, IPPROTO_UDP, 0, 0]);
+            ip.extend_from_slice(&src.octets());
+            ip.extend_from_slice(&dst.octets());
+            let sum = checksum(&[&ip]);
+            ip[10..12].copy_from_slice(&sum.to_be_bytes());
+            let addresses = ip[12..20].to_vec();
+            (ip, addresses)
+        }
+        (IpAddr::V6(src), IpAddr::V6(dst)) if udp_len <= u16::MAX as usize => {
+            let mut ip = vec![0x60, 0, 0, 0];
+            ip.extend_from_slice(&(udp_len as u16).to_be_bytes());
+            ip.extend_from_slice(&[IPPROTO_UDP, 64]);
+            ip.extend_from_slice(&src.octets());
+            ip.extend_from_slice(&dst.octets());
+            let addresses = ip[8..40].to_vec();
+            (ip, addresses)
+        }
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "mismatched address families or datagram too large",
+            ))
+        }
+    };
+
+    // the UDP checksum covers a pseudo-header and is mandatory over IPv6
+    let pseudo = [0, IPPROTO_UDP, (udp_len >> 8) as u8, udp_len as u8];
+    let sum = match checksum(&[&addresses, &pseudo, &udp]) {
+        0 => 0xffff,
+        sum => sum,
+    };
+    udp[6..8].copy_from_slice(&sum.to_be_bytes());
+    frame.extend_from_slice(&udp);
+    Ok(frame)
+}
+
+/// pcapng Capture Writer
+///
+/// Writes VRT packets as VRT-over-UDP frames to a pcapng capture which can be opened in
+/// Wireshark or read back with a [`PcapReader`]. Each packet is encoded and wrapped in
+/// synthetic raw IPv4 or IPv6 and UDP headers, with the addresses and timestamps of a
+/// [`PcapPacket`] preserved when it is written with [`PcapngWriter::write`].
+#[derive(Debug)]
+pub struct PcapngWriter<W: Write> {
+    writer: W,
+    source: SocketAddr,
+    destination: SocketAddr,
+}
+
+impl PcapngWriter<BufWriter<File>> {
+    /// Creates the pcapng capture file `path`
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        PcapngWriter::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> PcapngWriter<W> {
+    /// Creates a new PcapngWriter writing to `writer`, and writes the section header and a raw
+    /// IP interface description with nanosecond timestamps
+    ///
+    /// Packets written with [`PcapngWriter::write_packet`] are sent from and to
+    /// `127.0.0.1:4991` unless changed with [`PcapngWriter::with_addresses`].
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        let mut shb = PCAPNG_BYTE_ORDER_MAGIC.to_be_bytes().to_vec();
+        shb.extend_from_slice(&[0, 1, 0, 0]);
+        shb.extend_from_slice(&u64::MAX.to_be_bytes());
+        Self::write_block(&mut writer, PCAPNG_SECTION_HEADER, &[&shb])?;
+
+        let mut idb = LINKTYPE_RAW.to_be_bytes().to_vec();
+        idb.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+        idb.extend_from_slice(&PCAPNG_OPTION_TSRESOL.to_be_bytes());
+        idb.extend_from_slice(&[0, 1, 9, 0, 0, 0]);
+        idb.extend_from_slice(&[0; 4]);
+        Self::write_block(&mut writer, PCAPNG_INTERFACE_DESCRIPTION, &[&idb])?;
+
+        let address = SocketAddr::from(([127, 0, 0, 1], 4991));
+        Ok(PcapngWriter {
+            writer,
+            source: address,
+            destination: address,
+        })
+    }
+
+    /// Sets the source and destination addresses of packets written with
+    /// [`PcapngWriter::write_packet`]
+    pub fn with_addresses(mut self, source: SocketAddr, destination: SocketAddr) -> Self {
+        self.source = source;
+        self.destination = destination;
+        self
+    }
+
+    fn write_block(writer: &mut W, block_type: u32, body: &[&[u8]]) -> io::Result<()> {
+        let body_len: usize = body.iter().map(|b| b.len()).sum();
+        let padding = body_len.wrapping_neg() & 3;
+        let len = ((12 + body_len + padding) as u32).to_be_bytes();
+        writer.write_all(&block_type.to_be_bytes())?;
+        writer.write_all(&len)?;
+        for b in body {
+            writer.write_all(b)?;
+        }
+        writer.write_all(&[0; 3][..padding])?;
+        writer.write_all(&len)
+    }
+
+    fn write_frame(
+        &mut self,
+        timestamp: Duration,
+        source: SocketAddr,
+        destination: SocketAddr,
+        packet: &VrtPacket<'_>,
+    ) -> io::Result<()> {
+        let mut payload = vec![0u8; packet.encoded_len()];
+        encode_vrt_packet(packet, &mut payload)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid VRT packet"))?;
+        let frame = ip_udp_frame(source, destination, &payload)?;
+
+        let ts = timestamp.as_nanos() as u64;
+        let mut epb = [0u8; 20];
+        epb[4..8].copy_from_slice(&((ts >> 32) as u32).to_be_bytes());
+        epb[8..12].copy_from_slice(&(ts as u32).to_be_bytes());
+        epb[12..16].copy_from_slice(&(frame.len() as u32).to_be_bytes());
+        epb[16..20].copy_from_slice(&(frame.len() as u32).to_be_bytes());
+        Self::write_block(&mut self.writer, PCAPNG_ENHANCED_PACKET, &[&epb, &frame])
+    }
+
+    /// Writes `packet` with the capture timestamp `timestamp`, the time since the UNIX epoch
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidInput`] if the packet cannot be encoded.
+    pub fn write_packet(&mut self, timestamp: Duration, packet: &VrtPacket<'_>) -> io::Result<()> {
+        self.write_frame(timestamp, self.source, self.destination, packet)
+    }
+
+    /// Writes `packet` preserving its capture timestamp and addresses
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidInput`] if the packet cannot be encoded
+    /// or its source and destination addresses are of different families.
+    pub fn write(&mut self, packet: &PcapPacket) -> io::Result<()> {
+        let vrt = packet.packet.as_packet();
+        self.write_frame(packet.timestamp, packet.source, packet.destination, &vrt)
+    }
+
+    /// Flushes and returns the underlying writer
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
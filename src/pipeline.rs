@@ -0,0 +1,367 @@
+//! Pipelines of packet processing stages connected by bounded channels.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+/// Behaviour of a pipeline stage when the channel to the next stage is full
+pub enum Overflow {
+    /// Wait for the next stage to catch up, propagating backpressure up to the source
+    #[default]
+    Block,
+    /// Drop the item and count it in [`Pipeline::dropped`], for sources such as network
+    /// receivers which must not be stalled
+    Drop,
+}
+
+/// Pipeline of Processing Stages
+///
+/// Assembles a source, any number of transform stages and a sink into a pipeline, with each
+/// stage except the sink running on its own thread and connected to the next by a bounded
+/// channel of the given capacity. When a channel is full, the stage feeding it either blocks
+/// until the next stage catches up, so backpressure propagates back to the source, or drops the
+/// item, as given by the pipeline's [`Overflow`] policy.
+///
+/// Instead of running a sink, the pipeline can also be iterated to receive the items of its last
+/// stage. The pipeline ends when the source is exhausted and every item has reached the sink. If
+/// the sink fails, the pipeline is torn down: each upstream stage stops once it next tries to pass
+/// an item on.
+///
+/// ```
+/// use vrt::{parse_vrt_packet, Pipeline, VrtPacketOwned};
+///
+/// let datagrams = vec![vec![0x10, 0x01, 0x00, 0x03, 0, 0, 0, 2, 0xde, 0xad, 0xbe, 0xef]];
+/// let mut stream_ids = Vec::new();
+/// Pipeline::source(datagrams, 64)
+///     .transform(|bytes| parse_vrt_packet(&bytes).ok().map(|(_, p)| VrtPacketOwned::from(&p)))
+///     .sink(|packet| {
+///         stream_ids.push(packet.stream_id);
+///         Ok::<_, ()>(())
+///     })
+///     .unwrap();
+/// assert_eq!(stream_ids, vec![Some(2)]);
+/// ```
+pub struct Pipeline<T> {
+    receiver: Receiver<T>,
+    capacity: usize,
+    overflow: Overflow,
+    dropped: Arc<AtomicU64>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl<T: Send + 'static> Pipeline<T> {
+    /// Creates a new Pipeline whose source stage yields the items of `source`, with channels
+    /// holding up to `capacity` items between stages
+    pub fn source<I>(source: I, capacity: usize) -> Self
+    where
+        I: IntoIterator<Item = T> + Send + 'static,
+    {
+        Self::source_with_overflow(source, capacity, Overflow::Block)
+    }
+
+    /// Creates a new Pipeline as [`Pipeline::source`] does, using the `overflow` policy for
+    /// every stage
+    pub fn source_with_overflow<I>(source: I, capacity: usize, overflow: Overflow) -> Self
+    where
+        I: IntoIterator<Item = T> + Send + 'static,
+    {
+        let dropped = Arc::new(AtomicU64::new(0));
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        let stage = Stage {
+            sender,
+            overflow,
+            dropped: dropped.clone(),
+        };
+        let thread = thread::spawn(move || {
+            for item in source {
+                if !stage.send(item) {
+                    break;
+                }
+            }
+        });
+        Pipeline {
+            receiver,
+            capacity,
+            overflow,
+            dropped,
+            threads: vec![thread],
+        }
+    }
+
+    /// Adds a transform stage mapping each item with `transform`, which may filter the item out
+    /// by returning None
+    pub fn transform<U, F>(self, mut transform: F) -> Pipeline<U>
+    where
+        U: Send + 'static,
+        F: FnMut(T) -> Option<U> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::sync_channel(self.capacity);
+        let stage = Stage {
+            sender,
+            overflow: self.overflow,
+            dropped: self.dropped.clone(),
+        };
+        let input = self.receiver;
+        let thread = thread::spawn(move || {
+            for item in input {
+                if let Some(item) = transform(item) {
+                    if !stage.send(item) {
+                        break;
+                    }
+                }
+            }
+        });
+        let mut threads = self.threads;
+        threads.push(thread);
+        Pipeline {
+            receiver,
+            capacity: self.capacity,
+            overflow: self.overflow,
+            dropped: self.dropped,
+            threads,
+        }
+    }
+
+    /// Runs the pipeline on the current thread, passing each item to `sink`, until the source
+    /// is exhausted or `sink` returns an error
+    ///
+    /// A panic in one of the stages is propagated once the pipeline has ended.
+    pub fn sink<F, E>(self, mut sink: F) -> Result<(), E>
+    where
+        F: FnMut(T) -> Result<(), E>,
+    {
+        for item in &self.receiver {
+            sink(item)?;
+        }
+        for thread in self.threads {
+            if let Err(panic) = thread.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the number of items dropped so far by stages using [`Overflow::Drop`]
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<T> Iterator for Pipeline<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl<T> fmt::Debug for Pipeline<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("capacity", &self.capacity)
+            .field("overflow", &self.overflow)
+            .field("stages", &self.threads.len())
+            .field("dropped", &self.dropped.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+/// Output of a pipeline stage
+struct Stage<T> {
+    sender: SyncSender<T>,
+    overflow: Overflow,
+    dropped: Arc<AtomicU64>,
+}
+
+impl<T> Stage<T> {
+    /// Passes `item` to the next stage, returning false once the next stage has gone away
+    fn send(&self, item: T) -> bool {
+        match self.overflow {
+            Overflow::Block => self.sender.send(item).is_ok(),
+            Overflow::Drop => match self.sender.try_send(item) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    true
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            },
+        }
+    }
+}
+
+/// Asynchronous Pipeline of Processing Stages
+///
+/// The asynchronous flavour of [`Pipeline`], for use within a Tokio runtime: the source stage
+/// polls a [`Stream`](futures_core::Stream) and each stage runs as a task connected to the next
+/// by a bounded channel, with the same [`Overflow`] policies. The pipeline is itself a stream
+/// of the items of its last stage, or can be drained by an [`AsyncPipeline::sink`].
+#[cfg(feature = "tokio")]
+pub struct AsyncPipeline<T> {
+    receiver: tokio::sync::mpsc::Receiver<T>,
+    capacity: usize,
+    overflow: Overflow,
+    dropped: Arc<AtomicU64>,
+    tasks: Vec<tokio::task::JoinHandle<()>>,
+}
+
+#[cfg(feature = "tokio")]
+impl<T: Send + 'static> AsyncPipeline<T> {
+    /// Creates a new AsyncPipeline whose source stage yields the items of `source`, with
+    /// channels holding up to `capacity` items between stages
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime, or if `capacity` is zero.
+    pub fn source<S>(source: S, capacity: usize) -> Self
+    where
+        S: futures_core::Stream<Item = T> + Send + 'static,
+    {
+        Self::source_with_overflow(source, capacity, Overflow::Block)
+    }
+
+    /// Creates a new AsyncPipeline as [`AsyncPipeline::source`] does, using the `overflow`
+    /// policy for every stage
+    pub fn source_with_overflow<S>(source: S, capacity: usize, overflow: Overflow) -> Self
+    where
+        S: futures_core::Stream<Item = T> + Send + 'static,
+    {
+        let dropped = Arc::new(AtomicU64::new(0));
+        let (sender, receiver) = tokio::sync::mpsc::channel(capacity);
+        let stage = AsyncStage {
+            sender,
+            overflow,
+            dropped: dropped.clone(),
+        };
+        let task = tokio::spawn(async move {
+            let mut source = std::pin::pin!(source);
+            while let Some(item) = std::future::poll_fn(|cx| source.as_mut().poll_next(cx)).await {
+                if !stage.send(item).await {
+                    break;
+                }
+            }
+        });
+        AsyncPipeline {
+            receiver,
+            capacity,
+            overflow,
+            dropped,
+            tasks: vec![task],
+        }
+    }
+
+    /// Adds a transform stage mapping each item with `transform`, which may filter the item out
+    /// by returning None
+    pub fn transform<U, F>(self, mut transform: F) -> AsyncPipeline<U>
+    where
+        U: Send + 'static,
+        F: FnMut(T) -> Option<U> + Send + 'static,
+    {
+        let (sender, receiver) = tokio::sync::mpsc::channel(self.capacity);
+        let stage = AsyncStage {
+            sender,
+            overflow: self.overflow,
+            dropped: self.dropped.clone(),
+        };
+        let mut input = self.receiver;
+        let task = tokio::spawn(async move {
+            while let Some(item) = input.recv().await {
+                if let Some(item) = transform(item) {
+                    if !stage.send(item).await {
+                        break;
+                    }
+                }
+            }
+        });
+        let mut tasks = self.tasks;
+        tasks.push(task);
+        AsyncPipeline {
+            receiver,
+            capacity: self.capacity,
+            overflow: self.overflow,
+            dropped: self.dropped,
+            tasks,
+        }
+    }
+
+    /// Runs the pipeline, passing each item to `sink`, until the source is exhausted or `sink`
+    /// returns an error
+    ///
+    /// A panic in one of the stages is propagated once the pipeline has ended.
+    pub async fn sink<F, E>(mut self, mut sink: F) -> Result<(), E>
+    where
+        F: FnMut(T) -> Result<(), E>,
+    {
+        while let Some(item) = self.receiver.recv().await {
+            sink(item)?;
+        }
+        for task in self.tasks {
+            if let Err(e) = task.await {
+                if let Ok(panic) = e.try_into_panic() {
+                    std::panic::resume_unwind(panic);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the number of items dropped so far by stages using [`Overflow::Drop`]
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T> futures_core::Stream for AsyncPipeline<T> {
+    type Item = T;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<T>> {
+        self.get_mut().receiver.poll_recv(cx)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T> fmt::Debug for AsyncPipeline<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncPipeline")
+            .field("capacity", &self.capacity)
+            .field("overflow", &self.overflow)
+            .field("stages", &self.tasks.len())
+            .field("dropped", &self.dropped.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+/// Output of an asynchronous pipeline stage
+#[cfg(feature = "tokio")]
+struct AsyncStage<T> {
+    sender: tokio::sync::mpsc::Sender<T>,
+    overflow: Overflow,
+    dropped: Arc<AtomicU64>,
+}
+
+#[cfg(feature = "tokio")]
+impl<T> AsyncStage<T> {
+    /// Passes `item` to the next stage, returning false once the next stage has gone away
+    async fn send(&self, item: T) -> bool {
+        use tokio::sync::mpsc::error::TrySendError;
+
+        match self.overflow {
+            Overflow::Block => self.sender.send(item).await.is_ok(),
+            Overflow::Drop => match self.sender.try_send(item) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    true
+                }
+                Err(TrySendError::Closed(_)) => false,
+            },
+        }
+    }
+}
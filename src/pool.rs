@@ -0,0 +1,152 @@
+//! Pool of reusable, aligned receive buffers.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Default alignment of the buffers of a [`BufferPool`] used by receivers, a typical cache line
+pub const DEFAULT_BUFFER_ALIGNMENT: usize = 64;
+
+/// Pool of Receive Buffers
+///
+/// Hands out leases on fixed-size buffers whose start is aligned to a power of two, such as a
+/// cache line. A [`PooledBuffer`] returns its buffer to the pool when dropped, so packets parsed
+/// from it can be kept, and even sent to other threads, for as long as the lease is held,
+/// without copying them out of the buffer or allocating per packet. Buffers are only allocated
+/// when the pool has no idle buffer to hand out.
+///
+/// The pool can be cloned cheaply; clones share the same buffers.
+#[derive(Clone)]
+pub struct BufferPool {
+    inner: Arc<PoolInner>,
+}
+
+struct PoolInner {
+    buffer_len: usize,
+    alignment: usize,
+    // recovered when poisoned, as a panic cannot leave the list of idle buffers invalid
+    idle: Mutex<Vec<Vec<u8>>>,
+}
+
+impl BufferPool {
+    /// Creates a new BufferPool of buffers holding `buffer_len` bytes starting at an address
+    /// aligned to `alignment` bytes
+    ///
+    /// # Panics
+    ///
+    /// Panics if `alignment` is not a power of two.
+    pub fn new(buffer_len: usize, alignment: usize) -> Self {
+        assert!(
+            alignment.is_power_of_two(),
+            "alignment must be a power of two"
+        );
+        BufferPool {
+            inner: Arc::new(PoolInner {
+                buffer_len,
+                alignment,
+                idle: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Returns the length of the buffers
+    pub fn buffer_len(&self) -> usize {
+        self.inner.buffer_len
+    }
+
+    /// Returns the alignment of the buffers
+    pub fn alignment(&self) -> usize {
+        self.inner.alignment
+    }
+
+    /// Returns the number of idle buffers held by the pool
+    pub fn idle(&self) -> usize {
+        self.inner
+            .idle
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Leases a buffer, reusing an idle one if possible
+    ///
+    /// The contents of a reused buffer are those left by its previous lease.
+    pub fn lease(&self) -> PooledBuffer {
+        let buf = self
+            .inner
+            .idle
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop();
+        let buf =
+            buf.unwrap_or_else(|| vec![0u8; self.inner.buffer_len + self.inner.alignment - 1]);
+        let offset = buf.as_ptr().align_offset(self.inner.alignment);
+        PooledBuffer {
+            buf,
+            offset,
+            pool: self.inner.clone(),
+        }
+    }
+
+    /// Drops idle buffers until at most `idle` remain, releasing their memory
+    pub fn shrink_to(&self, idle: usize) {
+        self.inner
+            .idle
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .truncate(idle);
+    }
+}
+
+impl fmt::Debug for BufferPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufferPool")
+            .field("buffer_len", &self.buffer_len())
+            .field("alignment", &self.alignment())
+            .field("idle", &self.idle())
+            .finish()
+    }
+}
+
+/// Lease on a buffer of a [`BufferPool`], returning the buffer to the pool when dropped
+///
+/// Dereferences to the aligned bytes of the buffer.
+pub struct PooledBuffer {
+    buf: Vec<u8>,
+    offset: usize,
+    pool: Arc<PoolInner>,
+}
+
+impl Deref for PooledBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.buf[self.offset..self.offset + self.pool.buffer_len]
+    }
+}
+
+impl DerefMut for PooledBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.buf[self.offset..self.offset + self.pool.buffer_len]
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        let buf = std::mem::take(&mut self.buf);
+        self.pool
+            .idle
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(buf);
+    }
+}
+
+impl fmt::Debug for PooledBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledBuffer")
+            .field("len", &self.pool.buffer_len)
+            .field("alignment", &self.pool.alignment)
+            .finish()
+    }
+}
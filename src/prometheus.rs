@@ -0,0 +1,198 @@
+//! Export of stream statistics in the Prometheus text format.
+
+use crate::metrics::{Metrics, MetricsSnapshot};
+use crate::stats::{StreamStats, StreamTracker};
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+/// Name, type, help text and value of the time series of a metric
+type Series<T> = (
+    &'static str,
+    &'static str,
+    &'static str,
+    fn(&T) -> Option<f64>,
+);
+
+/// Prometheus Exporter
+///
+/// Renders the statistics collected by a [`Metrics`] collector and the continuity counters of
+/// a [`StreamTracker`] in the Prometheus text exposition format, with one time series per
+/// stream labelled by `stream_id` (`none` for packets without a Stream Identifier):
+///
+/// | Metric | Type | Source |
+/// |--------|------|--------|
+/// | `vrt_packets_total` | counter | [`MetricsSnapshot::packets`] |
+/// | `vrt_bytes_total` | counter | [`MetricsSnapshot::bytes`] |
+/// | `vrt_packets_per_second` | gauge | [`MetricsSnapshot::packets_per_sec`] |
+/// | `vrt_bytes_per_second` | gauge | [`MetricsSnapshot::bytes_per_sec`] |
+/// | `vrt_jitter_seconds` | gauge | [`MetricsSnapshot::jitter`] |
+/// | `vrt_timestamp_skew_seconds` | gauge | [`MetricsSnapshot::skew`] |
+/// | `vrt_packet_type_packets_total` | counter | [`MetricsSnapshot::packet_type_counts`] |
+/// | `vrt_lost_packets_total` | counter | [`StreamStats::lost`] |
+/// | `vrt_duplicated_packets_total` | counter | [`StreamStats::duplicated`] |
+/// | `vrt_reordered_packets_total` | counter | [`StreamStats::reordered`] |
+/// | `vrt_loss_ratio` | gauge | [`StreamStats::loss_rate`] |
+///
+/// The packet type counts are also labelled by `packet_type`. The `vrt` prefix can be changed with
+/// [`PrometheusExporter::with_namespace`]. The output can be served to scrapers with
+/// [`PrometheusExporter::respond`] or by any HTTP server.
+#[derive(Clone, Debug)]
+pub struct PrometheusExporter {
+    namespace: String,
+}
+
+impl PrometheusExporter {
+    /// Creates a new PrometheusExporter using the `vrt` namespace
+    pub fn new() -> Self {
+        PrometheusExporter {
+            namespace: "vrt".into(),
+        }
+    }
+
+    /// Sets the namespace prefixed to the metric names
+    pub fn with_namespace(mut self, namespace: &str) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
+    /// Renders the statistics of `metrics` and `tracker`, either of which may be omitted
+    pub fn render(&self, metrics: Option<&Metrics>, tracker: Option<&StreamTracker>) -> String {
+        let mut out = String::new();
+        if let Some(metrics) = metrics {
+            let snapshots = metrics.snapshots();
+            let series: [Series<MetricsSnapshot>; 6] = [
+                ("packets_total", "counter", "Packets received", |s| {
+                    Some(s.packets as f64)
+                }),
+                ("bytes_total", "counter", "Bytes received", |s| {
+                    Some(s.bytes as f64)
+                }),
+                ("packets_per_second", "gauge", "Average packet rate", |s| {
+                    Some(s.packets_per_sec)
+                }),
+                ("bytes_per_second", "gauge", "Average data rate", |s| {
+                    Some(s.bytes_per_sec)
+                }),
+                (
+                    "jitter_seconds",
+                    "gauge",
+                    "Inter-packet arrival jitter",
+                    |s| Some(s.jitter),
+                ),
+                (
+                    "timestamp_skew_seconds",
+                    "gauge",
+                    "Arrival time minus packet timestamp",
+                    |s| s.skew,
+                ),
+            ];
+            for (name, kind, help, value) in series {
+                self.header(&mut out, name, kind, help);
+                for snapshot in &snapshots {
+                    if let Some(value) = value(snapshot) {
+                        self.sample(&mut out, name, snapshot.stream_id, "", value);
+                    }
+                }
+            }
+            let name = "packet_type_packets_total";
+            self.header(&mut out, name, "counter", "Packets received by packet type");
+            for snapshot in &snapshots {
+                for (packet_type, count) in snapshot.packet_type_counts.iter().enumerate() {
+                    if *count > 0 {
+                        let label = format!(",packet_type=\"{}\"", packet_type);
+                        self.sample(&mut out, name, snapshot.stream_id, &label, *count as f64);
+                    }
+                }
+            }
+        }
+        if let Some(tracker) = tracker {
+            let mut stats: Vec<_> = tracker.iter().collect();
+            stats.sort_by_key(|(id, _)| *id);
+            let series: [Series<StreamStats>; 4] = [
+                ("lost_packets_total", "counter", "Packets lost", |s| {
+                    Some(s.lost as f64)
+                }),
+                (
+                    "duplicated_packets_total",
+                    "counter",
+                    "Packets duplicated",
+                    |s| Some(s.duplicated as f64),
+                ),
+                (
+                    "reordered_packets_total",
+                    "counter",
+                    "Packets reordered",
+                    |s| Some(s.reordered as f64),
+                ),
+                (
+                    "loss_ratio",
+                    "gauge",
+                    "Fraction of expected packets lost",
+                    |s| Some(s.loss_rate()),
+                ),
+            ];
+            for (name, kind, help, value) in series {
+                self.header(&mut out, name, kind, help);
+                for (stream_id, stats) in &stats {
+                    if let Some(value) = value(stats) {
+                        self.sample(&mut out, name, *stream_id, "", value);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Answers the HTTP request read from `stream` with the rendered statistics
+    ///
+    /// The request itself is not interpreted, so any path can be used as the scrape target.
+    pub fn respond<S: Read + Write>(
+        &self,
+        stream: S,
+        metrics: Option<&Metrics>,
+        tracker: Option<&StreamTracker>,
+    ) -> io::Result<()> {
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 0 && line != "\r\n" && line != "\n" {
+            line.clear();
+        }
+        let body = self.render(metrics, tracker);
+        let mut stream = reader.into_inner();
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )?;
+        stream.flush()
+    }
+
+    fn header(&self, out: &mut String, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {}_{} {}", self.namespace, name, help);
+        let _ = writeln!(out, "# TYPE {}_{} {}", self.namespace, name, kind);
+    }
+
+    fn sample(
+        &self,
+        out: &mut String,
+        name: &str,
+        stream_id: Option<u32>,
+        labels: &str,
+        value: f64,
+    ) {
+        let stream_id = stream_id.map_or_else(|| "none".into(), |id| id.to_string());
+        let _ = writeln!(
+            out,
+            "{}_{}{{stream_id=\"{}\"{}}} {}",
+            self.namespace, name, stream_id, labels, value
+        );
+    }
+}
+
+impl Default for PrometheusExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,150 @@
+//! Publishing and subscribing to VRT packets over ZeroMQ.
+
+use crate::encoder::encode_vrt_packet;
+use crate::parser::parse_vrt_packet;
+use crate::vrt::{VrtPacket, VrtPacketOwned};
+use std::fmt;
+use std::io;
+
+/// Returns the topic under which packets of the stream `stream_id` are published: the Stream
+/// Identifier as 4 big-endian bytes
+///
+/// Packets without a Stream Identifier are published under the empty topic, and only reach
+/// subscribers to all streams.
+pub fn zmq_topic(stream_id: Option<u32>) -> Vec<u8> {
+    stream_id.map_or_else(Vec::new, |id| id.to_be_bytes().to_vec())
+}
+
+/// ZeroMQ Publisher
+///
+/// Publishes each VRT packet on a ZeroMQ PUB socket as a two-part message: the topic given by
+/// [`zmq_topic`] for the packet's Stream Identifier, followed by the encoded packet. Since the
+/// topics of all streams have the same length, a [`ZmqSubscriber`] can subscribe to individual
+/// streams with ZeroMQ's prefix matching.
+pub struct ZmqPublisher {
+    socket: zmq::Socket,
+    buf: Vec<u8>,
+}
+
+impl ZmqPublisher {
+    /// Creates a new ZmqPublisher with a PUB socket of `context` bound to `endpoint`
+    pub fn bind(context: &zmq::Context, endpoint: &str) -> io::Result<Self> {
+        let socket = context.socket(zmq::PUB)?;
+        socket.bind(endpoint)?;
+        Ok(Self::from_socket(socket))
+    }
+
+    /// Creates a new ZmqPublisher publishing on the PUB or XPUB `socket`
+    pub fn from_socket(socket: zmq::Socket) -> Self {
+        ZmqPublisher {
+            socket,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Returns the underlying socket
+    pub fn socket(&self) -> &zmq::Socket {
+        &self.socket
+    }
+
+    /// Encodes and publishes `packet`
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidInput`], without publishing anything,
+    /// if the packet cannot be encoded.
+    pub fn send(&mut self, packet: &VrtPacket<'_>) -> io::Result<()> {
+        self.buf.resize(packet.encoded_len(), 0);
+        encode_vrt_packet(packet, &mut self.buf)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid VRT packet"))?;
+        let topic = zmq_topic(packet.stream_id);
+        self.socket.send_multipart([&topic[..], &self.buf[..]], 0)?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ZmqPublisher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ZmqPublisher").finish_non_exhaustive()
+    }
+}
+
+/// ZeroMQ Subscriber
+///
+/// Receives the VRT packets published by a [`ZmqPublisher`] on a ZeroMQ SUB socket, for all
+/// streams or for the streams subscribed to with [`ZmqSubscriber::subscribe`]. Messages which
+/// do not hold a single valid packet are dropped and counted by [`ZmqSubscriber::malformed`].
+pub struct ZmqSubscriber {
+    socket: zmq::Socket,
+    malformed: u64,
+}
+
+impl ZmqSubscriber {
+    /// Creates a new ZmqSubscriber with a SUB socket of `context` connected to `endpoint`,
+    /// without any subscription
+    pub fn connect(context: &zmq::Context, endpoint: &str) -> io::Result<Self> {
+        let socket = context.socket(zmq::SUB)?;
+        socket.connect(endpoint)?;
+        Ok(Self::from_socket(socket))
+    }
+
+    /// Creates a new ZmqSubscriber receiving from the SUB or XSUB `socket`
+    pub fn from_socket(socket: zmq::Socket) -> Self {
+        ZmqSubscriber {
+            socket,
+            malformed: 0,
+        }
+    }
+
+    /// Returns the underlying socket, for example to set a receive timeout
+    pub fn socket(&self) -> &zmq::Socket {
+        &self.socket
+    }
+
+    /// Returns the number of malformed messages dropped
+    pub fn malformed(&self) -> u64 {
+        self.malformed
+    }
+
+    /// Subscribes to the packets of the stream `stream_id`
+    pub fn subscribe(&self, stream_id: u32) -> io::Result<()> {
+        Ok(self.socket.set_subscribe(&zmq_topic(Some(stream_id)))?)
+    }
+
+    /// Unsubscribes from the packets of the stream `stream_id`
+    pub fn unsubscribe(&self, stream_id: u32) -> io::Result<()> {
+        Ok(self.socket.set_unsubscribe(&zmq_topic(Some(stream_id)))?)
+    }
+
+    /// Subscribes to the packets of all streams, including packets without a Stream Identifier
+    pub fn subscribe_all(&self) -> io::Result<()> {
+        Ok(self.socket.set_subscribe(b"")?)
+    }
+
+    /// Receives the next packet, blocking until a valid one arrives
+    pub fn recv_packet(&mut self) -> io::Result<VrtPacketOwned> {
+        loop {
+            let message = self.socket.recv_multipart(0)?;
+            if let [_, bytes] = &message[..] {
+                if let Ok(([], packet)) = parse_vrt_packet(bytes) {
+                    return Ok(VrtPacketOwned::from(&packet));
+                }
+            }
+            self.malformed += 1;
+        }
+    }
+}
+
+impl Iterator for ZmqSubscriber {
+    type Item = io::Result<VrtPacketOwned>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.recv_packet())
+    }
+}
+
+impl fmt::Debug for ZmqSubscriber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ZmqSubscriber")
+            .field("malformed", &self.malformed)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,155 @@
+//! Replay of recorded packets with their original timing.
+
+use crate::context::SignalContext;
+use crate::vrt::{Timestamp, Tsf, VitaPacketType, VrtPacketOwned};
+use std::collections::HashMap;
+use std::fmt;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Returns the time of the timestamp `ts` since its epoch, converting Sample Count timestamps
+/// with `sample_rate`
+fn time(ts: &Timestamp, sample_rate: Option<f64>) -> Option<Duration> {
+    match (ts.tsf, sample_rate) {
+        (Tsf::TSF_SAMPLE_COUNT, Some(rate)) if rate > 0.0 => {
+            let fractional = Duration::try_from_secs_f64(ts.fractional as f64 / rate).ok()?;
+            Duration::from_secs(ts.integer as u64).checked_add(fractional)
+        }
+        _ => ts.as_duration(),
+    }
+}
+
+/// Signal contexts of the context streams, keyed by Stream Identifier
+type Signals = HashMap<Option<u32>, SignalContext>;
+
+/// Returns the time of the packet `p`, after recording the fields of an IF context packet in
+/// the signal context of its stream
+fn packet_time(p: &VrtPacketOwned, signals: &mut Signals) -> Option<Duration> {
+    let packet = p.as_packet();
+    if packet.header.packet_type.0 == VitaPacketType::IFCONTEXT {
+        signals.entry(packet.stream_id).or_default().update(&packet);
+    }
+    let sample_rate = signals
+        .get(&packet.stream_id)
+        .and_then(|signal| signal.sample_rate);
+    time(&packet.timestamp()?, sample_rate)
+}
+
+/// Source of the time of each replayed item
+enum Key<T> {
+    Fn(fn(&T) -> Option<Duration>),
+    Context(fn(&T, &mut Signals) -> Option<Duration>, Signals),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Replay Speed
+pub enum ReplaySpeed {
+    /// Replay with the original timing scaled by the factor: 2.0 replays twice as fast and 0.5
+    /// half as fast.
+    Scaled(f64),
+    /// Replay without any delay between packets.
+    AsFastAsPossible,
+}
+
+/// Iterator replaying packets with their original inter-packet timing
+///
+/// Each packet is returned once the time elapsed since the first packet, divided by the speed
+/// factor, matches the difference between the packet's time and the time of the first packet.
+/// Packet times are derived from the packet timestamps by default, as described for
+/// [`Replayer::new`], or by a key function such as one returning recorded arrival times.
+/// Packets without a time, or with a time before the previous packet's, are returned without
+/// delay.
+pub struct Replayer<I: Iterator> {
+    packets: I,
+    speed: ReplaySpeed,
+    key: Key<I::Item>,
+    start: Option<(Instant, Duration)>,
+    last: Option<Duration>,
+}
+
+impl<I> Replayer<I>
+where
+    I: Iterator<Item = VrtPacketOwned>,
+{
+    /// Creates a new Replayer timing `packets` by their timestamps
+    ///
+    /// Real Time (Picoseconds) timestamps, and integer-seconds timestamps alone, are converted
+    /// with [`Timestamp::as_duration`]. Sample Count timestamps are converted with the Sample
+    /// Rate of the most recent IF context packet with the same Stream Identifier, so packets
+    /// preceding the first Sample Rate of their stream are returned without delay. Free Running
+    /// timestamps count at a rate unrelated to the timestamp epoch, so those packets are always
+    /// returned without delay.
+    pub fn new(packets: I, speed: ReplaySpeed) -> Self {
+        let key = Key::Context(packet_time, Signals::new());
+        Self::with_timing(packets, speed, key)
+    }
+}
+
+impl<I: Iterator> Replayer<I> {
+    /// Creates a new Replayer for items of any type, where `key` returns the time of an item
+    pub fn with_key(packets: I, speed: ReplaySpeed, key: fn(&I::Item) -> Option<Duration>) -> Self {
+        Self::with_timing(packets, speed, Key::Fn(key))
+    }
+
+    fn with_timing(packets: I, speed: ReplaySpeed, key: Key<I::Item>) -> Self {
+        Replayer {
+            packets,
+            speed,
+            key,
+            start: None,
+            last: None,
+        }
+    }
+
+    /// Returns the instant at which a packet at `time` is due, or None if it is due immediately
+    fn due(&self, time: Duration) -> Option<Instant> {
+        let factor = match self.speed {
+            ReplaySpeed::Scaled(factor) if factor > 0.0 => factor,
+            _ => return None,
+        };
+        let (start, first) = self.start?;
+        let offset = time.checked_sub(first)?;
+        Some(start + offset.div_f64(factor))
+    }
+}
+
+impl<I: Iterator> Iterator for Replayer<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.packets.next()?;
+        let time = match &mut self.key {
+            Key::Fn(key) => key(&item),
+            Key::Context(key, signals) => key(&item, signals),
+        };
+        if let Some(time) = time {
+            if self.start.is_none() {
+                self.start = Some((Instant::now(), time));
+            }
+            let in_order = match self.last {
+                Some(last) => time >= last,
+                None => true,
+            };
+            if in_order {
+                if let Some(due) = self.due(time) {
+                    let now = Instant::now();
+                    if due > now {
+                        thread::sleep(due - now);
+                    }
+                }
+                self.last = Some(time);
+            }
+        }
+        Some(item)
+    }
+}
+
+impl<I: Iterator> fmt::Debug for Replayer<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Replayer")
+            .field("speed", &self.speed)
+            .field("start", &self.start)
+            .field("last", &self.last)
+            .finish()
+    }
+}
@@ -0,0 +1,86 @@
+//! Conversion between SDR driver receive metadata and VRT data packets.
+
+use crate::vrt::{Header, Trailer, Tsf, Tsi, VitaPacketType, VrtPacket};
+use core::time::Duration;
+
+/// Receive metadata of one buffer of samples from an SDR driver
+///
+/// Holds the fields which UHD's `rx_metadata_t` and SoapySDR's `readStream` report for each
+/// buffer: the time of the first sample (UHD's `time_spec`, SoapySDR's `timeNs` with
+/// `SOAPY_SDR_HAS_TIME`), whether samples were dropped before the buffer (UHD's
+/// `ERROR_CODE_OVERFLOW`, SoapySDR's `SOAPY_SDR_OVERFLOW`) and the channel it was read from.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct RxMetadata {
+    /// Time of the first sample since the Unix epoch, if the driver reported one
+    pub time: Option<Duration>,
+    /// Whether samples were dropped before this buffer
+    pub overflow: bool,
+    /// Channel the buffer was read from
+    pub channel: u32,
+}
+
+impl RxMetadata {
+    /// Builds the IF data packet carrying `samples`, the raw sample buffer read with this
+    /// metadata, as the `packet_count`th packet of its stream
+    ///
+    /// The Stream Identifier is `stream_id_base` plus the channel. The time, if any, becomes a
+    /// UTC and Real Time (Picoseconds) timestamp, and the trailer reports the overflow as
+    /// sample loss. `samples` must fit in one packet; split larger buffers with a
+    /// `Fragmenter`.
+    pub fn to_packet<'a>(
+        &self,
+        stream_id_base: u32,
+        packet_count: u8,
+        samples: &'a [u8],
+    ) -> VrtPacket<'a> {
+        let (tsi, tsf) = match self.time {
+            Some(time) => (
+                Some(time.as_secs() as u32),
+                Some(time.subsec_nanos() as u64 * 1000),
+            ),
+            None => (None, None),
+        };
+        let mut packet = VrtPacket {
+            header: Header {
+                packet_type: VitaPacketType(VitaPacketType::IFDATAWITHSTREAM),
+                c: false,
+                t: true,
+                indicators: 0,
+                tsi: tsi.map_or(Tsi::TSI_NONE, |_| Tsi::TSI_UTC),
+                tsf: tsf.map_or(Tsf::TSF_NONE, |_| Tsf::TSF_REAL_TIME),
+                packet_count: packet_count & 0x0f,
+                packet_size: 0,
+            },
+            stream_id: Some(stream_id_base.wrapping_add(self.channel)),
+            class_id: None,
+            tsi,
+            tsf,
+            data_payload: samples,
+            trailer: Some(Trailer {
+                sample_loss_enable: true,
+                sample_loss_indicator: self.overflow,
+                ..Trailer::default()
+            }),
+        };
+        packet.header.packet_size = (packet.encoded_len() / 4).min(u16::MAX as usize) as u16;
+        packet
+    }
+
+    /// Recovers the receive metadata of a data packet built by [`RxMetadata::to_packet`] or
+    /// another source using the same conventions
+    ///
+    /// The channel is the Stream Identifier minus `stream_id_base`. The time is only recovered
+    /// from timestamps which [`Timestamp::as_duration`](crate::Timestamp::as_duration) can
+    /// convert, and the overflow flag from an enabled sample loss indicator.
+    pub fn from_packet(packet: &VrtPacket<'_>, stream_id_base: u32) -> Self {
+        RxMetadata {
+            time: packet.timestamp().and_then(|t| t.as_duration()),
+            overflow: packet
+                .trailer
+                .is_some_and(|t| t.sample_loss_enable && t.sample_loss_indicator),
+            channel: packet
+                .stream_id
+                .map_or(0, |id| id.wrapping_sub(stream_id_base)),
+        }
+    }
+}
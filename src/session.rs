@@ -0,0 +1,272 @@
+//! Pairing of data packets with the context packets describing them.
+
+use crate::context::SignalContext;
+use crate::stats::{Continuity, ContinuityChecker};
+use crate::vrt::{Timestamp, Tsf, VrtPacket, VrtPacketOwned};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A data packet annotated with the most recent context packet of its paired context stream
+#[derive(Debug)]
+pub struct SessionPacket<'a, 's> {
+    /// The data packet
+    pub packet: VrtPacket<'a>,
+    /// The most recent context packet of the paired context stream, if one has been received
+    pub context: Option<&'s VrtPacketOwned>,
+    /// The frequency, sample rate, gain and other fields accumulated from the paired context
+    /// stream, if a context packet has been received
+    pub signal: Option<&'s SignalContext>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Event raised by a [`VrtSession`] while processing packets
+pub enum SessionEvent<'a> {
+    /// The first packet with this Stream Identifier was received.
+    NewStream {
+        /// Stream Identifier, or None for packets without one
+        stream_id: Option<u32>,
+    },
+    /// A context packet was received whose Class Identifier or context fields differ from the
+    /// previous context packet of the same stream, or which is the first of its stream.
+    ContextChanged {
+        /// Stream Identifier of the context stream
+        stream_id: u32,
+        /// The new context packet
+        context: &'a VrtPacketOwned,
+    },
+    /// One or more packets of a stream were lost, as detected from the packet count.
+    PacketLoss {
+        /// Stream Identifier, or None for packets without one
+        stream_id: Option<u32>,
+        /// Whether the lost packets belong to the context stream
+        context: bool,
+        /// Number of lost packets
+        lost: u64,
+    },
+    /// The timestamp of a packet went backwards or changed type relative to the previous
+    /// packet of the stream, or, for data streams whose sample size is set with
+    /// [`VrtSession::with_bytes_per_sample`] and whose sample rate is known from context,
+    /// differs from the previous timestamp advanced by the samples of the previous packet by
+    /// more than half a sample period.
+    TimestampDiscontinuity {
+        /// Stream Identifier, or None for packets without one
+        stream_id: Option<u32>,
+        /// Timestamp of the previous packet
+        previous: Timestamp,
+        /// Timestamp of the packet
+        current: Timestamp,
+    },
+}
+
+type Handler<'h> = Box<dyn FnMut(&SessionEvent<'_>) + 'h>;
+
+#[derive(Clone, Debug, Default)]
+struct StreamState {
+    checker: ContinuityChecker,
+    last_timestamp: Option<Timestamp>,
+    last_samples: Option<u64>,
+}
+
+fn emit(handlers: &mut [Handler<'_>], event: SessionEvent<'_>) {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(?event, "VRT session event");
+    for handler in handlers.iter_mut() {
+        handler(&event);
+    }
+}
+
+/// Data and Context Packet Session
+///
+/// Consumes a mixed stream of data and context packets. Context packets are retained, keyed by
+/// their Stream Identifier, and each data packet is returned together with the most recent
+/// context packet of its paired context stream. By default a data stream is paired with the
+/// context stream carrying the same Stream Identifier; use [`VrtSession::with_association`]
+/// when the context stream identifiers are derived differently.
+///
+/// The raw context packet is attached to the data packet, together with the
+/// [`SignalContext`] decoded from the context stream. Context packets need only carry the
+/// fields which changed, so the signal context keeps the most recent value of each field.
+///
+/// Handlers registered with [`VrtSession::on_event`] are called with a [`SessionEvent`] when a
+/// new stream is seen, a context changes, packet loss is detected or a timestamp is
+/// discontinuous. Data and context packets sharing a Stream Identifier are tracked separately
+/// for loss and timestamp discontinuities.
+pub struct VrtSession<'h> {
+    contexts: HashMap<u32, VrtPacketOwned>,
+    signals: HashMap<u32, SignalContext>,
+    streams: HashMap<(Option<u32>, bool), StreamState>,
+    association: fn(u32) -> u32,
+    bytes_per_sample: Option<usize>,
+    handlers: Vec<Handler<'h>>,
+}
+
+impl<'h> VrtSession<'h> {
+    /// Creates a new VrtSession pairing data and context streams with the same Stream Identifier
+    pub fn new() -> Self {
+        Self::with_association(|stream_id| stream_id)
+    }
+
+    /// Creates a new VrtSession where `association` maps a data Stream Identifier to the
+    /// Stream Identifier of its paired context stream
+    pub fn with_association(association: fn(u32) -> u32) -> Self {
+        VrtSession {
+            contexts: HashMap::new(),
+            signals: HashMap::new(),
+            streams: HashMap::new(),
+            association,
+            bytes_per_sample: None,
+            handlers: Vec::new(),
+        }
+    }
+
+    /// Sets the size of a sample in the data packet payloads, so that timestamp
+    /// discontinuities can be detected from the number of samples in each packet
+    pub fn with_bytes_per_sample(mut self, bytes_per_sample: usize) -> Self {
+        self.bytes_per_sample = Some(bytes_per_sample).filter(|&n| n > 0);
+        self
+    }
+
+    /// Registers `handler` to be called for every [`SessionEvent`]
+    pub fn on_event<F>(&mut self, handler: F)
+    where
+        F: FnMut(&SessionEvent<'_>) + 'h,
+    {
+        self.handlers.push(Box::new(handler));
+    }
+
+    /// Processes `packet`. Context packets are retained and return None; any other packet is
+    /// returned annotated with its paired context packet.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "vrt_session_process",
+            level = "trace",
+            skip_all,
+            fields(
+                stream_id = packet.stream_id,
+                packet_count = packet.header.packet_count
+            )
+        )
+    )]
+    pub fn process<'a>(&mut self, packet: VrtPacket<'a>) -> Option<SessionPacket<'a, '_>> {
+        let is_context = packet.header.packet_type.is_context();
+        self.track(&packet, is_context);
+
+        if is_context {
+            if let Some(stream_id) = packet.stream_id {
+                self.signals.entry(stream_id).or_default().update(&packet);
+                let context = VrtPacketOwned::from(&packet);
+                let unchanged = self.contexts.get(&stream_id).is_some_and(|previous| {
+                    previous.class_id == context.class_id
+                        && previous.data_payload == context.data_payload
+                });
+                self.contexts.insert(stream_id, context);
+                if !unchanged {
+                    let context = &self.contexts[&stream_id];
+                    emit(
+                        &mut self.handlers,
+                        SessionEvent::ContextChanged { stream_id, context },
+                    );
+                }
+            }
+            return None;
+        }
+        let context_id = packet.stream_id.map(self.association);
+        let context = context_id.and_then(|stream_id| self.contexts.get(&stream_id));
+        let signal = context_id.and_then(|stream_id| self.signals.get(&stream_id));
+        Some(SessionPacket {
+            packet,
+            context,
+            signal,
+        })
+    }
+
+    fn track(&mut self, packet: &VrtPacket<'_>, is_context: bool) {
+        let stream_id = packet.stream_id;
+        let is_new = !self.streams.contains_key(&(stream_id, false))
+            && !self.streams.contains_key(&(stream_id, true));
+        if is_new {
+            emit(&mut self.handlers, SessionEvent::NewStream { stream_id });
+        }
+
+        let state = self.streams.entry((stream_id, is_context)).or_default();
+        if let Continuity::Lost(lost) = state.checker.update(packet) {
+            let event = SessionEvent::PacketLoss {
+                stream_id,
+                context: is_context,
+                lost,
+            };
+            emit(&mut self.handlers, event);
+        }
+
+        let current = packet.timestamp();
+        if let (Some(previous), Some(current)) = (state.last_timestamp, current) {
+            let sample_rate = packet
+                .stream_id
+                .filter(|_| !is_context)
+                .and_then(|stream_id| self.signals.get(&(self.association)(stream_id)))
+                .and_then(|signal| signal.sample_rate);
+            let discontinuous = match (state.last_samples, sample_rate) {
+                _ if (current.tsi, current.tsf) != (previous.tsi, previous.tsf) => true,
+                (Some(samples), Some(rate)) if rate > 0.0 => {
+                    match current.seconds_since(&previous, Some(rate)) {
+                        Some(elapsed) => {
+                            // integer-seconds timestamps alone are only precise to a second
+                            let precision = match current.tsf {
+                                Tsf::TSF_NONE => 1.0,
+                                _ => 0.0,
+                            };
+                            let expected = samples as f64 / rate;
+                            (elapsed - expected).abs() > precision + 0.5 / rate
+                        }
+                        None => current < previous,
+                    }
+                }
+                _ => current < previous,
+            };
+            if discontinuous {
+                let event = SessionEvent::TimestampDiscontinuity {
+                    stream_id,
+                    previous,
+                    current,
+                };
+                emit(&mut self.handlers, event);
+            }
+        }
+        if current.is_some() {
+            state.last_timestamp = current;
+            state.last_samples = self
+                .bytes_per_sample
+                .filter(|_| !is_context)
+                .map(|n| (packet.data_payload.len() / n) as u64);
+        }
+    }
+
+    /// Returns the most recent context packet with the Stream Identifier `stream_id`
+    pub fn context(&self, stream_id: u32) -> Option<&VrtPacketOwned> {
+        self.contexts.get(&stream_id)
+    }
+
+    /// Returns the signal context accumulated from the context stream with the Stream
+    /// Identifier `stream_id`
+    pub fn signal(&self, stream_id: u32) -> Option<&SignalContext> {
+        self.signals.get(&stream_id)
+    }
+}
+
+impl Default for VrtSession<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for VrtSession<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VrtSession")
+            .field("contexts", &self.contexts)
+            .field("signals", &self.signals)
+            .field("streams", &self.streams)
+            .field("handlers", &self.handlers.len())
+            .finish()
+    }
+}
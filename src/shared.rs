@@ -0,0 +1,92 @@
+//! VRT packets sharing their payload with the receive buffer.
+
+use crate::error::Error;
+use crate::parser::parse_vrt_packet;
+use crate::vrt::{Header, Trailer, VrtPacket, VrtPacketOwned};
+use bytes::Bytes;
+
+#[derive(Clone, Debug, PartialEq)]
+/// A VRT Packet whose data payload is a shared slice of a [`Bytes`] buffer
+///
+/// Parsing a packet with [`parse_vrt_packet_bytes`] does not copy its payload: the payload is a
+/// reference-counted slice of the buffer the packet was received into. Packets can therefore be
+/// cloned cheaply and sent to other threads, and the buffer is released once the last packet
+/// referring to it is dropped.
+pub struct VrtPacketBytes {
+    /// VRT Packet Header
+    pub header: Header,
+    /// Optional Stream Id
+    pub stream_id: Option<u32>,
+    /// Optional Class Id
+    pub class_id: Option<u64>,
+    /// Optional Integer-Seconds Timestamp
+    pub tsi: Option<u32>,
+    /// Optional Fractional-Seconds Timestamp
+    pub tsf: Option<u64>,
+    /// Data Payload
+    pub data_payload: Bytes,
+    /// Optional VRT Packet Trailer
+    pub trailer: Option<Trailer>,
+}
+
+impl VrtPacketBytes {
+    /// Returns a VrtPacket borrowing the data payload of this packet
+    pub fn as_packet(&self) -> VrtPacket<'_> {
+        VrtPacket {
+            header: self.header,
+            stream_id: self.stream_id,
+            class_id: self.class_id,
+            tsi: self.tsi,
+            tsf: self.tsf,
+            data_payload: &self.data_payload,
+            trailer: self.trailer,
+        }
+    }
+}
+
+impl From<&VrtPacket<'_>> for VrtPacketBytes {
+    /// Copies the data payload of `packet` into a new buffer
+    fn from(packet: &VrtPacket<'_>) -> Self {
+        VrtPacketBytes {
+            header: packet.header,
+            stream_id: packet.stream_id,
+            class_id: packet.class_id,
+            tsi: packet.tsi,
+            tsf: packet.tsf,
+            data_payload: Bytes::copy_from_slice(packet.data_payload),
+            trailer: packet.trailer,
+        }
+    }
+}
+
+impl From<VrtPacketBytes> for VrtPacketOwned {
+    fn from(packet: VrtPacketBytes) -> Self {
+        VrtPacketOwned {
+            header: packet.header,
+            stream_id: packet.stream_id,
+            class_id: packet.class_id,
+            tsi: packet.tsi,
+            tsf: packet.tsf,
+            data_payload: packet.data_payload.into(),
+            trailer: packet.trailer,
+        }
+    }
+}
+
+/// Parses the complete VRT packet at the start of `input` without copying its payload
+///
+/// Returns the remaining input, sharing `input`'s buffer, and the packet, as described for
+/// [`parse_vrt_packet`].
+pub fn parse_vrt_packet_bytes(input: &Bytes) -> Result<(Bytes, VrtPacketBytes), Error> {
+    let (rem, packet) = parse_vrt_packet(input)?;
+    let packet_bytes = VrtPacketBytes {
+        header: packet.header,
+        stream_id: packet.stream_id,
+        class_id: packet.class_id,
+        tsi: packet.tsi,
+        tsf: packet.tsf,
+        data_payload: input.slice_ref(packet.data_payload),
+        trailer: packet.trailer,
+    };
+    Ok((input.slice_ref(rem), packet_bytes))
+}
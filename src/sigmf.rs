@@ -0,0 +1,502 @@
+//! Conversion between VRT data streams and SigMF recordings.
+
+use crate::context::SignalContext;
+use crate::fragment::Fragmenter;
+use crate::vrt::{
+    radix20, Header, Trailer, Tsf, Tsi, VitaPacketType, VrtPacket, VrtPacketOwned, CIF0_SAMPLE_RATE,
+};
+use serde_json::{json, Map, Value};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Version of the SigMF specification written by [`SigmfExporter`]
+pub const SIGMF_VERSION: &str = "1.0.0";
+
+/// Formats the UTC time `secs` seconds and `picos` picoseconds after the UNIX epoch as an
+/// ISO 8601 date-time, as used for `core:datetime`
+fn iso8601(secs: u32, picos: u64) -> String {
+    // civil-from-days, valid for any date after the epoch
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days / 146_097;
+    let doe = days - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    let rem = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:09}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem / 60 % 60,
+        rem % 60,
+        picos / 1000
+    )
+}
+
+/// Parses an ISO 8601 UTC date-time such as `2024-01-02T03:04:05.5Z` into seconds and
+/// picoseconds after the UNIX epoch
+fn parse_iso8601(s: &str) -> Option<(u32, u64)> {
+    let s = s.strip_suffix('Z')?;
+    let (date, time) = s.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(|v| v.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    let (time, frac) = time.split_once('.').unwrap_or((time, ""));
+    let mut time = time.splitn(3, ':').map(|v| v.parse::<u64>().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || !frac.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+
+    // days-from-civil
+    let year = year - (month <= 2) as i64;
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    let secs = days * 86_400 + (hour * 3600 + minute * 60 + second) as i64;
+    let picos = frac
+        .bytes()
+        .chain(std::iter::repeat(b'0'))
+        .take(12)
+        .fold(0u64, |acc, b| acc * 10 + (b - b'0') as u64);
+    Some((u32::try_from(secs).ok()?, picos))
+}
+
+/// Returns `sample_rate` as a numerator and denominator in lowest terms, exact to a microhertz
+fn rate_ratio(sample_rate: f64) -> (u64, u64) {
+    let (mut numerator, mut denominator) = if sample_rate.fract() == 0.0 {
+        (sample_rate as u64, 1)
+    } else {
+        ((sample_rate * 1e6).round() as u64, 1_000_000)
+    };
+    let (mut a, mut b) = (numerator, denominator);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    if a > 1 {
+        numerator /= a;
+        denominator /= a;
+    }
+    (numerator, denominator)
+}
+
+fn trailer_labels(trailer: &Trailer) -> impl Iterator<Item = &'static str> {
+    [
+        (
+            trailer.overrange_enable && trailer.overrange_indicator,
+            "over-range",
+        ),
+        (
+            trailer.sample_loss_enable && trailer.sample_loss_indicator,
+            "sample loss",
+        ),
+        (
+            trailer.detected_signal_enable && trailer.detected_signal_indicator,
+            "detected signal",
+        ),
+        (
+            trailer.valid_data_enable && !trailer.valid_data_indicator,
+            "invalid data",
+        ),
+        (
+            trailer.reference_lock_enable && !trailer.reference_lock_indicator,
+            "reference unlocked",
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(set, label)| set.then_some(label))
+}
+
+/// SigMF Exporter
+///
+/// Writes the data payloads of a stream of data packets to a SigMF dataset and generates the
+/// matching SigMF metadata:
+///
+/// - a capture segment starting at the first packet and at every packet whose timestamp does
+///   not follow on from the previous packet, with `core:datetime` for UTC timestamps
+/// - an annotation labelled after each trailer event indicator, such as `over-range` or
+///   `sample loss`, spanning the samples of the packets where it was set
+///
+/// The datatype must be supplied by the caller. The sample rate and centre frequency are taken
+/// from the Sample Rate and RF Reference Frequency fields of the IF context packets, falling
+/// back to those given to [`SigmfExporter::new`] and [`SigmfExporter::with_frequency`]. A
+/// change of centre frequency starts a new capture segment. SigMF records one sample rate for
+/// the whole recording, so only a Sample Rate received before the first sample is used.
+#[derive(Debug)]
+pub struct SigmfExporter<W: Write> {
+    data: W,
+    datatype: String,
+    sample_rate: f64,
+    bytes_per_sample: usize,
+    frequency: Option<f64>,
+    signal: SignalContext,
+    samples: u64,
+    next_time: Option<f64>,
+    captures: Vec<Value>,
+    annotations: Vec<(&'static str, u64, u64)>,
+}
+
+impl SigmfExporter<BufWriter<File>> {
+    /// Creates the SigMF dataset file `path`, conventionally named `<name>.sigmf-data`
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        datatype: &str,
+        bytes_per_sample: usize,
+        sample_rate: f64,
+    ) -> io::Result<Self> {
+        let data = BufWriter::new(File::create(path)?);
+        Ok(SigmfExporter::new(
+            data,
+            datatype,
+            bytes_per_sample,
+            sample_rate,
+        ))
+    }
+}
+
+impl<W: Write> SigmfExporter<W> {
+    /// Creates a new SigmfExporter writing samples to `data`
+    ///
+    /// `datatype` is the SigMF `core:datatype` of the samples, such as `ci16_be`, and each
+    /// sample occupies `bytes_per_sample` bytes.
+    pub fn new(data: W, datatype: &str, bytes_per_sample: usize, sample_rate: f64) -> Self {
+        SigmfExporter {
+            data,
+            datatype: datatype.to_string(),
+            sample_rate,
+            bytes_per_sample: bytes_per_sample.max(1),
+            frequency: None,
+            signal: SignalContext::default(),
+            samples: 0,
+            next_time: None,
+            captures: Vec::new(),
+            annotations: Vec::new(),
+        }
+    }
+
+    /// Sets the centre frequency in Hz recorded as `core:frequency` of new capture segments
+    /// until a context packet provides one
+    pub fn with_frequency(mut self, frequency: f64) -> Self {
+        self.frequency = Some(frequency);
+        self
+    }
+
+    /// Appends the data payload of a data packet to the dataset, or records the context fields
+    /// of an IF context packet
+    pub fn push(&mut self, packet: &VrtPacket<'_>) -> io::Result<()> {
+        let packet_type = packet.header.packet_type;
+        if packet_type.0 == VitaPacketType::IFCONTEXT {
+            self.signal.update(packet);
+            if let Some(sample_rate) = self.signal.sample_rate.filter(|r| *r > 0.0) {
+                if self.samples == 0 {
+                    self.sample_rate = sample_rate;
+                }
+            }
+            return Ok(());
+        }
+        if !packet_type.is_data() {
+            return Ok(());
+        }
+        let frequency = self.signal.rf_frequency.or(self.frequency);
+        let count = (packet.data_payload.len() / self.bytes_per_sample) as u64;
+        let time = match (packet.tsi, packet.header.tsf, packet.tsf) {
+            (Some(tsi), Tsf::TSF_REAL_TIME, Some(tsf)) => Some(tsi as f64 + tsf as f64 * 1e-12),
+            (Some(tsi), Tsf::TSF_NONE, _) => Some(tsi as f64),
+            _ => None,
+        };
+
+        // a new segment unless the packet follows on within half a sample at the same frequency
+        let follows = match (time, self.next_time) {
+            (Some(time), Some(next)) => (time - next).abs() * self.sample_rate < 0.5,
+            (None, _) => !self.captures.is_empty(),
+            _ => false,
+        };
+        let retuned = self
+            .captures
+            .last()
+            .is_some_and(|capture| capture["core:frequency"].as_f64() != frequency);
+        if !follows || retuned {
+            let mut capture = Map::new();
+            capture.insert("core:sample_start".into(), json!(self.samples));
+            if let Some(frequency) = frequency {
+                capture.insert("core:frequency".into(), json!(frequency));
+            }
+            if let (Tsi::TSI_UTC, Some(tsi)) = (packet.header.tsi, packet.tsi) {
+                let picos = match packet.header.tsf {
+                    Tsf::TSF_REAL_TIME => packet.tsf.unwrap_or(0),
+                    _ => 0,
+                };
+                capture.insert("core:datetime".into(), json!(iso8601(tsi, picos)));
+            }
+            self.captures.push(Value::Object(capture));
+        }
+        self.next_time = time.map(|time| time + count as f64 / self.sample_rate);
+
+        if let Some(trailer) = &packet.trailer {
+            for label in trailer_labels(trailer) {
+                let open = self
+                    .annotations
+                    .iter_mut()
+                    .rev()
+                    .find(|(l, start, len)| *l == label && start + len == self.samples);
+                match open {
+                    Some((_, _, len)) => *len += count,
+                    None => self.annotations.push((label, self.samples, count)),
+                }
+            }
+        }
+
+        self.data.write_all(packet.data_payload)?;
+        self.samples += count;
+        Ok(())
+    }
+
+    /// Returns the number of samples written
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// Returns the SigMF metadata describing the samples written so far
+    pub fn metadata(&self) -> Value {
+        let mut annotations: Vec<_> = self
+            .annotations
+            .iter()
+            .map(|(label, start, len)| {
+                json!({
+                    "core:sample_start": start,
+                    "core:sample_count": len,
+                    "core:label": label,
+                })
+            })
+            .collect();
+        annotations.sort_by_key(|a| a["core:sample_start"].as_u64());
+        json!({
+            "global": {
+                "core:datatype": self.datatype,
+                "core:sample_rate": self.sample_rate,
+                "core:version": SIGMF_VERSION,
+                "core:recorder": "vrt-rs",
+            },
+            "captures": self.captures,
+            "annotations": annotations,
+        })
+    }
+
+    /// Writes the SigMF metadata to `meta`, conventionally a `<name>.sigmf-meta` file, and
+    /// returns the flushed dataset writer
+    pub fn finish<M: Write>(mut self, mut meta: M) -> io::Result<W> {
+        serde_json::to_writer_pretty(&mut meta, &self.metadata())?;
+        meta.flush()?;
+        self.data.flush()?;
+        Ok(self.data)
+    }
+}
+
+/// Context Indicator Field 0 bit of the RF Reference Frequency field
+const CIF0_RF_REFERENCE_FREQUENCY: u32 = 1 << 27;
+/// SigMF Importer
+///
+/// Converts a SigMF recording into a stream of data packets, preceded by a context packet
+/// synthesized from the metadata. The context packet carries the Sample Rate and, when the
+/// first capture segment has a `core:frequency`, the RF Reference Frequency field.
+///
+/// Each capture segment with a `core:datetime` starts a new run of packets timestamped with
+/// UTC Real Time (Picoseconds) timestamps; if the recording has no `core:datetime` the packets
+/// carry no timestamps. Samples are converted to big-endian byte order as required by VRT.
+#[derive(Clone, Debug)]
+pub struct SigmfImporter {
+    stream_id: u32,
+    sample_rate: f64,
+    frequency: Option<f64>,
+    component_len: usize,
+    bytes_per_sample: usize,
+    little_endian: bool,
+    captures: Vec<(u64, Option<(u32, u64)>)>,
+}
+
+impl SigmfImporter {
+    /// Reads the SigMF metadata file `path`, conventionally named `<name>.sigmf-meta`
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let meta: Value = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        SigmfImporter::from_metadata(&meta)
+    }
+
+    /// Creates a new SigmfImporter from parsed SigMF metadata
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if `core:datatype` or
+    /// `core:sample_rate` are missing or invalid.
+    pub fn from_metadata(meta: &Value) -> io::Result<Self> {
+        let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let global = &meta["global"];
+        let datatype = global["core:datatype"]
+            .as_str()
+            .ok_or_else(|| invalid("missing SigMF core:datatype"))?;
+        let sample_rate = global["core:sample_rate"]
+            .as_f64()
+            .filter(|r| *r > 0.0)
+            .ok_or_else(|| invalid("missing SigMF core:sample_rate"))?;
+
+        // e.g. ci16_le: complex, signed integer, 16 bits, little-endian
+        let (format, little_endian) = match datatype.split_once('_') {
+            Some((format, "le")) => (format, true),
+            Some((format, "be")) => (format, false),
+            Some(_) => return Err(invalid("invalid SigMF core:datatype")),
+            None => (datatype, false),
+        };
+        let (complex, format) = match format.strip_prefix('c') {
+            Some(format) => (true, format),
+            None => (false, format.strip_prefix('r').unwrap_or(format)),
+        };
+        let bits = format
+            .strip_prefix(['f', 'i', 'u'])
+            .and_then(|bits| bits.parse::<usize>().ok())
+            .filter(|bits| [8, 16, 32, 64].contains(bits))
+            .ok_or_else(|| invalid("invalid SigMF core:datatype"))?;
+        let component_len = bits / 8;
+
+        let mut captures: Vec<_> = meta["captures"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|c| {
+                let start = c["core:sample_start"].as_u64().unwrap_or(0);
+                (start, c["core:datetime"].as_str().and_then(parse_iso8601))
+            })
+            .collect();
+        captures.sort_by_key(|(start, _)| *start);
+        if captures.first().map(|(start, _)| *start) != Some(0) {
+            captures.insert(0, (0, None));
+        }
+        let frequency = meta["captures"][0]["core:frequency"].as_f64();
+
+        Ok(SigmfImporter {
+            stream_id: 0,
+            sample_rate,
+            frequency,
+            component_len,
+            bytes_per_sample: component_len * if complex { 2 } else { 1 },
+            little_endian,
+            captures,
+        })
+    }
+
+    /// Sets the Stream Identifier of the generated packets, which defaults to 0
+    pub fn with_stream_id(mut self, stream_id: u32) -> Self {
+        self.stream_id = stream_id;
+        self
+    }
+
+    fn timestamps(time: Option<(u32, u64)>) -> (Tsi, Tsf) {
+        match time {
+            Some(_) => (Tsi::TSI_UTC, Tsf::TSF_REAL_TIME),
+            None => (Tsi::TSI_NONE, Tsf::TSF_NONE),
+        }
+    }
+
+    /// Returns the context packet synthesized from the metadata
+    pub fn context_packet(&self) -> VrtPacketOwned {
+        let time = self.captures[0].1;
+        let (tsi, tsf) = Self::timestamps(time);
+        let mut cif0 = CIF0_SAMPLE_RATE;
+        let mut fields = Vec::new();
+        if let Some(frequency) = self.frequency {
+            cif0 |= CIF0_RF_REFERENCE_FREQUENCY;
+            fields.extend_from_slice(&radix20(frequency));
+        }
+        fields.extend_from_slice(&radix20(self.sample_rate));
+        let mut data_payload = cif0.to_be_bytes().to_vec();
+        data_payload.extend_from_slice(&fields);
+
+        let timestamp_words = if time.is_some() { 3 } else { 0 };
+        VrtPacketOwned {
+            header: Header {
+                packet_type: VitaPacketType(VitaPacketType::IFCONTEXT),
+                c: false,
+                t: false,
+                indicators: 0,
+                tsi,
+                tsf,
+                packet_count: 0,
+                packet_size: (2 + timestamp_words + data_payload.len() / 4) as u16,
+            },
+            stream_id: Some(self.stream_id),
+            class_id: None,
+            tsi: time.map(|(secs, _)| secs),
+            tsf: time.map(|(_, picos)| picos),
+            data_payload,
+            trailer: None,
+        }
+    }
+
+    /// Converts the SigMF dataset `data` into the context packet followed by data packets of
+    /// at most `mtu` bytes
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidInput`] if `mtu` is too small to hold
+    /// a sample.
+    pub fn packets(&self, data: &[u8], mtu: usize) -> io::Result<Vec<VrtPacketOwned>> {
+        let mut samples =
+            data[..data.len() / self.bytes_per_sample * self.bytes_per_sample].to_vec();
+        if self.little_endian {
+            samples
+                .chunks_exact_mut(self.component_len)
+                .for_each(|c| c.reverse());
+        }
+        let total = (samples.len() / self.bytes_per_sample) as u64;
+
+        let mut packets = vec![self.context_packet()];
+        let (numerator, denominator) = rate_ratio(self.sample_rate);
+        let mut packet_count = 0;
+        for (n, (start, time)) in self.captures.iter().enumerate() {
+            let end = self
+                .captures
+                .get(n + 1)
+                .map_or(total, |(end, _)| *end)
+                .min(total);
+            if *start >= end {
+                continue;
+            }
+            let (tsi, tsf) = Self::timestamps(*time);
+            let template = VrtPacket {
+                header: Header {
+                    packet_type: VitaPacketType(VitaPacketType::IFDATAWITHSTREAM),
+                    c: false,
+                    t: false,
+                    indicators: 0,
+                    tsi,
+                    tsf,
+                    packet_count,
+                    packet_size: 0,
+                },
+                stream_id: Some(self.stream_id),
+                class_id: None,
+                tsi: time.map(|(secs, _)| secs),
+                tsf: time.map(|(_, picos)| picos),
+                data_payload: &[],
+                trailer: None,
+            };
+            let range =
+                *start as usize * self.bytes_per_sample..end as usize * self.bytes_per_sample;
+            let fragmenter =
+                Fragmenter::new(&template, &samples[range], self.bytes_per_sample, mtu)
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "MTU too small"))?
+                    .with_sample_rate_ratio(numerator, denominator);
+            for packet in fragmenter {
+                packet_count = (packet.header.packet_count + 1) & 0x0f;
+                packets.push(VrtPacketOwned::from(&packet));
+            }
+        }
+        Ok(packets)
+    }
+}
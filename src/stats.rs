@@ -0,0 +1,202 @@
+//! Packet loss and continuity statistics for VRT packet streams.
+
+use crate::vrt::VrtPacket;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+/// Continuity counters for a single packet stream
+pub struct StreamStats {
+    /// Number of packets received, including duplicated and reordered packets.
+    pub received: u64,
+    /// Number of packets which were expected but not received.
+    pub lost: u64,
+    /// Number of packets received more than once.
+    pub duplicated: u64,
+    /// Number of packets received after a later packet of the same stream.
+    pub reordered: u64,
+}
+
+impl StreamStats {
+    /// Returns the fraction of expected packets which were lost, from 0.0 to 1.0.
+    pub fn loss_rate(&self) -> f64 {
+        let expected = self.received - self.duplicated + self.lost;
+        if expected == 0 {
+            0.0
+        } else {
+            self.lost as f64 / expected as f64
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// Continuity of a packet relative to the previous packets of its stream
+pub enum Continuity {
+    /// The packet is the first packet seen on the stream.
+    First,
+    /// The packet directly follows the previous packet.
+    InOrder,
+    /// The packet follows the previous packet after a gap of the given number of lost packets.
+    Lost(u64),
+    /// The packet repeats the previous packet.
+    Duplicate,
+    /// The packet belongs before the previous packet.
+    Reordered,
+}
+
+/// Continuity checker for a single packet stream
+///
+/// Uses the 4-bit header packet count to detect lost, duplicated and reordered packets. When
+/// packets carry timestamps they are used to disambiguate the packet count, which otherwise
+/// rolls over every 16 packets: a packet with an older timestamp is reordered and a repeated
+/// packet count with a newer timestamp is a gap of 15 lost packets. Without timestamps a
+/// packet count up to 7 behind the previous one is treated as reordered. A reordered packet
+/// is no longer counted as lost if it fills a gap among the last 16 packet counts.
+///
+/// Counters are kept both in total and for the current window; call
+/// [`ContinuityChecker::take_window`] at the end of each time window to collect and reset the
+/// window counters.
+#[derive(Clone, Debug, Default)]
+pub struct ContinuityChecker {
+    last_count: Option<u8>,
+    last_timestamp: Option<(u32, u64)>,
+    // bit n is set while the packet with count n of the last 16 counts is outstanding
+    missing: u16,
+    total: StreamStats,
+    window: StreamStats,
+}
+
+fn packet_timestamp(packet: &VrtPacket<'_>) -> Option<(u32, u64)> {
+    match (packet.tsi, packet.tsf) {
+        (None, None) => None,
+        (tsi, tsf) => Some((tsi.unwrap_or(0), tsf.unwrap_or(0))),
+    }
+}
+
+impl ContinuityChecker {
+    /// Creates a new ContinuityChecker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks the continuity of `packet` and updates the counters.
+    pub fn update(&mut self, packet: &VrtPacket<'_>) -> Continuity {
+        let count = packet.header.packet_count & 0x0f;
+        let timestamp = packet_timestamp(packet);
+        let continuity = match self.last_count {
+            None => Continuity::First,
+            Some(last_count) => {
+                let diff = count.wrapping_sub(last_count) & 0x0f;
+                match (timestamp, self.last_timestamp) {
+                    (Some(ts), Some(last_ts)) if ts < last_ts => Continuity::Reordered,
+                    (Some(ts), Some(last_ts)) if ts == last_ts && diff == 0 => {
+                        Continuity::Duplicate
+                    }
+                    (Some(_), Some(_)) if diff == 0 => Continuity::Lost(15),
+                    _ if diff == 0 => Continuity::Duplicate,
+                    (Some(_), Some(_)) => Self::gap(diff),
+                    _ if diff > 8 => Continuity::Reordered,
+                    _ => Self::gap(diff),
+                }
+            }
+        };
+
+        // a reordered packet was counted as lost if it fell in a gap which is still outstanding
+        let recovered = continuity == Continuity::Reordered && self.missing & (1 << count) != 0;
+        for stats in [&mut self.total, &mut self.window] {
+            stats.received += 1;
+            match continuity {
+                Continuity::Lost(n) => stats.lost += n,
+                Continuity::Duplicate => stats.duplicated += 1,
+                Continuity::Reordered => {
+                    stats.reordered += 1;
+                    if recovered {
+                        stats.lost = stats.lost.saturating_sub(1);
+                    }
+                }
+                Continuity::First | Continuity::InOrder => {}
+            }
+        }
+        match (continuity, self.last_count) {
+            (Continuity::Reordered, _) => self.missing &= !(1 << count),
+            (Continuity::InOrder | Continuity::Lost(_), Some(last_count)) => {
+                let lost = match continuity {
+                    Continuity::Lost(n) => n as u8,
+                    _ => 0,
+                };
+                for n in 1..=lost {
+                    self.missing |= 1 << (last_count.wrapping_add(n) & 0x0f);
+                }
+                self.missing &= !(1 << count);
+            }
+            (Continuity::First, _) => self.missing = 0,
+            _ => {}
+        }
+        if continuity != Continuity::Reordered {
+            self.last_count = Some(count);
+            self.last_timestamp = timestamp;
+        }
+        continuity
+    }
+
+    fn gap(diff: u8) -> Continuity {
+        if diff == 1 {
+            Continuity::InOrder
+        } else {
+            Continuity::Lost(diff as u64 - 1)
+        }
+    }
+
+    /// Returns the counters accumulated since the checker was created.
+    pub fn stats(&self) -> StreamStats {
+        self.total
+    }
+
+    /// Returns the counters accumulated in the current window and starts a new window.
+    pub fn take_window(&mut self) -> StreamStats {
+        core::mem::take(&mut self.window)
+    }
+}
+
+/// Continuity checker for multiple packet streams, keyed by Stream Identifier
+///
+/// Packets without a Stream Identifier are tracked together under the `None` key.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default)]
+pub struct StreamTracker {
+    streams: HashMap<Option<u32>, ContinuityChecker>,
+}
+
+#[cfg(feature = "std")]
+impl StreamTracker {
+    /// Creates a new StreamTracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks the continuity of `packet` against the previous packets of its stream.
+    pub fn update(&mut self, packet: &VrtPacket<'_>) -> Continuity {
+        self.streams
+            .entry(packet.stream_id)
+            .or_default()
+            .update(packet)
+    }
+
+    /// Returns the total counters for the stream `stream_id`, if any packets were seen.
+    pub fn stats(&self, stream_id: Option<u32>) -> Option<StreamStats> {
+        self.streams.get(&stream_id).map(|c| c.stats())
+    }
+
+    /// Returns the window counters of every stream and starts a new window.
+    pub fn take_window(&mut self) -> HashMap<Option<u32>, StreamStats> {
+        self.streams
+            .iter_mut()
+            .map(|(id, c)| (*id, c.take_window()))
+            .collect()
+    }
+
+    /// Returns an iterator over the tracked Stream Identifiers and their total counters.
+    pub fn iter(&self) -> impl Iterator<Item = (Option<u32>, StreamStats)> + '_ {
+        self.streams.iter().map(|(id, c)| (*id, c.stats()))
+    }
+}
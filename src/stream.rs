@@ -0,0 +1,298 @@
+//! Iteration over buffers containing back-to-back VRT packets.
+
+use crate::error::Error;
+use crate::parser::{malformed_field, packet_error, parse_vrt_header, parse_vrt_packet};
+use crate::vrt::{VitaPacketType, VrtPacket};
+
+/// Iterator over the VRT packets stored back-to-back in a byte buffer
+///
+/// Iteration stops when the buffer is exhausted or when the remaining bytes do not contain a
+/// complete packet; in that case the partial packet is available from [`PacketStream::remainder`]
+/// so it can be carried over to the next buffer. A malformed packet is returned as an error,
+/// locating the field at fault by its offset from the start of the buffer where possible, and
+/// ends the iteration unless recovery is enabled with [`PacketStream::with_recovery`].
+#[derive(Debug)]
+pub struct PacketStream<'a> {
+    buf: &'a [u8],
+    offset: usize,
+    failed: bool,
+    recover: bool,
+    skipped: usize,
+}
+
+impl<'a> PacketStream<'a> {
+    /// Creates a new PacketStream over `buf`
+    pub fn new(buf: &'a [u8]) -> Self {
+        PacketStream {
+            buf,
+            offset: 0,
+            failed: false,
+            recover: false,
+            skipped: 0,
+        }
+    }
+
+    /// Continues the iteration after a malformed packet, for analysing long captures in which
+    /// one corrupt packet should not hide the rest
+    ///
+    /// The malformed packet is still returned as an error, and [`PacketStream::offset`] gives
+    /// where it starts until the next call to `next`, which skips to the next 32-bit word from
+    /// which a complete packet with a plausible header, as checked by [`resync`], parses. The
+    /// bytes skipped are counted by [`PacketStream::skipped`]. If no complete packet follows,
+    /// the skipped bytes end at the first word starting an incomplete packet, which is left as
+    /// the remainder.
+    pub fn with_recovery(mut self) -> Self {
+        self.recover = true;
+        self
+    }
+
+    /// Returns the bytes which have not been consumed by the iterator yet
+    pub fn remainder(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    /// Returns the offset of the remainder from the start of the buffer
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the number of bytes skipped after malformed packets
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Skips the malformed packet at the start of the remainder, up to the next word from which
+    /// a complete packet with a plausible header parses
+    fn resync(&mut self) {
+        let len = self.buf.len();
+        let mut incomplete = None;
+        let mut skip = 4.min(len);
+        while skip < len {
+            if !plausible_header(&self.buf[skip..]) {
+                skip += 4;
+                continue;
+            }
+            match parse_vrt_packet(&self.buf[skip..]) {
+                Ok(_) => break,
+                Err(nom::Err::Incomplete(_)) => {
+                    incomplete.get_or_insert(skip);
+                }
+                Err(_) => {}
+            }
+            skip += 4;
+        }
+        if skip >= len {
+            skip = incomplete.unwrap_or(len);
+        }
+        self.buf = &self.buf[skip..];
+        self.offset += skip;
+        self.skipped += skip;
+    }
+}
+
+impl<'a> Iterator for PacketStream<'a> {
+    type Item = Result<VrtPacket<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            if !self.recover {
+                return None;
+            }
+            self.failed = false;
+            self.resync();
+        }
+        if self.buf.is_empty() {
+            return None;
+        }
+        match parse_vrt_packet(self.buf) {
+            Ok((rem, packet)) => {
+                self.offset += self.buf.len() - rem.len();
+                self.buf = rem;
+                Some(Ok(packet))
+            }
+            Err(nom::Err::Incomplete(_)) => None,
+            Err(e) => {
+                self.failed = true;
+                Some(Err(packet_error(self.buf, e).offset_by(self.offset)))
+            }
+        }
+    }
+}
+
+/// Returns whether `buf` starts with a plausible VRT Packet Header: a VITA 49.0 packet type,
+/// clear reserved bits, and a packet size holding every field the header implies
+fn plausible_header(buf: &[u8]) -> bool {
+    let header = match parse_vrt_header(buf) {
+        Ok((_, header)) => header,
+        Err(_) => return false,
+    };
+    let reserved = match header.packet_type.is_context() {
+        true => 0b0110,
+        false => 0b0011,
+    };
+    header.packet_type.0 <= VitaPacketType::EXTCONTEXT
+        && buf[0] & reserved == 0
+        && malformed_field(&header).is_none()
+}
+
+/// Returns the offset of the first byte of `buf` which starts a plausible VRT packet, for
+/// resynchronizing a byte stream, such as a TCP or serial link, after data loss
+///
+/// Every byte offset is tried, as data loss need not preserve word alignment. A plausible
+/// packet starts with a header of a VITA 49.0 packet type, with its reserved bits clear and a
+/// packet size large enough for the fields it declares. With `confirm` greater than zero the
+/// `confirm` packets from that offset must also parse back-to-back, each with a plausible
+/// header, unless they end exactly at the end of `buf`; a packet cut off by the end of `buf`
+/// does not confirm the offset, so wait for at least `confirm` packets' worth of data before
+/// searching.
+///
+/// Returns None if no offset is plausible. With `confirm` of zero, all but the last three
+/// bytes of `buf` can then be discarded.
+pub fn resync(buf: &[u8], confirm: usize) -> Option<usize> {
+    (0..buf.len()).find(|&start| {
+        let mut rest = &buf[start..];
+        if !plausible_header(rest) {
+            return false;
+        }
+        for _ in 0..confirm {
+            if rest.is_empty() {
+                return true;
+            }
+            if !plausible_header(rest) {
+                return false;
+            }
+            match parse_vrt_packet(rest) {
+                Ok((next, _)) => rest = next,
+                Err(_) => return false,
+            }
+        }
+        true
+    })
+}
+
+/// Push parser reassembling VRT packets from chunks of arbitrary size
+///
+/// Bytes are fed in as they arrive, for example from a DMA completion interrupt or a TCP
+/// socket, and each complete packet is passed to a callback. Packets contained entirely in a
+/// chunk are parsed in place; only a packet split across chunks is copied into the internal
+/// buffer of `N` bytes, which bounds the largest packet that can be reassembled. `N` must be at
+/// least 4, the size of a packet header, which is checked at compile time.
+///
+/// After an error the buffered bytes are discarded, so feeding resumes with the next chunk,
+/// which must start on a packet boundary.
+pub struct PushParser<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> PushParser<N> {
+    const HOLDS_HEADER: () = assert!(N >= 4, "PushParser needs a buffer of at least 4 bytes");
+
+    /// Creates a new PushParser
+    pub fn new() -> Self {
+        let () = Self::HOLDS_HEADER;
+        PushParser {
+            buf: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Returns the number of bytes of an incomplete packet carried over to the next chunk
+    pub fn buffered(&self) -> usize {
+        self.len
+    }
+
+    /// Discards any buffered bytes of an incomplete packet
+    pub fn reset(&mut self) {
+        self.len = 0;
+    }
+
+    /// Feeds `chunk`, calling `on_packet` for every packet completed by it
+    ///
+    /// Returns [`Error::MalformedField`] if the packet size of a packet is too small for the
+    /// fields its header declares, [`Error::Malformed`] if a packet is otherwise malformed,
+    /// and [`Error::BufferTooSmall`] if a packet split across chunks is larger than `N` bytes.
+    /// [`Error::Incomplete`] is never returned, as an incomplete packet is buffered until the
+    /// following chunks complete it. The packets completed before the error have already been
+    /// passed to `on_packet`.
+    pub fn feed(
+        &mut self,
+        chunk: &[u8],
+        mut on_packet: impl FnMut(VrtPacket<'_>),
+    ) -> Result<(), Error> {
+        let chunk = match self.complete(chunk, &mut on_packet) {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                self.len = 0;
+                return Err(e);
+            }
+        };
+
+        let mut stream = PacketStream::new(chunk);
+        for packet in &mut stream {
+            on_packet(packet?);
+        }
+        let rest = stream.remainder();
+        if rest.len() > N {
+            return Err(Error::BufferTooSmall);
+        }
+        self.buf[..rest.len()].copy_from_slice(rest);
+        self.len = rest.len();
+        Ok(())
+    }
+
+    /// Completes the buffered packet from the start of `chunk`, returning the rest of `chunk`,
+    /// or None if all of it was buffered
+    fn complete<'c>(
+        &mut self,
+        mut chunk: &'c [u8],
+        on_packet: &mut impl FnMut(VrtPacket<'_>),
+    ) -> Result<Option<&'c [u8]>, Error> {
+        if self.len == 0 {
+            return Ok(Some(chunk));
+        }
+        if self.len < 4 {
+            chunk = self.fill(4, chunk);
+            if self.len < 4 {
+                return Ok(None);
+            }
+        }
+        let want = u16::from_be_bytes([self.buf[2], self.buf[3]]) as usize * 4;
+        if want > N {
+            return Err(Error::BufferTooSmall);
+        }
+        chunk = self.fill(want, chunk);
+        if self.len < want {
+            return Ok(None);
+        }
+        self.len = 0;
+        let bytes = &self.buf[..want.max(4)];
+        let (_, packet) = parse_vrt_packet(bytes).map_err(|e| packet_error(bytes, e))?;
+        on_packet(packet);
+        Ok(Some(chunk))
+    }
+
+    /// Copies bytes from `chunk` until `want` bytes are buffered, returning the rest of `chunk`
+    fn fill<'c>(&mut self, want: usize, chunk: &'c [u8]) -> &'c [u8] {
+        let (head, rest) = chunk.split_at(want.saturating_sub(self.len).min(chunk.len()));
+        self.buf[self.len..self.len + head.len()].copy_from_slice(head);
+        self.len += head.len();
+        rest
+    }
+}
+
+impl<const N: usize> Default for PushParser<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> core::fmt::Debug for PushParser<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PushParser")
+            .field("capacity", &N)
+            .field("buffered", &self.len)
+            .finish()
+    }
+}
@@ -0,0 +1,248 @@
+//! Canonical VITA 49.0 packets for validating parsers and encoders.
+//!
+//! Each [`TestVector`] holds the bytes of a packet together with the values of every field
+//! they encode, covering each packet type, each timestamp type, the Class Identifier, trailer
+//! bits and common context fields. The encodings follow VITA 49.0-2015 and are checked by this
+//! crate's tests against both the parser and the encoder, so downstream implementations and
+//! language bindings can validate against the same fixtures.
+//!
+//! Available with the `test-vectors` feature.
+
+use crate::vrt::{Header, Tsf, Tsi, VitaPacketType};
+
+/// Packet bytes together with the field values they encode
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TestVector {
+    /// Short name of the vector, matching the name of its constant
+    pub name: &'static str,
+    /// The encoded packet
+    pub bytes: &'static [u8],
+    /// The packet header
+    pub header: Header,
+    /// The Stream Identifier, if present
+    pub stream_id: Option<u32>,
+    /// The Class Identifier, if present
+    pub class_id: Option<u64>,
+    /// The Integer-seconds Timestamp, if present
+    pub tsi: Option<u32>,
+    /// The Fractional-seconds Timestamp, if present
+    pub tsf: Option<u64>,
+    /// The data payload, or the context fields of a context packet
+    pub payload: &'static [u8],
+    /// The trailer word, if present
+    pub trailer: Option<u32>,
+}
+
+const fn header(packet_type: u8, c: bool, t: bool, tsi: Tsi, tsf: Tsf, size: u16) -> Header {
+    Header {
+        packet_type: VitaPacketType(packet_type),
+        c,
+        t,
+        indicators: 0,
+        tsi,
+        tsf,
+        packet_count: 0,
+        packet_size: size,
+    }
+}
+
+/// IF Data packet without Stream Identifier, carrying one word of payload
+pub const IF_DATA: TestVector = TestVector {
+    name: "IF_DATA",
+    bytes: &[0x00, 0x00, 0x00, 0x02, 0x01, 0x02, 0x03, 0x04],
+    header: header(0, false, false, Tsi::TSI_NONE, Tsf::TSF_NONE, 2),
+    stream_id: None,
+    class_id: None,
+    tsi: None,
+    tsf: None,
+    payload: &[0x01, 0x02, 0x03, 0x04],
+    trailer: None,
+};
+
+/// IF Data packet with Stream Identifier and a packet count of 1
+pub const IF_DATA_WITH_STREAM: TestVector = TestVector {
+    name: "IF_DATA_WITH_STREAM",
+    bytes: &[
+        0x10, 0x01, 0x00, 0x03, 0x12, 0x34, 0x56, 0x78, 0x01, 0x02, 0x03, 0x04,
+    ],
+    header: Header {
+        packet_count: 1,
+        ..header(1, false, false, Tsi::TSI_NONE, Tsf::TSF_NONE, 3)
+    },
+    stream_id: Some(0x1234_5678),
+    class_id: None,
+    tsi: None,
+    tsf: None,
+    payload: &[0x01, 0x02, 0x03, 0x04],
+    trailer: None,
+};
+
+/// Extension Data packet without Stream Identifier
+pub const EXT_DATA: TestVector = TestVector {
+    name: "EXT_DATA",
+    bytes: &[0x20, 0x00, 0x00, 0x02, 0x01, 0x02, 0x03, 0x04],
+    header: header(2, false, false, Tsi::TSI_NONE, Tsf::TSF_NONE, 2),
+    stream_id: None,
+    class_id: None,
+    tsi: None,
+    tsf: None,
+    payload: &[0x01, 0x02, 0x03, 0x04],
+    trailer: None,
+};
+
+/// Extension Data packet with Stream Identifier
+pub const EXT_DATA_WITH_STREAM: TestVector = TestVector {
+    name: "EXT_DATA_WITH_STREAM",
+    bytes: &[
+        0x30, 0x00, 0x00, 0x03, 0x12, 0x34, 0x56, 0x78, 0x01, 0x02, 0x03, 0x04,
+    ],
+    header: header(3, false, false, Tsi::TSI_NONE, Tsf::TSF_NONE, 3),
+    stream_id: Some(0x1234_5678),
+    class_id: None,
+    tsi: None,
+    tsf: None,
+    payload: &[0x01, 0x02, 0x03, 0x04],
+    trailer: None,
+};
+
+/// IF Data packet with every optional field: a Class Identifier of OUI 0x123456, Information
+/// Class Code 1 and Packet Class Code 2, a UTC timestamp of 1700000000.5 s in real-time
+/// picoseconds, and a trailer with valid data enabled and indicated
+pub const IF_DATA_ALL_FIELDS: TestVector = TestVector {
+    name: "IF_DATA_ALL_FIELDS",
+    bytes: &[
+        0x1c, 0x63, 0x00, 0x0a, 0x12, 0x34, 0x56, 0x78, 0x00, 0x12, 0x34, 0x56, 0x00, 0x01, 0x00,
+        0x02, 0x65, 0x53, 0xf1, 0x00, 0x00, 0x00, 0x00, 0x74, 0x6a, 0x52, 0x88, 0x00, 0x01, 0x02,
+        0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x40, 0x04, 0x00, 0x00,
+    ],
+    header: Header {
+        packet_count: 3,
+        ..header(1, true, true, Tsi::TSI_UTC, Tsf::TSF_REAL_TIME, 10)
+    },
+    stream_id: Some(0x1234_5678),
+    class_id: Some(0x0012_3456_0001_0002),
+    tsi: Some(1_700_000_000),
+    tsf: Some(500_000_000_000),
+    payload: &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08],
+    trailer: Some(0x4004_0000),
+};
+
+/// IF Data packet with a GPS timestamp and a sample count Fractional-seconds Timestamp
+pub const IF_DATA_GPS_SAMPLE_COUNT: TestVector = TestVector {
+    name: "IF_DATA_GPS_SAMPLE_COUNT",
+    bytes: &[
+        0x10, 0x90, 0x00, 0x06, 0x12, 0x34, 0x56, 0x78, 0x65, 0x53, 0xf1, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x10, 0x00, 0x01, 0x02, 0x03, 0x04,
+    ],
+    header: header(1, false, false, Tsi::TSI_GPS, Tsf::TSF_SAMPLE_COUNT, 6),
+    stream_id: Some(0x1234_5678),
+    class_id: None,
+    tsi: Some(1_700_000_000),
+    tsf: Some(0x1000),
+    payload: &[0x01, 0x02, 0x03, 0x04],
+    trailer: None,
+};
+
+/// IF Data packet with an "other" Integer-seconds Timestamp and a free-running count
+/// Fractional-seconds Timestamp
+pub const IF_DATA_OTHER_FREE_RUNNING: TestVector = TestVector {
+    name: "IF_DATA_OTHER_FREE_RUNNING",
+    bytes: &[
+        0x10, 0xf0, 0x00, 0x06, 0x12, 0x34, 0x56, 0x78, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0xab, 0xcd, 0xef, 0x01, 0x02, 0x03, 0x04,
+    ],
+    header: header(1, false, false, Tsi::TSI_OTHER, Tsf::TSF_FREE_RUNNING, 6),
+    stream_id: Some(0x1234_5678),
+    class_id: None,
+    tsi: Some(1),
+    tsf: Some(0x00ab_cdef),
+    payload: &[0x01, 0x02, 0x03, 0x04],
+    trailer: None,
+};
+
+/// IF Data packet whose trailer enables and indicates sample loss and carries an associated
+/// context packet count of 5
+pub const IF_DATA_TRAILER_SAMPLE_LOSS: TestVector = TestVector {
+    name: "IF_DATA_TRAILER_SAMPLE_LOSS",
+    bytes: &[
+        0x14, 0x00, 0x00, 0x04, 0x12, 0x34, 0x56, 0x78, 0x01, 0x02, 0x03, 0x04, 0x01, 0x00, 0x10,
+        0x85,
+    ],
+    header: header(1, false, true, Tsi::TSI_NONE, Tsf::TSF_NONE, 4),
+    stream_id: Some(0x1234_5678),
+    class_id: None,
+    tsi: None,
+    tsf: None,
+    payload: &[0x01, 0x02, 0x03, 0x04],
+    trailer: Some(0x0100_1085),
+};
+
+/// IF Context packet with a UTC timestamp and the Bandwidth (1 MHz), Reference Level
+/// (-10 dBm) and Sample Rate (2 MHz) context fields
+pub const IF_CONTEXT: TestVector = TestVector {
+    name: "IF_CONTEXT",
+    bytes: &[
+        0x40, 0x60, 0x00, 0x0b, 0x12, 0x34, 0x56, 0x78, 0x65, 0x53, 0xf1, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x21, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0xf4, 0x24, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0xfb, 0x00, 0x00, 0x00, 0x01, 0xe8, 0x48, 0x00, 0x00, 0x00,
+    ],
+    header: header(4, false, false, Tsi::TSI_UTC, Tsf::TSF_REAL_TIME, 11),
+    stream_id: Some(0x1234_5678),
+    class_id: None,
+    tsi: Some(1_700_000_000),
+    tsf: Some(0),
+    payload: &[
+        0x21, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0xf4, 0x24, 0x00, 0x00, 0x00, 0x00, 0x00, 0xfb,
+        0x00, 0x00, 0x00, 0x01, 0xe8, 0x48, 0x00, 0x00, 0x00,
+    ],
+    trailer: None,
+};
+
+/// IF Context packet with the Timestamp Mode bit set and only the Context Field Change
+/// Indicator in its Context Indicator Field
+pub const IF_CONTEXT_CHANGE: TestVector = TestVector {
+    name: "IF_CONTEXT_CHANGE",
+    bytes: &[
+        0x41, 0x00, 0x00, 0x03, 0x12, 0x34, 0x56, 0x78, 0x80, 0x00, 0x00, 0x00,
+    ],
+    header: Header {
+        indicators: 1,
+        ..header(4, false, false, Tsi::TSI_NONE, Tsf::TSF_NONE, 3)
+    },
+    stream_id: Some(0x1234_5678),
+    class_id: None,
+    tsi: None,
+    tsf: None,
+    payload: &[0x80, 0x00, 0x00, 0x00],
+    trailer: None,
+};
+
+/// Extension Context packet
+pub const EXT_CONTEXT: TestVector = TestVector {
+    name: "EXT_CONTEXT",
+    bytes: &[
+        0x50, 0x00, 0x00, 0x03, 0x12, 0x34, 0x56, 0x78, 0x01, 0x02, 0x03, 0x04,
+    ],
+    header: header(5, false, false, Tsi::TSI_NONE, Tsf::TSF_NONE, 3),
+    stream_id: Some(0x1234_5678),
+    class_id: None,
+    tsi: None,
+    tsf: None,
+    payload: &[0x01, 0x02, 0x03, 0x04],
+    trailer: None,
+};
+
+/// Every test vector of the suite
+pub const ALL: &[TestVector] = &[
+    IF_DATA,
+    IF_DATA_WITH_STREAM,
+    EXT_DATA,
+    EXT_DATA_WITH_STREAM,
+    IF_DATA_ALL_FIELDS,
+    IF_DATA_GPS_SAMPLE_COUNT,
+    IF_DATA_OTHER_FREE_RUNNING,
+    IF_DATA_TRAILER_SAMPLE_LOSS,
+    IF_CONTEXT,
+    IF_CONTEXT_CHANGE,
+    EXT_CONTEXT,
+];
@@ -0,0 +1,262 @@
+//! Live terminal monitor of VRT packet streams.
+
+use crate::net::AsyncUdpReceiver;
+use crate::stats::{Continuity, StreamTracker};
+use crate::vrt::VrtPacket;
+use futures_core::Stream;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, List, Row, Table};
+use ratatui::Frame;
+use std::collections::{BTreeMap, VecDeque};
+use std::future::poll_fn;
+use std::io;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+/// Default number of packet log lines kept by a [`Monitor`]
+pub const DEFAULT_MONITOR_LOG_LEN: usize = 1000;
+
+#[derive(Clone, Debug, Default)]
+struct StreamView {
+    packets: u64,
+    window: u64,
+    rate: f64,
+    context: Vec<(&'static str, String)>,
+}
+
+/// Live Stream Monitor
+///
+/// Keeps the state shown by the terminal monitor: the packet rate, packet count and loss of each
+/// stream, the latest value of each context field decoded by [`VrtPacket::dissect`] for the
+/// stream, and a scrolling log of the packets received. Loss is counted over the data packets
+/// of each stream, whose packet counts run separately from those of its context packets.
+///
+/// Feed it packets with [`Monitor::update`], call [`Monitor::tick`] periodically to update the
+/// packet rates, and draw it with [`Monitor::render`]; [`run_monitor`] does all three for
+/// packets received on a UDP socket.
+#[derive(Clone, Debug)]
+pub struct Monitor {
+    tracker: StreamTracker,
+    context_tracker: StreamTracker,
+    streams: BTreeMap<Option<u32>, StreamView>,
+    log: VecDeque<String>,
+    log_len: usize,
+    window_start: Instant,
+    dropped: u64,
+}
+
+impl Monitor {
+    /// Creates a new Monitor whose first rate window starts at `now`
+    pub fn new(now: Instant) -> Self {
+        Monitor {
+            tracker: StreamTracker::new(),
+            context_tracker: StreamTracker::new(),
+            streams: BTreeMap::new(),
+            log: VecDeque::new(),
+            log_len: DEFAULT_MONITOR_LOG_LEN,
+            window_start: now,
+            dropped: 0,
+        }
+    }
+
+    /// Sets the number of packet log lines kept
+    pub fn with_log_len(mut self, len: usize) -> Self {
+        self.log_len = len;
+        self
+    }
+
+    /// Adds `packet` to the stream statistics, context values and packet log
+    pub fn update(&mut self, packet: &VrtPacket<'_>) {
+        let context = packet.header.packet_type.is_context();
+        let continuity = match context {
+            true => self.context_tracker.update(packet),
+            false => self.tracker.update(packet),
+        };
+        let view = self.streams.entry(packet.stream_id).or_default();
+        view.packets += 1;
+        view.window += 1;
+        if context {
+            for field in packet.context_fields() {
+                let value = match field.value() {
+                    Some(value) if value.unit().is_some() => value.to_string(),
+                    _ => continue,
+                };
+                let name = field.field.name();
+                match view.context.iter_mut().find(|(n, _)| *n == name) {
+                    Some((_, v)) => *v = value,
+                    None => view.context.push((name, value)),
+                }
+            }
+        }
+
+        let mut line = match packet.stream_id {
+            Some(stream_id) => format!("{:#010x} ", stream_id),
+            None => "no stream  ".to_string(),
+        };
+        line += &format!(
+            "{} count {}",
+            packet.header.packet_type, packet.header.packet_count
+        );
+        if let Some(t) = packet.timestamp() {
+            line += &format!(" at {}.{:012}", t.integer, t.fractional);
+        }
+        match continuity {
+            Continuity::Lost(n) => line += &format!(" after {} lost", n),
+            Continuity::Duplicate => line += " duplicate",
+            Continuity::Reordered => line += " reordered",
+            Continuity::First | Continuity::InOrder => {}
+        }
+        if self.log.len() == self.log_len {
+            self.log.pop_front();
+        }
+        if self.log_len > 0 {
+            self.log.push_back(line);
+        }
+    }
+
+    /// Sets the number of datagrams dropped by the receiver, shown in the monitor title
+    pub fn set_dropped(&mut self, dropped: u64) {
+        self.dropped = dropped;
+    }
+
+    /// Ends the current rate window at `now`, updating the packet rate of each stream
+    pub fn tick(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.window_start).as_secs_f64();
+        if elapsed <= 0.0 {
+            return;
+        }
+        for view in self.streams.values_mut() {
+            view.rate = view.window as f64 / elapsed;
+            view.window = 0;
+        }
+        self.window_start = now;
+    }
+
+    /// Returns the packet rate of the stream `stream_id` over the last rate window, in packets
+    /// per second
+    pub fn rate(&self, stream_id: Option<u32>) -> Option<f64> {
+        self.streams.get(&stream_id).map(|view| view.rate)
+    }
+
+    /// Returns the latest value of each context field of the stream `stream_id`, such as
+    /// `("Sample Rate", "1000000 Hz")`
+    pub fn context(&self, stream_id: Option<u32>) -> &[(&'static str, String)] {
+        self.streams
+            .get(&stream_id)
+            .map_or(&[], |view| view.context.as_slice())
+    }
+
+    /// Returns the packet log, oldest line first
+    pub fn log(&self) -> impl Iterator<Item = &str> + '_ {
+        self.log.iter().map(String::as_str)
+    }
+
+    /// Draws the stream table and packet log over the whole of `frame`
+    pub fn render(&self, frame: &mut Frame<'_>) {
+        let [streams, log] = Layout::vertical([
+            Constraint::Length(self.streams.len() as u16 + 3),
+            Constraint::Min(3),
+        ])
+        .areas(frame.area());
+
+        let header = Row::new(["Stream", "Packets", "Rate/s", "Lost", "Loss", "Context"])
+            .style(Style::default().add_modifier(Modifier::BOLD));
+        let rows = self.streams.iter().map(|(stream_id, view)| {
+            let stats = self.tracker.stats(*stream_id).unwrap_or_default();
+            let context: Vec<_> = view
+                .context
+                .iter()
+                .map(|(name, value)| format!("{}: {}", name, value))
+                .collect();
+            Row::new([
+                stream_id.map_or("none".to_string(), |id| format!("{:#010x}", id)),
+                view.packets.to_string(),
+                format!("{:.1}", view.rate),
+                stats.lost.to_string(),
+                format!("{:.2}%", stats.loss_rate() * 100.0),
+                context.join(", "),
+            ])
+        });
+        let widths = [
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Fill(1),
+        ];
+        let title = format!(
+            " VRT streams, {} datagrams dropped (q to quit) ",
+            self.dropped
+        );
+        frame.render_widget(
+            Table::new(rows, widths)
+                .header(header)
+                .block(Block::bordered().title(title)),
+            streams,
+        );
+
+        let lines = log.height.saturating_sub(2) as usize;
+        let skip = self.log.len().saturating_sub(lines);
+        frame.render_widget(
+            List::new(self.log.iter().skip(skip).map(String::as_str))
+                .block(Block::bordered().title(" Packets ")),
+            log,
+        );
+    }
+}
+
+/// Returns whether a quit key, q, Esc or Ctrl-C, is waiting in the terminal input
+fn quit_requested() -> io::Result<bool> {
+    while event::poll(Duration::ZERO)? {
+        if let Event::Key(key) = event::read()? {
+            let ctrl_c =
+                key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+            let quit = matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || ctrl_c;
+            if key.kind == KeyEventKind::Press && quit {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+async fn monitor_loop(
+    terminal: &mut ratatui::DefaultTerminal,
+    mut receiver: AsyncUdpReceiver,
+    refresh: Duration,
+) -> io::Result<()> {
+    let mut monitor = Monitor::new(Instant::now());
+    let mut next_tick = Instant::now() + refresh;
+    loop {
+        let next = poll_fn(|cx| Pin::new(&mut receiver).poll_next(cx));
+        match tokio::time::timeout_at(next_tick.into(), next).await {
+            Ok(Some(Ok((_, packet)))) => monitor.update(&packet.as_packet()),
+            Ok(Some(Err(e))) => return Err(e),
+            Ok(None) => return Ok(()),
+            Err(_) => {
+                let now = Instant::now();
+                monitor.tick(now);
+                monitor.set_dropped(receiver.truncated() + receiver.malformed());
+                terminal.draw(|frame| monitor.render(frame))?;
+                if quit_requested()? {
+                    return Ok(());
+                }
+                next_tick = now + refresh;
+            }
+        }
+    }
+}
+
+/// Runs the live terminal monitor on the packets of `receiver` until q, Esc or Ctrl-C is
+/// pressed, redrawing the terminal every `refresh`
+///
+/// Takes over the terminal in raw mode on the alternate screen, restoring it on return.
+pub async fn run_monitor(receiver: AsyncUdpReceiver, refresh: Duration) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = monitor_loop(&mut terminal, receiver, refresh).await;
+    ratatui::restore();
+    result
+}
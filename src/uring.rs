@@ -0,0 +1,337 @@
+//! io_uring receive backend for VRT-over-UDP on Linux.
+
+use crate::net::{parse_datagram, DEFAULT_MAX_DATAGRAM_LEN};
+use crate::vrt::VrtPacketOwned;
+use io_uring::types::{BufRingEntry, Fd, RecvMsgOut};
+use io_uring::{cqueue, opcode, IoUring};
+use std::alloc::{self, Layout};
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::mem::{self, size_of};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6, ToSocketAddrs, UdpSocket};
+use std::os::fd::AsRawFd;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicU16, Ordering};
+
+/// Number of receive buffers registered by default
+pub const DEFAULT_URING_BUFFERS: u16 = 64;
+
+const BUFFER_GROUP: u16 = 0;
+const RECV: u64 = 1;
+const CANCEL: u64 = 2;
+// the header written by the kernel ahead of the source address and the payload
+const RECVMSG_OUT_LEN: usize = 16;
+const NAME_LEN: usize = size_of::<libc::sockaddr_storage>();
+
+/// io_uring UDP Receiver
+///
+/// Receives VRT-over-UDP datagrams with a single multishot `recvmsg` request feeding a ring of
+/// buffers registered with the kernel, so that a steady stream of datagrams is received
+/// without a system call or a copy per datagram. Like [`UdpReceiver`](crate::net::UdpReceiver)
+/// it yields each packet together with its source address, either from
+/// [`IoUringReceiver::recv_packet`] or by iterating, and drops and counts truncated and
+/// malformed datagrams in [`IoUringReceiver::truncated`] and [`IoUringReceiver::malformed`].
+///
+/// Multishot `recvmsg` needs Linux 6.0 or later; creating the receiver fails on older kernels
+/// or where io_uring is disabled, for example by a container's seccomp profile. Receiving
+/// blocks until a datagram arrives, regardless of the socket's read timeout. The backend uses
+/// unsafe code to share the buffer ring with the kernel, so the `io-uring` feature enables the
+/// `unsafe-fast` feature.
+pub struct IoUringReceiver {
+    // dropped before the buffers the kernel writes into
+    ring: IoUring,
+    socket: UdpSocket,
+    msghdr: Box<libc::msghdr>,
+    buf_ring: BufRing,
+    buffers: Box<[u8]>,
+    buf_len: usize,
+    armed: bool,
+    pending: VecDeque<(SocketAddr, VrtPacketOwned)>,
+    truncated: u64,
+    malformed: u64,
+}
+
+impl IoUringReceiver {
+    /// Creates a new IoUringReceiver with a socket bound to `addr`
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Self::from_socket(UdpSocket::bind(addr)?)
+    }
+
+    /// Creates a new IoUringReceiver receiving from `socket` into [`DEFAULT_URING_BUFFERS`]
+    /// buffers of [`DEFAULT_MAX_DATAGRAM_LEN`] bytes
+    pub fn from_socket(socket: UdpSocket) -> io::Result<Self> {
+        Self::with_buffers(socket, DEFAULT_URING_BUFFERS, DEFAULT_MAX_DATAGRAM_LEN)
+    }
+
+    /// Creates a new IoUringReceiver receiving from `socket` into `buffers` buffers, a power of
+    /// two of at most 32768, each holding a datagram of up to `max_datagram_len` bytes; longer
+    /// datagrams are truncated
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidInput`] for another number of buffers,
+    /// and the error of the kernel if io_uring or multishot `recvmsg` is unavailable.
+    pub fn with_buffers(
+        socket: UdpSocket,
+        buffers: u16,
+        max_datagram_len: usize,
+    ) -> io::Result<Self> {
+        if !buffers.is_power_of_two() || buffers > 32768 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the number of io_uring buffers must be a power of two of at most 32768",
+            ));
+        }
+        let buf_len = RECVMSG_OUT_LEN + NAME_LEN + max_datagram_len;
+        let ring = IoUring::new(32)?;
+        let mut buf_ring = BufRing::new(buffers)?;
+        let buffers = vec![0u8; buf_len * buf_ring.entries as usize].into_boxed_slice();
+        // SAFETY: the entries of the ring stay allocated until after the ring is closed
+        #[allow(unsafe_code)]
+        unsafe {
+            ring.submitter().register_buf_ring_with_flags(
+                buf_ring.ptr.as_ptr() as u64,
+                buf_ring.entries,
+                BUFFER_GROUP,
+                0,
+            )?;
+        }
+        for bid in 0..buf_ring.entries {
+            let addr = buffers[bid as usize * buf_len..].as_ptr() as u64;
+            buf_ring.push(addr, buf_len as u32, bid);
+        }
+        buf_ring.publish();
+
+        // SAFETY: an all-zero msghdr is valid, with no name, iovecs or control data
+        #[allow(unsafe_code)]
+        let mut msghdr: Box<libc::msghdr> = Box::new(unsafe { mem::zeroed() });
+        msghdr.msg_namelen = NAME_LEN as libc::socklen_t;
+
+        let mut receiver = IoUringReceiver {
+            ring,
+            socket,
+            msghdr,
+            buf_ring,
+            buffers,
+            buf_len,
+            armed: false,
+            pending: VecDeque::new(),
+            truncated: 0,
+            malformed: 0,
+        };
+        receiver.arm()?;
+        Ok(receiver)
+    }
+
+    /// Returns the underlying socket
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Returns the number of truncated datagrams dropped
+    pub fn truncated(&self) -> u64 {
+        self.truncated
+    }
+
+    /// Returns the number of malformed datagrams dropped
+    pub fn malformed(&self) -> u64 {
+        self.malformed
+    }
+
+    /// Submits the multishot receive, which the kernel ends when it runs out of buffers
+    fn arm(&mut self) -> io::Result<()> {
+        let recv = opcode::RecvMsgMulti::new(
+            Fd(self.socket.as_raw_fd()),
+            &*self.msghdr as *const libc::msghdr,
+            BUFFER_GROUP,
+        )
+        .build()
+        .user_data(RECV);
+        // SAFETY: the msghdr and the registered buffers outlive the request, which is
+        // cancelled before they are freed
+        #[allow(unsafe_code)]
+        unsafe {
+            self.ring
+                .submission()
+                .push(&recv)
+                .map_err(|_| io::Error::other("io_uring queue full"))?;
+        }
+        self.ring.submit()?;
+        self.armed = true;
+        Ok(())
+    }
+
+    /// Receives the next packet, blocking until a datagram holding valid packets arrives
+    pub fn recv_packet(&mut self) -> io::Result<(SocketAddr, VrtPacketOwned)> {
+        loop {
+            if let Some(packet) = self.pending.pop_front() {
+                return Ok(packet);
+            }
+            if !self.armed {
+                self.arm()?;
+            }
+            self.ring.submit_and_wait(1)?;
+            let completions: Vec<_> = self
+                .ring
+                .completion()
+                .filter(|cqe| cqe.user_data() == RECV)
+                .map(|cqe| (cqe.result(), cqe.flags()))
+                .collect();
+            for (result, flags) in completions {
+                if !cqueue::more(flags) {
+                    self.armed = false;
+                }
+                if let Some(bid) = cqueue::buffer_select(flags) {
+                    self.receive(bid);
+                }
+                if result == -libc::ENOBUFS {
+                    // the datagram stays queued on the socket until the receive is rearmed
+                    continue;
+                }
+                if result < 0 {
+                    return Err(io::Error::from_raw_os_error(-result));
+                }
+            }
+        }
+    }
+
+    /// Parses the datagram in buffer `bid` and returns the buffer to the ring
+    fn receive(&mut self, bid: u16) {
+        let start = bid as usize * self.buf_len;
+        let buffer = &self.buffers[start..start + self.buf_len];
+        match RecvMsgOut::parse(buffer, &self.msghdr) {
+            Ok(out) if out.is_payload_truncated() => self.truncated += 1,
+            Ok(out) => match (
+                source_addr(out.name_data()),
+                parse_datagram(out.payload_data()),
+            ) {
+                (Some(source), Some(packets)) => self
+                    .pending
+                    .extend(packets.into_iter().map(|p| (source, p))),
+                _ => self.malformed += 1,
+            },
+            Err(()) => self.malformed += 1,
+        }
+        self.buf_ring
+            .push(buffer.as_ptr() as u64, self.buf_len as u32, bid);
+        self.buf_ring.publish();
+    }
+}
+
+impl Iterator for IoUringReceiver {
+    type Item = io::Result<(SocketAddr, VrtPacketOwned)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.recv_packet())
+    }
+}
+
+impl Drop for IoUringReceiver {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        // wait for the receive to end so the kernel no longer writes into the buffers
+        let cancel = opcode::AsyncCancel::new(RECV).build().user_data(CANCEL);
+        // SAFETY: the cancel request refers to no memory
+        #[allow(unsafe_code)]
+        let pushed = unsafe { self.ring.submission().push(&cancel).is_ok() };
+        while pushed && self.armed && self.ring.submit_and_wait(1).is_ok() {
+            for cqe in self.ring.completion() {
+                if cqe.user_data() == RECV && !cqueue::more(cqe.flags()) {
+                    self.armed = false;
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for IoUringReceiver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IoUringReceiver")
+            .field("socket", &self.socket)
+            .field("buffers", &self.buf_ring.entries)
+            .field("buf_len", &self.buf_len)
+            .field("pending", &self.pending.len())
+            .field("truncated", &self.truncated)
+            .field("malformed", &self.malformed)
+            .finish()
+    }
+}
+
+/// Decodes the `sockaddr_in` or `sockaddr_in6` source address written by the kernel
+fn source_addr(name: &[u8]) -> Option<SocketAddr> {
+    let family = u16::from_ne_bytes([*name.first()?, *name.get(1)?]);
+    let port = u16::from_be_bytes([*name.get(2)?, *name.get(3)?]);
+    match family as libc::c_int {
+        libc::AF_INET => {
+            let ip: [u8; 4] = name.get(4..8)?.try_into().ok()?;
+            Some(SocketAddr::from((Ipv4Addr::from(ip), port)))
+        }
+        libc::AF_INET6 => {
+            let flowinfo = u32::from_be_bytes(name.get(4..8)?.try_into().ok()?);
+            let ip: [u8; 16] = name.get(8..24)?.try_into().ok()?;
+            let scope_id = u32::from_ne_bytes(name.get(24..28)?.try_into().ok()?);
+            let addr = SocketAddrV6::new(Ipv6Addr::from(ip), port, flowinfo, scope_id);
+            Some(SocketAddr::V6(addr))
+        }
+        _ => None,
+    }
+}
+
+/// Page-aligned ring of buffer descriptors shared with the kernel
+struct BufRing {
+    ptr: NonNull<BufRingEntry>,
+    entries: u16,
+    tail: u16,
+}
+
+impl BufRing {
+    fn layout(entries: u16) -> Layout {
+        let size = entries as usize * size_of::<BufRingEntry>();
+        Layout::from_size_align(size, 4096).expect("valid buffer ring layout")
+    }
+
+    fn new(entries: u16) -> io::Result<Self> {
+        // SAFETY: the layout has a non-zero size, as `entries` is a power of two
+        #[allow(unsafe_code)]
+        let ptr = unsafe { alloc::alloc_zeroed(Self::layout(entries)) };
+        let ptr = NonNull::new(ptr as *mut BufRingEntry)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::OutOfMemory))?;
+        Ok(BufRing {
+            ptr,
+            entries,
+            tail: 0,
+        })
+    }
+
+    /// Adds a buffer at the tail of the ring, to be published
+    fn push(&mut self, addr: u64, len: u32, bid: u16) {
+        let index = (self.tail & (self.entries - 1)) as usize;
+        // SAFETY: the index is within the ring, and the kernel reads entries only up to the
+        // published tail
+        #[allow(unsafe_code)]
+        let entry = unsafe { &mut *self.ptr.as_ptr().add(index) };
+        entry.set_addr(addr);
+        entry.set_len(len);
+        entry.set_bid(bid);
+        self.tail = self.tail.wrapping_add(1);
+    }
+
+    /// Makes the buffers pushed so far available to the kernel
+    fn publish(&self) {
+        // SAFETY: the tail is a naturally aligned u16 within the first entry
+        #[allow(unsafe_code)]
+        let tail = unsafe { &*(BufRingEntry::tail(self.ptr.as_ptr()) as *const AtomicU16) };
+        tail.store(self.tail, Ordering::Release);
+    }
+}
+
+impl Drop for BufRing {
+    fn drop(&mut self) {
+        // SAFETY: allocated in `new` with the same layout
+        #[allow(unsafe_code)]
+        unsafe {
+            alloc::dealloc(self.ptr.as_ptr() as *mut u8, Self::layout(self.entries))
+        };
+    }
+}
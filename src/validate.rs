@@ -0,0 +1,251 @@
+//! Conformance checks of packet streams against VITA 49.0 rules and the DIFI profile.
+
+use crate::difi;
+use crate::vrt::{Timestamp, Tsf, Tsi, VrtPacket};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+/// Rule checked by a [`Validator`]
+pub enum Rule {
+    /// The packet type is one defined by VITA 49.0 rather than reserved.
+    PacketType,
+    /// The reserved bits of the Class Identifier are zero.
+    ClassIdReserved,
+    /// A Real Time (Picoseconds) fractional-seconds timestamp is less than one second.
+    RealTimeRange,
+    /// The packets of a stream keep the same integer and fractional-seconds timestamp types.
+    TimestampMode,
+    /// The timestamps of a stream do not go backwards.
+    TimestampOrder,
+    /// The packet count of a stream increments by one, modulo 16, from packet to packet.
+    PacketCount,
+    /// The Class Identifier is that of a DIFI data, context or version packet.
+    DifiClassId,
+    /// The packet type, trailer and Timestamp Mode are those DIFI mandates for the packet
+    /// class.
+    DifiHeader,
+    /// The timestamps are UTC and Real Time (Picoseconds) timestamps.
+    DifiTimestamp,
+    /// A context packet carries exactly the context fields DIFI mandates, and a supported
+    /// Data Packet Payload Format.
+    DifiContextFields,
+}
+
+impl Rule {
+    /// Every VITA 49.0 rule, in report order
+    pub const ALL: [Rule; 6] = [
+        Rule::PacketType,
+        Rule::ClassIdReserved,
+        Rule::RealTimeRange,
+        Rule::TimestampMode,
+        Rule::TimestampOrder,
+        Rule::PacketCount,
+    ];
+
+    /// Every DIFI rule, in report order
+    pub const DIFI: [Rule; 4] = [
+        Rule::DifiClassId,
+        Rule::DifiHeader,
+        Rule::DifiTimestamp,
+        Rule::DifiContextFields,
+    ];
+
+    /// Returns the short name of the rule, such as `packet-count`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::PacketType => "packet-type",
+            Rule::ClassIdReserved => "class-id-reserved",
+            Rule::RealTimeRange => "real-time-range",
+            Rule::TimestampMode => "timestamp-mode",
+            Rule::TimestampOrder => "timestamp-order",
+            Rule::PacketCount => "packet-count",
+            Rule::DifiClassId => "difi-class-id",
+            Rule::DifiHeader => "difi-header",
+            Rule::DifiTimestamp => "difi-timestamp",
+            Rule::DifiContextFields => "difi-context-fields",
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A packet failing a [`Rule`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+    /// The rule failed
+    pub rule: Rule,
+    /// Index of the packet, counting from zero
+    pub packet: u64,
+    /// Offset of the packet in bytes from the start of the stream of back-to-back packets
+    pub offset: u64,
+    /// Description of the offending values
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: packet #{} at offset {}: {}",
+            self.rule, self.packet, self.offset, self.message
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct StreamState {
+    count: u8,
+    tsi: Tsi,
+    tsf: Tsf,
+    timestamp: Option<Timestamp>,
+}
+
+/// Packet Stream Validator
+///
+/// Checks each packet of a stream of back-to-back packets, such as a capture, against the
+/// [`Rule`]s and counts the packets failing each rule. Data and context packets sharing a
+/// Stream Identifier are checked as separate streams. Packets are located by their index and
+/// byte offset, which assumes each packet occupies the `packet_size` declared in its header.
+///
+/// The [`Rule::DIFI`] rules are checked too with [`Validator::with_difi`]. Other rules
+/// covering context fields are not checked.
+#[derive(Clone, Debug, Default)]
+pub struct Validator {
+    difi: bool,
+    streams: HashMap<(Option<u32>, bool), StreamState>,
+    packets: u64,
+    offset: u64,
+    failures: BTreeMap<Rule, u64>,
+}
+
+impl Validator {
+    /// Creates a new Validator
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks the [`Rule::DIFI`] rules too, for streams of the DIFI standard flow profile
+    pub fn with_difi(mut self) -> Self {
+        self.difi = true;
+        self
+    }
+
+    /// Checks `packet`, the next packet of the stream, returning the rules it fails
+    pub fn check(&mut self, packet: &VrtPacket<'_>) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let mut fail = |rule, message: String| {
+            violations.push(Violation {
+                rule,
+                packet: self.packets,
+                offset: self.offset,
+                message,
+            })
+        };
+        let header = &packet.header;
+        let packet_type = header.packet_type;
+        if !packet_type.is_data() && !packet_type.is_context() {
+            fail(
+                Rule::PacketType,
+                format!("reserved packet type {}", packet_type.0),
+            );
+        }
+        if let Some(class_id) = packet.class_id {
+            if class_id >> 56 & 0x07 != 0 {
+                fail(
+                    Rule::ClassIdReserved,
+                    format!("Class Identifier {:#018x}", class_id),
+                );
+            }
+        }
+        if let (Tsf::TSF_REAL_TIME, Some(tsf)) = (header.tsf, packet.tsf) {
+            if tsf >= 1_000_000_000_000 {
+                fail(
+                    Rule::RealTimeRange,
+                    format!("fractional-seconds timestamp {} ps", tsf),
+                );
+            }
+        }
+
+        if self.difi {
+            for (rule, message) in difi::deviations(packet) {
+                fail(rule, message);
+            }
+        }
+
+        let key = (packet.stream_id, packet_type.is_context());
+        let timestamp = packet.timestamp();
+        if let Some(previous) = self.streams.get(&key) {
+            if (previous.tsi, previous.tsf) != (header.tsi, header.tsf) {
+                fail(
+                    Rule::TimestampMode,
+                    format!(
+                        "TSI {} TSF {}, previously TSI {} TSF {}",
+                        header.tsi.0, header.tsf.0, previous.tsi.0, previous.tsf.0
+                    ),
+                );
+            } else if let (Some(previous), Some(current)) = (previous.timestamp, timestamp) {
+                if current < previous {
+                    fail(
+                        Rule::TimestampOrder,
+                        format!(
+                            "timestamp {}.{:012}, previously {}.{:012}",
+                            current.integer,
+                            current.fractional,
+                            previous.integer,
+                            previous.fractional
+                        ),
+                    );
+                }
+            }
+            let expected = (previous.count + 1) & 0x0f;
+            if header.packet_count != expected {
+                fail(
+                    Rule::PacketCount,
+                    format!(
+                        "packet count {}, expected {}",
+                        header.packet_count, expected
+                    ),
+                );
+            }
+        }
+        let state = StreamState {
+            count: header.packet_count,
+            tsi: header.tsi,
+            tsf: header.tsf,
+            timestamp: timestamp.or(self.streams.get(&key).and_then(|s| s.timestamp)),
+        };
+        self.streams.insert(key, state);
+
+        for violation in &violations {
+            *self.failures.entry(violation.rule).or_default() += 1;
+        }
+        self.packets += 1;
+        self.offset += header.packet_size as u64 * 4;
+        violations
+    }
+
+    /// Returns the number of packets checked
+    pub fn packets(&self) -> u64 {
+        self.packets
+    }
+
+    /// Returns the byte offset of the next packet
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the number of packets which failed `rule`
+    pub fn failures(&self, rule: Rule) -> u64 {
+        self.failures.get(&rule).copied().unwrap_or(0)
+    }
+
+    /// Returns whether every packet checked passed every rule
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
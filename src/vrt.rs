@@ -4,13 +4,23 @@
 //!
 //! [VITA 49.0-2015 VITA Radio Transport (VRT) Standard]: https://vitastore.dpdcart.com/product/168632
 
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+#[cfg(feature = "std")]
 use nom_derive::NomBE;
 
 /// Minimum VRT Record Size (8 bytes)
 pub const VRT_MINIMUM_RECORD_SIZE: i32 = 8;
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, NomBE)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "std", derive(NomBE))]
 /// VRT Packet Type
+///
+/// The raw 4-bit field rather than an enum, so packet types without a constant here, the
+/// reserved types, are carried through parsing and encoding unchanged.
 pub struct VitaPacketType(pub u8);
 
 impl VitaPacketType {
@@ -26,10 +36,38 @@ impl VitaPacketType {
     pub const IFCONTEXT: u8 = 0x04;
     /// EXTCONTEXT is a custom Extension packet with a context packet stream.
     pub const EXTCONTEXT: u8 = 0x05;
+    /// COMMAND is a VITA 49.2 Command packet: a control, cancellation or acknowledge packet.
+    pub const COMMAND: u8 = 0x06;
+    /// EXTCOMMAND is a VITA 49.2 Extension Command packet.
+    pub const EXTCOMMAND: u8 = 0x07;
+
+    /// Returns true if the packet is an IF Data or Extension Data packet.
+    pub fn is_data(&self) -> bool {
+        self.0 <= Self::EXTDATAWITHSTREAM
+    }
+
+    /// Returns true if the packet is an IF Context or Extension Context packet.
+    pub fn is_context(&self) -> bool {
+        self.0 == Self::IFCONTEXT || self.0 == Self::EXTCONTEXT
+    }
+
+    /// Returns true if the packet is a Command or Extension Command packet.
+    pub fn is_command(&self) -> bool {
+        self.0 == Self::COMMAND || self.0 == Self::EXTCOMMAND
+    }
+
+    /// Returns true if packets of this type carry a Stream Identifier.
+    pub fn has_stream_id(&self) -> bool {
+        self.0 != Self::IFDATA && self.0 != Self::EXTDATA
+    }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, NomBE)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "std", derive(NomBE))]
 /// Integer-Seconds Timestamp Type
+///
+/// The raw 2-bit field rather than an enum, like [`VitaPacketType`].
 pub struct Tsi(pub u8);
 
 impl Tsi {
@@ -43,8 +81,12 @@ impl Tsi {
     pub const TSI_OTHER: Tsi = Tsi(3);
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, NomBE)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "std", derive(NomBE))]
 /// Fractional-Seconds Timestamp Type
+///
+/// The raw 2-bit field rather than an enum, like [`VitaPacketType`].
 pub struct Tsf(pub u8);
 
 impl Tsf {
@@ -58,7 +100,9 @@ impl Tsf {
     pub const TSF_FREE_RUNNING: Tsf = Tsf(3);
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, NomBE)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "std", derive(NomBE))]
 /// Class Identifier
 pub struct ClassId {
     /// Organizationally Unique Identifier assigned by IEEE, VITA, the VRT Profile author, or a reserved OUI.
@@ -69,7 +113,8 @@ pub struct ClassId {
     pub information_class_code: u16,
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 /// VRT Packet Header
 pub struct Header {
     /// VRT Packet Type
@@ -78,6 +123,10 @@ pub struct Header {
     pub c: bool,
     /// Is the VRT Packet Trailer included in the packet?
     pub t: bool,
+    /// Packet-specific indicator bits 25 and 24 of the header, as the two least significant
+    /// bits. Bit 24 of a context packet is the Timestamp Mode (TSM); the other bits are
+    /// reserved in VITA 49.0 and should be zero. Both bits are encoded as they are.
+    pub indicators: u8,
     /// Type of Integer-seconds Timestamp included in the packet.
     pub tsi: Tsi,
     /// Type of Fractional-seconds Timestamp included in the packet.
@@ -88,7 +137,20 @@ pub struct Header {
     pub packet_size: u16,
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+impl Header {
+    /// Returns the smallest `packet_size`, in 32-bit words, which holds the header together
+    /// with the optional fields and trailer its flags declare
+    pub fn min_packet_size(&self) -> u16 {
+        1 + self.packet_type.has_stream_id() as u16
+            + 2 * self.c as u16
+            + (self.tsi != Tsi::TSI_NONE) as u16
+            + 2 * (self.tsf != Tsf::TSF_NONE) as u16
+            + (self.t && self.packet_type.is_data()) as u16
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 /// VRT Packet Trailer
 pub struct Trailer {
     /// Indicates whether or not the timestamp in teh IF Data packet is calibrated to an external reference.
@@ -145,7 +207,290 @@ pub struct Trailer {
     pub associated_context_packet_count: u8,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "std", derive(NomBE))]
+/// Per-field Warning/Error Response word returned in a VITA 49.2 Acknowledge packet
+pub struct WarningErrorResponse(pub u32);
+
+impl WarningErrorResponse {
+    /// The field was not executed.
+    pub const FIELD_NOT_EXECUTED: u32 = 1 << 31;
+    /// The device failed while executing the field.
+    pub const DEVICE_FAILURE: u32 = 1 << 30;
+    /// The field is erroneous.
+    pub const ERRONEOUS_FIELD: u32 = 1 << 29;
+    /// The field parameter is out of range.
+    pub const PARAMETER_OUT_OF_RANGE: u32 = 1 << 28;
+    /// The field parameter has a precision the device does not support.
+    pub const PARAMETER_UNSUPPORTED_PRECISION: u32 = 1 << 27;
+    /// The field value is invalid.
+    pub const FIELD_VALUE_INVALID: u32 = 1 << 26;
+    /// There was a problem with the timestamp of the control packet.
+    pub const TIMESTAMP_PROBLEM: u32 = 1 << 25;
+    /// Executing the field would result in hazardous power levels.
+    pub const HAZARDOUS_POWER_LEVELS: u32 = 1 << 24;
+    /// Executing the field would result in distortion.
+    pub const DISTORTION: u32 = 1 << 23;
+    /// Executing the field would violate in-band power compliance.
+    pub const IN_BAND_POWER_COMPLIANCE: u32 = 1 << 22;
+    /// Executing the field would violate out-of-band power compliance.
+    pub const OUT_OF_BAND_POWER_COMPLIANCE: u32 = 1 << 21;
+    /// Executing the field would cause co-site interference.
+    pub const CO_SITE_INTERFERENCE: u32 = 1 << 20;
+    /// Executing the field would cause regional interference.
+    pub const REGIONAL_INTERFERENCE: u32 = 1 << 19;
+    /// Mask of the user defined warning/error bits.
+    pub const USER_DEFINED_MASK: u32 = 0x0000_0fff;
+
+    /// Returns true if every bit of `flag` is set in the response word.
+    pub fn contains(&self, flag: u32) -> bool {
+        self.0 & flag == flag
+    }
+
+    /// Returns true if the response word reports `flag`.
+    pub fn has(&self, flag: WarningErrorFlag) -> bool {
+        self.contains(flag.bit())
+    }
+
+    /// Returns the standard warnings or errors reported, from the most significant bit.
+    pub fn flags(&self) -> impl Iterator<Item = WarningErrorFlag> + '_ {
+        WarningErrorFlag::ALL
+            .into_iter()
+            .filter(|flag| self.has(*flag))
+    }
+
+    /// Returns the user defined warning/error bits.
+    pub fn user_defined(&self) -> u16 {
+        (self.0 & Self::USER_DEFINED_MASK) as u16
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// Standard warning or error reported by a per-field Warning/Error Response word
+pub enum WarningErrorFlag {
+    /// The field was not executed.
+    FieldNotExecuted,
+    /// The device failed while executing the field.
+    DeviceFailure,
+    /// The field is erroneous.
+    ErroneousField,
+    /// The field parameter is out of range.
+    ParameterOutOfRange,
+    /// The field parameter has a precision the device does not support.
+    ParameterUnsupportedPrecision,
+    /// The field value is invalid.
+    FieldValueInvalid,
+    /// There was a problem with the timestamp of the control packet.
+    TimestampProblem,
+    /// Executing the field would result in hazardous power levels.
+    HazardousPowerLevels,
+    /// Executing the field would result in distortion.
+    Distortion,
+    /// Executing the field would violate in-band power compliance.
+    InBandPowerCompliance,
+    /// Executing the field would violate out-of-band power compliance.
+    OutOfBandPowerCompliance,
+    /// Executing the field would cause co-site interference.
+    CoSiteInterference,
+    /// Executing the field would cause regional interference.
+    RegionalInterference,
+}
+
+impl WarningErrorFlag {
+    /// Every standard warning or error, from the most significant bit of the response word.
+    pub const ALL: [WarningErrorFlag; 13] = [
+        Self::FieldNotExecuted,
+        Self::DeviceFailure,
+        Self::ErroneousField,
+        Self::ParameterOutOfRange,
+        Self::ParameterUnsupportedPrecision,
+        Self::FieldValueInvalid,
+        Self::TimestampProblem,
+        Self::HazardousPowerLevels,
+        Self::Distortion,
+        Self::InBandPowerCompliance,
+        Self::OutOfBandPowerCompliance,
+        Self::CoSiteInterference,
+        Self::RegionalInterference,
+    ];
+
+    /// Returns the bit of the flag in the response word.
+    pub fn bit(self) -> u32 {
+        1 << (31 - self as u32)
+    }
+}
+
+impl fmt::Display for WarningErrorFlag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::FieldNotExecuted => "field not executed",
+            Self::DeviceFailure => "device failure",
+            Self::ErroneousField => "erroneous field",
+            Self::ParameterOutOfRange => "parameter out of range",
+            Self::ParameterUnsupportedPrecision => "parameter unsupported precision",
+            Self::FieldValueInvalid => "field value invalid",
+            Self::TimestampProblem => "timestamp problem",
+            Self::HazardousPowerLevels => "hazardous power levels",
+            Self::Distortion => "distortion",
+            Self::InBandPowerCompliance => "in-band power compliance",
+            Self::OutOfBandPowerCompliance => "out-of-band power compliance",
+            Self::CoSiteInterference => "co-site interference",
+            Self::RegionalInterference => "regional interference",
+        })
+    }
+}
+
+/// Bits of a Warning or Error Indicator Field 0 word which announce a field, and with it a
+/// Warning/Error Response word: bits 30 to 8, as for Context Indicator Field 0
+pub(crate) const INDICATOR_FIELD_BITS: u32 = 0x7fff_ff00;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// The per-field Warning/Error Response words following a Warning or Error Indicator Field 0
+/// word in a VITA 49.2 Acknowledge packet, as returned by
+/// [`parse_vrt_warning_error_responses`](crate::parse_vrt_warning_error_responses)
+pub struct WarningErrorResponses<'a> {
+    /// The Warning or Error Indicator Field 0 word
+    pub indicators: u32,
+    words: &'a [u8],
+}
+
+impl<'a> WarningErrorResponses<'a> {
+    pub(crate) fn new(indicators: u32, words: &'a [u8]) -> Self {
+        WarningErrorResponses { indicators, words }
+    }
+
+    /// Returns the number of response words, one for each field announced by the indicators
+    pub fn len(&self) -> usize {
+        self.words.len() / 4
+    }
+
+    /// Returns true if no field is announced
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Returns the response word of the field with Context Indicator Field 0 bit `bit`, if
+    /// that field is announced
+    pub fn get(&self, bit: u32) -> Option<WarningErrorResponse> {
+        self.iter()
+            .find(|(b, _)| *b == bit)
+            .map(|(_, response)| response)
+    }
+
+    /// Returns each response word together with the Context Indicator Field 0 bit of its
+    /// field, from the most significant bit
+    pub fn iter(&self) -> impl Iterator<Item = (u32, WarningErrorResponse)> + 'a {
+        let indicators = self.indicators;
+        (8..31)
+            .rev()
+            .filter(move |bit| indicators >> bit & 1 == 1)
+            .zip(self.words.chunks_exact(4))
+            .map(|(bit, word)| {
+                let word = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+                (bit, WarningErrorResponse(word))
+            })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// VRT Packet Timestamp
+pub struct Timestamp {
+    /// Type of the Integer-seconds Timestamp.
+    pub tsi: Tsi,
+    /// Integer-seconds Timestamp value, zero when `tsi` is `TSI_NONE`.
+    pub integer: u32,
+    /// Type of the Fractional-seconds Timestamp.
+    pub tsf: Tsf,
+    /// Fractional-seconds Timestamp value, zero when `tsf` is `TSF_NONE`.
+    pub fractional: u64,
+}
+
+impl Timestamp {
+    /// Returns the timestamp as a duration since the epoch of its integer-seconds timestamp
+    ///
+    /// Only timestamps whose fractional-seconds timestamp is absent or a Real Time (Picoseconds)
+    /// timestamp can be converted; other fractional-seconds timestamps require the sample rate
+    /// or clock rate to interpret, and return None.
+    pub fn as_duration(&self) -> Option<core::time::Duration> {
+        let nanos = match self.tsf {
+            Tsf::TSF_NONE => 0,
+            Tsf::TSF_REAL_TIME if self.fractional < 1_000_000_000_000 => {
+                (self.fractional / 1000) as u32
+            }
+            _ => return None,
+        };
+        Some(core::time::Duration::new(self.integer as u64, nanos))
+    }
+
+    /// Returns the time from `earlier` to this timestamp in seconds, negative if this
+    /// timestamp is the earlier of the two
+    ///
+    /// Sample Count timestamps are converted with `sample_rate`, in Hz, and count the samples
+    /// within the second when an integer-seconds timestamp is present. Returns None if the
+    /// timestamps differ in type, for Free Running timestamps, or for Sample Count timestamps
+    /// without a sample rate.
+    pub fn seconds_since(&self, earlier: &Timestamp, sample_rate: Option<f64>) -> Option<f64> {
+        if (self.tsi, self.tsf) != (earlier.tsi, earlier.tsf) {
+            return None;
+        }
+        let integer = self.integer as i128 - earlier.integer as i128;
+        let fractional = self.fractional as i128 - earlier.fractional as i128;
+        match self.tsf {
+            Tsf::TSF_NONE => Some(integer as f64),
+            Tsf::TSF_REAL_TIME => Some((integer * 1_000_000_000_000 + fractional) as f64 / 1e12),
+            Tsf::TSF_SAMPLE_COUNT => match sample_rate {
+                Some(rate) if rate > 0.0 => {
+                    Some((integer as f64 * rate + fractional as f64) / rate)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Timestamps are ordered by the type of their Integer-seconds Timestamp, then by its value,
+/// then by the type of their Fractional-seconds Timestamp, then by its value.
+///
+/// Timestamps which share both types are therefore ordered in time. Integer-seconds
+/// Timestamps with different epochs cannot be compared in time, so all timestamps of one type
+/// sort before those of the next in the order None, UTC, GPS, Other. Within a second,
+/// timestamps which differ only in the type of their Fractional-seconds Timestamp are ordered
+/// by that type in the order None, Sample Count, Real Time, Free Running. The ordering is
+/// consistent with `Eq`.
+impl Ord for Timestamp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.tsi.0, self.integer, self.tsf.0, self.fractional).cmp(&(
+            other.tsi.0,
+            other.integer,
+            other.tsf.0,
+            other.fractional,
+        ))
+    }
+}
+
+impl PartialOrd for Timestamp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Context Indicator Field 0 bit of the Sample Rate field
+#[cfg(feature = "std")]
+pub(crate) const CIF0_SAMPLE_RATE: u32 = 1 << 21;
+
+/// Encodes `value` as a 64-bit context field with the radix point to the right of bit 20
+#[cfg(feature = "std")]
+pub(crate) fn radix20(value: f64) -> [u8; 8] {
+    ((value * (1u64 << 20) as f64).round() as i64).to_be_bytes()
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 /// A VRT Packet
 pub struct VrtPacket<'a> {
     /// VRT Packet Header
@@ -163,3 +508,149 @@ pub struct VrtPacket<'a> {
     /// Optional VRT Packet Trailer
     pub trailer: Option<Trailer>,
 }
+
+impl VrtPacket<'_> {
+    /// Returns the packet's timestamp, or None if the packet has neither an Integer-seconds
+    /// nor a Fractional-seconds Timestamp
+    pub fn timestamp(&self) -> Option<Timestamp> {
+        if self.tsi.is_none() && self.tsf.is_none() {
+            return None;
+        }
+        Some(Timestamp {
+            tsi: self.header.tsi,
+            integer: self.tsi.unwrap_or(0),
+            tsf: self.header.tsf,
+            fractional: self.tsf.unwrap_or(0),
+        })
+    }
+
+    /// Compares two packets by their timestamps, for use with `sort_by` and when merging
+    /// streams
+    ///
+    /// Packets without a timestamp sort before those with one, and timestamps are compared as
+    /// described for [`Timestamp`]. Packets with equal timestamps compare equal, so a stable
+    /// sort keeps them in their original order.
+    pub fn by_time(a: &Self, b: &Self) -> Ordering {
+        a.timestamp().cmp(&b.timestamp())
+    }
+}
+
+impl fmt::Display for VitaPacketType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Self::IFDATA => f.write_str("IF Data"),
+            Self::IFDATAWITHSTREAM => f.write_str("IF Data with Stream ID"),
+            Self::EXTDATA => f.write_str("Extension Data"),
+            Self::EXTDATAWITHSTREAM => f.write_str("Extension Data with Stream ID"),
+            Self::IFCONTEXT => f.write_str("IF Context"),
+            Self::EXTCONTEXT => f.write_str("Extension Context"),
+            Self::COMMAND => f.write_str("Command"),
+            Self::EXTCOMMAND => f.write_str("Extension Command"),
+            n => write!(f, "Packet Type {}", n),
+        }
+    }
+}
+
+impl fmt::Display for Tsi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match *self {
+            Tsi::TSI_NONE => "None",
+            Tsi::TSI_UTC => "UTC",
+            Tsi::TSI_GPS => "GPS",
+            _ => "Other",
+        })
+    }
+}
+
+impl fmt::Display for Tsf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match *self {
+            Tsf::TSF_NONE => "None",
+            Tsf::TSF_SAMPLE_COUNT => "Sample Count",
+            Tsf::TSF_REAL_TIME => "Real Time",
+            _ => "Free Running",
+        })
+    }
+}
+
+/// Summarizes the packet on one line, for example
+/// `IF Data with Stream ID, stream 0x00000001, count 0, 6 words, TSI UTC 10, TSF Real Time 5,
+/// 4 byte payload`
+impl fmt::Display for VrtPacket<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.header.packet_type)?;
+        if let Some(stream_id) = self.stream_id {
+            write!(f, ", stream {:#010x}", stream_id)?;
+        }
+        if let Some(class_id) = self.class_id {
+            write!(f, ", class {:#018x}", class_id)?;
+        }
+        write!(
+            f,
+            ", count {}, {} words",
+            self.header.packet_count, self.header.packet_size
+        )?;
+        if let Some(tsi) = self.tsi {
+            write!(f, ", TSI {} {}", self.header.tsi, tsi)?;
+        }
+        if let Some(tsf) = self.tsf {
+            write!(f, ", TSF {} {}", self.header.tsf, tsf)?;
+        }
+        write!(f, ", {} byte payload", self.data_payload.len())?;
+        if self.trailer.is_some() {
+            f.write_str(", trailer")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// A VRT Packet which owns its data payload
+pub struct VrtPacketOwned {
+    /// VRT Packet Header
+    pub header: Header,
+    /// Optional Stream Id
+    pub stream_id: Option<u32>,
+    /// Optional Class Id
+    pub class_id: Option<u64>,
+    /// Optional Integer-Seconds Timestamp
+    pub tsi: Option<u32>,
+    /// Optional Fractional-Seconds Timestamp
+    pub tsf: Option<u64>,
+    /// Data Payload
+    pub data_payload: Vec<u8>,
+    /// Optional VRT Packet Trailer
+    pub trailer: Option<Trailer>,
+}
+
+#[cfg(feature = "alloc")]
+impl VrtPacketOwned {
+    /// Returns a VrtPacket borrowing the data payload of this packet
+    pub fn as_packet(&self) -> VrtPacket<'_> {
+        VrtPacket {
+            header: self.header,
+            stream_id: self.stream_id,
+            class_id: self.class_id,
+            tsi: self.tsi,
+            tsf: self.tsf,
+            data_payload: &self.data_payload,
+            trailer: self.trailer,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<&VrtPacket<'_>> for VrtPacketOwned {
+    fn from(packet: &VrtPacket<'_>) -> Self {
+        VrtPacketOwned {
+            header: packet.header,
+            stream_id: packet.stream_id,
+            class_id: packet.class_id,
+            tsi: packet.tsi,
+            tsf: packet.tsf,
+            data_payload: packet.data_payload.to_vec(),
+            trailer: packet.trailer,
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! JavaScript bindings for parsing and printing packets in the browser.
+//!
+//! Built for `wasm32-unknown-unknown` with `wasm-bindgen`, for example with
+//! `wasm-pack build --features wasm`, this module exports `parsePacket` and `parsePackets`
+//! returning [`Packet`] objects.
+
+use crate::stream::PacketStream;
+use crate::vrt::{VrtPacket, VrtPacketOwned};
+use wasm_bindgen::prelude::*;
+
+/// A parsed packet
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct Packet {
+    packet: VrtPacketOwned,
+}
+
+#[wasm_bindgen]
+impl Packet {
+    /// Returns the packet type
+    #[wasm_bindgen(getter, js_name = packetType)]
+    pub fn packet_type(&self) -> u8 {
+        self.packet.header.packet_type.0
+    }
+
+    /// Returns the packet count
+    #[wasm_bindgen(getter, js_name = packetCount)]
+    pub fn packet_count(&self) -> u8 {
+        self.packet.header.packet_count
+    }
+
+    /// Returns the packet size in 32-bit words
+    #[wasm_bindgen(getter, js_name = packetSize)]
+    pub fn packet_size(&self) -> u16 {
+        self.packet.header.packet_size
+    }
+
+    /// Returns the Stream Identifier, if present
+    #[wasm_bindgen(getter, js_name = streamId)]
+    pub fn stream_id(&self) -> Option<u32> {
+        self.packet.stream_id
+    }
+
+    /// Returns the Class Identifier, if present
+    #[wasm_bindgen(getter, js_name = classId)]
+    pub fn class_id(&self) -> Option<u64> {
+        self.packet.class_id
+    }
+
+    /// Returns the Integer-seconds Timestamp, if present
+    #[wasm_bindgen(getter)]
+    pub fn tsi(&self) -> Option<u32> {
+        self.packet.tsi
+    }
+
+    /// Returns the Fractional-seconds Timestamp, if present
+    #[wasm_bindgen(getter)]
+    pub fn tsf(&self) -> Option<u64> {
+        self.packet.tsf
+    }
+
+    /// Returns a copy of the data payload
+    #[wasm_bindgen(getter)]
+    pub fn payload(&self) -> Vec<u8> {
+        self.packet.data_payload.clone()
+    }
+
+    /// Returns whether the packet has a trailer
+    #[wasm_bindgen(getter, js_name = hasTrailer)]
+    pub fn has_trailer(&self) -> bool {
+        self.packet.trailer.is_some()
+    }
+
+    /// Returns a one-line summary of the packet
+    #[wasm_bindgen(js_name = toString)]
+    pub fn describe(&self) -> String {
+        self.packet.as_packet().to_string()
+    }
+}
+
+impl From<VrtPacketOwned> for Packet {
+    fn from(packet: VrtPacketOwned) -> Self {
+        Packet { packet }
+    }
+}
+
+/// Parses the packet at the start of `bytes`, throwing if it is incomplete or malformed
+#[wasm_bindgen(js_name = parsePacket)]
+pub fn parse_packet(bytes: &[u8]) -> Result<Packet, JsError> {
+    let (_, packet) = VrtPacket::parse(bytes)?;
+    Ok(VrtPacketOwned::from(&packet).into())
+}
+
+/// Parses the back-to-back packets in `bytes`, ignoring a trailing incomplete packet and
+/// throwing if a packet is malformed
+#[wasm_bindgen(js_name = parsePackets)]
+pub fn parse_packets(bytes: &[u8]) -> Result<Vec<Packet>, JsError> {
+    PacketStream::new(bytes)
+        .map(|packet| {
+            packet
+                .map(|p| VrtPacketOwned::from(&p).into())
+                .map_err(JsError::from)
+        })
+        .collect()
+}
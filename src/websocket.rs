@@ -0,0 +1,149 @@
+//! Bridging VRT packets to WebSocket clients.
+
+use crate::vrt::VrtPacket;
+use serde_json::{json, Value};
+use std::fmt;
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use tungstenite::{Message, WebSocket};
+
+/// WebSocket Bridge
+///
+/// Re-frames VRT packets for WebSocket clients, such as browser or WASM dashboards: each packet
+/// is sent to every connected client as a binary message holding the encoded packet. With
+/// metadata enabled, each packet is preceded by a text message holding a JSON object which
+/// describes it, so clients can follow streams without parsing the packets themselves:
+///
+/// ```json
+/// {"packet_type": 1, "stream_id": 1, "class_id": null, "packet_count": 0,
+///  "timestamp": {"tsi": 1, "integer": 10, "tsf": 2, "fractional": 5}, "payload_len": 4}
+/// ```
+///
+/// New clients are accepted with [`WebSocketBridge::accept`]; clients which disconnect are
+/// dropped when sending to them fails. Clients are sent to one after another, so each send
+/// to a client, and each client's handshake, must complete within the write timeout, 100 ms
+/// unless set with [`WebSocketBridge::with_write_timeout`]; clients which cannot keep up are
+/// dropped rather than stalling the others.
+pub struct WebSocketBridge {
+    listener: TcpListener,
+    clients: Vec<(SocketAddr, WebSocket<TcpStream>)>,
+    metadata: bool,
+    write_timeout: Duration,
+}
+
+impl WebSocketBridge {
+    /// Creates a new WebSocketBridge listening on `addr`
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        Ok(WebSocketBridge {
+            listener,
+            clients: Vec::new(),
+            metadata: false,
+            write_timeout: Duration::from_millis(100),
+        })
+    }
+
+    /// Sets the time allowed for sending a message to a client, or for its handshake, before
+    /// the client is dropped, applying to clients accepted from then on
+    ///
+    /// # Panics
+    ///
+    /// Panics if `timeout` is zero.
+    pub fn with_write_timeout(mut self, timeout: Duration) -> Self {
+        assert!(!timeout.is_zero(), "write timeout must not be zero");
+        self.write_timeout = timeout;
+        self
+    }
+
+    /// Sets whether each packet is preceded by a JSON metadata message
+    pub fn with_metadata(mut self, metadata: bool) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Returns the address the bridge is listening on
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Returns the addresses of the connected clients
+    pub fn clients(&self) -> impl Iterator<Item = SocketAddr> + '_ {
+        self.clients.iter().map(|(addr, _)| *addr)
+    }
+
+    /// Accepts the clients waiting to connect without blocking, returning the number accepted
+    ///
+    /// Clients whose WebSocket handshake fails or exceeds the write timeout are not accepted.
+    pub fn accept(&mut self) -> io::Result<usize> {
+        let mut accepted = 0;
+        loop {
+            let (stream, addr) = match self.listener.accept() {
+                Ok(client) => client,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(accepted),
+                Err(e) => return Err(e),
+            };
+            stream.set_nonblocking(false)?;
+            stream.set_read_timeout(Some(self.write_timeout))?;
+            stream.set_write_timeout(Some(self.write_timeout))?;
+            if let Ok(websocket) = tungstenite::accept(stream) {
+                self.clients.push((addr, websocket));
+                accepted += 1;
+            }
+        }
+    }
+
+    /// Encodes `packet` and sends it to every connected client
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidInput`], without sending anything, if
+    /// the packet cannot be encoded.
+    pub fn send(&mut self, packet: &VrtPacket<'_>) -> io::Result<()> {
+        let bytes = packet
+            .to_vec()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid VRT packet"))?;
+        let metadata = match self.metadata {
+            true => Some(metadata(packet).to_string()),
+            false => None,
+        };
+        self.clients.retain_mut(|(_, websocket)| {
+            let sent = match &metadata {
+                Some(metadata) => websocket.send(Message::Text(metadata.clone())).is_ok(),
+                None => true,
+            };
+            sent && websocket.send(Message::Binary(bytes.clone())).is_ok()
+        });
+        Ok(())
+    }
+}
+
+impl fmt::Debug for WebSocketBridge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebSocketBridge")
+            .field("listener", &self.listener)
+            .field("clients", &self.clients().collect::<Vec<_>>())
+            .field("metadata", &self.metadata)
+            .field("write_timeout", &self.write_timeout)
+            .finish()
+    }
+}
+
+/// Returns the JSON metadata describing `packet`
+fn metadata(packet: &VrtPacket<'_>) -> Value {
+    let timestamp = packet.timestamp().map(|t| {
+        json!({
+            "tsi": t.tsi.0,
+            "integer": t.integer,
+            "tsf": t.tsf.0,
+            "fractional": t.fractional,
+        })
+    });
+    json!({
+        "packet_type": packet.header.packet_type.0,
+        "stream_id": packet.stream_id,
+        "class_id": packet.class_id,
+        "packet_count": packet.header.packet_count,
+        "timestamp": timestamp,
+        "payload_len": packet.data_payload.len(),
+    })
+}
@@ -0,0 +1,121 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TestPacket;
+    use crate::vrt::*;
+
+    fn packet(tsf: Tsf, fractional: u64, payload: &[u8]) -> VrtPacket<'_> {
+        TestPacket {
+            tsi: Some(10),
+            tsf: Some((tsf, fractional)),
+            payload,
+            ..TestPacket::default()
+        }
+        .build()
+    }
+
+    #[test]
+    fn test_align_different_starts() {
+        // 1 sample per byte, channel 1 starts 2 samples after channel 0
+        let mut aligner = Aligner::new(2, 1000, 1, 4);
+        let a: Vec<u8> = (0..8).collect();
+        let b: Vec<u8> = (102..110).collect();
+        aligner
+            .push(0, &packet(Tsf::TSF_SAMPLE_COUNT, 0, &a))
+            .unwrap();
+        assert_eq!(aligner.next_block(), None);
+        aligner
+            .push(1, &packet(Tsf::TSF_SAMPLE_COUNT, 2, &b))
+            .unwrap();
+
+        let block = aligner.next_block().unwrap();
+        assert_eq!(block.start, 10_002);
+        assert_eq!(
+            block.channels,
+            vec![vec![2, 3, 4, 5], vec![102, 103, 104, 105]]
+        );
+        assert_eq!(aligner.next_block(), None);
+
+        aligner
+            .push(0, &packet(Tsf::TSF_SAMPLE_COUNT, 8, &[8, 9, 10, 11]))
+            .unwrap();
+        aligner
+            .push(1, &packet(Tsf::TSF_SAMPLE_COUNT, 10, &[110, 111, 112, 113]))
+            .unwrap();
+        let block = aligner.next_block().unwrap();
+        assert_eq!(block.start, 10_006);
+        assert_eq!(
+            block.channels,
+            vec![vec![6, 7, 8, 9], vec![106, 107, 108, 109]]
+        );
+    }
+
+    #[test]
+    fn test_align_offset_and_real_time() {
+        // 1 ms per sample, channel 1 timestamps are 1 sample late
+        let mut aligner = Aligner::new(2, 1000, 2, 2);
+        aligner.set_offset(1, -1);
+        aligner
+            .push(0, &packet(Tsf::TSF_REAL_TIME, 0, &[0, 0, 0, 1, 0, 2, 0, 3]))
+            .unwrap();
+        aligner
+            .push(
+                1,
+                &packet(Tsf::TSF_REAL_TIME, 1_000_000_000, &[1, 0, 1, 1, 1, 2]),
+            )
+            .unwrap();
+        let block = aligner.next_block().unwrap();
+        assert_eq!(block.start, 10_000);
+        assert_eq!(block.channels, vec![vec![0, 0, 0, 1], vec![1, 0, 1, 1]]);
+        assert_eq!(aligner.next_block(), None);
+    }
+
+    #[test]
+    fn test_align_real_time_fractional_period() {
+        // at 3 MS/s a sample period is 333_333.33 ps, so timestamps are truncated
+        let mut aligner = Aligner::new(1, 3_000_000, 1, 200);
+        let samples = [0; 100];
+        for n in [0u64, 100] {
+            let fractional = n * 1_000_000_000_000 / 3_000_000;
+            aligner
+                .push(0, &packet(Tsf::TSF_REAL_TIME, fractional, &samples))
+                .unwrap();
+        }
+        let block = aligner.next_block().unwrap();
+        assert_eq!(block.start, 30_000_000);
+        assert_eq!(block.channels[0].len(), 200);
+    }
+
+    #[test]
+    fn test_align_gap_restarts_channel() {
+        let mut aligner = Aligner::new(2, 1000, 1, 2);
+        aligner
+            .push(0, &packet(Tsf::TSF_SAMPLE_COUNT, 0, &[0, 1, 2, 3]))
+            .unwrap();
+        aligner
+            .push(0, &packet(Tsf::TSF_SAMPLE_COUNT, 8, &[8, 9, 10, 11]))
+            .unwrap();
+        aligner
+            .push(
+                1,
+                &packet(Tsf::TSF_SAMPLE_COUNT, 0, &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]),
+            )
+            .unwrap();
+        let block = aligner.next_block().unwrap();
+        assert_eq!(block.start, 10_008);
+        assert_eq!(block.channels, vec![vec![8, 9], vec![8, 9]]);
+    }
+
+    #[test]
+    fn test_align_requires_timestamp() {
+        let mut aligner = Aligner::new(1, 1000, 1, 2);
+        let mut p = packet(Tsf::TSF_FREE_RUNNING, 0, &[0, 1, 2, 3]);
+        assert_eq!(aligner.push(0, &p), Err(Error::Malformed));
+        p.tsf = None;
+        assert_eq!(aligner.push(0, &p), Err(Error::Malformed));
+    }
+}
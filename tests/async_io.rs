@@ -0,0 +1,110 @@
+#![cfg(feature = "futures")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use futures_util::io::Cursor;
+    use futures_util::{SinkExt, StreamExt};
+    use std::sync::Arc;
+    use tokio::net::UdpSocket;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_STREAM_2: &[u8] = &[
+        0x10, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    fn owned(bytes: &[u8]) -> VrtPacketOwned {
+        VrtPacketOwned::from(&parse_vrt_packet(bytes).unwrap().1)
+    }
+
+    #[tokio::test]
+    async fn test_async_packet_reader() {
+        let bytes = [VRT_STATIC_STREAM_1, VRT_STATIC_STREAM_2].concat();
+        let packets: Vec<_> = AsyncPacketReader::new(Cursor::new(bytes))
+            .map(|p| p.unwrap())
+            .collect()
+            .await;
+        assert_eq!(
+            packets,
+            vec![owned(VRT_STATIC_STREAM_1), owned(VRT_STATIC_STREAM_2)]
+        );
+
+        let truncated = VRT_STATIC_STREAM_1[..10].to_vec();
+        let mut reader = AsyncPacketReader::new(Cursor::new(truncated));
+        let err = reader.next().await.unwrap().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        assert!(reader.next().await.is_none());
+
+        let mut reader = AsyncPacketReader::new(Cursor::new(vec![0x10, 0x00, 0x00, 0x00]));
+        let err = reader.next().await.unwrap().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[tokio::test]
+    async fn test_async_packet_writer() {
+        let mut writer = AsyncPacketWriter::new(Cursor::new(Vec::new()));
+        writer.feed(owned(VRT_STATIC_STREAM_1)).await.unwrap();
+        writer.send(owned(VRT_STATIC_STREAM_2)).await.unwrap();
+        let mut invalid = owned(VRT_STATIC_STREAM_2);
+        invalid.stream_id = None;
+        let err = writer.send(invalid).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        writer.close().await.unwrap();
+        let bytes = writer.into_inner().into_inner();
+        assert_eq!(bytes, [VRT_STATIC_STREAM_1, VRT_STATIC_STREAM_2].concat());
+    }
+
+    #[tokio::test]
+    async fn test_async_datagram_receiver_sender() {
+        let socket = Arc::new(UdpSocket::bind("127.0.0.1:0").await.unwrap());
+        let target = socket.local_addr().unwrap();
+        let mut receiver = AsyncDatagramReceiver::new(move |mut buf: Vec<u8>| {
+            let socket = socket.clone();
+            async move {
+                let (len, source) = socket.recv_from(&mut buf).await?;
+                Ok((buf, len, source))
+            }
+        })
+        .with_max_datagram_len(32);
+
+        let socket = Arc::new(UdpSocket::bind("127.0.0.1:0").await.unwrap());
+        let source = socket.local_addr().unwrap();
+        let mut sender = AsyncDatagramSender::new(move |buf: Vec<u8>| {
+            let socket = socket.clone();
+            async move {
+                socket.send_to(&buf, target).await?;
+                Ok(buf)
+            }
+        });
+        sender.send(owned(VRT_STATIC_STREAM_1)).await.unwrap();
+        let mut invalid = owned(VRT_STATIC_STREAM_2);
+        invalid.stream_id = None;
+        let err = sender.send(invalid).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+        // a datagram holding two packets, then an oversized and a malformed one
+        let raw = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let both = [VRT_STATIC_STREAM_2, VRT_STATIC_STREAM_2].concat();
+        raw.send_to(&both, target).await.unwrap();
+        raw.send_to(&[0; 40], target).await.unwrap();
+        raw.send_to(&[0x10, 0x00, 0x00, 0x09], target)
+            .await
+            .unwrap();
+        sender.send(owned(VRT_STATIC_STREAM_2)).await.unwrap();
+
+        let received = receiver.next().await.unwrap().unwrap();
+        assert_eq!(received, (source, owned(VRT_STATIC_STREAM_1)));
+        for _ in 0..2 {
+            let received = receiver.next().await.unwrap().unwrap();
+            assert_eq!(received.1, owned(VRT_STATIC_STREAM_2));
+        }
+        let received = receiver.next().await.unwrap().unwrap();
+        assert_eq!(received, (source, owned(VRT_STATIC_STREAM_2)));
+        assert_eq!((receiver.truncated(), receiver.malformed()), (1, 1));
+    }
+}
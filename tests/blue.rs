@@ -0,0 +1,81 @@
+#![cfg(feature = "blue")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use std::io::Cursor;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    // Sample Rate of 1 MHz and RF Reference Frequency of 100 MHz
+    static VRT_STATIC_CONTEXT_1: &[u8] = &[
+        0x40, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00, 0x01, 0x08, 0x20, 0x00, 0x00, 0x00, 0x00, 0x5f,
+        0x5e, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xf4, 0x24, 0x00, 0x00, 0x00,
+    ];
+
+    fn f64_at(bytes: &[u8], offset: usize) -> f64 {
+        f64::from_be_bytes(bytes[offset..offset + 8].try_into().unwrap())
+    }
+
+    fn i32_at(bytes: &[u8], offset: usize) -> i32 {
+        i32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_blue_exporter() {
+        let (_, context) = parse_vrt_packet(VRT_STATIC_CONTEXT_1).unwrap();
+        let (_, data) = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap();
+        let mut exporter = BlueExporter::new(Cursor::new(Vec::new()), "CI").unwrap();
+        exporter.push(&context).unwrap();
+        exporter.push(&data).unwrap();
+        exporter.push(&data).unwrap();
+        assert_eq!(exporter.samples(), 2);
+        let file = exporter.finish().unwrap().into_inner();
+
+        assert_eq!(&file[0..12], b"BLUEIEEEIEEE");
+        assert_eq!(i32_at(&file, 48), 1000);
+        assert_eq!(&file[52..54], b"CI");
+        assert_eq!(f64_at(&file, 32), 512.0);
+        assert_eq!(f64_at(&file, 40), 8.0);
+        assert_eq!(f64_at(&file, 56), 631_152_010.0);
+        assert_eq!(f64_at(&file, 264), 1e-6);
+        assert_eq!(
+            &file[512..520],
+            [0xca, 0xfe, 0xf0, 0x0d, 0xca, 0xfe, 0xf0, 0x0d]
+        );
+
+        // RF_FREQ, SAMPLE_RATE and STREAM_ID keywords in the block after the data
+        assert_eq!(i32_at(&file, 24), 2);
+        assert_eq!(i32_at(&file, 28), 24 + 32 + 24);
+        let ext = &file[1024..];
+        assert_eq!(ext.len(), 80);
+        assert_eq!((i32_at(ext, 0), ext[6], ext[7]), (24, 7, b'D'));
+        assert_eq!(f64_at(ext, 8), 100e6);
+        assert_eq!(&ext[16..23], b"RF_FREQ");
+        assert_eq!(f64_at(ext, 32), 1e6);
+        assert_eq!(&ext[40..51], b"SAMPLE_RATE");
+        assert_eq!((ext[63], i32_at(ext, 64)), (b'L', 1));
+        assert_eq!(&ext[68..77], b"STREAM_ID");
+    }
+
+    #[test]
+    fn test_blue_exporter_type_2000() {
+        let (_, data) = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap();
+        let mut exporter = BlueExporter::new(Cursor::new(Vec::new()), "SI")
+            .unwrap()
+            .with_subsize(2)
+            .with_sample_rate(4.0);
+        exporter.push(&data).unwrap();
+        let file = exporter.finish().unwrap().into_inner();
+        assert_eq!(i32_at(&file, 48), 2000);
+        assert_eq!(i32_at(&file, 276), 2);
+        assert_eq!(f64_at(&file, 264), 0.25);
+        assert_eq!(f64_at(&file, 288), 0.5);
+
+        assert!(BlueExporter::new(Cursor::new(Vec::new()), "CQ").is_err());
+    }
+}
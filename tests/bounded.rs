@@ -0,0 +1,58 @@
+#![cfg(feature = "heapless")]
+extern crate vrt;
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TestPacket;
+    use crate::vrt::*;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    fn packet(stream_id: u32, packet_count: u8) -> VrtPacket<'static> {
+        TestPacket {
+            stream_id,
+            packet_count,
+            ..TestPacket::default()
+        }
+        .build()
+    }
+
+    #[test]
+    fn test_heapless_packet() {
+        let (_, packet) = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap();
+        let owned = HeaplessPacket::<4>::try_from(&packet).unwrap();
+        assert_eq!(owned.as_packet(), packet);
+        assert_eq!(
+            HeaplessPacket::<3>::try_from(&packet),
+            Err(Error::BufferTooSmall)
+        );
+
+        assert_eq!(
+            packet.to_heapless::<32>().unwrap().as_slice(),
+            VRT_STATIC_STREAM_1
+        );
+        assert_eq!(packet.to_heapless::<20>(), Err(Error::BufferTooSmall));
+    }
+
+    #[test]
+    fn test_heapless_stream_tracker() {
+        let mut tracker = HeaplessStreamTracker::<2>::new();
+        assert_eq!(tracker.update(&packet(1, 0)), Ok(Continuity::First));
+        assert_eq!(tracker.update(&packet(2, 7)), Ok(Continuity::First));
+        assert_eq!(tracker.update(&packet(1, 3)), Ok(Continuity::Lost(2)));
+        assert_eq!(tracker.update(&packet(3, 0)), Err(Error::BufferTooSmall));
+        assert_eq!(tracker.update(&packet(2, 8)), Ok(Continuity::InOrder));
+
+        assert_eq!(tracker.stats(Some(1)).unwrap().lost, 2);
+        assert_eq!(tracker.stats(Some(3)), None);
+        assert_eq!(tracker.iter().count(), 2);
+        let window = tracker.take_window();
+        assert_eq!(window[&Some(2)].received, 2);
+        assert_eq!(tracker.take_window()[&Some(1)].received, 0);
+    }
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TestPacket;
+    use crate::vrt::*;
+
+    fn packet(packet_count: u8, payload: &[u8]) -> VrtPacket<'_> {
+        TestPacket {
+            packet_count,
+            payload,
+            ..TestPacket::default()
+        }
+        .build()
+    }
+
+    #[test]
+    fn test_burst_contiguous() {
+        let mut assembler = BurstAssembler::new(GapPolicy::Error);
+        assembler.push(&packet(15, &[1, 2, 3, 4])).unwrap();
+        assembler.push(&packet(0, &[5, 6, 7, 8])).unwrap();
+        assembler.push(&packet(0, &[5, 6, 7, 8])).unwrap();
+        let burst = assembler.finish();
+        assert_eq!(burst.stream_id, Some(1));
+        assert_eq!(burst.packets, 2);
+        assert_eq!(burst.samples, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(assembler.burst(), &Burst::default());
+    }
+
+    #[test]
+    fn test_burst_gap_error() {
+        let mut assembler = BurstAssembler::new(GapPolicy::Error);
+        assembler.push(&packet(1, &[1, 2, 3, 4])).unwrap();
+        let res = assembler.push(&packet(3, &[5, 6, 7, 8]));
+        assert_eq!(res, Err(Error::Discontinuity));
+        assembler.push(&packet(2, &[9, 9, 9, 9])).unwrap();
+        assert_eq!(assembler.burst().samples, vec![1, 2, 3, 4, 9, 9, 9, 9]);
+    }
+
+    #[test]
+    fn test_burst_gap_zero_fill() {
+        let mut assembler = BurstAssembler::new(GapPolicy::ZeroFill);
+        assembler.push(&packet(1, &[1, 2, 3, 4])).unwrap();
+        assembler.push(&packet(4, &[5, 6, 7, 8])).unwrap();
+        let burst = assembler.finish();
+        assert_eq!(burst.filled, 2);
+        assert_eq!(burst.samples.len(), 16);
+        assert_eq!(&burst.samples[4..12], &[0; 8]);
+        assert_eq!(&burst.samples[12..], &[5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_burst_gap_truncate() {
+        let mut assembler = BurstAssembler::new(GapPolicy::Truncate);
+        assembler.push(&packet(1, &[1, 2, 3, 4])).unwrap();
+        assembler.push(&packet(3, &[5, 6, 7, 8])).unwrap();
+        assembler.push(&packet(4, &[9, 9, 9, 9])).unwrap();
+        let burst = assembler.finish();
+        assert!(burst.truncated);
+        assert_eq!(burst.samples, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_burst_rejects_other_stream() {
+        let mut assembler = BurstAssembler::new(GapPolicy::ZeroFill);
+        assembler.push(&packet(1, &[1, 2, 3, 4])).unwrap();
+        let mut other = packet(2, &[5, 6, 7, 8]);
+        other.stream_id = Some(2);
+        assert_eq!(assembler.push(&other), Err(Error::Discontinuity));
+    }
+}
@@ -0,0 +1,372 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use std::io::Cursor;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_STREAM_2: &[u8] = &[
+        0x10, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    #[test]
+    fn test_index_entry_roundtrip() {
+        let entry = IndexEntry {
+            offset: 0x0102_0304_0506_0708,
+            len: 24,
+            stream_id: Some(0xdead_beef),
+            timestamp: Some(Timestamp {
+                tsi: Tsi::TSI_GPS,
+                integer: 12345,
+                tsf: Tsf::TSF_REAL_TIME,
+                fractional: 999_999_999_999,
+            }),
+        };
+        assert_eq!(IndexEntry::from_bytes(&entry.to_bytes()), entry);
+        let entry = IndexEntry {
+            stream_id: None,
+            timestamp: None,
+            ..entry
+        };
+        assert_eq!(IndexEntry::from_bytes(&entry.to_bytes()), entry);
+    }
+
+    #[test]
+    fn test_recorder() {
+        let mut recorder = Recorder::new(Vec::new(), Vec::new()).expect("failed to create");
+        let first = recorder.record(VRT_STATIC_STREAM_1).unwrap();
+        let mut with_trailing = VRT_STATIC_STREAM_2.to_vec();
+        with_trailing.extend_from_slice(&[0xff; 3]);
+        let second = recorder.record(&with_trailing).unwrap();
+        let err = recorder.record(&VRT_STATIC_STREAM_2[..6]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(recorder.packets(), 2);
+
+        assert_eq!(first.offset, 0);
+        assert_eq!(first.stream_id, Some(1));
+        assert_eq!(
+            first.timestamp.map(|t| (t.integer, t.fractional)),
+            Some((10, 5))
+        );
+        assert_eq!(second.offset, 24);
+        assert_eq!(second.len, 12);
+        assert_eq!(second.timestamp, None);
+
+        let (data, index) = recorder.into_inner().unwrap();
+        assert_eq!(&data[..24], VRT_STATIC_STREAM_1);
+        assert_eq!(&data[24..], VRT_STATIC_STREAM_2);
+        assert_eq!(&index[0..4], INDEX_MAGIC);
+        assert_eq!(index.len(), INDEX_HEADER_LEN + 2 * IndexEntry::LEN);
+        let mut record = [0u8; IndexEntry::LEN];
+        record.copy_from_slice(&index[INDEX_HEADER_LEN + IndexEntry::LEN..]);
+        assert_eq!(IndexEntry::from_bytes(&record), second);
+    }
+
+    #[test]
+    fn test_index_path() {
+        assert_eq!(
+            index_path("/tmp/capture.vrt"),
+            std::path::PathBuf::from("/tmp/capture.vrt.idx")
+        );
+    }
+
+    fn packet_with_time(stream_id: u32, tsi: u32, tsf: u64) -> Vec<u8> {
+        let mut bytes = vec![0x10, 0x60, 0x00, 0x05];
+        bytes.extend_from_slice(&stream_id.to_be_bytes());
+        bytes.extend_from_slice(&tsi.to_be_bytes());
+        bytes.extend_from_slice(&tsf.to_be_bytes());
+        bytes
+    }
+
+    fn capture() -> CaptureReader<Cursor<Vec<u8>>, Cursor<Vec<u8>>> {
+        let mut recorder = Recorder::new(Vec::new(), Vec::new()).expect("failed to create");
+        recorder.record(VRT_STATIC_STREAM_2).unwrap();
+        for n in 0..10 {
+            recorder
+                .record(&packet_with_time(n, 100 + n / 4, n as u64))
+                .unwrap();
+        }
+        let (data, index) = recorder.into_inner().unwrap();
+        CaptureReader::new(Cursor::new(data), Cursor::new(index)).expect("failed to open")
+    }
+
+    #[test]
+    fn test_capture_reader_iterates() {
+        let reader = capture();
+        assert_eq!(reader.len(), 11);
+        let stream_ids: Vec<_> = reader.map(|p| p.unwrap().stream_id.unwrap()).collect();
+        assert_eq!(stream_ids, vec![2, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_capture_reader_seek_to_packet() {
+        let mut reader = capture();
+        reader.seek_to_packet(9).unwrap();
+        let packet = reader.next().unwrap().unwrap();
+        assert_eq!(packet.stream_id, Some(8));
+        reader.seek_to_packet(1).unwrap();
+        let packet = reader.next().unwrap().unwrap();
+        assert_eq!(packet.stream_id, Some(0));
+        assert_eq!(reader.position(), 2);
+        assert!(reader.seek_to_packet(12).is_err());
+        reader.seek_to_packet(11).unwrap();
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_capture_reader_seek_to_time() {
+        let mut reader = capture();
+        let at = |integer, fractional| Timestamp {
+            tsi: Tsi::TSI_UTC,
+            integer,
+            tsf: Tsf::TSF_REAL_TIME,
+            fractional,
+        };
+        assert_eq!(reader.seek_to_time(at(101, 0)).unwrap(), 5);
+        assert_eq!(reader.next().unwrap().unwrap().stream_id, Some(4));
+        assert_eq!(reader.seek_to_time(at(101, 5)).unwrap(), 6);
+        assert_eq!(reader.seek_to_time(at(0, 0)).unwrap(), 1);
+        assert_eq!(reader.seek_to_time(at(200, 0)).unwrap(), 11);
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_capture_reader_trim_into() {
+        let mut reader = capture();
+        let at = |integer, fractional| Timestamp {
+            tsi: Tsi::TSI_UTC,
+            integer,
+            tsf: Tsf::TSF_REAL_TIME,
+            fractional,
+        };
+        let mut recorder = Recorder::new(Vec::new(), Vec::new()).expect("failed to create");
+        let packets = reader
+            .trim_into(at(101, 0), at(102, 0), &mut recorder)
+            .unwrap();
+        assert_eq!(packets, 4);
+        assert_eq!(reader.position(), 9);
+        let (data, index) = recorder.into_inner().unwrap();
+        let trimmed = CaptureReader::new(Cursor::new(data), Cursor::new(index)).unwrap();
+        let stream_ids: Vec<_> = trimmed.map(|p| p.unwrap().stream_id.unwrap()).collect();
+        assert_eq!(stream_ids, vec![4, 5, 6, 7]);
+
+        let mut recorder = Recorder::new(Vec::new(), Vec::new()).expect("failed to create");
+        let packets = reader
+            .trim_into(at(200, 0), at(300, 0), &mut recorder)
+            .unwrap();
+        assert_eq!(packets, 0);
+    }
+
+    #[test]
+    fn test_capture_reader_locate_sample() {
+        let mut recorder = Recorder::new(Vec::new(), Vec::new()).expect("failed to create");
+        // 1 kHz, 4-byte samples, 10 samples per packet, with 10 ms missing before packet 2
+        for (n, ms) in [0u64, 10, 30].iter().enumerate() {
+            let mut bytes = vec![0x10, 0x60, 0x00, 15];
+            bytes.extend_from_slice(&1u32.to_be_bytes());
+            bytes.extend_from_slice(&100u32.to_be_bytes());
+            bytes.extend_from_slice(&(ms * 1_000_000_000).to_be_bytes());
+            bytes.extend_from_slice(&[n as u8; 40]);
+            recorder.record(&bytes).unwrap();
+        }
+        let (data, index) = recorder.into_inner().unwrap();
+        let mut reader =
+            CaptureReader::new(Cursor::new(data), Cursor::new(index)).expect("failed to open");
+        let at = |integer, ms: u64| Timestamp {
+            tsi: Tsi::TSI_UTC,
+            integer,
+            tsf: Tsf::TSF_REAL_TIME,
+            fractional: ms * 1_000_000_000,
+        };
+
+        let location = reader.locate_sample(at(100, 13), 1000.0, 4).unwrap();
+        assert_eq!(
+            location,
+            Some(SampleLocation {
+                packet: 1,
+                sample: 3
+            })
+        );
+        assert_eq!(reader.next().unwrap().unwrap().data_payload[0], 1);
+        let location = reader.locate_sample(at(100, 30), 1000.0, 4).unwrap();
+        assert_eq!(
+            location,
+            Some(SampleLocation {
+                packet: 2,
+                sample: 0
+            })
+        );
+        assert_eq!(reader.locate_sample(at(100, 25), 1000.0, 4).unwrap(), None);
+        assert_eq!(reader.locate_sample(at(100, 40), 1000.0, 4).unwrap(), None);
+        assert_eq!(reader.locate_sample(at(99, 0), 1000.0, 4).unwrap(), None);
+    }
+
+    #[test]
+    fn test_capture_reader_locate_sample_count() {
+        let mut recorder = Recorder::new(Vec::new(), Vec::new()).expect("failed to create");
+        // context packet with a Sample Rate of 100 Hz
+        let mut context = vec![0x40, 0x50, 0x00, 8, 0, 0, 0, 1, 0, 0, 0, 100];
+        context.extend_from_slice(&[0; 8]);
+        context.extend_from_slice(&0x0020_0000u32.to_be_bytes());
+        context.extend_from_slice(&(100u64 << 20).to_be_bytes());
+        recorder.record(&context).unwrap();
+        // 10 samples per packet, the second packet crossing into the next second
+        for (n, count) in [85u64, 95].iter().enumerate() {
+            let mut bytes = vec![0x10, 0x50 | n as u8, 0x00, 15, 0, 0, 0, 1, 0, 0, 0, 100];
+            bytes.extend_from_slice(&count.to_be_bytes());
+            bytes.extend_from_slice(&[n as u8; 40]);
+            recorder.record(&bytes).unwrap();
+        }
+        let (data, index) = recorder.into_inner().unwrap();
+        let mut reader =
+            CaptureReader::new(Cursor::new(data), Cursor::new(index)).expect("failed to open");
+        let at = |integer, fractional| Timestamp {
+            tsi: Tsi::TSI_UTC,
+            integer,
+            tsf: Tsf::TSF_SAMPLE_COUNT,
+            fractional,
+        };
+
+        // the rate of the context packet overrides the 1 kHz fallback
+        let location = reader.locate_sample(at(101, 2), 1000.0, 4).unwrap();
+        assert_eq!(
+            location,
+            Some(SampleLocation {
+                packet: 2,
+                sample: 7
+            })
+        );
+        assert_eq!(reader.position(), 2);
+        let location = reader.locate_sample(at(100, 90), 1000.0, 4).unwrap();
+        assert_eq!(
+            location,
+            Some(SampleLocation {
+                packet: 1,
+                sample: 5
+            })
+        );
+        assert_eq!(reader.locate_sample(at(101, 5), 1000.0, 4).unwrap(), None);
+        // the context packet precedes the first data packet
+        assert_eq!(reader.locate_sample(at(100, 0), 1000.0, 4).unwrap(), None);
+    }
+
+    #[test]
+    fn test_capture_reader_invalid_index() {
+        let res = CaptureReader::new(Cursor::new(vec![]), Cursor::new(b"NOPE\0\0\0\x01".to_vec()));
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_capture_files() {
+        let path = std::env::temp_dir().join(format!("vrt-capture-{}.vrt", std::process::id()));
+        let mut recorder = Recorder::create(&path).expect("failed to create capture");
+        recorder.record(VRT_STATIC_STREAM_1).unwrap();
+        recorder.flush().unwrap();
+        drop(recorder);
+
+        let mut reader = CaptureReader::open(&path).expect("failed to open capture");
+        let packet = reader.next().unwrap().unwrap();
+        assert_eq!(packet.data_payload, vec![0xca, 0xfe, 0xf0, 0x0d]);
+        std::fs::remove_file(index_path(&path)).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_split_capture() {
+        let path = std::env::temp_dir().join(format!("vrt-split-{}.vrt", std::process::id()));
+        let with_class_id = [
+            0x18, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x01, 0x00, 0x12, 0x34, 0x56, 0x00, 0x01,
+            0x00, 0x02, 0xaa, 0xbb, 0xcc, 0xdd,
+        ];
+        let mut recorder = Recorder::create(&path).expect("failed to create capture");
+        recorder.record(VRT_STATIC_STREAM_1).unwrap();
+        recorder.record(VRT_STATIC_STREAM_2).unwrap();
+        recorder.record(&with_class_id).unwrap();
+        recorder.flush().unwrap();
+        drop(recorder);
+
+        let captures = split_capture(&path, false).unwrap();
+        assert_eq!(captures.len(), 2);
+        assert_eq!(captures[0].stream_id, Some(1));
+        assert_eq!(captures[0].class_id, None);
+        assert_eq!(captures[0].packets, 2);
+        assert_eq!(
+            captures[0].path.file_name().unwrap().to_str().unwrap(),
+            format!("vrt-split-{}-00000001.vrt", std::process::id())
+        );
+        assert_eq!(
+            std::fs::read(&captures[0].path).unwrap(),
+            [VRT_STATIC_STREAM_1, &with_class_id].concat()
+        );
+        let mut reader = CaptureReader::open(&captures[1].path).unwrap();
+        assert_eq!(reader.len(), 1);
+        assert_eq!(
+            reader.read_packet_bytes().unwrap().unwrap(),
+            VRT_STATIC_STREAM_2
+        );
+        for capture in &captures {
+            std::fs::remove_file(index_path(&capture.path)).unwrap();
+            std::fs::remove_file(&capture.path).unwrap();
+        }
+
+        let captures = split_capture(&path, true).unwrap();
+        let keys: Vec<_> = captures.iter().map(|c| (c.stream_id, c.class_id)).collect();
+        assert_eq!(
+            keys,
+            vec![
+                (Some(1), None),
+                (Some(1), Some(0x0012_3456_0001_0002)),
+                (Some(2), None)
+            ]
+        );
+        assert!(captures[1]
+            .path
+            .to_str()
+            .unwrap()
+            .ends_with("-00000001-0012345600010002.vrt"));
+        assert!(captures[0]
+            .path
+            .to_str()
+            .unwrap()
+            .ends_with("-00000001-none.vrt"));
+        for capture in &captures {
+            std::fs::remove_file(index_path(&capture.path)).unwrap();
+            std::fs::remove_file(&capture.path).unwrap();
+        }
+        std::fs::remove_file(index_path(&path)).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_trim_capture() {
+        let path = std::env::temp_dir().join(format!("vrt-trim-{}.vrt", std::process::id()));
+        let trimmed = std::env::temp_dir().join(format!("vrt-trimmed-{}.vrt", std::process::id()));
+        let mut recorder = Recorder::create(&path).expect("failed to create capture");
+        for n in 0..4 {
+            recorder.record(&packet_with_time(1, 10 + n, 0)).unwrap();
+        }
+        recorder.flush().unwrap();
+        drop(recorder);
+
+        let at = |integer| Timestamp {
+            tsi: Tsi::TSI_UTC,
+            integer,
+            tsf: Tsf::TSF_REAL_TIME,
+            fractional: 0,
+        };
+        assert_eq!(trim_capture(&path, &trimmed, at(11), at(13)).unwrap(), 2);
+        let reader = CaptureReader::open(&trimmed).expect("failed to open capture");
+        let times: Vec<_> = reader.map(|p| p.unwrap().tsi.unwrap()).collect();
+        assert_eq!(times, vec![11, 12]);
+        for path in [path, trimmed] {
+            std::fs::remove_file(index_path(&path)).unwrap();
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+}
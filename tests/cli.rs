@@ -0,0 +1,572 @@
+#![cfg(feature = "cli")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::process::{Command, Output, Stdio};
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_IFDATA_WITH_TRAILER: &[u8] = &[
+        0x14, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef, 0x40, 0x0f, 0x00,
+        0x00,
+    ];
+
+    static VRT_STATIC_CONTEXT: &[u8] = &[
+        0x40, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x01, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0xf4, 0x24, 0x00, 0x00, 0x00,
+    ];
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("vrt-cli-{}-{}", std::process::id(), name))
+    }
+
+    fn run(bin: &str, args: &[&str], stdin: &[u8]) -> Output {
+        let mut child = Command::new(bin)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        // A tool rejecting its arguments exits without reading its input
+        match child.stdin.take().unwrap().write_all(stdin) {
+            Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {}
+            result => result.unwrap(),
+        }
+        child.wait_with_output().unwrap()
+    }
+
+    #[test]
+    fn test_vrt_dump() {
+        let capture = [VRT_STATIC_STREAM_1, VRT_STATIC_IFDATA_WITH_TRAILER].concat();
+        let output = run(
+            env!("CARGO_BIN_EXE_vrt-dump"),
+            &["--hexdump", "2"],
+            &capture,
+        );
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(
+            stdout,
+            "#0 IF Data with Stream ID, stream 0x00000001, count 0, 6 words, TSI UTC 10, \
+             TSF Real Time 5, 4 byte payload\n\
+             \x20   header: type 1, C 0, T 0, TSI 1, TSF 2, count 0, size 6\n\
+             \x20   time: 10.000000000005 s\n\
+             \x20   0000  ca fe                                            ..\n\
+             #1 IF Data with Stream ID, stream 0x00000002, count 0, 4 words, 4 byte payload, \
+             trailer\n\
+             \x20   header: type 1, C 0, T 1, TSI 0, TSF 0, count 0, size 4\n\
+             \x20   trailer: valid data=1\n\
+             \x20   0000  de ad                                            ..\n"
+        );
+
+        let path = temp_path("dump.vrt");
+        std::fs::write(&path, &capture).unwrap();
+        let output = run(
+            env!("CARGO_BIN_EXE_vrt-dump"),
+            &["--stream", "0x2", path.to_str().unwrap()],
+            &[],
+        );
+        std::fs::remove_file(&path).unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.starts_with("#1 "));
+        assert_eq!(stdout.lines().count(), 3);
+
+        let output = run(env!("CARGO_BIN_EXE_vrt-dump"), &[], &capture[..10]);
+        assert!(!output.status.success());
+    }
+
+    #[test]
+    fn test_vrt_dump_json() {
+        let capture = [VRT_STATIC_STREAM_1, VRT_STATIC_IFDATA_WITH_TRAILER].concat();
+        let output = run(
+            env!("CARGO_BIN_EXE_vrt-dump"),
+            &["--json", "--payload", "--stream", "2"],
+            &capture,
+        );
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(stdout.lines().count(), 1);
+        assert!(stdout.starts_with("{\"class_id\":null,\"packet_count\":0"));
+        assert!(stdout.contains("\"payload\":\"3q2+7w==\""));
+    }
+
+    #[test]
+    fn test_vrt_stats() {
+        let mut capture = Vec::new();
+        let generator = Generator::new(1000.0, 250, Waveform::Ramp)
+            .with_stream_id(7)
+            .with_context_interval(4);
+        for (n, packet) in generator.take(11).enumerate() {
+            // drop the sixth data packet
+            if n != 7 {
+                capture.extend(packet.as_packet().to_vec().unwrap());
+            }
+        }
+        let output = run(env!("CARGO_BIN_EXE_vrt-stats"), &[], &capture);
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let mut lines = stdout.lines();
+        assert!(lines.next().unwrap().starts_with("stream"));
+        let fields: Vec<_> = lines.next().unwrap().split_whitespace().collect();
+        assert_eq!(
+            fields,
+            [
+                "0x00000007",
+                "10",
+                "7236",
+                "1",
+                "0",
+                "0",
+                "12.500",
+                "4.5",
+                "3602.0",
+                "0"
+            ]
+        );
+        assert!(
+            stdout.contains("context 0x00000007: 3 packets, 1 changes\n  #0 Sample Rate 1000 Hz\n")
+        );
+        assert!(!stdout.contains("fingerprint"));
+
+        let packets = [VRT_STATIC_STREAM_1, VRT_STATIC_IFDATA_WITH_TRAILER];
+        let capture = packets.concat();
+        let output = run(
+            env!("CARGO_BIN_EXE_vrt-stats"),
+            &["--fingerprint"],
+            &capture,
+        );
+        let expected = fingerprint(packets.iter().map(|p| parse_vrt_packet(p).unwrap().1));
+        assert!(String::from_utf8(output.stdout)
+            .unwrap()
+            .ends_with(&format!("\nfingerprint {}\n", expected)));
+    }
+
+    #[test]
+    fn test_vrt_stats_context_changes() {
+        let context = |cif0: u32, fields: &[u8]| {
+            let mut bytes = vec![0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x07];
+            bytes.extend_from_slice(&cif0.to_be_bytes());
+            bytes.extend_from_slice(fields);
+            bytes[3] = (bytes.len() / 4) as u8;
+            bytes
+        };
+        let rate = |hz: u64| (hz << 20).to_be_bytes();
+        let capture = [
+            context(0x0020_0000, &rate(1_000_000)),
+            context(
+                0x00a0_0000,
+                &[&[0x00, 0x00, 0x02, 0x80][..], &rate(2_000_000)].concat(),
+            ),
+            context(0x0080_0000, &[0x00, 0x00, 0x02, 0x80]),
+        ]
+        .concat();
+        let output = run(env!("CARGO_BIN_EXE_vrt-stats"), &[], &capture);
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains(
+            "  #0 Sample Rate 1000000 Hz\n  \
+             #1 Gain 5 dB, Sample Rate 1000000 Hz -> 2000000 Hz\n  \
+             #2 Sample Rate removed\n"
+        ));
+    }
+
+    #[test]
+    fn test_vrt_filter() {
+        let capture = [VRT_STATIC_STREAM_1, VRT_STATIC_IFDATA_WITH_TRAILER].concat();
+        let filter = |args: &[&str]| {
+            let output = run(env!("CARGO_BIN_EXE_vrt-filter"), args, &capture);
+            assert!(output.status.success());
+            output.stdout
+        };
+        assert_eq!(filter(&[]), capture);
+        assert_eq!(filter(&["--stream", "2"]), VRT_STATIC_IFDATA_WITH_TRAILER);
+        assert_eq!(filter(&["--invert", "--stream", "2"]), VRT_STATIC_STREAM_1);
+        assert_eq!(
+            filter(&["--start", "10", "--end", "10.5"]),
+            VRT_STATIC_STREAM_1
+        );
+        assert_eq!(filter(&["--start", "10.000000000006"]), b"");
+        assert_eq!(
+            filter(&["--type", "4", "--type", "1", "--stream", "1"]),
+            VRT_STATIC_STREAM_1
+        );
+        assert!(filter(&["--class-id", "0x0012345600010002"]).is_empty());
+
+        let path = temp_path("filter.vrt");
+        let output = run(
+            env!("CARGO_BIN_EXE_vrt-filter"),
+            &["-o", path.to_str().unwrap(), "--stream", "0x1"],
+            &capture,
+        );
+        assert!(output.status.success());
+        assert_eq!(std::fs::read(&path).unwrap(), VRT_STATIC_STREAM_1);
+        std::fs::remove_file(&path).unwrap();
+
+        let output = run(
+            env!("CARGO_BIN_EXE_vrt-filter"),
+            &["--start", "x"],
+            &capture,
+        );
+        assert_eq!(output.status.code(), Some(2));
+    }
+
+    #[test]
+    fn test_vrt_convert() {
+        let capture = [VRT_STATIC_STREAM_1, VRT_STATIC_IFDATA_WITH_TRAILER].concat();
+        let convert = |args: &[&str], stdin: &[u8]| {
+            let output = run(env!("CARGO_BIN_EXE_vrt-convert"), args, stdin);
+            assert!(output.status.success());
+            output.stdout
+        };
+        let framed = convert(&["--to", "vrl", "-", "-"], &capture);
+        assert_eq!(framed.len(), capture.len() + 2 * 12);
+        assert_eq!(&framed[..4], b"VRLP");
+        assert_eq!(&framed[4..8], [0x00, 0x00, 0x00, 0x09]);
+        assert_eq!(&framed[32..36], b"VEND");
+        assert_eq!(&framed[40..44], [0x00, 0x10, 0x00, 0x07]);
+        assert_eq!(convert(&["-", "-"], &framed), capture);
+
+        let path = temp_path("convert.pcapng");
+        convert(&["--to", "pcapng", "-", path.to_str().unwrap()], &capture);
+        assert_eq!(convert(&[path.to_str().unwrap(), "-"], b""), capture);
+        std::fs::remove_file(&path).unwrap();
+
+        let converted = convert(&["--payload", "i16:i8", "-", "-"], VRT_STATIC_STREAM_1);
+        assert_eq!(&converted[..20], &VRT_STATIC_STREAM_1[..20]);
+        assert_eq!(&converted[20..], [0xcb, 0xf0, 0x00, 0x00]);
+        let converted = convert(&["--payload", "i16:i16le", "-", "-"], VRT_STATIC_STREAM_1);
+        assert_eq!(&converted[20..], [0xfe, 0xca, 0x0d, 0xf0]);
+
+        let base = temp_path("convert");
+        let base = base.to_str().unwrap();
+        let args = ["--to", "sigmf", "--sample-rate", "1e6", "-", base];
+        convert(&args, VRT_STATIC_STREAM_1);
+        let data = format!("{}.sigmf-data", base);
+        let meta = format!("{}.sigmf-meta", base);
+        assert_eq!(std::fs::read(&data).unwrap(), [0xca, 0xfe, 0xf0, 0x0d]);
+        assert!(std::fs::read_to_string(&meta)
+            .unwrap()
+            .contains("\"ci16_be\""));
+        std::fs::remove_file(data).unwrap();
+        std::fs::remove_file(meta).unwrap();
+
+        let context = [VRT_STATIC_STREAM_1, VRT_STATIC_CONTEXT].concat();
+        let csv = convert(
+            &["--to", "csv", "--fields", "sample_rate,gain", "-", "-"],
+            &context,
+        );
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "time,stream_id,sample_rate,gain\n,0x00000001,1000000,\n"
+        );
+
+        let output = run(
+            env!("CARGO_BIN_EXE_vrt-convert"),
+            &["--to", "sigmf", "-", "-"],
+            &capture,
+        );
+        assert_eq!(output.status.code(), Some(2));
+    }
+
+    #[test]
+    fn test_vrt_replay() {
+        let mut later = VRT_STATIC_STREAM_1.to_vec();
+        later[11] = 0x0b;
+        let capture = [VRT_STATIC_STREAM_1, &later].concat();
+
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let destination = format!("udp:{}", socket.local_addr().unwrap());
+        let start = std::time::Instant::now();
+        let output = run(
+            env!("CARGO_BIN_EXE_vrt-replay"),
+            &[
+                "--speed",
+                "10",
+                "--repeat",
+                "2",
+                "--remap",
+                "1=0x07",
+                "-",
+                &destination,
+            ],
+            &capture,
+        );
+        assert!(output.status.success());
+        assert!(start.elapsed() >= std::time::Duration::from_millis(200));
+        let mut buf = [0; 64];
+        for expected in [10, 11, 10, 11] {
+            let len = socket.recv(&mut buf).unwrap();
+            let (_, packet) = parse_vrt_packet(&buf[..len]).unwrap();
+            assert_eq!((packet.stream_id, packet.tsi), (Some(7), Some(expected)));
+        }
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let destination = format!("tcp:{}", listener.local_addr().unwrap());
+        let receiver = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut connection = net::TcpConnection::new(stream, net::TcpFraming::Vrl);
+            let mut packets = Vec::new();
+            while let Some(packet) = connection.recv_packet().unwrap() {
+                packets.push(packet);
+            }
+            packets
+        });
+        let output = run(
+            env!("CARGO_BIN_EXE_vrt-replay"),
+            &["--speed", "0", "--vrl", "-", &destination],
+            &capture,
+        );
+        assert!(output.status.success());
+        let packets = receiver.join().unwrap();
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[1].as_packet().to_vec().unwrap(), later);
+
+        let output = run(
+            env!("CARGO_BIN_EXE_vrt-replay"),
+            &["--vrl", "-", "-"],
+            &capture,
+        );
+        assert_eq!(output.status.code(), Some(2));
+    }
+
+    #[test]
+    fn test_vrt_gen() {
+        let output = run(
+            env!("CARGO_BIN_EXE_vrt-gen"),
+            &[
+                "--count",
+                "4",
+                "--samples",
+                "2",
+                "--waveform",
+                "ramp",
+                "--context",
+                "2",
+                "--stream",
+                "1",
+                "--ext-stream",
+                "0x5",
+                "--drop-every",
+                "3",
+                "--duplicate-every",
+                "4",
+            ],
+            b"",
+        );
+        assert!(output.status.success());
+        let packets: Vec<_> = PacketStream::new(&output.stdout)
+            .map(|p| {
+                let p = p.unwrap();
+                (
+                    p.stream_id.unwrap(),
+                    p.header.packet_type.0,
+                    p.header.packet_count,
+                )
+            })
+            .collect();
+        assert_eq!(
+            packets,
+            [
+                (1, 4, 0),
+                (1, 1, 0),
+                (5, 5, 0),
+                (5, 3, 0),
+                (1, 1, 1),
+                (5, 3, 1),
+                (1, 4, 1),
+                (5, 5, 1),
+                (1, 1, 3),
+                (1, 1, 3),
+                (5, 3, 3),
+                (5, 3, 3),
+            ]
+        );
+
+        let output = run(env!("CARGO_BIN_EXE_vrt-gen"), &["--waveform", "tone"], b"");
+        assert_eq!(output.status.code(), Some(2));
+    }
+
+    #[test]
+    fn test_vrt_validate() {
+        let output = run(
+            env!("CARGO_BIN_EXE_vrt-validate"),
+            &[],
+            &VRT_STATIC_STREAM_1.repeat(2),
+        );
+        assert_eq!(output.status.code(), Some(1));
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(
+            stdout,
+            "packet-count: packet #1 at offset 24: packet count 0, expected 1\n\n\
+             packet-type          pass\n\
+             class-id-reserved    pass\n\
+             real-time-range      pass\n\
+             timestamp-mode       pass\n\
+             timestamp-order      pass\n\
+             packet-count         FAIL 1 packets\n\
+             2 packets checked: FAIL\n"
+        );
+
+        let capture = [VRT_STATIC_STREAM_1, &VRT_STATIC_STREAM_1[..8]].concat();
+        let output = run(env!("CARGO_BIN_EXE_vrt-validate"), &["--quiet"], &capture);
+        assert_eq!(output.status.code(), Some(1));
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("parse                FAIL packet #1 at offset 24"));
+
+        let output = run(
+            env!("CARGO_BIN_EXE_vrt-validate"),
+            &[],
+            VRT_STATIC_IFDATA_WITH_TRAILER,
+        );
+        assert!(output.status.success());
+        assert!(String::from_utf8(output.stdout)
+            .unwrap()
+            .ends_with("1 packets checked: pass\n"));
+    }
+
+    #[test]
+    fn test_vrt_validate_difi() {
+        let data = DifiPacket::Data(DifiData {
+            stream_id: 1,
+            packet_count: 0,
+            seconds: 10,
+            picoseconds: 5,
+            payload: &[0xca, 0xfe, 0xf0, 0x0d],
+        })
+        .to_packet();
+        let capture = [
+            data.as_packet().to_vec().unwrap(),
+            VRT_STATIC_IFDATA_WITH_TRAILER.to_vec(),
+        ]
+        .concat();
+        let output = run(env!("CARGO_BIN_EXE_vrt-validate"), &["--difi"], &capture);
+        assert_eq!(output.status.code(), Some(1));
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(
+            stdout,
+            "difi-class-id: packet #1 at offset 32: no Class Identifier\n\n\
+             packet-type          pass\n\
+             class-id-reserved    pass\n\
+             real-time-range      pass\n\
+             timestamp-mode       pass\n\
+             timestamp-order      pass\n\
+             packet-count         pass\n\
+             difi-class-id        FAIL 1 packets\n\
+             difi-header          pass\n\
+             difi-timestamp       pass\n\
+             difi-context-fields  pass\n\
+             2 packets checked: FAIL\n"
+        );
+
+        let output = run(
+            env!("CARGO_BIN_EXE_vrt-validate"),
+            &["--difi", "--quiet"],
+            &data.as_packet().to_vec().unwrap(),
+        );
+        assert!(output.status.success());
+    }
+
+    #[test]
+    fn test_vrt_diff() {
+        let golden = temp_path("diff-golden.vrt");
+        std::fs::write(&golden, [VRT_STATIC_STREAM_1, VRT_STATIC_CONTEXT].concat()).unwrap();
+        let golden = golden.to_str().unwrap();
+
+        let capture = [VRT_STATIC_CONTEXT, VRT_STATIC_STREAM_1].concat();
+        let output = run(env!("CARGO_BIN_EXE_vrt-diff"), &[golden, "-"], &capture);
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "2 and 2 packets, 2 matched, 0 divergences: identical\n"
+        );
+
+        let output = run(
+            env!("CARGO_BIN_EXE_vrt-diff"),
+            &[golden, "-"],
+            VRT_STATIC_STREAM_1,
+        );
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "stream 0x00000001: packet #1 of a missing from b\n\
+             2 and 1 packets, 1 matched, 1 divergences: DIFFERENT\n"
+        );
+        std::fs::remove_file(golden).unwrap();
+    }
+
+    #[test]
+    fn test_vrt_split() {
+        let base = temp_path("split.vrt");
+        let capture = [VRT_STATIC_STREAM_1, VRT_STATIC_IFDATA_WITH_TRAILER].concat();
+        let output = run(
+            env!("CARGO_BIN_EXE_vrt-split"),
+            &["-", base.to_str().unwrap()],
+            &capture,
+        );
+        assert!(output.status.success());
+        let stream_1 =
+            base.with_file_name(format!("vrt-cli-{}-split-00000001.vrt", std::process::id()));
+        let stream_2 =
+            base.with_file_name(format!("vrt-cli-{}-split-00000002.vrt", std::process::id()));
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            format!(
+                "{}: 1 packets\n{}: 1 packets\n",
+                stream_1.display(),
+                stream_2.display()
+            )
+        );
+        assert_eq!(std::fs::read(&stream_1).unwrap(), VRT_STATIC_STREAM_1);
+        let mut reader = CaptureReader::open(&stream_2).unwrap();
+        assert_eq!(
+            reader.read_packet_bytes().unwrap().unwrap(),
+            VRT_STATIC_IFDATA_WITH_TRAILER
+        );
+        for path in [stream_1, stream_2] {
+            std::fs::remove_file(index_path(&path)).unwrap();
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_vrt_trim() {
+        let input = temp_path("trim.vrt");
+        let output = temp_path("trimmed.vrt");
+        let mut recorder = Recorder::create(&input).unwrap();
+        for tsi in [9u8, 10, 11] {
+            let mut packet = VRT_STATIC_STREAM_1.to_vec();
+            packet[11] = tsi;
+            recorder.record(&packet).unwrap();
+        }
+        recorder.flush().unwrap();
+        drop(recorder);
+
+        let args = [
+            "--start",
+            "10",
+            "--end",
+            "11",
+            input.to_str().unwrap(),
+            output.to_str().unwrap(),
+        ];
+        let result = run(env!("CARGO_BIN_EXE_vrt-trim"), &args, b"");
+        assert!(result.status.success());
+        assert_eq!(String::from_utf8(result.stdout).unwrap(), "1 packets\n");
+        assert_eq!(std::fs::read(&output).unwrap(), VRT_STATIC_STREAM_1);
+
+        let result = run(env!("CARGO_BIN_EXE_vrt-trim"), &args[2..], b"");
+        assert_eq!(result.status.code(), Some(2));
+        for path in [input, output] {
+            std::fs::remove_file(index_path(&path)).unwrap();
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
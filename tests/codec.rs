@@ -0,0 +1,101 @@
+#![cfg(feature = "codec")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use bytes::BytesMut;
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_util::codec::{Decoder, Encoder, Framed};
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_STREAM_2: &[u8] = &[
+        0x10, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    #[test]
+    fn test_codec_decode() {
+        let mut codec = VrtCodec::new();
+        let mut buf = BytesMut::from(&VRT_STATIC_STREAM_1[..2]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        buf.extend_from_slice(&VRT_STATIC_STREAM_1[2..10]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        buf.extend_from_slice(&VRT_STATIC_STREAM_1[10..]);
+        buf.extend_from_slice(VRT_STATIC_STREAM_2);
+
+        let first = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(first.stream_id, Some(1));
+        let second = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(second.stream_id, Some(2));
+        assert!(buf.is_empty());
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+
+        let mut buf = BytesMut::from(&[0x10, 0x00, 0x00, 0x00][..]);
+        assert!(codec.decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_codec_encode() {
+        let mut codec = VrtCodec::new();
+        let mut buf = BytesMut::new();
+        let (_, packet) = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap();
+        codec.encode(&packet, &mut buf).unwrap();
+        codec
+            .encode(
+                VrtPacketOwned::from(&parse_vrt_packet(VRT_STATIC_STREAM_2).unwrap().1),
+                &mut buf,
+            )
+            .unwrap();
+        assert_eq!(&buf[..24], VRT_STATIC_STREAM_1);
+        assert_eq!(&buf[24..], VRT_STATIC_STREAM_2);
+
+        let mut invalid = packet;
+        invalid.stream_id = None;
+        assert!(codec.encode(&invalid, &mut buf).is_err());
+        assert_eq!(buf.len(), 36);
+    }
+
+    #[tokio::test]
+    async fn test_codec_framed() {
+        let (a, b) = tokio::io::duplex(16);
+        let mut tx = Framed::new(a, VrtCodec::new());
+        let mut rx = Framed::new(b, VrtCodec::new());
+        let packet = VrtPacketOwned::from(&parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap().1);
+        let sent = packet.clone();
+        let send = tokio::spawn(async move {
+            tx.send(sent.clone()).await.unwrap();
+            tx.send(sent).await.unwrap();
+        });
+        assert_eq!(rx.next().await.unwrap().unwrap(), packet);
+        assert_eq!(rx.next().await.unwrap().unwrap(), packet);
+        send.await.unwrap();
+    }
+
+    #[test]
+    fn test_bytes_codec() {
+        let mut codec = VrtBytesCodec::new();
+        let mut buf = BytesMut::from(&VRT_STATIC_STREAM_1[..10]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        buf.extend_from_slice(&VRT_STATIC_STREAM_1[10..]);
+        buf.extend_from_slice(VRT_STATIC_STREAM_2);
+
+        let first = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(first.stream_id, Some(1));
+        assert_eq!(&first.data_payload[..], &[0xca, 0xfe, 0xf0, 0x0d]);
+        let second = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(second.stream_id, Some(2));
+        assert!(buf.is_empty());
+
+        let mut out = BytesMut::new();
+        codec.encode(first, &mut out).unwrap();
+        codec.encode(second, &mut out).unwrap();
+        assert_eq!(
+            &out[..],
+            &[VRT_STATIC_STREAM_1, VRT_STATIC_STREAM_2].concat()[..]
+        );
+    }
+}
@@ -0,0 +1,279 @@
+#![cfg(feature = "alloc")]
+
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+
+    /// Control packet for stream 1 with Message ID 7, Controllee Identifier 0x10, requesting
+    /// execution of an RF Reference Frequency of 100 MHz and a Gain of 2 dB
+    static VRT_CONTROL: &[u8] = &[
+        0x60, 0x00, 0x00, 0x09, // header
+        0x00, 0x00, 0x00, 0x01, // Stream Identifier
+        0x81, 0x00, 0x00, 0x00, // CAM: Controllee Identifier, Execute
+        0x00, 0x00, 0x00, 0x07, // Message ID
+        0x00, 0x00, 0x00, 0x10, // Controllee Identifier
+        0x08, 0x80, 0x00, 0x00, // CIF0: RF Reference Frequency, Gain
+        0x00, 0x00, 0x5f, 0x5e, 0x10, 0x00, 0x00, 0x00, // RF Reference Frequency
+        0x00, 0x00, 0x01, 0x00, // Gain
+    ];
+
+    #[test]
+    fn test_command_parse() {
+        let (_, packet) = parse_vrt_packet(VRT_CONTROL).unwrap();
+        assert!(packet.header.packet_type.is_command());
+        assert_eq!(packet.header.packet_type.to_string(), "Command");
+        let command = packet.command().unwrap();
+        assert_eq!(command.kind, CommandKind::Control);
+        assert_eq!(command.cam.action(), Some(ActionMode::Execute));
+        assert_eq!(command.message_id, 7);
+        assert_eq!(command.controllee, Some(CommandId::Word(0x10)));
+        assert_eq!(command.controller, None);
+        assert_eq!(command.cif0(), Some(0x0880_0000));
+        assert_eq!(command.payload.len(), 16);
+
+        let encoded = command.to_vec();
+        let rebuilt = command.packet(1, &encoded);
+        assert_eq!(rebuilt.to_vec().unwrap(), VRT_CONTROL);
+
+        // not a command packet
+        let mut context = packet;
+        context.header.packet_type = VitaPacketType(VitaPacketType::IFCONTEXT);
+        assert_eq!(context.command(), None);
+        assert_eq!(
+            Command::parse(&packet.header, &VRT_CONTROL[8..12]),
+            Err(Error::Malformed)
+        );
+    }
+
+    #[test]
+    fn test_command_identifiers() {
+        let command = Command {
+            controllee: Some(CommandId::Uuid(0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10)),
+            controller: Some(CommandId::Word(0x22)),
+            // stale identifier bits are replaced
+            cam: ControlAckMode(ControlAckMode::CONTROLLER_UUID),
+            ..Command::control(1, &[0, 0, 0, 0])
+        };
+        let encoded = command.to_vec();
+        assert_eq!(encoded.len(), command.encoded_len());
+        assert_eq!(encoded[..4], [0xe0, 0x00, 0x00, 0x00]);
+        assert_eq!(encoded[8..12], [0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(encoded[24..28], [0x00, 0x00, 0x00, 0x22]);
+        let parsed = Command::parse(&command.header(), &encoded).unwrap();
+        assert_eq!(parsed.controllee, command.controllee);
+        assert_eq!(parsed.controller, command.controller);
+        assert_eq!(parsed.payload, [0, 0, 0, 0]);
+
+        assert_eq!(command.encode(&mut [0; 31]), Err(Error::BufferTooSmall));
+    }
+
+    #[test]
+    fn test_command_cancellation() {
+        let (_, packet) = parse_vrt_packet(VRT_CONTROL).unwrap();
+        let control = packet.command().unwrap();
+        let cancellation = control.cancellation();
+        assert_eq!(cancellation.kind, CommandKind::Cancellation);
+        assert_eq!(cancellation.message_id, 7);
+        assert_eq!(cancellation.controllee, Some(CommandId::Word(0x10)));
+        assert_eq!(cancellation.payload, [0x08, 0x80, 0x00, 0x00]);
+        assert!(cancellation.cancels(&control));
+        assert!(!control.cancels(&control));
+
+        let encoded = cancellation.to_vec();
+        let packet = cancellation.packet(1, &encoded);
+        assert_eq!(packet.header.indicators, 0x01);
+        assert!(!packet.header.t);
+        let bytes = packet.to_vec().unwrap();
+        assert_eq!(bytes[0], 0x61);
+        let (_, parsed) = parse_vrt_packet(&bytes).unwrap();
+        let parsed = parsed.command().unwrap();
+        assert_eq!(parsed, cancellation);
+        assert!(parsed.cancels(&control));
+
+        // a different Message ID or Controllee Identifier cancels another control
+        let other = Command {
+            message_id: 8,
+            ..control
+        };
+        assert!(!parsed.cancels(&other));
+        let other = Command {
+            controllee: None,
+            ..control
+        };
+        assert!(!parsed.cancels(&other));
+    }
+
+    #[test]
+    fn test_command_ack_kind() {
+        let ack = Command {
+            kind: CommandKind::ExecutionAck,
+            cam: ControlAckMode(ControlAckMode::EXECUTION),
+            ..Command::control(3, &[])
+        };
+        let encoded = ack.to_vec();
+        let header = ack.header();
+        assert!(header.t);
+        assert_eq!(
+            Command::parse(&header, &encoded).unwrap().kind,
+            CommandKind::ExecutionAck
+        );
+
+        // an acknowledge must select exactly one kind
+        let ambiguous = Command {
+            cam: ControlAckMode(ControlAckMode::EXECUTION | ControlAckMode::STATE),
+            ..ack
+        };
+        assert_eq!(
+            Command::parse(&header, &ambiguous.to_vec()),
+            Err(Error::Malformed)
+        );
+        assert!(CommandKind::QueryAck.is_ack());
+        assert!(!CommandKind::Cancellation.is_ack());
+        assert_eq!(ControlAckMode(3 << 23).action(), None);
+    }
+
+    #[test]
+    fn test_command_ack_responses() {
+        let (_, packet) = parse_vrt_packet(VRT_CONTROL).unwrap();
+        let control = packet.command().unwrap();
+        let ack = control.acknowledge(CommandKind::ValidationAck);
+        assert!(ack.acknowledges(&control));
+        assert!(!ack.acknowledges(&ack));
+        assert!(!control.cancellation().acknowledges(&control));
+        assert_eq!(
+            ack.cam.0 & ControlAckMode::VALIDATION,
+            ControlAckMode::VALIDATION
+        );
+        let responses = ack.responses().unwrap();
+        assert_eq!((responses.warnings, responses.errors), (None, None));
+
+        // WIF0 announcing WIF1 and the Gain, and EIF0 the RF Reference Frequency
+        let payload = [
+            0x00, 0x80, 0x00, 0x02, // WIF0
+            0x00, 0x00, 0x00, 0x03, // WIF1, two fields
+            0x08, 0x00, 0x00, 0x00, // EIF0
+            0x00, 0x80, 0x00, 0x00, // Gain: distortion
+            0x00, 0x00, 0x00, 0x01, // WIF1 fields, skipped
+            0x00, 0x00, 0x00, 0x02, 0x40, 0x00, 0x00,
+            0x00, // RF Reference Frequency: device failure
+        ];
+        let ack = Command {
+            cam: ack
+                .cam
+                .with(ControlAckMode::WARNINGS | ControlAckMode::ERRORS),
+            payload: &payload,
+            ..ack
+        };
+        let responses = ack.responses().unwrap();
+        let warnings = responses.warnings.unwrap();
+        assert_eq!(warnings.indicators, 0x0080_0002);
+        assert_eq!(
+            warnings
+                .get(23)
+                .map(|r| r.has(WarningErrorFlag::Distortion)),
+            Some(true)
+        );
+        let errors = responses.errors.unwrap();
+        assert_eq!(
+            errors.iter().collect::<Vec<_>>(),
+            [(
+                27,
+                WarningErrorResponse(WarningErrorResponse::DEVICE_FAILURE)
+            )]
+        );
+        assert!(responses.has_errors());
+
+        // truncated responses
+        let truncated = Command {
+            payload: &payload[..24],
+            ..ack
+        };
+        assert_eq!(truncated.responses(), None);
+        assert_eq!(control.responses(), None);
+    }
+
+    #[test]
+    fn test_command_query_state() {
+        let (_, packet) = parse_vrt_packet(VRT_CONTROL).unwrap();
+        let control = packet.command().unwrap();
+        assert_eq!(control.state(), None);
+        assert_eq!(control.context_fields().count(), 2);
+
+        // the controllee reports the state requested, laid out as the control's fields
+        let ack = Command {
+            payload: control.payload,
+            ..control.acknowledge(CommandKind::QueryAck)
+        };
+        let encoded = ack.to_vec();
+        let bytes = ack.packet(1, &encoded).to_vec().unwrap();
+        let (_, packet) = parse_vrt_packet(&bytes).unwrap();
+        let ack = packet.command().unwrap();
+        assert_eq!(ack.kind, CommandKind::QueryAck);
+        let state = ack.state().unwrap();
+        assert_eq!(state.rf_frequency, Some(100e6));
+        assert_eq!(state.gain, Some(2.0));
+        assert_eq!(state.sample_rate, None);
+        assert_eq!(ack.cancellation().context_fields().count(), 0);
+    }
+
+    #[test]
+    fn test_command_timing() {
+        let at = Timestamp {
+            tsi: Tsi::TSI_UTC,
+            integer: 1_700_000_000,
+            tsf: Tsf::TSF_REAL_TIME,
+            fractional: 500_000_000_000,
+        };
+        let command = Command::control(9, &[0, 0, 0, 0]).with_timing(TimingControl::LateAllowed);
+        assert_eq!(command.cam.timing(), Some(TimingControl::LateAllowed));
+        assert_eq!(command.cam.action(), Some(ActionMode::Execute));
+        let encoded = command.to_vec();
+        let bytes = command.packet_at(1, &encoded, &at).to_vec().unwrap();
+        assert_eq!(bytes[..2], [0x60, 0x60]);
+
+        let (_, packet) = parse_vrt_packet(&bytes).unwrap();
+        assert_eq!(packet.timestamp(), Some(at));
+        let parsed = packet.command().unwrap();
+        assert_eq!(parsed.cam.timing(), Some(TimingControl::LateAllowed));
+        assert_eq!(parsed.cam.0 & ControlAckMode::TIMING_MASK, 2 << 12);
+
+        // a packet without an integer-seconds timestamp has no Integer-seconds field
+        let free = Timestamp {
+            tsi: Tsi::TSI_NONE,
+            integer: 0,
+            ..at
+        };
+        let packet = command.packet_at(1, &encoded, &free);
+        assert_eq!((packet.tsi, packet.tsf), (None, Some(500_000_000_000)));
+        assert_eq!(ControlAckMode(5 << 12).timing(), None);
+    }
+
+    #[test]
+    fn test_timing_control_permits() {
+        let window = 0.001;
+        for (timing, early, on_time, late) in [
+            (TimingControl::IgnoreTimestamp, true, true, true),
+            (TimingControl::OnTime, false, true, false),
+            (TimingControl::LateAllowed, false, true, true),
+            (TimingControl::EarlyAllowed, true, true, false),
+            (TimingControl::EarlyOrLateAllowed, true, true, true),
+        ] {
+            assert_eq!(timing.permits(-0.5, window), early, "{:?}", timing);
+            assert_eq!(timing.permits(0.0005, window), on_time, "{:?}", timing);
+            assert_eq!(timing.permits(0.5, window), late, "{:?}", timing);
+        }
+
+        let at = Timestamp {
+            tsi: Tsi::TSI_UTC,
+            integer: 10,
+            tsf: Tsf::TSF_REAL_TIME,
+            fractional: 0,
+        };
+        let now = Timestamp { integer: 11, ..at };
+        let lateness = now.seconds_since(&at, None).unwrap();
+        assert!(TimingControl::LateAllowed.permits(lateness, window));
+        assert!(!TimingControl::OnTime.permits(lateness, window));
+    }
+}
@@ -0,0 +1,71 @@
+//! Packets shared by the integration tests.
+
+use vrt::{Header, Trailer, Tsf, Tsi, VitaPacketType, VrtPacket};
+
+/// A data packet with a Stream Identifier, built with [`TestPacket::build`]
+///
+/// The default is an empty IF data packet of stream 1 without a Class Identifier, timestamps
+/// or trailer. The packet size is computed from the fields present.
+#[derive(Clone, Copy, Debug)]
+pub struct TestPacket<'a> {
+    /// Packet type, one of the [`VitaPacketType`] constants
+    pub packet_type: u8,
+    /// Stream Identifier
+    pub stream_id: u32,
+    /// Packet count of the header
+    pub packet_count: u8,
+    /// Integer-seconds timestamp, of type UTC
+    pub tsi: Option<u32>,
+    /// Fractional-seconds timestamp and its type
+    pub tsf: Option<(Tsf, u64)>,
+    /// Payload, padded to a whole number of words in the packet size
+    pub payload: &'a [u8],
+    /// Trailer, which sets the trailer indicator of the header
+    pub trailer: Option<Trailer>,
+}
+
+impl Default for TestPacket<'_> {
+    fn default() -> Self {
+        TestPacket {
+            packet_type: VitaPacketType::IFDATAWITHSTREAM,
+            stream_id: 1,
+            packet_count: 0,
+            tsi: None,
+            tsf: None,
+            payload: &[],
+            trailer: None,
+        }
+    }
+}
+
+impl<'a> TestPacket<'a> {
+    /// Builds the packet
+    pub fn build(self) -> VrtPacket<'a> {
+        let words = 2
+            + self.tsi.is_some() as usize
+            + 2 * self.tsf.is_some() as usize
+            + self.payload.len().div_ceil(4)
+            + self.trailer.is_some() as usize;
+        VrtPacket {
+            header: Header {
+                packet_type: VitaPacketType(self.packet_type),
+                c: false,
+                t: self.trailer.is_some(),
+                indicators: 0,
+                tsi: match self.tsi {
+                    Some(_) => Tsi::TSI_UTC,
+                    None => Tsi::TSI_NONE,
+                },
+                tsf: self.tsf.map_or(Tsf::TSF_NONE, |(tsf, _)| tsf),
+                packet_count: self.packet_count,
+                packet_size: words as u16,
+            },
+            stream_id: Some(self.stream_id),
+            class_id: None,
+            tsi: self.tsi,
+            tsf: self.tsf.map(|(_, fractional)| fractional),
+            data_payload: self.payload,
+            trailer: self.trailer,
+        }
+    }
+}
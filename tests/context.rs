@@ -0,0 +1,176 @@
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+
+    static VRT_STATIC_CONTEXT: &[u8] = &[
+        0x40, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x01, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0xf4, 0x24, 0x00, 0x00, 0x00,
+    ];
+
+    /// CIF0 announcing CIF1, Bandwidth, Reference Level, Gain, Sample Rate, Timestamp
+    /// Adjustment, Temperature and Formatted GPS, followed by the CIF1 word and the fields
+    static CONTEXT_PAYLOAD: &[u8] = &[
+        0x21, 0xb4, 0x40, 0x02, // CIF0
+        0x00, 0x00, 0x00, 0x04, // CIF1
+        0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, // Bandwidth, 4096 Hz
+        0x00, 0x00, 0xfb, 0x00, // Reference Level, -10 dBm
+        0x01, 0x00, 0x02, 0x80, // Gain, 2 dB and 5 dB
+        0x00, 0x00, 0x01, 0xe8, 0x48, 0x00, 0x00, 0x00, // Sample Rate, 2 MHz
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfc, 0x18, // Timestamp Adjustment, -1000 fs
+        0x00, 0x00, 0x06, 0x40, // Temperature, 25 degC
+        0x00, 0x00, 0x00, 0x01, // Formatted GPS, truncated
+    ];
+
+    #[test]
+    fn test_cif0_field() {
+        assert_eq!(Cif0Field::ReferencePointId.bit(), 30);
+        assert_eq!(Cif0Field::SampleRate.bit(), 21);
+        assert_eq!(Cif0Field::EphemerisReferenceId.bit(), 10);
+        assert_eq!(Cif0Field::from_bit(18), Some(Cif0Field::Temperature));
+        assert_eq!(Cif0Field::from_bit(9), None);
+        assert_eq!(Cif0Field::Gain.words(), 1);
+        assert_eq!(Cif0Field::Bandwidth.words(), 2);
+        assert_eq!(Cif0Field::EcefEphemeris.words(), 13);
+        assert_eq!(
+            Cif0Field::TimestampAdjustment.name(),
+            "Timestamp Adjustment"
+        );
+    }
+
+    #[test]
+    fn test_context_fields() {
+        let mut fields = ContextFields::new(CONTEXT_PAYLOAD);
+        assert_eq!(fields.cif0(), Some(0x21b4_4002));
+        assert_eq!(fields.extensions().collect::<Vec<_>>(), [(1, 4)]);
+        let values: Vec<_> = fields
+            .by_ref()
+            .map(|f| (f.field, f.offset, f.value()))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                (Cif0Field::Bandwidth, 8, Some(ContextValue::Hertz(4096.0))),
+                (
+                    Cif0Field::ReferenceLevel,
+                    16,
+                    Some(ContextValue::Dbm(-10.0))
+                ),
+                (Cif0Field::Gain, 20, Some(ContextValue::Db(7.0))),
+                (Cif0Field::SampleRate, 24, Some(ContextValue::Hertz(2e6))),
+                (
+                    Cif0Field::TimestampAdjustment,
+                    32,
+                    Some(ContextValue::Femtoseconds(-1000))
+                ),
+                (
+                    Cif0Field::Temperature,
+                    40,
+                    Some(ContextValue::Celsius(25.0))
+                ),
+            ]
+        );
+        // iteration stops at the truncated Formatted GPS field
+        assert_eq!(fields.offset(), 44);
+        assert_eq!(fields.next(), None);
+
+        let fields = ContextFields::new(CONTEXT_PAYLOAD);
+        let gain = fields.get(Cif0Field::Gain).unwrap();
+        assert_eq!(gain.raw(), Some(0x0100_0280));
+        assert_eq!(fields.get(Cif0Field::IfBandOffset), None);
+
+        assert_eq!(ContextFields::new(&[0x00, 0x20]).cif0(), None);
+        assert_eq!(ContextFields::new(&[0x00, 0x20, 0x00, 0x00]).count(), 0);
+    }
+
+    #[test]
+    fn test_context_field_fixed() {
+        let fields = ContextFields::new(CONTEXT_PAYLOAD);
+        let fixed = |field| fields.get(field).and_then(|f| f.fixed());
+        let bandwidth = fixed(Cif0Field::Bandwidth).unwrap();
+        assert_eq!(
+            bandwidth,
+            FixedPoint {
+                raw: 1 << 32,
+                radix: 20
+            }
+        );
+        assert_eq!(bandwidth.integer(), 4096);
+        assert_eq!(bandwidth.scaled(1000), Some(4_096_000));
+        assert_eq!(bandwidth.to_f64(), 4096.0);
+        let level = fixed(Cif0Field::ReferenceLevel).unwrap();
+        assert_eq!((level.raw, level.radix, level.integer()), (-1280, 7, -10));
+        assert_eq!(fixed(Cif0Field::Gain).unwrap().scaled(10), Some(70));
+        assert_eq!(fixed(Cif0Field::Temperature).unwrap().integer(), 25);
+        // fields without a fractional part have no fixed-point value
+        let adjustment = fields.get(Cif0Field::TimestampAdjustment).unwrap();
+        assert_eq!(adjustment.fixed(), None);
+        assert_eq!(adjustment.raw(), Some(-1000i64 as u64));
+
+        let half = FixedPoint { raw: -1, radix: 1 };
+        assert_eq!((half.integer(), half.to_f64()), (-1, -0.5));
+        let max = FixedPoint {
+            raw: i64::MAX,
+            radix: 0,
+        };
+        assert_eq!(max.scaled(2), None);
+    }
+
+    #[test]
+    fn test_context_value_display() {
+        assert_eq!(ContextValue::Hertz(1e6).to_string(), "1000000 Hz");
+        assert_eq!(ContextValue::Dbm(-10.5).to_string(), "-10.5 dBm");
+        assert_eq!(ContextValue::Db(7.0).to_string(), "7 dB");
+        assert_eq!(ContextValue::Celsius(25.0).to_string(), "25 degC");
+        assert_eq!(ContextValue::Femtoseconds(-1000).to_string(), "-1000 fs");
+        assert_eq!(ContextValue::Integer(3).to_string(), "3");
+        assert_eq!(ContextValue::Bits(0xab).to_string(), "0xab");
+        assert_eq!(ContextValue::Bits(0xab).unit(), None);
+        assert_eq!(ContextValue::Femtoseconds(5).as_f64(), Some(5.0));
+    }
+
+    #[test]
+    fn test_packet_context_fields() {
+        let (_, packet) = parse_vrt_packet(VRT_STATIC_CONTEXT).unwrap();
+        let fields: Vec<_> = packet.context_fields().collect();
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].field, Cif0Field::SampleRate);
+        assert_eq!(fields[0].value(), Some(ContextValue::Hertz(1e6)));
+
+        let data = VrtPacket {
+            header: Header {
+                packet_type: VitaPacketType(VitaPacketType::IFDATAWITHSTREAM),
+                ..packet.header
+            },
+            ..packet
+        };
+        assert_eq!(data.context_fields().cif0(), None);
+        assert_eq!(data.context_fields().count(), 0);
+    }
+
+    #[test]
+    fn test_signal_context() {
+        let (_, packet) = parse_vrt_packet(VRT_STATIC_CONTEXT).unwrap();
+        let mut signal = SignalContext::default();
+        signal.update(&packet);
+        assert_eq!(signal.sample_rate, Some(1e6));
+        assert_eq!(signal.gain, None);
+
+        let mut bytes = vec![0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01];
+        bytes.extend_from_slice(CONTEXT_PAYLOAD);
+        bytes[3] = (bytes.len() / 4) as u8;
+        let (_, packet) = parse_vrt_packet(&bytes).unwrap();
+        signal.update(&packet);
+        assert_eq!(signal.sample_rate, Some(2e6));
+        assert_eq!(signal.bandwidth, Some(4096.0));
+        assert_eq!(signal.reference_level, Some(-10.0));
+        assert_eq!(signal.gain, Some(7.0));
+        assert_eq!(signal.temperature, Some(25.0));
+        // fields absent from the packet keep their previous value
+        assert_eq!(signal.rf_frequency, None);
+        let (_, packet) = parse_vrt_packet(VRT_STATIC_CONTEXT).unwrap();
+        signal.update(&packet);
+        assert_eq!((signal.sample_rate, signal.gain), (Some(1e6), Some(7.0)));
+    }
+}
@@ -0,0 +1,222 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::io;
+    use std::time::Duration;
+
+    /// CIF0 announcing RF Reference Frequency, with 100 MHz
+    static RF_FREQUENCY: &[u8] = &[
+        0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x5f, 0x5e, 0x10, 0x00, 0x00, 0x00,
+    ];
+
+    /// Returns the execution acknowledge of `control` for stream `stream_id`, with the CAM
+    /// bits `cam` and the warning and error words `payload`
+    fn ack(control: &Command<'_>, stream_id: u32, cam: u32, payload: &[u8]) -> VrtPacketOwned {
+        let ack = control.acknowledge(CommandKind::ExecutionAck);
+        let ack = Command {
+            cam: ack.cam.with(cam),
+            payload,
+            ..ack
+        };
+        let encoded = ack.to_vec();
+        VrtPacketOwned::from(&ack.packet(stream_id, &encoded))
+    }
+
+    #[test]
+    fn test_control_session_execute() {
+        let sent = RefCell::new(Vec::new());
+        let received = RefCell::new(VecDeque::new());
+        let mut session = ControlSession::new(
+            1,
+            |packet: &VrtPacket<'_>| {
+                let control = packet.command().unwrap();
+                sent.borrow_mut().push(VrtPacketOwned::from(packet));
+                received.borrow_mut().push_back(ack(&control, 1, 0, &[]));
+                Ok(())
+            },
+            |_| Ok(received.borrow_mut().pop_front()),
+        )
+        .with_controllee(CommandId::Word(0x10));
+
+        let first = session.execute(RF_FREQUENCY).unwrap();
+        assert_eq!(first.kind, CommandKind::ExecutionAck);
+        assert!(first.warnings.is_empty() && first.errors.is_empty());
+        let second = session.execute(RF_FREQUENCY).unwrap();
+        assert_eq!(second.packet.as_packet().command().unwrap().message_id, 2);
+        assert_eq!(session.skipped(), 0);
+
+        let sent = sent.borrow();
+        let control = sent[0].as_packet();
+        assert_eq!(control.stream_id, Some(1));
+        let control = control.command().unwrap();
+        assert_eq!(control.kind, CommandKind::Control);
+        assert_eq!(control.message_id, 1);
+        assert_eq!(control.controllee, Some(CommandId::Word(0x10)));
+        assert_eq!(control.cam.action(), Some(ActionMode::Execute));
+        assert!(control
+            .cam
+            .contains(ControlAckMode::EXECUTION | ControlAckMode::ERRORS));
+        assert_eq!(control.payload, RF_FREQUENCY);
+    }
+
+    #[test]
+    fn test_control_session_retry() {
+        let attempts = RefCell::new(0);
+        let received = RefCell::new(VecDeque::new());
+        let mut session = ControlSession::new(
+            1,
+            |packet: &VrtPacket<'_>| {
+                let control = packet.command().unwrap();
+                *attempts.borrow_mut() += 1;
+                // the first packet is lost; the second is acknowledged after an unrelated
+                // packet and a stale acknowledge
+                if *attempts.borrow() == 2 {
+                    let stale = Command {
+                        message_id: control.message_id - 1,
+                        ..control
+                    };
+                    let mut received = received.borrow_mut();
+                    received.push_back(ack(&control, 2, 0, &[]));
+                    received.push_back(ack(&stale, 1, 0, &[]));
+                    received.push_back(ack(&control, 1, 0, &[]));
+                }
+                Ok(())
+            },
+            |_| Ok(received.borrow_mut().pop_front()),
+        );
+        assert!(session.execute(RF_FREQUENCY).is_ok());
+        assert_eq!(*attempts.borrow(), 2);
+        assert_eq!(session.skipped(), 2);
+
+        // no acknowledge at all
+        let mut session = ControlSession::new(1, |_: &VrtPacket<'_>| Ok(()), |_| Ok(None))
+            .with_retries(1)
+            .with_timeout(Duration::from_millis(10));
+        match session.execute(RF_FREQUENCY) {
+            Err(ControlError::Timeout { attempts }) => assert_eq!(attempts, 2),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // socket read timeouts count as timeouts, other errors end the request
+        let mut session = ControlSession::new(
+            1,
+            |_: &VrtPacket<'_>| Ok(()),
+            |_| Err(io::ErrorKind::WouldBlock.into()),
+        )
+        .with_retries(0);
+        assert!(matches!(
+            session.execute(RF_FREQUENCY),
+            Err(ControlError::Timeout { attempts: 1 })
+        ));
+        let mut session = ControlSession::new(
+            1,
+            |_: &VrtPacket<'_>| Ok(()),
+            |_| Err(io::ErrorKind::ConnectionReset.into()),
+        );
+        match session.execute(RF_FREQUENCY) {
+            Err(ControlError::Io(e)) => assert_eq!(e.kind(), io::ErrorKind::ConnectionReset),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_control_session_rejected() {
+        // EIF0 reporting the RF Reference Frequency out of range
+        let errors = [
+            0x08, 0x00, 0x00, 0x00, // EIF0
+            0x10, 0x00, 0x00, 0x00, // parameter out of range
+        ];
+        let received = RefCell::new(VecDeque::new());
+        let mut session = ControlSession::new(
+            1,
+            |packet: &VrtPacket<'_>| {
+                let control = packet.command().unwrap();
+                let ack = ack(&control, 1, ControlAckMode::ERRORS, &errors);
+                received.borrow_mut().push_back(ack);
+                Ok(())
+            },
+            |_| Ok(received.borrow_mut().pop_front()),
+        );
+        let ack = match session.execute(RF_FREQUENCY) {
+            Err(ControlError::Rejected(ack)) => *ack,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(ack.errors, [(27, WarningErrorResponse(1 << 28))]);
+        assert!(ack.errors[0].1.has(WarningErrorFlag::ParameterOutOfRange));
+        assert!(ack.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_control_session_query() {
+        // Gain of 5 dB and Sample Rate of 2 MHz
+        let state = [
+            0x00, 0xa0, 0x00, 0x00, 0x00, 0x00, 0x02, 0x80, 0x00, 0x00, 0x01, 0xe8, 0x48, 0x00,
+            0x00, 0x00,
+        ];
+        let sent = RefCell::new(None);
+        let received = RefCell::new(VecDeque::new());
+        let mut session = ControlSession::new(
+            1,
+            |packet: &VrtPacket<'_>| {
+                let control = packet.command().unwrap();
+                let ack = Command {
+                    payload: &state,
+                    ..control.acknowledge(CommandKind::QueryAck)
+                };
+                let encoded = ack.to_vec();
+                let ack = VrtPacketOwned::from(&ack.packet(1, &encoded));
+                received.borrow_mut().push_back(ack);
+                *sent.borrow_mut() = Some(VrtPacketOwned::from(packet));
+                Ok(())
+            },
+            |_| Ok(received.borrow_mut().pop_front()),
+        );
+        let signal = session.query(0x00a0_0000).unwrap();
+        assert_eq!(signal.gain, Some(5.0));
+        assert_eq!(signal.sample_rate, Some(2e6));
+        assert_eq!(signal.rf_frequency, None);
+
+        let sent = sent.borrow();
+        let packet = sent.as_ref().unwrap().as_packet();
+        let query = packet.command().unwrap();
+        assert_eq!(query.cam.action(), Some(ActionMode::NoAction));
+        assert!(query.cam.contains(ControlAckMode::STATE));
+        assert_eq!(query.payload, [0x00, 0xa0, 0x00, 0x00]);
+        assert_eq!(query.context_fields().count(), 0);
+    }
+
+    #[test]
+    fn test_control_session_execute_at() {
+        let at = Timestamp {
+            tsi: Tsi::TSI_UTC,
+            integer: 1_700_000_000,
+            tsf: Tsf::TSF_REAL_TIME,
+            fractional: 0,
+        };
+        let sent = RefCell::new(None);
+        let received = RefCell::new(VecDeque::new());
+        let mut session = ControlSession::new(
+            1,
+            |packet: &VrtPacket<'_>| {
+                let control = packet.command().unwrap();
+                received.borrow_mut().push_back(ack(&control, 1, 0, &[]));
+                *sent.borrow_mut() = Some(VrtPacketOwned::from(packet));
+                Ok(())
+            },
+            |_| Ok(received.borrow_mut().pop_front()),
+        );
+        session
+            .execute_at(RF_FREQUENCY, &at, TimingControl::OnTime)
+            .unwrap();
+        let sent = sent.borrow();
+        let packet = sent.as_ref().unwrap().as_packet();
+        assert_eq!(packet.timestamp(), Some(at));
+        let control = packet.command().unwrap();
+        assert_eq!(control.cam.timing(), Some(TimingControl::OnTime));
+    }
+}
@@ -0,0 +1,94 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_CONTEXT: &[u8] = &[
+        0x40, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x01, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0xf4, 0x24, 0x00, 0x00, 0x00,
+    ];
+
+    fn context(tsi: u32, tsf: u64, gain: i16) -> Vec<u8> {
+        let packet = VrtPacket {
+            header: Header {
+                packet_type: VitaPacketType(VitaPacketType::IFCONTEXT),
+                c: false,
+                t: false,
+                indicators: 0,
+                tsi: Tsi::TSI_UTC,
+                tsf: Tsf::TSF_REAL_TIME,
+                packet_count: 0,
+                packet_size: 0,
+            },
+            stream_id: Some(7),
+            class_id: None,
+            tsi: Some(tsi),
+            tsf: Some(tsf),
+            data_payload: &[
+                0x00,
+                0x80,
+                0x00,
+                0x00,
+                0x00,
+                0x00,
+                (gain >> 8) as u8,
+                gain as u8,
+            ],
+            trailer: None,
+        };
+        packet.to_vec().unwrap()
+    }
+
+    #[test]
+    fn test_context_csv_writer() {
+        let mut writer = ContextCsvWriter::new(Vec::new());
+        let (_, packet) = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap();
+        assert!(!writer.write_packet(&packet).unwrap());
+        let (_, packet) = parse_vrt_packet(VRT_STATIC_CONTEXT).unwrap();
+        assert!(writer.write_packet(&packet).unwrap());
+        let bytes = context(1_700_000_000, 500_000_000_000, 0x0280);
+        let (_, packet) = parse_vrt_packet(&bytes).unwrap();
+        assert!(writer.write_packet(&packet).unwrap());
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(
+            output,
+            "time,stream_id,rf_reference_frequency,gain,sample_rate,temperature\n\
+             ,0x00000001,,,1000000,\n\
+             1700000000.500000000000,0x00000007,,5,,\n"
+        );
+    }
+
+    #[test]
+    fn test_context_csv_writer_columns() {
+        let mut writer = ContextCsvWriter::new(Vec::new())
+            .with_columns(&["sample_rate"])
+            .unwrap();
+        let bytes = context(1, 0, 0);
+        let (_, packet) = parse_vrt_packet(&bytes).unwrap();
+        assert!(!writer.write_packet(&packet).unwrap());
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(output, "time,stream_id,sample_rate\n");
+
+        let writer = ContextCsvWriter::new(Vec::new());
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(
+            output,
+            "time,stream_id,rf_reference_frequency,gain,sample_rate,temperature\n"
+        );
+
+        let err = ContextCsvWriter::new(Vec::new())
+            .with_columns(&["gain", "sample_rat"])
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        let message = err.to_string();
+        assert!(message.starts_with("unknown context field \"sample_rat\""));
+        assert!(message.contains("sample_rate, "));
+    }
+}
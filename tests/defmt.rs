@@ -0,0 +1,20 @@
+#![cfg(feature = "defmt")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+
+    fn assert_format<T: defmt::Format>() {}
+
+    #[test]
+    fn test_defmt_format() {
+        assert_format::<Header>();
+        assert_format::<Trailer>();
+        assert_format::<VrtPacket<'_>>();
+        assert_format::<Timestamp>();
+        assert_format::<WarningErrorResponse>();
+        assert_format::<WarningErrorFlag>();
+        assert_format::<Error>();
+    }
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use std::cell::RefCell;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x18, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x01, 0x00, 0x12, 0x34, 0x56, 0x00, 0x01, 0x00,
+        0x02, 0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    static VRT_STATIC_STREAM_2: &[u8] = &[
+        0x10, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_NO_STREAM: &[u8] = &[0x00, 0x00, 0x00, 0x02, 0x01, 0x02, 0x03, 0x04];
+
+    #[test]
+    fn test_demux_routes_by_stream_id() {
+        let seen = RefCell::new(Vec::new());
+        let mut demux = Demux::new();
+        demux.register(1, |p| seen.borrow_mut().push((1, p.data_payload.to_vec())));
+        demux.register(2, |p| seen.borrow_mut().push((2, p.data_payload.to_vec())));
+
+        for bytes in [VRT_STATIC_STREAM_2, VRT_STATIC_STREAM_1] {
+            let (_, packet) = parse_vrt_packet(bytes).expect("failed to parse packet");
+            assert!(demux.route(&packet));
+        }
+        let (_, packet) = parse_vrt_packet(VRT_STATIC_NO_STREAM).expect("failed to parse packet");
+        assert!(!demux.route(&packet));
+
+        drop(demux);
+        let expected = vec![
+            (2, vec![0xca, 0xfe, 0xf0, 0x0d]),
+            (1, vec![0xde, 0xad, 0xbe, 0xef]),
+        ];
+        assert_eq!(seen.into_inner(), expected);
+    }
+
+    #[test]
+    fn test_demux_class_id_takes_precedence() {
+        let seen = RefCell::new(Vec::new());
+        let mut demux = Demux::new();
+        demux.register(1, |_| seen.borrow_mut().push("stream"));
+        demux.register_with_class_id(1, 0x0012_3456_0001_0002, |_| {
+            seen.borrow_mut().push("class")
+        });
+
+        let (_, packet) = parse_vrt_packet(VRT_STATIC_STREAM_1).expect("failed to parse packet");
+        assert!(demux.route(&packet));
+        assert!(demux.unregister_with_class_id(1, 0x0012_3456_0001_0002));
+        assert!(demux.route(&packet));
+
+        drop(demux);
+        assert_eq!(seen.into_inner(), vec!["class", "stream"]);
+    }
+
+    #[test]
+    fn test_demux_unregister_and_fallback() {
+        let seen = RefCell::new(Vec::new());
+        let mut demux = Demux::new();
+        demux.register(2, |_| seen.borrow_mut().push("stream"));
+        demux.set_fallback(|_| seen.borrow_mut().push("fallback"));
+
+        let (_, packet) = parse_vrt_packet(VRT_STATIC_STREAM_2).expect("failed to parse packet");
+        assert!(demux.route(&packet));
+        assert!(demux.is_registered(2));
+        assert!(demux.unregister(2));
+        assert!(!demux.unregister(2));
+        assert!(!demux.is_registered(2));
+        assert!(demux.route(&packet));
+
+        drop(demux);
+        assert_eq!(seen.into_inner(), vec!["stream", "fallback"]);
+    }
+}
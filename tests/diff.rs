@@ -0,0 +1,116 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_IFDATA_WITH_TRAILER: &[u8] = &[
+        0x14, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef, 0x40, 0x0f, 0x00,
+        0x00,
+    ];
+
+    static VRT_STATIC_CONTEXT: &[u8] = &[
+        0x40, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x01, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0xf4, 0x24, 0x00, 0x00, 0x00,
+    ];
+
+    fn packets<'a>(capture: &[&'a [u8]]) -> Vec<VrtPacket<'a>> {
+        capture
+            .iter()
+            .map(|bytes| parse_vrt_packet(bytes).unwrap().1)
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_captures_identical() {
+        let capture = [
+            VRT_STATIC_STREAM_1,
+            VRT_STATIC_IFDATA_WITH_TRAILER,
+            VRT_STATIC_CONTEXT,
+        ];
+        let diff = diff_captures(packets(&capture), packets(&capture));
+        assert!(diff.is_identical());
+        assert_eq!((diff.packets_a, diff.packets_b, diff.matched), (3, 3, 3));
+
+        // Alignment does not depend on the interleaving of streams
+        let reordered = [
+            VRT_STATIC_CONTEXT,
+            VRT_STATIC_IFDATA_WITH_TRAILER,
+            VRT_STATIC_STREAM_1,
+        ];
+        assert!(diff_captures(packets(&capture), packets(&reordered)).is_identical());
+    }
+
+    #[test]
+    fn test_diff_captures_divergences() {
+        let mut payload = VRT_STATIC_STREAM_1.to_vec();
+        payload[22] = 0x00;
+        let mut context = VRT_STATIC_CONTEXT.to_vec();
+        context[17] = 0x25;
+
+        let diff = diff_captures(
+            packets(&[
+                VRT_STATIC_STREAM_1,
+                VRT_STATIC_IFDATA_WITH_TRAILER,
+                VRT_STATIC_CONTEXT,
+            ]),
+            packets(&[&payload, &context]),
+        );
+        assert!(!diff.is_identical());
+        assert_eq!(diff.matched, 2);
+        assert_eq!(diff.divergences.len(), 3);
+        let timestamp = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap().1.timestamp();
+        assert_eq!(
+            diff.divergences[0],
+            Divergence::Payload {
+                a: 0,
+                b: 0,
+                stream_id: Some(1),
+                timestamp,
+                offset: 2,
+            }
+        );
+        assert_eq!(
+            diff.divergences[1],
+            Divergence::Context {
+                a: 2,
+                b: 1,
+                stream_id: Some(1),
+                timestamp: None,
+                fields: vec!["Sample Rate"],
+            }
+        );
+        assert_eq!(
+            diff.divergences[2],
+            Divergence::MissingFromB {
+                packet: 1,
+                stream_id: Some(2),
+                timestamp: None,
+            }
+        );
+        assert_eq!(
+            diff.divergences[0].to_string(),
+            "stream 0x00000001 at 10.000000000005: payloads of packet #0 of a and #0 of b differ at byte 2"
+        );
+        assert_eq!(
+            diff.divergences[2].to_string(),
+            "stream 0x00000002: packet #1 of a missing from b"
+        );
+
+        let diff = diff_captures(packets(&[]), packets(&[VRT_STATIC_STREAM_1]));
+        assert_eq!(
+            diff.divergences,
+            vec![Divergence::MissingFromA {
+                packet: 0,
+                stream_id: Some(1),
+                timestamp,
+            }]
+        );
+    }
+}
@@ -0,0 +1,207 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+
+    fn context() -> DifiContext {
+        DifiContext {
+            stream_id: 5,
+            packet_count: 3,
+            seconds: 1_700_000_000,
+            picoseconds: 250_000_000_000,
+            changed: true,
+            reference_point: 0x64,
+            bandwidth: 10e6,
+            if_frequency: 0.0,
+            rf_frequency: 2.4e9,
+            if_band_offset: -1.5e6,
+            reference_level: -20.5,
+            gain: 12.25,
+            sample_rate: 12.5e6,
+            timestamp_adjustment: -1000,
+            timestamp_calibration: 1_600_000_000,
+            state_event: 0x000a_0000,
+            item_size: DifiItemSize::Bits16,
+        }
+    }
+
+    #[test]
+    fn test_difi_context_roundtrip() {
+        let packet = DifiPacket::Context(context()).to_packet();
+        let bytes = packet.as_packet().to_vec().unwrap();
+        assert_eq!(bytes.len(), 27 * 4);
+        assert_eq!(bytes[..4], [0x49, 0x63, 0x00, 0x1b]);
+        assert_eq!(
+            bytes[8..16],
+            [0x00, 0x6a, 0x62, 0x1e, 0x00, 0x00, 0x00, 0x01]
+        );
+        assert_eq!(bytes[28..32], [0xfb, 0xb9, 0x80, 0x00]);
+        assert_eq!(
+            bytes[100..],
+            [0xa0, 0x00, 0x03, 0xcf, 0x00, 0x00, 0x00, 0x00]
+        );
+
+        let (_, parsed) = parse_vrt_packet(&bytes).unwrap();
+        assert_eq!(
+            DifiPacket::try_from(&parsed),
+            Ok(DifiPacket::Context(context()))
+        );
+        let signal = parsed.context_fields().get(Cif0Field::SampleRate).unwrap();
+        assert_eq!(signal.value(), Some(ContextValue::Hertz(12.5e6)));
+    }
+
+    #[test]
+    fn test_difi_data_and_version() {
+        let payload = [1, 2, 3, 4, 5, 6, 7, 8];
+        let data = DifiData {
+            stream_id: 5,
+            packet_count: 9,
+            seconds: 10,
+            picoseconds: 20,
+            payload: &payload,
+        };
+        let packet = DifiPacket::Data(data).to_packet();
+        let bytes = packet.as_packet().to_vec().unwrap();
+        assert_eq!(bytes[..4], [0x18, 0x69, 0x00, 0x09]);
+        let (_, parsed) = parse_vrt_packet(&bytes).unwrap();
+        assert_eq!(DifiPacket::try_from(&parsed), Ok(DifiPacket::Data(data)));
+
+        let version = DifiVersion {
+            stream_id: 5,
+            packet_count: 0,
+            seconds: 10,
+            picoseconds: 0,
+            changed: false,
+            spec_compliance: DIFI_V49_2,
+            version: 0x0001_0002,
+        };
+        let packet = DifiPacket::Version(version).to_packet();
+        let bytes = packet.as_packet().to_vec().unwrap();
+        assert_eq!(bytes.len(), 11 * 4);
+        assert_eq!(bytes[..4], [0x59, 0x60, 0x00, 0x0b]);
+        let (_, parsed) = parse_vrt_packet(&bytes).unwrap();
+        let difi = DifiPacket::try_from(&parsed).unwrap();
+        assert_eq!(difi, DifiPacket::Version(version));
+        assert_eq!(difi.stream_id(), 5);
+    }
+
+    #[test]
+    fn test_difi_rejects_other_packets() {
+        let packet = DifiPacket::Context(context()).to_packet();
+        let accepted = |change: &dyn Fn(&mut VrtPacketOwned)| {
+            let mut packet = packet.clone();
+            change(&mut packet);
+            DifiPacket::try_from(&packet.as_packet()).is_ok()
+        };
+        assert!(accepted(&|_| ()));
+        assert!(!accepted(&|p| p.class_id = Some(0x0012_a200_0000_0001)));
+        assert!(!accepted(&|p| p.class_id = None));
+        assert!(!accepted(&|p| p.header.tsf = Tsf::TSF_SAMPLE_COUNT));
+        assert!(!accepted(&|p| p.header.indicators = 0));
+        // a missing mandatory field, the Gain
+        assert!(!accepted(&|p| {
+            p.data_payload[1] &= !0x80;
+            p.data_payload.drain(44..48);
+        }));
+        // a payload format other than 8 or 16-bit complex samples
+        assert!(!accepted(&|p| p.data_payload[75] = 0x0f));
+        assert_eq!(
+            DifiItemSize::from_payload_format(0xa000_01c7_0000_0000),
+            Some(DifiItemSize::Bits8)
+        );
+    }
+
+    #[test]
+    fn test_difi_writer_reader() {
+        let context = DifiContext {
+            seconds: 100,
+            picoseconds: 0,
+            sample_rate: 1000.0,
+            ..context()
+        };
+        let mut writer = DifiWriter::new(context, 250).with_context_interval(2);
+        let samples: Vec<(i16, i16)> = (0..1100).map(|n| (n, -n)).collect();
+        let mut packets = writer.write(&samples);
+        assert_eq!(packets.len(), 4 + 2);
+        packets.extend(writer.flush());
+        assert!(writer.flush().is_empty());
+
+        let mut reader = DifiReader::new();
+        let mut read = Vec::new();
+        let mut kinds = String::new();
+        for packet in &packets {
+            let bytes = packet.as_packet().to_vec().unwrap();
+            let (_, parsed) = parse_vrt_packet(&bytes).unwrap();
+            match reader.read(&parsed).unwrap() {
+                DifiPacket::Data(data) => {
+                    kinds.push('d');
+                    assert_eq!(data.packet_count as usize, read.len() / 250);
+                    assert_eq!(data.seconds, 100 + read.len() as u32 / 1000);
+                    read.extend(reader.samples(&data).unwrap());
+                }
+                DifiPacket::Context(context) => {
+                    kinds.push('c');
+                    assert_eq!(context.changed, read.is_empty());
+                    assert_eq!(
+                        context.picoseconds,
+                        (read.len() as u64 % 1000) * 1_000_000_000
+                    );
+                }
+                DifiPacket::Version(_) => unreachable!(),
+            }
+        }
+        assert_eq!(kinds, "cddcddcd");
+        assert_eq!(read, samples);
+        assert_eq!(reader.context(5).unwrap().sample_rate, 1000.0);
+
+        // a change is announced before the next data packet
+        writer.context_mut().item_size = DifiItemSize::Bits8;
+        let packets = writer.write(&[(1, -1), (-128, 127), (300, 0)]);
+        assert!(packets.is_empty());
+        let packets = writer.flush();
+        assert_eq!(packets.len(), 2);
+        let context = reader.read(&packets[0].as_packet()).unwrap();
+        match context {
+            DifiPacket::Context(context) => {
+                assert!(context.changed);
+                assert_eq!(
+                    (context.seconds, context.picoseconds),
+                    (101, 100_000_000_000)
+                );
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(
+            packets[1].data_payload,
+            [1, 0xff, 0x80, 0x7f, 0x2c, 0, 0, 0]
+        );
+        let data = match reader.read(&packets[1].as_packet()).unwrap() {
+            DifiPacket::Data(data) => data,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(
+            reader.samples(&data).unwrap(),
+            [(1, -1), (-128, 127), (44, 0), (0, 0)]
+        );
+        assert_eq!(DifiReader::new().samples(&data), None);
+    }
+
+    #[test]
+    fn test_difi_writer_partial_writes() {
+        let samples: Vec<(i16, i16)> = (0..1100).map(|n| (n, -n)).collect();
+        let mut writer = DifiWriter::new(context(), 250);
+        let mut expected = writer.write(&samples);
+        expected.extend(writer.flush());
+
+        // samples kept from one write complete the packets of the next
+        let mut writer = DifiWriter::new(context(), 250);
+        let mut packets = Vec::new();
+        for chunk in samples.chunks(70) {
+            packets.extend(writer.write(chunk));
+        }
+        packets.extend(writer.flush());
+        assert_eq!(packets, expected);
+    }
+}
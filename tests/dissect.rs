@@ -0,0 +1,91 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_IFDATA_WITH_TRAILER: &[u8] = &[
+        0x14, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef, 0x40, 0x0f, 0x00,
+        0x00,
+    ];
+
+    static VRT_STATIC_CONTEXT: &[u8] = &[
+        0x40, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x01, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0xf4, 0x24, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn test_dissect() {
+        let (_, packet) = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap();
+        let tree = packet.dissect();
+        assert_eq!(tree.bits, 0..192);
+        let names: Vec<_> = tree.children.iter().map(|f| f.name).collect();
+        assert_eq!(
+            names,
+            [
+                "Header",
+                "Stream Identifier",
+                "Integer-seconds Timestamp",
+                "Fractional-seconds Timestamp",
+                "Payload"
+            ]
+        );
+        let tsf = tree.find("Fractional-seconds Timestamp").unwrap();
+        assert_eq!((tsf.bits.clone(), tsf.raw), (96..160, Some(5)));
+        assert_eq!(tsf.value, "5 ps");
+        let packet_type = tree.find("Packet Type").unwrap();
+        assert_eq!(
+            (packet_type.raw, packet_type.value.as_str()),
+            (Some(1), "IF Data with Stream ID")
+        );
+        assert_eq!(tree.find("TSI").unwrap().value, "UTC");
+        assert_eq!(tree.find("Payload").unwrap().raw, None);
+        assert!(tree.find("Trailer").is_none());
+
+        let (_, packet) = parse_vrt_packet(VRT_STATIC_IFDATA_WITH_TRAILER).unwrap();
+        assert_eq!(
+            packet.dissect().to_string(),
+            "VRT Packet [bits 0..128]: IF Data with Stream ID, stream 0x00000002, count 0, \
+             4 words, 4 byte payload, trailer\n\
+             \x20   Header [bits 0..32]: 0x14000004\n\
+             \x20       Packet Type [bits 0..4]: IF Data with Stream ID\n\
+             \x20       Class Identifier Included [bits 4..5]: false\n\
+             \x20       Trailer Included [bits 5..6]: true\n\
+             \x20       TSI [bits 8..10]: None\n\
+             \x20       TSF [bits 10..12]: None\n\
+             \x20       Packet Count [bits 12..16]: 0\n\
+             \x20       Packet Size [bits 16..32]: 4 words\n\
+             \x20   Stream Identifier [bits 32..64]: 0x00000002\n\
+             \x20   Payload [bits 64..96]: 4 bytes\n\
+             \x20   Trailer [bits 96..128]: 0x400f0000\n\
+             \x20       Enables [bits 96..108]: valid data\n\
+             \x20       Indicators [bits 108..120]: calibrated time, valid data, reference lock, AGC/MGC\n\
+             \x20       Associated Context Packet Count [bits 120..128]: not given\n"
+        );
+
+        let (_, packet) = parse_vrt_packet(VRT_STATIC_CONTEXT).unwrap();
+        let tree = packet.dissect();
+        let payload = tree.find("Payload").unwrap();
+        assert_eq!(payload.children.len(), 2);
+        assert_eq!(payload.children[0].value, "bits 21");
+        let sample_rate = tree.find("Sample Rate").unwrap();
+        assert_eq!(sample_rate.bits, 96..160);
+        assert_eq!(sample_rate.value, "1000000 Hz");
+
+        // Timestamp Adjustment is in femtoseconds
+        let bytes = [
+            0x40, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x01, 0x00, 0x10, 0x00, 0x00, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xfc, 0x18,
+        ];
+        let (_, packet) = parse_vrt_packet(&bytes).unwrap();
+        let adjustment = packet.dissect();
+        let adjustment = adjustment.find("Timestamp Adjustment").unwrap();
+        assert_eq!(adjustment.value, "-1000 fs");
+    }
+}
@@ -0,0 +1,286 @@
+#![cfg(feature = "ffi")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::ffi::*;
+    use crate::vrt::{encode_vrt_trailer, parse_vrt_packet, ClassId, Header, VitaPacketType};
+    use std::ptr;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    #[test]
+    fn test_ffi_parse() {
+        unsafe {
+            let mut packet = ptr::null_mut();
+            let mut consumed = 0;
+            let status = vrt_packet_parse(
+                VRT_STATIC_STREAM_1.as_ptr(),
+                VRT_STATIC_STREAM_1.len(),
+                &mut packet,
+                &mut consumed,
+            );
+            assert_eq!(status, VRT_OK);
+            assert_eq!(consumed, 24);
+            assert_eq!(vrt_packet_type(packet), 1);
+
+            let mut stream_id = 0;
+            assert!(vrt_packet_stream_id(packet, &mut stream_id));
+            assert_eq!(stream_id, 1);
+            assert!(!vrt_packet_class_id(packet, &mut 0));
+
+            let (mut tsi_type, mut tsi, mut tsf_type, mut tsf) = (0, 0, 0, 0);
+            assert!(vrt_packet_timestamp(
+                packet,
+                &mut tsi_type,
+                &mut tsi,
+                &mut tsf_type,
+                &mut tsf
+            ));
+            assert_eq!((tsi_type, tsi, tsf_type, tsf), (1, 10, 2, 5));
+
+            let mut len = 0;
+            let payload = vrt_packet_payload(packet, &mut len);
+            assert_eq!(
+                std::slice::from_raw_parts(payload, len),
+                [0xca, 0xfe, 0xf0, 0x0d]
+            );
+            vrt_packet_free(packet);
+
+            let status = vrt_packet_parse(
+                VRT_STATIC_STREAM_1.as_ptr(),
+                10,
+                &mut packet,
+                ptr::null_mut(),
+            );
+            assert_eq!(status, VRT_ERR_INCOMPLETE);
+            assert_eq!(
+                vrt_packet_parse(ptr::null(), 0, &mut packet, ptr::null_mut()),
+                VRT_ERR_INVALID_ARGUMENT
+            );
+        }
+    }
+
+    #[test]
+    fn test_ffi_build() {
+        unsafe {
+            let packet = vrt_packet_new_data(1);
+            assert_eq!(vrt_packet_set_timestamp(packet, 1, 10, 2, 5), VRT_OK);
+            assert_eq!(
+                vrt_packet_set_payload(packet, [0xca, 0xfe, 0xf0, 0x0d].as_ptr(), 4),
+                VRT_OK
+            );
+            assert_eq!(vrt_packet_encoded_len(packet), 24);
+
+            let mut buf = [0u8; 24];
+            let mut written = 0;
+            assert_eq!(
+                vrt_packet_serialize(packet, buf.as_mut_ptr(), buf.len(), &mut written),
+                VRT_OK
+            );
+            assert_eq!(&buf[..written], VRT_STATIC_STREAM_1);
+            assert_eq!(
+                vrt_packet_serialize(packet, buf.as_mut_ptr(), 20, &mut written),
+                VRT_ERR_BUFFER_TOO_SMALL
+            );
+            assert_eq!(
+                vrt_packet_set_timestamp(packet, 4, 0, 0, 0),
+                VRT_ERR_INVALID_ARGUMENT
+            );
+            vrt_packet_free(packet);
+
+            let context = vrt_packet_new_context(1);
+            vrt_packet_set_count(context, 17);
+            assert_eq!(vrt_packet_type(context), 4);
+            assert_eq!(vrt_packet_count(context), 1);
+            vrt_packet_free(context);
+        }
+    }
+
+    #[test]
+    fn test_ffi_mirrors() {
+        let (_, packet) = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap();
+        let header = VrtHeader::from(packet.header);
+        assert_eq!((header.packet_type, header.tsi, header.tsf), (1, 1, 2));
+        assert_eq!(Header::from(header), packet.header);
+        // the TSM bit of a context packet survives the round trip
+        let context = Header {
+            packet_type: VitaPacketType(VitaPacketType::IFCONTEXT),
+            indicators: 0b01,
+            ..packet.header
+        };
+        let header = VrtHeader::from(context);
+        assert_eq!(header.indicators, 0b01);
+        assert_eq!(Header::from(header), context);
+
+        let class_id = vrt_class_id_split(0x0012_3456_0001_0002);
+        assert_eq!(
+            ClassId::from(class_id),
+            ClassId {
+                oui: 0x12_3456,
+                packet_class_code: 2,
+                information_class_code: 1
+            }
+        );
+        assert_eq!(vrt_class_id_join(class_id), 0x0012_3456_0001_0002);
+
+        unsafe {
+            let packet = vrt_packet_new_data(1);
+            let mut trailer = VrtTrailer::default();
+            assert!(!vrt_packet_trailer(packet, &mut trailer));
+            trailer.valid_data_enable = true;
+            trailer.valid_data_indicator = true;
+            assert_eq!(vrt_packet_set_trailer(packet, &trailer), VRT_OK);
+
+            let mut header = VrtHeader::default();
+            assert!(vrt_packet_header(packet, &mut header));
+            assert!(header.t);
+            let mut copy = VrtTrailer::default();
+            assert!(vrt_packet_trailer(packet, &mut copy));
+            assert_eq!(copy, trailer);
+            let mut buf = [0u8; 12];
+            assert_eq!(
+                vrt_packet_serialize(packet, buf.as_mut_ptr(), buf.len(), ptr::null_mut()),
+                VRT_OK
+            );
+            assert_eq!(&buf[8..], encode_vrt_trailer(&trailer.into()));
+
+            assert_eq!(vrt_packet_set_trailer(packet, ptr::null()), VRT_OK);
+            assert!(!vrt_packet_trailer(packet, &mut copy));
+            vrt_packet_free(packet);
+        }
+    }
+
+    #[test]
+    fn test_ffi_context() {
+        unsafe {
+            let packet = vrt_packet_new_context(1);
+            let mut context = VrtContext::default();
+            assert!(vrt_packet_context(packet, &mut context));
+            assert_eq!(context.cif0, 0);
+
+            context = VrtContext {
+                cif0: VRT_CIF0_CHANGE_INDICATOR
+                    | VRT_CIF0_RF_REFERENCE_FREQUENCY
+                    | VRT_CIF0_REFERENCE_LEVEL
+                    | VRT_CIF0_GAIN
+                    | VRT_CIF0_SAMPLE_RATE
+                    | VRT_CIF0_TIMESTAMP_ADJUSTMENT
+                    | VRT_CIF0_TEMPERATURE,
+                rf_reference_frequency: 100e6,
+                reference_level: -10.5,
+                gain: 7.0,
+                sample_rate: 1e6,
+                timestamp_adjustment: -1000,
+                temperature: 25.25,
+                // absent, so not encoded
+                bandwidth: 1e3,
+                ..VrtContext::default()
+            };
+            assert_eq!(vrt_packet_set_context(packet, &context), VRT_OK);
+            let mut len = 0;
+            vrt_packet_payload(packet, &mut len);
+            assert_eq!(len, 4 + 8 + 4 + 4 + 8 + 8 + 4);
+            let mut copy = VrtContext::default();
+            assert!(vrt_packet_context(packet, &mut copy));
+            assert_eq!(
+                copy,
+                VrtContext {
+                    bandwidth: 0.0,
+                    ..context
+                }
+            );
+
+            let mut buf = vec![0u8; vrt_packet_encoded_len(packet)];
+            let mut written = 0;
+            assert_eq!(
+                vrt_packet_serialize(packet, buf.as_mut_ptr(), buf.len(), &mut written),
+                VRT_OK
+            );
+            let (_, parsed) = parse_vrt_packet(&buf[..written]).unwrap();
+            assert_eq!(VrtContext::from(parsed.context_fields()), copy);
+            vrt_packet_free(packet);
+
+            let data = vrt_packet_new_data(1);
+            assert!(!vrt_packet_context(data, &mut copy));
+            assert_eq!(
+                vrt_packet_set_context(data, &context),
+                VRT_ERR_INVALID_ARGUMENT
+            );
+            vrt_packet_free(data);
+        }
+    }
+
+    #[test]
+    fn test_ffi_buffer_unpack() {
+        let mut buf = VRT_STATIC_STREAM_1.repeat(2);
+        buf.extend_from_slice(&VRT_STATIC_STREAM_1[..8]);
+        unsafe {
+            let (mut packets, mut samples) = (0, 0);
+            assert_eq!(
+                vrt_buffer_count(buf.as_ptr(), buf.len(), &mut packets, &mut samples),
+                VRT_OK
+            );
+            assert_eq!((packets, samples), (2, 4));
+
+            let mut infos = vec![VrtPacketInfo::default(); packets];
+            let mut values = vec![0i16; samples];
+            let mut written = 0;
+            let status = vrt_buffer_unpack(
+                buf.as_ptr(),
+                buf.len(),
+                infos.as_mut_ptr(),
+                infos.len(),
+                values.as_mut_ptr(),
+                values.len(),
+                &mut written,
+            );
+            assert_eq!(status, VRT_OK);
+            assert_eq!(written, 2);
+            assert_eq!(values, [-0x3502, -0x0ff3, -0x3502, -0x0ff3]);
+            assert_eq!(infos[1].header.packet_size, 6);
+            assert!(infos[1].has_stream_id);
+            assert_eq!((infos[1].tsi, infos[1].tsf), (10, 5));
+            assert_eq!((infos[1].sample_offset, infos[1].sample_count), (2, 2));
+
+            let status = vrt_buffer_unpack(
+                buf.as_ptr(),
+                buf.len(),
+                infos.as_mut_ptr(),
+                infos.len(),
+                values.as_mut_ptr(),
+                3,
+                &mut written,
+            );
+            assert_eq!(status, VRT_ERR_BUFFER_TOO_SMALL);
+            assert_eq!(written, 1);
+
+            // no packets, or an invalid argument, unpack nothing
+            written = 5;
+            let status = vrt_buffer_unpack(
+                buf.as_ptr(),
+                0,
+                ptr::null_mut(),
+                0,
+                ptr::null_mut(),
+                0,
+                &mut written,
+            );
+            assert_eq!((status, written), (VRT_OK, 0));
+            written = 5;
+            let status = vrt_buffer_unpack(
+                ptr::null(),
+                0,
+                ptr::null_mut(),
+                0,
+                ptr::null_mut(),
+                0,
+                &mut written,
+            );
+            assert_eq!((status, written), (VRT_ERR_INVALID_ARGUMENT, 0));
+        }
+    }
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x1c, 0x00, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x12, 0x34, 0x56, 0x00, 0x01, 0x00,
+        0x02, 0xde, 0xad, 0xbe, 0xef, 0x40, 0x04, 0x00, 0x00,
+    ];
+
+    static VRT_STATIC_STREAM_2: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_CONTEXT_STREAM_1: &[u8] = &[
+        0x40, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    fn packets() -> Vec<VrtPacket<'static>> {
+        [
+            VRT_STATIC_STREAM_1,
+            VRT_STATIC_STREAM_2,
+            VRT_STATIC_CONTEXT_STREAM_1,
+        ]
+        .iter()
+        .map(|b| parse_vrt_packet(b).expect("failed to parse packet").1)
+        .collect()
+    }
+
+    fn matching(filter: &Filter) -> Vec<(u8, Option<u32>)> {
+        filter
+            .apply(packets())
+            .map(|p| (p.header.packet_type.0, p.stream_id))
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_leaves() {
+        assert_eq!(matching(&Filter::Any).len(), 3);
+        assert_eq!(
+            matching(&Filter::PacketType(VitaPacketType(
+                VitaPacketType::IFCONTEXT
+            ))),
+            vec![(4, Some(1))]
+        );
+        assert_eq!(matching(&Filter::StreamIds(vec![2, 3])), vec![(1, Some(2))]);
+        assert_eq!(
+            matching(&Filter::ClassId(0x0012_3456_0001_0002)),
+            vec![(1, Some(1))]
+        );
+        let valid = true;
+        assert_eq!(
+            matching(&Filter::Trailer(Box::new(move |t| {
+                t.valid_data_enable && t.valid_data_indicator == valid
+            }))),
+            vec![(1, Some(1))]
+        );
+        assert_eq!(
+            format!("{:?}", !Filter::Trailer(Box::new(|_| true))),
+            "Not(Trailer(..))"
+        );
+    }
+
+    #[test]
+    fn test_filter_time_range() {
+        let at = |integer, fractional| Timestamp {
+            tsi: Tsi::TSI_UTC,
+            integer,
+            tsf: Tsf::TSF_REAL_TIME,
+            fractional,
+        };
+        let filter = Filter::TimeRange {
+            start: at(10, 5),
+            end: at(11, 0),
+        };
+        assert_eq!(matching(&filter), vec![(1, Some(2))]);
+        let filter = Filter::TimeRange {
+            start: at(10, 6),
+            end: at(11, 0),
+        };
+        assert!(matching(&filter).is_empty());
+        // a window of GPS timestamps does not contain UTC timestamps
+        let gps = |integer| Timestamp {
+            tsi: Tsi::TSI_GPS,
+            ..at(integer, 0)
+        };
+        let filter = Filter::TimeRange {
+            start: gps(0),
+            end: gps(20),
+        };
+        assert!(matching(&filter).is_empty());
+    }
+
+    #[test]
+    fn test_filter_combinators() {
+        let data = || Filter::PacketType(VitaPacketType(VitaPacketType::IFDATAWITHSTREAM));
+        let filter = data().and(Filter::StreamIds(vec![1]));
+        assert_eq!(matching(&filter), vec![(1, Some(1))]);
+
+        let filter = data().or(Filter::StreamIds(vec![1]));
+        assert_eq!(matching(&filter).len(), 3);
+
+        let filter = !data();
+        assert_eq!(matching(&filter), vec![(4, Some(1))]);
+    }
+}
@@ -0,0 +1,57 @@
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_IFDATA_WITH_TRAILER: &[u8] = &[
+        0x14, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef, 0x40, 0x0f, 0x00,
+        0x00,
+    ];
+
+    fn digest(capture: &[&[u8]]) -> Fingerprint {
+        fingerprint(
+            capture
+                .iter()
+                .map(|bytes| parse_vrt_packet(bytes).unwrap().1),
+        )
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let capture = [VRT_STATIC_STREAM_1, VRT_STATIC_IFDATA_WITH_TRAILER];
+        let fingerprint = digest(&capture);
+        assert_eq!(fingerprint.packets(), 2);
+        assert_eq!(fingerprint, digest(&capture));
+        assert_ne!(fingerprint, Fingerprint::new());
+
+        // Stable across releases and platforms
+        assert_eq!(fingerprint.to_string(), "ae9116503d7f4076/2");
+    }
+
+    #[test]
+    fn test_fingerprint_detects_changes() {
+        let capture = [VRT_STATIC_STREAM_1, VRT_STATIC_IFDATA_WITH_TRAILER];
+        let fingerprint = digest(&capture);
+        let reordered = [VRT_STATIC_IFDATA_WITH_TRAILER, VRT_STATIC_STREAM_1];
+        assert_ne!(digest(&reordered), fingerprint);
+
+        // Packet count, timestamp, payload and trailer bytes
+        for byte in [1, 19, 23] {
+            let mut changed = VRT_STATIC_STREAM_1.to_vec();
+            changed[byte] ^= 0x01;
+            assert_ne!(
+                digest(&[&changed, VRT_STATIC_IFDATA_WITH_TRAILER]),
+                fingerprint
+            );
+        }
+        let mut changed = VRT_STATIC_IFDATA_WITH_TRAILER.to_vec();
+        changed[13] = 0x0e;
+        assert_ne!(digest(&[VRT_STATIC_STREAM_1, &changed]), fingerprint);
+    }
+}
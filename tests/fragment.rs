@@ -0,0 +1,172 @@
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+
+    fn template(tsf: Tsf) -> VrtPacket<'static> {
+        VrtPacket {
+            header: Header {
+                packet_type: VitaPacketType(VitaPacketType::IFDATAWITHSTREAM),
+                c: false,
+                t: true,
+                indicators: 0,
+                tsi: Tsi::TSI_UTC,
+                tsf,
+                packet_count: 14,
+                packet_size: 0,
+            },
+            stream_id: Some(7),
+            class_id: None,
+            tsi: Some(100),
+            tsf: Some(0),
+            data_payload: &[],
+            trailer: Some(Trailer {
+                calibrated_time_enable: false,
+                valid_data_enable: true,
+                reference_lock_enable: false,
+                agcmgc_enable: false,
+                detected_signal_enable: false,
+                spectral_inversion_enable: false,
+                overrange_enable: false,
+                sample_loss_enable: false,
+                user_defined_enable_1: false,
+                user_defined_enable_2: false,
+                user_defined_enable_3: false,
+                user_defined_enable_4: false,
+                calibrated_time_indicator: false,
+                valid_data_indicator: true,
+                reference_lock_indicator: false,
+                agcmgc_indicator: false,
+                detected_signal_indicator: false,
+                spectral_inversion_indicator: false,
+                overrange_indicator: false,
+                sample_loss_indicator: false,
+                user_defined_indicator_1: false,
+                user_defined_indicator_2: false,
+                user_defined_indicator_3: false,
+                user_defined_indicator_4: false,
+                associated_context_packet_count_enable: false,
+                associated_context_packet_count: 0,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_fragmenter_sample_count() {
+        let samples: Vec<u8> = (0..40).collect();
+        // 1 header + 1 stream id + 1 tsi + 2 tsf + 1 trailer = 6 words of overhead
+        let mtu = 6 * 4 + 16;
+        let template = template(Tsf::TSF_SAMPLE_COUNT);
+        let fragmenter = Fragmenter::new(&template, &samples, 4, mtu)
+            .expect("failed to create fragmenter")
+            .with_sample_rate(10);
+        assert_eq!(fragmenter.payload_len(), 16);
+
+        let packets: Vec<_> = fragmenter.collect();
+        assert_eq!(packets.len(), 3);
+        assert_eq!(packets[0].data_payload, &samples[0..16]);
+        assert_eq!(packets[2].data_payload, &samples[32..40]);
+        let counts: Vec<_> = packets.iter().map(|p| p.header.packet_count).collect();
+        assert_eq!(counts, vec![14, 15, 0]);
+        let sizes: Vec<_> = packets.iter().map(|p| p.header.packet_size).collect();
+        assert_eq!(sizes, vec![10, 10, 8]);
+        let timestamps: Vec<_> = packets.iter().map(|p| (p.tsi, p.tsf)).collect();
+        assert_eq!(
+            timestamps,
+            vec![
+                (Some(100), Some(0)),
+                (Some(100), Some(4)),
+                (Some(100), Some(8))
+            ]
+        );
+        assert!(packets.iter().all(|p| p.trailer == template.trailer));
+    }
+
+    #[test]
+    fn test_fragmenter_real_time_rollover() {
+        let samples = [0u8; 24];
+        let template = template(Tsf::TSF_REAL_TIME);
+        let packets: Vec<_> = Fragmenter::new(&template, &samples, 2, 6 * 4 + 8)
+            .expect("failed to create fragmenter")
+            .with_sample_rate(8)
+            .collect();
+        assert_eq!(packets.len(), 3);
+        let timestamps: Vec<_> = packets.iter().map(|p| (p.tsi, p.tsf)).collect();
+        assert_eq!(
+            timestamps,
+            vec![
+                (Some(100), Some(0)),
+                (Some(100), Some(500_000_000_000)),
+                (Some(101), Some(0))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fragmenter_real_time_no_drift() {
+        let samples = [0u8; 16];
+        let template = template(Tsf::TSF_REAL_TIME);
+        // a sample period of a third of a second is not a whole number of picoseconds
+        let packets: Vec<_> = Fragmenter::new(&template, &samples, 4, 6 * 4 + 4)
+            .expect("failed to create fragmenter")
+            .with_sample_rate(3)
+            .collect();
+        let timestamps: Vec<_> = packets.iter().map(|p| (p.tsi, p.tsf)).collect();
+        assert_eq!(
+            timestamps,
+            vec![
+                (Some(100), Some(0)),
+                (Some(100), Some(333_333_333_333)),
+                (Some(100), Some(666_666_666_666)),
+                (Some(101), Some(0))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fragmenter_timestamp_overflow() {
+        let samples = [0u8; 8];
+        let mut template = template(Tsf::TSF_SAMPLE_COUNT);
+        template.tsi = Some(u32::MAX);
+        template.tsf = Some(u64::MAX - 1);
+        let packets: Vec<_> = Fragmenter::new(&template, &samples, 4, 6 * 4 + 4)
+            .expect("failed to create fragmenter")
+            .with_sample_rate(u64::MAX)
+            .collect();
+        let timestamps: Vec<_> = packets.iter().map(|p| (p.tsi, p.tsf)).collect();
+        assert_eq!(
+            timestamps,
+            vec![(Some(u32::MAX), Some(u64::MAX - 1)), (Some(0), Some(0))]
+        );
+
+        template.header.tsf = Tsf::TSF_FREE_RUNNING;
+        template.tsf = Some(u64::MAX);
+        let packets: Vec<_> = Fragmenter::new(&template, &samples, 4, 6 * 4 + 4)
+            .expect("failed to create fragmenter")
+            .collect();
+        assert_eq!(packets[1].tsf, Some(0));
+    }
+
+    #[test]
+    fn test_fragmenter_odd_sample_size() {
+        let samples = [0u8; 30];
+        let template = template(Tsf::TSF_FREE_RUNNING);
+        let fragmenter = Fragmenter::new(&template, &samples, 3, 6 * 4 + 20)
+            .expect("failed to create fragmenter");
+        assert_eq!(fragmenter.payload_len(), 12);
+        let packets: Vec<_> = fragmenter.collect();
+        assert_eq!(packets.len(), 3);
+        assert_eq!(packets[2].data_payload.len(), 6);
+        assert_eq!(packets[2].header.packet_size, 8);
+        assert_eq!(packets[2].tsf, Some(8));
+    }
+
+    #[test]
+    fn test_fragmenter_mtu_too_small() {
+        let samples = [0u8; 8];
+        let template = template(Tsf::TSF_SAMPLE_COUNT);
+        let res = Fragmenter::new(&template, &samples, 4, 6 * 4 + 3);
+        assert_eq!(res.unwrap_err(), Error::BufferTooSmall);
+    }
+}
@@ -0,0 +1,115 @@
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+
+    /// Inputs which claim more optional fields than their packet size allows
+    static REGRESSIONS: &[&[u8]] = &[
+        &[0x1c, 0xff, 0x00, 0x00],
+        &[0x1c, 0xff, 0x00, 0x01],
+        &[0x1c, 0xff, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01],
+        &[0x14, 0x00, 0x00, 0x01],
+        &[0x14, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01],
+        &[
+            0x5c, 0xf0, 0x00, 0x05, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4,
+        ],
+        &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        &[0x10, 0x00, 0x00],
+    ];
+
+    /// Runs every parsing entry point over `bytes`, none of which may panic
+    fn parse_all(bytes: &[u8]) {
+        let parsed = parse_vrt_packet(bytes);
+        assert_eq!(parsed.is_ok(), VrtPacket::parse(bytes).is_ok());
+        if let Ok((rest, packet)) = parsed {
+            #[cfg(feature = "alloc")]
+            assert_eq!(
+                packet.reserialize_exact().unwrap(),
+                &bytes[..bytes.len() - rest.len()]
+            );
+            #[cfg(feature = "std")]
+            packet.dissect();
+            let _ = (rest, packet);
+        }
+        let _ = parse_vrt_header(bytes);
+        let _ = parse_vrt_trailer(bytes);
+        let _ = PacketStream::new(bytes).with_recovery().count();
+        let _ = resync(bytes, 2);
+        let _ = PushParser::<64>::new().feed(bytes, |_| {});
+    }
+
+    #[test]
+    fn test_fuzz_regressions() {
+        for bytes in REGRESSIONS {
+            parse_all(bytes);
+            assert!(VrtPacket::parse(bytes).is_err());
+        }
+    }
+
+    #[test]
+    fn test_fuzz_header_flags() {
+        // every combination of header flags and timestamp types, with packet sizes up to and
+        // beyond the input
+        let mut bytes = [0xa5u8; 40];
+        for word in (0..=0xfff0_u16).step_by(0x10) {
+            bytes[..2].copy_from_slice(&word.to_be_bytes());
+            for size in 0..12u16 {
+                bytes[2..4].copy_from_slice(&size.to_be_bytes());
+                parse_all(&bytes);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fuzz_random() {
+        // xorshift, so the inputs are the same on every run
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let mut bytes = Vec::new();
+        for _ in 0..20_000 {
+            let len = (next() % 64) as usize;
+            bytes.clear();
+            bytes.extend((0..len).map(|_| next() as u8));
+            if len >= 4 {
+                // keep the declared size near the input length, where the edge cases are
+                let size = (next() % (len as u64 / 4 + 3)) as u16;
+                bytes[2..4].copy_from_slice(&size.to_be_bytes());
+            }
+            parse_all(&bytes);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "fuzz-helpers")]
+    fn test_fuzz_corpus() {
+        use crate::vrt::fuzz_helpers::*;
+        use std::fs;
+        use std::path::Path;
+
+        type Target = fn(&[u8]);
+        let targets: [(&str, Target); 4] = [
+            ("parse_packet", fuzz_packet),
+            ("parse_context", fuzz_context),
+            ("parse_vrl_frame", fuzz_vrl_frame),
+            ("round_trip", fuzz_round_trip),
+        ];
+        let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus");
+        for (target, fuzz) in targets {
+            let mut seeds = 0;
+            for entry in fs::read_dir(corpus.join(target)).unwrap() {
+                fuzz(&fs::read(entry.unwrap().path()).unwrap());
+                seeds += 1;
+            }
+            assert!(seeds > 0, "{}", target);
+            for bytes in REGRESSIONS {
+                fuzz(bytes);
+            }
+        }
+    }
+}
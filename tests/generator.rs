@@ -0,0 +1,103 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+
+    fn samples(packet: &VrtPacketOwned) -> Vec<(i16, i16)> {
+        packet
+            .data_payload
+            .chunks_exact(4)
+            .map(|s| {
+                (
+                    i16::from_be_bytes([s[0], s[1]]),
+                    i16::from_be_bytes([s[2], s[3]]),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_generator_data_packets() {
+        let packets: Vec<_> = Generator::new(1000.0, 250, Waveform::Ramp)
+            .with_stream_id(7)
+            .with_start_time(100)
+            .take(20)
+            .collect();
+        for (n, packet) in packets.iter().enumerate() {
+            let bytes = packet.as_packet().to_vec().unwrap();
+            let (rest, parsed) = parse_vrt_packet(&bytes).unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(parsed, packet.as_packet());
+            assert_eq!(packet.stream_id, Some(7));
+            assert_eq!(packet.header.packet_count, n as u8 & 0x0f);
+            assert_eq!(packet.tsi, Some(100 + n as u32 / 4));
+            assert_eq!(packet.tsf, Some((n as u64 % 4) * 250_000_000_000));
+            assert_eq!(samples(packet)[0], (n as i16 * 250, n as i16 * 250));
+        }
+    }
+
+    #[test]
+    fn test_generator_context_interval() {
+        let packets: Vec<_> = Generator::new(1e6, 100, Waveform::Ramp)
+            .with_context_interval(3)
+            .take(9)
+            .collect();
+        let types: Vec<_> = packets
+            .iter()
+            .map(|p| p.header.packet_type.is_context())
+            .collect();
+        assert_eq!(
+            types,
+            [true, false, false, false, true, false, false, false, true]
+        );
+        assert_eq!(packets[4].header.packet_count, 1);
+        assert_eq!(packets[4].tsf, packets[5].tsf);
+        assert_eq!(
+            packets[0].data_payload,
+            [0x00, 0x20, 0x00, 0x00, 0, 0, 0, 0xf4, 0x24, 0, 0, 0]
+        );
+
+        let mut session = VrtSession::new();
+        let mut paired = 0;
+        for packet in &packets {
+            if let Some(packet) = session.process(packet.as_packet()) {
+                assert!(packet.context.is_some());
+                paired += 1;
+            }
+        }
+        assert_eq!(paired, 6);
+    }
+
+    #[test]
+    fn test_generator_tone() {
+        let tone = Waveform::Tone {
+            frequency: 250.0,
+            amplitude: 0.5,
+        };
+        let packet = Generator::new(1000.0, 4, tone).next().unwrap();
+        assert_eq!(
+            samples(&packet),
+            [(16384, 0), (0, 16384), (-16384, 0), (0, -16384)]
+        );
+    }
+
+    #[test]
+    fn test_generator_noise() {
+        let noise = |seed| {
+            let waveform = Waveform::Noise {
+                amplitude: 0.25,
+                seed,
+            };
+            Generator::new(1000.0, 100, waveform).next().unwrap()
+        };
+        let samples = samples(&noise(1));
+        assert!(samples
+            .iter()
+            .all(|(i, q)| i.abs() <= 8192 && q.abs() <= 8192));
+        assert!(samples.iter().any(|(i, _)| *i != samples[0].0));
+        assert_eq!(noise(1), noise(1));
+        assert_ne!(noise(1), noise(2));
+    }
+}
@@ -0,0 +1,40 @@
+#![cfg(feature = "vita49")]
+extern crate vita49;
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use vita49::prelude::*;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    #[test]
+    fn test_vita49_round_trip() {
+        let (_, packet) = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap();
+        let converted = Vrt::try_from(&packet).unwrap();
+        assert_eq!(converted.stream_id(), Some(1));
+        assert_eq!(converted.integer_timestamp(), Some(10));
+        assert_eq!(converted.fractional_timestamp(), Some(5));
+        assert_eq!(
+            converted.signal_payload().unwrap(),
+            [0xca, 0xfe, 0xf0, 0x0d]
+        );
+
+        let owned = VrtPacketOwned::try_from(&converted).unwrap();
+        assert_eq!(owned.as_packet(), packet);
+    }
+
+    #[test]
+    fn test_vita49_context() {
+        let mut context = Vrt::new_context_packet();
+        context.set_stream_id(Some(7));
+        context.update_packet_size();
+        let owned = VrtPacketOwned::try_from(&context).unwrap();
+        assert_eq!(owned.header.packet_type.0, VitaPacketType::IFCONTEXT);
+        assert_eq!(owned.stream_id, Some(7));
+    }
+}
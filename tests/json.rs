@@ -0,0 +1,56 @@
+#![cfg(feature = "json")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_IFDATA_WITH_TRAILER: &[u8] = &[
+        0x14, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef, 0x40, 0x0f, 0x00,
+        0x00,
+    ];
+
+    static VRT_STATIC_CONTEXT: &[u8] = &[
+        0x40, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x01, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0xf4, 0x24, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn test_json_lines_writer() {
+        let mut writer = JsonLinesWriter::new(Vec::new()).with_payload(true);
+        for bytes in [
+            VRT_STATIC_STREAM_1,
+            VRT_STATIC_IFDATA_WITH_TRAILER,
+            VRT_STATIC_CONTEXT,
+        ] {
+            let (_, packet) = parse_vrt_packet(bytes).unwrap();
+            writer.write_packet(&packet).unwrap();
+        }
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "{\"class_id\":null,\"packet_count\":0,\"packet_size\":6,\"packet_type\":1,\
+             \"packet_type_name\":\"IF Data with Stream ID\",\"payload\":\"yv7wDQ==\",\
+             \"payload_len\":4,\"stream_id\":1,\
+             \"timestamp\":{\"fractional\":5,\"integer\":10,\"tsf\":2,\"tsi\":1},\
+             \"trailer\":null}"
+        );
+        assert!(lines[1].contains(
+            "\"trailer\":{\"associated_context_packet_count\":null,\"valid_data\":true}"
+        ));
+        assert!(lines[2].contains(
+            "\"context\":{\"context_indicator_field_0\":2097152,\"sample_rate\":1000000.0}"
+        ));
+        assert!(lines[2].contains("\"payload\":\"ACAAAAAAAPQkAAAA\""));
+
+        let (_, packet) = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap();
+        assert!(packet_json(&packet, false).get("payload").is_none());
+    }
+}
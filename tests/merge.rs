@@ -0,0 +1,83 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TestPacket;
+    use crate::vrt::*;
+
+    fn packet(stream_id: u32, tsi: Option<u32>, tsf: u64) -> VrtPacket<'static> {
+        TestPacket {
+            stream_id,
+            tsi,
+            tsf: tsi.map(|_| (Tsf::TSF_REAL_TIME, tsf)),
+            ..TestPacket::default()
+        }
+        .build()
+    }
+
+    #[test]
+    fn test_packet_timestamp() {
+        let p = packet(1, Some(10), 20);
+        let expected = Timestamp {
+            tsi: Tsi::TSI_UTC,
+            integer: 10,
+            tsf: Tsf::TSF_REAL_TIME,
+            fractional: 20,
+        };
+        assert_eq!(p.timestamp(), Some(expected));
+        assert_eq!(packet(1, None, 0).timestamp(), None);
+    }
+
+    #[test]
+    fn test_merge_by_time() {
+        let a = vec![
+            packet(1, Some(1), 0),
+            packet(1, Some(2), 0),
+            packet(1, Some(3), 5),
+        ];
+        let b = vec![packet(2, Some(1), 5), packet(2, Some(3), 0)];
+        let c = vec![packet(3, None, 0), packet(3, Some(2), 0)];
+        let merged: Vec<_> = MergeByTime::new(vec![a.into_iter(), b.into_iter(), c.into_iter()])
+            .map(|p| (p.stream_id.unwrap(), p.tsi, p.tsf))
+            .collect();
+        let expected = vec![
+            (3, None, None),
+            (1, Some(1), Some(0)),
+            (2, Some(1), Some(5)),
+            (1, Some(2), Some(0)),
+            (3, Some(2), Some(0)),
+            (2, Some(3), Some(0)),
+            (1, Some(3), Some(5)),
+        ];
+        assert_eq!(merged, expected);
+    }
+
+    #[test]
+    fn test_merge_by_time_owned() {
+        let a = vec![VrtPacketOwned::from(&packet(1, Some(2), 0))];
+        let b = vec![VrtPacketOwned::from(&packet(2, Some(1), 0))];
+        let merged: Vec<_> = MergeByTime::with_key(vec![a.into_iter(), b.into_iter()], |p| {
+            p.as_packet().timestamp()
+        })
+        .map(|p| p.stream_id)
+        .collect();
+        assert_eq!(merged, vec![Some(2), Some(1)]);
+    }
+
+    #[test]
+    fn test_packet_by_time() {
+        let mut packets = [
+            packet(1, Some(3), 0),
+            packet(2, Some(1), 5),
+            packet(3, None, 0),
+            packet(4, Some(1), 5),
+            packet(5, Some(1), 0),
+        ];
+        packets.sort_by(VrtPacket::by_time);
+        let order: Vec<_> = packets.iter().map(|p| p.stream_id.unwrap()).collect();
+        assert_eq!(order, vec![3, 5, 2, 4, 1]);
+    }
+}
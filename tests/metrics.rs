@@ -0,0 +1,78 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TestPacket;
+    use crate::vrt::*;
+    use std::time::Duration;
+
+    fn packet(packet_type: u8, stream_id: u32, tsi: u32, tsf: u64) -> VrtPacket<'static> {
+        TestPacket {
+            packet_type,
+            stream_id,
+            tsi: Some(tsi),
+            tsf: Some((Tsf::TSF_REAL_TIME, tsf)),
+            payload: &[0; 24],
+            ..TestPacket::default()
+        }
+        .build()
+    }
+
+    #[test]
+    fn test_metrics_rates_and_skew() {
+        let mut metrics = Metrics::new();
+        for n in 0..5u64 {
+            let p = packet(
+                VitaPacketType::IFDATAWITHSTREAM,
+                1,
+                1000,
+                n * 100_000_000_000,
+            );
+            let arrival = Duration::from_millis(1_000_250 + n * 100);
+            metrics.record(&p, arrival);
+        }
+        let snapshot = metrics.snapshot(Some(1)).expect("missing stream");
+        assert_eq!(snapshot.packets, 5);
+        assert_eq!(snapshot.bytes, 220);
+        assert!((snapshot.packets_per_sec - 10.0).abs() < 1e-6);
+        assert!((snapshot.bytes_per_sec - 440.0).abs() < 1e-6);
+        assert!(snapshot.jitter < 1e-6);
+        assert!((snapshot.skew.unwrap() - 0.25).abs() < 1e-6);
+        assert_eq!(
+            snapshot.packet_type_counts[VitaPacketType::IFDATAWITHSTREAM as usize],
+            5
+        );
+    }
+
+    #[test]
+    fn test_metrics_jitter_and_streams() {
+        let mut metrics = Metrics::new();
+        metrics.record(
+            &packet(VitaPacketType::IFDATAWITHSTREAM, 2, 10, 0),
+            Duration::from_millis(10_000),
+        );
+        metrics.record(
+            &packet(VitaPacketType::IFDATAWITHSTREAM, 2, 10, 100_000_000_000),
+            Duration::from_millis(10_260),
+        );
+        metrics.record(
+            &packet(VitaPacketType::IFCONTEXT, 1, 10, 0),
+            Duration::from_millis(10_000),
+        );
+        let snapshots = metrics.snapshots();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].stream_id, Some(1));
+        assert_eq!(
+            snapshots[0].packet_type_counts[VitaPacketType::IFCONTEXT as usize],
+            1
+        );
+        // arrival interval 0.26s against a timestamp interval of 0.1s
+        assert!((snapshots[1].jitter - 0.16 / 16.0).abs() < 1e-9);
+
+        metrics.reset();
+        assert!(metrics.snapshots().is_empty());
+    }
+}
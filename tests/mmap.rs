@@ -0,0 +1,46 @@
+#![cfg(feature = "mmap")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_STREAM_2: &[u8] = &[
+        0x10, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    #[test]
+    fn test_mmap_capture() {
+        let path = std::env::temp_dir().join(format!("vrt-mmap-{}.vrt", std::process::id()));
+        let mut recorder = Recorder::create(&path).expect("failed to create capture");
+        recorder.record(VRT_STATIC_STREAM_1).unwrap();
+        let second = recorder.record(VRT_STATIC_STREAM_2).unwrap();
+        recorder.flush().unwrap();
+        drop(recorder);
+
+        let capture = MmapCapture::open(&path).expect("failed to map capture");
+        assert_eq!(capture.as_bytes().len(), 36);
+        let stream_ids: Vec<_> = capture
+            .packets()
+            .map(|p| p.unwrap().stream_id.unwrap())
+            .collect();
+        assert_eq!(stream_ids, vec![1, 2]);
+
+        let packet = capture.packet(&second).expect("failed to parse packet");
+        assert_eq!(packet.data_payload, &[0xde, 0xad, 0xbe, 0xef]);
+        let beyond = IndexEntry {
+            offset: 32,
+            ..second
+        };
+        assert_eq!(capture.packet(&beyond), Err(Error::Incomplete(None)));
+
+        drop(capture);
+        std::fs::remove_file(index_path(&path)).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "multicast")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::net::*;
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    #[test]
+    fn test_multicast_bind_options() {
+        let options = MulticastOptions::new()
+            .with_interface_v4(Ipv4Addr::LOCALHOST)
+            .with_ttl(4)
+            .with_loopback(true)
+            .with_recv_buffer_size(1 << 20);
+        let socket = options
+            .bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+            .expect("failed to bind");
+        assert_eq!(socket.multicast_ttl_v4().unwrap(), 4);
+        assert!(socket.multicast_loop_v4().unwrap());
+
+        // a second socket can share the address
+        let addr = socket.local_addr().unwrap();
+        options.bind(addr).expect("failed to bind shared address");
+    }
+
+    #[test]
+    fn test_multicast_loopback() {
+        let options = MulticastOptions::new()
+            .with_interface_v4(Ipv4Addr::LOCALHOST)
+            .with_loopback(true);
+        let group = Ipv4Addr::new(239, 255, 49, 1);
+        let socket = options
+            .bind(SocketAddr::from(([0, 0, 0, 0], 0)))
+            .expect("failed to bind");
+        options.join(&socket, group.into()).expect("failed to join");
+        let port = socket.local_addr().unwrap().port();
+
+        let sender = options
+            .bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+            .expect("failed to bind");
+        sender
+            .send_to(&[0x10, 0x00, 0x00, 0x02, 0, 0, 0, 7], (group, port))
+            .unwrap();
+        socket
+            .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+            .unwrap();
+        let mut receiver = UdpReceiver::from_socket(socket);
+        let (_, packet) = receiver.recv_packet().unwrap();
+        assert_eq!(packet.stream_id, Some(7));
+        options
+            .leave(receiver.socket(), group.into())
+            .expect("failed to leave");
+    }
+}
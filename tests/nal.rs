@@ -0,0 +1,91 @@
+#![cfg(feature = "embedded-nal")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use embedded_nal::{SocketAddr, UdpClientStack};
+    use std::collections::VecDeque;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_STREAM_2: &[u8] = &[
+        0x10, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    /// Stack looping datagrams sent on its socket back to it
+    #[derive(Default)]
+    struct LoopbackStack {
+        remote: Option<SocketAddr>,
+        datagrams: VecDeque<Vec<u8>>,
+    }
+
+    impl UdpClientStack for LoopbackStack {
+        type UdpSocket = ();
+        type Error = ();
+
+        fn socket(&mut self) -> Result<(), ()> {
+            Ok(())
+        }
+
+        fn connect(&mut self, _: &mut (), remote: SocketAddr) -> Result<(), ()> {
+            self.remote = Some(remote);
+            Ok(())
+        }
+
+        fn send(&mut self, _: &mut (), buffer: &[u8]) -> nb::Result<(), ()> {
+            self.datagrams.push_back(buffer.to_vec());
+            Ok(())
+        }
+
+        fn receive(
+            &mut self,
+            _: &mut (),
+            buffer: &mut [u8],
+        ) -> nb::Result<(usize, SocketAddr), ()> {
+            let datagram = self.datagrams.pop_front().ok_or(nb::Error::WouldBlock)?;
+            buffer[..datagram.len()].copy_from_slice(&datagram);
+            Ok((datagram.len(), self.remote.unwrap()))
+        }
+
+        fn close(&mut self, _: ()) -> Result<(), ()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_nal_transport() {
+        let remote: SocketAddr = "192.168.1.10:4991".parse().unwrap();
+        let mut transport = NalTransport::connect(LoopbackStack::default(), remote).unwrap();
+        let mut buf = [0u8; 64];
+        assert_eq!(
+            transport.receive(&mut buf).unwrap_err(),
+            nb::Error::WouldBlock
+        );
+
+        let packet = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap().1;
+        transport.send(&packet, &mut buf).unwrap();
+        let err = transport.send(&packet, &mut [0u8; 16]).unwrap_err();
+        assert_eq!(
+            err,
+            nb::Error::Other(NalError::Packet(Error::BufferTooSmall))
+        );
+
+        let (source, mut packets) = transport.receive(&mut buf).unwrap();
+        assert_eq!(source, remote);
+        assert_eq!(packets.next().unwrap().unwrap(), packet);
+        assert!(packets.next().is_none());
+
+        let stack = transport.close().unwrap();
+        assert!(stack.datagrams.is_empty());
+        let mut transport = NalTransport::from_socket(stack, ());
+        transport
+            .send(&parse_vrt_packet(VRT_STATIC_STREAM_2).unwrap().1, &mut buf)
+            .unwrap();
+        let (_, mut packets) = transport.receive(&mut buf).unwrap();
+        assert_eq!(packets.next().unwrap().unwrap().stream_id, Some(2));
+    }
+}
@@ -0,0 +1,118 @@
+#![cfg(feature = "embedded-nal-async")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use core::convert::Infallible;
+    use core::net::SocketAddr;
+    use embedded_nal_async::{ConnectedUdp, UdpStack, UnconnectedUdp};
+    use std::collections::VecDeque;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_STREAM_2: &[u8] = &[
+        0x10, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    /// Socket looping datagrams sent on it back to it
+    #[derive(Default)]
+    struct LoopbackSocket {
+        datagrams: VecDeque<Vec<u8>>,
+    }
+
+    impl ConnectedUdp for LoopbackSocket {
+        type Error = Infallible;
+
+        async fn send(&mut self, data: &[u8]) -> Result<(), Infallible> {
+            self.datagrams.push_back(data.to_vec());
+            Ok(())
+        }
+
+        async fn receive_into(&mut self, buffer: &mut [u8]) -> Result<usize, Infallible> {
+            let datagram = self.datagrams.pop_front().unwrap();
+            let len = datagram.len().min(buffer.len());
+            buffer[..len].copy_from_slice(&datagram[..len]);
+            Ok(datagram.len())
+        }
+    }
+
+    impl UnconnectedUdp for LoopbackSocket {
+        type Error = Infallible;
+
+        async fn send(&mut self, _: SocketAddr, _: SocketAddr, _: &[u8]) -> Result<(), Infallible> {
+            unimplemented!()
+        }
+
+        async fn receive_into(
+            &mut self,
+            _: &mut [u8],
+        ) -> Result<(usize, SocketAddr, SocketAddr), Infallible> {
+            unimplemented!()
+        }
+    }
+
+    /// Stack creating loopback sockets
+    struct LoopbackStack;
+
+    impl UdpStack for LoopbackStack {
+        type Error = Infallible;
+        type Connected = LoopbackSocket;
+        type UniquelyBound = LoopbackSocket;
+        type MultiplyBound = LoopbackSocket;
+
+        async fn connect_from(
+            &self,
+            local: SocketAddr,
+            _: SocketAddr,
+        ) -> Result<(SocketAddr, LoopbackSocket), Infallible> {
+            Ok((local, LoopbackSocket::default()))
+        }
+
+        async fn bind_single(
+            &self,
+            _: SocketAddr,
+        ) -> Result<(SocketAddr, LoopbackSocket), Infallible> {
+            unimplemented!()
+        }
+
+        async fn bind_multiple(&self, _: SocketAddr) -> Result<LoopbackSocket, Infallible> {
+            unimplemented!()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nal_async_transport() {
+        let remote: SocketAddr = "192.168.1.10:4991".parse().unwrap();
+        let mut transport = NalAsyncTransport::connect(&LoopbackStack, remote)
+            .await
+            .unwrap();
+        let mut buf = [0u8; 64];
+
+        let packet = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap().1;
+        transport.send(&packet, &mut buf).await.unwrap();
+        let err = transport.send(&packet, &mut [0u8; 16]).await.unwrap_err();
+        assert_eq!(err, NalError::Packet(Error::BufferTooSmall));
+
+        let mut packets = transport.receive(&mut buf).await.unwrap();
+        assert_eq!(packets.next().unwrap().unwrap(), packet);
+        assert!(packets.next().is_none());
+
+        transport.send(&packet, &mut buf).await.unwrap();
+        let err = transport.receive(&mut [0u8; 16]).await.unwrap_err();
+        assert_eq!(err, NalError::Packet(Error::BufferTooSmall));
+
+        let socket = transport.into_inner();
+        assert!(socket.datagrams.is_empty());
+        let mut transport = NalAsyncTransport::from_socket(socket);
+        transport
+            .send(&parse_vrt_packet(VRT_STATIC_STREAM_2).unwrap().1, &mut buf)
+            .await
+            .unwrap();
+        let mut packets = transport.receive(&mut buf).await.unwrap();
+        assert_eq!(packets.next().unwrap().unwrap().stream_id, Some(2));
+    }
+}
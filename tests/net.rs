@@ -0,0 +1,246 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::net::*;
+    use std::net::UdpSocket;
+    use std::time::Duration;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_STREAM_2: &[u8] = &[
+        0x10, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    fn receiver(max_datagram_len: usize) -> (UdpReceiver, UdpSocket) {
+        let receiver = UdpReceiver::bind("127.0.0.1:0")
+            .expect("failed to bind")
+            .with_max_datagram_len(max_datagram_len);
+        receiver
+            .socket()
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:0").expect("failed to bind");
+        sender
+            .connect(receiver.socket().local_addr().unwrap())
+            .unwrap();
+        (receiver, sender)
+    }
+
+    #[test]
+    fn test_udp_receiver_packets() {
+        let (mut receiver, sender) = receiver(32);
+        // too long for the maximum datagram length
+        sender
+            .send(&[VRT_STATIC_STREAM_1, VRT_STATIC_STREAM_2].concat())
+            .unwrap();
+        sender.send(&VRT_STATIC_STREAM_1[..8]).unwrap();
+        sender
+            .send(&[VRT_STATIC_STREAM_2, VRT_STATIC_STREAM_2].concat())
+            .unwrap();
+        sender.send(VRT_STATIC_STREAM_1).unwrap();
+
+        let (source, first) = receiver.next().unwrap().unwrap();
+        assert_eq!(source, sender.local_addr().unwrap());
+        assert_eq!(first.stream_id, Some(2));
+        let (_, second) = receiver.next().unwrap().unwrap();
+        assert_eq!(second.stream_id, Some(2));
+        let (_, third) = receiver.next().unwrap().unwrap();
+        assert_eq!(third.stream_id, Some(1));
+        assert_eq!(receiver.truncated(), 1);
+        assert_eq!(receiver.malformed(), 1);
+    }
+
+    #[test]
+    fn test_udp_receiver_datagrams() {
+        let (mut receiver, sender) = receiver(DEFAULT_MAX_DATAGRAM_LEN);
+        sender
+            .send(&[VRT_STATIC_STREAM_1, VRT_STATIC_STREAM_2].concat())
+            .unwrap();
+        let datagram = receiver.recv().unwrap();
+        assert!(!datagram.truncated);
+        assert_eq!(datagram.bytes().len(), 36);
+        let stream_ids: Vec<_> = datagram
+            .packets()
+            .map(|p| p.unwrap().stream_id.unwrap())
+            .collect();
+        assert_eq!(stream_ids, vec![1, 2]);
+        receiver.recycle(datagram);
+
+        sender.send(VRT_STATIC_STREAM_2).unwrap();
+        let datagram = receiver.recv().unwrap();
+        assert_eq!(datagram.bytes(), VRT_STATIC_STREAM_2);
+    }
+
+    #[test]
+    fn test_batch_sender() {
+        let (mut receiver, _) = receiver(DEFAULT_MAX_DATAGRAM_LEN);
+        let socket = UdpSocket::bind("127.0.0.1:0").expect("failed to bind");
+        let mut sender = BatchSender::new(socket, receiver.socket().local_addr().unwrap());
+        let parse = |bytes| vrt::parse_vrt_packet(bytes).unwrap().1;
+        let batch = [
+            parse(VRT_STATIC_STREAM_1),
+            parse(VRT_STATIC_STREAM_2),
+            parse(VRT_STATIC_STREAM_1),
+        ];
+        assert_eq!(sender.send_batch(&batch).unwrap(), 3);
+
+        let stream_ids: Vec<_> = receiver
+            .by_ref()
+            .take(3)
+            .map(|p| p.unwrap().1.stream_id.unwrap())
+            .collect();
+        assert_eq!(stream_ids, vec![1, 2, 1]);
+
+        let mut invalid = parse(VRT_STATIC_STREAM_2);
+        invalid.stream_id = None;
+        let err = sender
+            .send_batch(&[parse(VRT_STATIC_STREAM_1), invalid])
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_udp_receiver_batch() {
+        let (receiver, sender) = receiver(32);
+        let mut receiver = receiver.with_batch_size(8);
+        sender.send(VRT_STATIC_STREAM_1).unwrap();
+        sender
+            .send(&[VRT_STATIC_STREAM_2, VRT_STATIC_STREAM_2].concat())
+            .unwrap();
+        sender.send(&[0u8; 40]).unwrap();
+        sender.send(VRT_STATIC_STREAM_2).unwrap();
+
+        let mut datagrams = Vec::new();
+        while datagrams.len() < 4 {
+            let batch = receiver.recv_batch(8).unwrap();
+            assert!(!batch.is_empty() && batch.len() <= 8);
+            datagrams.extend(batch);
+        }
+        let lens: Vec<_> = datagrams.iter().map(|d| d.bytes().len()).collect();
+        assert_eq!(lens, vec![24, 24, 32, 12]);
+        assert!(datagrams[2].truncated);
+        assert_eq!(datagrams[0].source, sender.local_addr().unwrap());
+        for datagram in datagrams {
+            receiver.recycle(datagram);
+        }
+
+        sender.send(VRT_STATIC_STREAM_1).unwrap();
+        sender.send(&[0u8; 40]).unwrap();
+        sender.send(VRT_STATIC_STREAM_2).unwrap();
+        let stream_ids: Vec<_> = receiver
+            .by_ref()
+            .take(2)
+            .map(|p| p.unwrap().1.stream_id.unwrap())
+            .collect();
+        assert_eq!(stream_ids, vec![1, 2]);
+        assert_eq!(receiver.truncated(), 1);
+    }
+
+    #[test]
+    fn test_arrival_time_latency() {
+        use crate::vrt::{Timestamp, Tsf, Tsi};
+
+        let timestamp = Timestamp {
+            tsi: Tsi::TSI_UTC,
+            integer: 1_700_000_000,
+            tsf: Tsf::TSF_REAL_TIME,
+            fractional: 250_000_000_000,
+        };
+        let arrival = ArrivalTime {
+            hardware: None,
+            software: Some(Duration::new(1_700_000_000, 251_500_000)),
+        };
+        let latency = arrival.latency(&timestamp).unwrap();
+        assert!((latency - 0.0015).abs() < 1e-9);
+
+        let early = ArrivalTime {
+            hardware: Some(Duration::new(1_700_000_000, 249_000_000)),
+            ..arrival
+        };
+        assert_eq!(early.time(), early.hardware);
+        assert!((early.latency(&timestamp).unwrap() + 0.001).abs() < 1e-9);
+
+        let gps = Timestamp {
+            tsi: Tsi::TSI_GPS,
+            ..timestamp
+        };
+        assert_eq!(arrival.latency(&gps), None);
+        assert_eq!(ArrivalTime::default().latency(&timestamp), None);
+    }
+
+    #[cfg(all(feature = "timestamping", target_os = "linux"))]
+    #[test]
+    fn test_udp_receiver_timestamping() {
+        use std::time::SystemTime;
+
+        let (mut receiver, sender) = receiver(DEFAULT_MAX_DATAGRAM_LEN);
+        sender.send(VRT_STATIC_STREAM_2).unwrap();
+        let datagram = receiver.recv().unwrap();
+        assert_eq!(datagram.arrival, ArrivalTime::default());
+        receiver.recycle(datagram);
+
+        receiver.enable_timestamping(false).unwrap();
+        let before = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap();
+        sender.send(VRT_STATIC_STREAM_1).unwrap();
+        let (source, arrival, packet) = receiver.recv_packet_with_arrival().unwrap();
+        assert_eq!(source, sender.local_addr().unwrap());
+        assert_eq!(packet.stream_id, Some(1));
+        assert_eq!(arrival.hardware, None);
+        let software = arrival.software.expect("no software timestamp");
+        assert!(software >= before - Duration::from_secs(1));
+        assert!(software <= before + Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_paced_sender() {
+        let (receiver, _) = receiver(DEFAULT_MAX_DATAGRAM_LEN);
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let destination = receiver.socket().local_addr().unwrap();
+        let mut sender = PacedSender::new(socket, destination, vrt::Pacer::new(1000.0));
+        let packet = vrt::parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap().1;
+        let start = std::time::Instant::now();
+        for _ in 0..5 {
+            sender.send(&packet).unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(4));
+        assert_eq!(sender.pacer_mut().packets(), 5);
+
+        let received: Vec<_> = receiver
+            .take(5)
+            .map(|p| p.unwrap().1.stream_id.unwrap())
+            .collect();
+        assert_eq!(received, vec![1; 5]);
+    }
+
+    #[test]
+    fn test_udp_receiver_pool() {
+        let pool = vrt::BufferPool::new(33, 8);
+        let (receiver, sender) = receiver(DEFAULT_MAX_DATAGRAM_LEN);
+        let mut receiver = receiver.with_pool(pool.clone());
+        sender.send(VRT_STATIC_STREAM_1).unwrap();
+        sender.send(VRT_STATIC_STREAM_2).unwrap();
+
+        // packets borrow the leased buffers for as long as the datagrams are kept
+        let first = receiver.recv().unwrap();
+        let second = receiver.recv().unwrap();
+        let packets: Vec<_> = first.packets().chain(second.packets()).collect();
+        assert_eq!(packets[0].as_ref().unwrap().stream_id, Some(1));
+        assert_eq!(packets[1].as_ref().unwrap().stream_id, Some(2));
+        assert_eq!(pool.idle(), 0);
+        drop(packets);
+        drop(first);
+        receiver.recycle(second);
+        assert_eq!(receiver.pool().idle(), 2);
+
+        // datagrams longer than the pool's buffers are truncated
+        sender.send(&[0u8; 40]).unwrap();
+        assert!(receiver.recv().unwrap().truncated);
+    }
+}
@@ -0,0 +1,61 @@
+extern crate vrt;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts the allocations made by the current thread
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::allocations;
+    use crate::vrt::*;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    #[test]
+    fn test_parse_and_encode_do_not_allocate() {
+        let mut buf = [0u8; 64];
+        let ((), count) = allocations(|| {
+            let (_, packet) = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap();
+            let len = encode_vrt_packet(&packet, &mut buf).unwrap();
+            assert_eq!(&buf[..len], VRT_STATIC_STREAM_1);
+            assert!(parse_vrt_packet(&VRT_STATIC_STREAM_1[..10]).is_err());
+            for packet in PacketStream::new(VRT_STATIC_STREAM_1) {
+                assert!(packet.is_ok());
+            }
+            let template = packet;
+            for packet in Fragmenter::new(&template, &[0; 64], 4, 40).unwrap() {
+                encode_vrt_packet(&packet, &mut buf).unwrap();
+            }
+        });
+        assert_eq!(count, 0);
+    }
+}
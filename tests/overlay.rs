@@ -0,0 +1,89 @@
+#![cfg(feature = "zerocopy")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use zerocopy::{FromBytes, IntoBytes};
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    #[test]
+    fn test_header_overlay() {
+        let (prologue, _) = StreamPrologue::ref_from_prefix(VRT_STATIC_STREAM_1).unwrap();
+        let header = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap().1.header;
+        assert_eq!(prologue.header.header(), header);
+        assert_eq!(
+            prologue.header.packet_type(),
+            VitaPacketType(VitaPacketType::IFDATAWITHSTREAM)
+        );
+        assert!(!prologue.header.c() && !prologue.header.t());
+        assert_eq!(prologue.header.tsi(), Tsi::TSI_UTC);
+        assert_eq!(prologue.header.tsf(), Tsf::TSF_REAL_TIME);
+        assert_eq!(prologue.header.packet_size(), 6);
+        assert_eq!(prologue.stream_id.get(), 1);
+        assert_eq!(
+            HeaderOverlay::from(&header).as_bytes(),
+            &VRT_STATIC_STREAM_1[..4]
+        );
+
+        // unaligned buffers can be patched in place
+        let mut buf = [&[0u8][..], VRT_STATIC_STREAM_1].concat();
+        let (prologue, _) = StreamPrologue::mut_from_prefix(&mut buf[1..]).unwrap();
+        prologue.header.set_packet_count(0x1f);
+        prologue.stream_id.set(7);
+        let packet = parse_vrt_packet(&buf[1..]).unwrap().1;
+        assert_eq!(packet.header.packet_count, 0x0f);
+        assert_eq!(packet.stream_id, Some(7));
+
+        let mut expected = packet.header;
+        expected.packet_size = 3;
+        let (header, _) = HeaderOverlay::mut_from_prefix(&mut buf[1..]).unwrap();
+        header.set_packet_size(3);
+        assert_eq!(header.header(), expected);
+        header.set_header(&parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap().1.header);
+        assert_eq!(&buf[1..5], &VRT_STATIC_STREAM_1[..4]);
+    }
+
+    #[test]
+    fn test_class_prologue() {
+        let mut buf = [0u8; 16];
+        let (prologue, _) = ClassPrologue::mut_from_prefix(&mut buf[..]).unwrap();
+        prologue.header.set_header(&Header {
+            packet_type: VitaPacketType(VitaPacketType::IFDATAWITHSTREAM),
+            c: true,
+            t: false,
+            indicators: 0,
+            tsi: Tsi::TSI_NONE,
+            tsf: Tsf::TSF_NONE,
+            packet_count: 0,
+            packet_size: 4,
+        });
+        prologue.stream_id.set(2);
+        prologue.class_id.set(0x0012_3456_0001_0002);
+        let packet = parse_vrt_packet(&buf).unwrap().1;
+        assert_eq!(packet.stream_id, Some(2));
+        assert_eq!(packet.class_id, Some(0x0012_3456_0001_0002));
+        assert!(ClassPrologue::ref_from_prefix(&buf[..15]).is_err());
+    }
+
+    #[test]
+    fn test_parse_vrt_packet_words() {
+        let mut words: Vec<u32> = VRT_STATIC_STREAM_1
+            .chunks_exact(4)
+            .map(|w| u32::from_be_bytes([w[0], w[1], w[2], w[3]]))
+            .collect();
+        words.push(0x1000_0002);
+        host_words_to_wire(&mut words);
+        let (rest, packet) = parse_vrt_packet_words(&words).unwrap();
+        assert_eq!(packet, parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap().1);
+        assert_eq!(rest.len(), 1);
+        assert_eq!(
+            parse_vrt_packet_words(rest).unwrap_err(),
+            Error::Incomplete(Some(4))
+        );
+    }
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_pacer_rate() {
+        let pacer = Pacer::from_sample_rate(1_000_000.0, 1000);
+        assert_eq!(pacer.interval(), Duration::from_millis(1));
+        assert_eq!(pacer.next_deadline(), None);
+
+        let mut pacer = Pacer::new(500.0);
+        let start = Instant::now();
+        for _ in 0..11 {
+            pacer.wait();
+        }
+        let elapsed = start.elapsed();
+        assert_eq!(pacer.packets(), 11);
+        assert!(elapsed >= Duration::from_millis(20), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(500), "{:?}", elapsed);
+
+        pacer.reset();
+        let start = Instant::now();
+        pacer.wait();
+        assert!(start.elapsed() < Duration::from_millis(2));
+        assert_eq!(pacer.packets(), 1);
+    }
+
+    #[test]
+    fn test_pacer_catches_up() {
+        let mut pacer = Pacer::with_interval(Duration::from_millis(5)).with_spin(Duration::ZERO);
+        pacer.wait();
+        std::thread::sleep(Duration::from_millis(20));
+        // the packets missed while sleeping are due immediately
+        let start = Instant::now();
+        pacer.wait();
+        pacer.wait();
+        assert!(start.elapsed() < Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_pacer_fractional_interval() {
+        // the interval of 3.33 ns is rounded to 3 ns, which must not accumulate
+        let mut pacer = Pacer::new(3e8).with_spin(Duration::ZERO);
+        pacer.wait();
+        let first = pacer.next_deadline().unwrap();
+        for _ in 0..3000 {
+            pacer.wait();
+        }
+        let elapsed = pacer.next_deadline().unwrap() - first;
+        assert!(
+            elapsed >= Duration::from_nanos(9_999) && elapsed <= Duration::from_nanos(10_001),
+            "{:?}",
+            elapsed
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_pacer_invalid_rate() {
+        let _ = Pacer::new(0.0);
+    }
+}
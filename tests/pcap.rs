@@ -0,0 +1,245 @@
+#![cfg(feature = "pcap")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use std::io::Cursor;
+    use std::net::SocketAddr;
+    use std::time::Duration;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_STREAM_2: &[u8] = &[
+        0x10, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    fn udp(src_port: u16, payload: &[u8]) -> Vec<u8> {
+        let mut udp = src_port.to_be_bytes().to_vec();
+        udp.extend_from_slice(&4991u16.to_be_bytes());
+        udp.extend_from_slice(&(8 + payload.len() as u16).to_be_bytes());
+        udp.extend_from_slice(&[0, 0]);
+        udp.extend_from_slice(payload);
+        udp
+    }
+
+    fn ethernet_ipv4(src_port: u16, payload: &[u8]) -> Vec<u8> {
+        let udp = udp(src_port, payload);
+        let mut frame = vec![0xff; 12];
+        // VLAN tag
+        frame.extend_from_slice(&[0x81, 0x00, 0x00, 0x05, 0x08, 0x00]);
+        frame.extend_from_slice(&[0x45, 0x00]);
+        frame.extend_from_slice(&(20 + udp.len() as u16).to_be_bytes());
+        frame.extend_from_slice(&[0, 0, 0x40, 0x00, 64, 17, 0, 0]);
+        frame.extend_from_slice(&[10, 0, 0, 1, 10, 0, 0, 2]);
+        frame.extend_from_slice(&udp);
+        // Ethernet padding
+        frame.extend_from_slice(&[0; 4]);
+        frame
+    }
+
+    fn raw_ipv6(src_port: u16, payload: &[u8]) -> Vec<u8> {
+        let udp = udp(src_port, payload);
+        let mut frame = vec![0x60, 0, 0, 0];
+        frame.extend_from_slice(&(udp.len() as u16).to_be_bytes());
+        frame.extend_from_slice(&[17, 64]);
+        frame.extend_from_slice(&[0; 15]);
+        frame.push(1);
+        frame.extend_from_slice(&[0; 15]);
+        frame.push(2);
+        frame.extend_from_slice(&udp);
+        frame
+    }
+
+    fn pcap(linktype: u32, frames: &[(u32, u32, Vec<u8>)]) -> Vec<u8> {
+        let mut file = vec![0xd4, 0xc3, 0xb2, 0xa1, 2, 0, 4, 0];
+        file.extend_from_slice(&[0; 8]);
+        file.extend_from_slice(&65535u32.to_le_bytes());
+        file.extend_from_slice(&linktype.to_le_bytes());
+        for (secs, micros, frame) in frames {
+            file.extend_from_slice(&secs.to_le_bytes());
+            file.extend_from_slice(&micros.to_le_bytes());
+            file.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            file.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            file.extend_from_slice(frame);
+        }
+        file
+    }
+
+    fn block(block_type: u32, body: &[u8]) -> Vec<u8> {
+        let mut body = body.to_vec();
+        body.resize(body.len().div_ceil(4) * 4, 0);
+        let len = (body.len() + 12) as u32;
+        let mut block = block_type.to_be_bytes().to_vec();
+        block.extend_from_slice(&len.to_be_bytes());
+        block.extend_from_slice(&body);
+        block.extend_from_slice(&len.to_be_bytes());
+        block
+    }
+
+    #[test]
+    fn test_pcap_reader() {
+        let mut datagram = VRT_STATIC_STREAM_1.to_vec();
+        datagram.extend_from_slice(VRT_STATIC_STREAM_2);
+        let file = pcap(
+            1,
+            &[
+                (100, 250_000, ethernet_ipv4(5000, &datagram)),
+                (101, 0, ethernet_ipv4(5001, &[0xde, 0xad])),
+                (102, 0, ethernet_ipv4(5001, VRT_STATIC_STREAM_2)),
+            ],
+        );
+        let mut reader = PcapReader::new(Cursor::new(file)).expect("failed to open");
+        let first = reader.next().unwrap().unwrap();
+        assert_eq!(first.timestamp, Duration::new(100, 250_000_000));
+        assert_eq!(first.source, "10.0.0.1:5000".parse::<SocketAddr>().unwrap());
+        assert_eq!(
+            first.destination,
+            "10.0.0.2:4991".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(first.packet.stream_id, Some(1));
+        assert_eq!(first.packet.data_payload, vec![0xca, 0xfe, 0xf0, 0x0d]);
+        let second = reader.next().unwrap().unwrap();
+        assert_eq!(second.timestamp, first.timestamp);
+        assert_eq!(second.packet.stream_id, Some(2));
+        let third = reader.next().unwrap().unwrap();
+        assert_eq!(third.source.port(), 5001);
+        assert!(reader.next().is_none());
+
+        assert_eq!(reader.skipped(), 1);
+        let ports = reader.streams_by_port();
+        assert_eq!(
+            ports[&5000].iter().copied().collect::<Vec<_>>(),
+            vec![Some(1), Some(2)]
+        );
+        assert_eq!(
+            ports[&5001].iter().copied().collect::<Vec<_>>(),
+            vec![Some(2)]
+        );
+    }
+
+    #[test]
+    fn test_pcapng_reader() {
+        let mut file = block(
+            0x0a0d_0d0a,
+            &[
+                0x1a, 0x2b, 0x3c, 0x4d, 0, 1, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            ],
+        );
+        // raw IP with nanosecond timestamps
+        let mut idb = vec![0, 101, 0, 0, 0, 0, 0xff, 0xff];
+        idb.extend_from_slice(&[0, 9, 0, 1, 9, 0, 0, 0, 0, 0, 0, 0]);
+        file.extend_from_slice(&block(1, &idb));
+        file.extend_from_slice(&block(0x0bad, &[1, 2, 3, 4]));
+
+        let frame = raw_ipv6(6000, VRT_STATIC_STREAM_2);
+        let ts: u64 = 5_000_000_123;
+        let mut epb = 0u32.to_be_bytes().to_vec();
+        epb.extend_from_slice(&((ts >> 32) as u32).to_be_bytes());
+        epb.extend_from_slice(&(ts as u32).to_be_bytes());
+        epb.extend_from_slice(&(frame.len() as u32).to_be_bytes());
+        epb.extend_from_slice(&(frame.len() as u32).to_be_bytes());
+        epb.extend_from_slice(&frame);
+        file.extend_from_slice(&block(6, &epb));
+
+        let mut reader = PcapReader::new(Cursor::new(file)).expect("failed to open");
+        let packet = reader.next().unwrap().unwrap();
+        assert_eq!(packet.timestamp, Duration::new(5, 123));
+        assert_eq!(packet.source, "[::1]:6000".parse::<SocketAddr>().unwrap());
+        assert_eq!(packet.packet.stream_id, Some(2));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_pcap_reader_invalid() {
+        let err = PcapReader::new(Cursor::new(vec![0u8; 24])).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        // a record claiming more than the snapshot length
+        let mut file = pcap(1, &[]);
+        file.extend_from_slice(&[0; 8]);
+        file.extend_from_slice(&65536u32.to_le_bytes());
+        file.extend_from_slice(&65536u32.to_le_bytes());
+        let mut reader = PcapReader::new(Cursor::new(file)).expect("failed to open");
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        // a pcapng block claiming 4 GiB
+        let mut file = block(
+            0x0a0d_0d0a,
+            &[
+                0x1a, 0x2b, 0x3c, 0x4d, 0, 1, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            ],
+        );
+        file.extend_from_slice(&[0, 0, 0, 6, 0xff, 0xff, 0xff, 0xfc]);
+        let mut reader = PcapReader::new(Cursor::new(file)).expect("failed to open");
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_pcapng_writer_roundtrip() {
+        let mut writer = PcapngWriter::new(Vec::new()).expect("failed to create");
+        let (_, first) = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap();
+        writer
+            .write_packet(Duration::new(1_700_000_000, 5), &first)
+            .unwrap();
+        let (_, second) = parse_vrt_packet(VRT_STATIC_STREAM_2).unwrap();
+        let preserved = PcapPacket {
+            timestamp: Duration::new(1_700_000_001, 0),
+            source: "[fe80::1]:5000".parse().unwrap(),
+            destination: "[ff02::1]:4991".parse().unwrap(),
+            packet: VrtPacketOwned::from(&second),
+        };
+        writer.write(&preserved).unwrap();
+        let mismatched = PcapPacket {
+            source: "10.0.0.1:5000".parse().unwrap(),
+            ..preserved.clone()
+        };
+        let err = writer.write(&mismatched).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        let file = writer.into_inner().unwrap();
+
+        let mut reader = PcapReader::new(Cursor::new(file)).expect("failed to open");
+        let packet = reader.next().unwrap().unwrap();
+        assert_eq!(packet.timestamp, Duration::new(1_700_000_000, 5));
+        assert_eq!(
+            packet.source,
+            "127.0.0.1:4991".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(packet.packet.as_packet(), first);
+        assert_eq!(reader.next().unwrap().unwrap(), preserved);
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_pcapng_reader_sections() {
+        let (_, first) = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap();
+        let (_, second) = parse_vrt_packet(VRT_STATIC_STREAM_2).unwrap();
+        let mut file = Vec::new();
+        let mut sections = Vec::new();
+        for packet in [&first, &second] {
+            let mut writer = PcapngWriter::new(Vec::new()).expect("failed to create");
+            writer.write_packet(Duration::ZERO, packet).unwrap();
+            sections.push(file.len());
+            file.extend(writer.into_inner().unwrap());
+        }
+        let reader = PcapReader::new(Cursor::new(file.clone())).expect("failed to open");
+        let packets: Vec<_> = reader.map(|p| p.unwrap().packet).collect();
+        assert_eq!(
+            packets,
+            vec![VrtPacketOwned::from(&first), VrtPacketOwned::from(&second)]
+        );
+
+        // a second section with a corrupt byte-order magic ends the capture after one error
+        let magic = sections[1] + 8;
+        file[magic..magic + 4].copy_from_slice(&[0; 4]);
+        let mut reader = PcapReader::new(Cursor::new(file)).expect("failed to open");
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+    }
+}
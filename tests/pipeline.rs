@@ -0,0 +1,120 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    static VRT_STATIC_STREAM_2: &[u8] = &[
+        0x10, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    #[test]
+    fn test_pipeline_stages() {
+        let datagrams = vec![
+            VRT_STATIC_STREAM_2.to_vec(),
+            vec![0u8; 3],
+            VRT_STATIC_STREAM_2.to_vec(),
+        ];
+        let mut received = Vec::new();
+        Pipeline::source(datagrams, 1)
+            .transform(|bytes| {
+                parse_vrt_packet(&bytes)
+                    .ok()
+                    .map(|(_, p)| VrtPacketOwned::from(&p))
+            })
+            .transform(|packet| Some(packet.data_payload))
+            .sink(|payload| {
+                received.push(payload);
+                Ok::<_, ()>(())
+            })
+            .unwrap();
+        assert_eq!(received, vec![vec![0xde, 0xad, 0xbe, 0xef]; 2]);
+
+        let pipeline = Pipeline::source(0..10u32, 4).transform(|i| Some(i * 2));
+        assert_eq!(
+            pipeline.collect::<Vec<_>>(),
+            (0..20).step_by(2).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_pipeline_backpressure() {
+        let produced = Arc::new(AtomicU64::new(0));
+        let counter = produced.clone();
+        let source = (0..100u64).inspect(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        let mut pipeline = Pipeline::source(source, 2).transform(Some);
+        assert_eq!(pipeline.next(), Some(0));
+        thread::sleep(Duration::from_millis(50));
+        // one item in each channel and one held by each stage
+        assert!(produced.load(Ordering::SeqCst) <= 7);
+        assert_eq!(pipeline.dropped(), 0);
+    }
+
+    #[test]
+    fn test_pipeline_overflow_drop() {
+        let mut pipeline = Pipeline::source_with_overflow(0..1000u32, 1, Overflow::Drop);
+        thread::sleep(Duration::from_millis(50));
+        let received = pipeline.by_ref().count() as u64;
+        assert!((1..1000).contains(&received));
+        assert_eq!(received + pipeline.dropped(), 1000);
+    }
+
+    #[test]
+    fn test_pipeline_sink_error() {
+        let mut seen = 0;
+        let result = Pipeline::source(0.., 8).sink(|i: u64| {
+            seen += 1;
+            if i == 5 {
+                Err(i)
+            } else {
+                Ok(())
+            }
+        });
+        assert_eq!(result, Err(5));
+        assert_eq!(seen, 6);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_async_pipeline() {
+        use futures_util::StreamExt;
+
+        let datagrams = vec![
+            VRT_STATIC_STREAM_2.to_vec(),
+            vec![0u8; 3],
+            VRT_STATIC_STREAM_2.to_vec(),
+        ];
+        let pipeline =
+            AsyncPipeline::source(futures_util::stream::iter(datagrams), 1).transform(|bytes| {
+                parse_vrt_packet(&bytes)
+                    .ok()
+                    .map(|(_, p)| VrtPacketOwned::from(&p))
+            });
+        let packets: Vec<_> = pipeline.collect().await;
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0].stream_id, Some(2));
+
+        let mut sum = 0;
+        AsyncPipeline::source(futures_util::stream::iter(1..=4u32), 2)
+            .transform(|i| Some(i * 10))
+            .sink(|i| {
+                sum += i;
+                Ok::<_, ()>(())
+            })
+            .await
+            .unwrap();
+        assert_eq!(sum, 100);
+
+        let result = AsyncPipeline::source(futures_util::stream::iter(0u32..), 2)
+            .sink(|i| if i < 3 { Ok(()) } else { Err(i) })
+            .await;
+        assert_eq!(result, Err(3));
+    }
+}
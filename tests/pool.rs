@@ -0,0 +1,42 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use std::thread;
+
+    #[test]
+    fn test_buffer_pool() {
+        let pool = BufferPool::new(100, 64);
+        let mut first = pool.lease();
+        let second = pool.lease();
+        assert_eq!(first.len(), 100);
+        assert_eq!(first.as_ptr() as usize % 64, 0);
+        assert_eq!(second.as_ptr() as usize % 64, 0);
+        assert_eq!(pool.idle(), 0);
+
+        first[..4].copy_from_slice(&[1, 2, 3, 4]);
+        let address = first.as_ptr();
+        drop(first);
+        assert_eq!(pool.idle(), 1);
+        let reused = pool.lease();
+        assert_eq!(reused.as_ptr(), address);
+        assert_eq!(&reused[..4], &[1, 2, 3, 4]);
+
+        // leases can be returned from other threads
+        let clone = pool.clone();
+        thread::spawn(move || drop((reused, second)))
+            .join()
+            .unwrap();
+        assert_eq!(clone.idle(), 2);
+        pool.shrink_to(1);
+        assert_eq!(pool.idle(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_buffer_pool_invalid_alignment() {
+        let _ = BufferPool::new(100, 48);
+    }
+}
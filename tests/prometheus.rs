@@ -0,0 +1,84 @@
+#![cfg(feature = "prometheus")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+    use std::time::Duration;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_STREAM_2: &[u8] = &[
+        0x10, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    fn collect() -> (Metrics, StreamTracker) {
+        let mut metrics = Metrics::new();
+        let mut tracker = StreamTracker::new();
+        let stream_1 = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap().1;
+        let stream_2 = parse_vrt_packet(VRT_STATIC_STREAM_2).unwrap().1;
+        metrics.record(&stream_1, Duration::from_secs(10));
+        metrics.record(&stream_2, Duration::from_secs(10));
+        metrics.record(&stream_2, Duration::from_secs(11));
+        tracker.update(&stream_1);
+        tracker.update(&stream_2);
+        (metrics, tracker)
+    }
+
+    #[test]
+    fn test_prometheus_render() {
+        let (metrics, tracker) = collect();
+        let text = PrometheusExporter::new().render(Some(&metrics), Some(&tracker));
+        let lines: Vec<_> = text.lines().collect();
+        assert!(lines.contains(&"# TYPE vrt_packets_total counter"));
+        assert!(lines.contains(&"vrt_packets_total{stream_id=\"1\"} 1"));
+        assert!(lines.contains(&"vrt_packets_total{stream_id=\"2\"} 2"));
+        assert!(lines.contains(&"vrt_bytes_total{stream_id=\"2\"} 24"));
+        assert!(lines.contains(&"vrt_packets_per_second{stream_id=\"2\"} 1"));
+        assert!(text.contains("vrt_timestamp_skew_seconds{stream_id=\"1\"} -0.0000000000"));
+        assert!(
+            lines.contains(&"vrt_packet_type_packets_total{stream_id=\"2\",packet_type=\"1\"} 2")
+        );
+        assert!(lines.contains(&"vrt_lost_packets_total{stream_id=\"1\"} 0"));
+        assert!(lines.contains(&"vrt_loss_ratio{stream_id=\"2\"} 0"));
+        assert!(!text.contains("skew_seconds{stream_id=\"2\"}"));
+
+        let text = PrometheusExporter::new()
+            .with_namespace("radio")
+            .render(None, Some(&tracker));
+        assert!(text.contains("radio_reordered_packets_total{stream_id=\"1\"} 0"));
+        assert!(!text.contains("packets_total{stream_id=\"1\"} 1"));
+    }
+
+    #[test]
+    fn test_prometheus_respond() {
+        let (metrics, tracker) = collect();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+                .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        });
+        let (stream, _) = listener.accept().unwrap();
+        let exporter = PrometheusExporter::default();
+        exporter
+            .respond(stream, Some(&metrics), Some(&tracker))
+            .unwrap();
+        let response = client.join().unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(head.contains(&format!("Content-Length: {}", body.len())));
+        assert_eq!(body, exporter.render(Some(&metrics), Some(&tracker)));
+    }
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "zmq")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_STREAM_2: &[u8] = &[
+        0x10, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    #[test]
+    fn test_zmq_pubsub() {
+        let context = zmq::Context::new();
+        let mut publisher = ZmqPublisher::bind(&context, "inproc://vrt-test").unwrap();
+        let mut subscriber = ZmqSubscriber::connect(&context, "inproc://vrt-test").unwrap();
+        subscriber.socket().set_rcvtimeo(100).unwrap();
+        subscriber.subscribe(1).unwrap();
+        assert_eq!(zmq_topic(Some(1)), vec![0, 0, 0, 1]);
+
+        let stream_1 = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap().1;
+        let stream_2 = parse_vrt_packet(VRT_STATIC_STREAM_2).unwrap().1;
+        // subscriptions reach the publisher asynchronously
+        let packet = (0..50)
+            .find_map(|_| {
+                publisher.send(&stream_2).unwrap();
+                publisher.send(&stream_1).unwrap();
+                subscriber.recv_packet().ok()
+            })
+            .expect("no packet received");
+        assert_eq!(packet.as_packet(), stream_1);
+
+        subscriber.subscribe_all().unwrap();
+        subscriber.unsubscribe(1).unwrap();
+        let packet = (0..50)
+            .find_map(|_| {
+                publisher
+                    .socket()
+                    .send_multipart([&[0, 0, 0, 2][..], &VRT_STATIC_STREAM_1[..8]], 0)
+                    .unwrap();
+                publisher.send(&stream_2).unwrap();
+                // skip packets of stream 1 still queued from before
+                subscriber
+                    .recv_packet()
+                    .ok()
+                    .filter(|p| p.stream_id == Some(2))
+            })
+            .expect("no packet received");
+        assert_eq!(packet.as_packet(), stream_2);
+        assert!(subscriber.malformed() >= 1);
+    }
+}
@@ -0,0 +1,144 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TestPacket;
+    use crate::vrt::*;
+    use std::time::{Duration, Instant};
+
+    fn packet(tsi: u32, tsf: u64) -> VrtPacketOwned {
+        let packet = TestPacket {
+            tsi: Some(tsi),
+            tsf: Some((Tsf::TSF_REAL_TIME, tsf)),
+            ..TestPacket::default()
+        };
+        VrtPacketOwned::from(&packet.build())
+    }
+
+    // 0, 20ms, 40ms and a packet going backwards
+    fn packets() -> Vec<VrtPacketOwned> {
+        vec![
+            packet(100, 0),
+            packet(100, 20_000_000_000),
+            packet(100, 40_000_000_000),
+            packet(99, 0),
+        ]
+    }
+
+    #[test]
+    fn test_timestamp_as_duration() {
+        let ts = packet(100, 20_000_000_000).as_packet().timestamp().unwrap();
+        assert_eq!(ts.as_duration(), Some(Duration::new(100, 20_000_000)));
+        let ts = Timestamp {
+            tsf: Tsf::TSF_SAMPLE_COUNT,
+            ..ts
+        };
+        assert_eq!(ts.as_duration(), None);
+    }
+
+    #[test]
+    fn test_replay_scaled() {
+        let start = Instant::now();
+        let replayed: Vec<_> =
+            Replayer::new(packets().into_iter(), ReplaySpeed::Scaled(1.0)).collect();
+        let elapsed = start.elapsed();
+        assert_eq!(replayed, packets());
+        assert!(elapsed >= Duration::from_millis(40));
+
+        let start = Instant::now();
+        let replayed: Vec<_> =
+            Replayer::new(packets().into_iter(), ReplaySpeed::Scaled(0.5)).collect();
+        assert_eq!(replayed.len(), 4);
+        assert!(start.elapsed() >= Duration::from_millis(80));
+    }
+
+    #[test]
+    fn test_replay_as_fast_as_possible() {
+        let mut packets = packets();
+        packets[1] = packet(200, 0);
+        let start = Instant::now();
+        let replayed: Vec<_> =
+            Replayer::new(packets.into_iter(), ReplaySpeed::AsFastAsPossible).collect();
+        assert_eq!(replayed.len(), 4);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_replay_with_key() {
+        let arrivals = vec![
+            (Duration::from_millis(5), 'a'),
+            (Duration::from_millis(35), 'b'),
+        ];
+        let start = Instant::now();
+        let replayed: Vec<_> =
+            Replayer::with_key(arrivals.into_iter(), ReplaySpeed::Scaled(2.0), |(t, _)| {
+                Some(*t)
+            })
+            .map(|(_, c)| c)
+            .collect();
+        assert_eq!(replayed, vec!['a', 'b']);
+        assert!(start.elapsed() >= Duration::from_millis(15));
+    }
+
+    #[test]
+    fn test_replay_sample_count() {
+        let sample_count = |tsf_type, count| VrtPacketOwned {
+            header: Header {
+                tsf: tsf_type,
+                ..packet(100, count).header
+            },
+            ..packet(100, count)
+        };
+        // context packet with a Sample Rate of 1 kHz
+        let mut data_payload = 0x0020_0000u32.to_be_bytes().to_vec();
+        data_payload.extend_from_slice(&(1000u64 << 20).to_be_bytes());
+        let context = VrtPacketOwned {
+            header: Header {
+                packet_type: VitaPacketType(VitaPacketType::IFCONTEXT),
+                packet_size: 8,
+                ..sample_count(Tsf::TSF_SAMPLE_COUNT, 0).header
+            },
+            data_payload,
+            ..sample_count(Tsf::TSF_SAMPLE_COUNT, 0)
+        };
+
+        // 0, 20ms and 40ms at 1 kHz
+        let packets = vec![
+            context.clone(),
+            sample_count(Tsf::TSF_SAMPLE_COUNT, 20),
+            sample_count(Tsf::TSF_SAMPLE_COUNT, 40),
+        ];
+        let start = Instant::now();
+        let replayed: Vec<_> =
+            Replayer::new(packets.into_iter(), ReplaySpeed::Scaled(1.0)).collect();
+        assert_eq!(replayed.len(), 3);
+        assert!(start.elapsed() >= Duration::from_millis(40));
+
+        // without a sample rate, and for Free Running timestamps, packets are not delayed
+        let packets = vec![
+            sample_count(Tsf::TSF_SAMPLE_COUNT, 0),
+            sample_count(Tsf::TSF_SAMPLE_COUNT, 5_000),
+            sample_count(Tsf::TSF_FREE_RUNNING, 0),
+            sample_count(Tsf::TSF_FREE_RUNNING, 5_000_000_000),
+        ];
+        let start = Instant::now();
+        let replayed: Vec<_> =
+            Replayer::new(packets.into_iter(), ReplaySpeed::Scaled(1.0)).collect();
+        assert_eq!(replayed.len(), 4);
+        assert!(start.elapsed() < Duration::from_secs(1));
+        // the Sample Rate of one stream does not apply to another
+        let other = |count| VrtPacketOwned {
+            stream_id: Some(2),
+            ..sample_count(Tsf::TSF_SAMPLE_COUNT, count)
+        };
+        let packets = vec![context, other(0), other(5_000)];
+        let start = Instant::now();
+        let replayed: Vec<_> =
+            Replayer::new(packets.into_iter(), ReplaySpeed::Scaled(1.0)).collect();
+        assert_eq!(replayed.len(), 3);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,38 @@
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_rx_metadata_to_packet() {
+        let metadata = RxMetadata {
+            time: Some(Duration::new(10, 5_000)),
+            overflow: true,
+            channel: 1,
+        };
+        let samples = [0xca, 0xfe, 0xf0, 0x0d];
+        let packet = metadata.to_packet(0x100, 17, &samples);
+        assert_eq!(packet.stream_id, Some(0x101));
+        assert_eq!(packet.header.packet_count, 1);
+        assert_eq!(packet.header.packet_size, 7);
+        assert_eq!((packet.tsi, packet.tsf), (Some(10), Some(5_000_000)));
+        assert!(packet.trailer.unwrap().sample_loss_indicator);
+
+        let mut buf = [0; 28];
+        assert_eq!(encode_vrt_packet(&packet, &mut buf), Ok(28));
+        let (_, parsed) = parse_vrt_packet(&buf).unwrap();
+        assert_eq!(parsed, packet);
+        assert_eq!(RxMetadata::from_packet(&parsed, 0x100), metadata);
+    }
+
+    #[test]
+    fn test_rx_metadata_without_time() {
+        let metadata = RxMetadata::default();
+        let packet = metadata.to_packet(0, 0, &[]);
+        assert_eq!(packet.header.tsi, Tsi::TSI_NONE);
+        assert_eq!(packet.encoded_len(), 12);
+        assert_eq!(RxMetadata::from_packet(&packet, 0), metadata);
+    }
+}
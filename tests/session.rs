@@ -0,0 +1,234 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    static VRT_STATIC_CONTEXT_STREAM_1: &[u8] = &[
+        0x40, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    static VRT_STATIC_CONTEXT_STREAM_1_UPDATE: &[u8] = &[
+        0x40, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x20, 0x00, 0x00, 0x00,
+    ];
+
+    /// Gain of 5 dB and Sample Rate of 2 MHz
+    static VRT_STATIC_CONTEXT_STREAM_1_SIGNAL: &[u8] = &[
+        0x40, 0x02, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0xa0, 0x00, 0x00, 0x00, 0x00, 0x02,
+        0x80, 0x00, 0x00, 0x01, 0xe8, 0x48, 0x00, 0x00, 0x00,
+    ];
+
+    static VRT_STATIC_CONTEXT_STREAM_81: &[u8] = &[
+        0x40, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x81, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    static VRT_STATIC_DATA_STREAM_1: &[u8] = &[
+        0x10, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    static VRT_STATIC_DATA_STREAM_2_TS_10: &[u8] = &[
+        0x10, 0x50, 0x00, 0x06, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_DATA_STREAM_2_TS_9: &[u8] = &[
+        0x10, 0x53, 0x00, 0x06, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x09, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_DATA_STREAM_2_TS_12: &[u8] = &[
+        0x10, 0x55, 0x00, 0x06, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x0c, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    fn parse(bytes: &[u8]) -> VrtPacket<'_> {
+        let (_, packet) = parse_vrt_packet(bytes).expect("failed to parse packet");
+        packet
+    }
+
+    #[test]
+    fn test_session_pairs_latest_context() {
+        let mut session = VrtSession::new();
+        let res = session.process(parse(VRT_STATIC_DATA_STREAM_1)).unwrap();
+        assert_eq!(res.context, None);
+
+        assert!(session
+            .process(parse(VRT_STATIC_CONTEXT_STREAM_1))
+            .is_none());
+        assert!(session
+            .process(parse(VRT_STATIC_CONTEXT_STREAM_1_UPDATE))
+            .is_none());
+
+        let res = session.process(parse(VRT_STATIC_DATA_STREAM_1)).unwrap();
+        assert_eq!(res.packet.data_payload, &[0xde, 0xad, 0xbe, 0xef]);
+        let context = res.context.expect("missing context");
+        assert_eq!(context.header.packet_count, 1);
+        assert_eq!(context.data_payload, vec![0x20, 0x00, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn test_session_with_association() {
+        let mut session = VrtSession::with_association(|stream_id| stream_id | 0x80);
+        session.process(parse(VRT_STATIC_CONTEXT_STREAM_1));
+        session.process(parse(VRT_STATIC_CONTEXT_STREAM_81));
+
+        let res = session.process(parse(VRT_STATIC_DATA_STREAM_1)).unwrap();
+        assert_eq!(res.context.and_then(|c| c.stream_id), Some(0x81));
+        assert!(session.context(1).is_some());
+        assert!(session.context(2).is_none());
+    }
+
+    #[test]
+    fn test_packet_owned_roundtrip() {
+        let packet = parse(VRT_STATIC_DATA_STREAM_1);
+        let owned = VrtPacketOwned::from(&packet);
+        assert_eq!(owned.as_packet(), packet);
+    }
+
+    #[test]
+    fn test_session_events() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut session = VrtSession::new();
+        let sink = events.clone();
+        session.on_event(move |event| {
+            let name = match event {
+                SessionEvent::NewStream { stream_id } => format!("new {:?}", stream_id),
+                SessionEvent::ContextChanged { stream_id, context } => {
+                    format!("context {} {}", stream_id, context.header.packet_count)
+                }
+                SessionEvent::PacketLoss {
+                    stream_id,
+                    context,
+                    lost,
+                } => format!("loss {:?} {} {}", stream_id, context, lost),
+                SessionEvent::TimestampDiscontinuity {
+                    stream_id,
+                    previous,
+                    current,
+                } => format!(
+                    "discontinuity {:?} {} {}",
+                    stream_id, previous.integer, current.integer
+                ),
+            };
+            sink.borrow_mut().push(name);
+        });
+
+        session.process(parse(VRT_STATIC_CONTEXT_STREAM_1));
+        session.process(parse(VRT_STATIC_CONTEXT_STREAM_1));
+        session.process(parse(VRT_STATIC_DATA_STREAM_1));
+        session.process(parse(VRT_STATIC_CONTEXT_STREAM_1_UPDATE));
+        session.process(parse(VRT_STATIC_DATA_STREAM_2_TS_10));
+        session.process(parse(VRT_STATIC_DATA_STREAM_2_TS_9));
+        session.process(parse(VRT_STATIC_DATA_STREAM_2_TS_12));
+
+        let expected = vec![
+            "new Some(1)",
+            "context 1 0",
+            "context 1 1",
+            "new Some(2)",
+            "discontinuity Some(2) 10 9",
+            // without a sample rate only backwards steps are discontinuities
+            "loss Some(2) false 4",
+        ];
+        assert_eq!(*events.borrow(), expected);
+    }
+
+    #[test]
+    fn test_session_signal_context() {
+        let mut session = VrtSession::new();
+        let res = session.process(parse(VRT_STATIC_DATA_STREAM_1)).unwrap();
+        assert_eq!(res.signal, None);
+
+        session.process(parse(VRT_STATIC_CONTEXT_STREAM_1_SIGNAL));
+        // a later context packet without these fields keeps them
+        session.process(parse(VRT_STATIC_CONTEXT_STREAM_1));
+        let res = session.process(parse(VRT_STATIC_DATA_STREAM_1)).unwrap();
+        let signal = res.signal.expect("missing signal context");
+        assert_eq!(signal.sample_rate, Some(2e6));
+        assert_eq!(signal.gain, Some(5.0));
+        assert_eq!(signal.rf_frequency, None);
+        assert_eq!(session.signal(1).and_then(|s| s.sample_rate), Some(2e6));
+        assert_eq!(session.signal(2), None);
+    }
+
+    #[test]
+    fn test_session_borrowing_handler() {
+        let mut streams = Vec::new();
+        {
+            let mut session = VrtSession::new();
+            session.on_event(|event| {
+                if let SessionEvent::NewStream { stream_id } = event {
+                    streams.push(*stream_id);
+                }
+            });
+            session.process(parse(VRT_STATIC_DATA_STREAM_1));
+            session.process(parse(VRT_STATIC_DATA_STREAM_2_TS_10));
+        }
+        assert_eq!(streams, [Some(1), Some(2)]);
+    }
+
+    /// Data packet of stream 2 with a Sample Count timestamp and one 32-bit sample
+    fn data_stream_2(packet_count: u8, integer: u8, fractional: u8) -> Vec<u8> {
+        vec![
+            0x10,
+            0x50 | packet_count,
+            0x00,
+            0x06,
+            0x00,
+            0x00,
+            0x00,
+            0x02,
+            0x00,
+            0x00,
+            0x00,
+            integer,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            fractional,
+            0xca,
+            0xfe,
+            0xf0,
+            0x0d,
+        ]
+    }
+
+    #[test]
+    fn test_session_timestamp_advance() {
+        // Sample Rate of 2 Hz
+        static CONTEXT_STREAM_2: &[u8] = &[
+            0x40, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x02, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x20, 0x00, 0x00,
+        ];
+        let discontinuities = Rc::new(RefCell::new(Vec::new()));
+        let mut session = VrtSession::new().with_bytes_per_sample(4);
+        let sink = discontinuities.clone();
+        session.on_event(move |event| {
+            if let SessionEvent::TimestampDiscontinuity { current, .. } = event {
+                sink.borrow_mut()
+                    .push((current.integer, current.fractional));
+            }
+        });
+        session.process(parse(CONTEXT_STREAM_2));
+        for (count, integer, fractional) in [
+            (0, 10, 0),
+            (1, 10, 1),
+            // one sample at 2 Hz carries into the next second
+            (2, 11, 0),
+            (3, 11, 1),
+            (4, 12, 1),
+            (5, 12, 0),
+        ] {
+            let bytes = data_stream_2(count, integer, fractional);
+            session.process(parse(&bytes));
+        }
+        assert_eq!(*discontinuities.borrow(), [(12, 1), (12, 0)]);
+    }
+}
@@ -0,0 +1,48 @@
+#![cfg(feature = "bytes")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use bytes::Bytes;
+    use std::thread;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_STREAM_2: &[u8] = &[
+        0x10, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    #[test]
+    fn test_parse_vrt_packet_bytes() {
+        let buf = Bytes::from([VRT_STATIC_STREAM_1, VRT_STATIC_STREAM_2].concat());
+        let (rem, first) = parse_vrt_packet_bytes(&buf).unwrap();
+        // the payload and remainder share the buffer
+        assert_eq!(first.data_payload.as_ptr(), buf[20..].as_ptr());
+        assert_eq!(rem.as_ptr(), buf[24..].as_ptr());
+        assert_eq!(
+            first.as_packet(),
+            parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap().1
+        );
+
+        let (rem, second) = parse_vrt_packet_bytes(&rem).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(second.stream_id, Some(2));
+        assert_eq!(
+            parse_vrt_packet_bytes(&buf.slice(..8)),
+            Err(Error::Incomplete(Some(16)))
+        );
+
+        let clone = first.clone();
+        let payload = thread::spawn(move || clone.data_payload).join().unwrap();
+        assert_eq!(&payload[..], &[0xca, 0xfe, 0xf0, 0x0d]);
+
+        let copied = VrtPacketBytes::from(&second.as_packet());
+        assert_eq!(copied, second);
+        let owned = VrtPacketOwned::from(second);
+        assert_eq!(owned.data_payload, vec![0xde, 0xad, 0xbe, 0xef]);
+    }
+}
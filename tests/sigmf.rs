@@ -0,0 +1,175 @@
+#![cfg(feature = "sigmf")]
+extern crate vrt;
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TestPacket;
+    use crate::vrt::*;
+
+    fn trailer(overrange: bool) -> Trailer {
+        let indicator = if overrange { 0x20 } else { 0x00 };
+        let (_, trailer) = parse_vrt_trailer(&[0x02, 0x00, indicator, 0x00]).unwrap();
+        assert!(trailer.overrange_enable);
+        trailer
+    }
+
+    fn packet(tsf: u64, payload: &[u8], overrange: bool) -> VrtPacket<'_> {
+        TestPacket {
+            // 2024-01-02T03:04:05Z
+            tsi: Some(1_704_164_645),
+            tsf: Some((Tsf::TSF_REAL_TIME, tsf)),
+            payload,
+            trailer: Some(trailer(overrange)),
+            ..TestPacket::default()
+        }
+        .build()
+    }
+
+    #[test]
+    fn test_sigmf_export() {
+        // 1 MHz, 4 samples per packet: packets at 0, 4 and 20 us
+        let payload = [0u8; 16];
+        let mut exporter = SigmfExporter::new(Vec::new(), "ci16_be", 4, 1e6).with_frequency(100e6);
+        exporter.push(&packet(0, &payload, true)).unwrap();
+        exporter.push(&packet(4_000_000, &payload, true)).unwrap();
+        exporter.push(&packet(20_000_000, &payload, false)).unwrap();
+        assert_eq!(exporter.samples(), 12);
+
+        let meta = exporter.metadata();
+        assert_eq!(meta["global"]["core:datatype"], "ci16_be");
+        assert_eq!(meta["global"]["core:sample_rate"], 1e6);
+        assert_eq!(meta["global"]["core:version"], SIGMF_VERSION);
+
+        let captures = meta["captures"].as_array().unwrap();
+        assert_eq!(captures.len(), 2);
+        assert_eq!(captures[0]["core:sample_start"], 0);
+        assert_eq!(captures[0]["core:frequency"], 100e6);
+        assert_eq!(
+            captures[0]["core:datetime"],
+            "2024-01-02T03:04:05.000000000Z"
+        );
+        assert_eq!(captures[1]["core:sample_start"], 8);
+        assert_eq!(
+            captures[1]["core:datetime"],
+            "2024-01-02T03:04:05.000020000Z"
+        );
+
+        let annotations = meta["annotations"].as_array().unwrap();
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0]["core:label"], "over-range");
+        assert_eq!(annotations[0]["core:sample_start"], 0);
+        assert_eq!(annotations[0]["core:sample_count"], 8);
+
+        let mut meta = Vec::new();
+        let data = exporter.finish(&mut meta).unwrap();
+        assert_eq!(data.len(), 48);
+        let parsed: serde_json::Value = serde_json::from_slice(&meta).unwrap();
+        assert_eq!(parsed["captures"][1]["core:sample_start"], 8);
+    }
+
+    #[test]
+    fn test_sigmf_import() {
+        let meta = serde_json::json!({
+            "global": { "core:datatype": "ci16_le", "core:sample_rate": 1e6 },
+            "captures": [
+                {
+                    "core:sample_start": 0,
+                    "core:frequency": 100e6,
+                    "core:datetime": "2024-01-02T03:04:05.5Z",
+                },
+                { "core:sample_start": 6, "core:datetime": "2024-01-02T03:04:06Z" },
+            ],
+        });
+        let importer = SigmfImporter::from_metadata(&meta)
+            .unwrap()
+            .with_stream_id(7);
+        let data: Vec<u8> = (0..40).collect();
+        // header, stream id, timestamps and 4 samples per packet
+        let packets = importer.packets(&data, 36).unwrap();
+        assert_eq!(packets.len(), 4);
+
+        let context = &packets[0];
+        assert!(context.header.packet_type.is_context());
+        assert_eq!(context.stream_id, Some(7));
+        assert_eq!(context.tsi, Some(1_704_164_645));
+        assert_eq!(context.tsf, Some(500_000_000_000));
+        assert_eq!(&context.data_payload[0..4], &[0x08, 0x20, 0x00, 0x00]);
+        assert_eq!(
+            &context.data_payload[4..12],
+            &((100e6 as i64) << 20).to_be_bytes()
+        );
+        assert_eq!(
+            &context.data_payload[12..20],
+            &((1e6 as i64) << 20).to_be_bytes()
+        );
+        assert_eq!(context.header.packet_size as usize, 5 + 5);
+
+        let counts: Vec<_> = packets[1..].iter().map(|p| p.header.packet_count).collect();
+        assert_eq!(counts, vec![0, 1, 2]);
+        assert_eq!(packets[1].data_payload[..4], [1, 0, 3, 2]);
+        assert_eq!(packets[1].data_payload.len(), 16);
+        assert_eq!(packets[2].data_payload.len(), 8);
+        assert_eq!(packets[2].tsf, Some(500_004_000_000));
+        assert_eq!(packets[3].tsi, Some(1_704_164_646));
+        assert_eq!(packets[3].tsf, Some(0));
+        assert_eq!(packets[3].data_payload.len(), 16);
+
+        let invalid = serde_json::json!({ "global": { "core:datatype": "cx16" } });
+        assert!(SigmfImporter::from_metadata(&invalid).is_err());
+    }
+
+    fn context(frequency: f64, sample_rate: Option<f64>) -> Vec<u8> {
+        let cif0: u32 = if sample_rate.is_some() {
+            0x0820_0000
+        } else {
+            0x0800_0000
+        };
+        let mut bytes = vec![0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01];
+        bytes.extend_from_slice(&cif0.to_be_bytes());
+        for value in std::iter::once(frequency).chain(sample_rate) {
+            bytes.extend_from_slice(&((value * (1 << 20) as f64) as i64).to_be_bytes());
+        }
+        bytes[3] = (bytes.len() / 4) as u8;
+        bytes
+    }
+
+    #[test]
+    fn test_sigmf_export_context() {
+        // 2 MHz from context, 4 samples per packet: packets at 0, 2 and 4 us
+        let payload = [0u8; 16];
+        let mut exporter = SigmfExporter::new(Vec::new(), "ci16_be", 4, 1e6).with_frequency(50e6);
+        let tuned = context(100e6, Some(2e6));
+        exporter.push(&parse_vrt_packet(&tuned).unwrap().1).unwrap();
+        exporter.push(&packet(0, &payload, false)).unwrap();
+        exporter.push(&packet(2_000_000, &payload, false)).unwrap();
+        let retuned = context(200e6, None);
+        exporter
+            .push(&parse_vrt_packet(&retuned).unwrap().1)
+            .unwrap();
+        exporter.push(&packet(4_000_000, &payload, false)).unwrap();
+
+        let meta = exporter.metadata();
+        assert_eq!(meta["global"]["core:sample_rate"], 2e6);
+        let captures = meta["captures"].as_array().unwrap();
+        assert_eq!(captures.len(), 2);
+        assert_eq!(captures[0]["core:frequency"], 100e6);
+        // a change of frequency starts a new segment although the samples are contiguous
+        assert_eq!(captures[1]["core:sample_start"], 8);
+        assert_eq!(captures[1]["core:frequency"], 200e6);
+    }
+
+    #[test]
+    fn test_sigmf_import_fractional_rate() {
+        let meta = serde_json::json!({
+            "global": { "core:datatype": "ci16_be", "core:sample_rate": 333333.333333 },
+            "captures": [{ "core:sample_start": 0, "core:datetime": "2024-01-02T03:04:05.5Z" }],
+        });
+        let importer = SigmfImporter::from_metadata(&meta).unwrap();
+        let packets = importer.packets(&[0u8; 32], 36).unwrap();
+        assert_eq!(packets.len(), 3);
+        // 4 samples at 1/3 MHz is 12 us, not the 12.000012 us of a rounded 333333 Hz
+        assert_eq!(packets[2].tsf, Some(500_012_000_000));
+    }
+}
@@ -0,0 +1,117 @@
+extern crate vrt;
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TestPacket;
+    use crate::vrt::*;
+
+    fn packet(stream_id: u32, packet_count: u8, tsi: Option<u32>) -> VrtPacket<'static> {
+        TestPacket {
+            stream_id,
+            packet_count,
+            tsi,
+            ..TestPacket::default()
+        }
+        .build()
+    }
+
+    #[test]
+    fn test_continuity_in_order_with_rollover() {
+        let mut checker = ContinuityChecker::new();
+        assert_eq!(checker.update(&packet(1, 14, None)), Continuity::First);
+        assert_eq!(checker.update(&packet(1, 15, None)), Continuity::InOrder);
+        assert_eq!(checker.update(&packet(1, 0, None)), Continuity::InOrder);
+        let expected = StreamStats {
+            received: 3,
+            ..Default::default()
+        };
+        assert_eq!(checker.stats(), expected);
+        assert_eq!(checker.stats().loss_rate(), 0.0);
+    }
+
+    #[test]
+    fn test_continuity_lost_duplicate_reordered() {
+        let mut checker = ContinuityChecker::new();
+        checker.update(&packet(1, 0, None));
+        assert_eq!(checker.update(&packet(1, 3, None)), Continuity::Lost(2));
+        assert_eq!(checker.update(&packet(1, 3, None)), Continuity::Duplicate);
+        assert_eq!(checker.update(&packet(1, 2, None)), Continuity::Reordered);
+        assert_eq!(checker.update(&packet(1, 4, None)), Continuity::InOrder);
+        let expected = StreamStats {
+            received: 5,
+            lost: 1,
+            duplicated: 1,
+            reordered: 1,
+        };
+        assert_eq!(checker.stats(), expected);
+        assert_eq!(checker.stats().loss_rate(), 0.2);
+    }
+
+    #[test]
+    fn test_continuity_reordered_without_gap() {
+        let mut checker = ContinuityChecker::new();
+        checker.update(&packet(1, 0, None));
+        checker.update(&packet(1, 1, None));
+        checker.update(&packet(1, 4, None));
+        // a late copy of a packet already received leaves the loss of 2 and 3 counted
+        assert_eq!(checker.update(&packet(1, 1, None)), Continuity::Reordered);
+        assert_eq!(checker.stats().lost, 2);
+        assert_eq!(checker.update(&packet(1, 2, None)), Continuity::Reordered);
+        assert_eq!(checker.update(&packet(1, 2, None)), Continuity::Reordered);
+        assert_eq!(checker.stats().lost, 1);
+    }
+
+    #[test]
+    fn test_continuity_timestamps_disambiguate_rollover() {
+        let mut checker = ContinuityChecker::new();
+        checker.update(&packet(1, 5, Some(100)));
+        assert_eq!(
+            checker.update(&packet(1, 5, Some(116))),
+            Continuity::Lost(15)
+        );
+        assert_eq!(
+            checker.update(&packet(1, 5, Some(116))),
+            Continuity::Duplicate
+        );
+        assert_eq!(
+            checker.update(&packet(1, 15, Some(110))),
+            Continuity::Reordered
+        );
+        assert_eq!(
+            checker.update(&packet(1, 15, Some(126))),
+            Continuity::Lost(9)
+        );
+    }
+
+    #[test]
+    fn test_continuity_windows() {
+        let mut checker = ContinuityChecker::new();
+        checker.update(&packet(1, 0, None));
+        checker.update(&packet(1, 2, None));
+        let window = checker.take_window();
+        assert_eq!(window.received, 2);
+        assert_eq!(window.lost, 1);
+        checker.update(&packet(1, 3, None));
+        let window = checker.take_window();
+        assert_eq!(window.received, 1);
+        assert_eq!(window.lost, 0);
+        assert_eq!(checker.stats().received, 3);
+        assert_eq!(checker.stats().lost, 1);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_stream_tracker() {
+        let mut tracker = StreamTracker::new();
+        tracker.update(&packet(1, 0, None));
+        tracker.update(&packet(2, 7, None));
+        assert_eq!(tracker.update(&packet(1, 1, None)), Continuity::InOrder);
+        assert_eq!(tracker.update(&packet(2, 9, None)), Continuity::Lost(1));
+        assert_eq!(tracker.stats(Some(1)).unwrap().lost, 0);
+        assert_eq!(tracker.stats(Some(2)).unwrap().lost, 1);
+        assert_eq!(tracker.stats(Some(3)), None);
+        assert_eq!(tracker.iter().count(), 2);
+    }
+}
@@ -0,0 +1,191 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::net::*;
+    use crate::vrt::parse_vrt_packet;
+    use std::io::Write;
+    use std::net::TcpStream;
+    use std::thread;
+    use std::time::Duration;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_STREAM_2: &[u8] = &[
+        0x10, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    #[test]
+    fn test_tcp_raw() {
+        let mut server = TcpServer::bind("127.0.0.1:0", TcpFraming::Raw).unwrap();
+        let addr = server.listener().local_addr().unwrap();
+        let client = thread::spawn(move || {
+            let mut client = TcpClient::connect(addr, TcpFraming::Raw).unwrap();
+            client
+                .send(&parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap().1)
+                .unwrap();
+            client
+                .send(&parse_vrt_packet(VRT_STATIC_STREAM_2).unwrap().1)
+                .unwrap();
+            client.connection().unwrap().stream().local_addr().unwrap()
+        });
+        let (source, first) = server.recv_packet().unwrap();
+        assert_eq!(first.stream_id, Some(1));
+        assert_eq!(first.data_payload, vec![0xca, 0xfe, 0xf0, 0x0d]);
+        let (_, second) = server.recv_packet().unwrap();
+        assert_eq!(second.stream_id, Some(2));
+        assert_eq!(source, client.join().unwrap());
+    }
+
+    #[test]
+    fn test_tcp_vrl_resync() {
+        let server = TcpServer::bind("127.0.0.1:0", TcpFraming::Vrl).unwrap();
+        let mut stream = TcpStream::connect(server.listener().local_addr().unwrap()).unwrap();
+        let (_, mut connection) = server.accept().unwrap();
+
+        let mut frame = b"VRLP".to_vec();
+        frame.extend_from_slice(&[0x00, 0x00, 0x00, 0x09]);
+        frame.extend_from_slice(VRT_STATIC_STREAM_1);
+        frame.extend_from_slice(b"VEND");
+        stream.write_all(&frame).unwrap();
+        stream.write_all(&[0x01, 0x02, 0x03]).unwrap();
+        stream.write_all(&frame).unwrap();
+        drop(stream);
+
+        for _ in 0..2 {
+            let packet = connection.recv_packet().unwrap().unwrap();
+            assert_eq!(packet.stream_id, Some(1));
+        }
+        assert_eq!(connection.recv_packet().unwrap(), None);
+        assert_eq!(connection.resyncs(), 1);
+    }
+
+    #[test]
+    fn test_tcp_vrl_malformed_frames() {
+        let server = TcpServer::bind("127.0.0.1:0", TcpFraming::Vrl).unwrap();
+        let mut stream = TcpStream::connect(server.listener().local_addr().unwrap()).unwrap();
+        let (_, mut connection) = server.accept().unwrap();
+
+        let mut frame = b"VRLP".to_vec();
+        frame.extend_from_slice(&[0x00, 0x00, 0x00, 0x09]);
+        frame.extend_from_slice(VRT_STATIC_STREAM_1);
+        frame.extend_from_slice(b"VEND");
+        // a frame too short to hold its end word
+        stream.write_all(b"VRLP\x00\x00\x00\x02").unwrap();
+        stream.write_all(&frame).unwrap();
+        // a frame whose length runs into the next frame, with a packet size of zero
+        stream
+            .write_all(b"VRLP\x00\x00\x00\x0c\x00\x00\x00\x00")
+            .unwrap();
+        stream.write_all(&frame).unwrap();
+        drop(stream);
+
+        for _ in 0..2 {
+            let packet = connection.recv_packet().unwrap().unwrap();
+            assert_eq!(packet.stream_id, Some(1));
+        }
+        assert_eq!(connection.recv_packet().unwrap(), None);
+        assert_eq!(connection.resyncs(), 2);
+    }
+
+    #[test]
+    fn test_tcp_errors_keep_connection() {
+        let mut server = TcpServer::bind("127.0.0.1:0", TcpFraming::Raw).unwrap();
+        let addr = server.listener().local_addr().unwrap();
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(&[0x10, 0x00, 0x00, 0x00]).unwrap();
+        let e = server.recv_packet().unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+        stream.write_all(VRT_STATIC_STREAM_2).unwrap();
+        assert_eq!(server.recv_packet().unwrap().1.stream_id, Some(2));
+
+        // a read timeout is returned rather than reconnecting
+        let mut client = TcpClient::connect(addr, TcpFraming::Raw).unwrap();
+        let _connection = server.accept().unwrap();
+        client
+            .connection()
+            .unwrap()
+            .stream()
+            .set_read_timeout(Some(Duration::from_millis(10)))
+            .unwrap();
+        let e = client.recv_packet().unwrap_err();
+        assert!(matches!(
+            e.kind(),
+            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+        ));
+        assert_eq!(client.reconnects(), 0);
+        assert!(client.connection().is_some());
+    }
+
+    #[test]
+    fn test_tcp_vrl_round_trip() {
+        let server = TcpServer::bind("127.0.0.1:0", TcpFraming::Vrl).unwrap();
+        let stream = TcpStream::connect(server.listener().local_addr().unwrap()).unwrap();
+        let mut sender = TcpConnection::new(stream, TcpFraming::Vrl);
+        let (_, mut receiver) = server.accept().unwrap();
+        for bytes in [VRT_STATIC_STREAM_1, VRT_STATIC_STREAM_2] {
+            sender.send(&parse_vrt_packet(bytes).unwrap().1).unwrap();
+        }
+        let first = receiver.recv_packet().unwrap().unwrap();
+        assert_eq!(first.stream_id, Some(1));
+        let second = receiver.recv_packet().unwrap().unwrap();
+        assert_eq!(second.stream_id, Some(2));
+        assert_eq!(receiver.resyncs(), 0);
+    }
+
+    #[test]
+    fn test_tcp_client_reconnect() {
+        let server = TcpServer::bind("127.0.0.1:0", TcpFraming::Raw).unwrap();
+        let addr = server.listener().local_addr().unwrap();
+        let handle = thread::spawn(move || {
+            let (_, mut connection) = server.accept().unwrap();
+            connection
+                .send(&parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap().1)
+                .unwrap();
+            // a partial packet, cut off by the connection loss
+            let mut stream = connection.stream();
+            stream.write_all(&VRT_STATIC_STREAM_1[..8]).unwrap();
+            drop(connection);
+
+            let (_, mut connection) = server.accept().unwrap();
+            connection
+                .send(&parse_vrt_packet(VRT_STATIC_STREAM_2).unwrap().1)
+                .unwrap();
+        });
+
+        let mut client = TcpClient::connect(addr, TcpFraming::Raw)
+            .unwrap()
+            .with_reconnect_delay(Duration::from_millis(10));
+        assert_eq!(client.recv_packet().unwrap().stream_id, Some(1));
+        assert_eq!(client.recv_packet().unwrap().stream_id, Some(2));
+        assert_eq!(client.reconnects(), 1);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_parse_vrl_frame() {
+        let mut frame = b"VRLP".to_vec();
+        frame.extend_from_slice(&[0x00, 0x00, 0x00, 0x0c]);
+        frame.extend_from_slice(VRT_STATIC_STREAM_1);
+        frame.extend_from_slice(VRT_STATIC_STREAM_2);
+        frame.extend_from_slice(b"VEND");
+        frame.extend_from_slice(b"VRLP");
+
+        let (len, packets) = parse_vrl_frame(&frame).unwrap();
+        assert_eq!(len, 48);
+        let stream_ids: Vec<_> = packets.map(|p| p.unwrap().stream_id).collect();
+        assert_eq!(stream_ids, [Some(1), Some(2)]);
+        assert_eq!(
+            parse_vrl_frame(&frame[..40]).map(|_| ()),
+            Err(vrt::Error::Incomplete(Some(8)))
+        );
+        assert_eq!(
+            parse_vrl_frame(&frame[4..]).map(|_| ()),
+            Err(vrt::Error::Malformed)
+        );
+    }
+}
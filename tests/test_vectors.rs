@@ -0,0 +1,56 @@
+#![cfg(feature = "test-vectors")]
+
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::test_vectors::*;
+    use crate::vrt::*;
+
+    #[test]
+    fn test_vectors_parse() {
+        for vector in ALL {
+            let (rest, packet) = parse_vrt_packet(vector.bytes).expect(vector.name);
+            assert!(rest.is_empty(), "{}", vector.name);
+            assert_eq!(packet.header, vector.header, "{}", vector.name);
+            assert_eq!(packet.stream_id, vector.stream_id, "{}", vector.name);
+            assert_eq!(packet.class_id, vector.class_id, "{}", vector.name);
+            assert_eq!(packet.tsi, vector.tsi, "{}", vector.name);
+            assert_eq!(packet.tsf, vector.tsf, "{}", vector.name);
+            assert_eq!(packet.data_payload, vector.payload, "{}", vector.name);
+            let trailer = packet
+                .trailer
+                .map(|t| u32::from_be_bytes(encode_vrt_trailer(&t)));
+            assert_eq!(trailer, vector.trailer, "{}", vector.name);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_vectors_encode() {
+        for vector in ALL {
+            let (_, packet) = parse_vrt_packet(vector.bytes).unwrap();
+            assert_eq!(packet.to_vec().unwrap(), vector.bytes, "{}", vector.name);
+        }
+    }
+
+    #[test]
+    fn test_vectors_names() {
+        let names: Vec<_> = ALL.iter().map(|v| v.name).collect();
+        assert!(names
+            .iter()
+            .enumerate()
+            .all(|(i, n)| !names[..i].contains(n)));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_vectors_context_fields() {
+        let (_, packet) = parse_vrt_packet(IF_CONTEXT.bytes).unwrap();
+        let tree = packet.dissect();
+        let value = |name: &str| tree.find(name).map(|f| f.value.clone());
+        assert_eq!(value("Bandwidth").as_deref(), Some("1000000 Hz"));
+        assert_eq!(value("Reference Level").as_deref(), Some("-10 dBm"));
+        assert_eq!(value("Sample Rate").as_deref(), Some("2000000 Hz"));
+    }
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "tokio")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::net::*;
+    use crate::vrt::*;
+    use futures_util::{SinkExt, StreamExt};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_STREAM_2: &[u8] = &[
+        0x10, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    fn owned(bytes: &[u8]) -> VrtPacketOwned {
+        VrtPacketOwned::from(&parse_vrt_packet(bytes).unwrap().1)
+    }
+
+    #[tokio::test]
+    async fn test_async_udp_roundtrip() {
+        let mut receiver = AsyncUdpReceiver::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind")
+            .with_max_datagram_len(16);
+        let socket = tokio::net::UdpSocket::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind");
+        let source = socket.local_addr().unwrap();
+        let mut sender = AsyncUdpSender::new(socket, receiver.socket().local_addr().unwrap());
+
+        sender.send(owned(VRT_STATIC_STREAM_2)).await.unwrap();
+        // too long for the maximum datagram length
+        sender.send(owned(VRT_STATIC_STREAM_1)).await.unwrap();
+        sender.send(owned(VRT_STATIC_STREAM_2)).await.unwrap();
+        let mut invalid = owned(VRT_STATIC_STREAM_2);
+        invalid.stream_id = None;
+        let err = sender.send(invalid).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+        let demux_count = Rc::new(RefCell::new(0));
+        let count = demux_count.clone();
+        let mut demux = Demux::new();
+        demux.register(2, move |_| *count.borrow_mut() += 1);
+
+        for _ in 0..2 {
+            let (from, packet) = receiver.next().await.unwrap().unwrap();
+            assert_eq!(from, source);
+            assert_eq!(packet, owned(VRT_STATIC_STREAM_2));
+            assert!(demux.route(&packet.as_packet()));
+        }
+        assert_eq!(*demux_count.borrow(), 2);
+        assert_eq!(receiver.truncated(), 1);
+    }
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "tracing")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    /// Subscriber recording each span name and event as its fields, formatted as `name=value`
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    struct Fields(String);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0 += &format!(" {}={:?}", field.name(), value);
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = Fields(span.metadata().name().to_string());
+            span.record(&mut fields);
+            self.0.lock().unwrap().push(fields.0);
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields(String::new());
+            event.record(&mut fields);
+            self.0.lock().unwrap().push(fields.0);
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn test_tracing_events() {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let packet = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap().1;
+            assert!(parse_vrt_packet(&VRT_STATIC_STREAM_1[..8]).is_err());
+            encode_vrt_packet(&packet, &mut [0u8; 8]).unwrap_err();
+            packet.to_vec().unwrap();
+            let mut demux = Demux::new();
+            demux.route(&packet);
+            let mut session = VrtSession::new();
+            session.process(packet);
+        });
+
+        let lines = recorder.0.lock().unwrap();
+        let expected = [
+            " message=parsed VRT packet stream_id=1 packet_type=1 packet_count=0 packet_size=6",
+            " message=incomplete VRT packet needed=Size(16)",
+            " message=buffer too small for VRT packet stream_id=1 len=24 available=8",
+            " message=encoded VRT packet stream_id=1 packet_count=0 len=24",
+            " message=routed VRT packet stream_id=1 packet_count=0 handled=false",
+            "vrt_session_process stream_id=1 packet_count=0",
+            " message=VRT session event event=NewStream { stream_id: Some(1) }",
+        ];
+        assert_eq!(&lines[..], &expected[..]);
+    }
+}
@@ -0,0 +1,58 @@
+#![cfg(feature = "tui")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use std::time::{Duration, Instant};
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_CONTEXT: &[u8] = &[
+        0x40, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x01, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0xf4, 0x24, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn test_monitor() {
+        let start = Instant::now();
+        let mut monitor = Monitor::new(start).with_log_len(2);
+        let mut later = VRT_STATIC_STREAM_1.to_vec();
+        later[1] = 0x63;
+        for bytes in [VRT_STATIC_STREAM_1, VRT_STATIC_CONTEXT, &later] {
+            monitor.update(&parse_vrt_packet(bytes).unwrap().1);
+        }
+        monitor.tick(start + Duration::from_secs(2));
+        assert_eq!(monitor.rate(Some(1)), Some(1.5));
+        assert_eq!(monitor.rate(Some(2)), None);
+        assert_eq!(
+            monitor.context(Some(1)),
+            [("Sample Rate", "1000000 Hz".to_string())]
+        );
+        assert_eq!(
+            monitor.log().collect::<Vec<_>>(),
+            [
+                "0x00000001 IF Context count 0",
+                "0x00000001 IF Data with Stream ID count 3 at 10.000000000005 after 2 lost",
+            ]
+        );
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 10)).unwrap();
+        terminal.draw(|frame| monitor.render(frame)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("0x00000001"));
+        assert!(screen.contains("Sample Rate: 1000000 Hz"));
+        assert!(screen.contains("after 2 lost"));
+    }
+}
@@ -0,0 +1,83 @@
+#![cfg(all(feature = "io-uring", target_os = "linux"))]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::net::DEFAULT_MAX_DATAGRAM_LEN;
+    use crate::vrt::*;
+    use std::io;
+    use std::net::UdpSocket;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    static VRT_STATIC_STREAM_2: &[u8] = &[
+        0x10, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad, 0xbe, 0xef,
+    ];
+
+    /// Returns None where the kernel does not offer io_uring with multishot recvmsg
+    fn receiver(buffers: u16, max_datagram_len: usize) -> Option<(IoUringReceiver, UdpSocket)> {
+        let socket = UdpSocket::bind("127.0.0.1:0").expect("failed to bind");
+        let receiver = match IoUringReceiver::with_buffers(socket, buffers, max_datagram_len) {
+            Ok(receiver) => receiver,
+            Err(e) if e.kind() == io::ErrorKind::InvalidInput => panic!("{}", e),
+            Err(_) => return None,
+        };
+        let sender = UdpSocket::bind("127.0.0.1:0").expect("failed to bind");
+        sender
+            .connect(receiver.socket().local_addr().unwrap())
+            .unwrap();
+        Some((receiver, sender))
+    }
+
+    #[test]
+    fn test_io_uring_receiver_packets() {
+        let Some((mut receiver, sender)) = receiver(8, 32) else {
+            return;
+        };
+        // too long for the maximum datagram length
+        sender
+            .send(&[VRT_STATIC_STREAM_1, VRT_STATIC_STREAM_2].concat())
+            .unwrap();
+        sender.send(&VRT_STATIC_STREAM_1[..8]).unwrap();
+        sender
+            .send(&[VRT_STATIC_STREAM_2, VRT_STATIC_STREAM_2].concat())
+            .unwrap();
+        sender.send(VRT_STATIC_STREAM_1).unwrap();
+
+        let (source, first) = receiver.next().unwrap().unwrap();
+        assert_eq!(source, sender.local_addr().unwrap());
+        assert_eq!(first.stream_id, Some(2));
+        let (_, second) = receiver.next().unwrap().unwrap();
+        assert_eq!(second.stream_id, Some(2));
+        let (_, third) = receiver.next().unwrap().unwrap();
+        assert_eq!(third.stream_id, Some(1));
+        assert_eq!(receiver.truncated(), 1);
+        assert_eq!(receiver.malformed(), 1);
+    }
+
+    #[test]
+    fn test_io_uring_receiver_recycles_buffers() {
+        let Some((mut receiver, sender)) = receiver(4, DEFAULT_MAX_DATAGRAM_LEN) else {
+            return;
+        };
+        // more datagrams than buffers end the multishot receive, which is then rearmed
+        for _ in 0..10 {
+            sender.send(VRT_STATIC_STREAM_2).unwrap();
+        }
+        for _ in 0..10 {
+            let (_, packet) = receiver.recv_packet().unwrap();
+            assert_eq!(packet.stream_id, Some(2));
+        }
+        assert_eq!(receiver.malformed(), 0);
+    }
+
+    #[test]
+    fn test_io_uring_receiver_buffers() {
+        let socket = UdpSocket::bind("127.0.0.1:0").expect("failed to bind");
+        let err = IoUringReceiver::with_buffers(socket, 6, DEFAULT_MAX_DATAGRAM_LEN).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
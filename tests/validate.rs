@@ -0,0 +1,136 @@
+#![cfg(feature = "std")]
+extern crate vrt;
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::TestPacket;
+    use crate::vrt::*;
+
+    fn packet() -> VrtPacket<'static> {
+        TestPacket {
+            tsi: Some(10),
+            tsf: Some((Tsf::TSF_REAL_TIME, 5)),
+            payload: &[0xca, 0xfe, 0xf0, 0x0d],
+            ..TestPacket::default()
+        }
+        .build()
+    }
+
+    #[test]
+    fn test_validator() {
+        let mut validator = Validator::new();
+        assert!(validator.check(&packet()).is_empty());
+
+        let mut next = packet();
+        next.header.packet_count = 1;
+        assert!(validator.check(&next).is_empty());
+
+        let mut late = packet();
+        late.header.packet_count = 3;
+        late.tsi = Some(9);
+        late.tsf = Some(2_000_000_000_000);
+        let violations = validator.check(&late);
+        let rules: Vec<_> = violations.iter().map(|v| v.rule).collect();
+        assert_eq!(
+            rules,
+            [Rule::RealTimeRange, Rule::TimestampOrder, Rule::PacketCount]
+        );
+        assert_eq!((violations[2].packet, violations[2].offset), (2, 48));
+        assert_eq!(
+            violations[2].to_string(),
+            "packet-count: packet #2 at offset 48: packet count 3, expected 2"
+        );
+
+        let mut context = packet();
+        context.header.packet_type = VitaPacketType(VitaPacketType::IFCONTEXT);
+        context.header.tsf = Tsf::TSF_NONE;
+        context.tsf = None;
+        assert!(validator.check(&context).is_empty());
+        context.header.packet_count = 1;
+        context.header.tsi = Tsi::TSI_GPS;
+        let rules: Vec<_> = validator.check(&context).iter().map(|v| v.rule).collect();
+        assert_eq!(rules, [Rule::TimestampMode]);
+
+        let mut reserved = packet();
+        reserved.header.packet_type = VitaPacketType(6);
+        reserved.stream_id = Some(2);
+        reserved.header.c = true;
+        reserved.class_id = Some(0x0100_0000_0000_0000);
+        let rules: Vec<_> = validator.check(&reserved).iter().map(|v| v.rule).collect();
+        assert_eq!(rules, [Rule::PacketType, Rule::ClassIdReserved]);
+
+        assert_eq!(validator.packets(), 6);
+        assert_eq!(validator.failures(Rule::PacketCount), 1);
+        assert_eq!(validator.failures(Rule::TimestampMode), 1);
+        assert!(!validator.passed());
+    }
+
+    #[test]
+    fn test_validator_difi() {
+        let version = DifiPacket::Version(DifiVersion {
+            stream_id: 1,
+            packet_count: 0,
+            seconds: 10,
+            picoseconds: 0,
+            changed: true,
+            spec_compliance: DIFI_V49_2,
+            version: 1,
+        })
+        .to_packet();
+        let data = DifiPacket::Data(DifiData {
+            stream_id: 1,
+            packet_count: 0,
+            seconds: 10,
+            picoseconds: 0,
+            payload: &[0; 8],
+        })
+        .to_packet();
+        let mut validator = Validator::new().with_difi();
+        assert!(validator.check(&version.as_packet()).is_empty());
+        assert!(validator.check(&data.as_packet()).is_empty());
+
+        // a VITA 49.0 packet without a Class Identifier
+        let mut other = packet();
+        other.stream_id = Some(2);
+        let rules: Vec<_> = validator.check(&other).iter().map(|v| v.rule).collect();
+        assert_eq!(rules, [Rule::DifiClassId]);
+        assert!(Validator::new().check(&packet()).is_empty());
+
+        let mut sample_count = data.clone();
+        sample_count.header.packet_count = 1;
+        sample_count.header.tsf = Tsf::TSF_SAMPLE_COUNT;
+        sample_count.header.t = true;
+        let violations = validator.check(&sample_count.as_packet());
+        let rules: Vec<_> = violations.iter().map(|v| v.rule).collect();
+        assert_eq!(
+            rules,
+            [Rule::DifiHeader, Rule::DifiTimestamp, Rule::TimestampMode]
+        );
+        assert_eq!(violations[0].message, "trailer included");
+
+        let mut fields = version.clone();
+        fields.header.packet_count = 1;
+        fields.data_payload[7] = 0x0e;
+        let violations = validator.check(&fields.as_packet());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].to_string(),
+            "difi-context-fields: packet #4 at offset 140: CIF0 0x00000002 CIF1 0x0000000e"
+        );
+        fields.data_payload[7] = 0x0c;
+        fields.data_payload.truncate(12);
+        fields.header.packet_size -= 1;
+        let rules: Vec<_> = validator
+            .check(&fields.as_packet())
+            .iter()
+            .map(|v| v.rule)
+            .collect();
+        assert_eq!(rules, [Rule::DifiContextFields, Rule::PacketCount]);
+
+        assert_eq!(validator.failures(Rule::DifiClassId), 1);
+        assert_eq!(validator.failures(Rule::DifiContextFields), 2);
+        assert_eq!(Rule::DIFI.map(|rule| rule.name())[3], "difi-context-fields");
+    }
+}
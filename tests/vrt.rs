@@ -47,6 +47,13 @@ mod tests {
 
     static VRT_STATIC_TRAILER_TEST_PATTERN: &[u8] = &[0xAA, 0xAA, 0xAA, 0xAA];
 
+    static VRT_STATIC_IFDATA_WITH_TRAILER: &[u8] = &[
+        0x14, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01, 0xde, 0xad, 0xbe, 0xef, 0xAA, 0xAA, 0xAA,
+        0xAA,
+    ];
+
+    static VRT_STATIC_WARNING_ERROR_TEST_PATTERN: &[u8] = &[0x90, 0x08, 0x00, 0x05];
+
     #[test]
     fn test_parse_vrt_header() {
         let bytes = &VRT_FLEXRADIO_BROADCAST_MSG[0..4];
@@ -54,6 +61,7 @@ mod tests {
             packet_type: VitaPacketType(3),
             c: true,
             t: false,
+            indicators: 0,
             tsi: Tsi(1),
             tsf: Tsf(1),
             packet_count: 15,
@@ -113,4 +121,615 @@ mod tests {
         let expected_error_bool = true;
         assert_eq!(actual_error_bool, expected_error_bool);
     }
+
+    #[test]
+    fn test_parse_vrt_warning_error_response() {
+        let bytes = &VRT_STATIC_WARNING_ERROR_TEST_PATTERN[0..4];
+        let (_, res) = parse_vrt_warning_error_response(bytes)
+            .expect("failed to parse warning/error response");
+        assert_eq!(res, WarningErrorResponse(0x9008_0005));
+        assert!(res.contains(WarningErrorResponse::FIELD_NOT_EXECUTED));
+        assert!(res.contains(WarningErrorResponse::PARAMETER_OUT_OF_RANGE));
+        assert!(res.contains(WarningErrorResponse::REGIONAL_INTERFERENCE));
+        assert!(!res.contains(WarningErrorResponse::DEVICE_FAILURE));
+        assert_eq!(res.user_defined(), 5);
+    }
+
+    #[test]
+    fn test_warning_error_flags() {
+        let res = WarningErrorResponse(0x9008_0005);
+        assert_eq!(
+            res.flags().collect::<Vec<_>>(),
+            [
+                WarningErrorFlag::FieldNotExecuted,
+                WarningErrorFlag::ParameterOutOfRange,
+                WarningErrorFlag::RegionalInterference,
+            ]
+        );
+        assert!(res.has(WarningErrorFlag::RegionalInterference));
+        assert!(!res.has(WarningErrorFlag::Distortion));
+        assert_eq!(
+            WarningErrorFlag::DeviceFailure.bit(),
+            WarningErrorResponse::DEVICE_FAILURE
+        );
+        assert_eq!(
+            WarningErrorFlag::ParameterOutOfRange.to_string(),
+            "parameter out of range"
+        );
+    }
+
+    #[test]
+    fn test_parse_vrt_warning_error_responses() {
+        // Bandwidth (bit 29) and Sample Rate (bit 21) announced, along with the CIF1 enable
+        // (bit 1), which has no response word of its own
+        let indicators = 1 << 29 | 1 << 21 | 1 << 1;
+        let bytes = [0x80, 0, 0, 0, 0x10, 0, 0, 0, 0xff];
+        let (rem, responses) = parse_vrt_warning_error_responses(indicators, &bytes).unwrap();
+        assert_eq!(rem, [0xff]);
+        assert_eq!(responses.len(), 2);
+        assert_eq!(
+            responses.iter().collect::<Vec<_>>(),
+            [
+                (29, WarningErrorResponse(0x8000_0000)),
+                (21, WarningErrorResponse(0x1000_0000)),
+            ]
+        );
+        assert_eq!(
+            responses
+                .get(21)
+                .map(|r| r.has(WarningErrorFlag::ParameterOutOfRange)),
+            Some(true)
+        );
+        assert_eq!(responses.get(24), None);
+
+        let err = parse_vrt_warning_error_responses(indicators, &bytes[..6]).unwrap_err();
+        assert_eq!(err, nom::Err::Incomplete(nom::Needed::new(2)));
+        let (_, responses) = parse_vrt_warning_error_responses(0, &[]).unwrap();
+        assert!(responses.is_empty());
+    }
+
+    #[test]
+    fn test_parse_vrt_warning_error_response_tooshort() {
+        let bytes = &VRT_STATIC_WARNING_ERROR_TEST_PATTERN[0..3];
+        let actual_error_bool = parse_vrt_warning_error_response(bytes)
+            .unwrap_err()
+            .is_incomplete();
+        let expected_error_bool = true;
+        assert_eq!(actual_error_bool, expected_error_bool);
+    }
+
+    #[test]
+    fn test_parse_vrt_packet() {
+        let bytes = VRT_FLEXRADIO_BROADCAST_MSG;
+        let (rem, res) = parse_vrt_packet(bytes).expect("failed to parse packet");
+        assert!(rem.is_empty());
+        assert_eq!(res.header.packet_size, 138);
+        assert_eq!(res.stream_id, Some(0x0000_0800));
+        assert_eq!(res.class_id, Some(0x0000_1c2d_534c_ffff));
+        assert_eq!(res.tsi, Some(0x6243_8ffc));
+        assert_eq!(res.tsf, Some(0));
+        assert_eq!(res.data_payload.len(), 552 - 28);
+        assert!(res
+            .data_payload
+            .starts_with(b"discovery_protocol_version=3.0.0.1"));
+        assert_eq!(res.trailer, None);
+    }
+
+    #[test]
+    fn test_parse_vrt_packet_with_trailer() {
+        let (rem, res) =
+            parse_vrt_packet(VRT_STATIC_IFDATA_WITH_TRAILER).expect("failed to parse packet");
+        assert!(rem.is_empty());
+        assert_eq!(res.stream_id, Some(1));
+        assert_eq!(res.class_id, None);
+        assert_eq!(res.data_payload, &[0xde, 0xad, 0xbe, 0xef]);
+        let (_, trailer) = parse_vrt_trailer(VRT_STATIC_TRAILER_TEST_PATTERN).unwrap();
+        assert_eq!(res.trailer, Some(trailer));
+    }
+
+    #[test]
+    fn test_vrt_packet_parse() {
+        let mut bytes = VRT_STATIC_IFDATA_WITH_TRAILER.to_vec();
+        bytes.extend_from_slice(&[0xff; 3]);
+        let (len, packet) = VrtPacket::parse(&bytes).expect("failed to parse packet");
+        assert_eq!(len, VRT_STATIC_IFDATA_WITH_TRAILER.len());
+        assert_eq!(packet.data_payload, &[0xde, 0xad, 0xbe, 0xef]);
+
+        let res = VrtPacket::parse(&VRT_FLEXRADIO_BROADCAST_MSG[0..100]);
+        assert_eq!(res.unwrap_err(), Error::Incomplete(Some(452)));
+        let res = VrtPacket::parse(&[0x38, 0x5f, 0x00, 0x02, 0x00, 0x00, 0x08, 0x00]);
+        assert_eq!(
+            res.unwrap_err(),
+            Error::MalformedField(FieldError {
+                field: PacketField::ClassId,
+                word: 1,
+                offset: 8,
+                reason: "beyond the declared packet size",
+            })
+        );
+    }
+
+    #[test]
+    fn test_header_min_packet_size() {
+        let (_, header) = parse_vrt_header(VRT_STATIC_IFDATA_WITH_TRAILER).unwrap();
+        assert_eq!(header.min_packet_size(), 3);
+        let (_, header) = parse_vrt_header(&[0x4c, 0xf0, 0x00, 0x01]).unwrap();
+        assert_eq!(header.min_packet_size(), 7);
+    }
+
+    #[test]
+    fn test_parse_checked() {
+        let mut bytes = VRT_STATIC_IFDATA_WITH_TRAILER.to_vec();
+        let (packet, rest) = VrtPacket::parse_checked(&bytes, TrailingBytes::Error).unwrap();
+        assert_eq!(packet.stream_id, Some(1));
+        assert!(rest.is_empty());
+
+        bytes.extend_from_slice(&[0xaa; 6]);
+        assert_eq!(
+            VrtPacket::parse_checked(&bytes, TrailingBytes::Error).map(|_| ()),
+            Err(Error::TrailingBytes(6))
+        );
+        let (_, rest) = VrtPacket::parse_checked(&bytes, TrailingBytes::Warn).unwrap();
+        assert!(rest.is_empty());
+        let (_, rest) = VrtPacket::parse_checked(&bytes, TrailingBytes::Remainder).unwrap();
+        assert_eq!(rest, &[0xaa; 6]);
+
+        // a trailer declared in a packet of two words, with no room for it
+        bytes[3] = 0x02;
+        assert!(matches!(
+            VrtPacket::parse_checked(&bytes, TrailingBytes::Remainder),
+            Err(Error::MalformedField(FieldError {
+                field: PacketField::Trailer,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_error_display() {
+        assert_eq!(
+            Error::Incomplete(Some(8)).to_string(),
+            "incomplete VRT packet: 8 more bytes needed"
+        );
+        assert_eq!(Error::Incomplete(None).to_string(), "incomplete VRT packet");
+        assert_eq!(Error::Malformed.to_string(), "malformed VRT packet");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_error_std() {
+        fn parse(bytes: &[u8]) -> Result<usize, Box<dyn std::error::Error>> {
+            let (len, _) = VrtPacket::parse(bytes)?;
+            Ok(len)
+        }
+        let err = parse(&[0x14, 0x00, 0x00, 0x00]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "malformed VRT packet: Header word 1 at offset 0: packet size is less than one word"
+        );
+    }
+
+    #[test]
+    fn test_parse_vrt_packet_small_stack() {
+        let buf = VRT_FLEXRADIO_BROADCAST_MSG.repeat(64);
+        std::thread::Builder::new()
+            .stack_size(32 * 1024)
+            .spawn(move || {
+                assert_eq!(PacketStream::new(&buf).filter(Result::is_ok).count(), 64);
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_parse_vrt_packet_tooshort() {
+        let bytes = &VRT_FLEXRADIO_BROADCAST_MSG[0..100];
+        let actual_error_bool = parse_vrt_packet(bytes).unwrap_err().is_incomplete();
+        let expected_error_bool = true;
+        assert_eq!(actual_error_bool, expected_error_bool);
+    }
+
+    #[test]
+    fn test_parse_vrt_packet_fields_exceed_packet_size() {
+        // class id and timestamps flagged in the header, but packet_size is only 2 words
+        let bytes = &[0x38, 0x5f, 0x00, 0x02, 0x00, 0x00, 0x08, 0x00];
+        let res = parse_vrt_packet(bytes);
+        assert!(matches!(res, Err(nom::Err::Error(_))));
+    }
+
+    #[test]
+    fn test_packet_stream() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(VRT_STATIC_IFDATA_WITH_TRAILER);
+        buf.extend_from_slice(VRT_FLEXRADIO_BROADCAST_MSG);
+        buf.extend_from_slice(&VRT_STATIC_IFDATA_WITH_TRAILER[0..6]);
+
+        let mut stream = PacketStream::new(&buf);
+        let first = stream
+            .next()
+            .unwrap()
+            .expect("failed to parse first packet");
+        assert_eq!(first.stream_id, Some(1));
+        let second = stream
+            .next()
+            .unwrap()
+            .expect("failed to parse second packet");
+        assert_eq!(second.stream_id, Some(0x0000_0800));
+        assert!(stream.next().is_none());
+        assert_eq!(stream.remainder(), &VRT_STATIC_IFDATA_WITH_TRAILER[0..6]);
+    }
+
+    #[test]
+    fn test_push_parser() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(VRT_STATIC_IFDATA_WITH_TRAILER);
+        buf.extend_from_slice(VRT_FLEXRADIO_BROADCAST_MSG);
+        buf.extend_from_slice(VRT_STATIC_IFDATA_WITH_TRAILER);
+
+        for size in 1..buf.len() {
+            let mut parser = PushParser::<1024>::new();
+            let mut stream_ids = Vec::new();
+            for chunk in buf.chunks(size) {
+                parser
+                    .feed(chunk, |packet| stream_ids.push(packet.stream_id))
+                    .unwrap();
+            }
+            assert_eq!(stream_ids, [Some(1), Some(0x0000_0800), Some(1)]);
+            assert_eq!(parser.buffered(), 0);
+        }
+
+        let mut parser = PushParser::<16>::new();
+        parser.feed(&buf[..20], |_| {}).unwrap();
+        assert_eq!(parser.buffered(), 4);
+        assert_eq!(parser.feed(&buf[20..], |_| {}), Err(Error::BufferTooSmall));
+        assert_eq!(parser.buffered(), 0);
+        assert_eq!(
+            parser.feed(&[0x14, 0x00, 0x00, 0x00], |_| {}),
+            Err(Error::MalformedField(FieldError {
+                field: PacketField::Header,
+                word: 1,
+                offset: 0,
+                reason: "packet size is less than one word",
+            }))
+        );
+        // a packet split across chunks whose size leaves no room for its Stream Identifier
+        parser.feed(&[0x10, 0x00], |_| {}).unwrap();
+        assert!(matches!(
+            parser.feed(&[0x00, 0x01], |_| {}),
+            Err(Error::MalformedField(FieldError {
+                field: PacketField::StreamId,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_packet_stream_malformed() {
+        // the second packet declares 3 words, ending within its Class Identifier
+        let mut bytes = VRT_STATIC_IFDATA_WITH_TRAILER.to_vec();
+        bytes.extend_from_slice(&[0x38, 0x5f, 0x00, 0x03, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00]);
+        bytes.extend_from_slice(&[0x1c, 0x2d]);
+        let mut stream = PacketStream::new(&bytes);
+        assert!(stream.next().unwrap().is_ok());
+        let err = stream.next().unwrap().unwrap_err();
+        assert_eq!(
+            err,
+            Error::MalformedField(FieldError {
+                field: PacketField::ClassId,
+                word: 2,
+                offset: 28,
+                reason: "beyond the declared packet size",
+            })
+        );
+        assert_eq!(
+            err.to_string(),
+            "malformed VRT packet: Class Identifier word 2 at offset 28: beyond the declared packet size"
+        );
+        assert_eq!(stream.next(), None);
+    }
+
+    #[test]
+    fn test_packet_stream_recovery() {
+        // a malformed packet between two valid packets, followed by a partial packet; the
+        // malformed packet's second word parses as a packet, but with reserved bits set
+        let mut bytes = VRT_STATIC_IFDATA_WITH_TRAILER.to_vec();
+        bytes.extend_from_slice(&[0x38, 0x5f, 0x00, 0x03, 0x01, 0x00, 0x00, 0x01]);
+        bytes.extend_from_slice(VRT_STATIC_IFDATA_WITH_TRAILER);
+        bytes.extend_from_slice(&VRT_STATIC_IFDATA_WITH_TRAILER[..8]);
+        let mut stream = PacketStream::new(&bytes).with_recovery();
+        assert!(stream.next().unwrap().is_ok());
+        assert!(stream.next().unwrap().is_err());
+        assert_eq!(stream.offset(), 16);
+        assert_eq!(stream.next().unwrap().unwrap().stream_id, Some(1));
+        assert_eq!(stream.skipped(), 8);
+        assert_eq!(stream.next(), None);
+        assert_eq!(stream.remainder(), &VRT_STATIC_IFDATA_WITH_TRAILER[..8]);
+        assert_eq!(stream.offset(), 40);
+
+        let mut stream = PacketStream::new(&[0x14, 0x00, 0x00, 0x00]).with_recovery();
+        assert!(stream.next().unwrap().is_err());
+        assert_eq!(stream.next(), None);
+        assert_eq!(stream.skipped(), 4);
+        assert!(stream.remainder().is_empty());
+    }
+
+    #[test]
+    fn test_resync() {
+        // garbage, a plausible header which is not followed by a packet, then two packets
+        let mut bytes = vec![0xee; 6];
+        bytes.extend_from_slice(&[0x10, 0x00, 0x00, 0x02, 0xee, 0xee, 0xee, 0xee]);
+        bytes.extend_from_slice(&[0xee; 4]);
+        bytes.extend_from_slice(VRT_STATIC_IFDATA_WITH_TRAILER);
+        bytes.extend_from_slice(VRT_STATIC_IFDATA_WITH_TRAILER);
+        assert_eq!(resync(&bytes, 0), Some(6));
+        assert_eq!(resync(&bytes, 1), Some(6));
+        assert_eq!(resync(&bytes, 2), Some(18));
+        assert_eq!(resync(&bytes, 3), Some(18));
+        assert_eq!(resync(&bytes[..30], 2), None);
+        assert_eq!(resync(&bytes[..18], 0), Some(6));
+        assert_eq!(resync(&[0xee; 16], 0), None);
+        assert_eq!(resync(&[], 0), None);
+    }
+
+    #[test]
+    fn test_encode_vrt_header() {
+        let (_, header) = parse_vrt_header(VRT_FLEXRADIO_BROADCAST_MSG).unwrap();
+        assert_eq!(
+            encode_vrt_header(&header),
+            VRT_FLEXRADIO_BROADCAST_MSG[0..4]
+        );
+    }
+
+    #[test]
+    fn test_encode_vrt_header_const() {
+        const PROLOGUE: [u8; 4] = encode_vrt_header(&Header {
+            packet_type: VitaPacketType(VitaPacketType::IFDATAWITHSTREAM),
+            c: false,
+            t: true,
+            indicators: 0,
+            tsi: Tsi::TSI_UTC,
+            tsf: Tsf::TSF_REAL_TIME,
+            packet_count: 0,
+            packet_size: 0,
+        });
+        assert_eq!(PROLOGUE, [0x14, 0x60, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn test_encode_vrt_trailer() {
+        let (_, trailer) = parse_vrt_trailer(VRT_STATIC_TRAILER_TEST_PATTERN).unwrap();
+        assert_eq!(
+            encode_vrt_trailer(&trailer),
+            VRT_STATIC_TRAILER_TEST_PATTERN
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_encode_vrt_packet_roundtrip() {
+        for bytes in [VRT_FLEXRADIO_BROADCAST_MSG, VRT_STATIC_IFDATA_WITH_TRAILER] {
+            let (_, packet) = parse_vrt_packet(bytes).unwrap();
+            assert_eq!(packet.encoded_len(), bytes.len());
+            assert_eq!(packet.to_vec().unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_reserialize_exact_roundtrip() {
+        // every combination of the first two header bytes, and every trailer bit on its own
+        let trailers = (0..32).map(|bit| (0x1400, 1u32 << bit));
+        let headers = (0..=0xffff_u16).map(|word| (word, 0x8000_0000));
+        for (word, trailer) in headers.chain(trailers) {
+            let [b0, b1] = word.to_be_bytes();
+            let packet_type = VitaPacketType(b0 >> 4);
+            let mut bytes = vec![b0, b1, 0, 0];
+            if packet_type.has_stream_id() {
+                bytes.extend_from_slice(&0x0102_0304_u32.to_be_bytes());
+            }
+            if b0 & 0x08 != 0 {
+                bytes.extend_from_slice(&0x0506_0708_090a_0b0c_u64.to_be_bytes());
+            }
+            if b1 >> 6 != 0 {
+                bytes.extend_from_slice(&0x0d0e_0f10_u32.to_be_bytes());
+            }
+            if b1 >> 4 & 0x03 != 0 {
+                bytes.extend_from_slice(&0x1112_1314_1516_1718_u64.to_be_bytes());
+            }
+            bytes.extend_from_slice(&[0xca, 0xfe, 0xf0, 0x0d]);
+            if b0 & 0x04 != 0 && packet_type.is_data() {
+                bytes.extend_from_slice(&trailer.to_be_bytes());
+            }
+            bytes[3] = (bytes.len() / 4) as u8;
+
+            let (rest, packet) = parse_vrt_packet(&bytes).unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(packet.reserialize_exact().unwrap(), bytes);
+            assert_eq!(packet.to_vec().unwrap(), bytes);
+            assert_eq!(encode_vrt_header(&packet.header), bytes[..4]);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_encode_vrt_packet_errors() {
+        let (_, mut packet) = parse_vrt_packet(VRT_STATIC_IFDATA_WITH_TRAILER).unwrap();
+        let mut buf = [0u8; 15];
+        assert_eq!(
+            encode_vrt_packet(&packet, &mut buf),
+            Err(Error::BufferTooSmall)
+        );
+        packet.trailer = None;
+        assert_eq!(packet.to_vec(), Err(Error::Malformed));
+    }
+
+    #[test]
+    fn test_encode_vrt_packet_uninit() {
+        use std::mem::MaybeUninit;
+
+        let (_, packet) = parse_vrt_packet(VRT_STATIC_IFDATA_WITH_TRAILER).unwrap();
+        let mut buf = [MaybeUninit::<u8>::uninit(); 20];
+        let len = encode_vrt_packet_uninit(&packet, &mut buf).unwrap();
+        assert_eq!(len, VRT_STATIC_IFDATA_WITH_TRAILER.len());
+        let bytes: Vec<u8> = buf[..len]
+            .iter()
+            .map(|b| unsafe { b.assume_init() })
+            .collect();
+        assert_eq!(bytes, VRT_STATIC_IFDATA_WITH_TRAILER);
+        assert_eq!(
+            encode_vrt_packet_uninit(&packet, &mut buf[..15]),
+            Err(Error::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn test_encode_vrt_frame() {
+        let (_, mut packet) = parse_vrt_packet(VRT_STATIC_IFDATA_WITH_TRAILER).unwrap();
+        let mut frame = [0xffu8; 64];
+        packet.data_payload = &[];
+        let layout = encode_vrt_frame(&packet, 3, &mut frame, 42).unwrap();
+        assert_eq!(layout.payload, 50..53);
+        assert_eq!(layout.end, 58);
+        assert_eq!(
+            &frame[42..50],
+            &[0x14, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01]
+        );
+        assert_eq!(&frame[53..58], &[0x00, 0xAA, 0xAA, 0xAA, 0xAA]);
+        assert_eq!(frame[41], 0xff);
+        assert_eq!(frame[58], 0xff);
+
+        frame[layout.payload].copy_from_slice(&[0xde, 0xad, 0xbe]);
+        packet.data_payload = &[0xde, 0xad, 0xbe, 0x00];
+        assert_eq!(parse_vrt_packet(&frame[42..58]).unwrap().1, packet);
+        assert_eq!(
+            encode_vrt_frame(&packet, 3, &mut frame, 50),
+            Err(Error::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_encode_vectored() {
+        use std::io::Write;
+
+        let (_, mut packet) = parse_vrt_packet(VRT_STATIC_IFDATA_WITH_TRAILER).unwrap();
+        for payload in [&[][..], &[0x01, 0x02], &[0xde, 0xad, 0xbe, 0xef]] {
+            packet.data_payload = payload;
+            let vectored = packet.encode_vectored().unwrap();
+            let slices = vectored.io_slices();
+            assert_eq!(slices[1].as_ptr(), payload.as_ptr());
+            let mut out = Vec::new();
+            assert_eq!(out.write_vectored(&slices).unwrap(), vectored.len());
+            assert_eq!(out, packet.to_vec().unwrap());
+        }
+        packet.trailer = None;
+        assert!(packet.encode_vectored().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_encode_vrt_packet_pads_payload() {
+        let (_, mut packet) = parse_vrt_packet(VRT_STATIC_IFDATA_WITH_TRAILER).unwrap();
+        packet.data_payload = &[0x01, 0x02];
+        let bytes = packet.to_vec().unwrap();
+        assert_eq!(
+            bytes,
+            vec![
+                0x14, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01, 0x01, 0x02, 0x00, 0x00, 0xAA, 0xAA,
+                0xAA, 0xAA
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_display_vrt_packet() {
+        let (_, packet) = parse_vrt_packet(VRT_STATIC_IFDATA_WITH_TRAILER).unwrap();
+        assert_eq!(
+            packet.to_string(),
+            "IF Data with Stream ID, stream 0x00000001, count 0, 4 words, 4 byte payload, trailer"
+        );
+        assert_eq!(VitaPacketType(0xf).to_string(), "Packet Type 15");
+        assert_eq!(Tsi::TSI_GPS.to_string(), "GPS");
+        assert_eq!(Tsf::TSF_SAMPLE_COUNT.to_string(), "Sample Count");
+    }
+
+    #[test]
+    fn test_packet_hash_keys() {
+        use std::collections::{HashMap, HashSet};
+
+        let (_, packet) = parse_vrt_packet(VRT_STATIC_IFDATA_WITH_TRAILER).unwrap();
+        let copy = packet;
+        let mut packets = HashSet::new();
+        assert!(packets.insert(packet));
+        assert!(!packets.insert(copy));
+
+        let mut by_header = HashMap::new();
+        by_header.insert(packet.header, packet.trailer);
+        assert_eq!(by_header.get(&copy.header), Some(&packet.trailer));
+        assert_eq!(
+            HashSet::from([packet.timestamp(), copy.timestamp()]).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_timestamp_ord() {
+        let at = |tsi, integer, tsf, fractional| Timestamp {
+            tsi,
+            integer,
+            tsf,
+            fractional,
+        };
+        let utc = |integer, fractional| at(Tsi::TSI_UTC, integer, Tsf::TSF_REAL_TIME, fractional);
+        assert!(utc(1, 5) < utc(2, 0));
+        assert!(utc(2, 0) < utc(2, 1));
+        assert_eq!(utc(2, 1).cmp(&utc(2, 1)), std::cmp::Ordering::Equal);
+
+        // timestamps of different types are grouped by type, seconds before fractions
+        let gps = at(Tsi::TSI_GPS, 0, Tsf::TSF_REAL_TIME, 0);
+        assert!(utc(u32::MAX, 0) < gps);
+        let sample_count = at(Tsi::TSI_UTC, 2, Tsf::TSF_SAMPLE_COUNT, 1000);
+        assert!(utc(1, 0) < sample_count);
+        assert!(sample_count < utc(2, 0));
+        assert!(at(Tsi::TSI_NONE, 0, Tsf::TSF_FREE_RUNNING, 7) < utc(0, 0));
+    }
+
+    #[test]
+    fn test_timestamp_seconds_since() {
+        let at = |tsi, integer, tsf, fractional| Timestamp {
+            tsi,
+            integer,
+            tsf,
+            fractional,
+        };
+        let utc = |integer, fractional| at(Tsi::TSI_UTC, integer, Tsf::TSF_REAL_TIME, fractional);
+        assert_eq!(
+            utc(2, 0).seconds_since(&utc(1, 500_000_000_000), None),
+            Some(0.5)
+        );
+        assert_eq!(utc(1, 0).seconds_since(&utc(2, 0), None), Some(-1.0));
+
+        let count =
+            |integer, fractional| at(Tsi::TSI_GPS, integer, Tsf::TSF_SAMPLE_COUNT, fractional);
+        assert_eq!(
+            count(3, 100).seconds_since(&count(3, 0), Some(1e3)),
+            Some(0.1)
+        );
+        assert_eq!(
+            count(4, 0).seconds_since(&count(3, 900), Some(1e3)),
+            Some(0.1)
+        );
+        assert_eq!(count(3, 100).seconds_since(&count(3, 0), None), None);
+
+        let seconds = at(Tsi::TSI_UTC, 7, Tsf::TSF_NONE, 0);
+        assert_eq!(
+            seconds.seconds_since(&at(Tsi::TSI_UTC, 5, Tsf::TSF_NONE, 0), None),
+            Some(2.0)
+        );
+        let free_running = at(Tsi::TSI_NONE, 0, Tsf::TSF_FREE_RUNNING, 10);
+        assert_eq!(free_running.seconds_since(&free_running, Some(1e3)), None);
+        assert_eq!(utc(1, 0).seconds_since(&count(1, 0), Some(1e3)), None);
+    }
 }
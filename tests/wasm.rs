@@ -0,0 +1,38 @@
+#![cfg(feature = "wasm")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::wasm::*;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    #[test]
+    fn test_wasm_parse_packet() {
+        let packet = parse_packet(VRT_STATIC_STREAM_1).ok().unwrap();
+        assert_eq!(packet.packet_type(), 1);
+        assert_eq!(packet.packet_size(), 6);
+        assert_eq!(packet.stream_id(), Some(1));
+        assert_eq!(packet.class_id(), None);
+        assert_eq!(packet.tsi(), Some(10));
+        assert_eq!(packet.tsf(), Some(5));
+        assert_eq!(packet.payload(), [0xca, 0xfe, 0xf0, 0x0d]);
+        assert!(!packet.has_trailer());
+        assert_eq!(
+            packet.describe(),
+            "IF Data with Stream ID, stream 0x00000001, count 0, 6 words, TSI UTC 10, \
+             TSF Real Time 5, 4 byte payload"
+        );
+    }
+
+    #[test]
+    fn test_wasm_parse_packets() {
+        let mut buf = VRT_STATIC_STREAM_1.repeat(2);
+        buf.extend_from_slice(&VRT_STATIC_STREAM_1[..8]);
+        let packets = parse_packets(&buf).ok().unwrap();
+        assert_eq!(packets.len(), 2);
+    }
+}
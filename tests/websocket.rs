@@ -0,0 +1,115 @@
+#![cfg(feature = "websocket")]
+extern crate vrt;
+
+#[cfg(test)]
+mod tests {
+    use crate::vrt::*;
+    use std::thread;
+    use std::time::Duration;
+    use tungstenite::Message;
+
+    static VRT_STATIC_STREAM_1: &[u8] = &[
+        0x10, 0x60, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x05, 0xca, 0xfe, 0xf0, 0x0d,
+    ];
+
+    #[test]
+    fn test_websocket_bridge() {
+        let mut bridge = WebSocketBridge::bind("127.0.0.1:0")
+            .unwrap()
+            .with_metadata(true);
+        let url = format!("ws://{}", bridge.local_addr().unwrap());
+        let client = thread::spawn(move || {
+            let (mut websocket, _) = tungstenite::connect(url).unwrap();
+            let mut messages = Vec::new();
+            for _ in 0..2 {
+                messages.push(websocket.read().unwrap());
+            }
+            messages
+        });
+
+        while bridge.accept().unwrap() == 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(bridge.clients().count(), 1);
+        let packet = parse_vrt_packet(VRT_STATIC_STREAM_1).unwrap().1;
+        bridge.send(&packet).unwrap();
+
+        let messages = client.join().unwrap();
+        let metadata: serde_json::Value = match &messages[0] {
+            Message::Text(text) => serde_json::from_str(text).unwrap(),
+            message => panic!("unexpected message {:?}", message),
+        };
+        assert_eq!(metadata["stream_id"], 1);
+        assert_eq!(metadata["packet_type"], 1);
+        assert_eq!(metadata["timestamp"]["integer"], 10);
+        assert_eq!(metadata["payload_len"], 4);
+        assert_eq!(messages[1], Message::Binary(VRT_STATIC_STREAM_1.to_vec()));
+
+        // the client has gone away, which is noticed once a send fails
+        for _ in 0..100 {
+            bridge.send(&packet).unwrap();
+            if bridge.clients().count() == 0 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(bridge.clients().count(), 0);
+    }
+
+    #[test]
+    fn test_websocket_bridge_slow_client() {
+        let mut bridge = WebSocketBridge::bind("127.0.0.1:0")
+            .unwrap()
+            .with_write_timeout(Duration::from_millis(200));
+        let url = format!("ws://{}", bridge.local_addr().unwrap());
+        let stalled = thread::spawn({
+            let url = url.clone();
+            move || tungstenite::connect(url).unwrap().0
+        });
+        let reader = thread::spawn(move || {
+            let (mut websocket, _) = tungstenite::connect(url).unwrap();
+            let mut received = 0;
+            while let Ok(Message::Binary(_)) = websocket.read() {
+                received += 1;
+            }
+            received
+        });
+        while bridge.clients().count() < 2 {
+            bridge.accept().unwrap();
+            thread::sleep(Duration::from_millis(1));
+        }
+        let stalled = stalled.join().unwrap();
+
+        // the stalled client never reads, so its socket buffers fill up and it is dropped
+        let payload = vec![0; 60_000];
+        let packet = VrtPacket {
+            header: Header {
+                packet_type: VitaPacketType(VitaPacketType::IFDATAWITHSTREAM),
+                c: false,
+                t: false,
+                indicators: 0,
+                tsi: Tsi::TSI_NONE,
+                tsf: Tsf::TSF_NONE,
+                packet_count: 0,
+                packet_size: 15_002,
+            },
+            stream_id: Some(1),
+            class_id: None,
+            tsi: None,
+            tsf: None,
+            data_payload: &payload,
+            trailer: None,
+        };
+        let mut sent = 0;
+        while bridge.clients().count() == 2 {
+            bridge.send(&packet).unwrap();
+            sent += 1;
+            assert!(sent < 10_000, "stalled client was not dropped");
+        }
+        assert_eq!(bridge.clients().count(), 1);
+        drop(stalled);
+        drop(bridge);
+        assert_eq!(reader.join().unwrap(), sent);
+    }
+}